@@ -15,6 +15,7 @@ fn make_lru<K: std::hash::Hash + Eq + Clone, V: Clone>(cap: usize) -> LruCache<K
     let config = LruCacheConfig {
         capacity: NonZeroUsize::new(cap).unwrap(),
         max_size: u64::MAX,
+        weight_limits: None,
     };
     LruCache::init(config, None)
 }
@@ -23,6 +24,7 @@ fn make_lfu<K: std::hash::Hash + Eq + Clone, V: Clone>(cap: usize) -> LfuCache<K
     let config = LfuCacheConfig {
         capacity: NonZeroUsize::new(cap).unwrap(),
         max_size: u64::MAX,
+        weight_limits: None,
     };
     LfuCache::init(config, None)
 }
@@ -32,6 +34,7 @@ fn make_lfuda<K: std::hash::Hash + Eq + Clone, V: Clone>(cap: usize) -> LfudaCac
         capacity: NonZeroUsize::new(cap).unwrap(),
         initial_age: 0,
         max_size: u64::MAX,
+        weight_limits: None,
     };
     LfudaCache::init(config, None)
 }
@@ -44,6 +47,7 @@ fn make_slru<K: std::hash::Hash + Eq + Clone, V: Clone>(
         capacity: NonZeroUsize::new(cap).unwrap(),
         protected_capacity: NonZeroUsize::new(protected_cap).unwrap(),
         max_size: u64::MAX,
+        weight_limits: None,
     };
     SlruCache::init(config, None)
 }
@@ -53,6 +57,7 @@ fn make_gdsf<K: std::hash::Hash + Eq + Clone, V: Clone>(cap: usize) -> GdsfCache
         capacity: NonZeroUsize::new(cap).unwrap(),
         initial_age: 0.0,
         max_size: u64::MAX,
+        weight_limits: None,
     };
     GdsfCache::init(config, None)
 }
@@ -25,8 +25,10 @@ fn lru_config(capacity: usize) -> ConcurrentLruCacheConfig {
         base: LruCacheConfig {
             capacity: NonZeroUsize::new(capacity).unwrap(),
             max_size: u64::MAX,
+            weight_limits: None,
         },
         segments: 16,
+        shared_budget: false,
     }
 }
 
@@ -35,8 +37,10 @@ fn lru_config_with_segments(capacity: usize, segments: usize) -> ConcurrentLruCa
         base: LruCacheConfig {
             capacity: NonZeroUsize::new(capacity).unwrap(),
             max_size: u64::MAX,
+            weight_limits: None,
         },
         segments,
+        shared_budget: false,
     }
 }
 
@@ -46,8 +50,10 @@ fn slru_config(capacity: usize, protected: usize) -> ConcurrentSlruCacheConfig {
             capacity: NonZeroUsize::new(capacity).unwrap(),
             protected_capacity: NonZeroUsize::new(protected).unwrap(),
             max_size: u64::MAX,
+            weight_limits: None,
         },
         segments: 16,
+        shared_budget: false,
     }
 }
 
@@ -56,8 +62,10 @@ fn lfu_config(capacity: usize) -> ConcurrentLfuCacheConfig {
         base: LfuCacheConfig {
             capacity: NonZeroUsize::new(capacity).unwrap(),
             max_size: u64::MAX,
+            weight_limits: None,
         },
         segments: 16,
+        shared_budget: false,
     }
 }
 
@@ -67,8 +75,10 @@ fn lfuda_config(capacity: usize) -> ConcurrentLfudaCacheConfig {
             capacity: NonZeroUsize::new(capacity).unwrap(),
             initial_age: 0,
             max_size: u64::MAX,
+            weight_limits: None,
         },
         segments: 16,
+        shared_budget: false,
     }
 }
 
@@ -78,8 +88,10 @@ fn gdsf_config(capacity: usize) -> ConcurrentGdsfCacheConfig {
             capacity: NonZeroUsize::new(capacity).unwrap(),
             initial_age: 0.0,
             max_size: u64::MAX,
+            weight_limits: None,
         },
         segments: 16,
+        shared_budget: false,
     }
 }
 
@@ -309,6 +321,79 @@ fn segment_count_comparison(c: &mut Criterion) {
     group.finish();
 }
 
+/// Benchmark hot-key reads from 1 to 64 threads
+///
+/// Every thread reads from a small set of hot keys, so most reads land on a
+/// handful of segments. This is the workload the shared-lock read path and
+/// read buffers are designed for.
+fn thread_scaling_hot_keys(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Thread Scaling Hot Keys (LRU)");
+
+    let cache: Arc<ConcurrentLruCache<usize, usize>> =
+        Arc::new(ConcurrentLruCache::init(lru_config(CACHE_SIZE), None));
+    for i in 0..CACHE_SIZE {
+        cache.put(i, i, 1);
+    }
+
+    for threads in [1, 2, 4, 8, 16, 32, 64] {
+        group.throughput(Throughput::Elements((threads * OPS_PER_THREAD) as u64));
+        group.bench_with_input(
+            BenchmarkId::new("threads", threads),
+            &threads,
+            |b, &num_threads| {
+                b.iter(|| {
+                    let cache = Arc::clone(&cache);
+                    run_hot_key_reads(cache, num_threads, OPS_PER_THREAD);
+                });
+            },
+        );
+    }
+
+    group.finish();
+}
+
+/// Benchmark LRU operations on long string keys
+///
+/// Hashing dominates lookups with long keys, so this shows what it costs to
+/// hash a key more than once per operation.
+fn long_string_keys(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Long String Keys (LRU)");
+    group.throughput(Throughput::Elements(OPS_PER_THREAD as u64));
+
+    let keys: Vec<String> = (0..OPS_PER_THREAD)
+        .map(|i| format!("tenant-0042/bucket-assets/objects/{:0>64}", i))
+        .collect();
+    let cache: ConcurrentLruCache<String, usize> =
+        ConcurrentLruCache::init(lru_config(CACHE_SIZE), None);
+    for (i, key) in keys.iter().enumerate() {
+        cache.put(key.clone(), i, 1);
+    }
+
+    group.bench_function("get", |b| {
+        b.iter(|| {
+            for key in &keys {
+                black_box(cache.get(key.as_str()));
+            }
+        });
+    });
+    group.bench_function("peek", |b| {
+        b.iter(|| {
+            for key in &keys {
+                black_box(cache.peek(key.as_str()));
+            }
+        });
+    });
+    group.bench_function("put", |b| {
+        b.iter(|| {
+            for (i, key) in keys.iter().enumerate() {
+                black_box(cache.put(key.clone(), i, 1));
+            }
+        });
+    });
+
+    group.finish();
+}
+
 // Helper trait for generic cache operations
 trait ConcurrentCache<K, V>: Send + Sync {
     fn cache_get(&self, key: &K) -> Option<V>;
@@ -317,8 +402,8 @@ trait ConcurrentCache<K, V>: Send + Sync {
 
 impl<K, V> ConcurrentCache<K, V> for ConcurrentLruCache<K, V>
 where
-    K: std::hash::Hash + Eq + Clone + Send,
-    V: Clone + Send,
+    K: std::hash::Hash + Eq + Clone + Send + Sync,
+    V: Clone + Send + Sync,
 {
     fn cache_get(&self, key: &K) -> Option<V> {
         self.get(key)
@@ -330,8 +415,8 @@ where
 
 impl<K, V> ConcurrentCache<K, V> for ConcurrentSlruCache<K, V>
 where
-    K: std::hash::Hash + Eq + Clone + Send,
-    V: Clone + Send,
+    K: std::hash::Hash + Eq + Clone + Send + Sync,
+    V: Clone + Send + Sync,
 {
     fn cache_get(&self, key: &K) -> Option<V> {
         self.get(key)
@@ -343,8 +428,8 @@ where
 
 impl<K, V> ConcurrentCache<K, V> for ConcurrentLfuCache<K, V>
 where
-    K: std::hash::Hash + Eq + Clone + Send,
-    V: Clone + Send,
+    K: std::hash::Hash + Eq + Clone + Send + Sync,
+    V: Clone + Send + Sync,
 {
     fn cache_get(&self, key: &K) -> Option<V> {
         self.get(key)
@@ -356,8 +441,8 @@ where
 
 impl<K, V> ConcurrentCache<K, V> for ConcurrentLfudaCache<K, V>
 where
-    K: std::hash::Hash + Eq + Clone + Send,
-    V: Clone + Send,
+    K: std::hash::Hash + Eq + Clone + Send + Sync,
+    V: Clone + Send + Sync,
 {
     fn cache_get(&self, key: &K) -> Option<V> {
         self.get(key)
@@ -433,6 +518,25 @@ where
     }
 }
 
+// Hot-key read runner: every thread cycles over the same 8 keys
+fn run_hot_key_reads<C>(cache: Arc<C>, num_threads: usize, ops_per_thread: usize)
+where
+    C: ConcurrentCache<usize, usize> + 'static,
+{
+    let mut handles = Vec::with_capacity(num_threads);
+    for _ in 0..num_threads {
+        let cache = Arc::clone(&cache);
+        handles.push(thread::spawn(move || {
+            for i in 0..ops_per_thread {
+                black_box(cache.cache_get(&(i % 8)));
+            }
+        }));
+    }
+    for handle in handles {
+        handle.join().unwrap();
+    }
+}
+
 // GDSF-specific runners (different API with size parameter)
 fn run_concurrent_reads_gdsf(
     cache: Arc<ConcurrentGdsfCache<usize, usize>>,
@@ -505,6 +609,8 @@ criterion_group!(
     concurrent_reads,
     concurrent_writes,
     concurrent_mixed,
-    segment_count_comparison
+    segment_count_comparison,
+    thread_scaling_hot_keys,
+    long_string_keys
 );
 criterion_main!(benches);
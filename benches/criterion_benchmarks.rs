@@ -1,6 +1,7 @@
 use cache_rs::config::{
     GdsfCacheConfig, LfuCacheConfig, LfudaCacheConfig, LruCacheConfig, SlruCacheConfig,
 };
+use cache_rs::metrics::NoMetrics;
 use cache_rs::{GdsfCache, LfuCache, LfudaCache, LruCache, SlruCache};
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
 use std::num::NonZeroUsize;
@@ -10,6 +11,7 @@ fn make_lru<K: std::hash::Hash + Eq + Clone, V: Clone>(cap: usize) -> LruCache<K
     let config = LruCacheConfig {
         capacity: NonZeroUsize::new(cap).unwrap(),
         max_size: u64::MAX,
+        weight_limits: None,
     };
     LruCache::init(config, None)
 }
@@ -18,6 +20,7 @@ fn make_lfu<K: std::hash::Hash + Eq + Clone, V: Clone>(cap: usize) -> LfuCache<K
     let config = LfuCacheConfig {
         capacity: NonZeroUsize::new(cap).unwrap(),
         max_size: u64::MAX,
+        weight_limits: None,
     };
     LfuCache::init(config, None)
 }
@@ -27,6 +30,7 @@ fn make_lfuda<K: std::hash::Hash + Eq + Clone, V: Clone>(cap: usize) -> LfudaCac
         capacity: NonZeroUsize::new(cap).unwrap(),
         initial_age: 0,
         max_size: u64::MAX,
+        weight_limits: None,
     };
     LfudaCache::init(config, None)
 }
@@ -39,6 +43,7 @@ fn make_slru<K: std::hash::Hash + Eq + Clone, V: Clone>(
         capacity: NonZeroUsize::new(cap).unwrap(),
         protected_capacity: NonZeroUsize::new(protected_cap).unwrap(),
         max_size: u64::MAX,
+        weight_limits: None,
     };
     SlruCache::init(config, None)
 }
@@ -48,6 +53,7 @@ fn make_gdsf<K: std::hash::Hash + Eq + Clone, V: Clone>(cap: usize) -> GdsfCache
         capacity: NonZeroUsize::new(cap).unwrap(),
         initial_age: 0.0,
         max_size: u64::MAX,
+        weight_limits: None,
     };
     GdsfCache::init(config, None)
 }
@@ -155,5 +161,98 @@ pub fn criterion_benchmark(c: &mut Criterion) {
     group.finish();
 }
 
-criterion_group!(benches, criterion_benchmark);
+/// Compares the default metrics against `NoMetrics` on the hot path.
+pub fn metrics_overhead_benchmark(c: &mut Criterion) {
+    const CACHE_SIZE: usize = 1000;
+    let mut group = c.benchmark_group("Metrics Overhead");
+
+    let lru_config = LruCacheConfig {
+        capacity: NonZeroUsize::new(CACHE_SIZE).unwrap(),
+        max_size: u64::MAX,
+        weight_limits: None,
+    };
+    let lfu_config = LfuCacheConfig {
+        capacity: NonZeroUsize::new(CACHE_SIZE).unwrap(),
+        max_size: u64::MAX,
+        weight_limits: None,
+    };
+
+    // LRU
+    {
+        let mut counted = make_lru(CACHE_SIZE);
+        let mut uncounted: LruCache<usize, usize, _, NoMetrics> =
+            LruCache::init_without_metrics(lru_config, None);
+        for i in 0..CACHE_SIZE {
+            counted.put(i, i, 1);
+            uncounted.put(i, i, 1);
+        }
+
+        group.bench_function("LRU get hit", |b| {
+            b.iter(|| {
+                for i in 0..100 {
+                    black_box(counted.get(&(i % CACHE_SIZE)));
+                }
+            });
+        });
+
+        group.bench_function("LRU get hit (NoMetrics)", |b| {
+            b.iter(|| {
+                for i in 0..100 {
+                    black_box(uncounted.get(&(i % CACHE_SIZE)));
+                }
+            });
+        });
+
+        group.bench_function("LRU put new", |b| {
+            let mut next = CACHE_SIZE;
+            b.iter(|| {
+                for _ in 0..100 {
+                    next += 1;
+                    black_box(counted.put(next, next, 1));
+                }
+            });
+        });
+
+        group.bench_function("LRU put new (NoMetrics)", |b| {
+            let mut next = CACHE_SIZE;
+            b.iter(|| {
+                for _ in 0..100 {
+                    next += 1;
+                    black_box(uncounted.put(next, next, 1));
+                }
+            });
+        });
+    }
+
+    // LFU, where every hit also walks the frequency lists for metrics
+    {
+        let mut counted = make_lfu(CACHE_SIZE);
+        let mut uncounted: LfuCache<usize, usize, _, NoMetrics> =
+            LfuCache::init_without_metrics(lfu_config, None);
+        for i in 0..CACHE_SIZE {
+            counted.put(i, i, 1);
+            uncounted.put(i, i, 1);
+        }
+
+        group.bench_function("LFU get hit", |b| {
+            b.iter(|| {
+                for i in 0..100 {
+                    black_box(counted.get(&(i % CACHE_SIZE)));
+                }
+            });
+        });
+
+        group.bench_function("LFU get hit (NoMetrics)", |b| {
+            b.iter(|| {
+                for i in 0..100 {
+                    black_box(uncounted.get(&(i % CACHE_SIZE)));
+                }
+            });
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, criterion_benchmark, metrics_overhead_benchmark);
 criterion_main!(benches);
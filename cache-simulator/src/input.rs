@@ -144,7 +144,7 @@ impl LogReader {
         }
 
         // Sort all requests by timestamp
-        all_requests.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+        all_requests.sort_by_key(|a| a.timestamp);
 
         Ok(all_requests)
     }
@@ -75,6 +75,10 @@ enum Commands {
         /// When enabled, caches evict based on --max-size instead of --capacity
         #[arg(long)]
         use_size: bool,
+
+        /// Share capacity across concurrent segments instead of splitting it evenly
+        #[arg(long)]
+        shared_budget: bool,
     },
 
     /// Generate random traffic logs
@@ -183,9 +187,18 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             threads,
             output_csv,
             use_size,
+            shared_budget,
         }) => run_simulator(
-            input_dir, capacity, max_size, algorithms, mode, segments, threads, output_csv,
+            input_dir,
+            capacity,
+            max_size,
+            algorithms,
+            mode,
+            segments,
+            threads,
+            output_csv,
             use_size,
+            shared_budget,
         ),
 
         None => {
@@ -200,6 +213,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 1, // default threads
                 None,
                 false,
+                false,
             )
         }
     }
@@ -230,6 +244,7 @@ fn run_simulator(
     threads: usize,
     output_csv: Option<PathBuf>,
     use_size: bool,
+    shared_budget: bool,
 ) -> Result<(), Box<dyn std::error::Error>> {
     // Determine input directory
     let input_dir = match input_dir {
@@ -296,6 +311,9 @@ fn run_simulator(
     if let Some(seg) = segments {
         println!("Concurrent segments: {seg}");
     }
+    if shared_budget {
+        println!("Concurrent segments share one capacity budget");
+    }
     if threads > 1 {
         println!("Worker threads: {threads}");
         println!("  Note: Multi-threaded execution is a planned feature.");
@@ -313,6 +331,7 @@ fn run_simulator(
         segment_count: segments,
         thread_count: threads,
         use_size,
+        shared_budget,
     };
 
     run_simulation(config, output_csv)
@@ -139,6 +139,8 @@ pub struct SimulationConfig {
     pub thread_count: usize,
     /// Use size-based eviction (uses max_size instead of capacity)
     pub use_size: bool,
+    /// Share capacity across concurrent segments instead of splitting it evenly
+    pub shared_budget: bool,
 }
 
 /// Results of a simulation run
@@ -388,6 +388,7 @@ impl CacheFactory {
     /// Create a new cache instance based on algorithm, mode, capacity, max_size, and use_size flag
     /// - capacity: maximum number of entries (always used)
     /// - max_size: maximum size in bytes (only used when use_size is true)
+    /// - shared_budget: share capacity across concurrent segments
     fn create_cache(
         algorithm: CacheAlgorithm,
         mode: CacheMode,
@@ -395,6 +396,7 @@ impl CacheFactory {
         max_size_bytes: u64,
         segment_count: Option<usize>,
         use_size: bool,
+        shared_budget: bool,
     ) -> CacheWrapper {
         let cap_nz = NonZeroUsize::new(capacity).unwrap_or(NonZeroUsize::new(1).unwrap());
         let max_size = if use_size { max_size_bytes } else { u64::MAX };
@@ -406,6 +408,7 @@ impl CacheFactory {
                 let config = LruCacheConfig {
                     capacity: cap_nz,
                     max_size,
+                    weight_limits: None,
                 };
                 CacheWrapper::LruSeq(LruCache::init(config, None))
             }
@@ -416,6 +419,7 @@ impl CacheFactory {
                     capacity: cap_nz,
                     protected_capacity: protected,
                     max_size,
+                    weight_limits: None,
                 };
                 CacheWrapper::SlruSeq(SlruCache::init(config, None))
             }
@@ -423,6 +427,7 @@ impl CacheFactory {
                 let config = LfuCacheConfig {
                     capacity: cap_nz,
                     max_size,
+                    weight_limits: None,
                 };
                 CacheWrapper::LfuSeq(LfuCache::init(config, None))
             }
@@ -431,6 +436,7 @@ impl CacheFactory {
                     capacity: cap_nz,
                     initial_age: 0,
                     max_size,
+                    weight_limits: None,
                 };
                 CacheWrapper::LfudaSeq(LfudaCache::init(config, None))
             }
@@ -440,6 +446,7 @@ impl CacheFactory {
                     capacity: cap_nz,
                     initial_age: 0.0,
                     max_size,
+                    weight_limits: None,
                 };
                 CacheWrapper::GdsfSeq(GdsfCache::init(config, None))
             }
@@ -448,6 +455,7 @@ impl CacheFactory {
                 let config = LruCacheConfig {
                     capacity: cap_nz,
                     max_size,
+                    weight_limits: None,
                 };
                 CacheWrapper::LruSeqSize(LruCache::init(config, None))
             }
@@ -458,6 +466,7 @@ impl CacheFactory {
                     capacity: cap_nz,
                     protected_capacity: protected,
                     max_size,
+                    weight_limits: None,
                 };
                 CacheWrapper::SlruSeqSize(SlruCache::init(config, None))
             }
@@ -465,6 +474,7 @@ impl CacheFactory {
                 let config = LfuCacheConfig {
                     capacity: cap_nz,
                     max_size,
+                    weight_limits: None,
                 };
                 CacheWrapper::LfuSeqSize(LfuCache::init(config, None))
             }
@@ -473,6 +483,7 @@ impl CacheFactory {
                     capacity: cap_nz,
                     initial_age: 0,
                     max_size,
+                    weight_limits: None,
                 };
                 CacheWrapper::LfudaSeqSize(LfudaCache::init(config, None))
             }
@@ -482,8 +493,10 @@ impl CacheFactory {
                     base: LruCacheConfig {
                         capacity: cap_nz,
                         max_size,
+                        weight_limits: None,
                     },
                     segments,
+                    shared_budget,
                 };
                 CacheWrapper::LruConc(ConcurrentLruCache::init(config, None))
             }
@@ -495,8 +508,10 @@ impl CacheFactory {
                         capacity: cap_nz,
                         protected_capacity: protected,
                         max_size,
+                        weight_limits: None,
                     },
                     segments,
+                    shared_budget,
                 };
                 CacheWrapper::SlruConc(ConcurrentSlruCache::init(config, None))
             }
@@ -505,8 +520,10 @@ impl CacheFactory {
                     base: LfuCacheConfig {
                         capacity: cap_nz,
                         max_size,
+                        weight_limits: None,
                     },
                     segments,
+                    shared_budget,
                 };
                 CacheWrapper::LfuConc(ConcurrentLfuCache::init(config, None))
             }
@@ -516,8 +533,10 @@ impl CacheFactory {
                         capacity: cap_nz,
                         initial_age: 0,
                         max_size,
+                        weight_limits: None,
                     },
                     segments,
+                    shared_budget,
                 };
                 CacheWrapper::LfudaConc(ConcurrentLfudaCache::init(config, None))
             }
@@ -528,8 +547,10 @@ impl CacheFactory {
                         capacity: cap_nz,
                         initial_age: 0.0,
                         max_size,
+                        weight_limits: None,
                     },
                     segments,
+                    shared_budget,
                 };
                 CacheWrapper::GdsfConc(ConcurrentGdsfCache::init(config, None))
             }
@@ -539,8 +560,10 @@ impl CacheFactory {
                     base: LruCacheConfig {
                         capacity: cap_nz,
                         max_size,
+                        weight_limits: None,
                     },
                     segments,
+                    shared_budget,
                 };
                 CacheWrapper::LruConcSize(ConcurrentLruCache::init(config, None))
             }
@@ -552,8 +575,10 @@ impl CacheFactory {
                         capacity: cap_nz,
                         protected_capacity: protected,
                         max_size,
+                        weight_limits: None,
                     },
                     segments,
+                    shared_budget,
                 };
                 CacheWrapper::SlruConcSize(ConcurrentSlruCache::init(config, None))
             }
@@ -562,8 +587,10 @@ impl CacheFactory {
                     base: LfuCacheConfig {
                         capacity: cap_nz,
                         max_size,
+                        weight_limits: None,
                     },
                     segments,
+                    shared_budget,
                 };
                 CacheWrapper::LfuConcSize(ConcurrentLfuCache::init(config, None))
             }
@@ -573,8 +600,10 @@ impl CacheFactory {
                         capacity: cap_nz,
                         initial_age: 0,
                         max_size,
+                        weight_limits: None,
                     },
                     segments,
+                    shared_budget,
                 };
                 CacheWrapper::LfudaConcSize(ConcurrentLfudaCache::init(config, None))
             }
@@ -761,6 +790,7 @@ impl SimulationRunner {
                     cache_max_size,
                     self.config.segment_count,
                     self.config.use_size,
+                    self.config.shared_budget,
                 );
 
                 // Track storage usage during simulation
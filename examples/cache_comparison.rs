@@ -23,6 +23,7 @@ fn main() {
     let lru_config = LruCacheConfig {
         capacity: cap,
         max_size: u64::MAX,
+        weight_limits: None,
     };
     let mut lru_cache = LruCache::init(lru_config, None);
 
@@ -30,12 +31,14 @@ fn main() {
         capacity: cap,
         protected_capacity: protected_cap,
         max_size: u64::MAX,
+        weight_limits: None,
     };
     let mut slru_cache = SlruCache::init(slru_config, None);
 
     let lfu_config = LfuCacheConfig {
         capacity: cap,
         max_size: u64::MAX,
+        weight_limits: None,
     };
     let mut lfu_cache = LfuCache::init(lfu_config, None);
 
@@ -43,6 +46,7 @@ fn main() {
         capacity: cap,
         initial_age: 0,
         max_size: u64::MAX,
+        weight_limits: None,
     };
     let mut lfuda_cache = LfudaCache::init(lfuda_config, None);
 
@@ -50,6 +54,7 @@ fn main() {
         capacity: cap,
         initial_age: 0.0,
         max_size: u64::MAX,
+        weight_limits: None,
     };
     let mut gdsf_cache = GdsfCache::init(gdsf_config, None);
 
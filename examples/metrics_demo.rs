@@ -55,6 +55,7 @@ fn test_lru_cache(capacity: NonZeroUsize) -> LruCache<&'static str, i32> {
     let config = LruCacheConfig {
         capacity,
         max_size: u64::MAX,
+        weight_limits: None,
     };
     let mut cache = LruCache::init(config, None);
 
@@ -87,6 +88,7 @@ fn test_lfu_cache(capacity: NonZeroUsize) -> LfuCache<&'static str, i32> {
     let config = LfuCacheConfig {
         capacity,
         max_size: u64::MAX,
+        weight_limits: None,
     };
     let mut cache = LfuCache::init(config, None);
 
@@ -120,6 +122,7 @@ fn test_slru_cache(capacity: NonZeroUsize) -> SlruCache<&'static str, i32> {
         capacity,
         protected_capacity,
         max_size: u64::MAX,
+        weight_limits: None,
     };
     let mut cache = SlruCache::init(config, None);
 
@@ -153,6 +156,7 @@ fn test_lfuda_cache(capacity: NonZeroUsize) -> LfudaCache<&'static str, i32> {
         capacity,
         initial_age: 0,
         max_size: u64::MAX,
+        weight_limits: None,
     };
     let mut cache = LfudaCache::init(config, None);
 
@@ -186,6 +190,7 @@ fn test_gdsf_cache(capacity: NonZeroUsize) -> GdsfCache<&'static str, i32> {
         capacity,
         initial_age: 0.0,
         max_size: u64::MAX,
+        weight_limits: None,
     };
     let mut cache = GdsfCache::init(config, None);
 
@@ -7,16 +7,16 @@
 //!
 //! Each concurrent cache uses segmented storage where:
 //! - The key space is partitioned across multiple segments using hash-based sharding
-//! - Each segment is protected by its own lock (using `parking_lot::Mutex`)
+//! - Each segment is protected by its own reader-writer lock (see [Locks](#locks))
 //! - Operations only lock the relevant segment, allowing concurrent access to different segments
 //!
 //! This design provides near-linear scalability with thread count for workloads
 //! with good key distribution.
 //!
-//! ## Why Mutex Instead of RwLock?
+//! ## Read Path: Shared Locks and Read Buffers
 //!
-//! Cache algorithms like LRU, LFU, LFUDA, GDSF, and SLRU require **mutable access
-//! even for read operations**. Every `get()` call must update internal state:
+//! Cache algorithms like LRU, LFU, LFUDA, GDSF, and SLRU update internal state
+//! on every `get()`:
 //!
 //! - **LRU**: Moves the accessed item to the front of the recency list
 //! - **LFU**: Increments the frequency counter and may move the item between frequency buckets
@@ -24,15 +24,52 @@
 //! - **GDSF**: Recalculates priority based on size, frequency, and cost
 //! - **SLRU**: May promote items from the probationary to protected segment
 //!
-//! Since `get()` is inherently a write operation, using `RwLock` would provide no benefit—
-//! every access would still require an exclusive write lock. `Mutex` is preferred because:
+//! Taking an exclusive lock for every read makes hot segments the bottleneck as
+//! thread counts grow. Instead, each segment is guarded by a reader-writer lock
+//! and paired with a striped, lossy **read buffer**:
 //!
-//! 1. **Lower overhead**: `Mutex` has less bookkeeping than `RwLock`
-//! 2. **No false promises**: Makes it clear that all operations are mutually exclusive
-//! 3. **Better performance**: `parking_lot::Mutex` is highly optimized for this use case
+//! 1. `get()` looks the entry up under the **shared** lock and clones the value
+//! 2. The access is appended to the calling thread's stripe of the read buffer
+//! 3. The next operation that takes the **exclusive** lock (`put()`, `remove()`,
+//!    `metrics()`, ...) first replays the buffered accesses, applying the
+//!    promotions in order
+//! 4. A reader whose stripe fills up tries to drain it right away, but never
+//!    waits for the exclusive lock to do so
 //!
-//! Concurrency is achieved through **segmentation** instead: different keys can be accessed
-//! in parallel as long as they hash to different segments.
+//! When a stripe is contended or full, the access is dropped rather than
+//! blocking the reader. Dropped accesses only lose recency/frequency
+//! precision: they still count as requests and hits, though not in the byte
+//! counters, and are reported as `read_buffer_drops` in the cache metrics. Single-threaded use never drops accesses, so it behaves
+//! exactly like the sequential caches.
+//!
+//! ## Capacity: Per-Segment Split or Shared Budget
+//!
+//! By default the configured `capacity` and `max_size` are split evenly, and
+//! each segment evicts on its own once its slice is full. With
+//! `shared_budget: true` in [`ConcurrentCacheConfig`](crate::config::ConcurrentCacheConfig),
+//! segments may grow up to the whole cache's limits and one budget is enforced
+//! across all of them: after a write leaves the cache over budget, the segment
+//! furthest over its fair share evicts its policy's victim. Segment usage is
+//! tracked in atomics, so no operation ever holds two segment locks.
+//!
+//! ## Diagnosing Hot Segments
+//!
+//! `segment_metrics()` returns a
+//! [`SegmentMetrics`](crate::concurrent::SegmentMetrics) per segment (entries,
+//! size, hits, evictions, read buffer drops and
+//! [`LockMetrics`](crate::concurrent::LockMetrics)), and `segment_skew()`
+//! summarizes how unevenly requests, entries, evictions and
+//! lock waits are spread, as a max/min ratio and a coefficient of variation.
+//! Evenly spread lock waits suggest raising `segments`; skewed requests point
+//! at hot keys, which more segments won't help.
+//!
+//! ## Tenants
+//!
+//! [`TenantCache`](crate::concurrent::TenantCache) shares one cache's limits
+//! among tenants, each with its own sharded cache and a
+//! [`TenantQuota`](crate::concurrent::TenantQuota) of entries and bytes, so a
+//! noisy tenant can only evict its own entries and those of tenants above
+//! their guaranteed minimum.
 //!
 //! # Available Concurrent Caches
 //!
@@ -44,11 +81,42 @@
 //! | [`ConcurrentLfudaCache`] | Thread-safe LFUDA cache |
 //! | [`ConcurrentGdsfCache`] | Thread-safe GDSF cache |
 //!
+//! All of them are aliases of
+//! [`ShardedCache`](crate::concurrent::ShardedCache) over the matching
+//! single-threaded cache.
+//!
+//! ## Custom Policies
+//!
+//! [`ShardedCache`](crate::concurrent::ShardedCache) works with any cache
+//! implementing [`Segment`](crate::concurrent::Segment), so a user-defined eviction policy gets segmentation, read buffers, the
+//! non-blocking and atomic operations, shared budgets and per-segment
+//! diagnostics without writing any locking code. Tags, weights, negative
+//! caching, refresh policies, hot keys and the other optional features are
+//! offered over policies that also implement
+//! [`SegmentFeatures`](crate::concurrent::SegmentFeatures).
+//!
+//! # Locks
+//!
+//! Segment locks are built on a [`lock_api::RawRwLock`], chosen by the last
+//! type parameter of [`ShardedCache`](crate::concurrent::ShardedCache) and of
+//! every alias. It defaults to [`DefaultRawRwLock`](crate::concurrent::DefaultRawRwLock):
+//!
+//! | Feature | Default lock | Needs `std` |
+//! |---------|--------------|-------------|
+//! | `concurrent` | `parking_lot::RawRwLock` | yes |
+//! | `concurrent-core` only | [`RawSpinRwLock`](crate::concurrent::RawSpinRwLock) | no |
+//!
+//! `concurrent-core` makes the concurrent caches available on multi-core
+//! `no_std` targets. Without `std` the read buffers have no thread-locals to
+//! pick a stripe with, so concurrent readers of one segment share a stripe
+//! and drop more accesses. `get_timeout()` needs a lock with timeouts and is
+//! only available with `parking_lot`.
+//!
 //! # Performance Characteristics
 //!
 //! - **Read/Write Latency**: O(1) average case, same as single-threaded variants
 //! - **Concurrency**: Near-linear scaling up to segment count
-//! - **Memory Overhead**: ~1 Mutex per segment (typically 16 segments by default)
+//! - **Memory Overhead**: ~1 RwLock and read buffer per segment (typically 16 segments by default)
 //!
 //! # Default Segment Count
 //!
@@ -83,6 +151,20 @@
 //! }
 //! ```
 //!
+//! # Non-Blocking Operations
+//!
+//! Latency-critical callers can use `try_get()`, `try_put()` and `try_remove()`,
+//! which never wait for a segment lock and return
+//! [`Busy`](crate::concurrent::Busy) instead. With the `std` feature, `get_timeout()` waits for at most the given duration. Every
+//! `Busy` outcome is counted as `lock_contention_misses` in the cache metrics.
+//!
+//! ```rust,ignore
+//! let value = match cache.try_get(&key) {
+//!     Ok(value) => value,
+//!     Err(Busy(())) => None, // treat a contended segment as a miss
+//! };
+//! ```
+//!
 //! # Thread Safety
 //!
 //! All concurrent cache types implement `Send` and `Sync`, making them safe to share
@@ -91,7 +173,7 @@
 //! # Zero-Copy Access
 //!
 //! For performance-critical code paths, use the `get_with()` method which provides
-//! access to the value while holding the segment lock, avoiding unnecessary cloning:
+//! access to the value while holding the segment's shared lock, avoiding unnecessary cloning:
 //!
 //! ```rust,ignore
 //! let result = cache.get_with(&key, |value| {
@@ -100,17 +182,80 @@
 //! });
 //! ```
 
+extern crate alloc;
+
+use alloc::vec::Vec;
+
+mod budget;
+mod diagnostics;
 mod gdsf;
 mod lfu;
 mod lfuda;
+mod lock_stats;
 mod lru;
+mod read_buffer;
+mod segment;
+mod sharded;
 mod slru;
+mod spin;
+mod tenant;
 
+pub use self::diagnostics::{LockMetrics, SegmentMetrics, SegmentSkew, Spread};
 pub use self::gdsf::ConcurrentGdsfCache;
 pub use self::lfu::ConcurrentLfuCache;
 pub use self::lfuda::ConcurrentLfudaCache;
 pub use self::lru::ConcurrentLruCache;
+pub use self::segment::{Segment, SegmentFeatures};
+pub use self::sharded::ShardedCache;
 pub use self::slru::ConcurrentSlruCache;
+pub use self::spin::RawSpinRwLock;
+pub use self::tenant::{TenantCache, TenantMetrics, TenantQuota};
+pub use lock_api::RawRwLock;
+
+/// The raw lock guarding segments unless another one is chosen.
+///
+/// `parking_lot`'s lock with the `concurrent` feature, [`RawSpinRwLock`]
+/// with only `concurrent-core`.
+#[cfg(feature = "concurrent")]
+pub type DefaultRawRwLock = parking_lot::RawRwLock;
+
+/// The raw lock guarding segments unless another one is chosen.
+///
+/// `parking_lot`'s lock with the `concurrent` feature, [`RawSpinRwLock`]
+/// with only `concurrent-core`.
+#[cfg(not(feature = "concurrent"))]
+pub type DefaultRawRwLock = RawSpinRwLock;
+
+/// Outcome of a non-blocking or bounded-wait operation whose segment was locked.
+///
+/// Returned by `try_get()`, `try_put()`, `try_remove()` and `get_timeout()` on
+/// the concurrent caches. The payload hands back whatever the operation would
+/// otherwise have consumed, e.g. the key and value passed to `try_put()`, so
+/// the caller can retry or fall back without cloning.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Busy<T = ()>(pub T);
+
+impl<T> Busy<T> {
+    /// Returns the value the operation handed back.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> core::fmt::Display for Busy<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("cache segment is locked")
+    }
+}
+
+/// Result of `try_put()`: the evicted entries, or the rejected key and value.
+pub type TryPutResult<K, V> = Result<Option<Vec<(K, V)>>, Busy<(K, V)>>;
+
+#[cfg(feature = "std")]
+extern crate std;
+
+#[cfg(feature = "std")]
+impl<T: core::fmt::Debug> std::error::Error for Busy<T> {}
 
 /// Returns the default number of segments based on CPU count.
 ///
@@ -0,0 +1,186 @@
+//! Shared Capacity Budget
+//!
+//! By default a concurrent cache splits its `capacity` and `max_size` evenly:
+//! each segment gets `capacity / segments` entries and `max_size / segments`
+//! bytes. With skewed keys or a few large objects, one segment evicts
+//! aggressively while the others sit half empty.
+//!
+//! With `shared_budget` enabled, every segment may grow up to the whole
+//! cache's limits and a single budget is enforced across all of them:
+//!
+//! ```text
+//! put(k) ──▶ segment[i].put ──▶ publish usage[i]
+//!                                     │ total over budget?
+//!                                     ▼
+//!                      victim = segment furthest over its fair share
+//!                                     │
+//!                                     ▼
+//!                      segment[victim].pop_victim ──▶ publish usage[victim]
+//! ```
+//!
+//! Every segment has the same fair share, so the segment furthest over it is
+//! simply the largest one, by bytes when the size budget is exceeded and by
+//! entries otherwise. The victim within that segment is chosen by its own
+//! eviction policy.
+//!
+//! # Accounting
+//!
+//! Each segment publishes its entry count and byte size to atomics after
+//! every write, so totals are computed without taking any lock. Concurrent
+//! writers may briefly overshoot the budget, or evict one entry more than
+//! strictly necessary, but never hold more than one segment lock at a time.
+
+extern crate alloc;
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+/// Last published usage of one segment.
+#[derive(Debug, Default)]
+struct SegmentUsage {
+    len: AtomicUsize,
+    size: AtomicU64,
+}
+
+/// Entry and size limits shared by all segments of a concurrent cache.
+#[derive(Debug)]
+pub(crate) struct SharedBudget {
+    capacity: AtomicUsize,
+    max_size: AtomicU64,
+    usage: Box<[SegmentUsage]>,
+}
+
+impl SharedBudget {
+    /// Creates a budget of `capacity` entries and `max_size` bytes.
+    pub(crate) fn new(capacity: usize, max_size: u64, segments: usize) -> Self {
+        let usage: Vec<_> = (0..segments).map(|_| SegmentUsage::default()).collect();
+        Self {
+            capacity: AtomicUsize::new(capacity),
+            max_size: AtomicU64::new(max_size),
+            usage: usage.into_boxed_slice(),
+        }
+    }
+
+    /// Returns the maximum number of entries across all segments.
+    pub(crate) fn capacity(&self) -> usize {
+        self.capacity.load(Ordering::Relaxed)
+    }
+
+    /// Returns the maximum total size across all segments.
+    pub(crate) fn max_size(&self) -> u64 {
+        self.max_size.load(Ordering::Relaxed)
+    }
+
+    /// Changes the limits. The segments are trimmed to the new limits by the
+    /// next writer.
+    pub(crate) fn set_limits(&self, capacity: usize, max_size: u64) {
+        self.capacity.store(capacity, Ordering::Relaxed);
+        self.max_size.store(max_size, Ordering::Relaxed);
+    }
+
+    /// Records the current usage of segment `idx`.
+    ///
+    /// The caller must hold the segment's exclusive lock so publications for
+    /// the same segment are not reordered.
+    pub(crate) fn publish(&self, idx: usize, len: usize, size: u64) {
+        let usage = &self.usage[idx];
+        usage.len.store(len, Ordering::Relaxed);
+        usage.size.store(size, Ordering::Relaxed);
+    }
+
+    /// Returns the published number of entries and total size across all
+    /// segments.
+    pub(crate) fn usage(&self) -> (usize, u64) {
+        let len: usize = self
+            .usage
+            .iter()
+            .map(|u| u.len.load(Ordering::Relaxed))
+            .sum();
+        let size: u64 = self
+            .usage
+            .iter()
+            .map(|u| u.size.load(Ordering::Relaxed))
+            .fold(0, u64::saturating_add);
+        (len, size)
+    }
+
+    /// Returns the segment to evict from, or `None` if the cache is within budget.
+    pub(crate) fn victim(&self) -> Option<usize> {
+        let (len, size) = self.usage();
+        if size > self.max_size() {
+            self.largest(true)
+        } else if len > self.capacity() {
+            self.largest(false)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the non-empty segment with the most bytes, or with the most
+    /// entries if `by_size` is false.
+    pub(crate) fn largest(&self, by_size: bool) -> Option<usize> {
+        let largest = if by_size {
+            self.largest_by(|u| u.size.load(Ordering::Relaxed))
+        } else {
+            self.largest_by(|u| u.len.load(Ordering::Relaxed) as u64)
+        };
+        largest.filter(|&idx| self.usage[idx].len.load(Ordering::Relaxed) > 0)
+    }
+
+    /// Returns the index of the segment with the largest `usage`.
+    fn largest_by<F>(&self, usage: F) -> Option<usize>
+    where
+        F: Fn(&SegmentUsage) -> u64,
+    {
+        self.usage
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, u)| usage(u))
+            .map(|(idx, _)| idx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_within_budget_has_no_victim() {
+        let budget = SharedBudget::new(10, 1000, 4);
+        budget.publish(0, 5, 500);
+        budget.publish(1, 5, 500);
+        assert_eq!(budget.victim(), None);
+    }
+
+    #[test]
+    fn test_victim_is_largest_segment_by_entries() {
+        let budget = SharedBudget::new(10, u64::MAX, 4);
+        budget.publish(0, 2, 2);
+        budget.publish(1, 7, 7);
+        budget.publish(2, 2, 2);
+        assert_eq!(budget.victim(), Some(1));
+    }
+
+    #[test]
+    fn test_lowered_limits_take_effect() {
+        let budget = SharedBudget::new(10, 1000, 2);
+        budget.publish(0, 3, 300);
+        budget.publish(1, 4, 100);
+        assert_eq!(budget.usage(), (7, 400));
+        assert_eq!(budget.victim(), None);
+
+        budget.set_limits(5, 1000);
+        assert_eq!(budget.victim(), Some(1));
+        budget.set_limits(10, 200);
+        assert_eq!(budget.victim(), Some(0));
+    }
+
+    #[test]
+    fn test_victim_is_largest_segment_by_size() {
+        let budget = SharedBudget::new(100, 1000, 4);
+        budget.publish(0, 8, 100);
+        budget.publish(2, 1, 950);
+        assert_eq!(budget.victim(), Some(2));
+    }
+}
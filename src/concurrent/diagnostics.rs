@@ -0,0 +1,179 @@
+//! Per-Segment Metrics and Skew Diagnostics
+//!
+//! [`CacheMetrics::metrics()`](crate::metrics::CacheMetrics::metrics) merges
+//! every segment into one map, which hides hot-segment problems. The types
+//! here describe each segment separately and summarize how unevenly load is
+//! spread across them.
+//!
+//! # Reading the Numbers
+//!
+//! - **Even load, many lock waits**: segments are evenly used but contended;
+//!   raise `segments`.
+//! - **Skewed requests**: a few hot keys dominate one segment; more segments
+//!   won't help, since the hot keys still share one lock.
+//! - **Skewed evictions**: with a per-segment capacity split, some segments
+//!   evict while others have room; consider `shared_budget`.
+
+extern crate alloc;
+
+use crate::metrics::CoreCacheMetrics;
+use alloc::vec::Vec;
+
+/// Lock statistics of one segment.
+///
+/// Times are only measured with the `std` feature and are zero otherwise.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LockMetrics {
+    /// Number of times the exclusive lock was taken
+    pub acquisitions: u64,
+    /// Number of shared or exclusive acquisitions that found the lock held
+    /// and had to wait
+    pub waits: u64,
+    /// Total time spent waiting for the lock, in nanoseconds
+    pub wait_nanos: u64,
+    /// Total time the exclusive lock was held, in nanoseconds
+    pub hold_nanos: u64,
+}
+
+/// Snapshot of one segment of a concurrent cache.
+#[derive(Debug, Clone, Default)]
+pub struct SegmentMetrics {
+    /// Number of entries in the segment
+    pub len: usize,
+    /// Total size of the entries in the segment
+    pub size: u64,
+    /// Requests, hits, evictions and byte counters of the segment
+    pub core: CoreCacheMetrics,
+    /// Accesses dropped by the segment's read buffer
+    pub read_buffer_drops: u64,
+    /// Lock statistics of the segment
+    pub lock: LockMetrics,
+}
+
+/// How unevenly a quantity is spread across segments.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Spread {
+    /// Largest value divided by the smallest.
+    ///
+    /// `1.0` when all segments are equal (including all zero), and infinite
+    /// when some segment is zero while another is not.
+    pub max_min_ratio: f64,
+    /// Standard deviation divided by the mean; `0.0` when the mean is zero.
+    pub coefficient_of_variation: f64,
+}
+
+impl Spread {
+    /// Computes the spread of `values`.
+    pub fn of<I>(values: I) -> Self
+    where
+        I: IntoIterator<Item = f64>,
+    {
+        let values: Vec<f64> = values.into_iter().collect();
+        if values.is_empty() {
+            return Self {
+                max_min_ratio: 1.0,
+                coefficient_of_variation: 0.0,
+            };
+        }
+
+        let max = values.iter().copied().fold(f64::MIN, f64::max);
+        let min = values.iter().copied().fold(f64::MAX, f64::min);
+        let max_min_ratio = if max == min {
+            1.0
+        } else if min == 0.0 {
+            f64::INFINITY
+        } else {
+            max / min
+        };
+
+        let count = values.len() as f64;
+        let mean = values.iter().sum::<f64>() / count;
+        let coefficient_of_variation = if mean == 0.0 {
+            0.0
+        } else {
+            let variance = values.iter().map(|v| (v - mean) * (v - mean)).sum::<f64>() / count;
+            sqrt(variance) / mean
+        };
+
+        Self {
+            max_min_ratio,
+            coefficient_of_variation,
+        }
+    }
+}
+
+/// Skew summary across the segments of a concurrent cache.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SegmentSkew {
+    /// Spread of `get()` requests (traffic load)
+    pub requests: Spread,
+    /// Spread of stored entries (occupancy)
+    pub entries: Spread,
+    /// Spread of evictions
+    pub evictions: Spread,
+    /// Spread of lock waits (contention)
+    pub lock_waits: Spread,
+}
+
+impl SegmentSkew {
+    /// Summarizes per-segment metrics as returned by `segment_metrics()`.
+    pub fn from_segments(segments: &[SegmentMetrics]) -> Self {
+        Self {
+            requests: Spread::of(segments.iter().map(|s| s.core.requests as f64)),
+            entries: Spread::of(segments.iter().map(|s| s.len as f64)),
+            evictions: Spread::of(segments.iter().map(|s| s.core.evictions as f64)),
+            lock_waits: Spread::of(segments.iter().map(|s| s.lock.waits as f64)),
+        }
+    }
+}
+
+/// Square root by Newton's method, since `core` has no `f64::sqrt`.
+fn sqrt(x: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    // Starting at or above the root, the iteration decreases monotonically
+    let mut guess = x.max(1.0);
+    loop {
+        let next = 0.5 * (guess + x / guess);
+        if next >= guess {
+            return guess;
+        }
+        guess = next;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spread_of_even_values() {
+        let spread = Spread::of([4.0, 4.0, 4.0]);
+        assert_eq!(spread.max_min_ratio, 1.0);
+        assert_eq!(spread.coefficient_of_variation, 0.0);
+
+        let spread = Spread::of([0.0, 0.0]);
+        assert_eq!(spread.max_min_ratio, 1.0);
+        assert_eq!(spread.coefficient_of_variation, 0.0);
+    }
+
+    #[test]
+    fn test_spread_of_skewed_values() {
+        let spread = Spread::of([2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0]);
+        assert_eq!(spread.max_min_ratio, 4.5);
+        // Mean 5, standard deviation 2
+        assert!((spread.coefficient_of_variation - 0.4).abs() < 1e-12);
+
+        assert_eq!(Spread::of([0.0, 3.0]).max_min_ratio, f64::INFINITY);
+    }
+
+    #[test]
+    fn test_sqrt() {
+        for x in [0.25, 1.0, 2.0, 1e6, 12345.678] {
+            let root = sqrt(x);
+            assert!((root * root - x).abs() <= x * 1e-12, "sqrt({x}) = {root}");
+        }
+        assert_eq!(sqrt(0.0), 0.0);
+    }
+}
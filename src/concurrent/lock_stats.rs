@@ -0,0 +1,185 @@
+//! Segment Lock Instrumentation
+//!
+//! Counts how often each segment's exclusive lock is taken and how often
+//! callers found the lock held and had to wait for it. With the `std`
+//! feature, the time spent waiting for a lock and holding the exclusive lock
+//! is accumulated as well.
+//!
+//! Uncontended acquisitions only pay for a `try_write()`/`try_read()` and an
+//! atomic increment; the clock is read only when timing is available.
+
+#[cfg(feature = "std")]
+extern crate std;
+
+use super::LockMetrics;
+use core::ops::{Deref, DerefMut};
+use core::sync::atomic::{AtomicU64, Ordering};
+use lock_api::{RawRwLock, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+#[cfg(feature = "std")]
+use std::time::Instant;
+
+/// Lock counters for one segment.
+#[derive(Debug, Default)]
+pub(crate) struct LockStats {
+    acquisitions: AtomicU64,
+    waits: AtomicU64,
+    wait_nanos: AtomicU64,
+    hold_nanos: AtomicU64,
+}
+
+impl LockStats {
+    /// Creates zeroed lock counters.
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Takes the exclusive lock, recording whether (and how long) the caller waited.
+    pub(crate) fn write<'a, R: RawRwLock, T>(
+        &'a self,
+        lock: &'a RwLock<R, T>,
+    ) -> TimedWriteGuard<'a, R, T> {
+        let guard = match lock.try_write() {
+            Some(guard) => guard,
+            None => {
+                self.waits.fetch_add(1, Ordering::Relaxed);
+                #[cfg(feature = "std")]
+                let start = Instant::now();
+                let guard = lock.write();
+                #[cfg(feature = "std")]
+                self.wait_nanos
+                    .fetch_add(elapsed_nanos(start), Ordering::Relaxed);
+                guard
+            }
+        };
+        self.acquired(guard)
+    }
+
+    /// Takes the exclusive lock if it is free.
+    pub(crate) fn try_write<'a, R: RawRwLock, T>(
+        &'a self,
+        lock: &'a RwLock<R, T>,
+    ) -> Option<TimedWriteGuard<'a, R, T>> {
+        lock.try_write().map(|guard| self.acquired(guard))
+    }
+
+    /// Takes the shared lock, recording whether (and how long) the caller waited.
+    pub(crate) fn read<'a, R: RawRwLock, T>(
+        &self,
+        lock: &'a RwLock<R, T>,
+    ) -> RwLockReadGuard<'a, R, T> {
+        if let Some(guard) = lock.try_read() {
+            return guard;
+        }
+        self.waits.fetch_add(1, Ordering::Relaxed);
+        #[cfg(feature = "std")]
+        let start = Instant::now();
+        let guard = lock.read();
+        #[cfg(feature = "std")]
+        self.wait_nanos
+            .fetch_add(elapsed_nanos(start), Ordering::Relaxed);
+        guard
+    }
+
+    /// Returns a snapshot of the counters.
+    pub(crate) fn snapshot(&self) -> LockMetrics {
+        LockMetrics {
+            acquisitions: self.acquisitions.load(Ordering::Relaxed),
+            waits: self.waits.load(Ordering::Relaxed),
+            wait_nanos: self.wait_nanos.load(Ordering::Relaxed),
+            hold_nanos: self.hold_nanos.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Zeroes the counters.
+    pub(crate) fn reset(&self) {
+        self.acquisitions.store(0, Ordering::Relaxed);
+        self.waits.store(0, Ordering::Relaxed);
+        self.wait_nanos.store(0, Ordering::Relaxed);
+        self.hold_nanos.store(0, Ordering::Relaxed);
+    }
+
+    fn acquired<'a, R: RawRwLock, T>(
+        &'a self,
+        guard: RwLockWriteGuard<'a, R, T>,
+    ) -> TimedWriteGuard<'a, R, T> {
+        self.acquisitions.fetch_add(1, Ordering::Relaxed);
+        TimedWriteGuard {
+            guard,
+            #[cfg(feature = "std")]
+            stats: self,
+            #[cfg(feature = "std")]
+            acquired: Instant::now(),
+        }
+    }
+}
+
+/// Nanoseconds since `start`, saturating at `u64::MAX`.
+#[cfg(feature = "std")]
+fn elapsed_nanos(start: Instant) -> u64 {
+    u64::try_from(start.elapsed().as_nanos()).unwrap_or(u64::MAX)
+}
+
+/// An exclusive segment guard that records how long it was held.
+pub(crate) struct TimedWriteGuard<'a, R: RawRwLock, T> {
+    guard: RwLockWriteGuard<'a, R, T>,
+    #[cfg(feature = "std")]
+    stats: &'a LockStats,
+    #[cfg(feature = "std")]
+    acquired: Instant,
+}
+
+impl<R: RawRwLock, T> Deref for TimedWriteGuard<'_, R, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+}
+
+impl<R: RawRwLock, T> DerefMut for TimedWriteGuard<'_, R, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.guard
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R: RawRwLock, T> Drop for TimedWriteGuard<'_, R, T> {
+    fn drop(&mut self) {
+        self.stats
+            .hold_nanos
+            .fetch_add(elapsed_nanos(self.acquired), Ordering::Relaxed);
+    }
+}
+
+impl<R: RawRwLock, T> core::fmt::Debug for TimedWriteGuard<'_, R, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("TimedWriteGuard").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::concurrent::RawSpinRwLock;
+
+    #[test]
+    fn test_counts_acquisitions_and_waits() {
+        let stats = LockStats::new();
+        let lock = RwLock::<RawSpinRwLock, _>::new(0);
+
+        *stats.write(&lock) += 1;
+        assert!(stats.try_write(&lock).is_some());
+        {
+            let _reader = lock.read();
+            assert!(stats.try_write(&lock).is_none());
+            // Shared acquisitions don't wait for other readers
+            let _second = stats.read(&lock);
+        }
+
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot.acquisitions, 2);
+        assert_eq!(snapshot.waits, 0);
+        assert_eq!(*lock.read(), 1);
+    }
+}
@@ -17,7 +17,7 @@
 //! │  ┌──────────────┐ ┌──────────────┐     ┌──────────────┐              │
 //! │  │  Segment 0   │ │  Segment 1   │ ... │  Segment N-1 │              │
 //! │  │  ┌────────┐  │ │  ┌────────┐  │     │  ┌────────┐  │              │
-//! │  │  │ Mutex  │  │ │  │ Mutex  │  │     │  │ Mutex  │  │              │
+//! │  │  │ RwLock │  │ │  │ RwLock │  │     │  │ RwLock │  │              │
 //! │  │  └────┬───┘  │ │  └────┬───┘  │     │  └────┬───┘  │              │
 //! │  │       │      │ │       │      │     │       │      │              │
 //! │  │  ┌────▼───┐  │ │  ┌────▼───┐  │     │  ┌────▼───┐  │              │
@@ -44,7 +44,7 @@
 //! |--------|-------|
 //! | Get/Put/Remove | O(1) average |
 //! | Concurrency | Near-linear scaling up to segment count |
-//! | Memory overhead | ~150 bytes per entry + one Mutex per segment |
+//! | Memory overhead | ~150 bytes per entry + one RwLock and read buffer per segment |
 //!
 //! # When to Use
 //!
@@ -75,8 +75,10 @@
 //!     base: LruCacheConfig {
 //!         capacity: NonZeroUsize::new(10_000).unwrap(),
 //!         max_size: u64::MAX,
+//!         weight_limits: None,
 //!     },
 //!     segments: 16,
+//!     shared_budget: false,
 //! };
 //! let cache = Arc::new(ConcurrentLruCache::init(config, None));
 //!
@@ -96,18 +98,8 @@
 //! println!("Total entries: {}", cache.len());
 //! ```
 
-extern crate alloc;
-
-use crate::lru::LruSegment;
-use crate::metrics::CacheMetrics;
-use alloc::boxed::Box;
-use alloc::collections::BTreeMap;
-use alloc::string::String;
-use alloc::vec::Vec;
-use core::borrow::Borrow;
-use core::hash::{BuildHasher, Hash};
-use core::num::NonZeroUsize;
-use parking_lot::Mutex;
+use super::{DefaultRawRwLock, ShardedCache};
+use crate::LruCache;
 
 #[cfg(feature = "hashbrown")]
 use hashbrown::DefaultHashBuilder;
@@ -126,6 +118,7 @@ use std::collections::hash_map::RandomState as DefaultHashBuilder;
 /// - `K`: Key type. Must implement `Hash + Eq + Clone + Send`.
 /// - `V`: Value type. Must implement `Clone + Send`.
 /// - `S`: Hash builder type. Defaults to `DefaultHashBuilder`.
+/// - `L`: Raw segment lock. Defaults to [`DefaultRawRwLock`].
 ///
 /// # Note on LRU Semantics
 ///
@@ -146,362 +139,22 @@ use std::collections::hash_map::RandomState as DefaultHashBuilder;
 /// cache.put("key".to_string(), 42, 1);
 /// assert_eq!(cache.get(&"key".to_string()), Some(42));
 /// ```
-pub struct ConcurrentLruCache<K, V, S = DefaultHashBuilder> {
-    segments: Box<[Mutex<LruSegment<K, V, S>>]>,
-    hash_builder: S,
-}
-
-impl<K, V> ConcurrentLruCache<K, V, DefaultHashBuilder>
-where
-    K: Hash + Eq + Clone + Send,
-    V: Clone + Send,
-{
-    /// Creates a new concurrent LRU cache from a configuration with an optional hasher.
-    ///
-    /// This is the **recommended** way to create a concurrent LRU cache.
-    ///
-    /// # Arguments
-    ///
-    /// * `config` - Configuration specifying capacity, segments, and optional size limit
-    /// * `hasher` - Optional custom hash builder. If `None`, uses `DefaultHashBuilder`
-    ///
-    /// # Example
-    ///
-    /// ```rust,ignore
-    /// use cache_rs::concurrent::ConcurrentLruCache;
-    /// use cache_rs::config::{ConcurrentLruCacheConfig, ConcurrentCacheConfig, LruCacheConfig};
-    /// use core::num::NonZeroUsize;
-    ///
-    /// // Simple capacity-only cache with default segments
-    /// let config: ConcurrentLruCacheConfig = ConcurrentCacheConfig {
-    ///     base: LruCacheConfig {
-    ///         capacity: NonZeroUsize::new(10000).unwrap(),
-    ///         max_size: u64::MAX,
-    ///     },
-    ///     segments: 16,
-    /// };
-    /// let cache: ConcurrentLruCache<String, i32> = ConcurrentLruCache::init(config, None);
-    ///
-    /// // With custom segments and size limit
-    /// let config: ConcurrentLruCacheConfig = ConcurrentCacheConfig {
-    ///     base: LruCacheConfig {
-    ///         capacity: NonZeroUsize::new(10000).unwrap(),
-    ///         max_size: 100 * 1024 * 1024,  // 100MB
-    ///     },
-    ///     segments: 32,
-    /// };
-    /// let cache: ConcurrentLruCache<String, Vec<u8>> = ConcurrentLruCache::init(config, None);
-    /// ```
-    pub fn init(
-        config: crate::config::ConcurrentLruCacheConfig,
-        hasher: Option<DefaultHashBuilder>,
-    ) -> Self {
-        let segment_count = config.segments;
-        let capacity = config.base.capacity;
-        let max_size = config.base.max_size;
-
-        let segment_capacity = capacity.get() / segment_count;
-        let segment_cap = NonZeroUsize::new(segment_capacity.max(1)).unwrap();
-        let segment_max_size = max_size / segment_count as u64;
-
-        let hash_builder = hasher.unwrap_or_default();
-        let segments: Vec<_> = (0..segment_count)
-            .map(|_| {
-                let segment_config = crate::config::LruCacheConfig {
-                    capacity: segment_cap,
-                    max_size: segment_max_size,
-                };
-                Mutex::new(crate::lru::LruSegment::init(
-                    segment_config,
-                    hash_builder.clone(),
-                ))
-            })
-            .collect();
-
-        Self {
-            segments: segments.into_boxed_slice(),
-            hash_builder,
-        }
-    }
-}
-
-impl<K, V, S> ConcurrentLruCache<K, V, S>
-where
-    K: Hash + Eq + Clone + Send,
-    V: Clone + Send,
-    S: BuildHasher + Clone + Send,
-{
-    /// Returns the segment index for the given key.
-    ///
-    /// This is used internally for routing operations to the correct segment.
-    #[inline]
-    fn segment_index<Q>(&self, key: &Q) -> usize
-    where
-        K: Borrow<Q>,
-        Q: ?Sized + Hash,
-    {
-        (self.hash_builder.hash_one(key) as usize) % self.segments.len()
-    }
-
-    /// Returns the total capacity across all segments.
-    pub fn capacity(&self) -> usize {
-        self.segments.iter().map(|s| s.lock().cap().get()).sum()
-    }
-
-    /// Returns the number of segments in the cache.
-    pub fn segment_count(&self) -> usize {
-        self.segments.len()
-    }
-
-    /// Returns the total number of entries across all segments.
-    ///
-    /// Note: This acquires a lock on each segment sequentially, so the
-    /// returned value may be slightly stale in high-concurrency scenarios.
-    pub fn len(&self) -> usize {
-        self.segments.iter().map(|s| s.lock().len()).sum()
-    }
-
-    /// Returns `true` if the cache contains no entries.
-    pub fn is_empty(&self) -> bool {
-        self.segments.iter().all(|s| s.lock().is_empty())
-    }
-
-    /// Retrieves a value from the cache.
-    ///
-    /// Returns a **clone** of the value to avoid holding the lock. For operations
-    /// that don't need ownership, use [`get_with()`](Self::get_with) instead.
-    ///
-    /// If the key exists, it is moved to the MRU position within its segment.
-    ///
-    /// # Example
-    ///
-    /// ```rust,ignore
-    /// let value = cache.get(&"key".to_string());
-    /// ```
-    pub fn get<Q>(&self, key: &Q) -> Option<V>
-    where
-        K: Borrow<Q>,
-        Q: ?Sized + Hash + Eq,
-    {
-        let idx = self.segment_index(key);
-        let mut segment = self.segments[idx].lock();
-        segment.get(key).cloned()
-    }
-
-    /// Retrieves a value and applies a function to it while holding the lock.
-    ///
-    /// More efficient than `get()` when you only need to read from the value,
-    /// as it avoids cloning. The lock is released after `f` returns.
-    ///
-    /// # Type Parameters
-    ///
-    /// - `F`: Function that takes `&V` and returns `R`
-    /// - `R`: Return type of the function
-    ///
-    /// # Example
-    ///
-    /// ```rust,ignore
-    /// // Get length without cloning the whole string
-    /// let len = cache.get_with(&key, |value| value.len());
-    /// ```
-    pub fn get_with<Q, F, R>(&self, key: &Q, f: F) -> Option<R>
-    where
-        K: Borrow<Q>,
-        Q: ?Sized + Hash + Eq,
-        F: FnOnce(&V) -> R,
-    {
-        let idx = self.segment_index(key);
-        let mut segment = self.segments[idx].lock();
-        segment.get(key).map(f)
-    }
-
-    /// Retrieves a mutable reference and applies a function to it.
-    ///
-    /// Allows in-place modification of cached values without removing them.
-    ///
-    /// # Example
-    ///
-    /// ```rust,ignore
-    /// // Increment a counter in-place
-    /// cache.get_mut_with(&"counter".to_string(), |value| *value += 1);
-    /// ```
-    pub fn get_mut_with<Q, F, R>(&self, key: &Q, f: F) -> Option<R>
-    where
-        K: Borrow<Q>,
-        Q: ?Sized + Hash + Eq,
-        F: FnOnce(&mut V) -> R,
-    {
-        let idx = self.segment_index(key);
-        let mut segment = self.segments[idx].lock();
-        segment.get_mut(key).map(f)
-    }
-
-    /// Inserts a key-value pair into the cache.
-    ///
-    /// If the key exists, the value is updated and moved to MRU position.
-    /// If at capacity, the LRU entry in the target segment is evicted.
-    /// Use `SIZE_UNIT` (1) for count-based caching.
-    ///
-    /// # Returns
-    ///
-    /// - `Some((old_key, old_value))` if key existed or entry was evicted
-    /// - `None` if inserted with available capacity
-    ///
-    /// # Example
-    ///
-    /// ```rust,ignore
-    /// cache.put("key".to_string(), 42, 1);
-    /// ```
-    pub fn put(&self, key: K, value: V, size: u64) -> Option<Vec<(K, V)>> {
-        let idx = self.segment_index(&key);
-        let mut segment = self.segments[idx].lock();
-        segment.put(key, value, size)
-    }
-
-    /// Removes a key from the cache.
-    ///
-    /// # Returns
-    ///
-    /// - `Some(value)` if the key existed
-    /// - `None` if the key was not found
-    pub fn remove<Q>(&self, key: &Q) -> Option<V>
-    where
-        K: Borrow<Q>,
-        Q: ?Sized + Hash + Eq,
-    {
-        let idx = self.segment_index(key);
-        let mut segment = self.segments[idx].lock();
-        segment.remove(key)
-    }
-
-    /// Removes all entries from all segments.
-    ///
-    /// Acquires locks on each segment sequentially.
-    pub fn clear(&self) {
-        for segment in self.segments.iter() {
-            segment.lock().clear();
-        }
-    }
-
-    /// Returns the current total size across all segments.
-    ///
-    /// This is the sum of all `size` values from `put()` calls.
-    pub fn current_size(&self) -> u64 {
-        self.segments.iter().map(|s| s.lock().current_size()).sum()
-    }
-
-    /// Returns the maximum total content size across all segments.
-    pub fn max_size(&self) -> u64 {
-        self.segments.iter().map(|s| s.lock().max_size()).sum()
-    }
-
-    /// Records a cache miss for metrics tracking.
-    ///
-    /// Call this after a failed `get()` when you fetch from the origin.
-    pub fn record_miss(&self, object_size: u64) {
-        // Record on the first segment (metrics are aggregated anyway)
-        if let Some(segment) = self.segments.first() {
-            segment.lock().record_miss(object_size);
-        }
-    }
-
-    /// Checks if the cache contains a key without promoting it.
-    ///
-    /// This is a pure existence check that does **not** update the entry's recency.
-    ///
-    /// # Example
-    ///
-    /// ```rust,ignore
-    /// if cache.contains(&"key".to_string()) {
-    ///     println!("Key exists!");
-    /// }
-    /// ```
-    pub fn contains<Q>(&self, key: &Q) -> bool
-    where
-        K: Borrow<Q>,
-        Q: ?Sized + Hash + Eq,
-    {
-        let idx = self.segment_index(key);
-        let segment = self.segments[idx].lock();
-        segment.contains(key)
-    }
-
-    /// Returns a clone of the value without updating the LRU order.
-    ///
-    /// Unlike [`get()`](Self::get), this does NOT move the entry to the front
-    /// or update any access metadata. Returns a cloned value because the
-    /// internal lock cannot be held across the return boundary.
-    ///
-    /// # Example
-    ///
-    /// ```rust,ignore
-    /// let value = cache.peek(&"key".to_string());
-    /// ```
-    pub fn peek<Q>(&self, key: &Q) -> Option<V>
-    where
-        K: Borrow<Q>,
-        Q: ?Sized + Hash + Eq,
-        V: Clone,
-    {
-        let idx = self.segment_index(key);
-        let segment = self.segments[idx].lock();
-        segment.peek(key).cloned()
-    }
-}
-
-impl<K, V, S> CacheMetrics for ConcurrentLruCache<K, V, S>
-where
-    K: Hash + Eq + Clone + Send,
-    V: Clone + Send,
-    S: BuildHasher + Clone + Send,
-{
-    fn metrics(&self) -> BTreeMap<String, f64> {
-        // Aggregate metrics from all segments
-        let mut aggregated = BTreeMap::new();
-
-        for segment in self.segments.iter() {
-            let segment_metrics = segment.lock().metrics().metrics();
-            for (key, value) in segment_metrics {
-                *aggregated.entry(key).or_insert(0.0) += value;
-            }
-        }
-
-        aggregated
-    }
-
-    fn algorithm_name(&self) -> &'static str {
-        "ConcurrentLRU"
-    }
-}
-
-// SAFETY: ConcurrentLruCache uses Mutex for synchronization, making it safe to
-// send and share across threads when K and V are Send.
-unsafe impl<K: Send, V: Send, S: Send> Send for ConcurrentLruCache<K, V, S> {}
-unsafe impl<K: Send, V: Send, S: Send + Sync> Sync for ConcurrentLruCache<K, V, S> {}
-
-impl<K, V, S> core::fmt::Debug for ConcurrentLruCache<K, V, S>
-where
-    K: Hash + Eq + Clone + Send,
-    V: Clone + Send,
-    S: BuildHasher + Clone + Send,
-{
-    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        f.debug_struct("ConcurrentLruCache")
-            .field("segment_count", &self.segments.len())
-            .field("total_len", &self.len())
-            .finish()
-    }
-}
+pub type ConcurrentLruCache<K, V, S = DefaultHashBuilder, L = DefaultRawRwLock> =
+    ShardedCache<LruCache<K, V, S>, L>;
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::concurrent::Busy;
     use crate::config::{ConcurrentCacheConfig, ConcurrentLruCacheConfig, LruCacheConfig};
+    use crate::metrics::CacheMetrics;
+    use core::num::NonZeroUsize;
 
     extern crate std;
-    use std::string::ToString;
+    use std::string::{String, ToString};
     use std::sync::Arc;
     use std::thread;
+    use std::vec;
     use std::vec::Vec;
 
     fn make_config(capacity: usize, segments: usize) -> ConcurrentLruCacheConfig {
@@ -509,8 +162,10 @@ mod tests {
             base: LruCacheConfig {
                 capacity: NonZeroUsize::new(capacity).unwrap(),
                 max_size: u64::MAX,
+                weight_limits: None,
             },
             segments,
+            shared_budget: false,
         }
     }
 
@@ -684,6 +339,20 @@ mod tests {
         assert_eq!(cache.segment_count(), 8);
     }
 
+    #[test]
+    fn test_segment_count_rounds_up_to_power_of_two() {
+        let cache: ConcurrentLruCache<usize, usize> =
+            ConcurrentLruCache::init(make_config(120, 6), None);
+        assert_eq!(cache.segment_count(), 8);
+
+        // Every segment still receives keys
+        let mut used = [false; 8];
+        for key in 0..1000 {
+            used[cache.segment_index(&key)] = true;
+        }
+        assert!(used.iter().all(|&used| used));
+    }
+
     #[test]
     fn test_capacity() {
         let cache: ConcurrentLruCache<String, i32> =
@@ -759,6 +428,24 @@ mod tests {
         assert!(!metrics.is_empty());
     }
 
+    #[test]
+    fn test_segments_without_metrics() {
+        use crate::metrics::NoMetrics;
+
+        let cache: ShardedCache<LruCache<String, i32, DefaultHashBuilder, NoMetrics>> =
+            ShardedCache::init(make_config(100, 4), None);
+        cache.put("a".to_string(), 1, 1);
+        assert_eq!(cache.get(&"a".to_string()), Some(1));
+        cache.record_miss(1);
+
+        let metrics = cache.metrics();
+        assert_eq!(metrics.get("requests"), None);
+        assert!(cache
+            .segment_metrics()
+            .iter()
+            .all(|segment| segment.core.requests == 0));
+    }
+
     #[test]
     fn test_record_miss() {
         let cache: ConcurrentLruCache<String, i32> =
@@ -834,4 +521,341 @@ mod tests {
         assert!(cache.contains(&"b".to_string()));
         assert!(!cache.contains(&"c".to_string()));
     }
+
+    #[test]
+    fn test_buffered_reads_promote_before_eviction() {
+        let cache: ConcurrentLruCache<String, i32> =
+            ConcurrentLruCache::init(make_config(3, 1), None);
+
+        cache.put("a".to_string(), 1, 1);
+        cache.put("b".to_string(), 2, 1);
+        cache.put("c".to_string(), 3, 1);
+
+        // The promotion of "a" is buffered and must be replayed before the
+        // eviction triggered by the next put
+        assert_eq!(cache.get(&"a".to_string()), Some(1));
+        cache.put("d".to_string(), 4, 1);
+
+        assert!(cache.contains(&"a".to_string()));
+        assert!(!cache.contains(&"b".to_string()));
+    }
+
+    #[test]
+    fn test_buffered_reads_counted_in_metrics() {
+        let cache: ConcurrentLruCache<String, i32> =
+            ConcurrentLruCache::init(make_config(100, 4), None);
+
+        cache.put("a".to_string(), 1, 1);
+        for _ in 0..100 {
+            assert_eq!(cache.get(&"a".to_string()), Some(1));
+        }
+
+        let metrics = cache.metrics();
+        assert_eq!(metrics.get("cache_hits"), Some(&100.0));
+        assert_eq!(metrics.get("read_buffer_drops"), Some(&0.0));
+    }
+
+    #[test]
+    fn test_dropped_reads_count_as_hits() {
+        let cache: ConcurrentLruCache<String, i32> =
+            ConcurrentLruCache::init(make_config(100, 1), None);
+        cache.put("a".to_string(), 1, 1);
+
+        // The outer read holds the shared lock, so a full stripe can't be
+        // drained and further reads are dropped
+        cache.get_with(&"a".to_string(), |_| {
+            for _ in 0..40 {
+                assert_eq!(cache.get(&"a".to_string()), Some(1));
+            }
+        });
+
+        let metrics = cache.metrics();
+        assert!(metrics["read_buffer_drops"] > 0.0);
+        assert_eq!(metrics.get("cache_hits"), Some(&41.0));
+        assert_eq!(metrics.get("requests"), Some(&41.0));
+    }
+
+    #[test]
+    fn test_hot_key_reads_across_threads() {
+        let cache: Arc<ConcurrentLruCache<String, i32>> =
+            Arc::new(ConcurrentLruCache::init(make_config(100, 4), None));
+        cache.put("hot".to_string(), 7, 1);
+
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let cache = Arc::clone(&cache);
+                thread::spawn(move || {
+                    for _ in 0..1000 {
+                        assert_eq!(cache.get(&"hot".to_string()), Some(7));
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        // Every read counts as a hit, whether it was replayed or dropped
+        let metrics = cache.metrics();
+        assert_eq!(metrics.get("cache_hits"), Some(&8000.0));
+        assert_eq!(metrics.get("requests"), Some(&8000.0));
+        assert_eq!(metrics.get("hit_rate"), Some(&1.0));
+        let segment_hits: u64 = cache
+            .segment_metrics()
+            .iter()
+            .map(|s| s.core.cache_hits)
+            .sum();
+        assert_eq!(segment_hits, 8000);
+    }
+
+    #[test]
+    fn test_compute_inserts_updates_and_removes() {
+        let cache: ConcurrentLruCache<String, i32> =
+            ConcurrentLruCache::init(make_config(100, 4), None);
+
+        assert_eq!(
+            cache.compute("a".to_string(), |v| Some((v.map_or(1, |v| v + 1), 10))),
+            (Some(1), None)
+        );
+        assert_eq!(
+            cache.compute("a".to_string(), |v| Some((v.map_or(1, |v| v + 1), 4))),
+            (Some(2), None)
+        );
+        assert_eq!(cache.current_size(), 4);
+
+        assert_eq!(cache.compute("a".to_string(), |_| None), (None, None));
+        assert!(!cache.contains("a"));
+        assert_eq!(cache.current_size(), 0);
+    }
+
+    #[test]
+    fn test_atomic_operations_return_evictions() {
+        let cache: ConcurrentLruCache<String, i32> =
+            ConcurrentLruCache::init(make_config(2, 1), None);
+        cache.put("a".to_string(), 1, 1);
+        cache.put("b".to_string(), 2, 1);
+
+        let (_, evicted) = cache.compute("c".to_string(), |_| Some((3, 1)));
+        assert_eq!(evicted, Some(vec![("a".to_string(), 1)]));
+        assert_eq!(
+            cache.put_if_absent("d".to_string(), 4, 1),
+            Ok(Some(vec![("b".to_string(), 2)]))
+        );
+
+        // Growing an entry past a shared max_size evicts the others
+        let mut config = make_config(10, 1);
+        config.base.max_size = 10;
+        config.shared_budget = true;
+        let cache: ConcurrentLruCache<String, i32> = ConcurrentLruCache::init(config, None);
+        cache.put("a".to_string(), 1, 5);
+        cache.put("b".to_string(), 2, 5);
+        assert_eq!(
+            cache.replace_if("b", |_| true, 3, 10),
+            Ok(Some(vec![("a".to_string(), 1)]))
+        );
+    }
+
+    #[test]
+    fn test_conditional_operations() {
+        let cache: ConcurrentLruCache<String, i32> =
+            ConcurrentLruCache::init(make_config(100, 4), None);
+
+        assert_eq!(cache.put_if_absent("a".to_string(), 1, 5), Ok(None));
+        assert_eq!(cache.put_if_absent("a".to_string(), 2, 5), Err(1));
+        assert_eq!(cache.peek("a"), Some(1));
+
+        assert_eq!(cache.replace_if("a", |v| *v == 0, 3, 7), Err(3));
+        assert_eq!(cache.replace_if("a", |v| *v == 1, 3, 7), Ok(None));
+        assert_eq!(cache.replace_if("missing", |_| true, 3, 7), Err(3));
+        assert_eq!(cache.peek("a"), Some(3));
+        assert_eq!(cache.current_size(), 7);
+
+        assert_eq!(cache.remove_if("a", |v| *v == 1), None);
+        assert_eq!(cache.remove_if("a", |v| *v == 3), Some(3));
+        assert_eq!(cache.current_size(), 0);
+        assert!(cache.is_empty());
+    }
+
+    fn make_shared_config(
+        capacity: usize,
+        max_size: u64,
+        segments: usize,
+    ) -> ConcurrentLruCacheConfig {
+        ConcurrentCacheConfig {
+            base: LruCacheConfig {
+                capacity: NonZeroUsize::new(capacity).unwrap(),
+                max_size,
+                weight_limits: None,
+            },
+            segments,
+            shared_budget: true,
+        }
+    }
+
+    #[test]
+    fn test_shared_budget_fills_whole_capacity() {
+        let cache: ConcurrentLruCache<i32, i32> =
+            ConcurrentLruCache::init(make_shared_config(16, u64::MAX, 4), None);
+        assert_eq!(cache.capacity(), 16);
+        assert_eq!(cache.max_size(), u64::MAX);
+
+        // Hashing never spreads 16 keys evenly over 4 segments, yet nothing is
+        // evicted until the cache as a whole is full
+        for i in 0..16 {
+            assert_eq!(cache.put(i, i, 1), None);
+        }
+        assert_eq!(cache.len(), 16);
+
+        let mut evicted = 0;
+        for i in 16..32 {
+            evicted += cache.put(i, i, 1).map_or(0, |e| e.len());
+        }
+        assert_eq!(evicted, 16);
+        assert_eq!(cache.len(), 16);
+    }
+
+    #[test]
+    fn test_shared_budget_evicts_from_largest_segment() {
+        let cache: ConcurrentLruCache<i32, i32> =
+            ConcurrentLruCache::init(make_shared_config(100, 1000, 4), None);
+
+        // One large object may use more than a quarter of the budget
+        cache.put(1, 1, 600);
+        cache.put(2, 2, 300);
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.current_size(), 900);
+
+        // Exceeding the budget evicts the largest segment's LRU entry
+        let evicted = cache.put(3, 3, 200).unwrap();
+        assert_eq!(evicted, [(1, 1)]);
+        assert_eq!(cache.current_size(), 500);
+
+        cache.remove(&2);
+        cache.clear();
+        assert_eq!(cache.current_size(), 0);
+        cache.put(4, 4, 1000);
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn test_segment_metrics_partition_the_cache() {
+        let cache: ConcurrentLruCache<String, i32> =
+            ConcurrentLruCache::init(make_config(100, 4), None);
+        for i in 0..40 {
+            cache.put(i.to_string(), i, 1);
+        }
+        for i in 0..10 {
+            cache.get(&i.to_string());
+        }
+        cache.get("missing");
+
+        let segments = cache.segment_metrics();
+        assert_eq!(segments.len(), 4);
+        assert_eq!(segments.iter().map(|s| s.len).sum::<usize>(), 40);
+        assert_eq!(segments.iter().map(|s| s.size).sum::<u64>(), 40);
+        assert_eq!(segments.iter().map(|s| s.core.cache_hits).sum::<u64>(), 10);
+        assert!(segments.iter().all(|s| s.lock.acquisitions > 0));
+
+        let idx = cache.segment_index("0");
+        assert!(segments[idx].core.cache_hits > 0);
+    }
+
+    #[test]
+    fn test_segment_skew_and_lock_waits() {
+        let cache = Arc::new(ConcurrentLruCache::<String, i32>::init(
+            make_config(100, 4),
+            None,
+        ));
+        let hot = "hot".to_string();
+        cache.put(hot.clone(), 1, 1);
+        for _ in 0..100 {
+            cache.get(&hot);
+        }
+
+        // A single hot key puts all requests on one segment
+        let skew = cache.segment_skew();
+        assert_eq!(skew.requests.max_min_ratio, f64::INFINITY);
+        assert!(skew.requests.coefficient_of_variation > 1.0);
+
+        // A writer blocked on a held lock shows up as a wait
+        let idx = cache.segment_index(&hot);
+        let writer = {
+            let _held = cache.segments[idx].write();
+            let writer_cache = Arc::clone(&cache);
+            let hot = hot.clone();
+            let writer = thread::spawn(move || writer_cache.put(hot, 2, 1));
+            while cache.lock_stats[idx].snapshot().waits == 0 {
+                thread::yield_now();
+            }
+            writer
+        };
+        writer.join().unwrap();
+
+        let segments = cache.segment_metrics();
+        assert_eq!(segments[idx].lock.waits, 1);
+        assert_eq!(cache.peek(&hot), Some(2));
+    }
+
+    #[test]
+    fn test_try_operations_uncontended() {
+        let cache: ConcurrentLruCache<String, i32> =
+            ConcurrentLruCache::init(make_config(100, 4), None);
+
+        assert_eq!(cache.try_put("a".to_string(), 1, 1), Ok(None));
+        assert_eq!(cache.try_get("a"), Ok(Some(1)));
+        assert_eq!(cache.try_get("missing"), Ok(None));
+        assert_eq!(cache.try_remove("a"), Ok(Some(1)));
+        assert_eq!(cache.try_remove("a"), Ok(None));
+
+        let metrics = cache.metrics();
+        assert_eq!(metrics.get("lock_contention_misses"), Some(&0.0));
+    }
+
+    #[test]
+    fn test_try_operations_report_busy() {
+        let cache: ConcurrentLruCache<String, i32> =
+            ConcurrentLruCache::init(make_config(100, 4), None);
+        cache.put("a".to_string(), 1, 1);
+
+        let idx = cache.segment_index("a");
+        {
+            let _writer = cache.segments[idx].write();
+            assert_eq!(cache.try_get("a"), Err(Busy(())));
+            assert_eq!(cache.try_remove("a"), Err(Busy(())));
+            let rejected = cache.try_put("a".to_string(), 2, 1).unwrap_err();
+            assert_eq!(rejected.into_inner(), ("a".to_string(), 2));
+        }
+        {
+            // Readers don't block other readers, but do block writers
+            let _reader = cache.segments[idx].read();
+            assert_eq!(cache.try_get("a"), Ok(Some(1)));
+            assert!(cache.try_put("a".to_string(), 3, 1).is_err());
+        }
+
+        assert_eq!(cache.get("a"), Some(1));
+        let metrics = cache.metrics();
+        assert_eq!(metrics.get("lock_contention_misses"), Some(&4.0));
+    }
+
+    #[cfg(all(feature = "std", feature = "concurrent"))]
+    #[test]
+    fn test_get_timeout() {
+        use core::time::Duration;
+
+        let cache: ConcurrentLruCache<String, i32> =
+            ConcurrentLruCache::init(make_config(100, 4), None);
+        cache.put("a".to_string(), 1, 1);
+
+        assert_eq!(
+            cache.get_timeout("a", Duration::from_millis(10)),
+            Ok(Some(1))
+        );
+
+        let idx = cache.segment_index("a");
+        let _writer = cache.segments[idx].write();
+        assert_eq!(
+            cache.get_timeout("a", Duration::from_millis(10)),
+            Err(Busy(()))
+        );
+    }
 }
@@ -0,0 +1,208 @@
+//! Striped, Lossy Read Buffers
+//!
+//! Every cache algorithm mutates its bookkeeping on `get()` (LRU promotion,
+//! frequency bumps, SLRU promotion, priority updates). Taking the segment's
+//! exclusive lock for every read makes hot segments the bottleneck under
+//! many threads.
+//!
+//! The concurrent caches instead look entries up under a **shared** lock and
+//! record the access here. The recorded accesses are replayed against the
+//! segment, in order, the next time someone holds the exclusive lock: before
+//! every write, or opportunistically by a reader whose stripe filled up.
+//!
+//! ```text
+//! get(k) ──▶ RwLock::read ──▶ peek ──▶ clone value ──▶ ReadBuffer::record(k)
+//!                                                            │ stripe full?
+//!                                                            ▼
+//!                                       RwLock::try_write ──▶ drain + replay
+//! put/remove/... ──▶ RwLock::write ──▶ drain + replay ──▶ operation
+//! ```
+//!
+//! # Lossy by Design
+//!
+//! The buffer never blocks a reader. When a stripe is contended (another
+//! thread holds it) or full (no drain could run yet), the access is dropped
+//! and counted in [`ReadBuffer::dropped`], which the cache adds to its hit
+//! count. A dropped access only costs recency/frequency precision, never
+//! correctness of the stored data.
+//!
+//! # Striping
+//!
+//! Each thread is assigned a stripe on first use, so a single thread always
+//! appends to the same stripe and its accesses are replayed in program order.
+//! Single-threaded use therefore behaves exactly like the sequential caches.
+//!
+//! Assigning stripes needs thread-locals. Without `std`, every thread uses
+//! the first stripe, so concurrent readers of one segment drop more accesses.
+
+extern crate alloc;
+#[cfg(any(feature = "std", feature = "concurrent"))]
+extern crate std;
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicU64, Ordering};
+use lock_api::{RawRwLock, RwLock};
+
+#[cfg(any(feature = "std", feature = "concurrent"))]
+use core::sync::atomic::AtomicUsize;
+
+/// Number of stripes per segment's read buffer.
+pub(crate) const READ_BUFFER_STRIPES: usize = 4;
+
+/// Number of pending accesses a stripe holds before it asks for a drain.
+pub(crate) const READ_BUFFER_STRIPE_CAPACITY: usize = 32;
+
+/// Source of per-thread stripe probes.
+#[cfg(any(feature = "std", feature = "concurrent"))]
+static NEXT_PROBE: AtomicUsize = AtomicUsize::new(0);
+
+#[cfg(any(feature = "std", feature = "concurrent"))]
+std::thread_local! {
+    static PROBE: usize = NEXT_PROBE.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Returns the calling thread's stripe probe.
+#[cfg(any(feature = "std", feature = "concurrent"))]
+#[inline]
+fn probe() -> usize {
+    PROBE.with(|probe| *probe)
+}
+
+/// Returns the calling thread's stripe probe.
+#[cfg(not(any(feature = "std", feature = "concurrent")))]
+#[inline]
+fn probe() -> usize {
+    0
+}
+
+/// A striped, lossy buffer of keys read under a segment's shared lock.
+///
+/// Stripes are guarded by the segment lock type `R`, used as a mutex.
+pub(crate) struct ReadBuffer<K, R: RawRwLock> {
+    stripes: Box<[RwLock<R, Vec<K>>]>,
+    dropped: AtomicU64,
+}
+
+impl<K, R: RawRwLock> ReadBuffer<K, R> {
+    /// Creates an empty read buffer.
+    pub(crate) fn new() -> Self {
+        let stripes: Vec<_> = (0..READ_BUFFER_STRIPES)
+            .map(|_| RwLock::new(Vec::with_capacity(READ_BUFFER_STRIPE_CAPACITY)))
+            .collect();
+        Self {
+            stripes: stripes.into_boxed_slice(),
+            dropped: AtomicU64::new(0),
+        }
+    }
+
+    /// Records an access to `key`.
+    ///
+    /// Returns `true` when the calling thread's stripe is full and the caller
+    /// should try to drain the buffer.
+    pub(crate) fn record(&self, key: K) -> bool {
+        let stripe = &self.stripes[probe() % self.stripes.len()];
+        let Some(mut pending) = stripe.try_write() else {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+            return false;
+        };
+        if pending.len() >= READ_BUFFER_STRIPE_CAPACITY {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+            return true;
+        }
+        pending.push(key);
+        pending.len() >= READ_BUFFER_STRIPE_CAPACITY
+    }
+
+    /// Removes every pending access and hands it to `replay`.
+    ///
+    /// The caller must hold the segment's exclusive lock so the replayed
+    /// accesses are applied atomically with respect to other writers.
+    pub(crate) fn drain<F>(&self, mut replay: F)
+    where
+        F: FnMut(K),
+    {
+        for stripe in self.stripes.iter() {
+            for key in stripe.write().drain(..) {
+                replay(key);
+            }
+        }
+    }
+
+    /// Discards every pending access without replaying it.
+    pub(crate) fn clear(&self) {
+        for stripe in self.stripes.iter() {
+            stripe.write().clear();
+        }
+    }
+
+    /// Returns the number of accesses dropped because a stripe was busy or full.
+    pub(crate) fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Zeroes the dropped access counter.
+    pub(crate) fn reset_dropped(&self) {
+        self.dropped.store(0, Ordering::Relaxed);
+    }
+}
+
+impl<K, R: RawRwLock> core::fmt::Debug for ReadBuffer<K, R> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ReadBuffer")
+            .field("stripes", &self.stripes.len())
+            .field("dropped", &self.dropped())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::concurrent::RawSpinRwLock;
+
+    #[test]
+    fn test_record_and_drain_preserves_order() {
+        let buffer: ReadBuffer<_, RawSpinRwLock> = ReadBuffer::new();
+        for key in 0..10 {
+            assert!(!buffer.record(key));
+        }
+
+        let mut replayed = Vec::new();
+        buffer.drain(|key| replayed.push(key));
+        assert_eq!(replayed, (0..10).collect::<Vec<_>>());
+
+        let mut again = Vec::new();
+        buffer.drain(|key| again.push(key));
+        assert!(again.is_empty());
+    }
+
+    #[test]
+    fn test_full_stripe_requests_drain_and_drops() {
+        let buffer: ReadBuffer<_, RawSpinRwLock> = ReadBuffer::new();
+        for key in 0..READ_BUFFER_STRIPE_CAPACITY - 1 {
+            assert!(!buffer.record(key));
+        }
+        assert!(buffer.record(READ_BUFFER_STRIPE_CAPACITY - 1));
+
+        // Stripe is full: further accesses are dropped until someone drains.
+        assert!(buffer.record(usize::MAX));
+        assert_eq!(buffer.dropped(), 1);
+
+        let mut count = 0;
+        buffer.drain(|_| count += 1);
+        assert_eq!(count, READ_BUFFER_STRIPE_CAPACITY);
+    }
+
+    #[test]
+    fn test_clear_discards_pending() {
+        let buffer: ReadBuffer<_, RawSpinRwLock> = ReadBuffer::new();
+        buffer.record(1);
+        buffer.record(2);
+        buffer.clear();
+
+        let mut count = 0;
+        buffer.drain(|_| count += 1);
+        assert_eq!(count, 0);
+    }
+}
@@ -0,0 +1,308 @@
+//! Segment Trait
+//!
+//! [`ShardedCache`](crate::concurrent::ShardedCache) makes any single-threaded
+//! cache thread-safe by running one instance per segment behind its own lock.
+//! The [`Segment`] trait is everything it needs from such a cache: a way to
+//! build one slice of the configured capacity, the usual lookups and writes,
+//! and a few hooks for read buffers, shared budgets and metrics.
+//! [`SegmentFeatures`] adds the optional features, such as tags, weights and
+//! refresh policies; the sharded cache only offers them over segments that
+//! implement it.
+//!
+//! All built-in caches implement both, and user-defined policies can
+//! implement either:
+//!
+//! ```text
+//! struct MyPolicy<K, V> { ... }
+//!
+//! impl<K: Hash + Eq + Clone, V: Clone> Segment for MyPolicy<K, V> { ... }
+//!
+//! type ConcurrentMyPolicy<K, V> = ShardedCache<MyPolicy<K, V>>;
+//! ```
+
+extern crate alloc;
+
+use crate::config::SizedConfig;
+use crate::metrics::{
+    CacheMetrics, Clock, CoreCacheMetrics, HotKeys, MissRatioCurve, RollingWindow,
+};
+use crate::negative::NegativeCache;
+use crate::refresh::{Freshness, RefreshPolicy, XFetch};
+use crate::weight::{WeightLimits, Weights};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::hash::{BuildHasher, Hash};
+use core::time::Duration;
+
+/// A single-threaded cache that can serve as one segment of a
+/// [`ShardedCache`](crate::concurrent::ShardedCache).
+///
+/// Segments are only ever accessed under their segment's lock: methods taking
+/// `&self` under the shared lock (possibly from several threads at once), and
+/// methods taking `&mut self` under the exclusive lock.
+///
+/// # Precomputed Hashes
+///
+/// The sharded cache hashes every key once, with a clone of the hasher passed
+/// to [`init()`](Self::init), to pick its segment. Methods taking a key also
+/// receive that `hash`, so segments can look the key up without hashing it
+/// again. Segments that keep their own map may ignore it.
+///
+/// # Read Buffers
+///
+/// Concurrent `get()` calls look entries up with
+/// [`peek_key_value()`](Self::peek_key_value), which must not change any
+/// policy state. The accesses are replayed later through [`get()`](Self::get),
+/// which is where the policy records the hit (promotion, frequency bump, ...).
+pub trait Segment: CacheMetrics + Sized {
+    /// Key type. Keys are cloned into the segment's read buffer on every read.
+    type Key: Hash + Eq + Clone;
+    /// Value type. Values are cloned out of the segment by `get()` and `peek()`.
+    type Value: Clone;
+    /// Hash builder of the segment's map. Every segment receives a clone of
+    /// the hash builder that also routes keys to segments.
+    type Hasher: BuildHasher + Clone;
+    /// Configuration of one segment, whose limits can be read without
+    /// building a segment.
+    type Config: Copy + SizedConfig;
+
+    /// Name reported by the sharded cache's
+    /// [`algorithm_name()`](CacheMetrics::algorithm_name).
+    const CONCURRENT_NAME: &'static str = "Sharded";
+
+    /// Creates an empty segment.
+    fn init(config: Self::Config, hasher: Self::Hasher) -> Self;
+
+    /// Creates an empty segment limited by `config`, one of `segments`
+    /// segments sharing a budget, that preallocates room for only
+    /// `capacity` entries.
+    ///
+    /// With a shared budget each segment may grow to the whole cache's
+    /// limits, but only preallocates its fair share. Weights aren't in the
+    /// budget, so segments that weigh their entries enforce their share of
+    /// the weight limits, like [`split_config()`](Self::split_config). Builds
+    /// the segment with [`init()`](Self::init) by default.
+    fn init_shared(
+        config: Self::Config,
+        hasher: Self::Hasher,
+        capacity: usize,
+        segments: usize,
+    ) -> Self {
+        let _ = (capacity, segments);
+        Self::init(config, hasher)
+    }
+
+    /// Returns the configuration of one of `segments` segments sharing the
+    /// limits of `config` evenly.
+    fn split_config(config: Self::Config, segments: usize) -> Self::Config;
+
+    /// Returns the maximum number of entries the segment can hold.
+    fn capacity(&self) -> usize;
+
+    /// Returns the number of entries in the segment.
+    fn len(&self) -> usize;
+
+    /// Returns `true` if the segment contains no entries.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the total size of the entries in the segment.
+    fn current_size(&self) -> u64;
+
+    /// Returns the maximum total size of the entries in the segment.
+    fn max_size(&self) -> u64;
+
+    /// Looks up `key` and records the access with the eviction policy.
+    fn get<Q>(&mut self, hash: u64, key: &Q) -> Option<&Self::Value>
+    where
+        Self::Key: Borrow<Q>,
+        Q: ?Sized + Hash + Eq;
+
+    /// Like [`get()`](Self::get), but returns a mutable reference.
+    fn get_mut<Q>(&mut self, hash: u64, key: &Q) -> Option<&mut Self::Value>
+    where
+        Self::Key: Borrow<Q>,
+        Q: ?Sized + Hash + Eq;
+
+    /// Looks up the stored key and value without recording the access.
+    fn peek_key_value<Q>(&self, hash: u64, key: &Q) -> Option<(&Self::Key, &Self::Value)>
+    where
+        Self::Key: Borrow<Q>,
+        Q: ?Sized + Hash + Eq;
+
+    /// Looks up `key` without recording the access.
+    fn peek<Q>(&self, hash: u64, key: &Q) -> Option<&Self::Value>
+    where
+        Self::Key: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.peek_key_value(hash, key).map(|(_, value)| value)
+    }
+
+    /// Returns `true` if `key` is cached, without recording the access.
+    fn contains<Q>(&self, hash: u64, key: &Q) -> bool
+    where
+        Self::Key: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.peek_key_value(hash, key).is_some()
+    }
+
+    /// Inserts or updates an entry of the given size, returning the entries
+    /// evicted to make room.
+    fn put(
+        &mut self,
+        hash: u64,
+        key: Self::Key,
+        value: Self::Value,
+        size: u64,
+    ) -> Option<Vec<(Self::Key, Self::Value)>>;
+
+    /// Removes `key`, returning its value.
+    fn remove<Q>(&mut self, hash: u64, key: &Q) -> Option<Self::Value>
+    where
+        Self::Key: Borrow<Q>,
+        Q: ?Sized + Hash + Eq;
+
+    /// Removes all entries.
+    fn clear(&mut self);
+
+    /// Evicts the entry the policy would evict next, counting it as an eviction.
+    ///
+    /// Used to enforce a shared budget across segments.
+    fn pop_victim(&mut self) -> Option<(Self::Key, Self::Value)>;
+
+    /// Records a cache miss for an object of the given size.
+    fn record_miss(&mut self, object_size: u64);
+
+    /// Returns the segment's request, hit, eviction and byte counters.
+    fn core_metrics(&self) -> &CoreCacheMetrics;
+
+    /// Samples a lookup of the key with the given hash for the miss-ratio
+    /// curve set with
+    /// [`SegmentFeatures::set_miss_ratio_curve()`]. [`ShardedCache`](super::ShardedCache)
+    /// calls it for lookups that miss; hits are sampled by
+    /// [`get()`](Self::get). Does nothing by default, as only segments
+    /// implementing [`SegmentFeatures`] have a curve.
+    fn record_lookup(&mut self, hash: u64) {
+        let _ = hash;
+    }
+
+    /// Returns the freshness of `key` under the refresh policy set with
+    /// [`SegmentFeatures::set_refresh_policy()`] without recording an
+    /// access, or `None` if it is not cached. Every cached entry is fresh by
+    /// default, as only segments implementing [`SegmentFeatures`] have a
+    /// policy.
+    fn peek_freshness<Q>(&self, hash: u64, key: &Q) -> Option<Freshness>
+    where
+        Self::Key: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.contains(hash, key).then_some(Freshness::Fresh)
+    }
+}
+
+/// The optional features of a [`Segment`]: rolling metrics windows,
+/// miss-ratio curves, hot keys, negative caching, tags, weights, miss costs
+/// and refresh policies.
+///
+/// [`ShardedCache`](crate::concurrent::ShardedCache) only offers the matching
+/// methods over segments implementing this trait, so a feature a segment
+/// lacks is a compile error rather than silently ignored. A segment
+/// implementing it must also sample lookups with
+/// [`Segment::record_lookup()`] and report freshness with
+/// [`Segment::peek_freshness()`].
+pub trait SegmentFeatures: Segment {
+    /// Tracks the metrics of the most recent requests in `window`, or stops
+    /// with `None`.
+    fn set_metrics_window(&mut self, window: Option<RollingWindow>);
+
+    /// Zeroes the segment's metrics counters, keeping its gauges.
+    fn reset_metrics(&mut self);
+
+    /// Estimates the miss ratio at other capacities with `curve`, or stops
+    /// with `None`.
+    fn set_miss_ratio_curve(&mut self, curve: Option<MissRatioCurve>);
+
+    /// Ranks the keys of hits with `hot_keys`, or stops with `None`.
+    fn set_hot_keys(&mut self, hot_keys: Option<HotKeys<Self::Key>>);
+
+    /// Returns the segment's hot-key tracker, if one is set.
+    fn hot_keys(&self) -> Option<&HotKeys<Self::Key>>;
+
+    /// Remembers keys known to be absent in `negative`, or stops with
+    /// `None`.
+    fn set_negative_cache(&mut self, negative: Option<NegativeCache<Self::Key>>);
+
+    /// Returns the segment's negative cache, if one is set.
+    fn negative_cache(&self) -> Option<&NegativeCache<Self::Key>>;
+
+    /// Records that `key` is absent for the next `ttl`, removing its cached
+    /// value. Does nothing without a negative cache.
+    fn put_absent(&mut self, hash: u64, key: Self::Key, ttl: Duration);
+
+    /// Returns `true` if `key`, which is not cached, has an unexpired
+    /// negative entry, counting a negative hit or miss.
+    fn check_absent<Q>(&mut self, hash: u64, key: &Q) -> bool
+    where
+        Self::Key: Borrow<Q>,
+        Q: ?Sized + Hash + Eq;
+
+    /// Inserts or updates an entry like [`put()`](Segment::put), tagging it
+    /// with `tags` in place of any previous tags.
+    fn put_tagged(
+        &mut self,
+        hash: u64,
+        key: Self::Key,
+        value: Self::Value,
+        size: u64,
+        tags: &[u64],
+    ) -> Option<Vec<(Self::Key, Self::Value)>>;
+
+    /// Removes every entry tagged with `tag`, returning how many were
+    /// removed.
+    fn invalidate_tag(&mut self, tag: u64) -> usize;
+
+    /// Inserts or updates an entry like [`put()`](Segment::put), weighing it
+    /// with `weights` after evicting entries until every weight dimension
+    /// fits.
+    fn put_weighted(
+        &mut self,
+        hash: u64,
+        key: Self::Key,
+        value: Self::Value,
+        size: u64,
+        weights: Weights,
+    ) -> Option<Vec<(Self::Key, Self::Value)>>;
+
+    /// Weighs entries in the dimensions of `limits`, or stops with `None`.
+    fn set_weight_limits(&mut self, limits: Option<WeightLimits>);
+
+    /// Returns the weight limits, if set.
+    fn weight_limits(&self) -> Option<WeightLimits>;
+
+    /// Inserts or updates an entry like [`put()`](Segment::put), whose
+    /// misses cost `cost`. Policies that don't weigh priorities by cost may
+    /// ignore it.
+    fn put_with_cost(
+        &mut self,
+        hash: u64,
+        key: Self::Key,
+        value: Self::Value,
+        size: u64,
+        cost: u64,
+    ) -> Option<Vec<(Self::Key, Self::Value)>>;
+
+    /// Ages entries under `policy`, or stops with `None`.
+    fn set_refresh_policy(&mut self, policy: Option<RefreshPolicy>);
+
+    /// Expires entries early at random under `xfetch`, or stops with
+    /// `None`.
+    fn set_xfetch(&mut self, xfetch: Option<XFetch>);
+
+    /// Stamps and ages entries with `clock`, or with the system time under
+    /// `None`.
+    fn set_clock(&mut self, clock: Option<Arc<dyn Clock>>);
+}
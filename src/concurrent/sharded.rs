@@ -0,0 +1,1310 @@
+//! Generic Sharded Cache
+//!
+//! [`ShardedCache`] turns any [`Segment`] into a thread-safe cache using lock
+//! striping: keys are partitioned across independent segments, each with its
+//! own lock, read buffer and lock statistics.
+//!
+//! ```text
+//! ┌──────────────────────────────────────────────────────────────────────┐
+//! │                        ShardedCache<C>                               │
+//! │                                                                      │
+//! │  hash(key) * φ >> 32 & (N-1)  ──▶  Segment Selection                 │
+//! │                                                                      │
+//! │  ┌──────────────┐ ┌──────────────┐     ┌──────────────┐              │
+//! │  │  Segment 0   │ │  Segment 1   │ ... │  Segment N-1 │              │
+//! │  │  ┌────────┐  │ │  ┌────────┐  │     │  ┌────────┐  │              │
+//! │  │  │ RwLock │  │ │  │ RwLock │  │     │  │ RwLock │  │              │
+//! │  │  └────┬───┘  │ │  └────┬───┘  │     │  └────┬───┘  │              │
+//! │  │       │      │ │       │      │     │       │      │              │
+//! │  │  ┌────▼───┐  │ │  ┌────▼───┐  │     │  ┌────▼───┐  │              │
+//! │  │  │   C    │  │ │  │   C    │  │     │  │   C    │  │              │
+//! │  │  └────────┘  │ │  └────────┘  │     │  └────────┘  │              │
+//! │  └──────────────┘ └──────────────┘     └──────────────┘              │
+//! └──────────────────────────────────────────────────────────────────────┘
+//! ```
+//!
+//! Each key is hashed once per operation. The segment is picked from bits
+//! 32 and up of the hash after a Fibonacci multiply, so the keys of a
+//! segment still vary in the bits its hash table uses for bucket selection,
+//! and the same hash is passed down for the lookup.
+//!
+//! The built-in concurrent caches are aliases, e.g.
+//! `ConcurrentLruCache<K, V> = ShardedCache<LruCache<K, V>>`.
+
+extern crate alloc;
+
+use super::budget::SharedBudget;
+use super::lock_stats::{LockStats, TimedWriteGuard};
+use super::read_buffer::ReadBuffer;
+use super::{
+    Busy, DefaultRawRwLock, Segment, SegmentFeatures, SegmentMetrics, SegmentSkew, TryPutResult,
+};
+use crate::config::{ConcurrentCacheConfig, SizedConfig};
+use crate::metrics::{
+    CacheMetrics, Clock, HotKey, HotKeys, MetricsSnapshot, MissRatioCurve, RollingWindow,
+};
+use crate::negative::{Lookup, NegativeCache};
+use crate::refresh::{Freshness, RefreshPolicy, XFetch};
+use crate::weight::{WeightLimits, Weights};
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::hash::{BuildHasher, Hash};
+use core::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use core::time::Duration;
+#[cfg(feature = "std")]
+use lock_api::RawRwLockTimed;
+use lock_api::{RawRwLock, RwLock};
+
+#[cfg(feature = "hashbrown")]
+use hashbrown::DefaultHashBuilder;
+
+#[cfg(not(feature = "hashbrown"))]
+use std::collections::hash_map::RandomState as DefaultHashBuilder;
+
+/// Key type of a segment.
+type Key<C> = <C as Segment>::Key;
+
+/// Value type of a segment.
+type Value<C> = <C as Segment>::Value;
+
+/// Entries evicted by a write.
+type Evicted<C> = Option<Vec<(Key<C>, Value<C>)>>;
+
+/// A read waiting to be replayed: the key's hash and the key.
+type BufferedRead<C> = (u64, Key<C>);
+
+/// One of the rankings of a hot-key tracker.
+type HotKeyRanking<C> = fn(&HotKeys<Key<C>>, usize) -> Vec<HotKey<Key<C>>>;
+
+/// A thread-safe cache made of segments of type `C`, each guarded by a
+/// reader-writer lock built on the raw lock `L`.
+///
+/// Keys are partitioned across multiple segments using hash-based sharding.
+/// Each segment has its own lock, allowing concurrent access to different
+/// segments without blocking. The segment count is rounded up to a power of
+/// two.
+///
+/// `L` defaults to [`DefaultRawRwLock`]: `parking_lot` with the `concurrent`
+/// feature, the bundled [`RawSpinRwLock`](super::RawSpinRwLock) otherwise.
+/// Any [`lock_api::RawRwLock`] can be used instead.
+///
+/// # Note on Eviction Semantics
+///
+/// The eviction policy runs **per-segment**, not globally. An item in segment
+/// A might be evicted while segment B holds items the policy would rank lower.
+/// For most workloads with good key distribution, this approximation works well.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use cache_rs::concurrent::ShardedCache;
+/// use cache_rs::LruCache;
+/// use std::sync::Arc;
+///
+/// let cache: Arc<ShardedCache<LruCache<String, i32>>> =
+///     Arc::new(ShardedCache::init(config, None));
+///
+/// // Safe to use from multiple threads
+/// cache.put("key".to_string(), 42, 1);
+/// assert_eq!(cache.get(&"key".to_string()), Some(42));
+/// ```
+pub struct ShardedCache<C: Segment, L: RawRwLock = DefaultRawRwLock> {
+    pub(super) segments: Box<[RwLock<L, C>]>,
+    read_buffers: Box<[ReadBuffer<BufferedRead<C>, L>]>,
+    pub(super) lock_stats: Box<[LockStats]>,
+    hash_builder: C::Hasher,
+    lock_contention_misses: AtomicU64,
+    /// Whether lookups that miss are sampled for a miss-ratio curve
+    sample_misses: AtomicBool,
+    /// Whether lookups that miss consult the segments' negative caches
+    check_absent: AtomicBool,
+    budget: Option<SharedBudget>,
+}
+
+impl<C: Segment<Hasher = DefaultHashBuilder>> ShardedCache<C> {
+    /// Creates a new sharded cache from a configuration with an optional hasher.
+    ///
+    /// This is the **recommended** way to create a concurrent cache. It uses
+    /// the [`DefaultRawRwLock`]; use [`with_hasher()`](Self::with_hasher) to
+    /// pick another segment lock.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - Configuration specifying the whole cache's limits, segments,
+    ///   and whether the segments share one budget
+    /// * `hasher` - Optional custom hash builder. If `None`, uses `DefaultHashBuilder`
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// use cache_rs::concurrent::ConcurrentLruCache;
+    /// use cache_rs::config::{ConcurrentLruCacheConfig, ConcurrentCacheConfig, LruCacheConfig};
+    /// use core::num::NonZeroUsize;
+    ///
+    /// let config: ConcurrentLruCacheConfig = ConcurrentCacheConfig {
+    ///     base: LruCacheConfig {
+    ///         capacity: NonZeroUsize::new(10000).unwrap(),
+    ///         max_size: 100 * 1024 * 1024,  // 100MB
+    ///         weight_limits: None,
+    ///     },
+    ///     segments: 32,
+    ///     shared_budget: false,
+    /// };
+    /// let cache: ConcurrentLruCache<String, Vec<u8>> = ConcurrentLruCache::init(config, None);
+    /// ```
+    pub fn init(
+        config: ConcurrentCacheConfig<C::Config>,
+        hasher: Option<DefaultHashBuilder>,
+    ) -> Self {
+        Self::with_hasher(config, hasher.unwrap_or_default())
+    }
+}
+
+impl<C: Segment, L: RawRwLock> ShardedCache<C, L> {
+    /// Creates a new sharded cache that routes keys with `hasher`.
+    ///
+    /// Every segment receives a clone of `hasher` for its own map. The segment
+    /// lock is taken from the type, e.g.
+    /// `ShardedCache<LruCache<K, V>, RawSpinRwLock>`.
+    pub fn with_hasher(config: ConcurrentCacheConfig<C::Config>, hasher: C::Hasher) -> Self {
+        Self::build(config, hasher, None)
+    }
+
+    /// Creates a sharded cache whose segments share a budget of `capacity`
+    /// entries and `max_size` bytes, in place of the limits of `config`.
+    ///
+    /// The limits can be changed later with [`set_budget()`](Self::set_budget).
+    pub(super) fn with_budget(
+        config: ConcurrentCacheConfig<C::Config>,
+        hasher: C::Hasher,
+        capacity: usize,
+        max_size: u64,
+    ) -> Self {
+        let config = ConcurrentCacheConfig {
+            shared_budget: true,
+            ..config
+        };
+        Self::build(config, hasher, Some((capacity, max_size)))
+    }
+
+    fn build(
+        config: ConcurrentCacheConfig<C::Config>,
+        hasher: C::Hasher,
+        limits: Option<(usize, u64)>,
+    ) -> Self {
+        let segment_count = config.segments.max(1).next_power_of_two();
+
+        let (capacity, max_size) =
+            limits.unwrap_or((config.base.capacity(), config.base.max_size()));
+
+        let segments: Vec<_> = (0..segment_count)
+            .map(|_| {
+                if !config.shared_budget {
+                    let segment_config = C::split_config(config.base, segment_count);
+                    return RwLock::new(C::init(segment_config, hasher.clone()));
+                }
+                // With a shared budget every segment may grow to the whole
+                // cache's limits, and the budget keeps the total in check, so
+                // each segment only preallocates its fair share
+                RwLock::new(C::init_shared(
+                    config.base,
+                    hasher.clone(),
+                    capacity / segment_count,
+                    segment_count,
+                ))
+            })
+            .collect();
+        let read_buffers: Vec<_> = (0..segment_count).map(|_| ReadBuffer::new()).collect();
+        let lock_stats: Vec<_> = (0..segment_count).map(|_| LockStats::new()).collect();
+
+        let budget = config
+            .shared_budget
+            .then(|| SharedBudget::new(capacity, max_size, segment_count));
+
+        Self {
+            segments: segments.into_boxed_slice(),
+            read_buffers: read_buffers.into_boxed_slice(),
+            lock_stats: lock_stats.into_boxed_slice(),
+            hash_builder: hasher,
+            lock_contention_misses: AtomicU64::new(0),
+            sample_misses: AtomicBool::new(false),
+            check_absent: AtomicBool::new(false),
+            budget,
+        }
+    }
+
+    /// Hashes `key` and returns its segment index together with the hash.
+    ///
+    /// The hash is passed on to the segment so the key is hashed only once.
+    /// Hash tables pick buckets from the low bits and tag entries with the
+    /// top 7 bits, so the segment is chosen from bits 32 and up to keep the
+    /// keys within a segment spread over its whole table. Those bits are
+    /// taken after a Fibonacci multiply, as some seeds of the default hasher
+    /// leave them nearly constant for small integer keys.
+    #[inline]
+    pub(crate) fn locate<Q>(&self, key: &Q) -> (usize, u64)
+    where
+        Key<C>: Borrow<Q>,
+        Q: ?Sized + Hash,
+    {
+        let hash = self.hash_builder.hash_one(key);
+        let mixed = hash.wrapping_mul(0x9E37_79B9_7F4A_7C15);
+        (((mixed >> 32) as usize) & (self.segments.len() - 1), hash)
+    }
+
+    /// Returns the segment index for the given key.
+    #[cfg(test)]
+    pub(super) fn segment_index<Q>(&self, key: &Q) -> usize
+    where
+        Key<C>: Borrow<Q>,
+        Q: ?Sized + Hash,
+    {
+        self.locate(key).0
+    }
+
+    /// Locks a segment exclusively after replaying its buffered reads.
+    ///
+    /// Every operation that mutates a segment goes through here so the
+    /// accesses recorded by [`get()`](Self::get) are applied first.
+    fn write_segment(&self, idx: usize) -> TimedWriteGuard<'_, L, C> {
+        let mut segment = self.lock_stats[idx].write(&self.segments[idx]);
+        self.read_buffers[idx].drain(|(hash, key)| {
+            segment.get(hash, &key);
+        });
+        segment
+    }
+
+    /// Like [`write_segment()`](Self::write_segment), but gives up instead of
+    /// waiting when the segment is locked.
+    fn try_write_segment(&self, idx: usize) -> Result<TimedWriteGuard<'_, L, C>, Busy> {
+        let mut segment = self.lock_stats[idx]
+            .try_write(&self.segments[idx])
+            .ok_or_else(|| self.contended())?;
+        self.read_buffers[idx].drain(|(hash, key)| {
+            segment.get(hash, &key);
+        });
+        Ok(segment)
+    }
+
+    /// Counts a lock-contention miss and returns the matching [`Busy`] outcome.
+    fn contended(&self) -> Busy {
+        self.lock_contention_misses.fetch_add(1, Ordering::Relaxed);
+        Busy(())
+    }
+
+    /// Records a read of `key` and drains the buffer if its stripe filled up.
+    ///
+    /// The drain is skipped when the segment is busy; the next writer will
+    /// pick up the pending reads instead.
+    fn record_read(&self, idx: usize, hash: u64, key: Key<C>) {
+        if self.read_buffers[idx].record((hash, key)) {
+            if let Some(mut segment) = self.lock_stats[idx].try_write(&self.segments[idx]) {
+                self.read_buffers[idx].drain(|(hash, key)| {
+                    segment.get(hash, &key);
+                });
+            }
+        }
+    }
+
+    /// Samples a lookup that found nothing for the miss-ratio curve, if one
+    /// is attached.
+    ///
+    /// Hits reach the curve when their buffered read is replayed. Like a
+    /// dropped read, the sample is skipped when the segment is busy.
+    fn record_missed_lookup(&self, idx: usize, hash: u64) {
+        if !self.sample_misses.load(Ordering::Relaxed) {
+            return;
+        }
+        if let Some(mut segment) = self.lock_stats[idx].try_write(&self.segments[idx]) {
+            self.read_buffers[idx].drain(|(hash, key)| {
+                segment.get(hash, &key);
+            });
+            segment.record_lookup(hash);
+        }
+    }
+
+    /// Returns `true` if the segments share one budget, so a write may evict
+    /// from segments other than its own.
+    pub(crate) fn shares_budget(&self) -> bool {
+        self.budget.is_some()
+    }
+
+    /// Publishes a segment's usage to the shared budget, if there is one.
+    ///
+    /// Called with the segment's exclusive lock held, after every write.
+    fn publish_usage(&self, idx: usize, segment: &C) {
+        if let Some(budget) = &self.budget {
+            budget.publish(idx, segment.len(), segment.current_size());
+        }
+    }
+
+    /// Evicts from the segments furthest over their fair share until the
+    /// cache fits its shared budget, returning `evicted` plus those entries.
+    ///
+    /// Must be called without holding a segment lock. With `blocking` false,
+    /// stops at the first locked segment and leaves the rest to the next write.
+    fn enforce_budget(
+        &self,
+        evicted: Option<Vec<(Key<C>, Value<C>)>>,
+        blocking: bool,
+    ) -> Option<Vec<(Key<C>, Value<C>)>> {
+        let Some(budget) = &self.budget else {
+            return evicted;
+        };
+        let mut evicted = evicted.unwrap_or_default();
+        while let Some(idx) = budget.victim() {
+            let mut segment = if blocking {
+                self.write_segment(idx)
+            } else {
+                match self.try_write_segment(idx) {
+                    Ok(segment) => segment,
+                    Err(_) => break,
+                }
+            };
+            evicted.extend(segment.pop_victim());
+            self.publish_usage(idx, &segment);
+        }
+        if evicted.is_empty() {
+            None
+        } else {
+            Some(evicted)
+        }
+    }
+
+    /// Changes the limits of the shared budget, evicting until the cache
+    /// fits them. Does nothing without a shared budget.
+    pub(super) fn set_budget(
+        &self,
+        capacity: usize,
+        max_size: u64,
+    ) -> Option<Vec<(Key<C>, Value<C>)>> {
+        let budget = self.budget.as_ref()?;
+        budget.set_limits(capacity, max_size);
+        self.enforce_budget(None, true)
+    }
+
+    /// Returns the number of entries and total size of the cache.
+    ///
+    /// With a shared budget these are the published totals, read without
+    /// taking any lock.
+    pub(super) fn usage(&self) -> (usize, u64) {
+        match &self.budget {
+            Some(budget) => budget.usage(),
+            None => (self.len(), self.current_size()),
+        }
+    }
+
+    /// Evicts one entry, chosen by the eviction policy of the segment with
+    /// the most bytes, or with the most entries if `by_size` is false.
+    ///
+    /// Returns `None` if the cache is empty or has no shared budget. Must be
+    /// called without holding a segment lock.
+    pub(super) fn evict(&self, by_size: bool) -> Option<(Key<C>, Value<C>)> {
+        let idx = self.budget.as_ref()?.largest(by_size)?;
+        let mut segment = self.write_segment(idx);
+        let evicted = segment.pop_victim();
+        self.publish_usage(idx, &segment);
+        evicted
+    }
+
+    /// Returns the total capacity across all segments.
+    pub fn capacity(&self) -> usize {
+        if let Some(budget) = &self.budget {
+            return budget.capacity();
+        }
+        self.segments.iter().map(|s| s.read().capacity()).sum()
+    }
+
+    /// Returns the number of segments in the cache.
+    pub fn segment_count(&self) -> usize {
+        self.segments.len()
+    }
+
+    /// Returns the total number of entries across all segments.
+    ///
+    /// Note: This acquires a lock on each segment sequentially, so the
+    /// returned value may be slightly stale in high-concurrency scenarios.
+    pub fn len(&self) -> usize {
+        self.segments.iter().map(|s| s.read().len()).sum()
+    }
+
+    /// Returns `true` if the cache contains no entries.
+    pub fn is_empty(&self) -> bool {
+        self.segments.iter().all(|s| s.read().is_empty())
+    }
+
+    /// Retrieves a value from the cache.
+    ///
+    /// Returns a **clone** of the value to avoid holding the lock. For operations
+    /// that don't need ownership, use [`get_with()`](Self::get_with) instead.
+    ///
+    /// The lookup only takes the segment's shared lock. The access is recorded
+    /// in the segment's read buffer and applied to the eviction policy before
+    /// the next write to that segment.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let value = cache.get(&"key".to_string());
+    /// ```
+    pub fn get<Q>(&self, key: &Q) -> Option<Value<C>>
+    where
+        Key<C>: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let (idx, hash) = self.locate(key);
+        self.get_located(idx, hash, key)
+    }
+
+    /// Retrieves a value like [`get()`](Self::get) from segment `idx`, given
+    /// the key's hash.
+    fn get_located<Q>(&self, idx: usize, hash: u64, key: &Q) -> Option<Value<C>>
+    where
+        Key<C>: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let found = {
+            let segment = self.lock_stats[idx].read(&self.segments[idx]);
+            let found = segment.peek_key_value(hash, key);
+            found.map(|(key, value)| (key.clone(), value.clone()))
+        };
+        let Some((key, value)) = found else {
+            self.record_missed_lookup(idx, hash);
+            return None;
+        };
+        self.record_read(idx, hash, key);
+        Some(value)
+    }
+
+    /// Retrieves a value and applies a function to it while holding the lock.
+    ///
+    /// More efficient than `get()` when you only need to read from the value,
+    /// as it avoids cloning. `f` runs under the segment's shared lock, so it
+    /// may run concurrently with other readers of the same segment.
+    ///
+    /// # Type Parameters
+    ///
+    /// - `F`: Function that takes `&V` and returns `R`
+    /// - `R`: Return type of the function
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// // Get length without cloning the whole string
+    /// let len = cache.get_with(&key, |value| value.len());
+    /// ```
+    pub fn get_with<Q, F, R>(&self, key: &Q, f: F) -> Option<R>
+    where
+        Key<C>: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+        F: FnOnce(&Value<C>) -> R,
+    {
+        let (idx, hash) = self.locate(key);
+        let found = {
+            let segment = self.lock_stats[idx].read(&self.segments[idx]);
+            let found = segment.peek_key_value(hash, key);
+            found.map(|(key, value)| (key.clone(), f(value)))
+        };
+        let Some((key, result)) = found else {
+            self.record_missed_lookup(idx, hash);
+            return None;
+        };
+        self.record_read(idx, hash, key);
+        Some(result)
+    }
+
+    /// Retrieves a mutable reference and applies a function to it.
+    ///
+    /// Allows in-place modification of cached values without removing them.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// // Increment a counter in-place
+    /// cache.get_mut_with(&"counter".to_string(), |value| *value += 1);
+    /// ```
+    pub fn get_mut_with<Q, F, R>(&self, key: &Q, f: F) -> Option<R>
+    where
+        Key<C>: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+        F: FnOnce(&mut Value<C>) -> R,
+    {
+        let (idx, hash) = self.locate(key);
+        let mut segment = self.write_segment(idx);
+        segment.get_mut(hash, key).map(f)
+    }
+
+    /// Inserts a key-value pair into the cache.
+    ///
+    /// If the key exists, the value is updated. If the target segment is at
+    /// capacity, its policy picks the entries to evict. Use `SIZE_UNIT` (1)
+    /// for count-based caching.
+    ///
+    /// # Returns
+    ///
+    /// - `Some(vec)` with the evicted entries if any were evicted
+    /// - `None` if inserted with available capacity
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// cache.put("key".to_string(), 42, 1);
+    /// ```
+    pub fn put(&self, key: Key<C>, value: Value<C>, size: u64) -> Option<Vec<(Key<C>, Value<C>)>> {
+        let (idx, hash) = self.locate(&key);
+        self.put_located(idx, hash, key, value, size)
+    }
+
+    /// Inserts a key-value pair like [`put()`](Self::put) into segment
+    /// `idx`, given the key's hash.
+    pub(crate) fn put_located(
+        &self,
+        idx: usize,
+        hash: u64,
+        key: Key<C>,
+        value: Value<C>,
+        size: u64,
+    ) -> Evicted<C> {
+        let evicted = {
+            let mut segment = self.write_segment(idx);
+            let evicted = segment.put(hash, key, value, size);
+            self.publish_usage(idx, &segment);
+            evicted
+        };
+        self.enforce_budget(evicted, true)
+    }
+
+    /// Removes a key from the cache.
+    ///
+    /// # Returns
+    ///
+    /// - `Some(value)` if the key existed
+    /// - `None` if the key was not found
+    pub fn remove<Q>(&self, key: &Q) -> Option<Value<C>>
+    where
+        Key<C>: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let (idx, hash) = self.locate(key);
+        let mut segment = self.write_segment(idx);
+        let removed = segment.remove(hash, key);
+        self.publish_usage(idx, &segment);
+        removed
+    }
+
+    /// Looks up `key` like [`get()`](Self::get), also returning the value's
+    /// [`Freshness`] under the refresh policy.
+    ///
+    /// Stale values and values in the grace window are returned for the
+    /// caller to refresh. Values past the grace window are removed under the
+    /// segment's exclusive lock and reported as missing.
+    pub fn get_fresh<Q>(&self, key: &Q) -> Option<(Value<C>, Freshness)>
+    where
+        Key<C>: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let (idx, hash) = self.locate(key);
+        let found = {
+            let segment = self.lock_stats[idx].read(&self.segments[idx]);
+            segment.peek_freshness(hash, key).and_then(|freshness| {
+                let (key, value) = segment.peek_key_value(hash, key)?;
+                Some((key.clone(), value.clone(), freshness))
+            })
+        };
+        match found {
+            Some((key, value, freshness)) if freshness != Freshness::Expired => {
+                self.record_read(idx, hash, key);
+                Some((value, freshness))
+            }
+            Some(_) => {
+                {
+                    let mut segment = self.write_segment(idx);
+                    // A concurrent put may have replaced the expired value
+                    if segment.peek_freshness(hash, key) == Some(Freshness::Expired) {
+                        segment.remove(hash, key);
+                        self.publish_usage(idx, &segment);
+                    }
+                }
+                self.record_missed_lookup(idx, hash);
+                None
+            }
+            None => {
+                self.record_missed_lookup(idx, hash);
+                None
+            }
+        }
+    }
+
+    /// Atomically inserts, updates or removes the entry for `key`.
+    ///
+    /// `f` receives the current value (if any) and returns the new value with
+    /// its size, or `None` to remove the entry. The lookup and the write happen
+    /// under a single segment lock, so concurrent `compute` calls on the same
+    /// key never lose updates. Size accounting follows [`put()`](Self::put).
+    ///
+    /// `f` runs while the segment lock is held and must not access the cache.
+    ///
+    /// # Returns
+    ///
+    /// The value stored after the call, or `None` if the entry was removed,
+    /// and the entries evicted to make room for it, as [`put()`](Self::put)
+    /// returns them.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// // Count requests per client
+    /// let (count, _) = cache.compute(client_id, |count| Some((count.map_or(1, |c| c + 1), 1)));
+    /// ```
+    pub fn compute<F>(&self, key: Key<C>, f: F) -> (Option<Value<C>>, Evicted<C>)
+    where
+        F: FnOnce(Option<&Value<C>>) -> Option<(Value<C>, u64)>,
+    {
+        let (idx, hash) = self.locate(&key);
+        let mut segment = self.write_segment(idx);
+        let (stored, evicted) = match f(segment.peek(hash, &key)) {
+            Some((value, size)) => {
+                let stored = value.clone();
+                (Some(stored), segment.put(hash, key, value, size))
+            }
+            None => {
+                segment.remove(hash, &key);
+                (None, None)
+            }
+        };
+        self.publish_usage(idx, &segment);
+        drop(segment);
+        (stored, self.enforce_budget(evicted, true))
+    }
+
+    /// Inserts a key-value pair only if the key is not already cached.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(evicted)` if the pair was inserted, with the entries evicted as
+    ///   [`put()`](Self::put) returns them
+    /// - `Err(existing)` if the key was present; the cache is left unchanged
+    pub fn put_if_absent(
+        &self,
+        key: Key<C>,
+        value: Value<C>,
+        size: u64,
+    ) -> Result<Evicted<C>, Value<C>> {
+        let (idx, hash) = self.locate(&key);
+        let mut segment = self.write_segment(idx);
+        if let Some(existing) = segment.peek(hash, &key) {
+            return Err(existing.clone());
+        }
+        let evicted = segment.put(hash, key, value, size);
+        self.publish_usage(idx, &segment);
+        drop(segment);
+        Ok(self.enforce_budget(evicted, true))
+    }
+
+    /// Replaces the value for `key` if it is cached and `pred` accepts it.
+    ///
+    /// The check and the replacement happen under a single segment lock,
+    /// which makes this a compare-and-swap. The entry's size is updated to
+    /// `size`.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(evicted)` if the value was replaced, with the entries evicted as
+    ///   [`put()`](Self::put) returns them
+    /// - `Err(value)` handing `value` back if the key was not cached or
+    ///   `pred` rejected it
+    pub fn replace_if<Q, P>(
+        &self,
+        key: &Q,
+        pred: P,
+        value: Value<C>,
+        size: u64,
+    ) -> Result<Evicted<C>, Value<C>>
+    where
+        Key<C>: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+        P: FnOnce(&Value<C>) -> bool,
+    {
+        let (idx, hash) = self.locate(key);
+        let mut segment = self.write_segment(idx);
+        let key = match segment.peek_key_value(hash, key) {
+            Some((key, current)) if pred(current) => key.clone(),
+            _ => return Err(value),
+        };
+        let evicted = segment.put(hash, key, value, size);
+        self.publish_usage(idx, &segment);
+        drop(segment);
+        Ok(self.enforce_budget(evicted, true))
+    }
+
+    /// Removes the entry for `key` if it is cached and `pred` accepts it.
+    ///
+    /// # Returns
+    ///
+    /// - `Some(value)` if the entry was removed
+    /// - `None` if the key was not found or `pred` rejected it
+    pub fn remove_if<Q, P>(&self, key: &Q, pred: P) -> Option<Value<C>>
+    where
+        Key<C>: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+        P: FnOnce(&Value<C>) -> bool,
+    {
+        let (idx, hash) = self.locate(key);
+        let mut segment = self.write_segment(idx);
+        if !segment.peek(hash, key).is_some_and(pred) {
+            return None;
+        }
+        let removed = segment.remove(hash, key);
+        self.publish_usage(idx, &segment);
+        removed
+    }
+
+    /// Retrieves a value without blocking.
+    ///
+    /// Behaves like [`get()`](Self::get), but returns [`Busy`] instead of
+    /// waiting when the key's segment is locked by a writer. Latency-critical
+    /// callers can treat `Busy` as a miss.
+    pub fn try_get<Q>(&self, key: &Q) -> Result<Option<Value<C>>, Busy>
+    where
+        Key<C>: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let (idx, hash) = self.locate(key);
+        let found = {
+            let segment = self.segments[idx]
+                .try_read()
+                .ok_or_else(|| self.contended())?;
+            let found = segment.peek_key_value(hash, key);
+            found.map(|(key, value)| (key.clone(), value.clone()))
+        };
+        let Some((key, value)) = found else {
+            self.record_missed_lookup(idx, hash);
+            return Ok(None);
+        };
+        self.record_read(idx, hash, key);
+        Ok(Some(value))
+    }
+
+    /// Inserts a key-value pair without blocking.
+    ///
+    /// Behaves like [`put()`](Self::put), but returns the key and value inside
+    /// [`Busy`] instead of waiting when the key's segment is locked.
+    pub fn try_put(
+        &self,
+        key: Key<C>,
+        value: Value<C>,
+        size: u64,
+    ) -> TryPutResult<Key<C>, Value<C>> {
+        let (idx, hash) = self.locate(&key);
+        let evicted = match self.try_write_segment(idx) {
+            Ok(mut segment) => {
+                let evicted = segment.put(hash, key, value, size);
+                self.publish_usage(idx, &segment);
+                evicted
+            }
+            Err(_) => return Err(Busy((key, value))),
+        };
+        Ok(self.enforce_budget(evicted, false))
+    }
+
+    /// Removes a key without blocking.
+    ///
+    /// Behaves like [`remove()`](Self::remove), but returns [`Busy`] instead of
+    /// waiting when the key's segment is locked.
+    pub fn try_remove<Q>(&self, key: &Q) -> Result<Option<Value<C>>, Busy>
+    where
+        Key<C>: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let (idx, hash) = self.locate(key);
+        let mut segment = self.try_write_segment(idx)?;
+        let removed = segment.remove(hash, key);
+        self.publish_usage(idx, &segment);
+        Ok(removed)
+    }
+
+    /// Removes all entries from all segments.
+    ///
+    /// Acquires locks on each segment sequentially. Pending buffered reads
+    /// are discarded rather than replayed.
+    pub fn clear(&self) {
+        let segments = self.segments.iter().zip(self.read_buffers.iter());
+        for (idx, (segment, buffer)) in segments.enumerate() {
+            let mut segment = self.lock_stats[idx].write(segment);
+            buffer.clear();
+            segment.clear();
+            self.publish_usage(idx, &segment);
+        }
+    }
+
+    /// Returns the current total size across all segments.
+    ///
+    /// This is the sum of all `size` values from `put()` calls.
+    pub fn current_size(&self) -> u64 {
+        self.segments.iter().map(|s| s.read().current_size()).sum()
+    }
+
+    /// Returns the maximum total content size across all segments.
+    pub fn max_size(&self) -> u64 {
+        if let Some(budget) = &self.budget {
+            return budget.max_size();
+        }
+        self.segments.iter().map(|s| s.read().max_size()).sum()
+    }
+
+    /// Returns the metrics snapshot of segment `idx`, whose buffered reads
+    /// were replayed, counting the hits whose access its read buffer dropped.
+    ///
+    /// Dropped accesses never reach the segment, so the segment itself
+    /// counts neither the request nor the hit. Their sizes are unknown, so
+    /// the byte counters still leave them out.
+    fn segment_snapshot(&self, idx: usize, segment: &C) -> MetricsSnapshot {
+        let mut snapshot = segment.snapshot();
+        if let Some(core) = snapshot.core_mut() {
+            let dropped = self.read_buffers[idx].dropped();
+            core.requests += dropped;
+            core.cache_hits += dropped;
+        }
+        snapshot
+    }
+
+    /// Returns a snapshot of every segment, in segment order.
+    ///
+    /// Unlike [`metrics()`](CacheMetrics::metrics), which merges all segments,
+    /// this shows hot or contended segments. Buffered reads are replayed first
+    /// so hit counts are up to date, and hits whose access was dropped by a
+    /// read buffer are counted too.
+    pub fn segment_metrics(&self) -> Vec<SegmentMetrics> {
+        (0..self.segments.len())
+            .map(|idx| {
+                let segment = self.write_segment(idx);
+                SegmentMetrics {
+                    len: segment.len(),
+                    size: segment.current_size(),
+                    core: self.segment_snapshot(idx, &segment).core().clone(),
+                    read_buffer_drops: self.read_buffers[idx].dropped(),
+                    lock: self.lock_stats[idx].snapshot(),
+                }
+            })
+            .collect()
+    }
+
+    /// Summarizes how unevenly requests, entries, evictions and lock waits
+    /// are spread across segments.
+    pub fn segment_skew(&self) -> SegmentSkew {
+        SegmentSkew::from_segments(&self.segment_metrics())
+    }
+
+    /// Records a cache miss for metrics tracking.
+    ///
+    /// Call this after a failed `get()` when you fetch from the origin.
+    pub fn record_miss(&self, object_size: u64) {
+        // Record on the first segment (metrics are aggregated anyway)
+        if !self.segments.is_empty() {
+            self.write_segment(0).record_miss(object_size);
+        }
+    }
+
+    /// Checks if the cache contains a key without recording an access.
+    ///
+    /// This is a pure existence check that does **not** update the entry's
+    /// recency, frequency or priority.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// if cache.contains(&"key".to_string()) {
+    ///     println!("Key exists!");
+    /// }
+    /// ```
+    pub fn contains<Q>(&self, key: &Q) -> bool
+    where
+        Key<C>: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let (idx, hash) = self.locate(key);
+        let segment = self.lock_stats[idx].read(&self.segments[idx]);
+        segment.contains(hash, key)
+    }
+
+    /// Returns a clone of the value without recording an access.
+    ///
+    /// Unlike [`get()`](Self::get), this does NOT update any access metadata.
+    /// Returns a cloned value because the internal lock cannot be held across
+    /// the return boundary.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let value = cache.peek(&"key".to_string());
+    /// ```
+    pub fn peek<Q>(&self, key: &Q) -> Option<Value<C>>
+    where
+        Key<C>: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let (idx, hash) = self.locate(key);
+        let segment = self.lock_stats[idx].read(&self.segments[idx]);
+        segment.peek(hash, key).cloned()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<C, L> ShardedCache<C, L>
+where
+    C: Segment,
+    L: RawRwLockTimed<Duration = Duration>,
+{
+    /// Retrieves a value, waiting at most `timeout` for the segment lock.
+    ///
+    /// Returns [`Busy`] if the key's segment stayed locked for the whole timeout.
+    /// Requires a lock that supports timeouts, such as `parking_lot`'s.
+    pub fn get_timeout<Q>(&self, key: &Q, timeout: Duration) -> Result<Option<Value<C>>, Busy>
+    where
+        Key<C>: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let (idx, hash) = self.locate(key);
+        let found = {
+            let segment = self.segments[idx]
+                .try_read_for(timeout)
+                .ok_or_else(|| self.contended())?;
+            let found = segment.peek_key_value(hash, key);
+            found.map(|(key, value)| (key.clone(), value.clone()))
+        };
+        let Some((key, value)) = found else {
+            self.record_missed_lookup(idx, hash);
+            return Ok(None);
+        };
+        self.record_read(idx, hash, key);
+        Ok(Some(value))
+    }
+}
+
+impl<C: SegmentFeatures, L: RawRwLock> ShardedCache<C, L> {
+    /// Inserts a key-value pair like [`put()`](Self::put), tagging it with
+    /// `tags` in place of any previous tags.
+    ///
+    /// See [`invalidate_tag()`](Self::invalidate_tag).
+    pub fn put_tagged(
+        &self,
+        key: Key<C>,
+        value: Value<C>,
+        size: u64,
+        tags: &[u64],
+    ) -> Option<Vec<(Key<C>, Value<C>)>> {
+        let (idx, hash) = self.locate(&key);
+        let evicted = {
+            let mut segment = self.write_segment(idx);
+            let evicted = segment.put_tagged(hash, key, value, size, tags);
+            self.publish_usage(idx, &segment);
+            evicted
+        };
+        self.enforce_budget(evicted, true)
+    }
+
+    /// Inserts a key-value pair like [`put()`](Self::put), whose misses cost
+    /// `cost`.
+    ///
+    /// GDSF and LFUDA segments weigh the entry's priority by its cost; the
+    /// others ignore it.
+    pub fn put_with_cost(
+        &self,
+        key: Key<C>,
+        value: Value<C>,
+        size: u64,
+        cost: u64,
+    ) -> Option<Vec<(Key<C>, Value<C>)>> {
+        let (idx, hash) = self.locate(&key);
+        let evicted = {
+            let mut segment = self.write_segment(idx);
+            let evicted = segment.put_with_cost(hash, key, value, size, cost);
+            self.publish_usage(idx, &segment);
+            evicted
+        };
+        self.enforce_budget(evicted, true)
+    }
+
+    /// Inserts a key-value pair like [`put()`](Self::put), weighing it with
+    /// `weights`.
+    ///
+    /// Each segment enforces its share of the
+    /// [weight limits](Self::set_weight_limits), evicting its own entries
+    /// until every dimension fits.
+    pub fn put_weighted(
+        &self,
+        key: Key<C>,
+        value: Value<C>,
+        size: u64,
+        weights: Weights,
+    ) -> Option<Vec<(Key<C>, Value<C>)>> {
+        let (idx, hash) = self.locate(&key);
+        let evicted = {
+            let mut segment = self.write_segment(idx);
+            let evicted = segment.put_weighted(hash, key, value, size, weights);
+            self.publish_usage(idx, &segment);
+            evicted
+        };
+        self.enforce_budget(evicted, true)
+    }
+
+    /// Removes every entry tagged with `tag` from all segments, returning
+    /// how many were removed.
+    ///
+    /// Locks one segment at a time, so entries tagged concurrently may
+    /// survive in segments already visited.
+    pub fn invalidate_tag(&self, tag: u64) -> usize {
+        (0..self.segments.len())
+            .map(|idx| {
+                let mut segment = self.write_segment(idx);
+                let removed = segment.invalidate_tag(tag);
+                if removed > 0 {
+                    self.publish_usage(idx, &segment);
+                }
+                removed
+            })
+            .sum()
+    }
+
+    /// Records that `key` is absent from the backing store for the next
+    /// `ttl`, removing any cached value for it.
+    ///
+    /// The entry counts against the segment's negative cache, not the
+    /// cache's capacity, and is dropped by the next [`put()`](Self::put) of
+    /// the key. Does nothing without a negative cache.
+    pub fn put_absent(&self, key: Key<C>, ttl: Duration) {
+        let (idx, hash) = self.locate(&key);
+        let mut segment = self.write_segment(idx);
+        segment.put_absent(hash, key, ttl);
+        self.publish_usage(idx, &segment);
+    }
+
+    /// Looks up `key` like [`get()`](Self::get), but tells a key known to be
+    /// absent apart from one that is not cached.
+    ///
+    /// Hits only take the segment's shared lock. With a negative cache,
+    /// misses check it under the exclusive lock, counting a negative hit or
+    /// miss.
+    pub fn lookup<Q>(&self, key: &Q) -> Lookup<Value<C>>
+    where
+        Key<C>: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let (idx, hash) = self.locate(key);
+        if let Some(value) = self.get_located(idx, hash, key) {
+            return Lookup::Hit(value);
+        }
+        if !self.check_absent.load(Ordering::Relaxed) {
+            return Lookup::Miss;
+        }
+        if self.write_segment(idx).check_absent(hash, key) {
+            Lookup::Absent
+        } else {
+            Lookup::Miss
+        }
+    }
+
+    /// Tracks the metrics of the most recent requests in `window`, or stops
+    /// with `None`.
+    ///
+    /// Every segment keeps its own window, with a request window split evenly
+    /// across segments. [`metrics()`](CacheMetrics::metrics) reports the
+    /// combined windows as `window_*` keys.
+    pub fn set_metrics_window(&self, window: Option<RollingWindow>) {
+        let segments = self.segments.len();
+        for idx in 0..segments {
+            let window = window.as_ref().map(|w| w.for_segment(segments));
+            self.write_segment(idx).set_metrics_window(window);
+        }
+    }
+
+    /// Estimates the miss ratio an LRU cache would have at other capacities
+    /// with `curve`, or stops with `None`.
+    ///
+    /// Every segment samples its own keys with an even share of the curve's
+    /// capacity and samples, and [`metrics()`](CacheMetrics::metrics) reports
+    /// the combined curve as `mrc_*` keys. Lookups that miss are sampled
+    /// under the segment's exclusive lock, and skipped when it is busy.
+    pub fn set_miss_ratio_curve(&self, curve: Option<MissRatioCurve>) {
+        let segments = self.segments.len();
+        for idx in 0..segments {
+            let curve = curve.as_ref().map(|c| c.for_segment(segments));
+            self.write_segment(idx).set_miss_ratio_curve(curve);
+        }
+        self.sample_misses.store(curve.is_some(), Ordering::Relaxed);
+    }
+
+    /// Ranks the keys of hits by accesses and by bytes served with
+    /// `hot_keys`, or stops with `None`.
+    ///
+    /// Every segment gets its own tracker with as many counters, and a
+    /// request window split evenly across segments. Hits are ranked when
+    /// their buffered read is replayed.
+    pub fn set_hot_keys(&self, hot_keys: Option<HotKeys<Key<C>>>) {
+        let segments = self.segments.len();
+        for idx in 0..segments {
+            let hot_keys = hot_keys.as_ref().map(|h| h.for_segment(segments));
+            self.write_segment(idx).set_hot_keys(hot_keys);
+        }
+    }
+
+    /// Remembers keys known to be absent from the backing store in
+    /// `negative`, or stops with `None`.
+    ///
+    /// Every segment gets its own negative cache with an even share of the
+    /// capacity. Mark keys absent with [`put_absent()`](Self::put_absent)
+    /// and tell them apart with [`lookup()`](Self::lookup).
+    pub fn set_negative_cache(&self, negative: Option<NegativeCache<Key<C>>>) {
+        let segments = self.segments.len();
+        for idx in 0..segments {
+            let negative = negative.as_ref().map(|n| n.for_segment(segments));
+            self.write_segment(idx).set_negative_cache(negative);
+        }
+        self.check_absent
+            .store(negative.is_some(), Ordering::Relaxed);
+    }
+
+    /// Weighs entries in the dimensions of `limits`, replacing any previous
+    /// limits, or stops with `None`.
+    ///
+    /// The limits are divided evenly among the segments, like the capacity.
+    /// Insert weighted entries with [`put_weighted()`](Self::put_weighted).
+    pub fn set_weight_limits(&self, limits: Option<WeightLimits>) {
+        let segments = self.segments.len();
+        for idx in 0..segments {
+            let limits = limits.map(|l| l.for_segment(segments));
+            self.write_segment(idx).set_weight_limits(limits);
+        }
+    }
+
+    /// Ages every segment's entries under `policy`, replacing any previous
+    /// policy, or stops with `None`.
+    ///
+    /// The policy only affects [`get_fresh()`](Self::get_fresh); other
+    /// lookups serve entries regardless of their age.
+    pub fn set_refresh_policy(&self, policy: Option<RefreshPolicy>) {
+        for idx in 0..self.segments.len() {
+            self.write_segment(idx).set_refresh_policy(policy);
+        }
+    }
+
+    /// Expires values early at random under `xfetch` in every segment,
+    /// replacing any previous mode, or stops with `None`.
+    ///
+    /// Only applies while a refresh policy is set; see
+    /// [`get_fresh()`](Self::get_fresh). The segments share `xfetch`'s
+    /// random source.
+    pub fn set_xfetch(&self, xfetch: Option<XFetch>) {
+        for idx in 0..self.segments.len() {
+            self.write_segment(idx).set_xfetch(xfetch.clone());
+        }
+    }
+
+    /// Stamps and ages every segment's entries with `clock`, or with the
+    /// system time under `None`, the default.
+    ///
+    /// Entries keep the timestamps they were given, so set the clock before
+    /// inserting any. Without the `std` feature there is no system time, and
+    /// entries only age under a clock.
+    pub fn set_clock(&self, clock: Option<Arc<dyn Clock>>) {
+        for idx in 0..self.segments.len() {
+            self.write_segment(idx).set_clock(clock.clone());
+        }
+    }
+
+    /// Returns the number of keys held by the segments' negative caches.
+    pub fn negative_len(&self) -> usize {
+        (0..self.segments.len())
+            .map(|idx| {
+                let segment = self.lock_stats[idx].read(&self.segments[idx]);
+                segment.negative_cache().map_or(0, NegativeCache::len)
+            })
+            .sum()
+    }
+
+    /// Returns up to `k` keys with the most accesses across all segments,
+    /// most accessed first, or nothing without a hot-key tracker.
+    pub fn hot_keys_by_accesses(&self, k: usize) -> Vec<HotKey<Key<C>>> {
+        self.top_hot_keys(k, HotKeys::top_by_accesses)
+    }
+
+    /// Returns up to `k` keys that served the most bytes across all
+    /// segments, largest first, or nothing without a hot-key tracker.
+    pub fn hot_keys_by_bytes(&self, k: usize) -> Vec<HotKey<Key<C>>> {
+        self.top_hot_keys(k, HotKeys::top_by_bytes)
+    }
+
+    /// Combines the top `k` of every segment. Each key lives in one segment,
+    /// so the segments' rankings never overlap.
+    fn top_hot_keys(&self, k: usize, top: HotKeyRanking<C>) -> Vec<HotKey<Key<C>>> {
+        let mut hot_keys = Vec::new();
+        for idx in 0..self.segments.len() {
+            if let Some(segment_hot_keys) = self.write_segment(idx).hot_keys() {
+                hot_keys.extend(top(segment_hot_keys, k));
+            }
+        }
+        hot_keys.sort_by(|a, b| {
+            b.count
+                .cmp(&a.count)
+                .then(b.guaranteed().cmp(&a.guaranteed()))
+        });
+        hot_keys.truncate(k);
+        hot_keys
+    }
+
+    /// Zeroes the metrics counters of every segment, keeping their gauges,
+    /// along with the read buffer, lock contention and lock statistics
+    /// counters.
+    pub fn reset_metrics(&self) {
+        for idx in 0..self.segments.len() {
+            self.write_segment(idx).reset_metrics();
+            self.read_buffers[idx].reset_dropped();
+            self.lock_stats[idx].reset();
+        }
+        self.lock_contention_misses.store(0, Ordering::Relaxed);
+    }
+}
+
+impl<C: Segment, L: RawRwLock> CacheMetrics for ShardedCache<C, L> {
+    fn metrics(&self) -> BTreeMap<String, f64> {
+        // Rates are derived from the merged counters rather than summed
+        let mut aggregated = self.snapshot().to_btreemap();
+        let read_buffer_drops: u64 = self.read_buffers.iter().map(|b| b.dropped()).sum();
+
+        aggregated.insert("read_buffer_drops".to_string(), read_buffer_drops as f64);
+        aggregated.insert(
+            "lock_contention_misses".to_string(),
+            self.lock_contention_misses.load(Ordering::Relaxed) as f64,
+        );
+        aggregated
+    }
+
+    fn snapshot(&self) -> MetricsSnapshot {
+        // Merge the segments' snapshots, replaying buffered reads first so
+        // hit counts are up to date
+        let mut merged = MetricsSnapshot::Disabled;
+        for idx in 0..self.segments.len() {
+            let segment = self.write_segment(idx);
+            merged.merge(&self.segment_snapshot(idx, &segment));
+        }
+        merged
+    }
+
+    fn algorithm_name(&self) -> &'static str {
+        C::CONCURRENT_NAME
+    }
+}
+
+impl<C: Segment, L: RawRwLock> core::fmt::Debug for ShardedCache<C, L> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ShardedCache")
+            .field("algorithm", &C::CONCURRENT_NAME)
+            .field("segment_count", &self.segments.len())
+            .field("total_len", &self.len())
+            .finish()
+    }
+}
@@ -21,7 +21,7 @@
 //! │  ┌────────────────────┐ ┌────────────────────┐     ┌────────────────────┐    │
 //! │  │     Shard 0        │ │     Shard 1        │ ... │    Shard N-1       │    │
 //! │  │  ┌──────────────┐  │ │  ┌──────────────┐  │     │  ┌──────────────┐  │    │
-//! │  │  │    Mutex     │  │ │  │    Mutex     │  │     │  │    Mutex     │  │    │
+//! │  │  │    RwLock    │  │ │  │    RwLock    │  │     │  │    RwLock    │  │    │
 //! │  │  └──────┬───────┘  │ │  └──────┬───────┘  │     │  └──────┬───────┘  │    │
 //! │  │         │          │ │         │          │     │         │          │    │
 //! │  │  ┌──────▼───────┐  │ │  ┌──────▼───────┐  │     │  ┌──────▼───────┐  │    │
@@ -53,7 +53,7 @@
 //! |--------|-------|
 //! | Get/Put/Remove | O(1) average |
 //! | Concurrency | Near-linear scaling up to shard count |
-//! | Memory overhead | ~140 bytes per entry + one Mutex per shard |
+//! | Memory overhead | ~140 bytes per entry + one RwLock and read buffer per shard |
 //! | Scan resistance | Good (two-tier protection) |
 //!
 //! # When to Use
@@ -89,8 +89,10 @@
 //!         capacity: NonZeroUsize::new(10_000).unwrap(),
 //!         protected_capacity: NonZeroUsize::new(2_000).unwrap(),
 //!         max_size: u64::MAX,
+//!         weight_limits: None,
 //!     },
 //!     segments: 16,
+//!     shared_budget: false,
 //! };
 //! let cache = Arc::new(ConcurrentSlruCache::init(config, None));
 //!
@@ -123,18 +125,8 @@
 //! }
 //! ```
 
-extern crate alloc;
-
-use crate::metrics::CacheMetrics;
-use crate::slru::SlruInner;
-use alloc::boxed::Box;
-use alloc::collections::BTreeMap;
-use alloc::string::String;
-use alloc::vec::Vec;
-use core::borrow::Borrow;
-use core::hash::{BuildHasher, Hash};
-use core::num::NonZeroUsize;
-use parking_lot::Mutex;
+use super::{DefaultRawRwLock, ShardedCache};
+use crate::SlruCache;
 
 #[cfg(feature = "hashbrown")]
 use hashbrown::DefaultHashBuilder;
@@ -143,251 +135,19 @@ use hashbrown::DefaultHashBuilder;
 use std::collections::hash_map::RandomState as DefaultHashBuilder;
 
 /// A thread-safe SLRU cache with segmented storage for high concurrency.
-pub struct ConcurrentSlruCache<K, V, S = DefaultHashBuilder> {
-    segments: Box<[Mutex<SlruInner<K, V, S>>]>,
-    hash_builder: S,
-}
-
-impl<K, V> ConcurrentSlruCache<K, V, DefaultHashBuilder>
-where
-    K: Hash + Eq + Clone + Send,
-    V: Clone + Send,
-{
-    /// Creates a new concurrent SLRU cache from a configuration.
-    ///
-    /// This is the **recommended** way to create a concurrent SLRU cache.
-    ///
-    /// # Arguments
-    /// * `config` - The cache configuration
-    /// * `hasher` - Optional custom hasher. If `None`, uses the default hasher.
-    pub fn init(
-        config: crate::config::ConcurrentSlruCacheConfig,
-        hasher: Option<DefaultHashBuilder>,
-    ) -> Self {
-        let segment_count = config.segments;
-        let capacity = config.base.capacity;
-        let protected_capacity = config.base.protected_capacity;
-        let max_size = config.base.max_size;
-
-        let hash_builder = hasher.unwrap_or_default();
-
-        let segment_capacity = capacity.get() / segment_count;
-        let segment_protected = protected_capacity.get() / segment_count;
-        let segment_cap = NonZeroUsize::new(segment_capacity.max(1)).unwrap();
-        let segment_protected_cap = NonZeroUsize::new(segment_protected.max(1)).unwrap();
-        let segment_max_size = max_size / segment_count as u64;
-
-        let segments: Vec<_> = (0..segment_count)
-            .map(|_| {
-                let segment_config = crate::config::SlruCacheConfig {
-                    capacity: segment_cap,
-                    protected_capacity: segment_protected_cap,
-                    max_size: segment_max_size,
-                };
-                Mutex::new(SlruInner::init(segment_config, hash_builder.clone()))
-            })
-            .collect();
-
-        Self {
-            segments: segments.into_boxed_slice(),
-            hash_builder,
-        }
-    }
-}
-
-impl<K, V, S> ConcurrentSlruCache<K, V, S>
-where
-    K: Hash + Eq + Clone + Send,
-    V: Clone + Send,
-    S: BuildHasher + Clone + Send,
-{
-    #[inline]
-    fn segment_index<Q>(&self, key: &Q) -> usize
-    where
-        K: Borrow<Q>,
-        Q: ?Sized + Hash,
-    {
-        (self.hash_builder.hash_one(key) as usize) % self.segments.len()
-    }
-
-    /// Returns the total capacity across all segments.
-    pub fn capacity(&self) -> usize {
-        self.segments.iter().map(|s| s.lock().cap().get()).sum()
-    }
-
-    /// Returns the number of segments in the cache.
-    pub fn segment_count(&self) -> usize {
-        self.segments.len()
-    }
-
-    /// Returns the total number of entries across all segments.
-    pub fn len(&self) -> usize {
-        self.segments.iter().map(|s| s.lock().len()).sum()
-    }
-
-    /// Returns `true` if the cache contains no entries.
-    pub fn is_empty(&self) -> bool {
-        self.segments.iter().all(|s| s.lock().is_empty())
-    }
-
-    /// Gets a value from the cache.
-    ///
-    /// This clones the value to avoid holding the lock. For zero-copy access,
-    /// use `get_with()` instead.
-    pub fn get<Q>(&self, key: &Q) -> Option<V>
-    where
-        K: Borrow<Q>,
-        Q: ?Sized + Hash + Eq,
-    {
-        let idx = self.segment_index(key);
-        let mut segment = self.segments[idx].lock();
-        segment.get(key).cloned()
-    }
-
-    /// Gets a value and applies a function to it while holding the lock.
-    ///
-    /// This is more efficient than `get()` when you only need to read from the value,
-    /// as it avoids cloning.
-    pub fn get_with<Q, F, R>(&self, key: &Q, f: F) -> Option<R>
-    where
-        K: Borrow<Q>,
-        Q: ?Sized + Hash + Eq,
-        F: FnOnce(&V) -> R,
-    {
-        let idx = self.segment_index(key);
-        let mut segment = self.segments[idx].lock();
-        segment.get(key).map(f)
-    }
-
-    /// Inserts a key-value pair into the cache with optional size tracking.
-    ///
-    /// New items enter the probationary segment and are promoted to the protected
-    /// segment on subsequent access. Use `SIZE_UNIT` (1) for count-based caching.
-    pub fn put(&self, key: K, value: V, size: u64) -> Option<Vec<(K, V)>> {
-        let idx = self.segment_index(&key);
-        let mut segment = self.segments[idx].lock();
-        segment.put(key, value, size)
-    }
-
-    /// Removes a key from the cache, returning the value if it existed.
-    pub fn remove<Q>(&self, key: &Q) -> Option<V>
-    where
-        K: Borrow<Q>,
-        Q: ?Sized + Hash + Eq,
-    {
-        let idx = self.segment_index(key);
-        let mut segment = self.segments[idx].lock();
-        segment.remove(key)
-    }
-
-    /// Clears all entries from the cache.
-    pub fn clear(&self) {
-        for segment in self.segments.iter() {
-            segment.lock().clear();
-        }
-    }
-
-    /// Returns the current total size of cached content across all segments.
-    pub fn current_size(&self) -> u64 {
-        self.segments.iter().map(|s| s.lock().current_size()).sum()
-    }
-
-    /// Returns the maximum content size the cache can hold across all segments.
-    pub fn max_size(&self) -> u64 {
-        self.segments.iter().map(|s| s.lock().max_size()).sum()
-    }
-
-    /// Checks if the cache contains a key without promoting it.
-    ///
-    /// This is a pure existence check that does **not** update the entry's recency
-    /// or promote it between segments.
-    ///
-    /// # Example
-    ///
-    /// ```rust,ignore
-    /// if cache.contains(&"key".to_string()) {
-    ///     println!("Key exists!");
-    /// }
-    /// ```
-    pub fn contains<Q>(&self, key: &Q) -> bool
-    where
-        K: Borrow<Q>,
-        Q: ?Sized + Hash + Eq,
-    {
-        let idx = self.segment_index(key);
-        let segment = self.segments[idx].lock();
-        segment.contains(key)
-    }
-
-    /// Returns a clone of the value without promoting or updating access metadata.
-    ///
-    /// Unlike [`get()`](Self::get), this does NOT promote the entry from
-    /// probationary to protected. Returns a cloned value because the internal
-    /// lock cannot be held across the return boundary.
-    ///
-    /// # Example
-    ///
-    /// ```rust,ignore
-    /// let value = cache.peek(&"key".to_string());
-    /// ```
-    pub fn peek<Q>(&self, key: &Q) -> Option<V>
-    where
-        K: Borrow<Q>,
-        Q: ?Sized + Hash + Eq,
-        V: Clone,
-    {
-        let idx = self.segment_index(key);
-        let segment = self.segments[idx].lock();
-        segment.peek(key).cloned()
-    }
-}
-
-impl<K, V, S> CacheMetrics for ConcurrentSlruCache<K, V, S>
-where
-    K: Hash + Eq + Clone + Send,
-    V: Clone + Send,
-    S: BuildHasher + Clone + Send,
-{
-    fn metrics(&self) -> BTreeMap<String, f64> {
-        let mut aggregated = BTreeMap::new();
-        for segment in self.segments.iter() {
-            let segment_metrics = segment.lock().metrics().metrics();
-            for (key, value) in segment_metrics {
-                *aggregated.entry(key).or_insert(0.0) += value;
-            }
-        }
-        aggregated
-    }
-
-    fn algorithm_name(&self) -> &'static str {
-        "ConcurrentSLRU"
-    }
-}
-
-unsafe impl<K: Send, V: Send, S: Send> Send for ConcurrentSlruCache<K, V, S> {}
-unsafe impl<K: Send, V: Send, S: Send + Sync> Sync for ConcurrentSlruCache<K, V, S> {}
-
-impl<K, V, S> core::fmt::Debug for ConcurrentSlruCache<K, V, S>
-where
-    K: Hash + Eq + Clone + Send,
-    V: Clone + Send,
-    S: BuildHasher + Clone + Send,
-{
-    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        f.debug_struct("ConcurrentSlruCache")
-            .field("segment_count", &self.segments.len())
-            .field("total_len", &self.len())
-            .finish()
-    }
-}
+pub type ConcurrentSlruCache<K, V, S = DefaultHashBuilder, L = DefaultRawRwLock> =
+    ShardedCache<SlruCache<K, V, S>, L>;
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::concurrent::Busy;
     use crate::config::{ConcurrentCacheConfig, ConcurrentSlruCacheConfig, SlruCacheConfig};
+    use crate::metrics::CacheMetrics;
+    use core::num::NonZeroUsize;
 
     extern crate std;
-    use std::string::ToString;
+    use std::string::{String, ToString};
     use std::sync::Arc;
     use std::thread;
     use std::vec::Vec;
@@ -402,8 +162,10 @@ mod tests {
                 capacity: NonZeroUsize::new(capacity).unwrap(),
                 protected_capacity: NonZeroUsize::new(protected).unwrap(),
                 max_size: u64::MAX,
+                weight_limits: None,
             },
             segments,
+            shared_budget: false,
         }
     }
 
@@ -629,4 +391,25 @@ mod tests {
         assert!(cache.contains(&"b".to_string()));
         assert!(!cache.contains(&"c".to_string()));
     }
+
+    #[test]
+    fn test_try_operations() {
+        let cache: ConcurrentSlruCache<String, i32> =
+            ConcurrentSlruCache::init(make_config(100, 50, 16), None);
+
+        assert_eq!(cache.try_put("a".to_string(), 1, 1), Ok(None));
+        assert_eq!(cache.try_get("a"), Ok(Some(1)));
+
+        let idx = cache.segment_index("a");
+        {
+            let _writer = cache.segments[idx].write();
+            assert_eq!(cache.try_get("a"), Err(Busy(())));
+            assert_eq!(cache.try_remove("a"), Err(Busy(())));
+            let rejected = cache.try_put("a".to_string(), 2, 1).unwrap_err();
+            assert_eq!(rejected.into_inner(), ("a".to_string(), 2));
+        }
+
+        assert_eq!(cache.try_remove("a"), Ok(Some(1)));
+        assert_eq!(cache.metrics().get("lock_contention_misses"), Some(&3.0));
+    }
 }
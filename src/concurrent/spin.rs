@@ -0,0 +1,178 @@
+//! Spinning Reader-Writer Lock
+//!
+//! [`RawSpinRwLock`] locks segments on targets without an operating system,
+//! where `parking_lot` (which parks threads through `std`) is unavailable.
+//! Waiting threads busy-wait with [`core::hint::spin_loop`].
+//!
+//! The lock prefers writers: once a writer is waiting, new readers wait
+//! too, so a steady stream of `get()` calls cannot starve `put()`.
+//!
+//! ```text
+//! state:  [ reader count ... | WRITER_WAITING | WRITER ]
+//!                               bit 1            bit 0
+//! ```
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+use lock_api::{GuardSend, RawRwLock};
+
+/// Set while a writer holds the lock.
+const WRITER: usize = 1;
+
+/// Set while a writer waits for the readers to leave.
+const WRITER_WAITING: usize = 1 << 1;
+
+/// One reader, counted above the writer bits.
+const READER: usize = 1 << 2;
+
+/// A writer-preferring spinlock implementing [`lock_api::RawRwLock`].
+///
+/// This is the segment lock of the concurrent caches when the `concurrent`
+/// feature (and with it `parking_lot`) is not enabled. It can also be chosen
+/// explicitly, e.g. `ShardedCache<LruCache<K, V>, RawSpinRwLock>`.
+///
+/// Spinning wastes the waiting core's time, so this lock suits short critical
+/// sections on dedicated cores. With an operating system scheduler, prefer
+/// `parking_lot`.
+#[derive(Debug)]
+pub struct RawSpinRwLock {
+    state: AtomicUsize,
+}
+
+// SAFETY: the lock hands out exclusive access only while WRITER is set, and
+// WRITER is only set when no reader and no other writer holds the lock.
+// Shared access is only granted while WRITER is clear.
+unsafe impl RawRwLock for RawSpinRwLock {
+    #[allow(clippy::declare_interior_mutable_const)]
+    const INIT: Self = RawSpinRwLock {
+        state: AtomicUsize::new(0),
+    };
+
+    type GuardMarker = GuardSend;
+
+    fn lock_shared(&self) {
+        while !self.try_lock_shared() {
+            core::hint::spin_loop();
+        }
+    }
+
+    fn try_lock_shared(&self) -> bool {
+        let mut state = self.state.load(Ordering::Relaxed);
+        loop {
+            if state & (WRITER | WRITER_WAITING) != 0 {
+                return false;
+            }
+            match self.state.compare_exchange_weak(
+                state,
+                state + READER,
+                Ordering::Acquire,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => return true,
+                Err(current) => state = current,
+            }
+        }
+    }
+
+    unsafe fn unlock_shared(&self) {
+        self.state.fetch_sub(READER, Ordering::Release);
+    }
+
+    fn lock_exclusive(&self) {
+        loop {
+            let state = self.state.load(Ordering::Relaxed);
+            if state & !WRITER_WAITING == 0 {
+                // Free: take it, clearing our waiting flag. Other waiting
+                // writers set theirs again on their next attempt.
+                if self
+                    .state
+                    .compare_exchange_weak(state, WRITER, Ordering::Acquire, Ordering::Relaxed)
+                    .is_ok()
+                {
+                    return;
+                }
+                continue;
+            }
+            if state & WRITER_WAITING == 0 {
+                self.state.fetch_or(WRITER_WAITING, Ordering::Relaxed);
+            }
+            core::hint::spin_loop();
+        }
+    }
+
+    fn try_lock_exclusive(&self) -> bool {
+        let state = self.state.load(Ordering::Relaxed);
+        state & !WRITER_WAITING == 0
+            && self
+                .state
+                .compare_exchange(state, WRITER, Ordering::Acquire, Ordering::Relaxed)
+                .is_ok()
+    }
+
+    unsafe fn unlock_exclusive(&self) {
+        self.state.fetch_and(!WRITER, Ordering::Release);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    extern crate std;
+    use std::sync::Arc;
+    use std::thread;
+    use std::vec::Vec;
+
+    type SpinRwLock<T> = lock_api::RwLock<RawSpinRwLock, T>;
+
+    #[test]
+    fn test_shared_and_exclusive_exclude_each_other() {
+        let lock = SpinRwLock::new(0);
+        {
+            let _first = lock.read();
+            let _second = lock.try_read().expect("readers share the lock");
+            assert!(lock.try_write().is_none());
+        }
+        {
+            let _writer = lock.write();
+            assert!(lock.try_read().is_none());
+            assert!(lock.try_write().is_none());
+        }
+        assert!(lock.try_write().is_some());
+    }
+
+    #[test]
+    fn test_waiting_writer_blocks_new_readers() {
+        let lock = RawSpinRwLock::INIT;
+        lock.lock_shared();
+        lock.state.fetch_or(WRITER_WAITING, Ordering::Relaxed);
+        assert!(!lock.try_lock_shared());
+
+        // SAFETY: the shared lock was taken above
+        unsafe { lock.unlock_shared() };
+        lock.lock_exclusive();
+        assert!(lock.is_locked_exclusive());
+        // SAFETY: the exclusive lock was taken above
+        unsafe { lock.unlock_exclusive() };
+        assert!(!lock.is_locked());
+    }
+
+    #[test]
+    fn test_counter_under_contention() {
+        let lock = Arc::new(SpinRwLock::new(0u64));
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let lock = Arc::clone(&lock);
+                thread::spawn(move || {
+                    for _ in 0..1000 {
+                        *lock.write() += 1;
+                        let _ = *lock.read();
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert_eq!(*lock.read(), 8000);
+    }
+}
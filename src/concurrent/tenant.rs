@@ -0,0 +1,550 @@
+//! Multi-Tenant Cache
+//!
+//! A cache shared by several tenants lets one noisy tenant evict everybody
+//! else's data. [`TenantCache`] gives every tenant a cache of its own, built
+//! from the same configuration and policy, and enforces the whole cache's
+//! limits across all of them:
+//!
+//! ```text
+//! put(t, k) ──▶ tenant[t].put ──▶ evict within t's quota
+//!                                       │ all tenants over the cache's limits?
+//!                                       ▼
+//!                  victim = tenant over its quota, or else the tenant
+//!                           furthest above its guaranteed minimum
+//!                                       │
+//!                                       ▼
+//!                         tenant[victim].evict ──▶ policy's victim
+//! ```
+//!
+//! # Quotas
+//!
+//! A [`TenantQuota`] caps a tenant's entries and bytes, and may guarantee it
+//! a minimum of each. A tenant at its cap evicts its own entries, like a
+//! cache of that size. When the tenants together exceed the cache's limits,
+//! the victim is picked among the tenants above their minimum: first those
+//! over their quota, then the one furthest above its minimum, by bytes when
+//! the size limit is exceeded and by entries otherwise. The entry evicted
+//! within that tenant is chosen by its eviction policy.
+//!
+//! Minimums are only honored as far as they fit: when every tenant is at or
+//! below its minimum, the writing tenant makes room for itself.
+//!
+//! # Accounting
+//!
+//! Each tenant's cache shares one budget across its segments, so usage is
+//! read from atomics without taking any segment lock, and no operation holds
+//! two segment locks at a time. As with a shared budget, concurrent writers
+//! may briefly overshoot the limits.
+
+extern crate alloc;
+
+use super::{DefaultRawRwLock, Segment, ShardedCache};
+use crate::config::{ConcurrentCacheConfig, SizedConfig};
+use crate::metrics::{CacheMetrics, MetricsSnapshot};
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::hash::Hash;
+use hashbrown::HashMap;
+use lock_api::{RawRwLock, RwLock, RwLockReadGuard};
+
+#[cfg(feature = "hashbrown")]
+use hashbrown::DefaultHashBuilder;
+
+#[cfg(not(feature = "hashbrown"))]
+use std::collections::hash_map::RandomState as DefaultHashBuilder;
+
+/// Key type of a segment.
+type Key<C> = <C as Segment>::Key;
+
+/// Value type of a segment.
+type Value<C> = <C as Segment>::Value;
+
+/// Entry and size limits of one tenant, and the share of the cache it is
+/// guaranteed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TenantQuota {
+    /// Maximum number of entries of the tenant
+    pub max_entries: usize,
+    /// Maximum total size of the tenant's entries
+    pub max_size: u64,
+    /// Number of entries the tenant keeps when other tenants need room
+    pub min_entries: usize,
+    /// Total size the tenant keeps when other tenants need room
+    pub min_size: u64,
+}
+
+impl TenantQuota {
+    /// Creates a quota of `max_entries` entries and `max_size` bytes, without
+    /// a guaranteed minimum.
+    pub fn new(max_entries: usize, max_size: u64) -> Self {
+        TenantQuota {
+            max_entries,
+            max_size,
+            min_entries: 0,
+            min_size: 0,
+        }
+    }
+
+    /// Creates a quota limited only by the whole cache's limits.
+    pub fn unlimited() -> Self {
+        Self::new(usize::MAX, u64::MAX)
+    }
+
+    /// Guarantees the tenant `min_entries` entries and `min_size` bytes, which
+    /// other tenants' writes do not evict.
+    pub fn with_minimum(self, min_entries: usize, min_size: u64) -> Self {
+        TenantQuota {
+            min_entries,
+            min_size,
+            ..self
+        }
+    }
+}
+
+impl Default for TenantQuota {
+    fn default() -> Self {
+        Self::unlimited()
+    }
+}
+
+/// Snapshot of one tenant of a [`TenantCache`].
+#[derive(Debug, Clone)]
+pub struct TenantMetrics {
+    /// Number of entries of the tenant
+    pub len: usize,
+    /// Total size of the tenant's entries
+    pub size: u64,
+    /// Quota of the tenant
+    pub quota: TenantQuota,
+    /// Metrics of the tenant's cache
+    pub snapshot: MetricsSnapshot,
+}
+
+/// The cache and quota of one tenant.
+struct Tenant<C: Segment, L: RawRwLock> {
+    quota: TenantQuota,
+    cache: ShardedCache<C, L>,
+}
+
+impl<C: Segment, L: RawRwLock> Tenant<C, L> {
+    /// Returns the tenant's usage, quota and minimum, in bytes if `by_size`
+    /// and in entries otherwise.
+    fn usage(&self, by_size: bool) -> (u64, u64, u64) {
+        let (len, size) = self.cache.usage();
+        let quota = &self.quota;
+        if by_size {
+            (size, quota.max_size, quota.min_size)
+        } else {
+            (
+                len as u64,
+                quota.max_entries as u64,
+                quota.min_entries as u64,
+            )
+        }
+    }
+}
+
+/// A thread-safe cache shared by tenants of type `T`, each with its own
+/// [`ShardedCache`] of segments of type `C` and its own [`TenantQuota`].
+///
+/// Tenants are created on their first write, with the default quota, or
+/// explicitly with [`set_quota()`](Self::set_quota). Each tenant's cache has
+/// the configured number of segments and may grow up to its quota or the
+/// whole cache's limits, whichever is smaller.
+///
+/// ```
+/// use cache_rs::concurrent::{TenantCache, TenantQuota};
+/// use cache_rs::config::{ConcurrentCacheConfig, GdsfCacheConfig};
+/// use cache_rs::GdsfCache;
+/// use core::num::NonZeroUsize;
+///
+/// let config = ConcurrentCacheConfig {
+///     base: GdsfCacheConfig {
+///         capacity: NonZeroUsize::new(100).unwrap(),
+///         initial_age: 0.0,
+///         max_size: 8_000,
+///         weight_limits: None,
+///     },
+///     segments: 4,
+///     shared_budget: true,
+/// };
+/// let cache: TenantCache<&str, GdsfCache<u32, u32>> = TenantCache::init(config, None)
+///     .with_default_quota(TenantQuota::new(100, 6_000));
+/// cache.set_quota("checkout", TenantQuota::unlimited().with_minimum(0, 3_000));
+///
+/// cache.put("checkout", 1, 1, 3_000);
+/// for key in 0..100 {
+///     cache.put("crawler", key, key, 100);
+/// }
+///
+/// // The crawler is held to its quota, and to what checkout's minimum leaves
+/// assert_eq!(cache.get(&"checkout", &1), Some(1));
+/// assert_eq!(cache.tenant_metrics(&"crawler").unwrap().size, 5_000);
+/// assert_eq!(cache.current_size(), 8_000);
+/// ```
+pub struct TenantCache<T, C: Segment, L: RawRwLock = DefaultRawRwLock> {
+    tenants: RwLock<L, HashMap<T, Tenant<C, L>>>,
+    config: ConcurrentCacheConfig<C::Config>,
+    hash_builder: C::Hasher,
+    default_quota: TenantQuota,
+    capacity: usize,
+    max_size: u64,
+}
+
+impl<T, C> TenantCache<T, C>
+where
+    T: Hash + Eq + Clone,
+    C: Segment<Hasher = DefaultHashBuilder>,
+{
+    /// Creates a multi-tenant cache with the whole cache's limits taken from
+    /// `config`, and an optional hasher for the tenants' caches.
+    ///
+    /// Every tenant's cache has `config.segments` segments sharing one
+    /// budget; `config.shared_budget` is ignored. Tenants get an unlimited
+    /// quota unless another default is set with
+    /// [`with_default_quota()`](Self::with_default_quota).
+    pub fn init(
+        config: ConcurrentCacheConfig<C::Config>,
+        hasher: Option<DefaultHashBuilder>,
+    ) -> Self {
+        Self::with_hasher(config, hasher.unwrap_or_default())
+    }
+}
+
+impl<T, C, L> TenantCache<T, C, L>
+where
+    T: Hash + Eq + Clone,
+    C: Segment,
+    L: RawRwLock,
+{
+    /// Creates a multi-tenant cache whose tenants' caches route keys with
+    /// `hasher`.
+    pub fn with_hasher(config: ConcurrentCacheConfig<C::Config>, hasher: C::Hasher) -> Self {
+        TenantCache {
+            tenants: RwLock::new(HashMap::new()),
+            config,
+            hash_builder: hasher,
+            default_quota: TenantQuota::unlimited(),
+            capacity: config.base.capacity(),
+            max_size: config.base.max_size(),
+        }
+    }
+
+    /// Gives tenants created by their first write `quota`.
+    pub fn with_default_quota(mut self, quota: TenantQuota) -> Self {
+        self.default_quota = quota;
+        self
+    }
+
+    /// Returns the quota of tenants created by their first write.
+    pub fn default_quota(&self) -> TenantQuota {
+        self.default_quota
+    }
+
+    /// Creates the cache of a tenant with `quota`.
+    fn new_tenant(&self, quota: TenantQuota) -> Tenant<C, L> {
+        let cache = ShardedCache::with_budget(
+            self.config,
+            self.hash_builder.clone(),
+            quota.max_entries.min(self.capacity),
+            quota.max_size.min(self.max_size),
+        );
+        Tenant { quota, cache }
+    }
+
+    /// Locks the tenants for reading, creating `tenant` first if needed.
+    fn tenants_with(&self, tenant: &T) -> RwLockReadGuard<'_, L, HashMap<T, Tenant<C, L>>> {
+        loop {
+            let tenants = self.tenants.read();
+            if tenants.contains_key(tenant) {
+                return tenants;
+            }
+            drop(tenants);
+            // Removed tenants may be recreated before the read lock is taken
+            // again, so check once more after inserting
+            let mut tenants = self.tenants.write();
+            if !tenants.contains_key(tenant) {
+                let created = self.new_tenant(self.default_quota);
+                tenants.insert(tenant.clone(), created);
+            }
+        }
+    }
+
+    /// Returns the tenant to evict from so the tenants fit the cache's
+    /// limits, or `None` if every tenant is at or below its minimum.
+    ///
+    /// Tenants over their quota come first, then the tenant furthest above
+    /// its minimum, in bytes if `by_size` and in entries otherwise.
+    fn victim(tenants: &HashMap<T, Tenant<C, L>>, by_size: bool) -> Option<&Tenant<C, L>> {
+        tenants
+            .values()
+            .filter_map(|tenant| {
+                let (used, max, min) = tenant.usage(by_size);
+                (used > min).then(|| ((used > max, used - min), tenant))
+            })
+            .max_by_key(|&(rank, _)| rank)
+            .map(|(_, tenant)| tenant)
+    }
+
+    /// Evicts from the victim tenants until all tenants together fit the
+    /// cache's limits, returning `evicted` plus those entries.
+    ///
+    /// `writer` makes room for itself if no tenant is above its minimum.
+    fn enforce_limits(
+        &self,
+        tenants: &HashMap<T, Tenant<C, L>>,
+        writer: &Tenant<C, L>,
+        evicted: Option<Vec<(Key<C>, Value<C>)>>,
+    ) -> Option<Vec<(Key<C>, Value<C>)>> {
+        let mut evicted = evicted.unwrap_or_default();
+        loop {
+            let (len, size) = Self::total_usage(tenants);
+            let by_size = if size > self.max_size {
+                true
+            } else if len > self.capacity {
+                false
+            } else {
+                break;
+            };
+            let victim = Self::victim(tenants, by_size).unwrap_or(writer);
+            match victim.cache.evict(by_size) {
+                Some(entry) => evicted.push(entry),
+                None => break,
+            }
+        }
+        if evicted.is_empty() {
+            None
+        } else {
+            Some(evicted)
+        }
+    }
+
+    /// Returns the number of entries and total size of all tenants.
+    fn total_usage(tenants: &HashMap<T, Tenant<C, L>>) -> (usize, u64) {
+        tenants
+            .values()
+            .map(|tenant| tenant.cache.usage())
+            .fold((0, 0), |(len, size), (l, s)| {
+                (len + l, size.saturating_add(s))
+            })
+    }
+
+    /// Sets the quota of `tenant`, creating it if needed, and returns the
+    /// entries evicted to fit the new quota.
+    ///
+    /// Holds the lock of the tenant list while evicting, which blocks every
+    /// other operation on the cache.
+    pub fn set_quota(&self, tenant: T, quota: TenantQuota) -> Option<Vec<(Key<C>, Value<C>)>> {
+        let mut tenants = self.tenants.write();
+        match tenants.get_mut(&tenant) {
+            Some(existing) => {
+                existing.quota = quota;
+                existing.cache.set_budget(
+                    quota.max_entries.min(self.capacity),
+                    quota.max_size.min(self.max_size),
+                )
+            }
+            None => {
+                let created = self.new_tenant(quota);
+                tenants.insert(tenant, created);
+                None
+            }
+        }
+    }
+
+    /// Returns the quota of `tenant`, or `None` if it does not exist.
+    pub fn quota(&self, tenant: &T) -> Option<TenantQuota> {
+        self.tenants.read().get(tenant).map(|tenant| tenant.quota)
+    }
+
+    /// Returns the tenants, in no particular order.
+    pub fn tenants(&self) -> Vec<T> {
+        self.tenants.read().keys().cloned().collect()
+    }
+
+    /// Removes `tenant` and all its entries, returning `true` if it existed.
+    pub fn remove_tenant(&self, tenant: &T) -> bool {
+        self.tenants.write().remove(tenant).is_some()
+    }
+
+    /// Retrieves a value of `tenant` from the cache.
+    ///
+    /// Returns a **clone** of the value, like [`ShardedCache::get()`].
+    pub fn get<Q>(&self, tenant: &T, key: &Q) -> Option<Value<C>>
+    where
+        Key<C>: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.tenants.read().get(tenant)?.cache.get(key)
+    }
+
+    /// Retrieves a value of `tenant` without recording the access.
+    pub fn peek<Q>(&self, tenant: &T, key: &Q) -> Option<Value<C>>
+    where
+        Key<C>: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.tenants.read().get(tenant)?.cache.peek(key)
+    }
+
+    /// Returns `true` if `tenant` has `key` cached, without recording the
+    /// access.
+    pub fn contains<Q>(&self, tenant: &T, key: &Q) -> bool
+    where
+        Key<C>: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.tenants
+            .read()
+            .get(tenant)
+            .is_some_and(|tenant| tenant.cache.contains(key))
+    }
+
+    /// Inserts a key-value pair of `tenant`, creating the tenant if needed.
+    ///
+    /// Returns the entries evicted to make room, from `tenant` when it is
+    /// over its quota and from the victim tenants when all tenants together
+    /// are over the cache's limits.
+    pub fn put(
+        &self,
+        tenant: T,
+        key: Key<C>,
+        value: Value<C>,
+        size: u64,
+    ) -> Option<Vec<(Key<C>, Value<C>)>> {
+        let tenants = self.tenants_with(&tenant);
+        let writer = &tenants[&tenant];
+        let evicted = writer.cache.put(key, value, size);
+        self.enforce_limits(&tenants, writer, evicted)
+    }
+
+    /// Removes a key of `tenant` from the cache, returning its value.
+    pub fn remove<Q>(&self, tenant: &T, key: &Q) -> Option<Value<C>>
+    where
+        Key<C>: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.tenants.read().get(tenant)?.cache.remove(key)
+    }
+
+    /// Records a cache miss of `tenant`, creating the tenant if needed.
+    ///
+    /// Call this after a failed `get()` when you fetch from the origin.
+    pub fn record_miss(&self, tenant: &T, object_size: u64) {
+        self.tenants_with(tenant)[tenant]
+            .cache
+            .record_miss(object_size);
+    }
+
+    /// Removes all entries of `tenant`, keeping its quota.
+    pub fn clear_tenant(&self, tenant: &T) {
+        if let Some(tenant) = self.tenants.read().get(tenant) {
+            tenant.cache.clear();
+        }
+    }
+
+    /// Removes all entries of all tenants, keeping their quotas.
+    pub fn clear(&self) {
+        for tenant in self.tenants.read().values() {
+            tenant.cache.clear();
+        }
+    }
+
+    /// Returns the maximum number of entries across all tenants.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the maximum total size across all tenants.
+    pub fn max_size(&self) -> u64 {
+        self.max_size
+    }
+
+    /// Returns the number of entries across all tenants.
+    pub fn len(&self) -> usize {
+        Self::total_usage(&self.tenants.read()).0
+    }
+
+    /// Returns `true` if no tenant has any entries.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the total size of the entries of all tenants.
+    pub fn current_size(&self) -> u64 {
+        Self::total_usage(&self.tenants.read()).1
+    }
+
+    /// Returns a snapshot of `tenant`, or `None` if it does not exist.
+    pub fn tenant_metrics(&self, tenant: &T) -> Option<TenantMetrics> {
+        self.tenants.read().get(tenant).map(Self::snapshot_tenant)
+    }
+
+    /// Returns a snapshot of every tenant, in no particular order.
+    ///
+    /// Unlike [`metrics()`](CacheMetrics::metrics), which merges all tenants,
+    /// this shows which tenants take the space and the evictions.
+    pub fn metrics_by_tenant(&self) -> Vec<(T, TenantMetrics)> {
+        self.tenants
+            .read()
+            .iter()
+            .map(|(id, tenant)| (id.clone(), Self::snapshot_tenant(tenant)))
+            .collect()
+    }
+
+    fn snapshot_tenant(tenant: &Tenant<C, L>) -> TenantMetrics {
+        // Take the snapshot first, as it replays buffered reads
+        let snapshot = tenant.cache.snapshot();
+        let (len, size) = tenant.cache.usage();
+        TenantMetrics {
+            len,
+            size,
+            quota: tenant.quota,
+            snapshot,
+        }
+    }
+}
+
+impl<T, C, L> CacheMetrics for TenantCache<T, C, L>
+where
+    T: Hash + Eq + Clone,
+    C: Segment,
+    L: RawRwLock,
+{
+    fn metrics(&self) -> BTreeMap<String, f64> {
+        self.snapshot().to_btreemap()
+    }
+
+    fn snapshot(&self) -> MetricsSnapshot {
+        let mut merged = MetricsSnapshot::Disabled;
+        for tenant in self.tenants.read().values() {
+            merged.merge(&tenant.cache.snapshot());
+        }
+        merged
+    }
+
+    fn algorithm_name(&self) -> &'static str {
+        C::CONCURRENT_NAME
+    }
+}
+
+impl<T, C, L> core::fmt::Debug for TenantCache<T, C, L>
+where
+    T: Hash + Eq + Clone,
+    C: Segment,
+    L: RawRwLock,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let (tenant_count, total_len) = {
+            let tenants = self.tenants.read();
+            (tenants.len(), Self::total_usage(&tenants).0)
+        };
+        f.debug_struct("TenantCache")
+            .field("algorithm", &C::CONCURRENT_NAME)
+            .field("tenant_count", &tenant_count)
+            .field("total_len", &total_len)
+            .finish()
+    }
+}
@@ -81,6 +81,7 @@
 //! let config = LruCacheConfig {
 //!     capacity: NonZeroUsize::new(10_000).unwrap(),
 //!     max_size: 10 * 1024 * 1024,  // 10MB
+//!     weight_limits: None,
 //! };
 //!
 //! // Create cache from config
@@ -101,6 +102,22 @@ pub use lfuda::LfudaCacheConfig;
 pub use lru::LruCacheConfig;
 pub use slru::SlruCacheConfig;
 
+/// A cache configuration with `capacity` and `max_size` limits.
+///
+/// Implemented by every `*CacheConfig`, so helpers can size any cache, e.g.
+/// `CgroupMemory::size_config()` in the `memory` module with the `std`
+/// feature, and read its limits without building it.
+pub trait SizedConfig {
+    /// Returns the maximum number of entries.
+    fn capacity(&self) -> usize;
+
+    /// Returns the maximum total size in bytes for cached values.
+    fn max_size(&self) -> u64;
+
+    /// Sets the maximum total size in bytes for cached values.
+    fn set_max_size(&mut self, max_size: u64);
+}
+
 /// Generic configuration wrapper for concurrent caches.
 ///
 /// Wraps any base cache configuration and adds the `segments` field
@@ -132,52 +149,80 @@ pub use slru::SlruCacheConfig;
 ///     base: LruCacheConfig {
 ///         capacity: NonZeroUsize::new(10_000).unwrap(),
 ///         max_size: 100 * 1024 * 1024,  // 100MB total
+///         weight_limits: None,
 ///     },
 ///     segments: 16,
+///     shared_budget: false,
 /// };
 /// ```
-#[cfg(feature = "concurrent")]
+#[cfg(feature = "concurrent-core")]
 #[derive(Clone, Copy)]
 pub struct ConcurrentCacheConfig<C> {
     /// Base configuration for the underlying cache algorithm.
     /// See individual config docs for sizing guidance.
     pub base: C,
     /// Number of segments for sharding (more segments = less contention)
+    ///
+    /// Rounded up to the next power of two.
     pub segments: usize,
+    /// Share `capacity` and `max_size` across all segments instead of giving
+    /// each segment a fixed `1 / segments` slice.
+    ///
+    /// When the cache is over budget, the segment furthest over its fair share
+    /// evicts, so skewed keys or a few large objects no longer cause one
+    /// segment to evict while the others sit half empty.
+    pub shared_budget: bool,
 }
 
-#[cfg(feature = "concurrent")]
+#[cfg(feature = "concurrent-core")]
 impl<C: core::fmt::Debug> core::fmt::Debug for ConcurrentCacheConfig<C> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.debug_struct("ConcurrentCacheConfig")
             .field("base", &self.base)
             .field("segments", &self.segments)
+            .field("shared_budget", &self.shared_budget)
             .finish()
     }
 }
 
+/// Sizes the whole concurrent cache, like its base configuration.
+#[cfg(feature = "concurrent-core")]
+impl<C: SizedConfig> SizedConfig for ConcurrentCacheConfig<C> {
+    fn capacity(&self) -> usize {
+        self.base.capacity()
+    }
+
+    fn max_size(&self) -> u64 {
+        self.base.max_size()
+    }
+
+    fn set_max_size(&mut self, max_size: u64) {
+        self.base.set_max_size(max_size);
+    }
+}
+
 // Type aliases for concurrent cache configs
-#[cfg(feature = "concurrent")]
+#[cfg(feature = "concurrent-core")]
 /// Configuration for a concurrent LRU cache.
 /// Type alias for `ConcurrentCacheConfig<LruCacheConfig>`.
 pub type ConcurrentLruCacheConfig = ConcurrentCacheConfig<LruCacheConfig>;
 
-#[cfg(feature = "concurrent")]
+#[cfg(feature = "concurrent-core")]
 /// Configuration for a concurrent LFU cache.
 /// Type alias for `ConcurrentCacheConfig<LfuCacheConfig>`.
 pub type ConcurrentLfuCacheConfig = ConcurrentCacheConfig<LfuCacheConfig>;
 
-#[cfg(feature = "concurrent")]
+#[cfg(feature = "concurrent-core")]
 /// Configuration for a concurrent LFUDA cache.
 /// Type alias for `ConcurrentCacheConfig<LfudaCacheConfig>`.
 pub type ConcurrentLfudaCacheConfig = ConcurrentCacheConfig<LfudaCacheConfig>;
 
-#[cfg(feature = "concurrent")]
+#[cfg(feature = "concurrent-core")]
 /// Configuration for a concurrent SLRU cache.
 /// Type alias for `ConcurrentCacheConfig<SlruCacheConfig>`.
 pub type ConcurrentSlruCacheConfig = ConcurrentCacheConfig<SlruCacheConfig>;
 
-#[cfg(feature = "concurrent")]
+#[cfg(feature = "concurrent-core")]
 /// Configuration for a concurrent GDSF cache.
 /// Type alias for `ConcurrentCacheConfig<GdsfCacheConfig>`.
 pub type ConcurrentGdsfCacheConfig = ConcurrentCacheConfig<GdsfCacheConfig>;
@@ -57,6 +57,7 @@
 //!     capacity: NonZeroUsize::new(10_000).unwrap(),
 //!     initial_age: 0.0,
 //!     max_size: 50 * 1024 * 1024,  // 50MB
+//!     weight_limits: None,
 //! };
 //! let cache: GdsfCache<String, Vec<u8>> = GdsfCache::init(config, None);
 //!
@@ -65,10 +66,13 @@
 //!     capacity: NonZeroUsize::new(20_000).unwrap(),  // ~50KB avg
 //!     initial_age: 0.0,
 //!     max_size: 1024 * 1024 * 1024,  // 1GB
+//!     weight_limits: None,
 //! };
 //! let cache: GdsfCache<String, Vec<u8>> = GdsfCache::init(config, None);
 //! ```
 
+use super::SizedConfig;
+use crate::weight::WeightLimits;
 use core::fmt;
 use core::num::NonZeroUsize;
 
@@ -87,6 +91,8 @@ use core::num::NonZeroUsize;
 /// - `initial_age`: Initial global age value (default: 0.0)
 /// - `max_size`: Maximum total size in bytes for cached values. **Essential for GDSF**
 ///   since the algorithm optimizes based on object sizes. See module docs for guidance.
+/// - `weight_limits`: Optional limits on other per-entry costs, such as open
+///   file handles. `None` unless entries are inserted with `put_weighted()`.
 ///
 /// # Sizing Recommendations
 ///
@@ -109,6 +115,7 @@ use core::num::NonZeroUsize;
 ///     capacity: NonZeroUsize::new(5_000).unwrap(),
 ///     initial_age: 0.0,
 ///     max_size: 10 * 1024 * 1024,  // 10MB
+///     weight_limits: None,
 /// };
 /// let cache: GdsfCache<String, Vec<u8>> = GdsfCache::init(config, None);
 ///
@@ -117,6 +124,7 @@ use core::num::NonZeroUsize;
 ///     capacity: NonZeroUsize::new(5_000).unwrap(),
 ///     initial_age: 0.0,
 ///     max_size: 100 * 1024 * 1024,  // 100MB
+///     weight_limits: None,
 /// };
 /// let cache: GdsfCache<String, Vec<u8>> = GdsfCache::init(config, None);
 /// ```
@@ -130,6 +138,9 @@ pub struct GdsfCacheConfig {
     /// Maximum total size in bytes for cached values.
     /// Set based on your memory/disk budget. Avoid using `u64::MAX`.
     pub max_size: u64,
+    /// Limits on the total weight of the entries in each dimension, or
+    /// `None` to leave entry weights unbounded. See [`crate::weight`].
+    pub weight_limits: Option<WeightLimits>,
 }
 
 impl fmt::Debug for GdsfCacheConfig {
@@ -138,10 +149,25 @@ impl fmt::Debug for GdsfCacheConfig {
             .field("capacity", &self.capacity)
             .field("initial_age", &self.initial_age)
             .field("max_size", &self.max_size)
+            .field("weight_limits", &self.weight_limits)
             .finish()
     }
 }
 
+impl SizedConfig for GdsfCacheConfig {
+    fn capacity(&self) -> usize {
+        self.capacity.get()
+    }
+
+    fn max_size(&self) -> u64 {
+        self.max_size
+    }
+
+    fn set_max_size(&mut self, max_size: u64) {
+        self.max_size = max_size;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -153,6 +179,7 @@ mod tests {
             capacity: NonZeroUsize::new(1000).unwrap(),
             initial_age: 0.0,
             max_size: 10 * 1024 * 1024,
+            weight_limits: None,
         };
         assert_eq!(config.capacity.get(), 1000);
         assert_eq!(config.initial_age, 0.0);
@@ -166,6 +193,7 @@ mod tests {
             capacity: NonZeroUsize::new(500).unwrap(),
             initial_age: 10.5,
             max_size: 1024 * 1024,
+            weight_limits: None,
         };
         assert_eq!(config.capacity.get(), 500);
         assert_eq!(config.initial_age, 10.5);
@@ -49,6 +49,7 @@
 //!     capacity: NonZeroUsize::new(10_000).unwrap(),
 //!     initial_age: 0,
 //!     max_size: 50 * 1024 * 1024,  // 50MB
+//!     weight_limits: None,
 //! };
 //! let cache: LfudaCache<String, Vec<u8>> = LfudaCache::init(config, None);
 //!
@@ -57,10 +58,13 @@
 //!     capacity: NonZeroUsize::new(1000).unwrap(),
 //!     initial_age: 100,
 //!     max_size: 1024 * 1024,  // 1MB is plenty for small values
+//!     weight_limits: None,
 //! };
 //! let cache: LfudaCache<String, i32> = LfudaCache::init(config, None);
 //! ```
 
+use super::SizedConfig;
+use crate::weight::WeightLimits;
 use core::fmt;
 use core::num::NonZeroUsize;
 
@@ -76,6 +80,8 @@ use core::num::NonZeroUsize;
 /// - `initial_age`: Initial global age value (default: 0)
 /// - `max_size`: Maximum total size in bytes for cached values. Set this based
 ///   on your memory budget, not to `u64::MAX`. See module docs for sizing guidance.
+/// - `weight_limits`: Optional limits on other per-entry costs, such as open
+///   file handles. `None` unless entries are inserted with `put_weighted()`.
 ///
 /// # Sizing Recommendations
 ///
@@ -98,6 +104,7 @@ use core::num::NonZeroUsize;
 ///     capacity: NonZeroUsize::new(10_000).unwrap(),
 ///     initial_age: 0,
 ///     max_size: 10 * 1024 * 1024,  // 10MB
+///     weight_limits: None,
 /// };
 /// let cache: LfudaCache<String, Vec<u8>> = LfudaCache::init(config, None);
 ///
@@ -106,6 +113,7 @@ use core::num::NonZeroUsize;
 ///     capacity: NonZeroUsize::new(500).unwrap(),
 ///     initial_age: 100,
 ///     max_size: 64 * 1024,  // 64KB is ample for small values
+///     weight_limits: None,
 /// };
 /// let cache: LfudaCache<&str, i32> = LfudaCache::init(config, None);
 /// ```
@@ -119,6 +127,9 @@ pub struct LfudaCacheConfig {
     /// Maximum total size in bytes for cached values.
     /// Set based on your memory/disk budget. Avoid using `u64::MAX`.
     pub max_size: u64,
+    /// Limits on the total weight of the entries in each dimension, or
+    /// `None` to leave entry weights unbounded. See [`crate::weight`].
+    pub weight_limits: Option<WeightLimits>,
 }
 
 impl fmt::Debug for LfudaCacheConfig {
@@ -127,10 +138,25 @@ impl fmt::Debug for LfudaCacheConfig {
             .field("capacity", &self.capacity)
             .field("initial_age", &self.initial_age)
             .field("max_size", &self.max_size)
+            .field("weight_limits", &self.weight_limits)
             .finish()
     }
 }
 
+impl SizedConfig for LfudaCacheConfig {
+    fn capacity(&self) -> usize {
+        self.capacity.get()
+    }
+
+    fn max_size(&self) -> u64 {
+        self.max_size
+    }
+
+    fn set_max_size(&mut self, max_size: u64) {
+        self.max_size = max_size;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -142,6 +168,7 @@ mod tests {
             capacity: NonZeroUsize::new(1000).unwrap(),
             initial_age: 0,
             max_size: 10 * 1024 * 1024,
+            weight_limits: None,
         };
         assert_eq!(config.capacity.get(), 1000);
         assert_eq!(config.initial_age, 0);
@@ -155,6 +182,7 @@ mod tests {
             capacity: NonZeroUsize::new(500).unwrap(),
             initial_age: 10,
             max_size: 1024 * 1024,
+            weight_limits: None,
         };
         assert_eq!(config.capacity.get(), 500);
         assert_eq!(config.initial_age, 10);
@@ -48,6 +48,7 @@
 //! let config = LruCacheConfig {
 //!     capacity: NonZeroUsize::new(10_000).unwrap(),
 //!     max_size: 50 * 1024 * 1024,  // 50MB
+//!     weight_limits: None,
 //! };
 //! let cache: LruCache<String, Vec<u8>> = LruCache::init(config, None);
 //!
@@ -56,10 +57,13 @@
 //! let config = LruCacheConfig {
 //!     capacity: NonZeroUsize::new(1000).unwrap(),
 //!     max_size: 1024 * 1024,  // 1MB is plenty for small values
+//!     weight_limits: None,
 //! };
 //! let cache: LruCache<String, i32> = LruCache::init(config, None);
 //! ```
 
+use super::SizedConfig;
+use crate::weight::WeightLimits;
 use core::fmt;
 use core::num::NonZeroUsize;
 
@@ -73,6 +77,8 @@ use core::num::NonZeroUsize;
 ///   memory overhead (~64-128 bytes) for keys, pointers, and metadata.
 /// - `max_size`: Maximum total size in bytes for cached values. Set this based
 ///   on your memory budget, not to `u64::MAX`. See module docs for sizing guidance.
+/// - `weight_limits`: Optional limits on other per-entry costs, such as open
+///   file handles. `None` unless entries are inserted with `put_weighted()`.
 ///
 /// # Sizing Recommendations
 ///
@@ -94,6 +100,7 @@ use core::num::NonZeroUsize;
 /// let config = LruCacheConfig {
 ///     capacity: NonZeroUsize::new(10_000).unwrap(),
 ///     max_size: 10 * 1024 * 1024,  // 10MB
+///     weight_limits: None,
 /// };
 /// let cache: LruCache<String, Vec<u8>> = LruCache::init(config, None);
 ///
@@ -101,6 +108,7 @@ use core::num::NonZeroUsize;
 /// let config = LruCacheConfig {
 ///     capacity: NonZeroUsize::new(500).unwrap(),
 ///     max_size: 64 * 1024,  // 64KB is ample for small values
+///     weight_limits: None,
 /// };
 /// let cache: LruCache<&str, i32> = LruCache::init(config, None);
 /// ```
@@ -112,6 +120,9 @@ pub struct LruCacheConfig {
     /// Maximum total size in bytes for cached values.
     /// Set based on your memory/disk budget. Avoid using `u64::MAX`.
     pub max_size: u64,
+    /// Limits on the total weight of the entries in each dimension, or
+    /// `None` to leave entry weights unbounded. See [`crate::weight`].
+    pub weight_limits: Option<WeightLimits>,
 }
 
 impl fmt::Debug for LruCacheConfig {
@@ -119,10 +130,25 @@ impl fmt::Debug for LruCacheConfig {
         f.debug_struct("LruCacheConfig")
             .field("capacity", &self.capacity)
             .field("max_size", &self.max_size)
+            .field("weight_limits", &self.weight_limits)
             .finish()
     }
 }
 
+impl SizedConfig for LruCacheConfig {
+    fn capacity(&self) -> usize {
+        self.capacity.get()
+    }
+
+    fn max_size(&self) -> u64 {
+        self.max_size
+    }
+
+    fn set_max_size(&mut self, max_size: u64) {
+        self.max_size = max_size;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -133,6 +159,7 @@ mod tests {
         let config = LruCacheConfig {
             capacity: NonZeroUsize::new(1000).unwrap(),
             max_size: 10 * 1024 * 1024,
+            weight_limits: None,
         };
         assert_eq!(config.capacity.get(), 1000);
         assert_eq!(config.max_size, 10 * 1024 * 1024);
@@ -144,6 +171,7 @@ mod tests {
         let config = LruCacheConfig {
             capacity: NonZeroUsize::new(1000).unwrap(),
             max_size: 1024 * 1024,
+            weight_limits: None,
         };
         assert_eq!(config.capacity.get(), 1000);
         assert_eq!(config.max_size, 1024 * 1024);
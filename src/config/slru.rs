@@ -54,6 +54,7 @@
 //!     capacity: NonZeroUsize::new(10_000).unwrap(),
 //!     protected_capacity: NonZeroUsize::new(2_000).unwrap(),  // 20%
 //!     max_size: 50 * 1024 * 1024,  // 50MB
+//!     weight_limits: None,
 //! };
 //! let cache: SlruCache<String, Vec<u8>> = SlruCache::init(config, None);
 //!
@@ -62,10 +63,13 @@
 //!     capacity: NonZeroUsize::new(1000).unwrap(),
 //!     protected_capacity: NonZeroUsize::new(200).unwrap(),
 //!     max_size: 10 * 1024 * 1024,  // 10MB
+//!     weight_limits: None,
 //! };
 //! let cache: SlruCache<String, i32> = SlruCache::init(config, None);
 //! ```
 
+use super::SizedConfig;
+use crate::weight::WeightLimits;
 use core::fmt;
 use core::num::NonZeroUsize;
 
@@ -82,6 +86,8 @@ use core::num::NonZeroUsize;
 ///   Typically 20% of total capacity for hot items.
 /// - `max_size`: Maximum total size in bytes for cached values. Set this based
 ///   on your memory budget, not to `u64::MAX`. See module docs for sizing guidance.
+/// - `weight_limits`: Optional limits on other per-entry costs, such as open
+///   file handles. `None` unless entries are inserted with `put_weighted()`.
 ///
 /// # Sizing Recommendations
 ///
@@ -104,6 +110,7 @@ use core::num::NonZeroUsize;
 ///     capacity: NonZeroUsize::new(10_000).unwrap(),
 ///     protected_capacity: NonZeroUsize::new(2_000).unwrap(),
 ///     max_size: 10 * 1024 * 1024,  // 10MB
+///     weight_limits: None,
 /// };
 /// let cache: SlruCache<String, Vec<u8>> = SlruCache::init(config, None);
 ///
@@ -112,6 +119,7 @@ use core::num::NonZeroUsize;
 ///     capacity: NonZeroUsize::new(500).unwrap(),
 ///     protected_capacity: NonZeroUsize::new(100).unwrap(),
 ///     max_size: 64 * 1024,  // 64KB is ample for small values
+///     weight_limits: None,
 /// };
 /// let cache: SlruCache<&str, i32> = SlruCache::init(config, None);
 /// ```
@@ -126,6 +134,9 @@ pub struct SlruCacheConfig {
     /// Maximum total size in bytes for cached values.
     /// Set based on your memory/disk budget. Avoid using `u64::MAX`.
     pub max_size: u64,
+    /// Limits on the total weight of the entries in each dimension, or
+    /// `None` to leave entry weights unbounded. See [`crate::weight`].
+    pub weight_limits: Option<WeightLimits>,
 }
 
 impl fmt::Debug for SlruCacheConfig {
@@ -134,10 +145,25 @@ impl fmt::Debug for SlruCacheConfig {
             .field("capacity", &self.capacity)
             .field("protected_capacity", &self.protected_capacity)
             .field("max_size", &self.max_size)
+            .field("weight_limits", &self.weight_limits)
             .finish()
     }
 }
 
+impl SizedConfig for SlruCacheConfig {
+    fn capacity(&self) -> usize {
+        self.capacity.get()
+    }
+
+    fn max_size(&self) -> u64 {
+        self.max_size
+    }
+
+    fn set_max_size(&mut self, max_size: u64) {
+        self.max_size = max_size;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -149,6 +175,7 @@ mod tests {
             capacity: NonZeroUsize::new(1000).unwrap(),
             protected_capacity: NonZeroUsize::new(200).unwrap(),
             max_size: 10 * 1024 * 1024,
+            weight_limits: None,
         };
         assert_eq!(config.capacity.get(), 1000);
         assert_eq!(config.protected_capacity.get(), 200);
@@ -162,6 +189,7 @@ mod tests {
             capacity: NonZeroUsize::new(1000).unwrap(),
             protected_capacity: NonZeroUsize::new(200).unwrap(),
             max_size: 1024 * 1024,
+            weight_limits: None,
         };
         assert_eq!(config.capacity.get(), 1000);
         assert_eq!(config.protected_capacity.get(), 200);
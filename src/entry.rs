@@ -21,6 +21,8 @@
 //!
 //! `CacheMetadata<M>` contains:
 //! - `size: u64` - 8 bytes (content size tracking)
+//! - `hash: u64` - 8 bytes (the key's hash, so evictions never rehash it)
+//! - `weights: Weights` - 32 bytes (per-dimension weights, see [`crate::weight`])
 //! - `last_accessed: u64` - 8 bytes (timestamps for monitoring)
 //! - `create_time: u64` - 8 bytes (timestamps for TTL)
 //! - `algorithm: M` - Algorithm-specific metadata (0-16 bytes depending on algorithm)
@@ -55,6 +57,9 @@
 
 extern crate alloc;
 
+use crate::metrics::Clock;
+use crate::weight::Weights;
+use alloc::sync::Arc;
 use core::fmt;
 
 /// Metadata associated with a cache entry.
@@ -82,10 +87,21 @@ pub struct CacheMetadata<M = ()> {
     /// For size-aware caches, use actual bytes (memory, disk, etc.)
     pub size: u64,
 
-    /// Last access timestamp (nanos since epoch or monotonic clock).
+    /// Hash of the key, as computed by the cache holding the entry.
+    /// Lets the cache remove an evicted entry without hashing its key again.
+    /// Entries built outside a cache have a hash of 0.
+    pub hash: u64,
+
+    /// Weights of the entry in each dimension of the holding cache's
+    /// weight limits. Zero unless inserted with `put_weighted()`.
+    pub weights: Weights,
+
+    /// Last access timestamp in nanoseconds, from the holding cache's clock.
+    /// Entries built outside a cache read the system time.
     pub last_accessed: u64,
 
-    /// Creation timestamp (nanos since epoch or monotonic clock).
+    /// Creation timestamp in nanoseconds, from the same clock as
+    /// `last_accessed`.
     pub create_time: u64,
 
     /// Algorithm-specific metadata (frequency, priority, segment, etc.)
@@ -105,6 +121,8 @@ impl<M: Default> CacheMetadata<M> {
         let now = Self::now_nanos();
         Self {
             size,
+            hash: 0,
+            weights: Weights::ZERO,
             last_accessed: now,
             create_time: now,
             algorithm: M::default(),
@@ -124,22 +142,64 @@ impl<M> CacheMetadata<M> {
         let now = Self::now_nanos();
         Self {
             size,
+            hash: 0,
+            weights: Weights::ZERO,
             last_accessed: now,
             create_time: now,
             algorithm,
         }
     }
 
+    /// Returns this metadata with the key's hash set to `hash`.
+    #[inline]
+    pub fn with_hash(mut self, hash: u64) -> Self {
+        self.hash = hash;
+        self
+    }
+
+    /// Returns this metadata with the entry weighing `weights`.
+    #[inline]
+    pub fn with_weights(mut self, weights: Weights) -> Self {
+        self.weights = weights;
+        self
+    }
+
+    /// Returns this metadata created and last accessed at `now`.
+    #[inline]
+    pub fn with_time(mut self, now: u64) -> Self {
+        self.create_time = now;
+        self.last_accessed = now;
+        self
+    }
+
     /// Updates the last_accessed timestamp to the current time.
+    ///
+    /// Returns the time since the previous access in nanoseconds.
+    #[inline]
+    pub fn touch(&mut self) -> u64 {
+        self.touch_at(Self::now_nanos())
+    }
+
+    /// Updates the last_accessed timestamp to `now`.
+    ///
+    /// Returns the time since the previous access in nanoseconds.
     #[inline]
-    pub fn touch(&mut self) {
-        self.last_accessed = Self::now_nanos();
+    pub fn touch_at(&mut self, now: u64) -> u64 {
+        let idle = now.saturating_sub(self.last_accessed);
+        self.last_accessed = now;
+        idle
     }
 
     /// Gets the age of this entry in nanoseconds.
     #[inline]
     pub fn age_nanos(&self) -> u64 {
-        Self::now_nanos().saturating_sub(self.create_time)
+        self.age_at(Self::now_nanos())
+    }
+
+    /// Gets the age of this entry in nanoseconds at `now`.
+    #[inline]
+    pub fn age_at(&self, now: u64) -> u64 {
+        now.saturating_sub(self.create_time)
     }
 
     /// Gets the time since last access in nanoseconds.
@@ -148,23 +208,71 @@ impl<M> CacheMetadata<M> {
         Self::now_nanos().saturating_sub(self.last_accessed)
     }
 
+    /// Gets the age and the time since last access in nanoseconds, read
+    /// from the same clock sample so the idle time never exceeds the age.
+    #[inline]
+    pub fn age_and_idle_nanos(&self) -> (u64, u64) {
+        self.age_and_idle_at(Self::now_nanos())
+    }
+
+    /// Gets the age and the time since last access in nanoseconds at `now`.
+    #[inline]
+    pub fn age_and_idle_at(&self, now: u64) -> (u64, u64) {
+        (
+            now.saturating_sub(self.create_time),
+            now.saturating_sub(self.last_accessed),
+        )
+    }
+
     /// Returns the current time in nanoseconds.
-    #[cfg(feature = "std")]
     #[inline]
     fn now_nanos() -> u64 {
-        extern crate std;
-        use std::time::{SystemTime, UNIX_EPOCH};
-        SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .map(|d| d.as_nanos() as u64)
-            .unwrap_or(0)
+        system_time_nanos()
     }
+}
+
+/// Returns the system time in nanoseconds since the Unix epoch.
+#[cfg(feature = "std")]
+#[inline]
+fn system_time_nanos() -> u64 {
+    extern crate std;
+    use std::time::{SystemTime, UNIX_EPOCH};
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0)
+}
 
-    /// Returns 0 in no_std environments where system time is not available.
-    #[cfg(not(feature = "std"))]
+/// Returns 0 in no_std environments where system time is not available.
+#[cfg(not(feature = "std"))]
+#[inline]
+fn system_time_nanos() -> u64 {
+    0
+}
+
+/// Time source a cache stamps and ages its entries with.
+///
+/// Reads the [`Clock`] set with `set_clock()`, or without one the system
+/// time, which is always 0 without the `std` feature.
+#[derive(Clone, Default)]
+pub(crate) struct EntryClock(Option<Arc<dyn Clock>>);
+
+impl EntryClock {
+    pub(crate) fn new(clock: Option<Arc<dyn Clock>>) -> Self {
+        EntryClock(clock)
+    }
+
+    pub(crate) fn clock(&self) -> Option<&Arc<dyn Clock>> {
+        self.0.as_ref()
+    }
+
+    /// Returns the current time in nanoseconds.
     #[inline]
-    fn now_nanos() -> u64 {
-        0
+    pub(crate) fn now_nanos(&self) -> u64 {
+        match &self.0 {
+            Some(clock) => clock.now_nanos(),
+            None => system_time_nanos(),
+        }
     }
 }
 
@@ -172,6 +280,8 @@ impl<M: Clone> Clone for CacheMetadata<M> {
     fn clone(&self) -> Self {
         Self {
             size: self.size,
+            hash: self.hash,
+            weights: self.weights,
             last_accessed: self.last_accessed,
             create_time: self.create_time,
             algorithm: self.algorithm.clone(),
@@ -183,6 +293,8 @@ impl<M: fmt::Debug> fmt::Debug for CacheMetadata<M> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("CacheMetadata")
             .field("size", &self.size)
+            .field("hash", &self.hash)
+            .field("weights", &self.weights)
             .field("last_accessed", &self.last_accessed)
             .field("create_time", &self.create_time)
             .field("algorithm", &self.algorithm)
@@ -288,10 +400,41 @@ impl<K, V, M> CacheEntry<K, V, M> {
         }
     }
 
+    /// Returns this entry with its key's hash set to `hash`.
+    #[inline]
+    pub fn with_hash(mut self, hash: u64) -> Self {
+        self.metadata.hash = hash;
+        self
+    }
+
+    /// Returns this entry weighing `weights`.
+    #[inline]
+    pub fn with_weights(mut self, weights: Weights) -> Self {
+        self.metadata.weights = weights;
+        self
+    }
+
+    /// Returns this entry created and last accessed at `now`.
+    #[inline]
+    pub fn with_time(mut self, now: u64) -> Self {
+        self.metadata = self.metadata.with_time(now);
+        self
+    }
+
     /// Updates the last_accessed timestamp to the current time.
+    ///
+    /// Returns the time since the previous access in nanoseconds.
     #[inline]
-    pub fn touch(&mut self) {
-        self.metadata.touch();
+    pub fn touch(&mut self) -> u64 {
+        self.metadata.touch()
+    }
+
+    /// Updates the last_accessed timestamp to `now`.
+    ///
+    /// Returns the time since the previous access in nanoseconds.
+    #[inline]
+    pub fn touch_at(&mut self, now: u64) -> u64 {
+        self.metadata.touch_at(now)
     }
 
     /// Gets the age of this entry in nanoseconds.
@@ -408,6 +551,17 @@ mod tests {
         let _idle_after = entry.idle_nanos();
     }
 
+    #[test]
+    fn test_timestamps_at() {
+        let mut entry: CacheEntry<&str, i32> = CacheEntry::new("key", 42, 1).with_time(100);
+        assert_eq!(entry.metadata.create_time, 100);
+        assert_eq!(entry.touch_at(130), 30);
+        assert_eq!(entry.metadata.age_at(150), 50);
+        assert_eq!(entry.metadata.age_and_idle_at(150), (50, 20));
+        // Timestamps from before the entry was stamped read as zero
+        assert_eq!(entry.metadata.age_and_idle_at(0), (0, 0));
+    }
+
     #[test]
     fn test_metadata_size() {
         let meta: CacheMetadata<()> = CacheMetadata::new(1024);
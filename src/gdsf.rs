@@ -151,6 +151,7 @@
 //!     capacity: NonZeroUsize::new(1000).unwrap(),
 //!     initial_age: 0.0,
 //!     max_size: 10 * 1024 * 1024,  // 10MB
+//!     weight_limits: None,
 //! };
 //! let mut cache: GdsfCache<String, Vec<u8>> = GdsfCache::init(config, None);
 //!
@@ -177,6 +178,7 @@
 //!     capacity: NonZeroUsize::new(10000).unwrap(),
 //!     initial_age: 0.0,
 //!     max_size: 100 * 1024 * 1024,
+//!     weight_limits: None,
 //! };
 //! let mut cache: GdsfCache<String, Vec<u8>> = GdsfCache::init(config, None);
 //!
@@ -193,8 +195,16 @@ extern crate alloc;
 
 use crate::config::GdsfCacheConfig;
 use crate::entry::CacheEntry;
+use crate::key_map::KeyMap;
 use crate::list::{List, ListEntry};
-use crate::metrics::{CacheMetrics, GdsfCacheMetrics};
+use crate::metrics::{
+    CacheMetrics, Clock, CoreCacheMetrics, GdsfCacheMetrics, HotKeys, MetricsRecorder,
+    MetricsSnapshot, MissRatioCurve, NoMetrics, RollingWindow,
+};
+use crate::negative::{Lookup, NegativeCache};
+use crate::refresh::{Freshness, RefreshPolicy, XFetch};
+use crate::side::{HasSideState, SideState};
+use crate::weight::{WeightLimits, Weights};
 
 /// Metadata for GDSF (Greedy Dual-Size Frequency) cache entries.
 ///
@@ -206,10 +216,11 @@ use crate::metrics::{CacheMetrics, GdsfCacheMetrics};
 /// # Priority Calculation
 ///
 /// ```text
-/// priority = (frequency / size) + global_age
+/// priority = (frequency * cost / size) + global_age
 /// ```
 ///
-/// Items with lower priority are evicted first.
+/// Items with lower priority are evicted first. The cost defaults to 1, so
+/// entries cached with `put()` rank by `frequency / size`.
 ///
 /// # Examples
 ///
@@ -219,19 +230,30 @@ use crate::metrics::{CacheMetrics, GdsfCacheMetrics};
 /// let meta = GdsfMeta::new(1, 0.5); // frequency=1, priority=0.5
 /// assert_eq!(meta.frequency, 1);
 /// assert_eq!(meta.priority, 0.5);
+/// assert_eq!(meta.cost, 1);
 /// ```
-#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct GdsfMeta {
     /// Access frequency count.
     pub frequency: u64,
 
-    /// Calculated priority: (frequency / size) + clock.
+    /// Calculated priority: (frequency * cost / size) + clock.
     /// Lower priority = more likely to be evicted.
     pub priority: f64,
+
+    /// Cost of a miss on this item, such as its fetch latency.
+    pub cost: u64,
+}
+
+impl Default for GdsfMeta {
+    fn default() -> Self {
+        Self::new(0, 0.0)
+    }
 }
 
 impl GdsfMeta {
-    /// Creates a new GDSF metadata with the specified frequency and priority.
+    /// Creates a new GDSF metadata with the specified frequency and priority,
+    /// and a cost of 1.
     ///
     /// # Arguments
     ///
@@ -242,9 +264,16 @@ impl GdsfMeta {
         Self {
             frequency,
             priority,
+            cost: 1,
         }
     }
 
+    /// Returns this metadata with the miss cost set to `cost`.
+    #[inline]
+    pub fn with_cost(self, cost: u64) -> Self {
+        Self { cost, ..self }
+    }
+
     /// Increments the frequency counter and returns the new value.
     #[inline]
     pub fn increment(&mut self) -> u64 {
@@ -252,7 +281,8 @@ impl GdsfMeta {
         self.frequency
     }
 
-    /// Calculates and updates the priority based on frequency, size, and global age.
+    /// Calculates and updates the priority based on frequency, cost, size, and
+    /// global age.
     ///
     /// # Arguments
     ///
@@ -267,55 +297,80 @@ impl GdsfMeta {
         self.priority = if size == 0 {
             f64::INFINITY
         } else {
-            (self.frequency as f64 / size as f64) + global_age
+            (self.frequency as f64 * self.cost as f64 / size as f64) + global_age
         };
         self.priority
     }
 }
+
+/// How a [`GdsfCache`] ranks its entries.
+///
+/// Both modes add the global age `L`, which rises to the priority of each
+/// evicted entry, so entries that are not accessed again age out.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum GdsfMode {
+    /// Greedy Dual-Size Frequency: `frequency * cost / size + L`.
+    #[default]
+    Gdsf,
+    /// Pure GreedyDual-Size: `cost / size + L`, ignoring the access count.
+    ///
+    /// A hit restores the entry's priority against the current `L` instead
+    /// of adding to it, so only recency, cost and size matter.
+    GreedyDualSize,
+}
 use alloc::boxed::Box;
 use alloc::collections::BTreeMap;
 use alloc::string::String;
+use alloc::sync::Arc;
 use alloc::vec::Vec;
 use core::borrow::Borrow;
 use core::hash::{BuildHasher, Hash};
 use core::num::NonZeroUsize;
+use core::time::Duration;
 
 #[cfg(feature = "hashbrown")]
 use hashbrown::DefaultHashBuilder;
-#[cfg(feature = "hashbrown")]
-use hashbrown::HashMap;
 
 #[cfg(not(feature = "hashbrown"))]
 use std::collections::hash_map::RandomState as DefaultHashBuilder;
-#[cfg(not(feature = "hashbrown"))]
-use std::collections::HashMap;
 
 /// Internal GDSF segment containing the actual cache algorithm.
 ///
 /// Uses `CacheEntry<K, V, GdsfMeta>` as the unified entry type. The map stores
 /// raw pointers to list nodes, and all entry data (key, value, size, metadata)
 /// is stored in the `CacheEntry`.
-pub(crate) struct GdsfSegment<K, V, S = DefaultHashBuilder> {
+pub(crate) struct GdsfSegment<K, V, S = DefaultHashBuilder, M = GdsfCacheMetrics> {
     config: GdsfCacheConfig,
     global_age: f64,
     min_priority: f64,
+    /// Priority formula
+    mode: GdsfMode,
     /// Maps keys to node pointers. The node contains CacheEntry with all data.
-    map: HashMap<K, *mut ListEntry<CacheEntry<K, V, GdsfMeta>>, S>,
+    map: KeyMap<K, *mut ListEntry<CacheEntry<K, V, GdsfMeta>>, S>,
     /// Priority lists: key is (priority * 1000) as u64 for BTreeMap ordering
     priority_lists: BTreeMap<u64, List<CacheEntry<K, V, GdsfMeta>>>,
-    metrics: GdsfCacheMetrics,
+    metrics: M,
+    /// Negative entries, tags, weight usage, hot keys and refresh policy
+    side: SideState<K>,
     /// Current total size of cached content (sum of entry sizes)
     current_size: u64,
 }
 
 // SAFETY: GdsfSegment owns all data and raw pointers point only to nodes owned by
 // `priority_lists`. Concurrent access is safe when wrapped in proper synchronization primitives.
-unsafe impl<K: Send, V: Send, S: Send> Send for GdsfSegment<K, V, S> {}
-
-// SAFETY: All mutation requires &mut self; shared references cannot cause data races.
-unsafe impl<K: Send, V: Send, S: Sync> Sync for GdsfSegment<K, V, S> {}
-
-impl<K: Hash + Eq, V: Clone, S: BuildHasher> GdsfSegment<K, V, S> {
+unsafe impl<K: Send, V: Send, S: Send, M: Send> Send for GdsfSegment<K, V, S, M> {}
+
+// SAFETY: All mutation requires &mut self; shared references only hand out
+// `&K`/`&V`, so sharing requires K and V to be Sync.
+unsafe impl<K: Sync, V: Sync, S: Sync, M: Sync> Sync for GdsfSegment<K, V, S, M> {}
+
+impl<K, V, S, M> GdsfSegment<K, V, S, M>
+where
+    K: Hash + Eq,
+    V: Clone,
+    S: BuildHasher,
+    M: MetricsRecorder<GdsfCacheMetrics>,
+{
     /// Creates a new GDSF segment from a configuration.
     ///
     /// This is the **only** way to create a GDSF segment. All configuration
@@ -327,16 +382,28 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> GdsfSegment<K, V, S> {
     /// * `hasher` - Hash builder for the internal HashMap
     #[allow(dead_code)] // Used by concurrent module when feature is enabled
     pub(crate) fn init(config: GdsfCacheConfig, hasher: S) -> Self {
-        let map_capacity = config.capacity.get().next_power_of_two();
-        GdsfSegment {
+        Self::init_with_capacity(config, hasher, config.capacity.get())
+    }
+
+    /// Like [`init()`](Self::init), but preallocates room for only
+    /// `capacity` entries.
+    #[allow(dead_code)] // Used by concurrent module when feature is enabled
+    pub(crate) fn init_with_capacity(config: GdsfCacheConfig, hasher: S, capacity: usize) -> Self {
+        let map_capacity = capacity.next_power_of_two();
+        let mut segment = GdsfSegment {
             global_age: config.initial_age,
             min_priority: 0.0,
-            map: HashMap::with_capacity_and_hasher(map_capacity, hasher),
+            mode: GdsfMode::default(),
+            map: KeyMap::with_capacity_and_hasher(map_capacity, hasher),
             priority_lists: BTreeMap::new(),
-            metrics: GdsfCacheMetrics::new(config.max_size),
+            metrics: M::init(|| GdsfCacheMetrics::new(config.max_size)),
+            side: SideState::new(config.weight_limits),
             current_size: 0,
             config,
-        }
+        };
+        // Limits from the config show in the metrics before any entry
+        segment.publish_weights();
+        segment
     }
 
     #[inline]
@@ -359,6 +426,14 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> GdsfSegment<K, V, S> {
         self.global_age
     }
 
+    pub(crate) fn set_mode(&mut self, mode: GdsfMode) {
+        self.mode = mode;
+    }
+
+    pub(crate) fn mode(&self) -> GdsfMode {
+        self.mode
+    }
+
     /// Returns the current total size of cached content.
     #[inline]
     pub(crate) fn current_size(&self) -> u64 {
@@ -372,25 +447,75 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> GdsfSegment<K, V, S> {
     }
 
     #[inline]
-    pub(crate) fn metrics(&self) -> &GdsfCacheMetrics {
-        &self.metrics
+    pub(crate) fn metrics(&self) -> Option<&GdsfCacheMetrics> {
+        self.metrics.collected()
     }
 
     #[inline]
     pub(crate) fn record_miss(&mut self, object_size: u64) {
-        self.metrics.core.record_miss(object_size);
+        self.metrics.record(|m| m.core.record_miss(object_size));
+    }
+
+    /// Records a miss whose fetch cost `cost`.
+    pub(crate) fn record_miss_with_cost(&mut self, object_size: u64, cost: u64) {
+        self.metrics.record(|m| {
+            m.core.record_miss(object_size);
+            m.record_cost_missed(cost);
+        });
     }
 
-    fn calculate_priority(&self, frequency: u64, size: u64) -> f64 {
+    /// Like [`get_hashed()`](Self::get_hashed), also returning the value's
+    /// freshness. Expired values are removed and reported as missing.
+    pub(crate) fn get_fresh_hashed<Q>(&mut self, hash: u64, key: &Q) -> Option<(V, Freshness)>
+    where
+        K: Borrow<Q> + Clone,
+        Q: ?Sized + Eq,
+    {
+        match self.peek_freshness_hashed(hash, key) {
+            Some(Freshness::Expired) => {
+                self.remove_hashed(hash, key);
+                self.record_lookup(hash);
+                None
+            }
+            freshness => {
+                let freshness = freshness.unwrap_or(Freshness::Fresh);
+                self.get_hashed(hash, key).map(|value| (value, freshness))
+            }
+        }
+    }
+
+    /// Like [`get_hashed()`](Self::get_hashed), telling a key known to be
+    /// absent apart from one that is not cached.
+    pub(crate) fn lookup_hashed<Q>(&mut self, hash: u64, key: &Q) -> Lookup<V>
+    where
+        K: Borrow<Q> + Clone,
+        Q: ?Sized + Eq,
+    {
+        if self.map.contains_key(hash, key) {
+            return self.get_hashed(hash, key).map_or(Lookup::Miss, Lookup::Hit);
+        }
+        self.lookup_uncached(hash, key)
+    }
+
+    pub(crate) fn reset_metrics(&mut self) {
+        self.metrics.record(|m| m.reset());
+    }
+
+    fn calculate_priority(&self, frequency: u64, cost: u64, size: u64) -> f64 {
         if size == 0 {
             return f64::INFINITY;
         }
-        (frequency as f64 / size as f64) + self.global_age
+        let frequency = match self.mode {
+            GdsfMode::Gdsf => frequency,
+            GdsfMode::GreedyDualSize => 1,
+        };
+        (frequency as f64 * cost as f64 / size as f64) + self.global_age
     }
 
     unsafe fn update_priority_by_node(
         &mut self,
         node: *mut ListEntry<CacheEntry<K, V, GdsfMeta>>,
+        hash: u64,
     ) -> *mut ListEntry<CacheEntry<K, V, GdsfMeta>>
     where
         K: Clone + Hash + Eq,
@@ -404,8 +529,8 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> GdsfSegment<K, V, S> {
 
         meta.increment();
 
-        let global_age = self.global_age;
-        let new_priority = meta.calculate_priority(size, global_age);
+        let new_priority = self.calculate_priority(meta.frequency, meta.cost, size);
+        meta.priority = new_priority;
 
         let old_priority_key = (old_priority * 1000.0) as u64;
         let new_priority_key = (new_priority * 1000.0) as u64;
@@ -447,7 +572,7 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> GdsfSegment<K, V, S> {
             .attach_from_other_list(entry_ptr);
 
         // Update map with new node pointer
-        self.map.insert(key_cloned, entry_ptr);
+        self.map.insert(hash, key_cloned, entry_ptr);
         entry_ptr
     }
 
@@ -456,17 +581,33 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> GdsfSegment<K, V, S> {
         K: Borrow<Q> + Clone,
         Q: ?Sized + Hash + Eq,
     {
-        if let Some(&node) = self.map.get(key) {
+        let hash = self.map.hash(key);
+        self.get_hashed(hash, key)
+    }
+
+    /// Like [`get()`](Self::get), for a key whose hash is already known.
+    pub(crate) fn get_hashed<Q>(&mut self, hash: u64, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q> + Clone,
+        Q: ?Sized + Eq,
+    {
+        self.record_lookup(hash);
+        if let Some(&node) = self.map.get(hash, key) {
             unsafe {
                 // SAFETY: node comes from our map
-                let entry = (*node).get_value();
+                let entry = (*node).get_value_mut();
+                let interval = entry.touch_at(self.side.now_nanos());
+                self.side.record_hit(hash, &entry.key, entry.metadata.size);
                 let entry_size = entry.metadata.size;
                 let meta = &entry.metadata.algorithm;
-                self.metrics.core.record_hit(entry_size);
-                self.metrics
-                    .record_item_access(meta.frequency, entry.metadata.size, meta.priority);
-
-                let new_node = self.update_priority_by_node(node);
+                self.metrics.record(|m| {
+                    m.core.record_hit(entry_size);
+                    m.core.record_access_interval(interval);
+                    m.record_item_access(meta.frequency, entry.metadata.size, meta.priority);
+                    m.record_cost_saved(meta.cost);
+                });
+
+                let new_node = self.update_priority_by_node(node, hash);
                 let value = (*new_node).get_value().value.clone();
                 Some(value)
             }
@@ -480,17 +621,33 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> GdsfSegment<K, V, S> {
         K: Borrow<Q> + Clone,
         Q: ?Sized + Hash + Eq,
     {
-        if let Some(&node) = self.map.get(key) {
+        let hash = self.map.hash(key);
+        self.get_mut_hashed(hash, key)
+    }
+
+    /// Like [`get_mut()`](Self::get_mut), for a key whose hash is already known.
+    pub(crate) fn get_mut_hashed<Q>(&mut self, hash: u64, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q> + Clone,
+        Q: ?Sized + Eq,
+    {
+        self.record_lookup(hash);
+        if let Some(&node) = self.map.get(hash, key) {
             unsafe {
                 // SAFETY: node comes from our map
-                let entry = (*node).get_value();
+                let entry = (*node).get_value_mut();
+                let interval = entry.touch_at(self.side.now_nanos());
+                self.side.record_hit(hash, &entry.key, entry.metadata.size);
                 let entry_size = entry.metadata.size;
                 let meta = &entry.metadata.algorithm;
-                self.metrics.core.record_hit(entry_size);
-                self.metrics
-                    .record_item_access(meta.frequency, entry.metadata.size, meta.priority);
-
-                let new_node = self.update_priority_by_node(node);
+                self.metrics.record(|m| {
+                    m.core.record_hit(entry_size);
+                    m.core.record_access_interval(interval);
+                    m.record_item_access(meta.frequency, entry.metadata.size, meta.priority);
+                    m.record_cost_saved(meta.cost);
+                });
+
+                let new_node = self.update_priority_by_node(node, hash);
                 let entry_mut = (*new_node).get_value_mut();
                 Some(&mut entry_mut.value)
             }
@@ -507,12 +664,95 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> GdsfSegment<K, V, S> {
     where
         K: Clone,
     {
+        let hash = self.map.hash(&key);
+        self.put_hashed(hash, key, val, size)
+    }
+
+    /// Like [`put()`](Self::put), for a key whose hash is already known.
+    pub(crate) fn put_hashed(&mut self, hash: u64, key: K, val: V, size: u64) -> Option<Vec<(K, V)>>
+    where
+        K: Clone,
+    {
+        self.put_hashed_with(hash, key, val, size, |key, value, _| (key, value))
+    }
+
+    /// Like [`put_hashed()`](Self::put_hashed), returning each evicted entry
+    /// as `evicted_entry(key, value, size)`.
+    pub(crate) fn put_hashed_with<E>(
+        &mut self,
+        hash: u64,
+        key: K,
+        val: V,
+        size: u64,
+        evicted_entry: impl FnMut(K, V, u64) -> E,
+    ) -> Option<Vec<E>>
+    where
+        K: Clone,
+    {
+        self.put_costed_hashed_with(hash, key, val, size, None, None, evicted_entry)
+    }
+
+    /// Like [`put_hashed_with()`](Self::put_hashed_with), for an entry
+    /// weighing `weights`. Without them, a replaced value's weights are kept
+    /// and a new entry weighs nothing.
+    fn put_weighted_hashed_with<E>(
+        &mut self,
+        hash: u64,
+        key: K,
+        val: V,
+        size: u64,
+        weights: Option<Weights>,
+        evicted_entry: impl FnMut(K, V, u64) -> E,
+    ) -> Option<Vec<E>>
+    where
+        K: Clone,
+    {
+        self.put_costed_hashed_with(hash, key, val, size, None, weights, evicted_entry)
+    }
+
+    /// Like [`put_hashed()`](Self::put_hashed), for an entry whose misses
+    /// cost `cost`.
+    pub(crate) fn put_with_cost_hashed(
+        &mut self,
+        hash: u64,
+        key: K,
+        val: V,
+        size: u64,
+        cost: u64,
+    ) -> Option<Vec<(K, V)>>
+    where
+        K: Clone,
+    {
+        self.put_costed_hashed_with(hash, key, val, size, Some(cost), None, |key, value, _| {
+            (key, value)
+        })
+    }
+
+    /// Like [`put_hashed_with()`](Self::put_hashed_with), for an entry whose
+    /// misses cost `cost` and that weighs `weights`. Without them, a replaced
+    /// value's cost and weights are kept, and a new entry costs 1 and weighs
+    /// nothing.
+    #[allow(clippy::too_many_arguments)]
+    fn put_costed_hashed_with<E>(
+        &mut self,
+        hash: u64,
+        key: K,
+        val: V,
+        size: u64,
+        cost: Option<u64>,
+        weights: Option<Weights>,
+        mut evicted_entry: impl FnMut(K, V, u64) -> E,
+    ) -> Option<Vec<E>>
+    where
+        K: Clone,
+    {
+        self.side.forget(hash, &key);
         if size == 0 {
             return None;
         }
 
         // Check if key exists - update existing entry
-        if let Some(&node) = self.map.get(&key) {
+        if let Some(&node) = self.map.get(hash, &key) {
             unsafe {
                 // SAFETY: node comes from our map
                 let entry = (*node).get_value_mut();
@@ -520,6 +760,7 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> GdsfSegment<K, V, S> {
                 let meta = &mut entry.metadata.algorithm;
                 let old_priority_key = (meta.priority * 1000.0) as u64;
                 let frequency = meta.frequency;
+                let cost = cost.unwrap_or(meta.cost);
 
                 // Remove from old priority list
                 let list = self.priority_lists.get_mut(&old_priority_key).unwrap();
@@ -534,21 +775,26 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> GdsfSegment<K, V, S> {
                 // Discard old key/value since replacement is not eviction
                 let _ = (cache_entry.key, cache_entry.value);
                 let _ = Box::from_raw(entry_ptr);
+                let weights = weights.unwrap_or(cache_entry.metadata.weights);
+                self.release_weights(&cache_entry.metadata.weights);
 
                 // Update size tracking
                 self.current_size = self.current_size.saturating_sub(old_size);
                 self.current_size += size;
 
                 // Create new entry with updated values but preserved frequency
-                let new_priority = self.calculate_priority(frequency, size);
+                let new_priority = self.calculate_priority(frequency, cost, size);
                 let new_priority_key = (new_priority * 1000.0) as u64;
 
                 let new_entry = CacheEntry::with_algorithm_metadata(
                     key.clone(),
                     val,
                     size,
-                    GdsfMeta::new(frequency, new_priority),
-                );
+                    GdsfMeta::new(frequency, new_priority).with_cost(cost),
+                )
+                .with_hash(hash)
+                .with_weights(weights)
+                .with_time(self.side.now_nanos());
 
                 let capacity = self.cap();
                 let list = self
@@ -557,19 +803,23 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> GdsfSegment<K, V, S> {
                     .or_insert_with(|| List::new(capacity));
 
                 if let Some(new_node) = list.add(new_entry) {
-                    self.map.insert(key, new_node);
-                    self.metrics.core.record_size_change(old_size, size);
-                    self.metrics.core.bytes_written_to_cache += size;
+                    self.map.insert(hash, key, new_node);
+                    self.add_weights(&weights);
+                    self.metrics.record(|m| {
+                        m.core.record_size_change(old_size, size);
+                        m.core.bytes_written_to_cache += size;
+                    });
                     // Replacement is not eviction - don't return the old value
                     return None;
                 } else {
-                    self.map.remove(&key);
+                    self.map.remove(hash, &key);
                     return None;
                 }
             }
         }
 
         // New entry - check capacity and size limits
+        let weights = weights.unwrap_or_default();
         let capacity = self.config.capacity.get();
         let max_size = self.config.max_size;
 
@@ -577,15 +827,16 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> GdsfSegment<K, V, S> {
         while self.len() >= capacity
             || (self.current_size + size > max_size && !self.map.is_empty())
         {
-            if let Some(entry) = self.evict() {
-                self.metrics.core.evictions += 1;
-                evicted.push(entry);
+            if let Some((old_key, old_value, old_size)) = self.evict() {
+                self.metrics.record(|m| m.core.count_eviction());
+                evicted.push(evicted_entry(old_key, old_value, old_size));
             } else {
                 break;
             }
         }
 
-        let priority = self.calculate_priority(1, size);
+        let cost = cost.unwrap_or(1);
+        let priority = self.calculate_priority(1, cost, size);
         let priority_key = (priority * 1000.0) as u64;
 
         let cap = self.config.capacity;
@@ -594,21 +845,30 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> GdsfSegment<K, V, S> {
             .entry(priority_key)
             .or_insert_with(|| List::new(cap));
 
-        let cache_entry =
-            CacheEntry::with_algorithm_metadata(key.clone(), val, size, GdsfMeta::new(1, priority));
+        let cache_entry = CacheEntry::with_algorithm_metadata(
+            key.clone(),
+            val,
+            size,
+            GdsfMeta::new(1, priority).with_cost(cost),
+        )
+        .with_hash(hash)
+        .with_weights(weights)
+        .with_time(self.side.now_nanos());
 
         if let Some(node) = list.add(cache_entry) {
-            self.map.insert(key, node);
+            self.map.insert(hash, key, node);
             self.current_size += size;
+            self.add_weights(&weights);
 
             if self.len() == 1 || priority < self.min_priority {
                 self.min_priority = priority;
             }
 
-            self.metrics.core.record_insertion(size);
-            self.metrics
-                .record_item_cached(size, self.metrics.average_item_size());
-            self.metrics.record_item_access(1, size, priority);
+            self.metrics.record(|m| {
+                m.core.record_insertion(size);
+                m.record_item_cached(size, m.average_item_size());
+                m.record_item_access(1, size, priority);
+            });
         }
 
         if evicted.is_empty() {
@@ -626,8 +886,9 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> GdsfSegment<K, V, S> {
     /// Eviction metrics are only recorded when the cache internally evicts
     /// entries to make room during `put()` operations.
     ///
-    /// Returns `None` if the cache is empty.
-    fn evict(&mut self) -> Option<(K, V)> {
+    /// Returns the evicted key, value and size, or `None` if the cache is
+    /// empty.
+    fn evict(&mut self) -> Option<(K, V, u64)> {
         if self.is_empty() {
             return None;
         }
@@ -643,23 +904,32 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> GdsfSegment<K, V, S> {
             let cache_entry = (*entry_ptr).take_value();
             let evicted_size = cache_entry.metadata.size;
             let priority_to_update = cache_entry.metadata.algorithm.priority;
+            let evicted_cost = cache_entry.metadata.algorithm.cost;
 
             // Update global age to the evicted item's priority (GDSF aging)
             self.global_age = priority_to_update;
-            self.metrics.core.record_removal(evicted_size);
-            self.metrics.record_size_based_eviction();
-            self.metrics.record_aging_event(priority_to_update);
-
-            self.map.remove(&cache_entry.key);
+            self.metrics.record(|m| {
+                m.core.record_removal(evicted_size);
+                let (age, idle) = cache_entry.metadata.age_and_idle_at(self.side.now_nanos());
+                m.core.record_eviction_age(age, idle);
+                m.record_size_based_eviction();
+                m.record_aging_event(priority_to_update);
+                m.record_cost_evicted(evicted_cost);
+            });
+
+            let hash = cache_entry.metadata.hash;
+            self.map.remove(hash, &cache_entry.key);
+            self.side.untag(hash, &cache_entry.key);
             self.current_size = self.current_size.saturating_sub(evicted_size);
 
             // Remove empty priority list
             if list.is_empty() {
                 self.priority_lists.remove(&min_priority_key);
             }
+            self.release_weights(&cache_entry.metadata.weights);
 
             let _ = Box::from_raw(entry_ptr);
-            Some((cache_entry.key, cache_entry.value))
+            Some((cache_entry.key, cache_entry.value, evicted_size))
         }
     }
 
@@ -668,7 +938,18 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> GdsfSegment<K, V, S> {
         K: Borrow<Q>,
         Q: ?Sized + Hash + Eq,
     {
-        if let Some(node) = self.map.remove(key) {
+        let hash = self.map.hash(key);
+        self.remove_hashed(hash, key)
+    }
+
+    /// Like [`remove()`](Self::remove), for a key whose hash is already known.
+    pub(crate) fn remove_hashed<Q>(&mut self, hash: u64, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Eq,
+    {
+        self.side.forget(hash, key);
+        if let Some(node) = self.map.remove(hash, key) {
             unsafe {
                 // SAFETY: node comes from our map
                 // Read priority before removal — needed to find the correct priority list
@@ -686,7 +967,8 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> GdsfSegment<K, V, S> {
                 let cache_entry = (*entry_ptr).take_value();
                 let removed_size = cache_entry.metadata.size;
                 self.current_size = self.current_size.saturating_sub(removed_size);
-                self.metrics.core.record_removal(removed_size);
+                self.release_weights(&cache_entry.metadata.weights);
+                self.metrics.record(|m| m.core.record_removal(removed_size));
                 let _ = Box::from_raw(entry_ptr);
 
                 Some(cache_entry.value)
@@ -697,6 +979,7 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> GdsfSegment<K, V, S> {
     }
 
     pub(crate) fn clear(&mut self) {
+        self.clear_side();
         self.map.clear();
         self.priority_lists.clear();
         self.global_age = 0.0;
@@ -711,7 +994,7 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> GdsfSegment<K, V, S> {
         K: Borrow<Q>,
         Q: ?Sized + Hash + Eq,
     {
-        self.map.contains_key(key)
+        self.map.contains_key(self.map.hash(key), key)
     }
 
     /// Returns a reference to the value without updating priority or access metadata.
@@ -723,16 +1006,104 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> GdsfSegment<K, V, S> {
         K: Borrow<Q>,
         Q: ?Sized + Hash + Eq,
     {
-        let &node = self.map.get(key)?;
+        let &node = self.map.get(self.map.hash(key), key)?;
         unsafe {
             // SAFETY: node comes from our map, so it's a valid pointer
             let entry = (*node).get_value();
             Some(&entry.value)
         }
     }
+
+    /// Returns the stored key and value without updating access metadata.
+    ///
+    /// Used by the concurrent read path, which looks entries up under a shared
+    /// lock and needs an owned copy of the key to record the access.
+    #[allow(dead_code)] // Used by concurrent module when feature is enabled
+    pub(crate) fn peek_key_value_hashed<Q>(&self, hash: u64, key: &Q) -> Option<(&K, &V)>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Eq,
+    {
+        let &node = self.map.get(hash, key)?;
+        unsafe {
+            // SAFETY: node comes from our map, so it's a valid pointer
+            let entry = (*node).get_value();
+            Some((&entry.key, &entry.value))
+        }
+    }
+
+    /// Evicts the entry this segment's policy would evict next.
+    ///
+    /// Used by the concurrent caches to enforce a budget shared by all segments.
+    #[allow(dead_code)] // Used by concurrent module when feature is enabled
+    pub(crate) fn pop_victim(&mut self) -> Option<(K, V)> {
+        let (key, value, _) = self.evict()?;
+        self.metrics.record(|m| m.core.count_eviction());
+        Some((key, value))
+    }
+}
+
+impl<K, V, S, M> HasSideState for GdsfSegment<K, V, S, M>
+where
+    K: Hash + Eq,
+    V: Clone,
+    S: BuildHasher,
+    M: MetricsRecorder<GdsfCacheMetrics>,
+{
+    type Key = K;
+    type Value = V;
+    type Meta = GdsfMeta;
+    type Hasher = S;
+
+    fn side(&self) -> &SideState<K> {
+        &self.side
+    }
+
+    fn side_mut(&mut self) -> &mut SideState<K> {
+        &mut self.side
+    }
+
+    fn entries(&self) -> &KeyMap<K, *mut ListEntry<CacheEntry<K, V, GdsfMeta>>, S> {
+        &self.map
+    }
+
+    fn record_core(&mut self, record: impl FnOnce(&mut CoreCacheMetrics)) {
+        self.metrics.record(|m| record(&mut m.core));
+    }
+
+    fn insert_entry(
+        &mut self,
+        hash: u64,
+        key: K,
+        value: V,
+        size: u64,
+        weights: Option<Weights>,
+    ) -> Option<Vec<(K, V)>>
+    where
+        K: Clone,
+    {
+        self.put_weighted_hashed_with(hash, key, value, size, weights, |key, value, _| {
+            (key, value)
+        })
+    }
+
+    fn remove_entry<Q>(&mut self, hash: u64, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q> + Clone,
+        Q: ?Sized + Eq,
+    {
+        self.remove_hashed(hash, key)
+    }
+
+    fn evict_entry(&mut self) -> Option<(K, V)>
+    where
+        K: Clone,
+    {
+        self.pop_victim()
+    }
 }
 
-impl<K, V, S> core::fmt::Debug for GdsfSegment<K, V, S> {
+impl<K, V, S, M> core::fmt::Debug for GdsfSegment<K, V, S, M> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.debug_struct("GdsfSegment")
             .field("capacity", &self.config.capacity)
@@ -744,11 +1115,17 @@ impl<K, V, S> core::fmt::Debug for GdsfSegment<K, V, S> {
 
 /// An implementation of a Greedy Dual-Size Frequency (GDSF) cache.
 #[derive(Debug)]
-pub struct GdsfCache<K, V, S = DefaultHashBuilder> {
-    segment: GdsfSegment<K, V, S>,
+pub struct GdsfCache<K, V, S = DefaultHashBuilder, M = GdsfCacheMetrics> {
+    segment: GdsfSegment<K, V, S, M>,
 }
 
-impl<K: Hash + Eq, V: Clone, S: BuildHasher> GdsfCache<K, V, S> {
+impl<K, V, S, M> GdsfCache<K, V, S, M>
+where
+    K: Hash + Eq,
+    V: Clone,
+    S: BuildHasher,
+    M: MetricsRecorder<GdsfCacheMetrics>,
+{
     #[inline]
     pub fn cap(&self) -> NonZeroUsize {
         self.segment.cap()
@@ -781,11 +1158,270 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> GdsfCache<K, V, S> {
         self.segment.global_age()
     }
 
+    /// Ranks entries by `mode` from now on.
+    ///
+    /// Cached entries keep their priority until they are accessed or
+    /// replaced, so the mode is best set before inserting.
+    pub fn set_mode(&mut self, mode: GdsfMode) {
+        self.segment.set_mode(mode);
+    }
+
+    /// Returns how entries are ranked.
+    pub fn mode(&self) -> GdsfMode {
+        self.segment.mode()
+    }
+
     #[inline]
     pub fn record_miss(&mut self, object_size: u64) {
         self.segment.record_miss(object_size);
     }
 
+    /// Records a miss like [`record_miss()`](Self::record_miss), whose fetch
+    /// cost `cost`.
+    ///
+    /// Counted as `cost_missed` in the metrics, against the `cost_saved` by
+    /// hits.
+    pub fn record_miss_with_cost(&mut self, object_size: u64, cost: u64) {
+        self.segment.record_miss_with_cost(object_size, cost);
+    }
+
+    /// Tracks the metrics of the most recent requests in `window`, replacing
+    /// any previous window, or stops tracking them with `None`.
+    ///
+    /// The window is reported as `window_*` keys by
+    /// [`metrics()`](CacheMetrics::metrics). Does nothing without metrics.
+    pub fn set_metrics_window(&mut self, window: Option<RollingWindow>) {
+        self.segment.set_metrics_window(window);
+    }
+
+    /// Estimates the miss ratio an LRU cache would have at other capacities
+    /// with `curve`, replacing any previous curve, or stops with `None`.
+    ///
+    /// Every [`get()`](Self::get) and [`get_mut()`](Self::get_mut) lookup is
+    /// sampled, hit or miss. The curve is reported as `mrc_*` keys by
+    /// [`metrics()`](CacheMetrics::metrics). Does nothing without metrics.
+    pub fn set_miss_ratio_curve(&mut self, curve: Option<MissRatioCurve>) {
+        self.segment.set_miss_ratio_curve(curve);
+    }
+
+    /// Ranks the keys of lookups that hit by accesses and by bytes served
+    /// with `hot_keys`, replacing any previous tracker, or stops with `None`.
+    ///
+    /// Works with and without metrics. Read the rankings through
+    /// [`hot_keys()`](Self::hot_keys).
+    pub fn set_hot_keys(&mut self, hot_keys: Option<HotKeys<K>>) {
+        self.segment.set_hot_keys(hot_keys);
+    }
+
+    /// Returns the hot-key tracker, if one is set.
+    pub fn hot_keys(&self) -> Option<&HotKeys<K>> {
+        self.segment.hot_keys()
+    }
+
+    /// Remembers keys known to be absent from the backing store in
+    /// `negative`, replacing any previous negative cache, or stops with
+    /// `None`.
+    ///
+    /// Works with and without metrics. Mark keys absent with
+    /// [`put_absent()`](Self::put_absent) and tell them apart from keys that
+    /// are not cached with [`lookup()`](Self::lookup).
+    pub fn set_negative_cache(&mut self, negative: Option<NegativeCache<K>>) {
+        self.segment.set_negative_cache(negative);
+    }
+
+    /// Returns the negative cache, if one is set.
+    pub fn negative_cache(&self) -> Option<&NegativeCache<K>> {
+        self.segment.negative_cache()
+    }
+
+    /// Ages entries from their creation under `policy`, replacing any
+    /// previous policy, or stops with `None`.
+    ///
+    /// The policy only affects [`get_fresh()`](Self::get_fresh); other
+    /// lookups serve entries regardless of their age.
+    pub fn set_refresh_policy(&mut self, policy: Option<RefreshPolicy>) {
+        self.segment.set_refresh_policy(policy);
+    }
+
+    /// Returns the refresh policy, if one is set.
+    pub fn refresh_policy(&self) -> Option<RefreshPolicy> {
+        self.segment.refresh_policy()
+    }
+
+    /// Expires values early at random under `xfetch`, replacing any
+    /// previous mode, or stops with `None`.
+    ///
+    /// Only applies while a refresh policy is set: fresh values are reported
+    /// as stale by [`get_fresh()`](Self::get_fresh) with a probability that
+    /// grows as the `ttl` nears, and stay cached.
+    pub fn set_xfetch(&mut self, xfetch: Option<XFetch>) {
+        self.segment.set_xfetch(xfetch);
+    }
+
+    /// Returns the early expiration mode, if one is set.
+    pub fn xfetch(&self) -> Option<&XFetch> {
+        self.segment.xfetch()
+    }
+
+    /// Stamps and ages entries with `clock`, or with the system time under
+    /// `None`, the default.
+    ///
+    /// Ages drive the refresh policy and the age and idle-time metrics.
+    /// Entries keep the timestamps they were given, so set the clock before
+    /// inserting any. Without the `std` feature there is no system time, and
+    /// entries only age under a clock.
+    pub fn set_clock(&mut self, clock: Option<Arc<dyn Clock>>) {
+        self.segment.set_clock(clock);
+    }
+
+    /// Returns the clock entries are stamped with, if one is set.
+    pub fn clock(&self) -> Option<&Arc<dyn Clock>> {
+        self.segment.clock()
+    }
+
+    /// Looks up `key` like [`get()`](Self::get), also returning the value's
+    /// [`Freshness`] under the refresh policy.
+    ///
+    /// Stale values and values in the grace window are returned for the
+    /// caller to refresh, e.g. with [`put()`](Self::put). Values past the
+    /// grace window are removed and reported as missing. Without a policy
+    /// every value is fresh.
+    pub fn get_fresh<Q>(&mut self, key: &Q) -> Option<(V, Freshness)>
+    where
+        K: Borrow<Q> + Clone,
+        Q: ?Sized + Hash + Eq,
+    {
+        let hash = self.segment.map.hash(key);
+        self.segment.get_fresh_hashed(hash, key)
+    }
+
+    /// Inserts a key-value pair like [`put()`](Self::put), tagging it with
+    /// `tags`.
+    ///
+    /// The tags replace any previous tags of the key, and are dropped with
+    /// the entry when it is replaced by [`put()`](Self::put), removed or
+    /// evicted. See [`invalidate_tag()`](Self::invalidate_tag).
+    pub fn put_tagged(&mut self, key: K, value: V, size: u64, tags: &[u64]) -> Option<Vec<(K, V)>>
+    where
+        K: Clone,
+    {
+        let hash = self.segment.map.hash(&key);
+        self.segment.put_tagged_hashed(hash, key, value, size, tags)
+    }
+
+    /// Weighs entries in the dimensions of `limits`, replacing any previous
+    /// limits, or stops with `None`.
+    ///
+    /// Cached entries keep their weights, so they count against the new
+    /// limits. Limits can also be set up front with
+    /// `weight_limits` in the config. See
+    /// [`put_weighted()`](Self::put_weighted).
+    pub fn set_weight_limits(&mut self, limits: Option<WeightLimits>) {
+        self.segment.set_weight_limits(limits);
+    }
+
+    /// Returns the weight limits, if set.
+    pub fn weight_limits(&self) -> Option<&WeightLimits> {
+        self.segment.weight_limits()
+    }
+
+    /// Inserts a key-value pair like [`put()`](Self::put), weighing it with
+    /// `weights`.
+    ///
+    /// Entries are evicted in the cache's usual order until every dimension
+    /// of the [weight limits](Self::set_weight_limits) fits `weights`, then
+    /// the entry is inserted even if it exceeds a limit on its own. The
+    /// weights replace any previous weights of the key, are kept when
+    /// [`put()`](Self::put) replaces its value, and are dropped with the
+    /// entry when it is removed or evicted. Without weight limits the weights
+    /// are kept but evict nothing.
+    pub fn put_weighted(
+        &mut self,
+        key: K,
+        value: V,
+        size: u64,
+        weights: Weights,
+    ) -> Option<Vec<(K, V)>>
+    where
+        K: Clone,
+    {
+        let hash = self.segment.map.hash(&key);
+        self.segment
+            .put_weighted_hashed(hash, key, value, size, weights)
+    }
+
+    /// Returns the weights of `key`, which are zero if it is unweighted or
+    /// not cached.
+    pub fn weights<Q>(&self, key: &Q) -> Weights
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let hash = self.segment.map.hash(key);
+        self.segment.weights_hashed(hash, key)
+    }
+
+    /// Returns the tags of `key`, which are empty if it is untagged or not
+    /// cached.
+    pub fn tags<Q>(&self, key: &Q) -> &[u64]
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let hash = self.segment.map.hash(key);
+        self.segment.tags_hashed(hash, key)
+    }
+
+    /// Removes every entry tagged with `tag`, returning how many were
+    /// removed.
+    ///
+    /// Takes time proportional to the number of entries removed. Removals
+    /// are recorded like [`remove()`](Self::remove)'s.
+    pub fn invalidate_tag(&mut self, tag: u64) -> usize
+    where
+        K: Clone,
+    {
+        self.segment.invalidate_tag(tag)
+    }
+
+    /// Records that `key` is absent from the backing store for the next
+    /// `ttl`, removing any cached value for it.
+    ///
+    /// The entry counts against the negative cache's budget, not this
+    /// cache's, and is dropped by the next [`put()`](Self::put) of the key.
+    /// Does nothing without a negative cache.
+    pub fn put_absent(&mut self, key: K, ttl: Duration)
+    where
+        K: Clone,
+    {
+        let hash = self.segment.map.hash(&key);
+        self.segment.put_absent_hashed(hash, key, ttl);
+    }
+
+    /// Looks up `key` like [`get()`](Self::get), but tells a key known to be
+    /// absent apart from one that is not cached.
+    ///
+    /// With a negative cache, lookups answered by a negative entry count as
+    /// negative hits and lookups that find neither a value nor a negative
+    /// entry count as negative misses.
+    pub fn lookup<Q>(&mut self, key: &Q) -> Lookup<V>
+    where
+        K: Borrow<Q> + Clone,
+        Q: ?Sized + Hash + Eq,
+    {
+        let hash = self.segment.map.hash(key);
+        self.segment.lookup_hashed(hash, key)
+    }
+
+    /// Zeroes the metrics counters and clears the rolling window and the
+    /// miss-ratio curve.
+    ///
+    /// Gauges describing the cache's current contents, such as its size in
+    /// bytes, are kept.
+    pub fn reset_metrics(&mut self) {
+        self.segment.reset_metrics();
+    }
+
     #[inline]
     pub fn get<Q>(&mut self, key: &Q) -> Option<V>
     where
@@ -828,6 +1464,44 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> GdsfCache<K, V, S> {
         self.segment.put(key, val, size)
     }
 
+    /// Inserts a key-value pair like [`put()`](Self::put), whose misses cost
+    /// `cost`, such as the fetch latency in microseconds.
+    ///
+    /// The priority becomes `frequency * cost / size + global_age`, so
+    /// entries that are expensive to fetch stay longer than cheap entries of
+    /// the same size and frequency. [`put()`](Self::put) caches new entries
+    /// with a cost of 1 and keeps the cost of a value it replaces. Hits count the entry's cost as `cost_saved` in the metrics.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cache_rs::GdsfCache;
+    /// use cache_rs::config::GdsfCacheConfig;
+    /// use core::num::NonZeroUsize;
+    ///
+    /// let config = GdsfCacheConfig {
+    ///     capacity: NonZeroUsize::new(2).unwrap(),
+    ///     initial_age: 0.0,
+    ///     max_size: u64::MAX,
+    ///     weight_limits: None,
+    /// };
+    /// let mut cache = GdsfCache::init(config, None);
+    /// cache.put_with_cost("remote", 1, 100, 5_000);
+    /// cache.put_with_cost("local", 2, 10, 20);
+    ///
+    /// // "local" is smaller but much cheaper to fetch again
+    /// let evicted = cache.put_with_cost("other", 3, 10, 100);
+    /// assert_eq!(evicted, Some(vec![("local", 2)]));
+    /// ```
+    pub fn put_with_cost(&mut self, key: K, val: V, size: u64, cost: u64) -> Option<Vec<(K, V)>>
+    where
+        K: Clone,
+    {
+        let hash = self.segment.map.hash(&key);
+        self.segment
+            .put_with_cost_hashed(hash, key, val, size, cost)
+    }
+
     #[inline]
     pub fn clear(&mut self) {
         self.segment.clear()
@@ -849,6 +1523,7 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> GdsfCache<K, V, S> {
     ///     capacity: NonZeroUsize::new(2).unwrap(),
     ///     initial_age: 0.0,
     ///     max_size: u64::MAX,
+    ///     weight_limits: None,
     /// };
     /// let mut cache = GdsfCache::init(config, None);
     /// cache.put("a", 1, 10);
@@ -882,6 +1557,7 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> GdsfCache<K, V, S> {
     ///     capacity: NonZeroUsize::new(3).unwrap(),
     ///     initial_age: 0.0,
     ///     max_size: u64::MAX,
+    ///     weight_limits: None,
     /// };
     /// let mut cache = GdsfCache::init(config, None);
     /// cache.put("a", 1, 1);
@@ -910,13 +1586,330 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> GdsfCache<K, V, S> {
     }
 }
 
-impl<K: Hash + Eq, V: Clone, S: BuildHasher> CacheMetrics for GdsfCache<K, V, S> {
+impl<K, V, S, M> CacheMetrics for GdsfCache<K, V, S, M>
+where
+    K: Hash + Eq,
+    V: Clone,
+    S: BuildHasher,
+    M: MetricsRecorder<GdsfCacheMetrics>,
+{
     fn metrics(&self) -> BTreeMap<String, f64> {
-        self.segment.metrics().metrics()
+        self.segment
+            .metrics()
+            .map_or_else(BTreeMap::new, CacheMetrics::metrics)
+    }
+
+    fn snapshot(&self) -> MetricsSnapshot {
+        self.segment
+            .metrics()
+            .map_or(MetricsSnapshot::Disabled, CacheMetrics::snapshot)
     }
 
     fn algorithm_name(&self) -> &'static str {
-        self.segment.metrics().algorithm_name()
+        "GDSF"
+    }
+}
+
+impl<K, V, S, M> crate::tiered::PrimaryCache for GdsfCache<K, V, S, M>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+    S: BuildHasher,
+    M: MetricsRecorder<GdsfCacheMetrics>,
+{
+    type Key = K;
+    type Value = V;
+
+    fn get(&mut self, key: &K) -> Option<&V> {
+        // The segment's `get()` clones the value; `get_mut()` records the
+        // same access without cloning
+        self.segment.get_mut(key).map(|value| &*value)
+    }
+
+    fn peek(&self, key: &K) -> Option<&V> {
+        self.segment.peek(key)
+    }
+
+    fn contains(&self, key: &K) -> bool {
+        self.segment.contains(key)
+    }
+
+    fn put(&mut self, key: K, value: V, size: u64) -> Option<Vec<(K, V, u64)>> {
+        let hash = self.segment.map.hash(&key);
+        self.segment
+            .put_hashed_with(hash, key, value, size, |key, value, size| {
+                (key, value, size)
+            })
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        self.segment.remove(key)
+    }
+
+    fn len(&self) -> usize {
+        self.segment.len()
+    }
+
+    fn clear(&mut self) {
+        self.segment.clear()
+    }
+
+    fn set_refresh_policy(&mut self, policy: Option<RefreshPolicy>) {
+        self.segment.set_refresh_policy(policy)
+    }
+
+    fn set_xfetch(&mut self, xfetch: Option<XFetch>) {
+        self.segment.set_xfetch(xfetch)
+    }
+
+    fn peek_freshness(&self, key: &K) -> Option<Freshness> {
+        let hash = self.segment.map.hash(key);
+        self.segment.peek_freshness_hashed(hash, key)
+    }
+
+    fn record_miss(&mut self, object_size: u64) {
+        self.segment.record_miss(object_size)
+    }
+}
+
+#[cfg(feature = "concurrent-core")]
+impl<K, V, S, M> crate::concurrent::Segment for GdsfCache<K, V, S, M>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+    S: BuildHasher + Clone,
+    M: MetricsRecorder<GdsfCacheMetrics>,
+{
+    type Key = K;
+
+    type Value = V;
+
+    type Hasher = S;
+
+    type Config = GdsfCacheConfig;
+
+    const CONCURRENT_NAME: &'static str = "ConcurrentGDSF";
+
+    fn init(config: GdsfCacheConfig, hasher: S) -> Self {
+        GdsfCache {
+            segment: GdsfSegment::init(config, hasher),
+        }
+    }
+
+    fn init_shared(config: GdsfCacheConfig, hasher: S, capacity: usize, segments: usize) -> Self {
+        // Weights aren't in the shared budget, so each segment weighs its share
+        let config = GdsfCacheConfig {
+            weight_limits: config
+                .weight_limits
+                .map(|limits| limits.for_segment(segments)),
+            ..config
+        };
+        GdsfCache {
+            segment: GdsfSegment::init_with_capacity(config, hasher, capacity),
+        }
+    }
+
+    fn split_config(config: GdsfCacheConfig, segments: usize) -> GdsfCacheConfig {
+        GdsfCacheConfig {
+            capacity: NonZeroUsize::new((config.capacity.get() / segments).max(1)).unwrap(),
+            initial_age: config.initial_age,
+            max_size: config.max_size / segments as u64,
+            weight_limits: config
+                .weight_limits
+                .map(|limits| limits.for_segment(segments)),
+        }
+    }
+
+    fn capacity(&self) -> usize {
+        self.segment.cap().get()
+    }
+
+    fn len(&self) -> usize {
+        self.segment.len()
+    }
+
+    fn current_size(&self) -> u64 {
+        self.segment.current_size()
+    }
+
+    fn max_size(&self) -> u64 {
+        self.segment.max_size()
+    }
+
+    fn get<Q>(&mut self, hash: u64, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        // The segment's `get()` clones the value; `get_mut()` records the
+        // same access without cloning
+        self.segment.get_mut_hashed(hash, key).map(|value| &*value)
+    }
+
+    fn get_mut<Q>(&mut self, hash: u64, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.segment.get_mut_hashed(hash, key)
+    }
+
+    fn peek_key_value<Q>(&self, hash: u64, key: &Q) -> Option<(&K, &V)>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.segment.peek_key_value_hashed(hash, key)
+    }
+
+    fn put(&mut self, hash: u64, key: K, value: V, size: u64) -> Option<Vec<(K, V)>> {
+        self.segment.put_hashed(hash, key, value, size)
+    }
+
+    fn remove<Q>(&mut self, hash: u64, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.segment.remove_hashed(hash, key)
+    }
+
+    fn clear(&mut self) {
+        self.segment.clear()
+    }
+
+    fn pop_victim(&mut self) -> Option<(K, V)> {
+        self.segment.pop_victim()
+    }
+
+    fn record_miss(&mut self, object_size: u64) {
+        self.segment.record_miss(object_size)
+    }
+
+    fn record_lookup(&mut self, hash: u64) {
+        self.segment.record_lookup(hash)
+    }
+
+    fn peek_freshness<Q>(&self, hash: u64, key: &Q) -> Option<Freshness>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.segment.peek_freshness_hashed(hash, key)
+    }
+
+    fn core_metrics(&self) -> &crate::metrics::CoreCacheMetrics {
+        self.segment
+            .metrics()
+            .map_or(&crate::metrics::NO_CORE_METRICS, |metrics| &metrics.core)
+    }
+}
+
+#[cfg(feature = "concurrent-core")]
+impl<K, V, S, M> crate::concurrent::SegmentFeatures for GdsfCache<K, V, S, M>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+    S: BuildHasher + Clone,
+    M: MetricsRecorder<GdsfCacheMetrics>,
+{
+    fn set_metrics_window(&mut self, window: Option<RollingWindow>) {
+        self.segment.set_metrics_window(window)
+    }
+
+    fn reset_metrics(&mut self) {
+        self.segment.reset_metrics()
+    }
+
+    fn set_miss_ratio_curve(&mut self, curve: Option<MissRatioCurve>) {
+        self.segment.set_miss_ratio_curve(curve)
+    }
+
+    fn set_hot_keys(&mut self, hot_keys: Option<HotKeys<K>>) {
+        self.segment.set_hot_keys(hot_keys)
+    }
+
+    fn hot_keys(&self) -> Option<&HotKeys<K>> {
+        self.segment.hot_keys()
+    }
+
+    fn set_negative_cache(&mut self, negative: Option<NegativeCache<K>>) {
+        self.segment.set_negative_cache(negative)
+    }
+
+    fn negative_cache(&self) -> Option<&NegativeCache<K>> {
+        self.segment.negative_cache()
+    }
+
+    fn put_absent(&mut self, hash: u64, key: K, ttl: Duration) {
+        self.segment.put_absent_hashed(hash, key, ttl)
+    }
+
+    fn check_absent<Q>(&mut self, hash: u64, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.segment.check_absent(hash, key)
+    }
+
+    fn put_tagged(
+        &mut self,
+        hash: u64,
+        key: K,
+        value: V,
+        size: u64,
+        tags: &[u64],
+    ) -> Option<Vec<(K, V)>> {
+        self.segment.put_tagged_hashed(hash, key, value, size, tags)
+    }
+
+    fn invalidate_tag(&mut self, tag: u64) -> usize {
+        self.segment.invalidate_tag(tag)
+    }
+
+    fn put_weighted(
+        &mut self,
+        hash: u64,
+        key: K,
+        value: V,
+        size: u64,
+        weights: Weights,
+    ) -> Option<Vec<(K, V)>> {
+        self.segment
+            .put_weighted_hashed(hash, key, value, size, weights)
+    }
+
+    fn set_weight_limits(&mut self, limits: Option<WeightLimits>) {
+        self.segment.set_weight_limits(limits)
+    }
+
+    fn weight_limits(&self) -> Option<WeightLimits> {
+        self.segment.weight_limits().copied()
+    }
+
+    fn put_with_cost(
+        &mut self,
+        hash: u64,
+        key: K,
+        value: V,
+        size: u64,
+        cost: u64,
+    ) -> Option<Vec<(K, V)>> {
+        self.segment
+            .put_with_cost_hashed(hash, key, value, size, cost)
+    }
+
+    fn set_refresh_policy(&mut self, policy: Option<RefreshPolicy>) {
+        self.segment.set_refresh_policy(policy)
+    }
+
+    fn set_xfetch(&mut self, xfetch: Option<XFetch>) {
+        self.segment.set_xfetch(xfetch)
+    }
+
+    fn set_clock(&mut self, clock: Option<Arc<dyn Clock>>) {
+        self.segment.set_clock(clock)
     }
 }
 
@@ -943,6 +1936,7 @@ impl<K: Hash + Eq, V: Clone> GdsfCache<K, V, DefaultHashBuilder> {
     ///     capacity: NonZeroUsize::new(100).unwrap(),
     ///     initial_age: 0.0,
     ///     max_size: u64::MAX,
+    ///     weight_limits: None,
     /// };
     /// let mut cache: GdsfCache<&str, i32> = GdsfCache::init(config, None);
     /// cache.put("key", 42, 1);
@@ -952,6 +1946,7 @@ impl<K: Hash + Eq, V: Clone> GdsfCache<K, V, DefaultHashBuilder> {
     ///     capacity: NonZeroUsize::new(1000).unwrap(),
     ///     initial_age: 0.0,
     ///     max_size: 10 * 1024 * 1024,  // 10MB
+    ///     weight_limits: None,
     /// };
     /// let cache: GdsfCache<String, Vec<u8>> = GdsfCache::init(config, None);
     /// ```
@@ -960,6 +1955,20 @@ impl<K: Hash + Eq, V: Clone> GdsfCache<K, V, DefaultHashBuilder> {
             segment: GdsfSegment::init(config, hasher.unwrap_or_default()),
         }
     }
+
+    /// Creates a new GDSF cache that collects no metrics.
+    ///
+    /// Behaves exactly like a cache created with [`init()`](Self::init), but
+    /// skips all metrics bookkeeping; [`metrics()`](CacheMetrics::metrics)
+    /// returns an empty map.
+    pub fn init_without_metrics(
+        config: GdsfCacheConfig,
+        hasher: Option<DefaultHashBuilder>,
+    ) -> GdsfCache<K, V, DefaultHashBuilder, NoMetrics> {
+        GdsfCache {
+            segment: GdsfSegment::init(config, hasher.unwrap_or_default()),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -974,6 +1983,7 @@ mod tests {
             capacity: NonZeroUsize::new(cap).unwrap(),
             initial_age: 0.0,
             max_size: u64::MAX,
+            weight_limits: None,
         };
         GdsfCache::init(config, None)
     }
@@ -1120,6 +2130,7 @@ mod tests {
             capacity: NonZeroUsize::new(2).unwrap(),
             initial_age: 0.0,
             max_size: u64::MAX,
+            weight_limits: None,
         };
         let mut segment: GdsfSegment<&str, i32, DefaultHashBuilder> =
             GdsfSegment::init(config, DefaultHashBuilder::default());
@@ -1194,6 +2205,7 @@ mod tests {
             capacity: NonZeroUsize::new(1000).unwrap(),
             initial_age: 0.0,
             max_size: 1024 * 1024,
+            weight_limits: None,
         };
         let cache: GdsfCache<String, i32> = GdsfCache::init(config, None);
 
@@ -1207,6 +2219,7 @@ mod tests {
             capacity: NonZeroUsize::new(100).unwrap(),
             initial_age: 0.0,
             max_size: 1024 * 1024,
+            weight_limits: None,
         };
         let cache: GdsfCache<String, String> = GdsfCache::init(config, None);
 
@@ -1274,6 +2287,7 @@ mod tests {
             capacity: NonZeroUsize::new(10).unwrap(),
             initial_age: 0.0,
             max_size: 100,
+            weight_limits: None,
         };
         let mut cache = GdsfCache::init(config, None);
         for i in 0..10 {
@@ -1298,4 +2312,29 @@ mod tests {
 
         assert_eq!(cache.remove(&"nonexistent"), None);
     }
+
+    #[test]
+    fn test_no_metrics_matches_default() {
+        let config = GdsfCacheConfig {
+            capacity: NonZeroUsize::new(4).unwrap(),
+            initial_age: 0.0,
+            max_size: u64::MAX,
+            weight_limits: None,
+        };
+        let mut counted = GdsfCache::init(config, None);
+        let mut uncounted: GdsfCache<u32, u32, _, NoMetrics> =
+            GdsfCache::init_without_metrics(config, None);
+        for i in 0..64u32 {
+            let key = (i * 7) % 10;
+            assert_eq!(counted.get(&key), uncounted.get(&key));
+            assert_eq!(
+                counted.put(key, i, 1 + u64::from(key % 3)),
+                uncounted.put(key, i, 1 + u64::from(key % 3))
+            );
+        }
+        assert_eq!(counted.len(), uncounted.len());
+        assert!(!counted.metrics().is_empty());
+        assert!(uncounted.metrics().is_empty());
+        assert_eq!(uncounted.algorithm_name(), "GDSF");
+    }
 }
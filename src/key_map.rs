@@ -0,0 +1,218 @@
+//! Pre-Hashed Key Map
+//!
+//! Every cache segment maps keys to list nodes. [`KeyMap`] stores each key
+//! together with its hash and hands the stored hash straight back to the
+//! table, so a key is hashed once per operation and never again when the
+//! table grows.
+//!
+//! Callers pass the hash in explicitly. The sequential caches compute it with
+//! [`KeyMap::hash`]; the concurrent caches compute it once to pick a segment
+//! and reuse it for the segment's lookup.
+
+use core::borrow::Borrow;
+use core::hash::{BuildHasher, BuildHasherDefault, Hash, Hasher};
+
+use hashbrown::{Equivalent, HashMap};
+
+/// A key stored with its hash.
+struct Hashed<K> {
+    hash: u64,
+    key: K,
+}
+
+impl<K> Hash for Hashed<K> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.hash);
+    }
+}
+
+impl<K: PartialEq> PartialEq for Hashed<K> {
+    fn eq(&self, other: &Self) -> bool {
+        self.hash == other.hash && self.key == other.key
+    }
+}
+
+impl<K: Eq> Eq for Hashed<K> {}
+
+/// A borrowed lookup key with its hash.
+struct Lookup<'a, Q: ?Sized> {
+    hash: u64,
+    key: &'a Q,
+}
+
+impl<Q: ?Sized> Hash for Lookup<'_, Q> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.hash);
+    }
+}
+
+impl<K, Q> Equivalent<Hashed<K>> for Lookup<'_, Q>
+where
+    K: Borrow<Q>,
+    Q: ?Sized + Eq,
+{
+    fn equivalent(&self, stored: &Hashed<K>) -> bool {
+        self.hash == stored.hash && self.key == stored.key.borrow()
+    }
+}
+
+/// Hasher that passes a precomputed hash through unchanged.
+#[derive(Default)]
+struct PassThrough(u64);
+
+impl Hasher for PassThrough {
+    fn write(&mut self, bytes: &[u8]) {
+        // Only `write_u64` is reached through `Hashed` and `Lookup`
+        for &byte in bytes {
+            self.0 = self.0.rotate_left(8) ^ u64::from(byte);
+        }
+    }
+
+    fn write_u64(&mut self, hash: u64) {
+        self.0 = hash;
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+/// A hash map from keys to `T` that hashes every key only once.
+pub(crate) struct KeyMap<K, T, S> {
+    map: HashMap<Hashed<K>, T, BuildHasherDefault<PassThrough>>,
+    hash_builder: S,
+}
+
+impl<K, T, S> KeyMap<K, T, S> {
+    /// Returns the number of keys in the map.
+    #[inline]
+    pub(crate) fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns `true` if the map contains no keys.
+    #[inline]
+    pub(crate) fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Removes all keys.
+    pub(crate) fn clear(&mut self) {
+        self.map.clear();
+    }
+
+    /// Iterates over the hashes, keys and values in arbitrary order.
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (u64, &K, &T)> {
+        self.map
+            .iter()
+            .map(|(hashed, value)| (hashed.hash, &hashed.key, value))
+    }
+}
+
+impl<K, T, S: BuildHasher> KeyMap<K, T, S> {
+    /// Hashes `key` with the map's hash builder.
+    #[inline]
+    pub(crate) fn hash<Q>(&self, key: &Q) -> u64
+    where
+        Q: ?Sized + Hash,
+    {
+        self.hash_builder.hash_one(key)
+    }
+}
+
+impl<K: Eq, T, S> KeyMap<K, T, S> {
+    /// Creates an empty map with room for `capacity` keys, hashing keys with
+    /// `hash_builder`.
+    pub(crate) fn with_capacity_and_hasher(capacity: usize, hash_builder: S) -> Self {
+        KeyMap {
+            map: HashMap::with_capacity_and_hasher(capacity, BuildHasherDefault::default()),
+            hash_builder,
+        }
+    }
+
+    /// Returns the value for `key`, whose hash is `hash`.
+    #[inline]
+    pub(crate) fn get<Q>(&self, hash: u64, key: &Q) -> Option<&T>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Eq,
+    {
+        self.map.get(&Lookup { hash, key })
+    }
+
+    /// Returns a mutable reference to the value for `key`, whose hash is `hash`.
+    #[inline]
+    pub(crate) fn get_mut<Q>(&mut self, hash: u64, key: &Q) -> Option<&mut T>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Eq,
+    {
+        self.map.get_mut(&Lookup { hash, key })
+    }
+
+    /// Returns `true` if the map contains `key`, whose hash is `hash`.
+    #[inline]
+    pub(crate) fn contains_key<Q>(&self, hash: u64, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Eq,
+    {
+        self.map.contains_key(&Lookup { hash, key })
+    }
+
+    /// Inserts `key`, whose hash is `hash`, returning the previous value.
+    #[inline]
+    pub(crate) fn insert(&mut self, hash: u64, key: K, value: T) -> Option<T> {
+        self.map.insert(Hashed { hash, key }, value)
+    }
+
+    /// Removes `key`, whose hash is `hash`, returning its value.
+    #[inline]
+    pub(crate) fn remove<Q>(&mut self, hash: u64, key: &Q) -> Option<T>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Eq,
+    {
+        self.map.remove(&Lookup { hash, key })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hashbrown::DefaultHashBuilder;
+
+    extern crate alloc;
+    use alloc::string::{String, ToString};
+
+    #[test]
+    fn test_lookups_use_the_given_hash() {
+        let mut map: KeyMap<String, i32, DefaultHashBuilder> =
+            KeyMap::with_capacity_and_hasher(4, DefaultHashBuilder::default());
+        let hash = map.hash("a");
+        assert_eq!(map.insert(hash, "a".to_string(), 1), None);
+        assert_eq!(map.insert(hash, "a".to_string(), 2), Some(1));
+
+        assert_eq!(map.get(hash, "a"), Some(&2));
+        assert!(map.contains_key(hash, "a"));
+        // The same key under a different hash is a different entry
+        assert_eq!(map.get(hash ^ 1, "a"), None);
+
+        *map.get_mut(hash, "a").unwrap() += 1;
+        assert_eq!(map.remove(hash, "a"), Some(3));
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn test_growth_keeps_stored_hashes() {
+        let mut map: KeyMap<u32, u32, DefaultHashBuilder> =
+            KeyMap::with_capacity_and_hasher(1, DefaultHashBuilder::default());
+        for i in 0..1000 {
+            map.insert(map.hash(&i), i, i * 2);
+        }
+        assert_eq!(map.len(), 1000);
+        for i in 0..1000 {
+            assert_eq!(map.get(map.hash(&i), &i), Some(&(i * 2)));
+        }
+    }
+}
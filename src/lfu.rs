@@ -119,6 +119,7 @@
 //! let config = LfuCacheConfig {
 //!     capacity: NonZeroUsize::new(3).unwrap(),
 //!     max_size: u64::MAX,
+//!     weight_limits: None,
 //! };
 //! let mut cache = LfuCache::init(config, None);
 //!
@@ -148,6 +149,7 @@
 //! let config = LfuCacheConfig {
 //!     capacity: NonZeroUsize::new(1000).unwrap(),
 //!     max_size: 10 * 1024 * 1024,
+//!     weight_limits: None,
 //! };
 //! let mut cache: LfuCache<String, Vec<u8>> = LfuCache::init(config, None);
 //!
@@ -159,8 +161,16 @@ extern crate alloc;
 
 use crate::config::LfuCacheConfig;
 use crate::entry::CacheEntry;
+use crate::key_map::KeyMap;
 use crate::list::{List, ListEntry};
-use crate::metrics::{CacheMetrics, LfuCacheMetrics};
+use crate::metrics::{
+    CacheMetrics, Clock, CoreCacheMetrics, HotKeys, LfuCacheMetrics, MetricsRecorder,
+    MetricsSnapshot, MissRatioCurve, NoMetrics, RollingWindow,
+};
+use crate::negative::{Lookup, NegativeCache};
+use crate::refresh::{Freshness, RefreshPolicy, XFetch};
+use crate::side::{HasSideState, SideState};
+use crate::weight::{WeightLimits, Weights};
 
 /// Metadata for LFU (Least Frequently Used) cache entries.
 ///
@@ -207,20 +217,18 @@ impl LfuMeta {
 use alloc::boxed::Box;
 use alloc::collections::BTreeMap;
 use alloc::string::String;
+use alloc::sync::Arc;
 use alloc::vec::Vec;
 use core::borrow::Borrow;
 use core::hash::{BuildHasher, Hash};
 use core::num::NonZeroUsize;
+use core::time::Duration;
 
 #[cfg(feature = "hashbrown")]
 use hashbrown::DefaultHashBuilder;
-#[cfg(feature = "hashbrown")]
-use hashbrown::HashMap;
 
 #[cfg(not(feature = "hashbrown"))]
 use std::collections::hash_map::RandomState as DefaultHashBuilder;
-#[cfg(not(feature = "hashbrown"))]
-use std::collections::HashMap;
 
 /// Internal LFU segment containing the actual cache algorithm.
 ///
@@ -239,7 +247,7 @@ use std::collections::HashMap;
 /// - The pointer was obtained from a `frequency_lists` entry's `add()` call
 /// - The node has not been removed from the list
 /// - The segment has not been dropped
-pub(crate) struct LfuSegment<K, V, S = DefaultHashBuilder> {
+pub(crate) struct LfuSegment<K, V, S = DefaultHashBuilder, M = LfuCacheMetrics> {
     /// Configuration for the LFU cache (includes capacity and max_size)
     config: LfuCacheConfig,
 
@@ -248,14 +256,16 @@ pub(crate) struct LfuSegment<K, V, S = DefaultHashBuilder> {
 
     /// Map from keys to their list node pointer.
     /// Frequency is stored in CacheEntry.metadata (LfuMeta), not duplicated here.
-    map: HashMap<K, *mut ListEntry<CacheEntry<K, V, LfuMeta>>, S>,
+    map: KeyMap<K, *mut ListEntry<CacheEntry<K, V, LfuMeta>>, S>,
 
     /// Map from frequency to list of items with that frequency
     /// Items within each frequency list are ordered by recency (LRU within frequency)
     frequency_lists: BTreeMap<usize, List<CacheEntry<K, V, LfuMeta>>>,
 
     /// Metrics for tracking cache performance and frequency distribution
-    metrics: LfuCacheMetrics,
+    metrics: M,
+    /// Negative entries, tags, weight usage, hot keys and refresh policy
+    side: SideState<K>,
 
     /// Current total size of cached content (sum of entry sizes)
     current_size: u64,
@@ -263,12 +273,19 @@ pub(crate) struct LfuSegment<K, V, S = DefaultHashBuilder> {
 
 // SAFETY: LfuSegment owns all data and raw pointers point only to nodes owned by
 // `frequency_lists`. Concurrent access is safe when wrapped in proper synchronization primitives.
-unsafe impl<K: Send, V: Send, S: Send> Send for LfuSegment<K, V, S> {}
+unsafe impl<K: Send, V: Send, S: Send, M: Send> Send for LfuSegment<K, V, S, M> {}
 
-// SAFETY: All mutation requires &mut self; shared references cannot cause data races.
-unsafe impl<K: Send, V: Send, S: Sync> Sync for LfuSegment<K, V, S> {}
+// SAFETY: All mutation requires &mut self; shared references only hand out
+// `&K`/`&V`, so sharing requires K and V to be Sync.
+unsafe impl<K: Sync, V: Sync, S: Sync, M: Sync> Sync for LfuSegment<K, V, S, M> {}
 
-impl<K: Hash + Eq, V: Clone, S: BuildHasher> LfuSegment<K, V, S> {
+impl<K, V, S, M> LfuSegment<K, V, S, M>
+where
+    K: Hash + Eq,
+    V: Clone,
+    S: BuildHasher,
+    M: MetricsRecorder<LfuCacheMetrics>,
+{
     /// Creates a new LFU segment from a configuration.
     ///
     /// This is the **recommended** way to create an LFU segment. All configuration
@@ -280,15 +297,26 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> LfuSegment<K, V, S> {
     /// * `hasher` - Hash builder for the internal HashMap
     #[allow(dead_code)] // Used by concurrent module when feature is enabled
     pub(crate) fn init(config: LfuCacheConfig, hasher: S) -> Self {
-        let map_capacity = config.capacity.get().next_power_of_two();
-        LfuSegment {
+        Self::init_with_capacity(config, hasher, config.capacity.get())
+    }
+
+    /// Like [`init()`](Self::init), but preallocates room for only
+    /// `capacity` entries.
+    #[allow(dead_code)] // Used by concurrent module when feature is enabled
+    pub(crate) fn init_with_capacity(config: LfuCacheConfig, hasher: S, capacity: usize) -> Self {
+        let map_capacity = capacity.next_power_of_two();
+        let mut segment = LfuSegment {
             config,
             min_frequency: 1,
-            map: HashMap::with_capacity_and_hasher(map_capacity, hasher),
+            map: KeyMap::with_capacity_and_hasher(map_capacity, hasher),
             frequency_lists: BTreeMap::new(),
-            metrics: LfuCacheMetrics::new(config.max_size),
+            metrics: M::init(|| LfuCacheMetrics::new(config.max_size)),
+            side: SideState::new(config.weight_limits),
             current_size: 0,
-        }
+        };
+        // Limits from the config show in the metrics before any entry
+        segment.publish_weights();
+        segment
     }
 
     /// Returns the maximum number of key-value pairs the segment can hold.
@@ -321,14 +349,14 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> LfuSegment<K, V, S> {
         self.config.max_size
     }
 
-    /// Returns a reference to the metrics for this segment.
+    /// Returns the metrics for this segment, or `None` if it collects none.
     #[inline]
-    pub(crate) fn metrics(&self) -> &LfuCacheMetrics {
-        &self.metrics
+    pub(crate) fn metrics(&self) -> Option<&LfuCacheMetrics> {
+        self.metrics.collected()
     }
 
     /// Updates the frequency of an item and moves it to the appropriate frequency list.
-    /// Takes the node pointer directly to avoid aliasing issues.
+    /// Takes the node pointer and the key's hash directly to avoid aliasing issues.
     ///
     /// # Safety
     ///
@@ -338,6 +366,7 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> LfuSegment<K, V, S> {
         &mut self,
         node: *mut ListEntry<CacheEntry<K, V, LfuMeta>>,
         old_frequency: usize,
+        hash: u64,
     ) -> *mut ListEntry<CacheEntry<K, V, LfuMeta>>
     where
         K: Clone + Hash + Eq,
@@ -346,14 +375,14 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> LfuSegment<K, V, S> {
 
         // Record frequency increment
         self.metrics
-            .record_frequency_increment(old_frequency, new_frequency);
+            .record(|m| m.record_frequency_increment(old_frequency, new_frequency));
 
         // SAFETY: node is guaranteed to be valid by the caller's contract
         let entry = (*node).get_value();
         let key_cloned = entry.key.clone();
 
         // Get the current node from the map
-        let node = *self.map.get(&key_cloned).unwrap();
+        let node = *self.map.get(hash, &key_cloned).unwrap();
 
         // Remove from old frequency list
         let boxed_entry = self
@@ -388,10 +417,11 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> LfuSegment<K, V, S> {
             .attach_from_other_list(entry_ptr);
 
         // Update the map with the new node pointer
-        *self.map.get_mut(&key_cloned).unwrap() = entry_ptr;
+        *self.map.get_mut(hash, &key_cloned).unwrap() = entry_ptr;
 
         // Update metrics with new frequency levels
-        self.metrics.update_frequency_levels(&self.frequency_lists);
+        self.metrics
+            .record(|m| m.update_frequency_levels(&self.frequency_lists));
 
         entry_ptr
     }
@@ -402,15 +432,31 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> LfuSegment<K, V, S> {
         K: Borrow<Q> + Clone,
         Q: ?Sized + Hash + Eq,
     {
-        if let Some(&node) = self.map.get(key) {
+        let hash = self.map.hash(key);
+        self.get_hashed(hash, key)
+    }
+
+    /// Like [`get()`](Self::get), for a key whose hash is already known.
+    pub(crate) fn get_hashed<Q>(&mut self, hash: u64, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q> + Clone,
+        Q: ?Sized + Eq,
+    {
+        self.record_lookup(hash);
+        if let Some(&node) = self.map.get(hash, key) {
             unsafe {
                 // SAFETY: node comes from our map, so it's a valid pointer to an entry in our frequency list
-                let entry = (*node).get_value();
+                let entry = (*node).get_value_mut();
+                let interval = entry.touch_at(self.side.now_nanos());
+                self.side.record_hit(hash, &entry.key, entry.metadata.size);
                 let frequency = entry.metadata.algorithm.frequency as usize;
                 let object_size = entry.metadata.size;
-                self.metrics.record_frequency_hit(object_size, frequency);
+                self.metrics.record(|m| {
+                    m.record_frequency_hit(object_size, frequency);
+                    m.core.record_access_interval(interval);
+                });
 
-                let new_node = self.update_frequency_by_node(node, frequency);
+                let new_node = self.update_frequency_by_node(node, frequency, hash);
                 let new_entry = (*new_node).get_value();
                 Some(&new_entry.value)
             }
@@ -425,15 +471,31 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> LfuSegment<K, V, S> {
         K: Borrow<Q> + Clone,
         Q: ?Sized + Hash + Eq,
     {
-        if let Some(&node) = self.map.get(key) {
+        let hash = self.map.hash(key);
+        self.get_mut_hashed(hash, key)
+    }
+
+    /// Like [`get_mut()`](Self::get_mut), for a key whose hash is already known.
+    pub(crate) fn get_mut_hashed<Q>(&mut self, hash: u64, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q> + Clone,
+        Q: ?Sized + Eq,
+    {
+        self.record_lookup(hash);
+        if let Some(&node) = self.map.get(hash, key) {
             unsafe {
                 // SAFETY: node comes from our map, so it's a valid pointer to an entry in our frequency list
-                let entry = (*node).get_value();
+                let entry = (*node).get_value_mut();
+                let interval = entry.touch_at(self.side.now_nanos());
+                self.side.record_hit(hash, &entry.key, entry.metadata.size);
                 let frequency = entry.metadata.algorithm.frequency as usize;
                 let object_size = entry.metadata.size;
-                self.metrics.record_frequency_hit(object_size, frequency);
+                self.metrics.record(|m| {
+                    m.record_frequency_hit(object_size, frequency);
+                    m.core.record_access_interval(interval);
+                });
 
-                let new_node = self.update_frequency_by_node(node, frequency);
+                let new_node = self.update_frequency_by_node(node, frequency, hash);
                 let new_entry = (*new_node).get_value_mut();
                 Some(&mut new_entry.value)
             }
@@ -453,13 +515,65 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> LfuSegment<K, V, S> {
     where
         K: Clone,
     {
+        let hash = self.map.hash(&key);
+        self.put_hashed(hash, key, value, size)
+    }
+
+    /// Like [`put()`](Self::put), for a key whose hash is already known.
+    pub(crate) fn put_hashed(
+        &mut self,
+        hash: u64,
+        key: K,
+        value: V,
+        size: u64,
+    ) -> Option<Vec<(K, V)>>
+    where
+        K: Clone,
+    {
+        self.put_hashed_with(hash, key, value, size, |key, value, _| (key, value))
+    }
+
+    /// Like [`put_hashed()`](Self::put_hashed), returning each evicted entry
+    /// as `evicted_entry(key, value, size)`.
+    pub(crate) fn put_hashed_with<E>(
+        &mut self,
+        hash: u64,
+        key: K,
+        value: V,
+        size: u64,
+        evicted_entry: impl FnMut(K, V, u64) -> E,
+    ) -> Option<Vec<E>>
+    where
+        K: Clone,
+    {
+        self.put_weighted_hashed_with(hash, key, value, size, None, evicted_entry)
+    }
+
+    /// Like [`put_hashed_with()`](Self::put_hashed_with), for an entry
+    /// weighing `weights`. Without them, a replaced value's weights are kept
+    /// and a new entry weighs nothing.
+    fn put_weighted_hashed_with<E>(
+        &mut self,
+        hash: u64,
+        key: K,
+        value: V,
+        size: u64,
+        weights: Option<Weights>,
+        mut evicted_entry: impl FnMut(K, V, u64) -> E,
+    ) -> Option<Vec<E>>
+    where
+        K: Clone,
+    {
+        self.side.forget(hash, &key);
         // If key already exists, update it
-        if let Some(&node) = self.map.get(&key) {
+        if let Some(&node) = self.map.get(hash, &key) {
             unsafe {
                 // SAFETY: node comes from our map, so it's a valid pointer to an entry in our frequency list
                 let entry = (*node).get_value();
                 let frequency = entry.metadata.algorithm.frequency as usize;
                 let old_size = entry.metadata.size;
+                let replaced = entry.metadata.weights;
+                let weights = weights.unwrap_or(replaced);
 
                 // Create new CacheEntry with same frequency
                 let new_entry = CacheEntry::with_algorithm_metadata(
@@ -467,34 +581,42 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> LfuSegment<K, V, S> {
                     value,
                     size,
                     LfuMeta::new(frequency as u64),
-                );
+                )
+                .with_hash(hash)
+                .with_weights(weights)
+                .with_time(self.side.now_nanos());
 
                 let _old_entry = self
                     .frequency_lists
                     .get_mut(&frequency)
                     .unwrap()
                     .update(node, new_entry, true);
+                self.release_weights(&replaced);
+                self.add_weights(&weights);
 
                 // Update size tracking
                 self.current_size = self.current_size.saturating_sub(old_size);
                 self.current_size += size;
-                self.metrics.core.record_size_change(old_size, size);
-                self.metrics.core.bytes_written_to_cache += size;
+                self.metrics.record(|m| {
+                    m.core.record_size_change(old_size, size);
+                    m.core.bytes_written_to_cache += size;
+                });
 
                 // Replacement is not eviction - don't return the old value
                 return None;
             }
         }
 
+        let weights = weights.unwrap_or_default();
         let mut evicted = Vec::new();
 
         // Evict while entry count limit OR size limit would be exceeded
         while self.len() >= self.config.capacity.get()
             || (self.current_size + size > self.config.max_size && !self.map.is_empty())
         {
-            if let Some(entry) = self.evict() {
-                self.metrics.core.evictions += 1;
-                evicted.push(entry);
+            if let Some((old_key, old_value, old_size)) = self.evict() {
+                self.metrics.record(|m| m.core.count_eviction());
+                evicted.push(evicted_entry(old_key, old_value, old_size));
             } else {
                 break;
             }
@@ -516,7 +638,10 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> LfuSegment<K, V, S> {
             value,
             size,
             LfuMeta::new(frequency as u64),
-        );
+        )
+        .with_hash(hash)
+        .with_weights(weights)
+        .with_time(self.side.now_nanos());
 
         if let Some(node) = self
             .frequency_lists
@@ -524,12 +649,15 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> LfuSegment<K, V, S> {
             .unwrap()
             .add(cache_entry)
         {
-            self.map.insert(key, node);
+            self.map.insert(hash, key, node);
             self.current_size += size;
+            self.add_weights(&weights);
         }
 
-        self.metrics.core.record_insertion(size);
-        self.metrics.update_frequency_levels(&self.frequency_lists);
+        self.metrics.record(|m| {
+            m.core.record_insertion(size);
+            m.update_frequency_levels(&self.frequency_lists);
+        });
 
         if evicted.is_empty() {
             None
@@ -544,7 +672,18 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> LfuSegment<K, V, S> {
         K: Borrow<Q>,
         Q: ?Sized + Hash + Eq,
     {
-        let node = self.map.remove(key)?;
+        let hash = self.map.hash(key);
+        self.remove_hashed(hash, key)
+    }
+
+    /// Like [`remove()`](Self::remove), for a key whose hash is already known.
+    pub(crate) fn remove_hashed<Q>(&mut self, hash: u64, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Eq,
+    {
+        self.side.forget(hash, key);
+        let node = self.map.remove(hash, key)?;
 
         unsafe {
             // SAFETY: node comes from our map; take_value moves the value out
@@ -557,9 +696,10 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> LfuSegment<K, V, S> {
             let cache_entry = (*entry_ptr).take_value();
             let removed_size = cache_entry.metadata.size;
             let _ = Box::from_raw(entry_ptr);
+            self.release_weights(&cache_entry.metadata.weights);
 
             self.current_size = self.current_size.saturating_sub(removed_size);
-            self.metrics.core.record_removal(removed_size);
+            self.metrics.record(|m| m.core.record_removal(removed_size));
 
             // Remove empty frequency list and update min_frequency if necessary
             if self.frequency_lists.get(&frequency).unwrap().is_empty() {
@@ -575,6 +715,7 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> LfuSegment<K, V, S> {
 
     /// Clears the segment, removing all key-value pairs.
     pub(crate) fn clear(&mut self) {
+        self.clear_side();
         self.map.clear();
         self.frequency_lists.clear();
         self.min_frequency = 1;
@@ -584,7 +725,44 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> LfuSegment<K, V, S> {
     /// Records a cache miss for metrics tracking
     #[inline]
     pub(crate) fn record_miss(&mut self, object_size: u64) {
-        self.metrics.record_miss(object_size);
+        self.metrics.record(|m| m.record_miss(object_size));
+    }
+
+    /// Like [`get_hashed()`](Self::get_hashed), also returning the value's
+    /// freshness. Expired values are removed and reported as missing.
+    pub(crate) fn get_fresh_hashed<Q>(&mut self, hash: u64, key: &Q) -> Option<(&V, Freshness)>
+    where
+        K: Borrow<Q> + Clone,
+        Q: ?Sized + Eq,
+    {
+        match self.peek_freshness_hashed(hash, key) {
+            Some(Freshness::Expired) => {
+                self.remove_hashed(hash, key);
+                self.record_lookup(hash);
+                None
+            }
+            freshness => {
+                let freshness = freshness.unwrap_or(Freshness::Fresh);
+                self.get_hashed(hash, key).map(|value| (value, freshness))
+            }
+        }
+    }
+
+    /// Like [`get_hashed()`](Self::get_hashed), telling a key known to be
+    /// absent apart from one that is not cached.
+    pub(crate) fn lookup_hashed<Q>(&mut self, hash: u64, key: &Q) -> Lookup<&V>
+    where
+        K: Borrow<Q> + Clone,
+        Q: ?Sized + Eq,
+    {
+        if self.map.contains_key(hash, key) {
+            return self.get_hashed(hash, key).map_or(Lookup::Miss, Lookup::Hit);
+        }
+        self.lookup_uncached(hash, key)
+    }
+
+    pub(crate) fn reset_metrics(&mut self) {
+        self.metrics.record(|m| m.reset());
     }
 
     /// Check if key exists without updating its frequency.
@@ -597,7 +775,7 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> LfuSegment<K, V, S> {
         K: Borrow<Q>,
         Q: ?Sized + Hash + Eq,
     {
-        self.map.contains_key(key)
+        self.map.contains_key(self.map.hash(key), key)
     }
 
     /// Returns a reference to the value without updating frequency or access metadata.
@@ -609,7 +787,7 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> LfuSegment<K, V, S> {
         K: Borrow<Q>,
         Q: ?Sized + Hash + Eq,
     {
-        let &node = self.map.get(key)?;
+        let &node = self.map.get(self.map.hash(key), key)?;
         unsafe {
             // SAFETY: node comes from our map, so it's a valid pointer
             let entry = (*node).get_value();
@@ -617,6 +795,34 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> LfuSegment<K, V, S> {
         }
     }
 
+    /// Returns the stored key and value without updating access metadata.
+    ///
+    /// Used by the concurrent read path, which looks entries up under a shared
+    /// lock and needs an owned copy of the key to record the access.
+    #[allow(dead_code)] // Used by concurrent module when feature is enabled
+    pub(crate) fn peek_key_value_hashed<Q>(&self, hash: u64, key: &Q) -> Option<(&K, &V)>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Eq,
+    {
+        let &node = self.map.get(hash, key)?;
+        unsafe {
+            // SAFETY: node comes from our map, so it's a valid pointer
+            let entry = (*node).get_value();
+            Some((&entry.key, &entry.value))
+        }
+    }
+
+    /// Evicts the entry this segment's policy would evict next.
+    ///
+    /// Used by the concurrent caches to enforce a budget shared by all segments.
+    #[allow(dead_code)] // Used by concurrent module when feature is enabled
+    pub(crate) fn pop_victim(&mut self) -> Option<(K, V)> {
+        let (key, value, _) = self.evict()?;
+        self.metrics.record(|m| m.core.count_eviction());
+        Some((key, value))
+    }
+
     /// Removes and returns the eviction candidate (lowest frequency entry).
     ///
     /// Returns the entry with the lowest frequency. In case of a tie,
@@ -626,8 +832,9 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> LfuSegment<K, V, S> {
     /// Eviction metrics are only recorded when the cache internally evicts
     /// entries to make room during `put()` operations.
     ///
-    /// Returns `None` if the cache is empty.
-    fn evict(&mut self) -> Option<(K, V)> {
+    /// Returns the evicted key, value and size, or `None` if the cache is
+    /// empty.
+    fn evict(&mut self) -> Option<(K, V, u64)> {
         if self.is_empty() {
             return None;
         }
@@ -642,9 +849,16 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> LfuSegment<K, V, S> {
             let entry_ptr = Box::into_raw(old_entry);
             let cache_entry = (*entry_ptr).take_value();
             let evicted_size = cache_entry.metadata.size;
-            self.map.remove(&cache_entry.key);
+            let hash = cache_entry.metadata.hash;
+            self.map.remove(hash, &cache_entry.key);
+            self.side.untag(hash, &cache_entry.key);
+            self.release_weights(&cache_entry.metadata.weights);
             self.current_size = self.current_size.saturating_sub(evicted_size);
-            self.metrics.core.record_removal(evicted_size);
+            self.metrics.record(|m| {
+                m.core.record_removal(evicted_size);
+                let (age, idle) = cache_entry.metadata.age_and_idle_at(self.side.now_nanos());
+                m.core.record_eviction_age(age, idle);
+            });
 
             // Update min_frequency if the list is now empty
             if is_list_empty {
@@ -653,13 +867,73 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> LfuSegment<K, V, S> {
             }
 
             let _ = Box::from_raw(entry_ptr);
-            Some((cache_entry.key, cache_entry.value))
+            Some((cache_entry.key, cache_entry.value, evicted_size))
         }
     }
 }
 
+impl<K, V, S, M> HasSideState for LfuSegment<K, V, S, M>
+where
+    K: Hash + Eq,
+    V: Clone,
+    S: BuildHasher,
+    M: MetricsRecorder<LfuCacheMetrics>,
+{
+    type Key = K;
+    type Value = V;
+    type Meta = LfuMeta;
+    type Hasher = S;
+
+    fn side(&self) -> &SideState<K> {
+        &self.side
+    }
+
+    fn side_mut(&mut self) -> &mut SideState<K> {
+        &mut self.side
+    }
+
+    fn entries(&self) -> &KeyMap<K, *mut ListEntry<CacheEntry<K, V, LfuMeta>>, S> {
+        &self.map
+    }
+
+    fn record_core(&mut self, record: impl FnOnce(&mut CoreCacheMetrics)) {
+        self.metrics.record(|m| record(&mut m.core));
+    }
+
+    fn insert_entry(
+        &mut self,
+        hash: u64,
+        key: K,
+        value: V,
+        size: u64,
+        weights: Option<Weights>,
+    ) -> Option<Vec<(K, V)>>
+    where
+        K: Clone,
+    {
+        self.put_weighted_hashed_with(hash, key, value, size, weights, |key, value, _| {
+            (key, value)
+        })
+    }
+
+    fn remove_entry<Q>(&mut self, hash: u64, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q> + Clone,
+        Q: ?Sized + Eq,
+    {
+        self.remove_hashed(hash, key)
+    }
+
+    fn evict_entry(&mut self) -> Option<(K, V)>
+    where
+        K: Clone,
+    {
+        self.pop_victim()
+    }
+}
+
 // Implement Debug for LfuSegment manually since it contains raw pointers
-impl<K, V, S> core::fmt::Debug for LfuSegment<K, V, S> {
+impl<K, V, S, M> core::fmt::Debug for LfuSegment<K, V, S, M> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.debug_struct("LfuSegment")
             .field("capacity", &self.config.capacity)
@@ -687,6 +961,7 @@ impl<K, V, S> core::fmt::Debug for LfuSegment<K, V, S> {
 /// let config = LfuCacheConfig {
 ///     capacity: NonZeroUsize::new(3).unwrap(),
 ///     max_size: u64::MAX,
+///     weight_limits: None,
 /// };
 /// let mut cache = LfuCache::init(config, None);
 ///
@@ -704,11 +979,17 @@ impl<K, V, S> core::fmt::Debug for LfuSegment<K, V, S> {
 /// assert_eq!(cache.get(&"b"), None); // "b" was evicted as it had frequency 0
 /// ```
 #[derive(Debug)]
-pub struct LfuCache<K, V, S = DefaultHashBuilder> {
-    segment: LfuSegment<K, V, S>,
+pub struct LfuCache<K, V, S = DefaultHashBuilder, M = LfuCacheMetrics> {
+    segment: LfuSegment<K, V, S, M>,
 }
 
-impl<K: Hash + Eq, V: Clone, S: BuildHasher> LfuCache<K, V, S> {
+impl<K, V, S, M> LfuCache<K, V, S, M>
+where
+    K: Hash + Eq,
+    V: Clone,
+    S: BuildHasher,
+    M: MetricsRecorder<LfuCacheMetrics>,
+{
     /// Returns the maximum number of key-value pairs the cache can hold.
     #[inline]
     pub fn cap(&self) -> NonZeroUsize {
@@ -824,6 +1105,243 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> LfuCache<K, V, S> {
         self.segment.record_miss(object_size);
     }
 
+    /// Tracks the metrics of the most recent requests in `window`, replacing
+    /// any previous window, or stops tracking them with `None`.
+    ///
+    /// The window is reported as `window_*` keys by
+    /// [`metrics()`](CacheMetrics::metrics). Does nothing without metrics.
+    pub fn set_metrics_window(&mut self, window: Option<RollingWindow>) {
+        self.segment.set_metrics_window(window);
+    }
+
+    /// Estimates the miss ratio an LRU cache would have at other capacities
+    /// with `curve`, replacing any previous curve, or stops with `None`.
+    ///
+    /// Every [`get()`](Self::get) and [`get_mut()`](Self::get_mut) lookup is
+    /// sampled, hit or miss. The curve is reported as `mrc_*` keys by
+    /// [`metrics()`](CacheMetrics::metrics). Does nothing without metrics.
+    pub fn set_miss_ratio_curve(&mut self, curve: Option<MissRatioCurve>) {
+        self.segment.set_miss_ratio_curve(curve);
+    }
+
+    /// Ranks the keys of lookups that hit by accesses and by bytes served
+    /// with `hot_keys`, replacing any previous tracker, or stops with `None`.
+    ///
+    /// Works with and without metrics. Read the rankings through
+    /// [`hot_keys()`](Self::hot_keys).
+    pub fn set_hot_keys(&mut self, hot_keys: Option<HotKeys<K>>) {
+        self.segment.set_hot_keys(hot_keys);
+    }
+
+    /// Returns the hot-key tracker, if one is set.
+    pub fn hot_keys(&self) -> Option<&HotKeys<K>> {
+        self.segment.hot_keys()
+    }
+
+    /// Remembers keys known to be absent from the backing store in
+    /// `negative`, replacing any previous negative cache, or stops with
+    /// `None`.
+    ///
+    /// Works with and without metrics. Mark keys absent with
+    /// [`put_absent()`](Self::put_absent) and tell them apart from keys that
+    /// are not cached with [`lookup()`](Self::lookup).
+    pub fn set_negative_cache(&mut self, negative: Option<NegativeCache<K>>) {
+        self.segment.set_negative_cache(negative);
+    }
+
+    /// Returns the negative cache, if one is set.
+    pub fn negative_cache(&self) -> Option<&NegativeCache<K>> {
+        self.segment.negative_cache()
+    }
+
+    /// Ages entries from their creation under `policy`, replacing any
+    /// previous policy, or stops with `None`.
+    ///
+    /// The policy only affects [`get_fresh()`](Self::get_fresh); other
+    /// lookups serve entries regardless of their age.
+    pub fn set_refresh_policy(&mut self, policy: Option<RefreshPolicy>) {
+        self.segment.set_refresh_policy(policy);
+    }
+
+    /// Returns the refresh policy, if one is set.
+    pub fn refresh_policy(&self) -> Option<RefreshPolicy> {
+        self.segment.refresh_policy()
+    }
+
+    /// Expires values early at random under `xfetch`, replacing any
+    /// previous mode, or stops with `None`.
+    ///
+    /// Only applies while a refresh policy is set: fresh values are reported
+    /// as stale by [`get_fresh()`](Self::get_fresh) with a probability that
+    /// grows as the `ttl` nears, and stay cached.
+    pub fn set_xfetch(&mut self, xfetch: Option<XFetch>) {
+        self.segment.set_xfetch(xfetch);
+    }
+
+    /// Returns the early expiration mode, if one is set.
+    pub fn xfetch(&self) -> Option<&XFetch> {
+        self.segment.xfetch()
+    }
+
+    /// Stamps and ages entries with `clock`, or with the system time under
+    /// `None`, the default.
+    ///
+    /// Ages drive the refresh policy and the age and idle-time metrics.
+    /// Entries keep the timestamps they were given, so set the clock before
+    /// inserting any. Without the `std` feature there is no system time, and
+    /// entries only age under a clock.
+    pub fn set_clock(&mut self, clock: Option<Arc<dyn Clock>>) {
+        self.segment.set_clock(clock);
+    }
+
+    /// Returns the clock entries are stamped with, if one is set.
+    pub fn clock(&self) -> Option<&Arc<dyn Clock>> {
+        self.segment.clock()
+    }
+
+    /// Looks up `key` like [`get()`](Self::get), also returning the value's
+    /// [`Freshness`] under the refresh policy.
+    ///
+    /// Stale values and values in the grace window are returned for the
+    /// caller to refresh, e.g. with [`put()`](Self::put). Values past the
+    /// grace window are removed and reported as missing. Without a policy
+    /// every value is fresh.
+    pub fn get_fresh<Q>(&mut self, key: &Q) -> Option<(&V, Freshness)>
+    where
+        K: Borrow<Q> + Clone,
+        Q: ?Sized + Hash + Eq,
+    {
+        let hash = self.segment.map.hash(key);
+        self.segment.get_fresh_hashed(hash, key)
+    }
+
+    /// Inserts a key-value pair like [`put()`](Self::put), tagging it with
+    /// `tags`.
+    ///
+    /// The tags replace any previous tags of the key, and are dropped with
+    /// the entry when it is replaced by [`put()`](Self::put), removed or
+    /// evicted. See [`invalidate_tag()`](Self::invalidate_tag).
+    pub fn put_tagged(&mut self, key: K, value: V, size: u64, tags: &[u64]) -> Option<Vec<(K, V)>>
+    where
+        K: Clone,
+    {
+        let hash = self.segment.map.hash(&key);
+        self.segment.put_tagged_hashed(hash, key, value, size, tags)
+    }
+
+    /// Weighs entries in the dimensions of `limits`, replacing any previous
+    /// limits, or stops with `None`.
+    ///
+    /// Cached entries keep their weights, so they count against the new
+    /// limits. Limits can also be set up front with
+    /// `weight_limits` in the config. See
+    /// [`put_weighted()`](Self::put_weighted).
+    pub fn set_weight_limits(&mut self, limits: Option<WeightLimits>) {
+        self.segment.set_weight_limits(limits);
+    }
+
+    /// Returns the weight limits, if set.
+    pub fn weight_limits(&self) -> Option<&WeightLimits> {
+        self.segment.weight_limits()
+    }
+
+    /// Inserts a key-value pair like [`put()`](Self::put), weighing it with
+    /// `weights`.
+    ///
+    /// Entries are evicted in the cache's usual order until every dimension
+    /// of the [weight limits](Self::set_weight_limits) fits `weights`, then
+    /// the entry is inserted even if it exceeds a limit on its own. The
+    /// weights replace any previous weights of the key, are kept when
+    /// [`put()`](Self::put) replaces its value, and are dropped with the
+    /// entry when it is removed or evicted. Without weight limits the weights
+    /// are kept but evict nothing.
+    pub fn put_weighted(
+        &mut self,
+        key: K,
+        value: V,
+        size: u64,
+        weights: Weights,
+    ) -> Option<Vec<(K, V)>>
+    where
+        K: Clone,
+    {
+        let hash = self.segment.map.hash(&key);
+        self.segment
+            .put_weighted_hashed(hash, key, value, size, weights)
+    }
+
+    /// Returns the weights of `key`, which are zero if it is unweighted or
+    /// not cached.
+    pub fn weights<Q>(&self, key: &Q) -> Weights
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let hash = self.segment.map.hash(key);
+        self.segment.weights_hashed(hash, key)
+    }
+
+    /// Returns the tags of `key`, which are empty if it is untagged or not
+    /// cached.
+    pub fn tags<Q>(&self, key: &Q) -> &[u64]
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let hash = self.segment.map.hash(key);
+        self.segment.tags_hashed(hash, key)
+    }
+
+    /// Removes every entry tagged with `tag`, returning how many were
+    /// removed.
+    ///
+    /// Takes time proportional to the number of entries removed. Removals
+    /// are recorded like [`remove()`](Self::remove)'s.
+    pub fn invalidate_tag(&mut self, tag: u64) -> usize
+    where
+        K: Clone,
+    {
+        self.segment.invalidate_tag(tag)
+    }
+
+    /// Records that `key` is absent from the backing store for the next
+    /// `ttl`, removing any cached value for it.
+    ///
+    /// The entry counts against the negative cache's budget, not this
+    /// cache's, and is dropped by the next [`put()`](Self::put) of the key.
+    /// Does nothing without a negative cache.
+    pub fn put_absent(&mut self, key: K, ttl: Duration)
+    where
+        K: Clone,
+    {
+        let hash = self.segment.map.hash(&key);
+        self.segment.put_absent_hashed(hash, key, ttl);
+    }
+
+    /// Looks up `key` like [`get()`](Self::get), but tells a key known to be
+    /// absent apart from one that is not cached.
+    ///
+    /// With a negative cache, lookups answered by a negative entry count as
+    /// negative hits and lookups that find neither a value nor a negative
+    /// entry count as negative misses.
+    pub fn lookup<Q>(&mut self, key: &Q) -> Lookup<&V>
+    where
+        K: Borrow<Q> + Clone,
+        Q: ?Sized + Hash + Eq,
+    {
+        let hash = self.segment.map.hash(key);
+        self.segment.lookup_hashed(hash, key)
+    }
+
+    /// Zeroes the metrics counters and clears the rolling window and the
+    /// miss-ratio curve.
+    ///
+    /// Gauges describing the cache's current contents, such as its size in
+    /// bytes, are kept.
+    pub fn reset_metrics(&mut self) {
+        self.segment.reset_metrics();
+    }
+
     /// Check if key exists without updating its frequency.
     ///
     /// Unlike `get()`, this method does NOT update the entry's frequency
@@ -840,6 +1358,7 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> LfuCache<K, V, S> {
     /// let config = LfuCacheConfig {
     ///     capacity: NonZeroUsize::new(2).unwrap(),
     ///     max_size: u64::MAX,
+    ///     weight_limits: None,
     /// };
     /// let mut cache = LfuCache::init(config, None);
     /// cache.put("a", 1, 1);
@@ -872,6 +1391,7 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> LfuCache<K, V, S> {
     /// let config = LfuCacheConfig {
     ///     capacity: NonZeroUsize::new(3).unwrap(),
     ///     max_size: u64::MAX,
+    ///     weight_limits: None,
     /// };
     /// let mut cache = LfuCache::init(config, None);
     /// cache.put("a", 1, 1);
@@ -915,6 +1435,7 @@ where
     /// let config = LfuCacheConfig {
     ///     capacity: NonZeroUsize::new(100).unwrap(),
     ///     max_size: u64::MAX,
+    ///     weight_limits: None,
     /// };
     /// let mut cache: LfuCache<&str, i32> = LfuCache::init(config, None);
     /// cache.put("key", 42, 1);
@@ -923,6 +1444,7 @@ where
     /// let config = LfuCacheConfig {
     ///     capacity: NonZeroUsize::new(1000).unwrap(),
     ///     max_size: 10 * 1024 * 1024,  // 10MB
+    ///     weight_limits: None,
     /// };
     /// let cache: LfuCache<String, Vec<u8>> = LfuCache::init(config, None);
     /// ```
@@ -934,15 +1456,342 @@ where
             segment: LfuSegment::init(config, hasher.unwrap_or_default()),
         }
     }
+
+    /// Creates a new LFU cache that collects no metrics.
+    ///
+    /// Behaves exactly like a cache created with [`init()`](Self::init), but
+    /// skips all metrics bookkeeping; [`metrics()`](CacheMetrics::metrics)
+    /// returns an empty map.
+    pub fn init_without_metrics(
+        config: LfuCacheConfig,
+        hasher: Option<DefaultHashBuilder>,
+    ) -> LfuCache<K, V, DefaultHashBuilder, NoMetrics> {
+        LfuCache {
+            segment: LfuSegment::init(config, hasher.unwrap_or_default()),
+        }
+    }
 }
 
-impl<K: Hash + Eq, V: Clone, S: BuildHasher> CacheMetrics for LfuCache<K, V, S> {
+impl<K, V, S, M> CacheMetrics for LfuCache<K, V, S, M>
+where
+    K: Hash + Eq,
+    V: Clone,
+    S: BuildHasher,
+    M: MetricsRecorder<LfuCacheMetrics>,
+{
     fn metrics(&self) -> BTreeMap<String, f64> {
-        self.segment.metrics().metrics()
+        self.segment
+            .metrics()
+            .map_or_else(BTreeMap::new, CacheMetrics::metrics)
+    }
+
+    fn snapshot(&self) -> MetricsSnapshot {
+        self.segment
+            .metrics()
+            .map_or(MetricsSnapshot::Disabled, CacheMetrics::snapshot)
     }
 
     fn algorithm_name(&self) -> &'static str {
-        self.segment.metrics().algorithm_name()
+        "LFU"
+    }
+}
+
+impl<K, V, S, M> crate::tiered::PrimaryCache for LfuCache<K, V, S, M>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+    S: BuildHasher,
+    M: MetricsRecorder<LfuCacheMetrics>,
+{
+    type Key = K;
+    type Value = V;
+
+    fn get(&mut self, key: &K) -> Option<&V> {
+        self.segment.get(key)
+    }
+
+    fn peek(&self, key: &K) -> Option<&V> {
+        self.segment.peek(key)
+    }
+
+    fn contains(&self, key: &K) -> bool {
+        self.segment.contains(key)
+    }
+
+    fn put(&mut self, key: K, value: V, size: u64) -> Option<Vec<(K, V, u64)>> {
+        let hash = self.segment.map.hash(&key);
+        self.segment
+            .put_hashed_with(hash, key, value, size, |key, value, size| {
+                (key, value, size)
+            })
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        self.segment.remove(key)
+    }
+
+    fn len(&self) -> usize {
+        self.segment.len()
+    }
+
+    fn clear(&mut self) {
+        self.segment.clear()
+    }
+
+    fn set_refresh_policy(&mut self, policy: Option<RefreshPolicy>) {
+        self.segment.set_refresh_policy(policy)
+    }
+
+    fn set_xfetch(&mut self, xfetch: Option<XFetch>) {
+        self.segment.set_xfetch(xfetch)
+    }
+
+    fn peek_freshness(&self, key: &K) -> Option<Freshness> {
+        let hash = self.segment.map.hash(key);
+        self.segment.peek_freshness_hashed(hash, key)
+    }
+
+    fn record_miss(&mut self, object_size: u64) {
+        self.segment.record_miss(object_size)
+    }
+}
+
+#[cfg(feature = "concurrent-core")]
+impl<K, V, S, M> crate::concurrent::Segment for LfuCache<K, V, S, M>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+    S: BuildHasher + Clone,
+    M: MetricsRecorder<LfuCacheMetrics>,
+{
+    type Key = K;
+
+    type Value = V;
+
+    type Hasher = S;
+
+    type Config = LfuCacheConfig;
+
+    const CONCURRENT_NAME: &'static str = "ConcurrentLFU";
+
+    fn init(config: LfuCacheConfig, hasher: S) -> Self {
+        LfuCache {
+            segment: LfuSegment::init(config, hasher),
+        }
+    }
+
+    fn init_shared(config: LfuCacheConfig, hasher: S, capacity: usize, segments: usize) -> Self {
+        // Weights aren't in the shared budget, so each segment weighs its share
+        let config = LfuCacheConfig {
+            weight_limits: config
+                .weight_limits
+                .map(|limits| limits.for_segment(segments)),
+            ..config
+        };
+        LfuCache {
+            segment: LfuSegment::init_with_capacity(config, hasher, capacity),
+        }
+    }
+
+    fn split_config(config: LfuCacheConfig, segments: usize) -> LfuCacheConfig {
+        LfuCacheConfig {
+            capacity: NonZeroUsize::new((config.capacity.get() / segments).max(1)).unwrap(),
+            max_size: config.max_size / segments as u64,
+            weight_limits: config
+                .weight_limits
+                .map(|limits| limits.for_segment(segments)),
+        }
+    }
+
+    fn capacity(&self) -> usize {
+        self.segment.cap().get()
+    }
+
+    fn len(&self) -> usize {
+        self.segment.len()
+    }
+
+    fn current_size(&self) -> u64 {
+        self.segment.current_size()
+    }
+
+    fn max_size(&self) -> u64 {
+        self.segment.max_size()
+    }
+
+    fn get<Q>(&mut self, hash: u64, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.segment.get_hashed(hash, key)
+    }
+
+    fn get_mut<Q>(&mut self, hash: u64, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.segment.get_mut_hashed(hash, key)
+    }
+
+    fn peek_key_value<Q>(&self, hash: u64, key: &Q) -> Option<(&K, &V)>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.segment.peek_key_value_hashed(hash, key)
+    }
+
+    fn put(&mut self, hash: u64, key: K, value: V, size: u64) -> Option<Vec<(K, V)>> {
+        self.segment.put_hashed(hash, key, value, size)
+    }
+
+    fn remove<Q>(&mut self, hash: u64, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.segment.remove_hashed(hash, key)
+    }
+
+    fn clear(&mut self) {
+        self.segment.clear()
+    }
+
+    fn pop_victim(&mut self) -> Option<(K, V)> {
+        self.segment.pop_victim()
+    }
+
+    fn record_miss(&mut self, object_size: u64) {
+        self.segment.record_miss(object_size)
+    }
+
+    fn record_lookup(&mut self, hash: u64) {
+        self.segment.record_lookup(hash)
+    }
+
+    fn peek_freshness<Q>(&self, hash: u64, key: &Q) -> Option<Freshness>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.segment.peek_freshness_hashed(hash, key)
+    }
+
+    fn core_metrics(&self) -> &crate::metrics::CoreCacheMetrics {
+        self.segment
+            .metrics()
+            .map_or(&crate::metrics::NO_CORE_METRICS, |metrics| &metrics.core)
+    }
+}
+
+#[cfg(feature = "concurrent-core")]
+impl<K, V, S, M> crate::concurrent::SegmentFeatures for LfuCache<K, V, S, M>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+    S: BuildHasher + Clone,
+    M: MetricsRecorder<LfuCacheMetrics>,
+{
+    fn set_metrics_window(&mut self, window: Option<RollingWindow>) {
+        self.segment.set_metrics_window(window)
+    }
+
+    fn reset_metrics(&mut self) {
+        self.segment.reset_metrics()
+    }
+
+    fn set_miss_ratio_curve(&mut self, curve: Option<MissRatioCurve>) {
+        self.segment.set_miss_ratio_curve(curve)
+    }
+
+    fn set_hot_keys(&mut self, hot_keys: Option<HotKeys<K>>) {
+        self.segment.set_hot_keys(hot_keys)
+    }
+
+    fn hot_keys(&self) -> Option<&HotKeys<K>> {
+        self.segment.hot_keys()
+    }
+
+    fn set_negative_cache(&mut self, negative: Option<NegativeCache<K>>) {
+        self.segment.set_negative_cache(negative)
+    }
+
+    fn negative_cache(&self) -> Option<&NegativeCache<K>> {
+        self.segment.negative_cache()
+    }
+
+    fn put_absent(&mut self, hash: u64, key: K, ttl: Duration) {
+        self.segment.put_absent_hashed(hash, key, ttl)
+    }
+
+    fn check_absent<Q>(&mut self, hash: u64, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.segment.check_absent(hash, key)
+    }
+
+    fn put_tagged(
+        &mut self,
+        hash: u64,
+        key: K,
+        value: V,
+        size: u64,
+        tags: &[u64],
+    ) -> Option<Vec<(K, V)>> {
+        self.segment.put_tagged_hashed(hash, key, value, size, tags)
+    }
+
+    fn invalidate_tag(&mut self, tag: u64) -> usize {
+        self.segment.invalidate_tag(tag)
+    }
+
+    fn put_weighted(
+        &mut self,
+        hash: u64,
+        key: K,
+        value: V,
+        size: u64,
+        weights: Weights,
+    ) -> Option<Vec<(K, V)>> {
+        self.segment
+            .put_weighted_hashed(hash, key, value, size, weights)
+    }
+
+    fn set_weight_limits(&mut self, limits: Option<WeightLimits>) {
+        self.segment.set_weight_limits(limits)
+    }
+
+    fn weight_limits(&self) -> Option<WeightLimits> {
+        self.segment.weight_limits().copied()
+    }
+
+    fn put_with_cost(
+        &mut self,
+        hash: u64,
+        key: K,
+        value: V,
+        size: u64,
+        cost: u64,
+    ) -> Option<Vec<(K, V)>> {
+        // LFU doesn't weigh entries by their miss cost
+        let _ = cost;
+        self.segment.put_hashed(hash, key, value, size)
+    }
+
+    fn set_refresh_policy(&mut self, policy: Option<RefreshPolicy>) {
+        self.segment.set_refresh_policy(policy)
+    }
+
+    fn set_xfetch(&mut self, xfetch: Option<XFetch>) {
+        self.segment.set_xfetch(xfetch)
+    }
+
+    fn set_clock(&mut self, clock: Option<Arc<dyn Clock>>) {
+        self.segment.set_clock(clock)
     }
 }
 
@@ -963,6 +1812,7 @@ mod tests {
         let config = LfuCacheConfig {
             capacity: NonZeroUsize::new(cap).unwrap(),
             max_size: u64::MAX,
+            weight_limits: None,
         };
         LfuCache::init(config, None)
     }
@@ -1165,6 +2015,7 @@ mod tests {
         let config = LfuCacheConfig {
             capacity: NonZeroUsize::new(3).unwrap(),
             max_size: u64::MAX,
+            weight_limits: None,
         };
         let mut segment: LfuSegment<&str, i32, DefaultHashBuilder> =
             LfuSegment::init(config, DefaultHashBuilder::default());
@@ -1245,6 +2096,7 @@ mod tests {
         let config = LfuCacheConfig {
             capacity: NonZeroUsize::new(1000).unwrap(),
             max_size: 1024 * 1024,
+            weight_limits: None,
         };
         let cache: LfuCache<String, i32> = LfuCache::init(config, None);
 
@@ -1257,6 +2109,7 @@ mod tests {
         let config = LfuCacheConfig {
             capacity: NonZeroUsize::new(100).unwrap(),
             max_size: 1024 * 1024,
+            weight_limits: None,
         };
         let cache: LfuCache<String, String> = LfuCache::init(config, None);
 
@@ -1396,6 +2249,7 @@ mod tests {
         let config = LfuCacheConfig {
             capacity: NonZeroUsize::new(10).unwrap(),
             max_size: 100,
+            weight_limits: None,
         };
         let mut cache = LfuCache::init(config, None);
         for i in 0..10 {
@@ -1405,4 +2259,28 @@ mod tests {
         let evicted = result.unwrap();
         assert_eq!(evicted.len(), 5);
     }
+
+    #[test]
+    fn test_no_metrics_matches_default() {
+        let config = LfuCacheConfig {
+            capacity: NonZeroUsize::new(4).unwrap(),
+            max_size: u64::MAX,
+            weight_limits: None,
+        };
+        let mut counted = LfuCache::init(config, None);
+        let mut uncounted: LfuCache<u32, u32, _, NoMetrics> =
+            LfuCache::init_without_metrics(config, None);
+        for i in 0..64u32 {
+            let key = (i * 7) % 10;
+            assert_eq!(counted.get(&key), uncounted.get(&key));
+            assert_eq!(
+                counted.put(key, i, 1 + u64::from(key % 3)),
+                uncounted.put(key, i, 1 + u64::from(key % 3))
+            );
+        }
+        assert_eq!(counted.len(), uncounted.len());
+        assert!(!counted.metrics().is_empty());
+        assert!(uncounted.metrics().is_empty());
+        assert_eq!(uncounted.algorithm_name(), "LFU");
+    }
 }
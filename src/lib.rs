@@ -76,6 +76,7 @@
 //! let config = LruCacheConfig {
 //!     capacity: NonZeroUsize::new(2).unwrap(),
 //!     max_size: u64::MAX,
+//!     weight_limits: None,
 //! };
 //! let mut cache = LruCache::init(config, None);
 //! cache.put("a", 1, 1);
@@ -99,6 +100,7 @@
 //!     capacity: NonZeroUsize::new(100).unwrap(),
 //!     protected_capacity: NonZeroUsize::new(20).unwrap(),
 //!     max_size: u64::MAX,
+//!     weight_limits: None,
 //! };
 //! let mut cache = SlruCache::init(config, None);
 //!
@@ -119,6 +121,7 @@
 //! let config = LfuCacheConfig {
 //!     capacity: NonZeroUsize::new(2).unwrap(),
 //!     max_size: u64::MAX,
+//!     weight_limits: None,
 //! };
 //! let mut cache = LfuCache::init(config, None);
 //! cache.put("rare", 1, 1);
@@ -145,6 +148,7 @@
 //!     capacity: NonZeroUsize::new(100).unwrap(),
 //!     initial_age: 0,
 //!     max_size: u64::MAX,
+//!     weight_limits: None,
 //! };
 //! let mut cache = LfudaCache::init(config, None);
 //!
@@ -168,6 +172,7 @@
 //!     capacity: NonZeroUsize::new(1000).unwrap(),
 //!     initial_age: 0.0,
 //!     max_size: 10 * 1024 * 1024,  // 10MB
+//!     weight_limits: None,
 //! };
 //! let mut cache: GdsfCache<String, Vec<u8>> = GdsfCache::init(config, None);
 //!
@@ -186,6 +191,9 @@
 //! cache-rs = { version = "0.3", features = ["concurrent"] }
 //! ```
 //!
+//! On `no_std` targets, enable `concurrent-core` instead. Segments are then
+//! locked with the bundled spinlock rather than `parking_lot`.
+//!
 //! ```rust,ignore
 //! use cache_rs::ConcurrentLruCache;
 //! use std::sync::Arc;
@@ -207,7 +215,7 @@
 //! │                                                                    │
 //! │  ┌─────────┐ ┌─────────┐ ┌─────────┐     ┌─────────┐              │
 //! │  │Segment 0│ │Segment 1│ │Segment 2│ ... │Segment15│              │
-//! │  │ [Mutex] │ │ [Mutex] │ │ [Mutex] │     │ [Mutex] │              │
+//! │  │[RwLock] │ │[RwLock] │ │[RwLock] │     │[RwLock] │              │
 //! │  └─────────┘ └─────────┘ └─────────┘     └─────────┘              │
 //! │       ▲           ▲           ▲               ▲                   │
 //! │       │           │           │               │                   │
@@ -228,6 +236,7 @@
 //! let config = LruCacheConfig {
 //!     capacity: NonZeroUsize::new(1000).unwrap(),
 //!     max_size: 10 * 1024 * 1024,
+//!     weight_limits: None,
 //! };
 //! let mut cache: LruCache<String, Vec<u8>> = LruCache::init(config, None);
 //!
@@ -245,7 +254,7 @@
 //! - [`gdsf`]: Greedy Dual Size Frequency cache implementation
 //! - [`config`]: Configuration structures for all cache algorithms
 //! - [`metrics`]: Metrics collection for cache performance monitoring
-//! - `concurrent`: Thread-safe concurrent cache implementations (requires `concurrent` feature)
+//! - `concurrent`: Thread-safe concurrent cache implementations (requires the `concurrent` or `concurrent-core` feature)
 
 #![no_std]
 
@@ -277,6 +286,22 @@ pub mod meta;
 /// careful invariant maintenance. Use the high-level cache implementations instead.
 pub(crate) mod list;
 
+/// Hash map from keys to list nodes that hashes each key only once.
+///
+/// **Note**: This module is internal infrastructure shared by all cache segments.
+pub(crate) mod key_map;
+
+/// Index from tags to the entries carrying them, for tag invalidation.
+///
+/// **Note**: This module is internal infrastructure shared by all cache segments.
+pub(crate) mod tags;
+
+/// Negative entries, tags and weight usage kept beside the entries of each
+/// segment.
+///
+/// **Note**: This module is internal infrastructure shared by all cache segments.
+pub(crate) mod side;
+
 /// Cache configuration structures.
 ///
 /// Provides configuration structures for all cache algorithm implementations.
@@ -313,6 +338,48 @@ pub mod lfuda;
 /// Assigns priority based on (Frequency / Size) + Global_Age formula.
 pub mod gdsf;
 
+/// Tiered caches.
+///
+/// Composes any cache as an in-memory L1 tier over a pluggable
+/// [`SecondaryStore`](crate::tiered::SecondaryStore) L2 tier, demoting
+/// evicted entries and promoting L2 hits.
+pub mod tiered;
+
+/// Read-through, write-through and write-back caches.
+///
+/// Attaches a [`CacheLoader`](crate::loader::CacheLoader) and a
+/// [`CacheWriter`](crate::loader::CacheWriter) to any cache, so the cache
+/// fetches the values it misses and persists the writes made through it.
+pub mod loader;
+
+/// Negative caching.
+///
+/// Remembers keys known to be absent from the backing store, with their own
+/// TTL and a budget separate from the cache's, so repeated lookups of
+/// missing keys stop reaching the backend.
+pub mod negative;
+
+/// Refresh-ahead and stale-while-revalidate.
+///
+/// Ages entries from their creation time so stale values can be served while
+/// they are refreshed, and through a grace window if the refresh fails.
+pub mod refresh;
+
+/// Multi-dimensional entry weights.
+///
+/// Bounds per-entry costs other than entries and bytes, such as open file
+/// handles, with a limit per named dimension.
+pub mod weight;
+
+/// Memory budget sizing from cgroup limits.
+///
+/// Derives `max_size` from the memory limit of a Linux cgroup, and watches
+/// its usage against high and low watermarks.
+///
+/// Available when the `std` feature is enabled.
+#[cfg(feature = "std")]
+pub mod memory;
+
 /// Cache metrics system.
 ///
 /// Provides a flexible metrics collection and reporting system for all cache algorithms.
@@ -325,8 +392,8 @@ pub mod metrics;
 /// multi-threaded access. Each concurrent cache partitions the key space across multiple
 /// segments, with each segment protected by its own lock.
 ///
-/// Available when the `concurrent` feature is enabled.
-#[cfg(feature = "concurrent")]
+/// Available when the `concurrent` or `concurrent-core` feature is enabled.
+#[cfg(feature = "concurrent-core")]
 pub mod concurrent;
 
 /// Size value for entry-count mode where actual size doesn't matter.
@@ -344,6 +411,7 @@ pub mod concurrent;
 /// let config = LruCacheConfig {
 ///     capacity: NonZeroUsize::new(100).unwrap(),
 ///     max_size: u64::MAX,  // No size limit
+///     weight_limits: None,
 /// };
 /// let mut cache = LruCache::init(config, None);
 ///
@@ -371,8 +439,10 @@ pub const SIZE_UNIT: u64 = 1;
 pub use gdsf::GdsfCache;
 pub use lfu::LfuCache;
 pub use lfuda::LfudaCache;
+pub use loader::LoadingCache;
 pub use lru::LruCache;
 pub use slru::SlruCache;
+pub use tiered::TieredCache;
 
 // Re-export entry types
 pub use entry::{CacheEntry, CacheMetadata};
@@ -383,7 +453,7 @@ pub use meta::{GdsfMeta, LfuMeta, LfudaMeta, SlruMeta};
 // Re-export SLRU Location enum for completeness
 pub use slru::Location as SlruLocation;
 
-#[cfg(feature = "concurrent")]
+#[cfg(feature = "concurrent-core")]
 pub use concurrent::{
     ConcurrentGdsfCache, ConcurrentLfuCache, ConcurrentLfudaCache, ConcurrentLruCache,
     ConcurrentSlruCache,
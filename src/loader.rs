@@ -0,0 +1,974 @@
+//! Read-Through and Write-Through Caches
+//!
+//! [`LoadingCache`](crate::loader::LoadingCache) lets a cache own the fetch
+//! and store logic that call sites would otherwise repeat. A
+//! [`CacheLoader`](crate::loader::CacheLoader) fetches values the cache
+//! misses, and an optional [`CacheWriter`](crate::loader::CacheWriter)
+//! persists writes and deletions. With the `concurrent-core` feature,
+//! `ConcurrentLoadingCache` does the same for the concurrent caches.
+//!
+//! Writes follow the [`WriteMode`](crate::loader::WriteMode):
+//!
+//! - **Write-through** writes to the backing store before caching the value.
+//! - **Write-back** only marks the cached entry dirty. Dirty entries are
+//!   written when the cache evicts them and by `flush()`. Writes of evicted
+//!   entries that fail are kept, served by `get()` in place of a load, and
+//!   retried in order by the next `flush()`.
+//!
+//! ```
+//! use cache_rs::LruCache;
+//! use cache_rs::config::LruCacheConfig;
+//! use cache_rs::loader::{CacheWriter, LoadingCache, WriteMode};
+//! use core::cell::RefCell;
+//! use core::convert::Infallible;
+//! use core::num::NonZeroUsize;
+//! use std::collections::BTreeMap;
+//!
+//! /// Backing store recording every write
+//! #[derive(Default)]
+//! struct Database(RefCell<BTreeMap<u32, String>>);
+//!
+//! impl CacheWriter<u32, String> for Database {
+//!     type Error = Infallible;
+//!
+//!     fn write(&self, key: &u32, value: &String) -> Result<(), Infallible> {
+//!         self.0.borrow_mut().insert(*key, value.clone());
+//!         Ok(())
+//!     }
+//!
+//!     fn delete(&self, key: &u32) -> Result<(), Infallible> {
+//!         self.0.borrow_mut().remove(key);
+//!         Ok(())
+//!     }
+//! }
+//!
+//! let config = LruCacheConfig {
+//!     capacity: NonZeroUsize::new(2).unwrap(),
+//!     max_size: u64::MAX,
+//!     weight_limits: None,
+//! };
+//! let loader = |key: &u32| Ok::<_, Infallible>(Some((format!("user {key}"), 1)));
+//! let mut cache = LoadingCache::new(LruCache::init(config, None), loader)
+//!     .with_writer(Database::default(), WriteMode::WriteBack);
+//!
+//! // Misses are loaded and cached
+//! assert_eq!(cache.get(&1).unwrap(), Some(&"user 1".to_string()));
+//!
+//! // Write-back: the write reaches the database on eviction or flush
+//! cache.put(2, "renamed".to_string(), 1).unwrap();
+//! assert!(cache.writer().0.borrow().is_empty());
+//! assert_eq!(cache.flush(), Ok(1));
+//! assert_eq!(cache.writer().0.borrow()[&2], "renamed");
+//! ```
+
+extern crate alloc;
+
+use crate::refresh::{Freshness, RefreshPolicy, XFetch};
+use crate::tiered::PrimaryCache;
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+use core::convert::Infallible;
+use core::hash::Hash;
+use hashbrown::HashSet;
+
+#[cfg(feature = "concurrent-core")]
+pub use concurrent::ConcurrentLoadingCache;
+
+/// Fetches values that a cache misses from the data source behind it.
+///
+/// Closures `Fn(&K) -> Result<Option<(V, u64)>, E>` are loaders.
+pub trait CacheLoader<K, V> {
+    /// Error returned when the data source fails.
+    type Error;
+
+    /// Fetches the value for `key` with its size, or `None` if the data
+    /// source has no value for it.
+    fn load(&self, key: &K) -> Result<Option<(V, u64)>, Self::Error>;
+
+    /// Fetches the values for several keys, returning one result per key in
+    /// the same order.
+    ///
+    /// The default implementation calls [`load()`](Self::load) for each key;
+    /// override it to batch requests to the data source.
+    fn load_all(&self, keys: &[K]) -> Result<Vec<Option<(V, u64)>>, Self::Error> {
+        keys.iter().map(|key| self.load(key)).collect()
+    }
+}
+
+impl<K, V, E, F> CacheLoader<K, V> for F
+where
+    F: Fn(&K) -> Result<Option<(V, u64)>, E>,
+{
+    type Error = E;
+
+    fn load(&self, key: &K) -> Result<Option<(V, u64)>, E> {
+        self(key)
+    }
+}
+
+/// Persists the writes and deletions made through a cache.
+pub trait CacheWriter<K, V> {
+    /// Error returned when the backing store fails.
+    type Error;
+
+    /// Stores `value` for `key`.
+    fn write(&self, key: &K, value: &V) -> Result<(), Self::Error>;
+
+    /// Deletes the value for `key`.
+    fn delete(&self, key: &K) -> Result<(), Self::Error>;
+}
+
+/// The [`CacheWriter`] of a cache that only loads: writes stay in the cache.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct NoWriter;
+
+impl<K, V> CacheWriter<K, V> for NoWriter {
+    type Error = Infallible;
+
+    fn write(&self, _key: &K, _value: &V) -> Result<(), Infallible> {
+        Ok(())
+    }
+
+    fn delete(&self, _key: &K) -> Result<(), Infallible> {
+        Ok(())
+    }
+}
+
+/// When writes reach the [`CacheWriter`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum WriteMode {
+    /// Every `put()` writes to the backing store before caching the value.
+    #[default]
+    WriteThrough,
+    /// `put()` only caches the value and marks it dirty. Dirty entries are
+    /// written when evicted and by `flush()`.
+    WriteBack,
+}
+
+/// A cache that loads the values it misses and writes through or back to a
+/// backing store.
+///
+/// Wraps any cache implementing [`PrimaryCache`]. See the
+/// [module documentation](self) for an example.
+///
+/// Dirty entries are not written when the cache is dropped; call
+/// [`flush()`](Self::flush) first.
+pub struct LoadingCache<C: PrimaryCache, L, W = NoWriter> {
+    cache: C,
+    loader: L,
+    writer: W,
+    mode: WriteMode,
+    /// Keys of cached entries not yet written (write-back only)
+    dirty: HashSet<C::Key>,
+    /// Evicted dirty entries whose write failed, oldest first, at most one
+    /// per key and none for dirty keys
+    pending: VecDeque<(C::Key, C::Value)>,
+    /// Keys whose stale value failed to refresh
+    refresh_failed: HashSet<C::Key>,
+}
+
+impl<C, L> LoadingCache<C, L>
+where
+    C: PrimaryCache,
+    C::Key: Hash + Eq + Clone,
+    L: CacheLoader<C::Key, C::Value>,
+{
+    /// Wraps `cache`, loading the values it misses with `loader`.
+    ///
+    /// Writes stay in the cache until a writer is attached with
+    /// [`with_writer()`](Self::with_writer).
+    pub fn new(cache: C, loader: L) -> Self {
+        LoadingCache {
+            cache,
+            loader,
+            writer: NoWriter,
+            mode: WriteMode::WriteThrough,
+            dirty: HashSet::new(),
+            pending: VecDeque::new(),
+            refresh_failed: HashSet::new(),
+        }
+    }
+
+    /// Persists writes and deletions with `writer` in the given `mode`.
+    pub fn with_writer<W>(self, writer: W, mode: WriteMode) -> LoadingCache<C, L, W>
+    where
+        W: CacheWriter<C::Key, C::Value>,
+    {
+        LoadingCache {
+            cache: self.cache,
+            loader: self.loader,
+            writer,
+            mode,
+            dirty: HashSet::new(),
+            pending: VecDeque::new(),
+            refresh_failed: HashSet::new(),
+        }
+    }
+}
+
+impl<C, L, W> LoadingCache<C, L, W>
+where
+    C: PrimaryCache,
+    C::Key: Hash + Eq + Clone,
+    L: CacheLoader<C::Key, C::Value>,
+    W: CacheWriter<C::Key, C::Value>,
+{
+    /// Refreshes stale values through the loader under `policy`.
+    ///
+    /// Once a value is older than `refresh_after`, the next
+    /// [`get()`](Self::get) loads it again and returns the new value. If
+    /// that load fails, the stale value is served without further refreshes
+    /// until it is past the policy's grace window, after which it is loaded
+    /// like a miss. Dirty entries are never refreshed, as they are newer than
+    /// the backing store.
+    pub fn with_refresh(mut self, policy: RefreshPolicy) -> Self {
+        self.cache.set_refresh_policy(Some(policy));
+        self
+    }
+
+    /// Expires values early at random under `xfetch`, so values cached
+    /// together are reloaded at different times.
+    ///
+    /// Requires a refresh policy set with [`with_refresh()`](Self::with_refresh);
+    /// values expired early are refreshed like stale ones.
+    pub fn with_xfetch(mut self, xfetch: XFetch) -> Self {
+        self.cache.set_xfetch(Some(xfetch));
+        self
+    }
+
+    /// Returns the cached value for `key`, loading and caching it on a miss.
+    ///
+    /// A load is recorded as a miss in the cache's metrics. Returns `None` if
+    /// the loader has no value for the key. An evicted value whose write
+    /// failed is returned without loading, as it is newer than the backing
+    /// store. With a refresh policy, stale values are refreshed first; see
+    /// [`with_refresh()`](Self::with_refresh).
+    pub fn get(&mut self, key: &C::Key) -> Result<Option<&C::Value>, L::Error> {
+        match self.cache.peek_freshness(key) {
+            Some(Freshness::Stale | Freshness::Grace) if !self.refresh(key) => {
+                return Ok(None);
+            }
+            Some(Freshness::Expired) if !self.is_dirty(key) => {
+                self.refresh_failed.remove(key);
+                self.cache.remove(key);
+            }
+            _ => {}
+        }
+        if !self.cache.contains(key) {
+            if let Some(index) = self.pending_index(key) {
+                return Ok(Some(&self.pending[index].1));
+            }
+            let Some((value, size)) = self.loader.load(key)? else {
+                return Ok(None);
+            };
+            self.cache.record_miss(size);
+            self.insert(key.clone(), value, size);
+        }
+        Ok(self.cache.get(key))
+    }
+
+    /// Returns the values for `keys` in order, loading all missed keys with
+    /// one [`load_all()`](CacheLoader::load_all) call.
+    pub fn get_all(&mut self, keys: &[C::Key]) -> Result<Vec<Option<C::Value>>, L::Error>
+    where
+        C::Value: Clone,
+    {
+        let cached: Vec<bool> = keys
+            .iter()
+            .map(|key| self.cache.contains(key) || self.pending_index(key).is_some())
+            .collect();
+        let missing: Vec<C::Key> = keys
+            .iter()
+            .zip(&cached)
+            .filter(|(_, &cached)| !cached)
+            .map(|(key, _)| key.clone())
+            .collect();
+        let mut loaded = if missing.is_empty() {
+            Vec::new()
+        } else {
+            self.loader.load_all(&missing)?
+        }
+        .into_iter();
+
+        let mut values = Vec::with_capacity(keys.len());
+        for (key, cached) in keys.iter().zip(cached) {
+            if cached {
+                let value = match self.pending_index(key) {
+                    Some(index) if !self.cache.contains(key) => Some(&self.pending[index].1),
+                    _ => self.cache.get(key),
+                };
+                values.push(value.cloned());
+            } else if let Some((value, size)) = loaded.next().flatten() {
+                self.cache.record_miss(size);
+                values.push(Some(value.clone()));
+                self.insert(key.clone(), value, size);
+            } else {
+                values.push(None);
+            }
+        }
+        Ok(values)
+    }
+
+    /// Caches `value` for `key`.
+    ///
+    /// In write-through mode the value is written first and not cached if the
+    /// write fails. In write-back mode the entry is marked dirty. Either way
+    /// the value supersedes a failed write of the key.
+    pub fn put(&mut self, key: C::Key, value: C::Value, size: u64) -> Result<(), W::Error> {
+        match self.mode {
+            WriteMode::WriteThrough => self.writer.write(&key, &value)?,
+            WriteMode::WriteBack => {
+                self.dirty.insert(key.clone());
+            }
+        }
+        if let Some(index) = self.pending_index(&key) {
+            self.pending.remove(index);
+        }
+        self.insert(key, value, size);
+        Ok(())
+    }
+
+    /// Deletes `key` from the backing store and the cache, returning the
+    /// cached value.
+    ///
+    /// The cache is left unchanged if the deletion fails.
+    pub fn remove(&mut self, key: &C::Key) -> Result<Option<C::Value>, W::Error> {
+        self.writer.delete(key)?;
+        self.dirty.remove(key);
+        self.pending.retain(|(pending, _)| pending != key);
+        self.refresh_failed.remove(key);
+        Ok(self.cache.remove(key))
+    }
+
+    /// Retries failed writes of evicted entries in the order they failed,
+    /// then writes every dirty entry, returning the number of entries
+    /// written.
+    ///
+    /// Stops at the first failed write; the entries not yet written stay
+    /// dirty.
+    pub fn flush(&mut self) -> Result<usize, W::Error> {
+        let mut written = 0;
+        while let Some((key, value)) = self.pending.front() {
+            self.writer.write(key, value)?;
+            self.pending.pop_front();
+            written += 1;
+        }
+
+        let mut result = Ok(());
+        let (cache, writer) = (&self.cache, &self.writer);
+        self.dirty.retain(|key| {
+            if result.is_err() {
+                return true;
+            }
+            let Some(value) = cache.peek(key) else {
+                return false;
+            };
+            match writer.write(key, value) {
+                Ok(()) => {
+                    written += 1;
+                    false
+                }
+                Err(error) => {
+                    result = Err(error);
+                    true
+                }
+            }
+        });
+        result.map(|()| written)
+    }
+
+    /// Returns the number of entries not yet written to the backing store.
+    pub fn dirty_len(&self) -> usize {
+        self.dirty.len() + self.pending.len()
+    }
+
+    /// Returns `true` if the entry for `key` has not been written yet.
+    pub fn is_dirty(&self, key: &C::Key) -> bool {
+        self.dirty.contains(key) || self.pending_index(key).is_some()
+    }
+
+    /// Returns the write mode.
+    pub fn mode(&self) -> WriteMode {
+        self.mode
+    }
+
+    /// Returns the wrapped cache.
+    pub fn cache(&self) -> &C {
+        &self.cache
+    }
+
+    /// Returns the wrapped cache mutably, e.g. to attach a metrics window.
+    ///
+    /// Dirty entries it evicts or removes through this reference are not
+    /// written.
+    pub fn cache_mut(&mut self) -> &mut C {
+        &mut self.cache
+    }
+
+    /// Returns the loader.
+    pub fn loader(&self) -> &L {
+        &self.loader
+    }
+
+    /// Returns the writer.
+    pub fn writer(&self) -> &W {
+        &self.writer
+    }
+
+    /// Returns the position of the failed write of `key` in `pending`.
+    fn pending_index(&self, key: &C::Key) -> Option<usize> {
+        self.pending.iter().position(|(pending, _)| pending == key)
+    }
+
+    /// Loads a stale value again, at most once until it is replaced.
+    ///
+    /// Keeps the stale value if the load fails. Returns `false` if the
+    /// loader no longer has a value, after removing the stale one.
+    fn refresh(&mut self, key: &C::Key) -> bool {
+        if self.is_dirty(key) || self.refresh_failed.contains(key) {
+            return true;
+        }
+        match self.loader.load(key) {
+            Ok(Some((value, size))) => self.insert(key.clone(), value, size),
+            Ok(None) => {
+                self.cache.remove(key);
+                return false;
+            }
+            Err(_) => {
+                self.refresh_failed.insert(key.clone());
+            }
+        }
+        true
+    }
+
+    /// Inserts into the cache and writes the dirty entries it evicts.
+    fn insert(&mut self, key: C::Key, value: C::Value, size: u64) {
+        self.refresh_failed.remove(&key);
+        let Some(evicted) = self.cache.put(key, value, size) else {
+            return;
+        };
+        for (key, value, _) in evicted {
+            self.refresh_failed.remove(&key);
+            if self.dirty.remove(&key) && self.writer.write(&key, &value).is_err() {
+                self.pending.push_back((key, value));
+            }
+        }
+    }
+}
+
+impl<C, L, W> core::fmt::Debug for LoadingCache<C, L, W>
+where
+    C: PrimaryCache + core::fmt::Debug,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("LoadingCache")
+            .field("cache", &self.cache)
+            .field("mode", &self.mode)
+            .field("dirty", &(self.dirty.len() + self.pending.len()))
+            .finish()
+    }
+}
+
+#[cfg(feature = "concurrent-core")]
+mod concurrent {
+    extern crate alloc;
+
+    use super::{CacheLoader, CacheWriter, NoWriter, WriteMode};
+    use crate::concurrent::{DefaultRawRwLock, RawRwLock, Segment, SegmentFeatures, ShardedCache};
+    use crate::refresh::{Freshness, RefreshPolicy, XFetch};
+    use alloc::boxed::Box;
+    use alloc::collections::VecDeque;
+    use alloc::vec::Vec;
+    use core::hash::Hash;
+    use hashbrown::HashSet;
+    use lock_api::RwLock;
+
+    /// Lock guarding the dirty state of one segment.
+    type DirtyLock<Lk, K, V> = RwLock<Lk, DirtyState<K, V>>;
+
+    /// Dirty state of each segment.
+    type DirtyStripes<Lk, K, V> = Box<[DirtyLock<Lk, K, V>]>;
+
+    /// Dirty keys and failed writes of one segment of a write-back cache.
+    struct DirtyState<K, V> {
+        keys: HashSet<K>,
+        /// Evicted dirty entries whose write failed, oldest first, at most
+        /// one per key and none for dirty keys
+        pending: VecDeque<(K, V)>,
+    }
+
+    impl<K: Hash + Eq, V> DirtyState<K, V> {
+        fn new() -> Self {
+            DirtyState {
+                keys: HashSet::new(),
+                pending: VecDeque::new(),
+            }
+        }
+
+        fn pending_index(&self, key: &K) -> Option<usize> {
+            self.pending.iter().position(|(pending, _)| pending == key)
+        }
+
+        /// Drops the failed write of `key`, superseded by a newer value.
+        fn drop_pending(&mut self, key: &K) {
+            if let Some(index) = self.pending_index(key) {
+                self.pending.remove(index);
+            }
+        }
+
+        /// Keeps the failed write of `key` for the next flush, unless the key
+        /// has been written again since.
+        fn push_pending(&mut self, key: K, value: V) {
+            if !self.keys.contains(&key) && self.pending_index(&key).is_none() {
+                self.pending.push_back((key, value));
+            }
+        }
+    }
+
+    /// The concurrent counterpart of [`LoadingCache`](super::LoadingCache),
+    /// wrapping a [`ShardedCache`].
+    ///
+    /// Lookups that hit take only the segment's lock. Loads run without any
+    /// lock, so concurrent misses on the same key may load it more than once.
+    /// In write-back mode every insertion also takes a lock tracking the
+    /// dirty entries of its segment; writes to the backing store happen
+    /// after it is released. With a refresh policy, one thread
+    /// refreshes each stale value while the others keep serving it.
+    ///
+    /// ```
+    /// use cache_rs::ConcurrentLruCache;
+    /// use cache_rs::config::{ConcurrentCacheConfig, LruCacheConfig};
+    /// use cache_rs::loader::ConcurrentLoadingCache;
+    /// use core::convert::Infallible;
+    /// use core::num::NonZeroUsize;
+    ///
+    /// let config = ConcurrentCacheConfig {
+    ///     base: LruCacheConfig {
+    ///         capacity: NonZeroUsize::new(100).unwrap(),
+    ///         max_size: u64::MAX,
+    ///         weight_limits: None,
+    ///     },
+    ///     segments: 4,
+    ///     shared_budget: false,
+    /// };
+    /// let cache: ConcurrentLruCache<u32, u32> = ConcurrentLruCache::init(config, None);
+    /// let loader = |key: &u32| Ok::<_, Infallible>(Some((key * 2, 1)));
+    /// let cache = ConcurrentLoadingCache::new(cache, loader);
+    /// assert_eq!(cache.get(&21), Ok(Some(42)));
+    /// assert!(cache.cache().contains(&21));
+    /// ```
+    pub struct ConcurrentLoadingCache<C: Segment, L, W = NoWriter, Lk: RawRwLock = DefaultRawRwLock> {
+        cache: ShardedCache<C, Lk>,
+        loader: L,
+        writer: W,
+        mode: WriteMode,
+        dirty: DirtyStripes<Lk, C::Key, C::Value>,
+        /// Whether stale values are refreshed
+        refresh: bool,
+        /// Keys whose stale value is being refreshed
+        refreshing: RwLock<Lk, HashSet<C::Key>>,
+    }
+
+    impl<C, L, Lk> ConcurrentLoadingCache<C, L, NoWriter, Lk>
+    where
+        C: Segment,
+        L: CacheLoader<C::Key, C::Value>,
+        Lk: RawRwLock,
+    {
+        /// Wraps `cache`, loading the values it misses with `loader`.
+        ///
+        /// Writes stay in the cache until a writer is attached with
+        /// [`with_writer()`](Self::with_writer).
+        pub fn new(cache: ShardedCache<C, Lk>, loader: L) -> Self {
+            ConcurrentLoadingCache {
+                loader,
+                writer: NoWriter,
+                mode: WriteMode::WriteThrough,
+                dirty: (0..cache.segment_count())
+                    .map(|_| RwLock::new(DirtyState::new()))
+                    .collect(),
+                cache,
+                refresh: false,
+                refreshing: RwLock::new(HashSet::new()),
+            }
+        }
+
+        /// Persists writes and deletions with `writer` in the given `mode`.
+        pub fn with_writer<W>(
+            self,
+            writer: W,
+            mode: WriteMode,
+        ) -> ConcurrentLoadingCache<C, L, W, Lk>
+        where
+            W: CacheWriter<C::Key, C::Value>,
+        {
+            ConcurrentLoadingCache {
+                cache: self.cache,
+                loader: self.loader,
+                writer,
+                mode,
+                dirty: self.dirty,
+                refresh: self.refresh,
+                refreshing: self.refreshing,
+            }
+        }
+    }
+
+    impl<C, L, W, Lk> ConcurrentLoadingCache<C, L, W, Lk>
+    where
+        C: SegmentFeatures,
+        L: CacheLoader<C::Key, C::Value>,
+        W: CacheWriter<C::Key, C::Value>,
+        Lk: RawRwLock,
+    {
+        /// Refreshes stale values through the loader under `policy`.
+        ///
+        /// Once a value is older than `refresh_after`, the next
+        /// [`get()`](Self::get) loads it again and returns the new value,
+        /// while concurrent gets of the key keep returning the stale one. If
+        /// that load fails, the stale value is served and the next get tries
+        /// again, until the value is past the policy's grace window and is
+        /// loaded like a miss. Dirty entries are never refreshed, as they are
+        /// newer than the backing store.
+        pub fn with_refresh(mut self, policy: RefreshPolicy) -> Self {
+            self.cache.set_refresh_policy(Some(policy));
+            self.refresh = true;
+            self
+        }
+
+        /// Expires values early at random under `xfetch`, so values cached
+        /// together are reloaded at different times.
+        ///
+        /// Requires a refresh policy set with
+        /// [`with_refresh()`](Self::with_refresh); values expired early are
+        /// refreshed like stale ones.
+        pub fn with_xfetch(self, xfetch: XFetch) -> Self {
+            self.cache.set_xfetch(Some(xfetch));
+            self
+        }
+    }
+
+    impl<C, L, W, Lk> ConcurrentLoadingCache<C, L, W, Lk>
+    where
+        C: Segment,
+        L: CacheLoader<C::Key, C::Value>,
+        W: CacheWriter<C::Key, C::Value>,
+        Lk: RawRwLock,
+    {
+        /// Returns the cached value for `key`, loading and caching it on a
+        /// miss.
+        ///
+        /// A load is recorded as a miss in the cache's metrics. Returns
+        /// `None` if the loader has no value for the key. An evicted value
+        /// whose write failed is returned without loading, as it is newer
+        /// than the backing store. With a refresh policy, stale values are
+        /// refreshed first; see [`with_refresh()`](Self::with_refresh).
+        pub fn get(&self, key: &C::Key) -> Result<Option<C::Value>, L::Error> {
+            if self.refresh && !self.is_dirty(key) {
+                match self.cache.get_fresh(key) {
+                    Some((value, Freshness::Fresh)) => return Ok(Some(value)),
+                    Some((value, _)) => return Ok(self.refresh(key, value)),
+                    // Expired or removed, so a refresh that is still marked
+                    // has nothing left to refresh
+                    None => {
+                        self.refreshing.write().remove(key);
+                    }
+                }
+            } else if let Some(value) = self.cache.get(key) {
+                return Ok(Some(value));
+            }
+            if let Some(value) = self.pending_value(key) {
+                return Ok(Some(value));
+            }
+            let Some((value, size)) = self.loader.load(key)? else {
+                return Ok(None);
+            };
+            self.cache.record_miss(size);
+            self.insert(key.clone(), value.clone(), size, false);
+            Ok(Some(value))
+        }
+
+        /// Returns the values for `keys` in order, loading all missed keys
+        /// with one [`load_all()`](CacheLoader::load_all) call.
+        pub fn get_all(&self, keys: &[C::Key]) -> Result<Vec<Option<C::Value>>, L::Error> {
+            let mut values: Vec<Option<C::Value>> = keys
+                .iter()
+                .map(|key| self.cache.get(key).or_else(|| self.pending_value(key)))
+                .collect();
+            let missing: Vec<C::Key> = keys
+                .iter()
+                .zip(&values)
+                .filter(|(_, value)| value.is_none())
+                .map(|(key, _)| key.clone())
+                .collect();
+            if missing.is_empty() {
+                return Ok(values);
+            }
+
+            let mut loaded = self.loader.load_all(&missing)?.into_iter();
+            for (key, value) in keys.iter().zip(&mut values) {
+                if value.is_some() {
+                    continue;
+                }
+                if let Some((loaded, size)) = loaded.next().flatten() {
+                    self.cache.record_miss(size);
+                    self.insert(key.clone(), loaded.clone(), size, false);
+                    *value = Some(loaded);
+                }
+            }
+            Ok(values)
+        }
+
+        /// Caches `value` for `key`.
+        ///
+        /// In write-through mode the value is written first and not cached if
+        /// the write fails. In write-back mode the entry is marked dirty.
+        /// Either way the value supersedes a failed write of the key.
+        pub fn put(&self, key: C::Key, value: C::Value, size: u64) -> Result<(), W::Error> {
+            if self.mode == WriteMode::WriteThrough {
+                self.writer.write(&key, &value)?;
+            }
+            self.insert(key, value, size, true);
+            Ok(())
+        }
+
+        /// Deletes `key` from the backing store and the cache, returning the
+        /// cached value.
+        ///
+        /// The cache is left unchanged if the deletion fails.
+        pub fn remove(&self, key: &C::Key) -> Result<Option<C::Value>, W::Error> {
+            self.writer.delete(key)?;
+            if self.refresh {
+                self.refreshing.write().remove(key);
+            }
+            if self.mode == WriteMode::WriteBack {
+                let mut dirty = self.dirty_of(key).write();
+                dirty.keys.remove(key);
+                dirty.drop_pending(key);
+                return Ok(self.cache.remove(key));
+            }
+            Ok(self.cache.remove(key))
+        }
+
+        /// Retries failed writes of evicted entries in the order they
+        /// failed, then writes every dirty entry, returning the number of
+        /// entries written. Segments are flushed one at a time.
+        ///
+        /// Stops at the first failed write; the entries not yet written stay
+        /// dirty.
+        pub fn flush(&self) -> Result<usize, W::Error> {
+            let mut written = 0;
+            for stripe in self.dirty.iter() {
+                let mut entries = {
+                    let mut dirty = stripe.write();
+                    let mut entries = core::mem::take(&mut dirty.pending);
+                    for key in dirty.keys.drain() {
+                        if let Some(value) = self.cache.peek(&key) {
+                            entries.push_back((key, value));
+                        }
+                    }
+                    entries
+                };
+
+                while let Some((key, value)) = entries.front() {
+                    if let Err(error) = self.writer.write(key, value) {
+                        // Keep the unwritten entries ahead of writes that
+                        // failed meanwhile, unless those keys were put again
+                        let mut dirty = stripe.write();
+                        let newer = core::mem::take(&mut dirty.pending);
+                        entries.retain(|(key, _)| !newer.iter().any(|(newer, _)| newer == key));
+                        for (key, value) in entries.into_iter().chain(newer) {
+                            dirty.push_pending(key, value);
+                        }
+                        return Err(error);
+                    }
+                    entries.pop_front();
+                    written += 1;
+                }
+            }
+            Ok(written)
+        }
+
+        /// Returns the number of entries not yet written to the backing store.
+        pub fn dirty_len(&self) -> usize {
+            self.dirty
+                .iter()
+                .map(|stripe| {
+                    let dirty = stripe.read();
+                    dirty.keys.len() + dirty.pending.len()
+                })
+                .sum()
+        }
+
+        /// Returns the write mode.
+        pub fn mode(&self) -> WriteMode {
+            self.mode
+        }
+
+        /// Returns the wrapped cache.
+        ///
+        /// Dirty entries evicted or removed through it are not written.
+        pub fn cache(&self) -> &ShardedCache<C, Lk> {
+            &self.cache
+        }
+
+        /// Returns the loader.
+        pub fn loader(&self) -> &L {
+            &self.loader
+        }
+
+        /// Returns the writer.
+        pub fn writer(&self) -> &W {
+            &self.writer
+        }
+
+        /// Returns `true` if the entry for `key` has not been written yet.
+        fn is_dirty(&self, key: &C::Key) -> bool {
+            self.mode == WriteMode::WriteBack && self.dirty_of(key).read().keys.contains(key)
+        }
+
+        /// Returns the dirty state of the segment holding `key`.
+        fn dirty_of(&self, key: &C::Key) -> &DirtyLock<Lk, C::Key, C::Value> {
+            &self.dirty[self.cache.locate(key).0]
+        }
+
+        /// Returns the value of a failed write of `key`.
+        fn pending_value(&self, key: &C::Key) -> Option<C::Value> {
+            if self.mode != WriteMode::WriteBack {
+                return None;
+            }
+            let dirty = self.dirty_of(key).read();
+            let index = dirty.pending_index(key)?;
+            Some(dirty.pending[index].1.clone())
+        }
+
+        /// Loads a stale value again, unless another thread is already doing
+        /// so, in which case `stale` is returned. `stale` is also returned if
+        /// the load fails, and the next get tries again.
+        ///
+        /// Returns `None`, after removing the stale value, if the loader no
+        /// longer has one.
+        fn refresh(&self, key: &C::Key, stale: C::Value) -> Option<C::Value> {
+            if !self.refreshing.write().insert(key.clone()) {
+                return Some(stale);
+            }
+            let refreshed = match self.loader.load(key) {
+                Ok(Some((value, size))) => {
+                    self.insert(key.clone(), value.clone(), size, false);
+                    Some(value)
+                }
+                Ok(None) => {
+                    self.cache.remove(key);
+                    None
+                }
+                Err(_) => Some(stale),
+            };
+            self.refreshing.write().remove(key);
+            refreshed
+        }
+
+        /// Inserts into the cache and writes the dirty entries it evicts.
+        ///
+        /// In write-back mode the insertion and the bookkeeping of its
+        /// evictions happen under the dirty lock of the key's segment, and
+        /// `is_dirty` marks the new entry dirty. A loaded value never
+        /// replaces a dirty one. Refreshes of the inserted and evicted keys
+        /// are forgotten.
+        fn insert(&self, key: C::Key, value: C::Value, size: u64, is_dirty: bool) {
+            let inserted = self.refresh.then(|| key.clone());
+            if self.mode == WriteMode::WriteThrough {
+                let evicted = self.cache.put(key, value, size);
+                self.forget_refreshes(inserted, evicted.as_deref().unwrap_or_default());
+                return;
+            }
+            let (idx, hash) = self.cache.locate(&key);
+            let (evicted, elsewhere) = {
+                let mut dirty = self.dirty[idx].write();
+                if is_dirty {
+                    dirty.keys.insert(key.clone());
+                    dirty.drop_pending(&key);
+                } else if dirty.keys.contains(&key) || dirty.pending_index(&key).is_some() {
+                    // Written by another thread while this value was loading
+                    return;
+                }
+                let mut evicted = self
+                    .cache
+                    .put_located(idx, hash, key, value, size)
+                    .unwrap_or_default();
+                self.forget_refreshes(inserted, &evicted);
+                // A shared budget may evict from other segments
+                let elsewhere = if self.cache.shares_budget() {
+                    let (own, elsewhere) = evicted
+                        .into_iter()
+                        .partition(|(key, _)| self.cache.locate(key).0 == idx);
+                    evicted = own;
+                    elsewhere
+                } else {
+                    Vec::new()
+                };
+                evicted.retain(|(key, _)| dirty.keys.remove(key));
+                (evicted, elsewhere)
+            };
+            self.write_evicted(idx, evicted);
+
+            for (key, value) in elsewhere {
+                let idx = self.cache.locate(&key).0;
+                {
+                    let mut dirty = self.dirty[idx].write();
+                    // A cached value was put since the eviction and is newer
+                    if !dirty.keys.contains(&key) || self.cache.contains(&key) {
+                        continue;
+                    }
+                    dirty.keys.remove(&key);
+                }
+                self.write_evicted(idx, alloc::vec![(key, value)]);
+            }
+        }
+
+        /// Writes dirty entries evicted from segment `idx`, keeping the
+        /// failed writes for the next flush.
+        fn write_evicted(&self, idx: usize, mut evicted: Vec<(C::Key, C::Value)>) {
+            evicted.retain(|(key, value)| self.writer.write(key, value).is_err());
+            if !evicted.is_empty() {
+                let mut dirty = self.dirty[idx].write();
+                for (key, value) in evicted {
+                    dirty.push_pending(key, value);
+                }
+            }
+        }
+
+        /// Unmarks the refreshes of an inserted key and of evicted entries.
+        fn forget_refreshes(&self, inserted: Option<C::Key>, evicted: &[(C::Key, C::Value)]) {
+            let Some(inserted) = inserted else {
+                return;
+            };
+            let mut refreshing = self.refreshing.write();
+            refreshing.remove(&inserted);
+            for (key, _) in evicted {
+                refreshing.remove(key);
+            }
+        }
+    }
+
+    impl<C, L, W, Lk> core::fmt::Debug for ConcurrentLoadingCache<C, L, W, Lk>
+    where
+        C: Segment,
+        Lk: RawRwLock,
+    {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            let dirty: usize = self
+                .dirty
+                .iter()
+                .map(|stripe| {
+                    let dirty = stripe.read();
+                    dirty.keys.len() + dirty.pending.len()
+                })
+                .sum();
+            f.debug_struct("ConcurrentLoadingCache")
+                .field("cache", &self.cache)
+                .field("mode", &self.mode)
+                .field("dirty", &dirty)
+                .finish()
+        }
+    }
+}
@@ -100,6 +100,7 @@
 //! let config = LruCacheConfig {
 //!     capacity: NonZeroUsize::new(3).unwrap(),
 //!     max_size: u64::MAX,
+//!     weight_limits: None,
 //! };
 //! let mut cache = LruCache::init(config, None);
 //!
@@ -124,6 +125,7 @@
 //! let config = LruCacheConfig {
 //!     capacity: NonZeroUsize::new(1000).unwrap(),
 //!     max_size: 10 * 1024 * 1024,
+//!     weight_limits: None,
 //! };
 //! let mut cache: LruCache<String, Vec<u8>> = LruCache::init(config, None);
 //!
@@ -134,26 +136,32 @@
 extern crate alloc;
 
 use crate::config::LruCacheConfig;
-use crate::entry::CacheEntry;
+use crate::entry::{CacheEntry, CacheMetadata};
+use crate::key_map::KeyMap;
 use crate::list::{List, ListEntry};
-use crate::metrics::{CacheMetrics, LruCacheMetrics};
+use crate::metrics::{
+    CacheMetrics, Clock, CoreCacheMetrics, HotKeys, LruCacheMetrics, MetricsRecorder,
+    MetricsSnapshot, MissRatioCurve, NoMetrics, RollingWindow,
+};
+use crate::negative::{Lookup, NegativeCache};
+use crate::refresh::{Freshness, RefreshPolicy, XFetch};
+use crate::side::{HasSideState, SideState};
+use crate::weight::{WeightLimits, Weights};
 use alloc::boxed::Box;
 use alloc::collections::BTreeMap;
 use alloc::string::String;
+use alloc::sync::Arc;
 use alloc::vec::Vec;
 use core::borrow::Borrow;
 use core::hash::{BuildHasher, Hash};
 use core::num::NonZeroUsize;
+use core::time::Duration;
 
 #[cfg(feature = "hashbrown")]
 use hashbrown::DefaultHashBuilder;
-#[cfg(feature = "hashbrown")]
-use hashbrown::HashMap;
 
 #[cfg(not(feature = "hashbrown"))]
 use std::collections::hash_map::RandomState as DefaultHashBuilder;
-#[cfg(not(feature = "hashbrown"))]
-use std::collections::HashMap;
 
 /// Internal LRU segment containing the actual cache algorithm.
 ///
@@ -172,24 +180,33 @@ use std::collections::HashMap;
 /// - The pointer was obtained from a `list` entry's `add()` call
 /// - The node has not been removed from the list
 /// - The segment has not been dropped
-pub(crate) struct LruSegment<K, V, S = DefaultHashBuilder> {
+pub(crate) struct LruSegment<K, V, S = DefaultHashBuilder, M = LruCacheMetrics> {
     /// Configuration for the LRU cache (includes capacity and max_size)
     config: LruCacheConfig,
     list: List<CacheEntry<K, V>>,
-    map: HashMap<K, *mut ListEntry<CacheEntry<K, V>>, S>,
-    metrics: LruCacheMetrics,
+    map: KeyMap<K, *mut ListEntry<CacheEntry<K, V>>, S>,
+    metrics: M,
+    /// Negative entries, tags, weight usage, hot keys and refresh policy
+    side: SideState<K>,
     /// Current total size of cached content (sum of entry.metadata.size values)
     current_size: u64,
 }
 
 // SAFETY: LruSegment owns all data and raw pointers point only to nodes owned by `list`.
 // Concurrent access is safe when wrapped in proper synchronization primitives.
-unsafe impl<K: Send, V: Send, S: Send> Send for LruSegment<K, V, S> {}
+unsafe impl<K: Send, V: Send, S: Send, M: Send> Send for LruSegment<K, V, S, M> {}
 
-// SAFETY: All mutation requires &mut self; shared references cannot cause data races.
-unsafe impl<K: Send, V: Send, S: Sync> Sync for LruSegment<K, V, S> {}
+// SAFETY: All mutation requires &mut self; shared references only hand out
+// `&K`/`&V`, so sharing requires K and V to be Sync.
+unsafe impl<K: Sync, V: Sync, S: Sync, M: Sync> Sync for LruSegment<K, V, S, M> {}
 
-impl<K: Hash + Eq, V: Clone, S: BuildHasher> LruSegment<K, V, S> {
+impl<K, V, S, M> LruSegment<K, V, S, M>
+where
+    K: Hash + Eq,
+    V: Clone,
+    S: BuildHasher,
+    M: MetricsRecorder<LruCacheMetrics>,
+{
     /// Creates a new LRU segment from a configuration.
     ///
     /// This is the **recommended** way to create an LRU segment. All configuration
@@ -201,14 +218,25 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> LruSegment<K, V, S> {
     /// * `hasher` - Hash builder for the internal HashMap
     #[allow(dead_code)] // Used by concurrent module when feature is enabled
     pub(crate) fn init(config: LruCacheConfig, hasher: S) -> Self {
-        let map_capacity = config.capacity.get().next_power_of_two();
-        LruSegment {
+        Self::init_with_capacity(config, hasher, config.capacity.get())
+    }
+
+    /// Like [`init()`](Self::init), but preallocates room for only
+    /// `capacity` entries.
+    #[allow(dead_code)] // Used by concurrent module when feature is enabled
+    pub(crate) fn init_with_capacity(config: LruCacheConfig, hasher: S, capacity: usize) -> Self {
+        let map_capacity = capacity.next_power_of_two();
+        let mut segment = LruSegment {
             config,
             list: List::new(config.capacity),
-            map: HashMap::with_capacity_and_hasher(map_capacity, hasher),
-            metrics: LruCacheMetrics::new(config.max_size),
+            map: KeyMap::with_capacity_and_hasher(map_capacity, hasher),
+            metrics: M::init(|| LruCacheMetrics::new(config.max_size)),
+            side: SideState::new(config.weight_limits),
             current_size: 0,
-        }
+        };
+        // Limits from the config show in the metrics before any entry
+        segment.publish_weights();
+        segment
     }
 
     #[inline]
@@ -239,8 +267,8 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> LruSegment<K, V, S> {
     }
 
     #[inline]
-    pub(crate) fn metrics(&self) -> &LruCacheMetrics {
-        &self.metrics
+    pub(crate) fn metrics(&self) -> Option<&LruCacheMetrics> {
+        self.metrics.collected()
     }
 
     pub(crate) fn get<Q>(&mut self, key: &Q) -> Option<&V>
@@ -248,13 +276,28 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> LruSegment<K, V, S> {
         K: Borrow<Q>,
         Q: ?Sized + Hash + Eq,
     {
-        if let Some(node) = self.map.get(key).copied() {
+        let hash = self.map.hash(key);
+        self.get_hashed(hash, key)
+    }
+
+    /// Like [`get()`](Self::get), for a key whose hash is already known.
+    pub(crate) fn get_hashed<Q>(&mut self, hash: u64, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Eq,
+    {
+        self.record_lookup(hash);
+        if let Some(node) = self.map.get(hash, key).copied() {
             unsafe {
                 // SAFETY: node comes from our map
                 self.list.move_to_front(node);
                 let entry = (*node).get_value_mut();
-                entry.touch(); // Update last_accessed timestamp
-                self.metrics.core.record_hit(entry.metadata.size);
+                let interval = entry.touch_at(self.side.now_nanos());
+                self.side.record_hit(hash, &entry.key, entry.metadata.size);
+                self.metrics.record(|m| {
+                    m.core.record_hit(entry.metadata.size);
+                    m.core.record_access_interval(interval);
+                });
                 Some(&entry.value)
             }
         } else {
@@ -264,7 +307,44 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> LruSegment<K, V, S> {
 
     #[inline]
     pub(crate) fn record_miss(&mut self, object_size: u64) {
-        self.metrics.core.record_miss(object_size);
+        self.metrics.record(|m| m.core.record_miss(object_size));
+    }
+
+    /// Like [`get_hashed()`](Self::get_hashed), also returning the value's
+    /// freshness. Expired values are removed and reported as missing.
+    pub(crate) fn get_fresh_hashed<Q>(&mut self, hash: u64, key: &Q) -> Option<(&V, Freshness)>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Eq,
+    {
+        match self.peek_freshness_hashed(hash, key) {
+            Some(Freshness::Expired) => {
+                self.remove_hashed(hash, key);
+                self.record_lookup(hash);
+                None
+            }
+            freshness => {
+                let freshness = freshness.unwrap_or(Freshness::Fresh);
+                self.get_hashed(hash, key).map(|value| (value, freshness))
+            }
+        }
+    }
+
+    /// Like [`get_hashed()`](Self::get_hashed), telling a key known to be
+    /// absent apart from one that is not cached.
+    pub(crate) fn lookup_hashed<Q>(&mut self, hash: u64, key: &Q) -> Lookup<&V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Eq,
+    {
+        if self.map.contains_key(hash, key) {
+            return self.get_hashed(hash, key).map_or(Lookup::Miss, Lookup::Hit);
+        }
+        self.lookup_uncached(hash, key)
+    }
+
+    pub(crate) fn reset_metrics(&mut self) {
+        self.metrics.record(|m| m.reset());
     }
 
     pub(crate) fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
@@ -272,13 +352,28 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> LruSegment<K, V, S> {
         K: Borrow<Q>,
         Q: ?Sized + Hash + Eq,
     {
-        let node = self.map.get(key).copied()?;
+        let hash = self.map.hash(key);
+        self.get_mut_hashed(hash, key)
+    }
+
+    /// Like [`get_mut()`](Self::get_mut), for a key whose hash is already known.
+    pub(crate) fn get_mut_hashed<Q>(&mut self, hash: u64, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Eq,
+    {
+        self.record_lookup(hash);
+        let node = self.map.get(hash, key).copied()?;
         unsafe {
             // SAFETY: node comes from our map
             self.list.move_to_front(node);
             let entry = (*node).get_value_mut();
-            entry.touch(); // Update last_accessed timestamp
-            self.metrics.core.record_hit(entry.metadata.size);
+            let interval = entry.touch_at(self.side.now_nanos());
+            self.side.record_hit(hash, &entry.key, entry.metadata.size);
+            self.metrics.record(|m| {
+                m.core.record_hit(entry.metadata.size);
+                m.core.record_access_interval(interval);
+            });
             Some(&mut entry.value)
         }
     }
@@ -294,7 +389,57 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> LruSegment<K, V, S> {
     where
         K: Clone + Hash + Eq,
     {
-        if let Some(&node) = self.map.get(&key) {
+        let hash = self.map.hash(&key);
+        self.put_hashed(hash, key, value, size)
+    }
+
+    /// Like [`put()`](Self::put), for a key whose hash is already known.
+    pub(crate) fn put_hashed(
+        &mut self,
+        hash: u64,
+        key: K,
+        value: V,
+        size: u64,
+    ) -> Option<Vec<(K, V)>>
+    where
+        K: Clone,
+    {
+        self.put_hashed_with(hash, key, value, size, |key, value, _| (key, value))
+    }
+
+    /// Like [`put_hashed()`](Self::put_hashed), returning each evicted entry
+    /// as `evicted_entry(key, value, size)`.
+    pub(crate) fn put_hashed_with<E>(
+        &mut self,
+        hash: u64,
+        key: K,
+        value: V,
+        size: u64,
+        evicted_entry: impl FnMut(K, V, u64) -> E,
+    ) -> Option<Vec<E>>
+    where
+        K: Clone,
+    {
+        self.put_weighted_hashed_with(hash, key, value, size, None, evicted_entry)
+    }
+
+    /// Like [`put_hashed_with()`](Self::put_hashed_with), for an entry
+    /// weighing `weights`. Without them, a replaced value's weights are kept
+    /// and a new entry weighs nothing.
+    fn put_weighted_hashed_with<E>(
+        &mut self,
+        hash: u64,
+        key: K,
+        value: V,
+        size: u64,
+        weights: Option<Weights>,
+        mut evicted_entry: impl FnMut(K, V, u64) -> E,
+    ) -> Option<Vec<E>>
+    where
+        K: Clone,
+    {
+        self.side.forget(hash, &key);
+        if let Some(&node) = self.map.get(hash, &key) {
             unsafe {
                 // SAFETY: node comes from our map
                 self.list.move_to_front(node);
@@ -303,45 +448,58 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> LruSegment<K, V, S> {
                 // Update size tracking: remove old size, add new size
                 let old_size = entry.metadata.size;
                 self.current_size = self.current_size.saturating_sub(old_size);
-                self.metrics.core.cache_size_bytes =
-                    self.metrics.core.cache_size_bytes.saturating_sub(old_size);
 
                 // Update entry fields
                 // TODO: seems wasteful to replace key since it should be the same?
                 let _old_key = core::mem::replace(&mut entry.key, key);
                 let _old_value = core::mem::replace(&mut entry.value, value);
-                entry.metadata.size = size;
-                entry.touch();
+                let replaced = entry.metadata.weights;
+                let weights = weights.unwrap_or(replaced);
+                // A replaced value starts aging afresh
+                entry.metadata = CacheMetadata::new(size)
+                    .with_hash(hash)
+                    .with_weights(weights)
+                    .with_time(self.side.now_nanos());
+                self.release_weights(&replaced);
+                self.add_weights(&weights);
 
                 self.current_size += size;
-                self.metrics.core.cache_size_bytes += size;
-                self.metrics.core.bytes_written_to_cache += size;
+                self.metrics.record(|m| {
+                    m.core.cache_size_bytes =
+                        m.core.cache_size_bytes.saturating_sub(old_size) + size;
+                    m.core.bytes_written_to_cache += size;
+                });
 
                 // Replacement is not eviction - don't return the old value
                 return None;
             }
         }
 
+        let weights = weights.unwrap_or_default();
         let mut evicted = Vec::new();
 
         // Evict while entry count limit OR size limit would be exceeded
         while self.map.len() >= self.cap().get()
             || (self.current_size + size > self.config.max_size && !self.map.is_empty())
         {
-            if let Some(entry) = self.evict() {
-                self.metrics.core.evictions += 1;
-                evicted.push(entry);
+            if let Some((old_key, old_value, old_size)) = self.evict() {
+                self.metrics.record(|m| m.core.count_eviction());
+                evicted.push(evicted_entry(old_key, old_value, old_size));
             } else {
                 break;
             }
         }
 
         // Create new CacheEntry and add to list
-        let cache_entry = CacheEntry::new(key.clone(), value, size);
+        let cache_entry = CacheEntry::new(key.clone(), value, size)
+            .with_hash(hash)
+            .with_weights(weights)
+            .with_time(self.side.now_nanos());
         if let Some(node) = self.list.add(cache_entry) {
-            self.map.insert(key, node);
+            self.map.insert(hash, key, node);
             self.current_size += size;
-            self.metrics.core.record_insertion(size);
+            self.add_weights(&weights);
+            self.metrics.record(|m| m.core.record_insertion(size));
         }
 
         if evicted.is_empty() {
@@ -356,7 +514,18 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> LruSegment<K, V, S> {
         K: Borrow<Q>,
         Q: ?Sized + Hash + Eq,
     {
-        let node = self.map.remove(key)?;
+        let hash = self.map.hash(key);
+        self.remove_hashed(hash, key)
+    }
+
+    /// Like [`remove()`](Self::remove), for a key whose hash is already known.
+    pub(crate) fn remove_hashed<Q>(&mut self, hash: u64, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Eq,
+    {
+        self.side.forget(hash, key);
+        let node = self.map.remove(hash, key)?;
         unsafe {
             // SAFETY: node comes from our map; take_value moves the value out
             // and Box::from_raw frees memory (MaybeUninit won't double-drop).
@@ -365,8 +534,9 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> LruSegment<K, V, S> {
                 let cache_entry = (*entry_ptr).take_value();
                 let removed_size = cache_entry.metadata.size;
                 let _ = Box::from_raw(entry_ptr);
+                self.release_weights(&cache_entry.metadata.weights);
                 self.current_size = self.current_size.saturating_sub(removed_size);
-                self.metrics.core.record_removal(removed_size);
+                self.metrics.record(|m| m.core.record_removal(removed_size));
                 Some(cache_entry.value)
             } else {
                 None
@@ -375,8 +545,9 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> LruSegment<K, V, S> {
     }
 
     pub(crate) fn clear(&mut self) {
+        self.clear_side();
         self.current_size = 0;
-        self.metrics.core.cache_size_bytes = 0;
+        self.metrics.record(|m| m.core.cache_size_bytes = 0);
         self.map.clear();
         self.list.clear();
     }
@@ -391,7 +562,7 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> LruSegment<K, V, S> {
         K: Borrow<Q>,
         Q: ?Sized + Hash + Eq,
     {
-        self.map.contains_key(key)
+        self.map.contains_key(self.map.hash(key), key)
     }
 
     /// Returns a reference to the value without updating the LRU order.
@@ -403,7 +574,7 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> LruSegment<K, V, S> {
         K: Borrow<Q>,
         Q: ?Sized + Hash + Eq,
     {
-        let node = self.map.get(key).copied()?;
+        let node = self.map.get(self.map.hash(key), key).copied()?;
         unsafe {
             // SAFETY: node comes from our map, so it's a valid pointer
             let entry = (*node).get_value();
@@ -411,14 +582,43 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> LruSegment<K, V, S> {
         }
     }
 
+    /// Returns the stored key and value without updating access metadata.
+    ///
+    /// Used by the concurrent read path, which looks entries up under a shared
+    /// lock and needs an owned copy of the key to record the access.
+    #[allow(dead_code)] // Used by concurrent module when feature is enabled
+    pub(crate) fn peek_key_value_hashed<Q>(&self, hash: u64, key: &Q) -> Option<(&K, &V)>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Eq,
+    {
+        let &node = self.map.get(hash, key)?;
+        unsafe {
+            // SAFETY: node comes from our map, so it's a valid pointer
+            let entry = (*node).get_value();
+            Some((&entry.key, &entry.value))
+        }
+    }
+
+    /// Evicts the entry this segment's policy would evict next.
+    ///
+    /// Used by the concurrent caches to enforce a budget shared by all segments.
+    #[allow(dead_code)] // Used by concurrent module when feature is enabled
+    pub(crate) fn pop_victim(&mut self) -> Option<(K, V)> {
+        let (key, value, _) = self.evict()?;
+        self.metrics.record(|m| m.core.count_eviction());
+        Some((key, value))
+    }
+
     /// Removes and returns the eviction candidate (least recently used entry).
     ///
     /// This method does **not** increment the eviction counter in metrics.
     /// Eviction metrics are only recorded when the cache internally evicts
     /// entries to make room during `put()` operations.
     ///
-    /// Returns `None` if the cache is empty.
-    fn evict(&mut self) -> Option<(K, V)> {
+    /// Returns the evicted key, value and size, or `None` if the cache is
+    /// empty.
+    fn evict(&mut self) -> Option<(K, V, u64)> {
         let old_entry = self.list.remove_last()?;
         unsafe {
             // SAFETY: entry comes from list.remove_last(); take_value moves the
@@ -427,16 +627,83 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> LruSegment<K, V, S> {
             let entry_ptr = Box::into_raw(old_entry);
             let cache_entry = (*entry_ptr).take_value();
             let evicted_size = cache_entry.metadata.size;
-            self.map.remove(&cache_entry.key);
+            let hash = cache_entry.metadata.hash;
+            self.map.remove(hash, &cache_entry.key);
+            self.side.untag(hash, &cache_entry.key);
+            self.release_weights(&cache_entry.metadata.weights);
             self.current_size = self.current_size.saturating_sub(evicted_size);
-            self.metrics.core.record_removal(evicted_size);
+            self.metrics.record(|m| {
+                m.core.record_removal(evicted_size);
+                let (age, idle) = cache_entry.metadata.age_and_idle_at(self.side.now_nanos());
+                m.core.record_eviction_age(age, idle);
+            });
             let _ = Box::from_raw(entry_ptr);
-            Some((cache_entry.key, cache_entry.value))
+            Some((cache_entry.key, cache_entry.value, evicted_size))
         }
     }
 }
 
-impl<K, V, S> core::fmt::Debug for LruSegment<K, V, S> {
+impl<K, V, S, M> HasSideState for LruSegment<K, V, S, M>
+where
+    K: Hash + Eq,
+    V: Clone,
+    S: BuildHasher,
+    M: MetricsRecorder<LruCacheMetrics>,
+{
+    type Key = K;
+    type Value = V;
+    type Meta = ();
+    type Hasher = S;
+
+    fn side(&self) -> &SideState<K> {
+        &self.side
+    }
+
+    fn side_mut(&mut self) -> &mut SideState<K> {
+        &mut self.side
+    }
+
+    fn entries(&self) -> &KeyMap<K, *mut ListEntry<CacheEntry<K, V>>, S> {
+        &self.map
+    }
+
+    fn record_core(&mut self, record: impl FnOnce(&mut CoreCacheMetrics)) {
+        self.metrics.record(|m| record(&mut m.core));
+    }
+
+    fn insert_entry(
+        &mut self,
+        hash: u64,
+        key: K,
+        value: V,
+        size: u64,
+        weights: Option<Weights>,
+    ) -> Option<Vec<(K, V)>>
+    where
+        K: Clone,
+    {
+        self.put_weighted_hashed_with(hash, key, value, size, weights, |key, value, _| {
+            (key, value)
+        })
+    }
+
+    fn remove_entry<Q>(&mut self, hash: u64, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q> + Clone,
+        Q: ?Sized + Eq,
+    {
+        self.remove_hashed(hash, key)
+    }
+
+    fn evict_entry(&mut self) -> Option<(K, V)>
+    where
+        K: Clone,
+    {
+        self.pop_victim()
+    }
+}
+
+impl<K, V, S, M> core::fmt::Debug for LruSegment<K, V, S, M> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.debug_struct("LruSegment")
             .field("capacity", &self.config.capacity)
@@ -455,6 +722,7 @@ impl<K, V, S> core::fmt::Debug for LruSegment<K, V, S> {
 /// - `K`: Key type. Must implement `Hash + Eq`. For mutation operations, also needs `Clone`.
 /// - `V`: Value type. Must implement `Clone` for retrieval operations.
 /// - `S`: Hash builder type. Defaults to `DefaultHashBuilder`.
+/// - `M`: Metrics recorder. Defaults to [`LruCacheMetrics`]; [`NoMetrics`] collects none.
 ///
 /// # Capacity Modes
 ///
@@ -472,6 +740,7 @@ impl<K, V, S> core::fmt::Debug for LruSegment<K, V, S> {
 /// let config = LruCacheConfig {
 ///     capacity: NonZeroUsize::new(2).unwrap(),
 ///     max_size: u64::MAX,
+///     weight_limits: None,
 /// };
 /// let mut cache = LruCache::init(config, None);
 ///
@@ -484,11 +753,17 @@ impl<K, V, S> core::fmt::Debug for LruSegment<K, V, S> {
 /// assert_eq!(cache.get(&"banana"), None);
 /// ```
 #[derive(Debug)]
-pub struct LruCache<K, V, S = DefaultHashBuilder> {
-    segment: LruSegment<K, V, S>,
+pub struct LruCache<K, V, S = DefaultHashBuilder, M = LruCacheMetrics> {
+    segment: LruSegment<K, V, S, M>,
 }
 
-impl<K: Hash + Eq, V: Clone, S: BuildHasher> LruCache<K, V, S> {
+impl<K, V, S, M> LruCache<K, V, S, M>
+where
+    K: Hash + Eq,
+    V: Clone,
+    S: BuildHasher,
+    M: MetricsRecorder<LruCacheMetrics>,
+{
     /// Returns the maximum number of entries the cache can hold.
     #[inline]
     pub fn cap(&self) -> NonZeroUsize {
@@ -539,6 +814,7 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> LruCache<K, V, S> {
     /// let config = LruCacheConfig {
     ///     capacity: NonZeroUsize::new(10).unwrap(),
     ///     max_size: u64::MAX,
+    ///     weight_limits: None,
     /// };
     /// let mut cache = LruCache::init(config, None);
     /// cache.put("key", 42, 1);
@@ -568,6 +844,243 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> LruCache<K, V, S> {
         self.segment.record_miss(object_size);
     }
 
+    /// Tracks the metrics of the most recent requests in `window`, replacing
+    /// any previous window, or stops tracking them with `None`.
+    ///
+    /// The window is reported as `window_*` keys by
+    /// [`metrics()`](CacheMetrics::metrics). Does nothing without metrics.
+    pub fn set_metrics_window(&mut self, window: Option<RollingWindow>) {
+        self.segment.set_metrics_window(window);
+    }
+
+    /// Estimates the miss ratio an LRU cache would have at other capacities
+    /// with `curve`, replacing any previous curve, or stops with `None`.
+    ///
+    /// Every [`get()`](Self::get) and [`get_mut()`](Self::get_mut) lookup is
+    /// sampled, hit or miss. The curve is reported as `mrc_*` keys by
+    /// [`metrics()`](CacheMetrics::metrics). Does nothing without metrics.
+    pub fn set_miss_ratio_curve(&mut self, curve: Option<MissRatioCurve>) {
+        self.segment.set_miss_ratio_curve(curve);
+    }
+
+    /// Ranks the keys of lookups that hit by accesses and by bytes served
+    /// with `hot_keys`, replacing any previous tracker, or stops with `None`.
+    ///
+    /// Works with and without metrics. Read the rankings through
+    /// [`hot_keys()`](Self::hot_keys).
+    pub fn set_hot_keys(&mut self, hot_keys: Option<HotKeys<K>>) {
+        self.segment.set_hot_keys(hot_keys);
+    }
+
+    /// Returns the hot-key tracker, if one is set.
+    pub fn hot_keys(&self) -> Option<&HotKeys<K>> {
+        self.segment.hot_keys()
+    }
+
+    /// Remembers keys known to be absent from the backing store in
+    /// `negative`, replacing any previous negative cache, or stops with
+    /// `None`.
+    ///
+    /// Works with and without metrics. Mark keys absent with
+    /// [`put_absent()`](Self::put_absent) and tell them apart from keys that
+    /// are not cached with [`lookup()`](Self::lookup).
+    pub fn set_negative_cache(&mut self, negative: Option<NegativeCache<K>>) {
+        self.segment.set_negative_cache(negative);
+    }
+
+    /// Returns the negative cache, if one is set.
+    pub fn negative_cache(&self) -> Option<&NegativeCache<K>> {
+        self.segment.negative_cache()
+    }
+
+    /// Ages entries from their creation under `policy`, replacing any
+    /// previous policy, or stops with `None`.
+    ///
+    /// The policy only affects [`get_fresh()`](Self::get_fresh); other
+    /// lookups serve entries regardless of their age.
+    pub fn set_refresh_policy(&mut self, policy: Option<RefreshPolicy>) {
+        self.segment.set_refresh_policy(policy);
+    }
+
+    /// Returns the refresh policy, if one is set.
+    pub fn refresh_policy(&self) -> Option<RefreshPolicy> {
+        self.segment.refresh_policy()
+    }
+
+    /// Expires values early at random under `xfetch`, replacing any
+    /// previous mode, or stops with `None`.
+    ///
+    /// Only applies while a refresh policy is set: fresh values are reported
+    /// as stale by [`get_fresh()`](Self::get_fresh) with a probability that
+    /// grows as the `ttl` nears, and stay cached.
+    pub fn set_xfetch(&mut self, xfetch: Option<XFetch>) {
+        self.segment.set_xfetch(xfetch);
+    }
+
+    /// Returns the early expiration mode, if one is set.
+    pub fn xfetch(&self) -> Option<&XFetch> {
+        self.segment.xfetch()
+    }
+
+    /// Stamps and ages entries with `clock`, or with the system time under
+    /// `None`, the default.
+    ///
+    /// Ages drive the refresh policy and the age and idle-time metrics.
+    /// Entries keep the timestamps they were given, so set the clock before
+    /// inserting any. Without the `std` feature there is no system time, and
+    /// entries only age under a clock.
+    pub fn set_clock(&mut self, clock: Option<Arc<dyn Clock>>) {
+        self.segment.set_clock(clock);
+    }
+
+    /// Returns the clock entries are stamped with, if one is set.
+    pub fn clock(&self) -> Option<&Arc<dyn Clock>> {
+        self.segment.clock()
+    }
+
+    /// Looks up `key` like [`get()`](Self::get), also returning the value's
+    /// [`Freshness`] under the refresh policy.
+    ///
+    /// Stale values and values in the grace window are returned for the
+    /// caller to refresh, e.g. with [`put()`](Self::put). Values past the
+    /// grace window are removed and reported as missing. Without a policy
+    /// every value is fresh.
+    pub fn get_fresh<Q>(&mut self, key: &Q) -> Option<(&V, Freshness)>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let hash = self.segment.map.hash(key);
+        self.segment.get_fresh_hashed(hash, key)
+    }
+
+    /// Inserts a key-value pair like [`put()`](Self::put), tagging it with
+    /// `tags`.
+    ///
+    /// The tags replace any previous tags of the key, and are dropped with
+    /// the entry when it is replaced by [`put()`](Self::put), removed or
+    /// evicted. See [`invalidate_tag()`](Self::invalidate_tag).
+    pub fn put_tagged(&mut self, key: K, value: V, size: u64, tags: &[u64]) -> Option<Vec<(K, V)>>
+    where
+        K: Clone,
+    {
+        let hash = self.segment.map.hash(&key);
+        self.segment.put_tagged_hashed(hash, key, value, size, tags)
+    }
+
+    /// Weighs entries in the dimensions of `limits`, replacing any previous
+    /// limits, or stops with `None`.
+    ///
+    /// Cached entries keep their weights, so they count against the new
+    /// limits. Limits can also be set up front with
+    /// `weight_limits` in the config. See
+    /// [`put_weighted()`](Self::put_weighted).
+    pub fn set_weight_limits(&mut self, limits: Option<WeightLimits>) {
+        self.segment.set_weight_limits(limits);
+    }
+
+    /// Returns the weight limits, if set.
+    pub fn weight_limits(&self) -> Option<&WeightLimits> {
+        self.segment.weight_limits()
+    }
+
+    /// Inserts a key-value pair like [`put()`](Self::put), weighing it with
+    /// `weights`.
+    ///
+    /// Entries are evicted in the cache's usual order until every dimension
+    /// of the [weight limits](Self::set_weight_limits) fits `weights`, then
+    /// the entry is inserted even if it exceeds a limit on its own. The
+    /// weights replace any previous weights of the key, are kept when
+    /// [`put()`](Self::put) replaces its value, and are dropped with the
+    /// entry when it is removed or evicted. Without weight limits the weights
+    /// are kept but evict nothing.
+    pub fn put_weighted(
+        &mut self,
+        key: K,
+        value: V,
+        size: u64,
+        weights: Weights,
+    ) -> Option<Vec<(K, V)>>
+    where
+        K: Clone,
+    {
+        let hash = self.segment.map.hash(&key);
+        self.segment
+            .put_weighted_hashed(hash, key, value, size, weights)
+    }
+
+    /// Returns the weights of `key`, which are zero if it is unweighted or
+    /// not cached.
+    pub fn weights<Q>(&self, key: &Q) -> Weights
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let hash = self.segment.map.hash(key);
+        self.segment.weights_hashed(hash, key)
+    }
+
+    /// Returns the tags of `key`, which are empty if it is untagged or not
+    /// cached.
+    pub fn tags<Q>(&self, key: &Q) -> &[u64]
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let hash = self.segment.map.hash(key);
+        self.segment.tags_hashed(hash, key)
+    }
+
+    /// Removes every entry tagged with `tag`, returning how many were
+    /// removed.
+    ///
+    /// Takes time proportional to the number of entries removed. Removals
+    /// are recorded like [`remove()`](Self::remove)'s.
+    pub fn invalidate_tag(&mut self, tag: u64) -> usize
+    where
+        K: Clone,
+    {
+        self.segment.invalidate_tag(tag)
+    }
+
+    /// Records that `key` is absent from the backing store for the next
+    /// `ttl`, removing any cached value for it.
+    ///
+    /// The entry counts against the negative cache's budget, not this
+    /// cache's, and is dropped by the next [`put()`](Self::put) of the key.
+    /// Does nothing without a negative cache.
+    pub fn put_absent(&mut self, key: K, ttl: Duration)
+    where
+        K: Clone,
+    {
+        let hash = self.segment.map.hash(&key);
+        self.segment.put_absent_hashed(hash, key, ttl);
+    }
+
+    /// Looks up `key` like [`get()`](Self::get), but tells a key known to be
+    /// absent apart from one that is not cached.
+    ///
+    /// With a negative cache, lookups answered by a negative entry count as
+    /// negative hits and lookups that find neither a value nor a negative
+    /// entry count as negative misses.
+    pub fn lookup<Q>(&mut self, key: &Q) -> Lookup<&V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let hash = self.segment.map.hash(key);
+        self.segment.lookup_hashed(hash, key)
+    }
+
+    /// Zeroes the metrics counters and clears the rolling window and the
+    /// miss-ratio curve.
+    ///
+    /// Gauges describing the cache's current contents, such as its size in
+    /// bytes, are kept.
+    pub fn reset_metrics(&mut self) {
+        self.segment.reset_metrics();
+    }
+
     /// Retrieves a mutable reference to the value for the given key.
     ///
     /// If the key exists, it is moved to the MRU position.
@@ -583,6 +1096,7 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> LruCache<K, V, S> {
     /// let config = LruCacheConfig {
     ///     capacity: NonZeroUsize::new(10).unwrap(),
     ///     max_size: u64::MAX,
+    ///     weight_limits: None,
     /// };
     /// let mut cache = LruCache::init(config, None);
     /// cache.put("counter", 0, 1);
@@ -602,7 +1116,13 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> LruCache<K, V, S> {
     }
 }
 
-impl<K: Hash + Eq + Clone, V: Clone, S: BuildHasher> LruCache<K, V, S> {
+impl<K, V, S, M> LruCache<K, V, S, M>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+    S: BuildHasher,
+    M: MetricsRecorder<LruCacheMetrics>,
+{
     /// Inserts a key-value pair into the cache.
     ///
     /// If the key already exists, the value is updated and the entry moves
@@ -631,6 +1151,7 @@ impl<K: Hash + Eq + Clone, V: Clone, S: BuildHasher> LruCache<K, V, S> {
     /// let config = LruCacheConfig {
     ///     capacity: NonZeroUsize::new(2).unwrap(),
     ///     max_size: u64::MAX,
+    ///     weight_limits: None,
     /// };
     /// let mut cache = LruCache::init(config, None);
     ///
@@ -651,6 +1172,7 @@ impl<K: Hash + Eq + Clone, V: Clone, S: BuildHasher> LruCache<K, V, S> {
     /// let config = LruCacheConfig {
     ///     capacity: NonZeroUsize::new(100).unwrap(),
     ///     max_size: 1024 * 1024,  // 1MB max
+    ///     weight_limits: None,
     /// };
     /// let mut cache: LruCache<String, Vec<u8>> = LruCache::init(config, None);
     ///
@@ -678,6 +1200,7 @@ impl<K: Hash + Eq + Clone, V: Clone, S: BuildHasher> LruCache<K, V, S> {
     /// let config = LruCacheConfig {
     ///     capacity: NonZeroUsize::new(10).unwrap(),
     ///     max_size: u64::MAX,
+    ///     weight_limits: None,
     /// };
     /// let mut cache = LruCache::init(config, None);
     /// cache.put("key", 42, 1);
@@ -718,6 +1241,7 @@ impl<K: Hash + Eq + Clone, V: Clone, S: BuildHasher> LruCache<K, V, S> {
     /// let config = LruCacheConfig {
     ///     capacity: NonZeroUsize::new(2).unwrap(),
     ///     max_size: u64::MAX,
+    ///     weight_limits: None,
     /// };
     /// let mut cache = LruCache::init(config, None);
     /// cache.put("a", 1, 1);
@@ -754,6 +1278,7 @@ impl<K: Hash + Eq + Clone, V: Clone, S: BuildHasher> LruCache<K, V, S> {
     /// let config = LruCacheConfig {
     ///     capacity: NonZeroUsize::new(3).unwrap(),
     ///     max_size: u64::MAX,
+    ///     weight_limits: None,
     /// };
     /// let mut cache = LruCache::init(config, None);
     /// cache.put("a", 1, 1);
@@ -797,7 +1322,8 @@ where
 {
     /// Creates a new LRU cache from a configuration with an optional hasher.
     ///
-    /// This is the **only** way to create an LRU cache.
+    /// Use [`init_without_metrics()`](Self::init_without_metrics) to skip
+    /// metrics collection.
     ///
     /// # Arguments
     ///
@@ -815,6 +1341,7 @@ where
     /// let config = LruCacheConfig {
     ///     capacity: NonZeroUsize::new(100).unwrap(),
     ///     max_size: u64::MAX,
+    ///     weight_limits: None,
     /// };
     /// let mut cache: LruCache<&str, i32> = LruCache::init(config, None);
     /// cache.put("key", 42, 1);
@@ -823,6 +1350,7 @@ where
     /// let config = LruCacheConfig {
     ///     capacity: NonZeroUsize::new(1000).unwrap(),
     ///     max_size: 10 * 1024 * 1024,  // 10MB
+    ///     weight_limits: None,
     /// };
     /// let cache: LruCache<String, Vec<u8>> = LruCache::init(config, None);
     /// ```
@@ -834,15 +1362,360 @@ where
             segment: LruSegment::init(config, hasher.unwrap_or_default()),
         }
     }
+
+    /// Creates a new LRU cache that collects no metrics.
+    ///
+    /// Behaves exactly like a cache created with [`init()`](Self::init), but
+    /// skips all metrics bookkeeping; [`metrics()`](CacheMetrics::metrics)
+    /// returns an empty map.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cache_rs::LruCache;
+    /// use cache_rs::config::LruCacheConfig;
+    /// use cache_rs::metrics::NoMetrics;
+    /// use core::num::NonZeroUsize;
+    ///
+    /// let config = LruCacheConfig {
+    ///     capacity: NonZeroUsize::new(100).unwrap(),
+    ///     max_size: u64::MAX,
+    ///     weight_limits: None,
+    /// };
+    /// let mut cache: LruCache<&str, i32, _, NoMetrics> = LruCache::init_without_metrics(config, None);
+    /// cache.put("key", 42, 1);
+    /// assert_eq!(cache.get(&"key"), Some(&42));
+    /// ```
+    pub fn init_without_metrics(
+        config: LruCacheConfig,
+        hasher: Option<DefaultHashBuilder>,
+    ) -> LruCache<K, V, DefaultHashBuilder, NoMetrics> {
+        LruCache {
+            segment: LruSegment::init(config, hasher.unwrap_or_default()),
+        }
+    }
 }
 
-impl<K: Hash + Eq, V: Clone, S: BuildHasher> CacheMetrics for LruCache<K, V, S> {
+impl<K, V, S, M> CacheMetrics for LruCache<K, V, S, M>
+where
+    K: Hash + Eq,
+    V: Clone,
+    S: BuildHasher,
+    M: MetricsRecorder<LruCacheMetrics>,
+{
     fn metrics(&self) -> BTreeMap<String, f64> {
-        self.segment.metrics().metrics()
+        self.segment
+            .metrics()
+            .map_or_else(BTreeMap::new, CacheMetrics::metrics)
+    }
+
+    fn snapshot(&self) -> MetricsSnapshot {
+        self.segment
+            .metrics()
+            .map_or(MetricsSnapshot::Disabled, CacheMetrics::snapshot)
     }
 
     fn algorithm_name(&self) -> &'static str {
-        self.segment.metrics().algorithm_name()
+        "LRU"
+    }
+}
+
+impl<K, V, S, M> crate::tiered::PrimaryCache for LruCache<K, V, S, M>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+    S: BuildHasher,
+    M: MetricsRecorder<LruCacheMetrics>,
+{
+    type Key = K;
+    type Value = V;
+
+    fn get(&mut self, key: &K) -> Option<&V> {
+        self.segment.get(key)
+    }
+
+    fn peek(&self, key: &K) -> Option<&V> {
+        self.segment.peek(key)
+    }
+
+    fn contains(&self, key: &K) -> bool {
+        self.segment.contains(key)
+    }
+
+    fn put(&mut self, key: K, value: V, size: u64) -> Option<Vec<(K, V, u64)>> {
+        let hash = self.segment.map.hash(&key);
+        self.segment
+            .put_hashed_with(hash, key, value, size, |key, value, size| {
+                (key, value, size)
+            })
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        self.segment.remove(key)
+    }
+
+    fn len(&self) -> usize {
+        self.segment.len()
+    }
+
+    fn clear(&mut self) {
+        self.segment.clear()
+    }
+
+    fn set_refresh_policy(&mut self, policy: Option<RefreshPolicy>) {
+        self.segment.set_refresh_policy(policy)
+    }
+
+    fn set_xfetch(&mut self, xfetch: Option<XFetch>) {
+        self.segment.set_xfetch(xfetch)
+    }
+
+    fn peek_freshness(&self, key: &K) -> Option<Freshness> {
+        let hash = self.segment.map.hash(key);
+        self.segment.peek_freshness_hashed(hash, key)
+    }
+
+    fn record_miss(&mut self, object_size: u64) {
+        self.segment.record_miss(object_size)
+    }
+}
+
+#[cfg(feature = "concurrent-core")]
+impl<K, V, S, M> crate::concurrent::Segment for LruCache<K, V, S, M>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+    S: BuildHasher + Clone,
+    M: MetricsRecorder<LruCacheMetrics>,
+{
+    type Key = K;
+
+    type Value = V;
+
+    type Hasher = S;
+
+    type Config = LruCacheConfig;
+
+    const CONCURRENT_NAME: &'static str = "ConcurrentLRU";
+
+    fn init(config: LruCacheConfig, hasher: S) -> Self {
+        LruCache {
+            segment: LruSegment::init(config, hasher),
+        }
+    }
+
+    fn init_shared(config: LruCacheConfig, hasher: S, capacity: usize, segments: usize) -> Self {
+        // Weights aren't in the shared budget, so each segment weighs its share
+        let config = LruCacheConfig {
+            weight_limits: config
+                .weight_limits
+                .map(|limits| limits.for_segment(segments)),
+            ..config
+        };
+        LruCache {
+            segment: LruSegment::init_with_capacity(config, hasher, capacity),
+        }
+    }
+
+    fn split_config(config: LruCacheConfig, segments: usize) -> LruCacheConfig {
+        LruCacheConfig {
+            capacity: NonZeroUsize::new((config.capacity.get() / segments).max(1)).unwrap(),
+            max_size: config.max_size / segments as u64,
+            weight_limits: config
+                .weight_limits
+                .map(|limits| limits.for_segment(segments)),
+        }
+    }
+
+    fn capacity(&self) -> usize {
+        self.segment.cap().get()
+    }
+
+    fn len(&self) -> usize {
+        self.segment.len()
+    }
+
+    fn current_size(&self) -> u64 {
+        self.segment.current_size()
+    }
+
+    fn max_size(&self) -> u64 {
+        self.segment.max_size()
+    }
+
+    fn get<Q>(&mut self, hash: u64, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.segment.get_hashed(hash, key)
+    }
+
+    fn get_mut<Q>(&mut self, hash: u64, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.segment.get_mut_hashed(hash, key)
+    }
+
+    fn peek_key_value<Q>(&self, hash: u64, key: &Q) -> Option<(&K, &V)>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.segment.peek_key_value_hashed(hash, key)
+    }
+
+    fn put(&mut self, hash: u64, key: K, value: V, size: u64) -> Option<Vec<(K, V)>> {
+        self.segment.put_hashed(hash, key, value, size)
+    }
+
+    fn remove<Q>(&mut self, hash: u64, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.segment.remove_hashed(hash, key)
+    }
+
+    fn clear(&mut self) {
+        self.segment.clear()
+    }
+
+    fn pop_victim(&mut self) -> Option<(K, V)> {
+        self.segment.pop_victim()
+    }
+
+    fn record_miss(&mut self, object_size: u64) {
+        self.segment.record_miss(object_size)
+    }
+
+    fn record_lookup(&mut self, hash: u64) {
+        self.segment.record_lookup(hash)
+    }
+
+    fn peek_freshness<Q>(&self, hash: u64, key: &Q) -> Option<Freshness>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.segment.peek_freshness_hashed(hash, key)
+    }
+
+    fn core_metrics(&self) -> &crate::metrics::CoreCacheMetrics {
+        self.segment
+            .metrics()
+            .map_or(&crate::metrics::NO_CORE_METRICS, |metrics| &metrics.core)
+    }
+}
+
+#[cfg(feature = "concurrent-core")]
+impl<K, V, S, M> crate::concurrent::SegmentFeatures for LruCache<K, V, S, M>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+    S: BuildHasher + Clone,
+    M: MetricsRecorder<LruCacheMetrics>,
+{
+    fn set_metrics_window(&mut self, window: Option<RollingWindow>) {
+        self.segment.set_metrics_window(window)
+    }
+
+    fn reset_metrics(&mut self) {
+        self.segment.reset_metrics()
+    }
+
+    fn set_miss_ratio_curve(&mut self, curve: Option<MissRatioCurve>) {
+        self.segment.set_miss_ratio_curve(curve)
+    }
+
+    fn set_hot_keys(&mut self, hot_keys: Option<HotKeys<K>>) {
+        self.segment.set_hot_keys(hot_keys)
+    }
+
+    fn hot_keys(&self) -> Option<&HotKeys<K>> {
+        self.segment.hot_keys()
+    }
+
+    fn set_negative_cache(&mut self, negative: Option<NegativeCache<K>>) {
+        self.segment.set_negative_cache(negative)
+    }
+
+    fn negative_cache(&self) -> Option<&NegativeCache<K>> {
+        self.segment.negative_cache()
+    }
+
+    fn put_absent(&mut self, hash: u64, key: K, ttl: Duration) {
+        self.segment.put_absent_hashed(hash, key, ttl)
+    }
+
+    fn check_absent<Q>(&mut self, hash: u64, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.segment.check_absent(hash, key)
+    }
+
+    fn put_tagged(
+        &mut self,
+        hash: u64,
+        key: K,
+        value: V,
+        size: u64,
+        tags: &[u64],
+    ) -> Option<Vec<(K, V)>> {
+        self.segment.put_tagged_hashed(hash, key, value, size, tags)
+    }
+
+    fn invalidate_tag(&mut self, tag: u64) -> usize {
+        self.segment.invalidate_tag(tag)
+    }
+
+    fn put_weighted(
+        &mut self,
+        hash: u64,
+        key: K,
+        value: V,
+        size: u64,
+        weights: Weights,
+    ) -> Option<Vec<(K, V)>> {
+        self.segment
+            .put_weighted_hashed(hash, key, value, size, weights)
+    }
+
+    fn set_weight_limits(&mut self, limits: Option<WeightLimits>) {
+        self.segment.set_weight_limits(limits)
+    }
+
+    fn weight_limits(&self) -> Option<WeightLimits> {
+        self.segment.weight_limits().copied()
+    }
+
+    fn put_with_cost(
+        &mut self,
+        hash: u64,
+        key: K,
+        value: V,
+        size: u64,
+        cost: u64,
+    ) -> Option<Vec<(K, V)>> {
+        // LRU doesn't weigh entries by their miss cost
+        let _ = cost;
+        self.segment.put_hashed(hash, key, value, size)
+    }
+
+    fn set_refresh_policy(&mut self, policy: Option<RefreshPolicy>) {
+        self.segment.set_refresh_policy(policy)
+    }
+
+    fn set_xfetch(&mut self, xfetch: Option<XFetch>) {
+        self.segment.set_xfetch(xfetch)
+    }
+
+    fn set_clock(&mut self, clock: Option<Arc<dyn Clock>>) {
+        self.segment.set_clock(clock)
     }
 }
 
@@ -866,6 +1739,7 @@ mod tests {
         let config = LruCacheConfig {
             capacity: NonZeroUsize::new(cap).unwrap(),
             max_size: u64::MAX,
+            weight_limits: None,
         };
         LruCache::init(config, None)
     }
@@ -1024,6 +1898,7 @@ mod tests {
         let config = LruCacheConfig {
             capacity: NonZeroUsize::new(2).unwrap(),
             max_size: u64::MAX,
+            weight_limits: None,
         };
         let mut segment: LruSegment<&str, i32, DefaultHashBuilder> =
             LruSegment::init(config, DefaultHashBuilder::default());
@@ -1203,6 +2078,7 @@ mod tests {
         let config = LruCacheConfig {
             capacity: NonZeroUsize::new(1000).unwrap(),
             max_size: 1024 * 1024,
+            weight_limits: None,
         };
         let cache: LruCache<String, i32> = LruCache::init(config, None);
 
@@ -1217,6 +2093,7 @@ mod tests {
         let config = LruCacheConfig {
             capacity: NonZeroUsize::new(100).unwrap(),
             max_size: 1024 * 1024,
+            weight_limits: None,
         };
         let cache: LruCache<String, String> = LruCache::init(config, None);
 
@@ -1249,15 +2126,15 @@ mod tests {
         let mut cache = make_cache(2);
         cache.put("a", 1, 1);
         cache.put("b", 2, 1);
-        assert_eq!(cache.segment.metrics().core.evictions, 0);
+        assert_eq!(cache.segment.metrics().unwrap().core.evictions, 0);
 
         // Inserting a 3rd item should evict one (capacity=2)
         cache.put("c", 3, 1);
-        assert_eq!(cache.segment.metrics().core.evictions, 1);
+        assert_eq!(cache.segment.metrics().unwrap().core.evictions, 1);
 
         // Another insert should evict again
         cache.put("d", 4, 1);
-        assert_eq!(cache.segment.metrics().core.evictions, 2);
+        assert_eq!(cache.segment.metrics().unwrap().core.evictions, 2);
     }
 
     #[test]
@@ -1292,6 +2169,7 @@ mod tests {
         let config = LruCacheConfig {
             capacity: NonZeroUsize::new(10).unwrap(),
             max_size: 100,
+            weight_limits: None,
         };
         let mut cache = LruCache::init(config, None);
         // Fill with small entries: 10 entries × 10 bytes = 100 bytes
@@ -1303,4 +2181,28 @@ mod tests {
         let evicted = result.unwrap();
         assert_eq!(evicted.len(), 5);
     }
+
+    #[test]
+    fn test_no_metrics_matches_default() {
+        let config = LruCacheConfig {
+            capacity: NonZeroUsize::new(4).unwrap(),
+            max_size: u64::MAX,
+            weight_limits: None,
+        };
+        let mut counted = LruCache::init(config, None);
+        let mut uncounted: LruCache<u32, u32, _, NoMetrics> =
+            LruCache::init_without_metrics(config, None);
+        for i in 0..64u32 {
+            let key = (i * 7) % 10;
+            assert_eq!(counted.get(&key), uncounted.get(&key));
+            assert_eq!(
+                counted.put(key, i, 1 + u64::from(key % 3)),
+                uncounted.put(key, i, 1 + u64::from(key % 3))
+            );
+        }
+        assert_eq!(counted.len(), uncounted.len());
+        assert!(!counted.metrics().is_empty());
+        assert!(uncounted.metrics().is_empty());
+        assert_eq!(uncounted.algorithm_name(), "LRU");
+    }
 }
@@ -0,0 +1,513 @@
+//! Memory Budget Sizing
+//!
+//! Containers run with different memory limits, so a hard-coded `max_size`
+//! is too large for some deployments and wastes memory in others.
+//! [`CgroupMemory`](crate::memory::CgroupMemory) reads the memory limit and
+//! usage of a Linux cgroup, v2 (`memory.max`, `memory.current`) or v1
+//! (`memory.limit_in_bytes`, `memory.usage_in_bytes`), and sizes any cache
+//! configuration as a fraction of the limit.
+//!
+//! The cgroup directory defaults to `/sys/fs/cgroup`, and any other
+//! directory can be given instead, such as a fixture with fake files:
+//!
+//! ```
+//! use cache_rs::config::LruCacheConfig;
+//! use cache_rs::memory::CgroupMemory;
+//! use core::num::NonZeroUsize;
+//! use std::fs;
+//!
+//! let dir = std::env::temp_dir().join(format!("cache-rs-cgroup-doc-{}", std::process::id()));
+//! fs::create_dir_all(&dir).unwrap();
+//! fs::write(dir.join("memory.max"), "1073741824\n").unwrap();
+//! fs::write(dir.join("memory.current"), "268435456\n").unwrap();
+//!
+//! let config = LruCacheConfig {
+//!     capacity: NonZeroUsize::new(100_000).unwrap(),
+//!     max_size: 64 * 1024 * 1024,
+//!     weight_limits: None,
+//! };
+//! let cgroup = CgroupMemory::at(&dir);
+//! let config = cgroup.size_config(config, 0.25).unwrap();
+//! assert_eq!(config.max_size, 256 * 1024 * 1024);
+//! fs::remove_dir_all(&dir).unwrap();
+//! ```
+//!
+//! Without a limit (`max`), the configuration keeps its own `max_size`.
+//!
+//! # Watching Usage
+//!
+//! A [`MemoryWatcher`](crate::memory::MemoryWatcher) polls the cgroup's usage
+//! and calls a hook when it rises above a high watermark, and again when it
+//! falls back below a low watermark, both fractions of the limit. The gap
+//! between the two keeps the hook from firing on every poll while usage
+//! hovers around one threshold.
+//! [`MemoryWatcher::spawn()`](crate::memory::MemoryWatcher::spawn) polls from
+//! a background thread.
+
+extern crate std;
+
+use crate::config::SizedConfig;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// Limits from this value up mean "no limit" in cgroup v1, which reports
+/// the largest page-aligned `i64` instead of `max`.
+const V1_UNLIMITED: u64 = 1 << 62;
+
+/// Version of a cgroup hierarchy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CgroupVersion {
+    /// The legacy hierarchy, with the memory controller in `memory/`.
+    V1,
+    /// The unified hierarchy.
+    V2,
+}
+
+/// Memory limit and usage of a cgroup.
+#[derive(Debug, Clone)]
+pub struct CgroupMemory {
+    root: PathBuf,
+}
+
+impl CgroupMemory {
+    /// Where cgroups are mounted, which inside a container is usually the
+    /// container's own cgroup.
+    pub const DEFAULT_ROOT: &'static str = "/sys/fs/cgroup";
+
+    /// Reads the cgroup mounted at [`DEFAULT_ROOT`](Self::DEFAULT_ROOT).
+    pub fn new() -> Self {
+        Self::at(Self::DEFAULT_ROOT)
+    }
+
+    /// Reads the cgroup at `root`: the cgroup's directory for v2, or the
+    /// directory holding the `memory/` controller for v1.
+    pub fn at(root: impl Into<PathBuf>) -> Self {
+        CgroupMemory { root: root.into() }
+    }
+
+    /// Returns the directory read.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Returns the version of the cgroup, or a `NotFound` error if `root`
+    /// holds neither version's memory files.
+    pub fn version(&self) -> io::Result<CgroupVersion> {
+        if self.root.join("memory.max").is_file() {
+            Ok(CgroupVersion::V2)
+        } else if self.root.join("memory/memory.limit_in_bytes").is_file() {
+            Ok(CgroupVersion::V1)
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                "no cgroup memory controller found",
+            ))
+        }
+    }
+
+    /// Returns the memory limit in bytes, or `None` if there is no limit.
+    pub fn limit(&self) -> io::Result<Option<u64>> {
+        self.limit_of(self.version()?)
+    }
+
+    /// Returns the memory used by the cgroup in bytes, page cache included.
+    pub fn usage(&self) -> io::Result<u64> {
+        self.usage_of(self.version()?)
+    }
+
+    fn limit_of(&self, version: CgroupVersion) -> io::Result<Option<u64>> {
+        match version {
+            CgroupVersion::V2 => read_limit(&self.root.join("memory.max")),
+            CgroupVersion::V1 => Ok(read_limit(&self.root.join("memory/memory.limit_in_bytes"))?
+                .filter(|&limit| limit < V1_UNLIMITED)),
+        }
+    }
+
+    fn usage_of(&self, version: CgroupVersion) -> io::Result<u64> {
+        let path = match version {
+            CgroupVersion::V2 => self.root.join("memory.current"),
+            CgroupVersion::V1 => self.root.join("memory/memory.usage_in_bytes"),
+        };
+        read_limit(&path)?.ok_or_else(|| invalid_data(&path))
+    }
+
+    /// Returns `fraction` of the memory limit in bytes, or `None` if there is
+    /// no limit. `fraction` is clamped to `0.0..=1.0`.
+    pub fn budget(&self, fraction: f64) -> io::Result<Option<u64>> {
+        let fraction = fraction.clamp(0.0, 1.0);
+        Ok(self.limit()?.map(|limit| (limit as f64 * fraction) as u64))
+    }
+
+    /// Sets the `max_size` of `config` to `fraction` of the memory limit.
+    ///
+    /// Without a limit, `config` is returned unchanged, so its own `max_size`
+    /// serves as the fallback.
+    pub fn size_config<C: SizedConfig>(&self, mut config: C, fraction: f64) -> io::Result<C> {
+        if let Some(budget) = self.budget(fraction)? {
+            config.set_max_size(budget);
+        }
+        Ok(config)
+    }
+}
+
+impl Default for CgroupMemory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Reads a byte count, or `None` for `max`.
+fn read_limit(path: &Path) -> io::Result<Option<u64>> {
+    let contents = fs::read_to_string(path)?;
+    parse_limit(&contents).ok_or_else(|| invalid_data(path))
+}
+
+/// Parses a byte count, `Some(None)` for `max`, or `None` if malformed.
+fn parse_limit(contents: &str) -> Option<Option<u64>> {
+    match contents.trim() {
+        "max" => Some(None),
+        value => value.parse().ok().map(Some),
+    }
+}
+
+fn invalid_data(path: &Path) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        std::format!("malformed cgroup file {}", path.display()),
+    )
+}
+
+/// Direction of a watermark crossing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemoryPressure {
+    /// Usage rose to the high watermark or above.
+    High,
+    /// Usage fell back to the low watermark or below.
+    Low,
+}
+
+/// A watermark crossing reported by a [`MemoryWatcher`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryEvent {
+    /// Which watermark was crossed
+    pub pressure: MemoryPressure,
+    /// Memory used by the cgroup, in bytes
+    pub usage: u64,
+    /// Memory limit of the cgroup, in bytes
+    pub limit: u64,
+}
+
+/// Calls a hook when a cgroup's memory usage crosses a high or low
+/// watermark.
+///
+/// The watcher starts below the high watermark. It reports
+/// [`MemoryPressure::High`] once usage reaches `high` times the limit, then
+/// [`MemoryPressure::Low`] once usage is back down to `low` times the limit,
+/// and so on. Cgroups without a limit never cross a watermark.
+///
+/// The cgroup's version and limit are read once, when the watcher is
+/// created; each poll only reads the usage.
+///
+/// The hook typically resizes or clears caches, e.g. by rebuilding them with
+/// a smaller [`size_config()`](CgroupMemory::size_config) or by pausing
+/// admission of large values until the pressure is low again.
+pub struct MemoryWatcher<F> {
+    memory: CgroupMemory,
+    version: CgroupVersion,
+    /// Memory limit in bytes, or `None` without a limit
+    limit: Option<u64>,
+    low: f64,
+    high: f64,
+    hook: F,
+    /// Whether the last crossing was above the high watermark
+    above: bool,
+}
+
+impl<F: FnMut(MemoryEvent)> MemoryWatcher<F> {
+    /// Creates a watcher of `memory` calling `hook` at the `low` and `high`
+    /// watermarks, given as fractions of the limit.
+    ///
+    /// Fails like [`CgroupMemory::limit()`] if the cgroup's version or
+    /// limit cannot be read.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `low` is greater than `high`.
+    pub fn new(memory: CgroupMemory, low: f64, high: f64, hook: F) -> io::Result<Self> {
+        assert!(low <= high, "low watermark above high watermark");
+        let version = memory.version()?;
+        let limit = memory.limit_of(version)?;
+        Ok(MemoryWatcher {
+            memory,
+            version,
+            limit,
+            low,
+            high,
+            hook,
+            above: false,
+        })
+    }
+
+    /// Returns the memory limit in bytes read when the watcher was created,
+    /// or `None` if there is no limit.
+    pub fn limit(&self) -> Option<u64> {
+        self.limit
+    }
+
+    /// Returns `true` if usage last crossed the high watermark.
+    pub fn is_high(&self) -> bool {
+        self.above
+    }
+
+    /// Reads the usage once, calling the hook and returning the event if a
+    /// watermark was crossed since the last poll.
+    pub fn poll(&mut self) -> io::Result<Option<MemoryEvent>> {
+        let Some(limit) = self.limit else {
+            return Ok(None);
+        };
+        let usage = self.memory.usage_of(self.version)?;
+        let ratio = usage as f64 / limit as f64;
+        let pressure = if !self.above && ratio >= self.high {
+            MemoryPressure::High
+        } else if self.above && ratio <= self.low {
+            MemoryPressure::Low
+        } else {
+            return Ok(None);
+        };
+        self.above = pressure == MemoryPressure::High;
+        let event = MemoryEvent {
+            pressure,
+            usage,
+            limit,
+        };
+        (self.hook)(event);
+        Ok(Some(event))
+    }
+
+    /// Polls every `interval` from a background thread until the returned
+    /// handle is stopped or dropped.
+    ///
+    /// A poll that fails to read the usage is skipped, and the first such
+    /// error is returned by [`WatcherHandle::stop()`].
+    pub fn spawn(mut self, interval: Duration) -> io::Result<WatcherHandle>
+    where
+        F: Send + 'static,
+    {
+        let state = Arc::new((Mutex::new(WatcherState::default()), Condvar::new()));
+        let thread = {
+            let state = Arc::clone(&state);
+            thread::Builder::new()
+                .name("cache-rs-memory-watcher".into())
+                .spawn(move || {
+                    let (lock, wakeup) = &*state;
+                    let mut state = lock.lock().unwrap_or_else(|e| e.into_inner());
+                    while !state.stopped {
+                        if let Err(error) = self.poll() {
+                            state.error.get_or_insert(error);
+                        }
+                        state = wakeup
+                            .wait_timeout(state, interval)
+                            .unwrap_or_else(|e| e.into_inner())
+                            .0;
+                    }
+                })?
+        };
+        Ok(WatcherHandle {
+            state,
+            thread: Some(thread),
+        })
+    }
+}
+
+impl<F> core::fmt::Debug for MemoryWatcher<F> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("MemoryWatcher")
+            .field("memory", &self.memory)
+            .field("version", &self.version)
+            .field("limit", &self.limit)
+            .field("low", &self.low)
+            .field("high", &self.high)
+            .field("above", &self.above)
+            .finish_non_exhaustive()
+    }
+}
+
+/// State shared by a [`WatcherHandle`] and its watcher's thread.
+#[derive(Debug, Default)]
+struct WatcherState {
+    /// Whether the watcher was asked to stop
+    stopped: bool,
+    /// First error of a background poll
+    error: Option<io::Error>,
+}
+
+/// Stops a [`MemoryWatcher`] polling in the background when stopped or
+/// dropped.
+#[derive(Debug)]
+pub struct WatcherHandle {
+    state: Arc<(Mutex<WatcherState>, Condvar)>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl WatcherHandle {
+    /// Stops the watcher, waiting for a poll in progress to finish, and
+    /// returns the first error a poll ran into, if any.
+    pub fn stop(mut self) -> io::Result<()> {
+        self.join();
+        let (lock, _) = &*self.state;
+        let error = lock.lock().unwrap_or_else(|e| e.into_inner()).error.take();
+        error.map_or(Ok(()), Err)
+    }
+
+    /// Asks the watcher's thread to stop and waits for it.
+    fn join(&mut self) {
+        let (lock, wakeup) = &*self.state;
+        lock.lock().unwrap_or_else(|e| e.into_inner()).stopped = true;
+        wakeup.notify_all();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for WatcherHandle {
+    fn drop(&mut self) {
+        self.join();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+
+    /// Creates a cgroup directory holding `files`, unique to this process.
+    fn fixture(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let dir = std::env::temp_dir().join(std::format!(
+            "cache-rs-memory-{name}-{}",
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        for (file, contents) in files {
+            let path = dir.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn test_parse_limit() {
+        assert_eq!(parse_limit("max\n"), Some(None));
+        assert_eq!(parse_limit(" 1048576\n"), Some(Some(1 << 20)));
+        assert_eq!(parse_limit("-1"), None);
+        assert_eq!(parse_limit(""), None);
+    }
+
+    #[test]
+    fn test_watcher_polls_v1_usage_against_cached_limit() {
+        let dir = fixture(
+            "v1",
+            &[
+                ("memory/memory.limit_in_bytes", "1000\n"),
+                ("memory/memory.usage_in_bytes", "500\n"),
+            ],
+        );
+        let mut watcher = MemoryWatcher::new(CgroupMemory::at(&dir), 0.6, 0.9, |_| {}).unwrap();
+        assert_eq!(watcher.limit(), Some(1000));
+        assert_eq!(watcher.poll().unwrap(), None);
+
+        // The limit was read once, so a broken limit file goes unnoticed
+        fs::write(dir.join("memory/memory.limit_in_bytes"), "lots\n").unwrap();
+        fs::write(dir.join("memory/memory.usage_in_bytes"), "950\n").unwrap();
+        let event = watcher.poll().unwrap().unwrap();
+        assert_eq!(event.pressure, MemoryPressure::High);
+        assert_eq!((event.usage, event.limit), (950, 1000));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_watcher_ignores_v1_unlimited() {
+        let dir = fixture(
+            "v1-unlimited",
+            &[
+                ("memory/memory.limit_in_bytes", "9223372036854771712\n"),
+                ("memory/memory.usage_in_bytes", "9223372036854771712\n"),
+            ],
+        );
+        let mut watcher = MemoryWatcher::new(CgroupMemory::at(&dir), 0.6, 0.9, |_| {
+            panic!("no limit, no crossing")
+        })
+        .unwrap();
+        assert_eq!(watcher.limit(), None);
+        assert_eq!(watcher.poll().unwrap(), None);
+
+        // Just below the threshold is a real limit
+        fs::write(
+            dir.join("memory/memory.limit_in_bytes"),
+            "4611686018427383808\n",
+        )
+        .unwrap();
+        let watcher = MemoryWatcher::new(CgroupMemory::at(&dir), 0.6, 0.9, |_| {}).unwrap();
+        assert_eq!(watcher.limit(), Some(V1_UNLIMITED - 4096));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_watcher_hysteresis() {
+        let dir = fixture(
+            "hysteresis",
+            &[("memory.max", "1000\n"), ("memory.current", "0\n")],
+        );
+        let mut pressures = Vec::new();
+        let mut watcher = MemoryWatcher::new(CgroupMemory::at(&dir), 0.6, 0.9, |event| {
+            pressures.push(event.pressure)
+        })
+        .unwrap();
+        let mut poll_at = |usage: u64| {
+            fs::write(dir.join("memory.current"), std::format!("{usage}\n")).unwrap();
+            watcher.poll().unwrap().map(|event| event.pressure)
+        };
+
+        // Each watermark reports once, and only after the other one
+        assert_eq!(poll_at(899), None);
+        assert_eq!(poll_at(900), Some(MemoryPressure::High));
+        assert_eq!(poll_at(1000), None);
+        assert_eq!(poll_at(601), None);
+        assert_eq!(poll_at(950), None);
+        assert_eq!(poll_at(600), Some(MemoryPressure::Low));
+        assert_eq!(poll_at(0), None);
+        assert_eq!(poll_at(899), None);
+        assert_eq!(poll_at(900), Some(MemoryPressure::High));
+        assert!(watcher.is_high());
+        drop(watcher);
+        assert_eq!(
+            pressures,
+            [
+                MemoryPressure::High,
+                MemoryPressure::Low,
+                MemoryPressure::High
+            ]
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_watcher_reports_errors() {
+        let dir = fixture("errors", &[("memory.max", "1000\n")]);
+        let missing = CgroupMemory::at(dir.join("missing"));
+        let error = MemoryWatcher::new(missing, 0.6, 0.9, |_| {}).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+
+        // Without memory.current every background poll fails
+        let watcher = MemoryWatcher::new(CgroupMemory::at(&dir), 0.6, 0.9, |_| {}).unwrap();
+        let handle = watcher.spawn(Duration::from_millis(1)).unwrap();
+        thread::sleep(Duration::from_millis(100));
+        assert_eq!(handle.stop().unwrap_err().kind(), io::ErrorKind::NotFound);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -4,7 +4,7 @@
 
 extern crate alloc;
 
-use super::{CacheMetrics, CoreCacheMetrics};
+use super::{CacheMetrics, CoreCacheMetrics, MetricsRecorder, MetricsSnapshot};
 use alloc::collections::BTreeMap;
 use alloc::string::{String, ToString};
 
@@ -12,8 +12,8 @@ use alloc::string::{String, ToString};
 ///
 /// This struct contains metrics specific to the GDSF (Greedy Dual-Size Frequency)
 /// cache algorithm. GDSF combines frequency, size, and aging using the formula:
-/// Priority = (Frequency / Size) + Global_Age
-#[derive(Debug, Clone)]
+/// Priority = (Frequency * Cost / Size) + Global_Age
+#[derive(Debug, Clone, PartialEq)]
 pub struct GdsfCacheMetrics {
     /// Core metrics common to all cache algorithms
     pub core: CoreCacheMetrics,
@@ -47,6 +47,15 @@ pub struct GdsfCacheMetrics {
 
     /// Sum of all frequency/size ratios for efficiency analysis
     pub total_frequency_size_ratio: f64,
+
+    /// Total miss cost of the entries that hit: the cost the cache saved
+    pub cost_saved: u64,
+
+    /// Total miss cost of the misses recorded with a cost
+    pub cost_missed: u64,
+
+    /// Total miss cost of the evicted entries
+    pub cost_evicted: u64,
 }
 
 impl GdsfCacheMetrics {
@@ -67,6 +76,9 @@ impl GdsfCacheMetrics {
             large_items_cached: 0,
             size_based_evictions: 0,
             total_frequency_size_ratio: 0.0,
+            cost_saved: 0,
+            cost_missed: 0,
+            cost_evicted: 0,
         }
     }
 
@@ -121,6 +133,35 @@ impl GdsfCacheMetrics {
         self.size_based_evictions += 1;
     }
 
+    /// Records a hit on an item whose misses cost `cost`
+    pub fn record_cost_saved(&mut self, cost: u64) {
+        self.cost_saved = self.cost_saved.saturating_add(cost);
+    }
+
+    /// Records a miss costing `cost`
+    pub fn record_cost_missed(&mut self, cost: u64) {
+        self.cost_missed = self.cost_missed.saturating_add(cost);
+    }
+
+    /// Records the eviction of an item whose misses cost `cost`
+    pub fn record_cost_evicted(&mut self, cost: u64) {
+        self.cost_evicted = self.cost_evicted.saturating_add(cost);
+    }
+
+    /// Calculates the cost-weighted hit rate
+    ///
+    /// # Returns
+    /// Share of the miss cost of all lookups saved by hits, or 0.0 if no cost
+    /// was recorded
+    pub fn cost_hit_rate(&self) -> f64 {
+        let total = self.cost_saved.saturating_add(self.cost_missed);
+        if total > 0 {
+            self.cost_saved as f64 / total as f64
+        } else {
+            0.0
+        }
+    }
+
     /// Calculates the average frequency across all processed items
     ///
     /// # Returns
@@ -194,6 +235,81 @@ impl GdsfCacheMetrics {
         }
     }
 
+    /// Returns the change since `previous`.
+    ///
+    /// Counters are subtracted; gauges keep their current values.
+    pub fn delta(&self, previous: &Self) -> Self {
+        Self {
+            core: self.core.delta(&previous.core),
+            total_aging_events: self
+                .total_aging_events
+                .saturating_sub(previous.total_aging_events),
+            total_frequency: self
+                .total_frequency
+                .saturating_sub(previous.total_frequency),
+            total_item_size_processed: self
+                .total_item_size_processed
+                .saturating_sub(previous.total_item_size_processed),
+            small_items_cached: self
+                .small_items_cached
+                .saturating_sub(previous.small_items_cached),
+            large_items_cached: self
+                .large_items_cached
+                .saturating_sub(previous.large_items_cached),
+            size_based_evictions: self
+                .size_based_evictions
+                .saturating_sub(previous.size_based_evictions),
+            total_frequency_size_ratio: (self.total_frequency_size_ratio
+                - previous.total_frequency_size_ratio)
+                .max(0.0),
+            cost_saved: self.cost_saved.saturating_sub(previous.cost_saved),
+            cost_missed: self.cost_missed.saturating_sub(previous.cost_missed),
+            cost_evicted: self.cost_evicted.saturating_sub(previous.cost_evicted),
+            ..self.clone()
+        }
+    }
+
+    /// Adds the metrics of another cache (or segment) to these.
+    ///
+    /// Counters are summed, priority bounds widened, and the global age is the
+    /// larger of the two.
+    pub fn merge(&mut self, other: &Self) {
+        self.core.merge(&other.core);
+        self.global_age = self.global_age.max(other.global_age);
+        self.total_aging_events += other.total_aging_events;
+        if self.min_priority == 0.0
+            || (other.min_priority != 0.0 && other.min_priority < self.min_priority)
+        {
+            self.min_priority = other.min_priority;
+        }
+        self.max_priority = self.max_priority.max(other.max_priority);
+        self.total_frequency += other.total_frequency;
+        self.total_item_size_processed += other.total_item_size_processed;
+        self.small_items_cached += other.small_items_cached;
+        self.large_items_cached += other.large_items_cached;
+        self.size_based_evictions += other.size_based_evictions;
+        self.total_frequency_size_ratio += other.total_frequency_size_ratio;
+        self.cost_saved = self.cost_saved.saturating_add(other.cost_saved);
+        self.cost_missed = self.cost_missed.saturating_add(other.cost_missed);
+        self.cost_evicted = self.cost_evicted.saturating_add(other.cost_evicted);
+    }
+
+    /// Zeroes the counters, keeping gauges that describe the cache's current
+    /// contents. See [`CoreCacheMetrics::reset()`].
+    pub fn reset(&mut self) {
+        self.core.reset();
+        self.total_aging_events = 0;
+        self.total_frequency = 0;
+        self.total_item_size_processed = 0;
+        self.small_items_cached = 0;
+        self.large_items_cached = 0;
+        self.size_based_evictions = 0;
+        self.total_frequency_size_ratio = 0.0;
+        self.cost_saved = 0;
+        self.cost_missed = 0;
+        self.cost_evicted = 0;
+    }
+
     /// Converts GDSF metrics to a BTreeMap for reporting
     ///
     /// This method returns all metrics relevant to the GDSF cache algorithm,
@@ -259,6 +375,12 @@ impl GdsfCacheMetrics {
             self.size_eviction_efficiency(),
         );
 
+        // Cost-weighted metrics
+        metrics.insert("cost_saved".to_string(), self.cost_saved as f64);
+        metrics.insert("cost_missed".to_string(), self.cost_missed as f64);
+        metrics.insert("cost_evicted".to_string(), self.cost_evicted as f64);
+        metrics.insert("cost_hit_rate".to_string(), self.cost_hit_rate());
+
         // Rate metrics
         if self.core.requests > 0 {
             metrics.insert(
@@ -284,6 +406,11 @@ impl CacheMetrics for GdsfCacheMetrics {
         self.to_btreemap()
     }
 
+    /// Returns a typed snapshot of GDSF metrics
+    fn snapshot(&self) -> MetricsSnapshot {
+        MetricsSnapshot::Gdsf(self.clone())
+    }
+
     /// Returns the algorithm name for this cache implementation
     ///
     /// # Returns
@@ -292,3 +419,20 @@ impl CacheMetrics for GdsfCacheMetrics {
         "GDSF"
     }
 }
+
+impl MetricsRecorder<GdsfCacheMetrics> for GdsfCacheMetrics {
+    #[inline]
+    fn init(metrics: impl FnOnce() -> Self) -> Self {
+        metrics()
+    }
+
+    #[inline]
+    fn record(&mut self, update: impl FnOnce(&mut Self)) {
+        update(self)
+    }
+
+    #[inline]
+    fn collected(&self) -> Option<&Self> {
+        Some(self)
+    }
+}
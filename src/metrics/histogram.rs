@@ -0,0 +1,280 @@
+//! Log-Bucketed Histograms
+//!
+//! [`LogHistogram`] counts `u64` values in power-of-two buckets, so it covers
+//! nanoseconds to centuries in a fixed 65 buckets and records a value with a
+//! single `leading_zeros()`. Caches use it for the age and idle time of
+//! evicted entries and the interval between accesses to the same entry; see
+//! [`CoreCacheMetrics`](crate::metrics::CoreCacheMetrics).
+//!
+//! ```
+//! use cache_rs::metrics::LogHistogram;
+//!
+//! let mut histogram = LogHistogram::new();
+//! for value in 1..=100 {
+//!     histogram.record(value);
+//! }
+//! assert_eq!(histogram.count(), 100);
+//! assert_eq!(histogram.max(), Some(100));
+//!
+//! // Percentiles are exact to within their power-of-two bucket
+//! let p50 = histogram.percentile(50.0).unwrap();
+//! assert!((32..=63).contains(&p50));
+//! ```
+
+/// Number of buckets: one for zero and one per power of two.
+const BUCKETS: usize = 65;
+
+/// Histogram of `u64` values in power-of-two buckets.
+///
+/// Bucket 0 holds zero and bucket `i` holds values in `2^(i-1)..2^i`.
+/// Percentiles interpolate linearly within a bucket and are clamped to the
+/// recorded minimum and maximum.
+#[derive(Clone, PartialEq, Eq)]
+pub struct LogHistogram {
+    counts: [u64; BUCKETS],
+    count: u64,
+    sum: u64,
+    min: u64,
+    max: u64,
+}
+
+impl LogHistogram {
+    /// Creates an empty histogram.
+    pub const fn new() -> Self {
+        Self {
+            counts: [0; BUCKETS],
+            count: 0,
+            sum: 0,
+            min: u64::MAX,
+            max: 0,
+        }
+    }
+
+    /// Records one value.
+    #[inline]
+    pub fn record(&mut self, value: u64) {
+        self.counts[bucket_of(value)] += 1;
+        self.count += 1;
+        self.sum = self.sum.saturating_add(value);
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+    }
+
+    /// Returns the number of recorded values.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Returns `true` if no values were recorded.
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Returns the sum of the recorded values, saturating at `u64::MAX`.
+    pub fn sum(&self) -> u64 {
+        self.sum
+    }
+
+    /// Returns the smallest recorded value.
+    pub fn min(&self) -> Option<u64> {
+        (self.count > 0).then_some(self.min)
+    }
+
+    /// Returns the largest recorded value.
+    pub fn max(&self) -> Option<u64> {
+        (self.count > 0).then_some(self.max)
+    }
+
+    /// Returns the mean of the recorded values, or 0.0 if there are none.
+    pub fn mean(&self) -> f64 {
+        if self.count > 0 {
+            self.sum as f64 / self.count as f64
+        } else {
+            0.0
+        }
+    }
+
+    /// Returns the value below which `percentile` percent of the recorded
+    /// values fall, e.g. `percentile(99.0)`.
+    ///
+    /// `percentile` is clamped to `0.0..=100.0`. Returns `None` if no values
+    /// were recorded.
+    pub fn percentile(&self, percentile: f64) -> Option<u64> {
+        if self.count == 0 {
+            return None;
+        }
+        let fraction = if percentile.is_nan() {
+            0.0
+        } else {
+            percentile.clamp(0.0, 100.0) / 100.0
+        };
+        // Round up without `f64::ceil()`, which needs `std`
+        let exact = fraction * self.count as f64;
+        let mut rank = exact as u64;
+        if (rank as f64) < exact {
+            rank += 1;
+        }
+        let rank = rank.clamp(1, self.count);
+
+        let mut seen = 0;
+        for (bucket, &count) in self.counts.iter().enumerate() {
+            if count == 0 {
+                continue;
+            }
+            if seen + count >= rank {
+                let (low, high) = bucket_bounds(bucket);
+                let position = u128::from(rank - seen);
+                let offset = u128::from(high - low) * position / u128::from(count);
+                let value = low + offset as u64;
+                return Some(value.clamp(self.min, self.max));
+            }
+            seen += count;
+        }
+        Some(self.max)
+    }
+
+    /// Returns the inclusive upper bound and count of every non-empty bucket,
+    /// in ascending order.
+    pub fn buckets(&self) -> impl Iterator<Item = (u64, u64)> + '_ {
+        self.counts
+            .iter()
+            .enumerate()
+            .filter(|(_, &count)| count > 0)
+            .map(|(bucket, &count)| (bucket_bounds(bucket).1, count))
+    }
+
+    /// Returns the values recorded since `previous`, an earlier copy of this
+    /// histogram.
+    ///
+    /// The minimum and maximum are those of the whole histogram, as the
+    /// extremes of the interval are not known.
+    pub fn delta(&self, previous: &Self) -> Self {
+        let mut delta = Self::new();
+        for (bucket, (current, previous)) in self.counts.iter().zip(&previous.counts).enumerate() {
+            delta.counts[bucket] = current.saturating_sub(*previous);
+        }
+        delta.count = delta.counts.iter().sum();
+        delta.sum = self.sum.saturating_sub(previous.sum);
+        if delta.count > 0 {
+            delta.min = self.min;
+            delta.max = self.max;
+        }
+        delta
+    }
+
+    /// Adds the values recorded by `other`.
+    pub fn merge(&mut self, other: &Self) {
+        for (count, other) in self.counts.iter_mut().zip(&other.counts) {
+            *count += other;
+        }
+        self.count += other.count;
+        self.sum = self.sum.saturating_add(other.sum);
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+    }
+
+    /// Discards all recorded values.
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+}
+
+impl Default for LogHistogram {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl core::fmt::Debug for LogHistogram {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("LogHistogram")
+            .field("count", &self.count)
+            .field("min", &self.min())
+            .field("p50", &self.percentile(50.0))
+            .field("p99", &self.percentile(99.0))
+            .field("max", &self.max())
+            .finish()
+    }
+}
+
+/// Returns the bucket holding `value`.
+#[inline]
+fn bucket_of(value: u64) -> usize {
+    (u64::BITS - value.leading_zeros()) as usize
+}
+
+/// Returns the smallest and largest value of a bucket.
+fn bucket_bounds(bucket: usize) -> (u64, u64) {
+    match bucket {
+        0 => (0, 0),
+        64 => (1 << 63, u64::MAX),
+        _ => (1 << (bucket - 1), (1 << bucket) - 1),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bucket_boundaries() {
+        assert_eq!(bucket_of(0), 0);
+        assert_eq!(bucket_of(1), 1);
+        assert_eq!(bucket_of(2), 2);
+        assert_eq!(bucket_of(3), 2);
+        assert_eq!(bucket_of(4), 3);
+        assert_eq!(bucket_of(u64::MAX), 64);
+        for bucket in 0..BUCKETS {
+            let (low, high) = bucket_bounds(bucket);
+            assert_eq!(bucket_of(low), bucket);
+            assert_eq!(bucket_of(high), bucket);
+        }
+    }
+
+    #[test]
+    fn test_percentiles() {
+        let mut histogram = LogHistogram::new();
+        assert_eq!(histogram.percentile(50.0), None);
+
+        for _ in 0..90 {
+            histogram.record(10);
+        }
+        for _ in 0..10 {
+            histogram.record(1_000_000);
+        }
+        assert_eq!(histogram.percentile(0.0), Some(10));
+        let p50 = histogram.percentile(50.0).unwrap();
+        assert!((8..=15).contains(&p50), "{p50}");
+        let p99 = histogram.percentile(99.0).unwrap();
+        assert!((524_288..=1_000_000).contains(&p99), "{p99}");
+        assert_eq!(histogram.percentile(100.0), Some(1_000_000));
+        assert_eq!(histogram.percentile(f64::NAN), Some(10));
+        assert_eq!(histogram.mean(), 100_009.0);
+    }
+
+    #[test]
+    fn test_merge_and_delta() {
+        let mut a = LogHistogram::new();
+        a.record(1);
+        a.record(100);
+        let before = a.clone();
+        a.record(5);
+        a.record(5);
+
+        let delta = a.delta(&before);
+        assert_eq!(delta.count(), 2);
+        assert_eq!(delta.sum(), 10);
+        assert!(delta.buckets().eq([(7, 2)]));
+        assert!(a.delta(&a).is_empty());
+
+        let mut b = LogHistogram::new();
+        b.record(0);
+        b.merge(&a);
+        assert_eq!(b.count(), 5);
+        assert_eq!(b.min(), Some(0));
+        assert_eq!(b.max(), Some(100));
+
+        b.clear();
+        assert_eq!(b, LogHistogram::new());
+    }
+}
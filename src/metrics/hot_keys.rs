@@ -0,0 +1,434 @@
+//! Hot-Key Detection
+//!
+//! When one key takes a disproportionate share of the traffic, the cache's
+//! aggregate metrics only show that something is hot, not what. A
+//! [`HotKeys`] tracker finds the heaviest keys with the Space-Saving
+//! algorithm (Metwally et al., *Efficient Computation of Frequent and Top-k
+//! Elements in Data Streams*, ICDT '05), which monitors a fixed number of
+//! counters instead of every key: an unmonitored key takes over the counter
+//! with the smallest count and inherits that count as its error bound.
+//! Every key with more than `1 / counters` of the total is guaranteed to be
+//! monitored.
+//!
+//! Trackers are attached with `set_hot_keys()` on any cache and rank the
+//! keys of lookups that hit, both by access count and by bytes served:
+//!
+//! ```
+//! use cache_rs::config::LruCacheConfig;
+//! use cache_rs::metrics::HotKeys;
+//! use cache_rs::LruCache;
+//! use core::num::NonZeroUsize;
+//!
+//! let config = LruCacheConfig {
+//!     capacity: NonZeroUsize::new(100).unwrap(),
+//!     max_size: u64::MAX,
+//!     weight_limits: None,
+//! };
+//! let mut cache: LruCache<&str, i32> = LruCache::init(config, None);
+//! cache.set_hot_keys(Some(HotKeys::new(NonZeroUsize::new(16).unwrap())));
+//!
+//! cache.put("small", 1, 1);
+//! cache.put("large", 2, 1000);
+//! for _ in 0..10 {
+//!     cache.get(&"small");
+//! }
+//! cache.get(&"large");
+//!
+//! let hot_keys = cache.hot_keys().unwrap();
+//! assert_eq!(hot_keys.top_by_accesses(1)[0].key, "small");
+//! assert_eq!(hot_keys.top_by_bytes(1)[0].key, "large");
+//! ```
+
+extern crate alloc;
+
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::vec::Vec;
+use core::num::NonZeroUsize;
+
+/// A key ranked by [`HotKeys`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HotKey<K> {
+    /// The key.
+    pub key: K,
+    /// Estimated accesses or bytes, never less than the true value.
+    pub count: u64,
+    /// How much `count` may overestimate the true value.
+    pub error: u64,
+}
+
+impl<K> HotKey<K> {
+    /// Returns the smallest possible true count.
+    pub fn guaranteed(&self) -> u64 {
+        self.count - self.error
+    }
+}
+
+/// Heavy-hitter tracker ranking keys by accesses and by bytes served, in
+/// memory bounded by its number of counters.
+///
+/// Without a window the counts cover the tracker's lifetime. With
+/// [`last_requests()`](Self::last_requests) counting restarts every
+/// `requests` accesses, and the rankings cover the current window together
+/// with the previous one, so between `requests` and twice that many recent
+/// accesses.
+#[derive(Clone)]
+pub struct HotKeys<K> {
+    counters: usize,
+    /// Accesses per window, if windowed
+    window: Option<usize>,
+    /// Accesses recorded in the current window
+    seen: usize,
+    current: Counts<K>,
+    previous: Option<Counts<K>>,
+    /// Clones a key when it starts being monitored
+    clone_key: fn(&K) -> K,
+}
+
+impl<K: Clone + Eq> HotKeys<K> {
+    /// Creates a tracker with `counters` counters per ranking, counting for
+    /// its whole lifetime.
+    ///
+    /// The tracker stores up to `counters` keys for each of its two rankings.
+    pub fn new(counters: NonZeroUsize) -> Self {
+        Self::with_window(counters, None)
+    }
+
+    /// Creates a tracker with `counters` counters per ranking that forgets
+    /// accesses older than two windows of `requests` accesses.
+    pub fn last_requests(counters: NonZeroUsize, requests: NonZeroUsize) -> Self {
+        Self::with_window(counters, Some(requests.get()))
+    }
+
+    fn with_window(counters: NonZeroUsize, window: Option<usize>) -> Self {
+        HotKeys {
+            counters: counters.get(),
+            window,
+            seen: 0,
+            current: Counts::new(counters.get()),
+            previous: None,
+            clone_key: K::clone,
+        }
+    }
+
+    /// Returns up to `k` keys with the most accesses, most accessed first.
+    pub fn top_by_accesses(&self, k: usize) -> Vec<HotKey<K>> {
+        self.top(k, |counts| &counts.accesses)
+    }
+
+    /// Returns up to `k` keys that served the most bytes, largest first.
+    pub fn top_by_bytes(&self, k: usize) -> Vec<HotKey<K>> {
+        self.top(k, |counts| &counts.bytes)
+    }
+
+    fn top(&self, k: usize, ranking: fn(&Counts<K>) -> &Summary<K>) -> Vec<HotKey<K>> {
+        let current = ranking(&self.current);
+        let previous = self.previous.as_ref().map(ranking);
+
+        // A key missing from one summary counted at most its smallest count
+        let hot_key = |slot: &Slot<K>, other: Option<&Summary<K>>| {
+            let (count, error) = match other {
+                Some(other) => match other.find(slot.hash, &slot.key) {
+                    Some(other) => (other.count, other.error),
+                    None => (other.min_count(), other.min_count()),
+                },
+                None => (0, 0),
+            };
+            HotKey {
+                key: slot.key.clone(),
+                count: slot.count + count,
+                error: slot.error + error,
+            }
+        };
+        let mut top: Vec<HotKey<K>> = current
+            .slots
+            .iter()
+            .map(|slot| hot_key(slot, previous))
+            .collect();
+        if let Some(previous) = previous {
+            top.extend(
+                previous
+                    .slots
+                    .iter()
+                    .filter(|slot| current.find(slot.hash, &slot.key).is_none())
+                    .map(|slot| hot_key(slot, Some(current))),
+            );
+        }
+        top.sort_by(|a, b| {
+            b.count
+                .cmp(&a.count)
+                .then(b.guaranteed().cmp(&a.guaranteed()))
+        });
+        top.truncate(k);
+        top
+    }
+}
+
+impl<K: Eq> HotKeys<K> {
+    /// Returns the number of counters per ranking.
+    pub fn counters(&self) -> usize {
+        self.counters
+    }
+
+    /// Forgets all counts.
+    pub fn clear(&mut self) {
+        self.seen = 0;
+        self.current = Counts::new(self.counters);
+        self.previous = None;
+    }
+
+    /// Returns an empty tracker for one of `segments` segments, each seeing
+    /// a share of the keys. Windows are split evenly.
+    #[allow(dead_code)] // Used by concurrent module when feature is enabled
+    pub(crate) fn for_segment(&self, segments: usize) -> Self {
+        HotKeys {
+            counters: self.counters,
+            window: self.window.map(|window| window.div_ceil(segments)),
+            seen: 0,
+            current: Counts::new(self.counters),
+            previous: None,
+            clone_key: self.clone_key,
+        }
+    }
+
+    /// Records a lookup of `key`, with the given hash, that served `size`
+    /// bytes.
+    pub(crate) fn record(&mut self, hash: u64, key: &K, size: u64) {
+        if self.window == Some(self.seen) {
+            let fresh = Counts::new(self.counters);
+            self.previous = Some(core::mem::replace(&mut self.current, fresh));
+            self.seen = 0;
+        }
+        self.seen += 1;
+        self.current.accesses.record(hash, key, 1, self.clone_key);
+        self.current.bytes.record(hash, key, size, self.clone_key);
+    }
+}
+
+impl<K> core::fmt::Debug for HotKeys<K> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("HotKeys")
+            .field("counters", &self.counters)
+            .field("window", &self.window)
+            .field("seen", &self.seen)
+            .finish()
+    }
+}
+
+/// The two rankings of one window.
+#[derive(Clone)]
+struct Counts<K> {
+    accesses: Summary<K>,
+    bytes: Summary<K>,
+}
+
+impl<K> Counts<K> {
+    fn new(counters: usize) -> Self {
+        Counts {
+            accesses: Summary::new(counters),
+            bytes: Summary::new(counters),
+        }
+    }
+}
+
+/// A monitored key.
+#[derive(Clone)]
+struct Slot<K> {
+    key: K,
+    hash: u64,
+    count: u64,
+    error: u64,
+}
+
+/// Space-Saving summary with a fixed number of counters.
+#[derive(Clone)]
+struct Summary<K> {
+    capacity: usize,
+    slots: Vec<Slot<K>>,
+    /// Slot of each monitored key, by hash
+    by_hash: BTreeMap<u64, usize>,
+    /// Slots ordered by count, to find the smallest
+    by_count: BTreeSet<(u64, usize)>,
+}
+
+impl<K> Summary<K> {
+    fn new(capacity: usize) -> Self {
+        Summary {
+            capacity,
+            slots: Vec::new(),
+            by_hash: BTreeMap::new(),
+            by_count: BTreeSet::new(),
+        }
+    }
+
+    /// Returns the count every unmonitored key is known not to exceed.
+    fn min_count(&self) -> u64 {
+        if self.slots.len() < self.capacity {
+            return 0;
+        }
+        self.by_count.first().map_or(0, |&(count, _)| count)
+    }
+}
+
+impl<K: Eq> Summary<K> {
+    fn find(&self, hash: u64, key: &K) -> Option<&Slot<K>> {
+        let slot = &self.slots[*self.by_hash.get(&hash)?];
+        (slot.key == *key).then_some(slot)
+    }
+
+    fn record(&mut self, hash: u64, key: &K, weight: u64, clone_key: fn(&K) -> K) {
+        if weight == 0 {
+            return;
+        }
+        if let Some(&idx) = self.by_hash.get(&hash) {
+            let slot = &mut self.slots[idx];
+            // Another key with the same hash stays unmonitored
+            if slot.key == *key {
+                self.by_count.remove(&(slot.count, idx));
+                slot.count += weight;
+                self.by_count.insert((slot.count, idx));
+            }
+            return;
+        }
+
+        if self.slots.len() < self.capacity {
+            let idx = self.slots.len();
+            self.slots.push(Slot {
+                key: clone_key(key),
+                hash,
+                count: weight,
+                error: 0,
+            });
+            self.by_hash.insert(hash, idx);
+            self.by_count.insert((weight, idx));
+            return;
+        }
+
+        // Take over the counter with the smallest count
+        let Some((min, idx)) = self.by_count.pop_first() else {
+            return;
+        };
+        let slot = &mut self.slots[idx];
+        self.by_hash.remove(&slot.hash);
+        *slot = Slot {
+            key: clone_key(key),
+            hash,
+            count: min + weight,
+            error: min,
+        };
+        self.by_hash.insert(hash, idx);
+        self.by_count.insert((min + weight, idx));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(hot_keys: &mut HotKeys<u64>, key: u64, size: u64) {
+        hot_keys.record(key.wrapping_mul(0x9E37_79B9_7F4A_7C15), &key, size);
+    }
+
+    #[test]
+    fn test_exact_while_counters_suffice() {
+        let mut hot_keys = HotKeys::new(NonZeroUsize::new(4).unwrap());
+        for (key, accesses) in [(1, 5), (2, 3), (3, 1)] {
+            for _ in 0..accesses {
+                record(&mut hot_keys, key, 10 * (4 - key));
+            }
+        }
+        let top = hot_keys.top_by_accesses(2);
+        assert_eq!(
+            top,
+            [
+                HotKey {
+                    key: 1,
+                    count: 5,
+                    error: 0
+                },
+                HotKey {
+                    key: 2,
+                    count: 3,
+                    error: 0
+                },
+            ]
+        );
+        let bytes: Vec<_> = hot_keys
+            .top_by_bytes(10)
+            .iter()
+            .map(|hot| (hot.key, hot.count))
+            .collect();
+        assert_eq!(bytes, [(1, 150), (2, 60), (3, 10)]);
+    }
+
+    #[test]
+    fn test_heavy_hitters_survive_many_cold_keys() {
+        let mut hot_keys = HotKeys::new(NonZeroUsize::new(32).unwrap());
+        for cold in 100..10_000 {
+            record(&mut hot_keys, cold, 1);
+            if cold % 4 == 0 {
+                record(&mut hot_keys, 1, 1);
+            }
+            if cold % 10 == 0 {
+                record(&mut hot_keys, 2, 1);
+            }
+        }
+        let top = hot_keys.top_by_accesses(2);
+        assert_eq!(top[0].key, 1);
+        assert_eq!(top[1].key, 2);
+        assert!(top[0].guaranteed() <= 2475 && 2475 <= top[0].count);
+        assert!(top[1].guaranteed() <= 990 && 990 <= top[1].count);
+        assert_eq!(hot_keys.current.accesses.slots.len(), 32);
+    }
+
+    #[test]
+    fn test_windows_forget_old_keys() {
+        let mut hot_keys = HotKeys::last_requests(
+            NonZeroUsize::new(4).unwrap(),
+            NonZeroUsize::new(10).unwrap(),
+        );
+        for _ in 0..10 {
+            record(&mut hot_keys, 1, 1);
+        }
+        for _ in 0..10 {
+            record(&mut hot_keys, 2, 1);
+        }
+        let keys = |hot_keys: &HotKeys<u64>| {
+            let mut keys: Vec<_> = hot_keys
+                .top_by_accesses(4)
+                .iter()
+                .map(|hot| hot.key)
+                .collect();
+            keys.sort_unstable();
+            keys
+        };
+        // Both windows are reported
+        assert_eq!(keys(&hot_keys), [1, 2]);
+
+        for _ in 0..10 {
+            record(&mut hot_keys, 3, 1);
+        }
+        assert_eq!(keys(&hot_keys), [2, 3]);
+
+        hot_keys.clear();
+        assert!(hot_keys.top_by_accesses(4).is_empty());
+    }
+
+    #[test]
+    fn test_window_merge_bounds_keys_missing_from_one_window() {
+        let mut hot_keys =
+            HotKeys::last_requests(NonZeroUsize::new(2).unwrap(), NonZeroUsize::new(6).unwrap());
+        // Previous window: key 1 four times, keys 2 and 3 once each
+        for key in [1, 1, 1, 1, 2, 3] {
+            record(&mut hot_keys, key, 1);
+        }
+        // Current window: key 4 only
+        for _ in 0..3 {
+            record(&mut hot_keys, 4, 1);
+        }
+        // Key 4 may have been among the previous window's unmonitored keys
+        let top = hot_keys.top_by_accesses(3);
+        assert_eq!((top[0].key, top[0].guaranteed(), top[0].count), (4, 3, 5));
+        assert_eq!((top[1].key, top[1].guaranteed(), top[1].count), (1, 4, 4));
+        assert_eq!((top[2].key, top[2].guaranteed(), top[2].count), (3, 1, 2));
+    }
+}
@@ -4,7 +4,7 @@
 
 extern crate alloc;
 
-use super::{CacheMetrics, CoreCacheMetrics};
+use super::{CacheMetrics, CoreCacheMetrics, MetricsRecorder, MetricsSnapshot};
 use alloc::collections::BTreeMap;
 use alloc::string::{String, ToString};
 
@@ -13,7 +13,7 @@ use alloc::string::{String, ToString};
 /// This struct contains metrics specific to the LFU (Least Frequently Used) cache algorithm.
 /// LFU tracks frequency of access for each item, so these metrics focus on frequency
 /// distribution and access patterns.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct LfuCacheMetrics {
     /// Core metrics common to all cache algorithms
     pub core: CoreCacheMetrics,
@@ -134,6 +134,44 @@ impl LfuCacheMetrics {
         self.max_frequency.saturating_sub(self.min_frequency)
     }
 
+    /// Returns the change since `previous`.
+    ///
+    /// Counters are subtracted; gauges keep their current values.
+    pub fn delta(&self, previous: &Self) -> Self {
+        Self {
+            core: self.core.delta(&previous.core),
+            total_frequency_increments: self
+                .total_frequency_increments
+                .saturating_sub(previous.total_frequency_increments),
+            ..self.clone()
+        }
+    }
+
+    /// Adds the metrics of another cache (or segment) to these.
+    ///
+    /// Counters are summed, frequency bounds widened, and the number of active
+    /// frequency levels is the larger of the two.
+    pub fn merge(&mut self, other: &Self) {
+        self.core.merge(&other.core);
+        if self.min_frequency == 0
+            || (other.min_frequency != 0 && other.min_frequency < self.min_frequency)
+        {
+            self.min_frequency = other.min_frequency;
+        }
+        self.max_frequency = self.max_frequency.max(other.max_frequency);
+        self.total_frequency_increments += other.total_frequency_increments;
+        self.active_frequency_levels = self
+            .active_frequency_levels
+            .max(other.active_frequency_levels);
+    }
+
+    /// Zeroes the counters, keeping gauges that describe the cache's current
+    /// contents. See [`CoreCacheMetrics::reset()`].
+    pub fn reset(&mut self) {
+        self.core.reset();
+        self.total_frequency_increments = 0;
+    }
+
     /// Converts LFU metrics to a BTreeMap for reporting
     ///
     /// This method returns all metrics relevant to the LFU cache algorithm,
@@ -188,6 +226,11 @@ impl CacheMetrics for LfuCacheMetrics {
         self.to_btreemap()
     }
 
+    /// Returns a typed snapshot of LFU metrics
+    fn snapshot(&self) -> MetricsSnapshot {
+        MetricsSnapshot::Lfu(self.clone())
+    }
+
     /// Returns the algorithm name for this cache implementation
     ///
     /// # Returns
@@ -196,3 +239,20 @@ impl CacheMetrics for LfuCacheMetrics {
         "LFU"
     }
 }
+
+impl MetricsRecorder<LfuCacheMetrics> for LfuCacheMetrics {
+    #[inline]
+    fn init(metrics: impl FnOnce() -> Self) -> Self {
+        metrics()
+    }
+
+    #[inline]
+    fn record(&mut self, update: impl FnOnce(&mut Self)) {
+        update(self)
+    }
+
+    #[inline]
+    fn collected(&self) -> Option<&Self> {
+        Some(self)
+    }
+}
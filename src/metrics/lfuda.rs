@@ -4,7 +4,7 @@
 
 extern crate alloc;
 
-use super::{CacheMetrics, CoreCacheMetrics};
+use super::{CacheMetrics, CoreCacheMetrics, MetricsRecorder, MetricsSnapshot};
 use alloc::collections::BTreeMap;
 use alloc::string::{String, ToString};
 
@@ -13,7 +13,7 @@ use alloc::string::{String, ToString};
 /// This struct contains metrics specific to the LFUDA (Least Frequently Used with Dynamic Aging)
 /// cache algorithm. LFUDA combines frequency tracking with aging to prevent old frequently-used
 /// items from blocking new items indefinitely.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct LfudaCacheMetrics {
     /// Core metrics common to all cache algorithms
     pub core: CoreCacheMetrics,
@@ -137,6 +137,57 @@ impl LfudaCacheMetrics {
         }
     }
 
+    /// Returns the change since `previous`.
+    ///
+    /// Counters are subtracted; gauges keep their current values.
+    pub fn delta(&self, previous: &Self) -> Self {
+        Self {
+            core: self.core.delta(&previous.core),
+            total_aging_events: self
+                .total_aging_events
+                .saturating_sub(previous.total_aging_events),
+            total_frequency_increments: self
+                .total_frequency_increments
+                .saturating_sub(previous.total_frequency_increments),
+            items_benefited_from_aging: self
+                .items_benefited_from_aging
+                .saturating_sub(previous.items_benefited_from_aging),
+            total_age_distributed: self
+                .total_age_distributed
+                .saturating_sub(previous.total_age_distributed),
+            ..self.clone()
+        }
+    }
+
+    /// Adds the metrics of another cache (or segment) to these.
+    ///
+    /// Counters are summed, priority bounds widened, and the global age is the
+    /// larger of the two.
+    pub fn merge(&mut self, other: &Self) {
+        self.core.merge(&other.core);
+        self.global_age = self.global_age.max(other.global_age);
+        self.total_aging_events += other.total_aging_events;
+        if self.min_priority == 0
+            || (other.min_priority != 0 && other.min_priority < self.min_priority)
+        {
+            self.min_priority = other.min_priority;
+        }
+        self.max_priority = self.max_priority.max(other.max_priority);
+        self.total_frequency_increments += other.total_frequency_increments;
+        self.items_benefited_from_aging += other.items_benefited_from_aging;
+        self.total_age_distributed += other.total_age_distributed;
+    }
+
+    /// Zeroes the counters, keeping gauges that describe the cache's current
+    /// contents. See [`CoreCacheMetrics::reset()`].
+    pub fn reset(&mut self) {
+        self.core.reset();
+        self.total_aging_events = 0;
+        self.total_frequency_increments = 0;
+        self.items_benefited_from_aging = 0;
+        self.total_age_distributed = 0;
+    }
+
     /// Converts LFUDA metrics to a BTreeMap for reporting
     ///
     /// This method returns all metrics relevant to the LFUDA cache algorithm,
@@ -218,6 +269,11 @@ impl CacheMetrics for LfudaCacheMetrics {
         self.to_btreemap()
     }
 
+    /// Returns a typed snapshot of LFUDA metrics
+    fn snapshot(&self) -> MetricsSnapshot {
+        MetricsSnapshot::Lfuda(self.clone())
+    }
+
     /// Returns the algorithm name for this cache implementation
     ///
     /// # Returns
@@ -226,3 +282,20 @@ impl CacheMetrics for LfudaCacheMetrics {
         "LFUDA"
     }
 }
+
+impl MetricsRecorder<LfudaCacheMetrics> for LfudaCacheMetrics {
+    #[inline]
+    fn init(metrics: impl FnOnce() -> Self) -> Self {
+        metrics()
+    }
+
+    #[inline]
+    fn record(&mut self, update: impl FnOnce(&mut Self)) {
+        update(self)
+    }
+
+    #[inline]
+    fn collected(&self) -> Option<&Self> {
+        Some(self)
+    }
+}
@@ -4,7 +4,7 @@
 
 extern crate alloc;
 
-use super::{CacheMetrics, CoreCacheMetrics};
+use super::{CacheMetrics, CoreCacheMetrics, MetricsRecorder, MetricsSnapshot};
 use alloc::collections::BTreeMap;
 use alloc::string::String;
 
@@ -13,7 +13,7 @@ use alloc::string::String;
 /// This struct contains metrics specific to the LRU (Least Recently Used) cache algorithm.
 /// Currently, LRU uses only the core metrics, but this structure allows for future
 /// LRU-specific metrics to be added.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct LruCacheMetrics {
     /// Core metrics common to all cache algorithms
     pub core: CoreCacheMetrics,
@@ -36,6 +36,27 @@ impl LruCacheMetrics {
         }
     }
 
+    /// Returns the change since `previous`.
+    ///
+    /// Counters are subtracted; gauges keep their current values.
+    pub fn delta(&self, previous: &Self) -> Self {
+        Self {
+            core: self.core.delta(&previous.core),
+        }
+    }
+
+    /// Adds the metrics of another cache (or segment) to these.
+    ///
+    /// LRU only tracks core metrics, see [`CoreCacheMetrics::merge()`].
+    pub fn merge(&mut self, other: &Self) {
+        self.core.merge(&other.core);
+    }
+
+    /// Zeroes the counters. See [`CoreCacheMetrics::reset()`].
+    pub fn reset(&mut self) {
+        self.core.reset();
+    }
+
     /// Converts LRU metrics to a BTreeMap for reporting
     ///
     /// This method returns all metrics relevant to the LRU cache algorithm.
@@ -67,6 +88,11 @@ impl CacheMetrics for LruCacheMetrics {
         self.to_btreemap()
     }
 
+    /// Returns a typed snapshot of LRU metrics
+    fn snapshot(&self) -> MetricsSnapshot {
+        MetricsSnapshot::Lru(self.clone())
+    }
+
     /// Returns the algorithm name for this cache implementation
     ///
     /// # Returns
@@ -75,3 +101,20 @@ impl CacheMetrics for LruCacheMetrics {
         "LRU"
     }
 }
+
+impl MetricsRecorder<LruCacheMetrics> for LruCacheMetrics {
+    #[inline]
+    fn init(metrics: impl FnOnce() -> Self) -> Self {
+        metrics()
+    }
+
+    #[inline]
+    fn record(&mut self, update: impl FnOnce(&mut Self)) {
+        update(self)
+    }
+
+    #[inline]
+    fn collected(&self) -> Option<&Self> {
+        Some(self)
+    }
+}
@@ -14,27 +14,134 @@
 //!
 //! The performance difference (O(log n) vs O(1)) is negligible with ~15 metric keys,
 //! but the deterministic behavior is invaluable for a simulation system.
+//!
+//! # Opting Out
+//!
+//! Every cache records its metrics through a
+//! [`MetricsRecorder`](crate::metrics::MetricsRecorder) type parameter,
+//! which defaults to the algorithm's metrics struct. Caches built with
+//! [`NoMetrics`](crate::metrics::NoMetrics) instead skip all metrics bookkeeping
+//! at compile time:
+//!
+//! ```
+//! use cache_rs::config::LruCacheConfig;
+//! use cache_rs::metrics::{CacheMetrics, NoMetrics};
+//! use cache_rs::LruCache;
+//! use core::num::NonZeroUsize;
+//!
+//! let config = LruCacheConfig {
+//!     capacity: NonZeroUsize::new(10).unwrap(),
+//!     max_size: u64::MAX,
+//!     weight_limits: None,
+//! };
+//! let mut cache: LruCache<&str, i32, _, NoMetrics> = LruCache::init_without_metrics(config, None);
+//! cache.put("key", 42, 1);
+//! assert_eq!(cache.get(&"key"), Some(&42));
+//! assert!(cache.metrics().is_empty());
+//! ```
 
 extern crate alloc;
 
 use alloc::collections::BTreeMap;
+use alloc::format;
 use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::weight::{WeightLimits, Weights};
 
 // Re-export algorithm-specific metrics
 pub mod gdsf;
+pub mod histogram;
+pub mod hot_keys;
 pub mod lfu;
 pub mod lfuda;
 pub mod lru;
+pub mod mrc;
+#[cfg(feature = "std")]
+pub mod prometheus;
 pub mod slru;
+pub mod snapshot;
+pub mod tiered;
+pub mod weights;
+pub mod window;
 
 pub use gdsf::GdsfCacheMetrics;
+pub use histogram::LogHistogram;
+pub use hot_keys::{HotKey, HotKeys};
 pub use lfu::LfuCacheMetrics;
 pub use lfuda::LfudaCacheMetrics;
 pub use lru::LruCacheMetrics;
+pub use mrc::MissRatioCurve;
+#[cfg(feature = "std")]
+pub use prometheus::PrometheusExporter;
 pub use slru::SlruCacheMetrics;
+pub use snapshot::MetricsSnapshot;
+pub use tiered::TieredCacheMetrics;
+pub use weights::DimensionMetrics;
+#[cfg(feature = "std")]
+pub use window::SystemClock;
+pub use window::{Clock, RollingWindow, WindowStats};
+
+/// Decides whether a cache collects metrics of type `T`.
+///
+/// Caches hand every metrics update to [`record()`](Self::record) as a
+/// closure. The algorithm's metrics struct runs it; [`NoMetrics`] drops it, so
+/// the update (and anything computed only for it) compiles away.
+pub trait MetricsRecorder<T> {
+    /// Creates the recorder, calling `metrics` only if metrics are collected.
+    fn init(metrics: impl FnOnce() -> T) -> Self;
+
+    /// Applies a metrics update.
+    fn record(&mut self, update: impl FnOnce(&mut T));
+
+    /// Returns the collected metrics, or `None` if metrics are not collected.
+    fn collected(&self) -> Option<&T>;
+}
+
+/// Zero-sized [`MetricsRecorder`] that collects no metrics at all.
+///
+/// Caches using it report an empty [`CacheMetrics::metrics()`] map.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct NoMetrics;
+
+impl<T> MetricsRecorder<T> for NoMetrics {
+    #[inline(always)]
+    fn init(_metrics: impl FnOnce() -> T) -> Self {
+        NoMetrics
+    }
+
+    #[inline(always)]
+    fn record(&mut self, _update: impl FnOnce(&mut T)) {}
+
+    #[inline(always)]
+    fn collected(&self) -> Option<&T> {
+        None
+    }
+}
+
+/// Core metrics reported by caches that collect none.
+#[allow(dead_code)] // Used by concurrent module when feature is enabled
+pub(crate) static NO_CORE_METRICS: CoreCacheMetrics = CoreCacheMetrics {
+    requests: 0,
+    cache_hits: 0,
+    total_bytes_requested: 0,
+    bytes_served_from_cache: 0,
+    bytes_written_to_cache: 0,
+    evictions: 0,
+    negative_hits: 0,
+    negative_misses: 0,
+    cache_size_bytes: 0,
+    max_cache_size_bytes: 0,
+    window: None,
+    eviction_age: LogHistogram::new(),
+    eviction_idle: LogHistogram::new(),
+    access_interval: LogHistogram::new(),
+    miss_ratio_curve: None,
+    weights: Vec::new(),
+};
 
 /// Common metrics tracked by all cache algorithms
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub struct CoreCacheMetrics {
     /// Total number of requests (gets) made to the cache
     pub requests: u64,
@@ -54,11 +161,37 @@ pub struct CoreCacheMetrics {
     /// Number of items evicted from the cache due to capacity constraints
     pub evictions: u64,
 
+    /// Lookups answered by a negative entry: the key is known to be absent
+    pub negative_hits: u64,
+
+    /// Lookups that found neither a cached value nor a negative entry, while
+    /// a negative cache is attached
+    pub negative_misses: u64,
+
     /// Current size of data stored in the cache (in bytes)
     pub cache_size_bytes: u64,
 
     /// Maximum allowed cache size (in bytes) - the capacity limit
     pub max_cache_size_bytes: u64,
+
+    /// Metrics of the most recent requests, if a window is configured
+    pub window: Option<RollingWindow>,
+
+    /// Time in nanoseconds evicted entries spent in the cache
+    pub eviction_age: LogHistogram,
+
+    /// Time in nanoseconds evicted entries had not been accessed
+    pub eviction_idle: LogHistogram,
+
+    /// Time in nanoseconds between consecutive accesses to the same entry,
+    /// recorded on every hit
+    pub access_interval: LogHistogram,
+
+    /// Estimated miss ratio at other capacities, if a curve is attached
+    pub miss_ratio_curve: Option<MissRatioCurve>,
+
+    /// Usage of each weight dimension, if weight limits are attached
+    pub weights: Vec<DimensionMetrics>,
 }
 
 impl CoreCacheMetrics {
@@ -85,6 +218,9 @@ impl CoreCacheMetrics {
         self.cache_hits += 1;
         self.total_bytes_requested += object_size;
         self.bytes_served_from_cache += object_size;
+        if let Some(window) = &mut self.window {
+            window.record_request(object_size, true);
+        }
     }
 
     /// Records a cache miss - when requested data was not found in the cache
@@ -98,6 +234,9 @@ impl CoreCacheMetrics {
         self.requests += 1;
         self.total_bytes_requested += object_size;
         // Note: cache misses can be calculated as (requests - cache_hits)
+        if let Some(window) = &mut self.window {
+            window.record_request(object_size, false);
+        }
     }
 
     /// Records an eviction - when an item is removed from cache due to capacity constraints
@@ -107,10 +246,92 @@ impl CoreCacheMetrics {
     /// # Arguments
     /// * `evicted_size` - Size of the evicted object (in bytes)
     pub fn record_eviction(&mut self, evicted_size: u64) {
-        self.evictions += 1;
+        self.count_eviction();
         self.cache_size_bytes = self.cache_size_bytes.saturating_sub(evicted_size);
     }
 
+    /// Counts an eviction whose size was already released with
+    /// [`record_removal()`](Self::record_removal).
+    pub(crate) fn count_eviction(&mut self) {
+        self.evictions += 1;
+        if let Some(window) = &mut self.window {
+            window.record_eviction();
+        }
+    }
+
+    /// Records a lookup answered by a negative entry.
+    pub fn record_negative_hit(&mut self) {
+        self.negative_hits += 1;
+    }
+
+    /// Records a lookup that found neither a cached value nor a negative
+    /// entry.
+    pub fn record_negative_miss(&mut self) {
+        self.negative_misses += 1;
+    }
+
+    /// Records how long an evicted entry was cached and how long it had been
+    /// idle, in nanoseconds.
+    ///
+    /// Without the `std` feature entry timestamps are unavailable and both
+    /// are zero.
+    pub fn record_eviction_age(&mut self, age_nanos: u64, idle_nanos: u64) {
+        self.eviction_age.record(age_nanos);
+        self.eviction_idle.record(idle_nanos);
+    }
+
+    /// Records the nanoseconds since an entry's previous access when it is
+    /// accessed again.
+    pub fn record_access_interval(&mut self, interval_nanos: u64) {
+        self.access_interval.record(interval_nanos);
+    }
+
+    /// Records a lookup of the key with the given hash for the miss-ratio
+    /// curve, whether it hits or misses.
+    #[inline]
+    pub fn record_lookup(&mut self, hash: u64) {
+        if let Some(curve) = &mut self.miss_ratio_curve {
+            curve.record(hash);
+        }
+    }
+
+    /// Records the total weight of the cached entries under `limits`, or
+    /// stops reporting weights with `None`.
+    ///
+    /// Eviction counts carry over while the dimensions keep their names.
+    pub fn record_weight_usage(&mut self, limits: Option<&WeightLimits>, used: Weights) {
+        let Some(limits) = limits else {
+            self.weights.clear();
+            return;
+        };
+        let names = limits.names();
+        if !self
+            .weights
+            .iter()
+            .map(|d| d.name)
+            .eq(names.iter().copied())
+        {
+            self.weights = names
+                .iter()
+                .map(|&name| DimensionMetrics {
+                    name,
+                    ..Default::default()
+                })
+                .collect();
+        }
+        for (d, dimension) in self.weights.iter_mut().enumerate() {
+            dimension.used = used.get(d);
+            dimension.limit = limits.limits()[d];
+        }
+    }
+
+    /// Counts an entry evicted to keep weight `dimension` within its limit.
+    pub fn count_weight_eviction(&mut self, dimension: usize) {
+        if let Some(dimension) = self.weights.get_mut(dimension) {
+            dimension.evictions += 1;
+        }
+    }
+
     /// Records a user-initiated removal — when an item is explicitly removed via `remove()`.
     ///
     /// Unlike [`record_eviction()`](Self::record_eviction), this does **not** increment the eviction counter.
@@ -145,6 +366,139 @@ impl CoreCacheMetrics {
         self.cache_size_bytes = self.cache_size_bytes - old_size + new_size;
     }
 
+    /// Returns the change since `previous`.
+    ///
+    /// Counters, histograms and weight evictions are subtracted; the current
+    /// and maximum cache size, the rolling window, the miss-ratio curve and
+    /// the weight usage keep their current values.
+    pub fn delta(&self, previous: &Self) -> Self {
+        Self {
+            requests: self.requests.saturating_sub(previous.requests),
+            cache_hits: self.cache_hits.saturating_sub(previous.cache_hits),
+            total_bytes_requested: self
+                .total_bytes_requested
+                .saturating_sub(previous.total_bytes_requested),
+            bytes_served_from_cache: self
+                .bytes_served_from_cache
+                .saturating_sub(previous.bytes_served_from_cache),
+            bytes_written_to_cache: self
+                .bytes_written_to_cache
+                .saturating_sub(previous.bytes_written_to_cache),
+            evictions: self.evictions.saturating_sub(previous.evictions),
+            negative_hits: self.negative_hits.saturating_sub(previous.negative_hits),
+            negative_misses: self
+                .negative_misses
+                .saturating_sub(previous.negative_misses),
+            cache_size_bytes: self.cache_size_bytes,
+            max_cache_size_bytes: self.max_cache_size_bytes,
+            window: self.window.clone(),
+            eviction_age: self.eviction_age.delta(&previous.eviction_age),
+            eviction_idle: self.eviction_idle.delta(&previous.eviction_idle),
+            access_interval: self.access_interval.delta(&previous.access_interval),
+            miss_ratio_curve: self.miss_ratio_curve.clone(),
+            weights: self
+                .weights
+                .iter()
+                .map(|dimension| {
+                    let previous = previous
+                        .weights
+                        .iter()
+                        .find(|p| p.name == dimension.name)
+                        .map_or(0, |p| p.evictions);
+                    DimensionMetrics {
+                        evictions: dimension.evictions.saturating_sub(previous),
+                        ..dimension.clone()
+                    }
+                })
+                .collect(),
+        }
+    }
+
+    /// Adds the metrics of another cache (or segment) to these.
+    ///
+    /// Counters, histograms, the current size and the maximum size are summed,
+    /// and the rolling windows' statistics and miss-ratio curves are combined.
+    /// Weight dimensions are summed by name.
+    pub fn merge(&mut self, other: &Self) {
+        self.requests += other.requests;
+        self.cache_hits += other.cache_hits;
+        self.total_bytes_requested += other.total_bytes_requested;
+        self.bytes_served_from_cache += other.bytes_served_from_cache;
+        self.bytes_written_to_cache += other.bytes_written_to_cache;
+        self.evictions += other.evictions;
+        self.negative_hits += other.negative_hits;
+        self.negative_misses += other.negative_misses;
+        self.cache_size_bytes += other.cache_size_bytes;
+        self.max_cache_size_bytes = self
+            .max_cache_size_bytes
+            .saturating_add(other.max_cache_size_bytes);
+        self.eviction_age.merge(&other.eviction_age);
+        self.eviction_idle.merge(&other.eviction_idle);
+        self.access_interval.merge(&other.access_interval);
+        match (&mut self.window, &other.window) {
+            (Some(window), Some(other)) => window.merge(other),
+            (None, Some(other)) => self.window = Some(other.clone()),
+            (_, None) => {}
+        }
+        match (&mut self.miss_ratio_curve, &other.miss_ratio_curve) {
+            (Some(curve), Some(other)) => curve.merge(other),
+            (None, Some(other)) => self.miss_ratio_curve = Some(other.clone()),
+            (_, None) => {}
+        }
+        for other in &other.weights {
+            match self.weights.iter_mut().find(|d| d.name == other.name) {
+                Some(dimension) => {
+                    dimension.used += other.used;
+                    dimension.limit = dimension.limit.saturating_add(other.limit);
+                    dimension.evictions += other.evictions;
+                }
+                None => self.weights.push(other.clone()),
+            }
+        }
+    }
+
+    /// Zeroes the counters and clears the histograms, the rolling window and
+    /// the miss-ratio curve, keeping the current and maximum cache size and
+    /// weight usage.
+    pub fn reset(&mut self) {
+        *self = Self {
+            cache_size_bytes: self.cache_size_bytes,
+            max_cache_size_bytes: self.max_cache_size_bytes,
+            window: self.window.take(),
+            miss_ratio_curve: self.miss_ratio_curve.take(),
+            weights: core::mem::take(&mut self.weights),
+            ..Default::default()
+        };
+        for dimension in &mut self.weights {
+            dimension.evictions = 0;
+        }
+        if let Some(window) = &mut self.window {
+            window.clear();
+        }
+        if let Some(curve) = &mut self.miss_ratio_curve {
+            curve.clear();
+        }
+    }
+
+    /// Reads the counters back from a map produced by
+    /// [`to_btreemap()`](Self::to_btreemap). Missing keys read as zero.
+    pub fn from_btreemap(metrics: &BTreeMap<String, f64>) -> Self {
+        let get = |key: &str| metrics.get(key).map_or(0, |&value| value as u64);
+        Self {
+            requests: get("requests"),
+            cache_hits: get("cache_hits"),
+            total_bytes_requested: get("total_bytes_requested"),
+            bytes_served_from_cache: get("bytes_served_from_cache"),
+            bytes_written_to_cache: get("bytes_written_to_cache"),
+            evictions: get("evictions"),
+            negative_hits: get("negative_hits"),
+            negative_misses: get("negative_misses"),
+            cache_size_bytes: get("cache_size_bytes"),
+            max_cache_size_bytes: get("max_cache_size_bytes"),
+            ..Default::default()
+        }
+    }
+
     /// Calculates the cache hit rate as a percentage
     ///
     /// # Returns
@@ -208,6 +562,8 @@ impl CoreCacheMetrics {
         // Basic counters (alphabetical order for consistency)
         metrics.insert("cache_hits".to_string(), self.cache_hits as f64);
         metrics.insert("evictions".to_string(), self.evictions as f64);
+        metrics.insert("negative_hits".to_string(), self.negative_hits as f64);
+        metrics.insert("negative_misses".to_string(), self.negative_misses as f64);
         metrics.insert("requests".to_string(), self.requests as f64);
 
         // Calculated metrics
@@ -255,6 +611,49 @@ impl CoreCacheMetrics {
             );
         }
 
+        // Timing percentiles, in seconds
+        for (name, histogram) in [
+            ("eviction_age", &self.eviction_age),
+            ("eviction_idle", &self.eviction_idle),
+            ("access_interval", &self.access_interval),
+        ] {
+            for (suffix, percentile) in [("p50", 50.0), ("p90", 90.0), ("p99", 99.0)] {
+                if let Some(nanos) = histogram.percentile(percentile) {
+                    metrics.insert(format!("{name}_{suffix}_seconds"), nanos as f64 / 1e9);
+                }
+            }
+        }
+
+        // Rolling window
+        if let Some(window) = &self.window {
+            let stats = window.stats();
+            metrics.insert("window_requests".to_string(), stats.requests as f64);
+            metrics.insert("window_evictions".to_string(), stats.evictions as f64);
+            metrics.insert("window_hit_rate".to_string(), stats.hit_rate());
+            metrics.insert("window_byte_hit_rate".to_string(), stats.byte_hit_rate());
+            metrics.insert("window_eviction_rate".to_string(), stats.eviction_rate());
+        }
+
+        // Miss-ratio curve
+        if let Some(curve) = &self.miss_ratio_curve {
+            metrics.insert("mrc_sample_rate".to_string(), curve.sample_rate());
+            metrics.insert("mrc_sampled_keys".to_string(), curve.sampled_keys() as f64);
+            for (key, ratio) in curve.reported() {
+                metrics.insert(key.to_string(), ratio);
+            }
+        }
+
+        // Weight dimensions
+        for dimension in &self.weights {
+            let name = dimension.name;
+            metrics.insert(format!("weight_{name}_used"), dimension.used as f64);
+            metrics.insert(format!("weight_{name}_limit"), dimension.limit as f64);
+            metrics.insert(
+                format!("weight_{name}_evictions"),
+                dimension.evictions as f64,
+            );
+        }
+
         metrics
     }
 }
@@ -277,6 +676,15 @@ pub trait CacheMetrics {
     /// A BTreeMap where keys are metric names and values are metric values as f64
     fn metrics(&self) -> BTreeMap<String, f64>;
 
+    /// Returns a typed snapshot of the metrics.
+    ///
+    /// [`metrics()`](Self::metrics) is the snapshot's
+    /// [`to_btreemap()`](MetricsSnapshot::to_btreemap) view. The default
+    /// implementation reads the core counters back from `metrics()`.
+    fn snapshot(&self) -> MetricsSnapshot {
+        MetricsSnapshot::Core(CoreCacheMetrics::from_btreemap(&self.metrics()))
+    }
+
     /// Algorithm name for identification
     ///
     /// # Returns
@@ -0,0 +1,497 @@
+//! Miss-Ratio Curves
+//!
+//! A [`MissRatioCurve`] estimates, online, the miss ratio an LRU cache would
+//! have at other capacities, answering "what would the hit rate be at twice
+//! or half the size?" without replaying a trace through the simulator.
+//!
+//! It implements fixed-size SHARDS (Waldspurger et al., *Efficient MRC
+//! Construction with SHARDS*, FAST '15). Keys are sampled spatially, by
+//! whether their hash falls below a threshold, so every access to a sampled
+//! key is seen. For each sampled access the reuse (stack) distance — the
+//! number of distinct sampled keys accessed since the key's previous access —
+//! is measured and scaled up by the inverse sampling rate. At most
+//! `max_samples` keys are tracked: when another key would exceed that, the
+//! threshold is lowered to drop the keys with the largest hashes, so memory
+//! stays bounded however many distinct keys the cache sees.
+//!
+//! Curves are attached with `set_miss_ratio_curve()` on any cache, fed by
+//! `get()` and `get_mut()` lookups, hits and misses alike, and reported by
+//! [`CacheMetrics::metrics()`](crate::metrics::CacheMetrics::metrics) as
+//! `mrc_*` keys. The curve models LRU whatever the cache's own algorithm.
+//!
+//! ```
+//! use cache_rs::config::LruCacheConfig;
+//! use cache_rs::metrics::{CacheMetrics, MissRatioCurve};
+//! use cache_rs::LruCache;
+//! use core::num::NonZeroUsize;
+//!
+//! let capacity = NonZeroUsize::new(4).unwrap();
+//! let config = LruCacheConfig {
+//!     capacity,
+//!     max_size: u64::MAX,
+//!     weight_limits: None,
+//! };
+//! let mut cache: LruCache<u32, u32> = LruCache::init(config, None);
+//! let max_samples = NonZeroUsize::new(1000).unwrap();
+//! cache.set_miss_ratio_curve(Some(MissRatioCurve::new(capacity, max_samples)));
+//!
+//! // Cycle through 6 keys: every lookup misses at 4 entries, none at 8
+//! for _ in 0..100 {
+//!     for key in 0..6 {
+//!         if cache.get(&key).is_none() {
+//!             cache.put(key, key, 1);
+//!         }
+//!     }
+//! }
+//! let metrics = cache.metrics();
+//! assert!(metrics["mrc_miss_ratio_1x"] > 0.95);
+//! assert!(metrics["mrc_miss_ratio_2x"] < 0.05);
+//! ```
+
+extern crate alloc;
+
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::num::NonZeroUsize;
+
+/// Range of the spatial hash; keys whose hash modulo this falls below the
+/// threshold are sampled.
+const MODULUS: u64 = 1 << 24;
+
+/// Curve points per multiple of the reference capacity.
+const STEPS: usize = 16;
+
+/// Largest capacity on the curve, as a multiple of the reference capacity.
+const MAX_FACTOR: usize = 4;
+
+/// Number of reuse-distance buckets.
+const BUCKETS: usize = STEPS * MAX_FACTOR;
+
+/// Fractional bits of the per-access weights.
+const WEIGHT_SHIFT: u32 = 16;
+
+/// Metric keys of the points reported by `metrics()`, with their step.
+const REPORTED: [(&str, usize); 5] = [
+    ("mrc_miss_ratio_0_25x", STEPS / 4),
+    ("mrc_miss_ratio_0_5x", STEPS / 2),
+    ("mrc_miss_ratio_1x", STEPS),
+    ("mrc_miss_ratio_2x", 2 * STEPS),
+    ("mrc_miss_ratio_4x", 4 * STEPS),
+];
+
+/// Online estimate of the LRU miss-ratio curve, built from a bounded sample
+/// of keys.
+///
+/// The curve spans 1/16 to 4 times a reference `capacity`, in steps of 1/16
+/// of it. Reuse distances count entries, so the curve describes caches
+/// limited by entry count rather than by size.
+#[derive(Clone, PartialEq, Eq)]
+pub struct MissRatioCurve {
+    /// Capacity the curve's steps are relative to
+    capacity: usize,
+    max_samples: usize,
+    /// Keys whose spatial hash is below this are sampled
+    threshold: u64,
+    /// Time of each sampled key's last access, by key hash
+    last_access: BTreeMap<u64, usize>,
+    /// Sampled keys ordered by spatial hash, to find the ones to drop
+    by_spatial: BTreeSet<(u64, u64)>,
+    /// Which times are some sampled key's last access
+    times: Fenwick,
+    /// Time of the next sampled access
+    now: usize,
+    /// Estimated accesses per reuse-distance bucket, in units of
+    /// `1 << WEIGHT_SHIFT` accesses. Bucket `i` holds the accesses that hit
+    /// from `(i + 1) * capacity / STEPS` entries on, but not one step below.
+    buckets: Vec<u64>,
+    /// Estimated accesses at a distance beyond the curve
+    beyond: u64,
+    /// Estimated first accesses, which miss at any capacity
+    cold: u64,
+    /// Lowest threshold of the curves merged into this one
+    merged_threshold: u64,
+    /// Keys sampled by the curves merged into this one
+    merged_keys: usize,
+}
+
+impl MissRatioCurve {
+    /// Creates a curve spanning up to four times `capacity` entries that
+    /// tracks at most `max_samples` keys.
+    ///
+    /// `capacity` is usually the cache's own capacity, so the curve reports
+    /// the effect of shrinking or growing it. More samples give a more
+    /// accurate curve at the cost of roughly 100 bytes each.
+    pub fn new(capacity: NonZeroUsize, max_samples: NonZeroUsize) -> Self {
+        let max_samples = max_samples.get();
+        MissRatioCurve {
+            capacity: capacity.get(),
+            max_samples,
+            threshold: MODULUS,
+            last_access: BTreeMap::new(),
+            by_spatial: BTreeSet::new(),
+            times: Fenwick::new(2 * max_samples + 1),
+            now: 0,
+            buckets: vec![0; BUCKETS],
+            beyond: 0,
+            cold: 0,
+            merged_threshold: MODULUS,
+            merged_keys: 0,
+        }
+    }
+
+    /// Returns the capacity the curve is relative to.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the share of keys currently sampled, the lowest of any
+    /// merged curve.
+    pub fn sample_rate(&self) -> f64 {
+        self.sample_threshold() as f64 / MODULUS as f64
+    }
+
+    /// Returns the number of keys currently sampled, including those of
+    /// merged curves.
+    pub fn sampled_keys(&self) -> usize {
+        self.last_access.len() + self.merged_keys
+    }
+
+    /// Returns the estimated number of lookups the curve is built from.
+    pub fn lookups(&self) -> f64 {
+        self.total() as f64 / (1u64 << WEIGHT_SHIFT) as f64
+    }
+
+    /// Returns the estimated miss ratio of an LRU cache of `capacity`
+    /// entries, or `None` before the first sampled lookup.
+    ///
+    /// `capacity` is rounded down to a step of the curve; capacities beyond
+    /// four times the reference capacity report the ratio at that size.
+    pub fn miss_ratio(&self, capacity: usize) -> Option<f64> {
+        let steps = capacity as u128 * STEPS as u128 / self.capacity as u128;
+        self.miss_ratio_at_step(steps.min(BUCKETS as u128) as usize)
+    }
+
+    /// Returns `(capacity, miss ratio)` at every step of the curve, in
+    /// ascending order of capacity, or nothing before the first sampled
+    /// lookup.
+    pub fn curve(&self) -> Vec<(usize, f64)> {
+        let total = self.total();
+        let mut curve = Vec::new();
+        if total == 0 {
+            return curve;
+        }
+        let mut hits = 0;
+        for (step, count) in self.buckets.iter().enumerate() {
+            hits += count;
+            let capacity = ((step + 1) as u128 * self.capacity as u128 / STEPS as u128) as usize;
+            // Small reference capacities round several steps to one size
+            if capacity > curve.last().map_or(0, |&(last, _)| last) {
+                curve.push((capacity, (total - hits) as f64 / total as f64));
+            }
+        }
+        curve
+    }
+
+    /// Discards the recorded reuse distances.
+    ///
+    /// The sampled keys and their last accesses are kept, so later lookups
+    /// of them still measure their true reuse distance instead of counting
+    /// as first accesses.
+    pub fn clear(&mut self) {
+        self.buckets.iter_mut().for_each(|count| *count = 0);
+        self.beyond = 0;
+        self.cold = 0;
+    }
+
+    /// Adds the reuse distances recorded by `other`, e.g. to combine the
+    /// segments of a concurrent cache.
+    ///
+    /// The reference capacities are summed, so the merged curve describes
+    /// caches sized in the same proportion as the sources. The sampled keys
+    /// are not merged; the result is meant for reporting.
+    pub fn merge(&mut self, other: &Self) {
+        for (count, other) in self.buckets.iter_mut().zip(&other.buckets) {
+            *count += other;
+        }
+        self.beyond += other.beyond;
+        self.cold += other.cold;
+        self.capacity += other.capacity;
+        self.merged_threshold = self.merged_threshold.min(other.sample_threshold());
+        self.merged_keys += other.sampled_keys();
+    }
+
+    /// Returns an empty curve for one of `segments` segments, each seeing a
+    /// share of the keys.
+    #[allow(dead_code)] // Used by concurrent module when feature is enabled
+    pub(crate) fn for_segment(&self, segments: usize) -> Self {
+        let share =
+            |total: usize| NonZeroUsize::new(total.div_ceil(segments)).unwrap_or(NonZeroUsize::MIN);
+        Self::new(share(self.capacity), share(self.max_samples))
+    }
+
+    /// Records a lookup of the key with the given hash.
+    pub(crate) fn record(&mut self, hash: u64) {
+        let spatial = hash & (MODULUS - 1);
+        if spatial >= self.threshold {
+            return;
+        }
+        let weight = (MODULUS << WEIGHT_SHIFT) / self.threshold;
+        if self.now == self.times.len() {
+            self.compact();
+        }
+        let now = self.now;
+        self.now += 1;
+
+        match self.last_access.insert(hash, now) {
+            Some(previous) => {
+                let distance = self.times.prefix(now) - self.times.prefix(previous + 1);
+                self.times.remove(previous);
+                // Scale the sampled distance up by the inverse sampling rate;
+                // the lookup hits in caches holding one more entry than that
+                let scaled = distance as u128 * MODULUS as u128 / self.threshold as u128;
+                let bucket = ((scaled + 1) * STEPS as u128).div_ceil(self.capacity as u128) - 1;
+                if bucket < BUCKETS as u128 {
+                    self.buckets[bucket as usize] += weight;
+                } else {
+                    self.beyond += weight;
+                }
+            }
+            None => {
+                self.cold += weight;
+                self.by_spatial.insert((spatial, hash));
+            }
+        }
+        self.times.insert(now);
+
+        while self.last_access.len() > self.max_samples {
+            self.lower_threshold();
+        }
+    }
+
+    /// Stops sampling the keys with the largest spatial hash.
+    fn lower_threshold(&mut self) {
+        let Some(&(largest, _)) = self.by_spatial.last() else {
+            return;
+        };
+        self.threshold = largest;
+        while let Some(&(spatial, hash)) = self.by_spatial.last() {
+            if spatial < largest {
+                break;
+            }
+            self.by_spatial.pop_last();
+            if let Some(time) = self.last_access.remove(&hash) {
+                self.times.remove(time);
+            }
+        }
+    }
+
+    /// Renumbers the last accesses from zero once the time range is used up.
+    fn compact(&mut self) {
+        let mut accesses: Vec<(usize, u64)> = self
+            .last_access
+            .iter()
+            .map(|(&hash, &time)| (time, hash))
+            .collect();
+        accesses.sort_unstable();
+        self.times = Fenwick::new(self.times.len());
+        for (time, (_, hash)) in accesses.into_iter().enumerate() {
+            self.last_access.insert(hash, time);
+            self.times.insert(time);
+        }
+        self.now = self.last_access.len();
+    }
+
+    /// Returns the metric keys and miss ratios at a quarter, half, one, two
+    /// and four times the reference capacity, or nothing before the first
+    /// sampled lookup.
+    pub(crate) fn reported(&self) -> impl Iterator<Item = (&'static str, f64)> + '_ {
+        REPORTED
+            .iter()
+            .filter_map(|&(key, steps)| Some((key, self.miss_ratio_at_step(steps)?)))
+    }
+
+    /// Returns the miss ratio `steps` steps into the curve.
+    fn miss_ratio_at_step(&self, steps: usize) -> Option<f64> {
+        let total = self.total();
+        if total == 0 {
+            return None;
+        }
+        let hits: u64 = self.buckets[..steps].iter().sum();
+        Some((total - hits) as f64 / total as f64)
+    }
+
+    /// Returns the lowest threshold of this and any merged curve.
+    fn sample_threshold(&self) -> u64 {
+        self.threshold.min(self.merged_threshold)
+    }
+
+    /// Returns the weight of all recorded lookups.
+    fn total(&self) -> u64 {
+        self.buckets.iter().sum::<u64>() + self.beyond + self.cold
+    }
+}
+
+impl core::fmt::Debug for MissRatioCurve {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("MissRatioCurve")
+            .field("capacity", &self.capacity)
+            .field("sample_rate", &self.sample_rate())
+            .field("sampled_keys", &self.sampled_keys())
+            .field("lookups", &self.lookups())
+            .finish()
+    }
+}
+
+/// Fenwick tree counting which positions are set.
+#[derive(Clone, PartialEq, Eq)]
+struct Fenwick {
+    tree: Vec<u32>,
+}
+
+impl Fenwick {
+    fn new(len: usize) -> Self {
+        Fenwick {
+            tree: vec![0; len + 1],
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.tree.len() - 1
+    }
+
+    fn insert(&mut self, position: usize) {
+        let mut i = position + 1;
+        while i < self.tree.len() {
+            self.tree[i] += 1;
+            i += i & i.wrapping_neg();
+        }
+    }
+
+    fn remove(&mut self, position: usize) {
+        let mut i = position + 1;
+        while i < self.tree.len() {
+            self.tree[i] -= 1;
+            i += i & i.wrapping_neg();
+        }
+    }
+
+    /// Returns the number of set positions below `end`.
+    fn prefix(&self, end: usize) -> usize {
+        let mut sum = 0;
+        let mut i = end;
+        while i > 0 {
+            sum += self.tree[i] as usize;
+            i -= i & i.wrapping_neg();
+        }
+        sum
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn curve(capacity: usize, max_samples: usize) -> MissRatioCurve {
+        MissRatioCurve::new(
+            NonZeroUsize::new(capacity).unwrap(),
+            NonZeroUsize::new(max_samples).unwrap(),
+        )
+    }
+
+    /// Spreads small keys across the hash space like a real hasher.
+    fn hash(key: u64) -> u64 {
+        key.wrapping_mul(0x9E37_79B9_7F4A_7C15) ^ (key >> 7)
+    }
+
+    #[test]
+    fn test_exact_when_every_key_is_sampled() {
+        let mut mrc = curve(8, 100);
+        // Loop over 6 keys: reuse distance 5 for every access after the first
+        for _ in 0..10 {
+            for key in 0..6 {
+                mrc.record(hash(key));
+            }
+        }
+        assert_eq!(mrc.sample_rate(), 1.0);
+        assert_eq!(mrc.lookups(), 60.0);
+        assert_eq!(mrc.miss_ratio(5), Some(1.0));
+        assert_eq!(mrc.miss_ratio(6), Some(0.1));
+        assert_eq!(mrc.miss_ratio(1000), Some(0.1));
+        assert_eq!(curve(8, 100).miss_ratio(8), None);
+
+        let points = mrc.curve();
+        assert_eq!(points.first(), Some(&(1, 1.0)));
+        assert_eq!(points.last(), Some(&(32, 0.1)));
+        assert_eq!(points.len(), 32);
+    }
+
+    #[test]
+    fn test_sampling_bounds_memory_and_estimates_distances() {
+        let keys = 20_000;
+        let mut mrc = curve(keys as usize, 500);
+        for _ in 0..3 {
+            for key in 0..keys {
+                mrc.record(hash(key));
+            }
+        }
+        assert!(mrc.sampled_keys() <= 500);
+        assert!(mrc.sample_rate() < 0.05, "{}", mrc.sample_rate());
+        assert!(mrc.times.len() <= 1001);
+
+        // Every reuse is at distance `keys - 1`, so misses below that size
+        // and hits from it on, apart from the cold first pass
+        let below = mrc.miss_ratio(keys as usize * 7 / 8).unwrap();
+        let above = mrc.miss_ratio(keys as usize * 9 / 8).unwrap();
+        assert!(below > 0.95, "{below}");
+        assert!((above - 1.0 / 3.0).abs() < 0.1, "{above}");
+    }
+
+    #[test]
+    fn test_clear_keeps_sampled_keys() {
+        let mut mrc = curve(4, 100);
+        mrc.record(hash(1));
+        mrc.record(hash(2));
+        mrc.clear();
+        assert_eq!(mrc.miss_ratio(4), None);
+
+        mrc.record(hash(1));
+        assert_eq!(mrc.miss_ratio(1), Some(1.0));
+        assert_eq!(mrc.miss_ratio(2), Some(0.0));
+    }
+
+    #[test]
+    fn test_merge_and_segments() {
+        let mut a = curve(16, 64).for_segment(2);
+        assert_eq!(a.capacity(), 8);
+        assert_eq!(a.max_samples, 32);
+        let mut b = a.clone();
+        for key in [1, 2, 1] {
+            a.record(hash(key));
+        }
+        for key in [3, 3] {
+            b.record(hash(key));
+        }
+        a.merge(&b);
+        assert_eq!(a.capacity(), 16);
+        assert_eq!(a.lookups(), 5.0);
+        assert_eq!(a.sampled_keys(), 3);
+        // Three cold misses. The reuse at distance 1 needs 2 of the
+        // segment's 8 entries, so 4 of the merged 16; the one at distance 0
+        // needs 2 of the merged 16
+        assert_eq!(a.miss_ratio(2), Some(0.8));
+        assert_eq!(a.miss_ratio(4), Some(0.6));
+    }
+
+    #[test]
+    fn test_compaction_preserves_distances() {
+        let mut mrc = curve(4, 2);
+        for _ in 0..50 {
+            mrc.record(hash(1));
+            mrc.record(hash(1));
+        }
+        assert_eq!(mrc.sampled_keys(), 1);
+        assert!(mrc.now <= mrc.times.len());
+        assert_eq!(mrc.miss_ratio(1), Some(0.01));
+    }
+}
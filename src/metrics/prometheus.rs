@@ -0,0 +1,1168 @@
+//! Prometheus Exposition
+//!
+//! Renders [`CacheMetrics`] in the Prometheus text exposition format, so a
+//! `/metrics` endpoint can serve any cache without hand-written glue.
+//!
+//! Each metric key becomes a metric named `<namespace>_<name>`, typed as a
+//! counter (with a `_total` suffix) or a gauge and described by a `# HELP`
+//! line. Keys the exporter does not know are exported as `untyped`. Every
+//! sample carries an `algorithm` label plus any labels the caller supplies,
+//! e.g. a cache name:
+//!
+//! ```text
+//! # HELP cache_hits_total Lookups that found the key in the cache.
+//! # TYPE cache_hits_total counter
+//! cache_hits_total{algorithm="LRU",cache="sessions"} 42
+//! ```
+//!
+//! Several caches can share one exposition: samples of the same metric are
+//! grouped under one `# HELP`/`# TYPE` header, as the format requires.
+
+extern crate alloc;
+
+use super::CacheMetrics;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+#[cfg(feature = "concurrent-core")]
+use crate::concurrent::{RawRwLock, Segment, ShardedCache};
+
+/// `Content-Type` of the rendered text.
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Namespace used by [`PrometheusExporter::new()`].
+pub const DEFAULT_NAMESPACE: &str = "cache";
+
+/// Prometheus metric type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetricType {
+    /// Monotonically increasing count, exported with a `_total` suffix
+    Counter,
+    /// Value that can go up and down
+    Gauge,
+    /// Metric of unknown type
+    Untyped,
+}
+
+impl MetricType {
+    /// Returns the type as written in `# TYPE` lines.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            MetricType::Counter => "counter",
+            MetricType::Gauge => "gauge",
+            MetricType::Untyped => "untyped",
+        }
+    }
+}
+
+/// Name, type and help text of a known metric key.
+struct Descriptor {
+    key: &'static str,
+    name: &'static str,
+    kind: MetricType,
+    help: &'static str,
+}
+
+const fn counter(key: &'static str, name: &'static str, help: &'static str) -> Descriptor {
+    Descriptor {
+        key,
+        name,
+        kind: MetricType::Counter,
+        help,
+    }
+}
+
+const fn gauge(key: &'static str, name: &'static str, help: &'static str) -> Descriptor {
+    Descriptor {
+        key,
+        name,
+        kind: MetricType::Gauge,
+        help,
+    }
+}
+
+/// Every key reported by the built-in caches, sorted by key.
+const DESCRIPTORS: &[Descriptor] = &[
+    gauge(
+        "access_interval_p50_seconds",
+        "access_interval_p50_seconds",
+        "50th percentile of the time between accesses to the same entry, in seconds.",
+    ),
+    gauge(
+        "access_interval_p90_seconds",
+        "access_interval_p90_seconds",
+        "90th percentile of the time between accesses to the same entry, in seconds.",
+    ),
+    gauge(
+        "access_interval_p99_seconds",
+        "access_interval_p99_seconds",
+        "99th percentile of the time between accesses to the same entry, in seconds.",
+    ),
+    gauge(
+        "active_frequency_levels",
+        "active_frequency_levels",
+        "Distinct access frequencies currently in use.",
+    ),
+    gauge(
+        "aging_benefit_rate",
+        "aging_benefit_rate",
+        "Entries that benefited from aging per request.",
+    ),
+    gauge(
+        "aging_contribution_ratio",
+        "aging_contribution_ratio",
+        "Share of entry priority contributed by aging.",
+    ),
+    gauge(
+        "aging_effectiveness",
+        "aging_effectiveness",
+        "Aging events per eviction.",
+    ),
+    gauge(
+        "aging_event_rate",
+        "aging_event_rate",
+        "Aging events per request.",
+    ),
+    gauge(
+        "average_aging_benefit",
+        "average_aging_benefit",
+        "Average age added to an entry's priority on insertion.",
+    ),
+    gauge(
+        "average_frequency",
+        "average_frequency",
+        "Average access frequency of cached entries.",
+    ),
+    gauge(
+        "average_frequency_size_ratio",
+        "average_frequency_size_ratio",
+        "Average frequency-to-size ratio of accessed entries.",
+    ),
+    gauge(
+        "average_item_size",
+        "average_item_size_bytes",
+        "Average size of processed entries.",
+    ),
+    gauge(
+        "avg_object_size",
+        "average_object_size_bytes",
+        "Average size of requested objects.",
+    ),
+    gauge(
+        "byte_hit_rate",
+        "byte_hit_ratio",
+        "Share of requested bytes served from the cache.",
+    ),
+    counter(
+        "bytes_served_from_cache",
+        "served_bytes_total",
+        "Bytes served from the cache.",
+    ),
+    counter(
+        "bytes_written_to_cache",
+        "written_bytes_total",
+        "Bytes written to the cache.",
+    ),
+    counter(
+        "cache_hits",
+        "hits_total",
+        "Lookups that found the key in the cache.",
+    ),
+    counter(
+        "cache_misses",
+        "misses_total",
+        "Lookups that did not find the key in the cache.",
+    ),
+    gauge(
+        "cache_size_bytes",
+        "size_bytes",
+        "Total size of cached entries.",
+    ),
+    gauge(
+        "cache_utilization",
+        "utilization_ratio",
+        "Size of cached entries relative to the maximum size.",
+    ),
+    counter(
+        "cost_evicted",
+        "cost_evicted_total",
+        "Miss cost of the entries evicted.",
+    ),
+    gauge(
+        "cost_hit_rate",
+        "cost_hit_ratio",
+        "Share of the miss cost of lookups saved by hits.",
+    ),
+    counter(
+        "cost_missed",
+        "cost_missed_total",
+        "Miss cost of the lookups that missed.",
+    ),
+    counter(
+        "cost_saved",
+        "cost_saved_total",
+        "Miss cost of the entries that hit.",
+    ),
+    gauge(
+        "demotion_rate",
+        "demotion_rate",
+        "Demotions from the protected segment per request.",
+    ),
+    gauge("entries", "entries", "Number of cached entries."),
+    gauge(
+        "eviction_age_p50_seconds",
+        "eviction_age_p50_seconds",
+        "50th percentile of the time evicted entries spent cached, in seconds.",
+    ),
+    gauge(
+        "eviction_age_p90_seconds",
+        "eviction_age_p90_seconds",
+        "90th percentile of the time evicted entries spent cached, in seconds.",
+    ),
+    gauge(
+        "eviction_age_p99_seconds",
+        "eviction_age_p99_seconds",
+        "99th percentile of the time evicted entries spent cached, in seconds.",
+    ),
+    gauge(
+        "eviction_idle_p50_seconds",
+        "eviction_idle_p50_seconds",
+        "50th percentile of the time evicted entries went unaccessed, in seconds.",
+    ),
+    gauge(
+        "eviction_idle_p90_seconds",
+        "eviction_idle_p90_seconds",
+        "90th percentile of the time evicted entries went unaccessed, in seconds.",
+    ),
+    gauge(
+        "eviction_idle_p99_seconds",
+        "eviction_idle_p99_seconds",
+        "99th percentile of the time evicted entries went unaccessed, in seconds.",
+    ),
+    gauge("eviction_rate", "eviction_rate", "Evictions per request."),
+    counter(
+        "evictions",
+        "evictions_total",
+        "Entries evicted to make room.",
+    ),
+    gauge(
+        "frequency_distribution_efficiency",
+        "frequency_distribution_efficiency",
+        "Frequency levels in use per hit.",
+    ),
+    gauge(
+        "frequency_increment_rate",
+        "frequency_increment_rate",
+        "Frequency increments per request.",
+    ),
+    gauge(
+        "frequency_range",
+        "frequency_range",
+        "Difference between the highest and lowest access frequency.",
+    ),
+    gauge(
+        "global_age",
+        "global_age",
+        "Current global age of the cache.",
+    ),
+    gauge("hit_rate", "hit_ratio", "Share of lookups that hit."),
+    counter(
+        "items_benefited_from_aging",
+        "items_benefited_from_aging_total",
+        "Entries inserted with a priority raised by aging.",
+    ),
+    counter(
+        "large_items_cached",
+        "large_items_cached_total",
+        "Entries cached that were larger than average.",
+    ),
+    counter(
+        "lock_acquisitions",
+        "lock_acquisitions_total",
+        "Exclusive acquisitions of the segment lock.",
+    ),
+    counter(
+        "lock_contention_misses",
+        "lock_contention_misses_total",
+        "Non-blocking operations that gave up on a locked segment.",
+    ),
+    counter(
+        "lock_hold_seconds",
+        "lock_hold_seconds_total",
+        "Time the segment lock was held exclusively.",
+    ),
+    counter(
+        "lock_wait_seconds",
+        "lock_wait_seconds_total",
+        "Time spent waiting for the segment lock.",
+    ),
+    counter(
+        "lock_waits",
+        "lock_waits_total",
+        "Lock acquisitions that had to wait.",
+    ),
+    gauge(
+        "max_cache_size_bytes",
+        "max_size_bytes",
+        "Maximum total size of cached entries.",
+    ),
+    gauge(
+        "max_frequency",
+        "max_frequency",
+        "Highest access frequency in the cache.",
+    ),
+    gauge(
+        "max_priority",
+        "max_priority",
+        "Highest entry priority in the cache.",
+    ),
+    gauge(
+        "min_frequency",
+        "min_frequency",
+        "Lowest access frequency in the cache.",
+    ),
+    gauge(
+        "min_priority",
+        "min_priority",
+        "Lowest entry priority in the cache.",
+    ),
+    gauge("miss_rate", "miss_ratio", "Share of lookups that missed."),
+    gauge(
+        "mrc_miss_ratio_0_25x",
+        "mrc_miss_ratio_0_25x",
+        "Estimated LRU miss ratio at a quarter of the curve's reference capacity.",
+    ),
+    gauge(
+        "mrc_miss_ratio_0_5x",
+        "mrc_miss_ratio_0_5x",
+        "Estimated LRU miss ratio at half the curve's reference capacity.",
+    ),
+    gauge(
+        "mrc_miss_ratio_1x",
+        "mrc_miss_ratio_1x",
+        "Estimated LRU miss ratio at the curve's reference capacity.",
+    ),
+    gauge(
+        "mrc_miss_ratio_2x",
+        "mrc_miss_ratio_2x",
+        "Estimated LRU miss ratio at twice the curve's reference capacity.",
+    ),
+    gauge(
+        "mrc_miss_ratio_4x",
+        "mrc_miss_ratio_4x",
+        "Estimated LRU miss ratio at four times the curve's reference capacity.",
+    ),
+    gauge(
+        "mrc_sample_rate",
+        "mrc_sample_rate",
+        "Share of keys sampled for the miss-ratio curve.",
+    ),
+    gauge(
+        "mrc_sampled_keys",
+        "mrc_sampled_keys",
+        "Keys currently sampled for the miss-ratio curve.",
+    ),
+    counter(
+        "negative_hits",
+        "negative_hits_total",
+        "Lookups answered by a negative entry for a key known to be absent.",
+    ),
+    counter(
+        "negative_misses",
+        "negative_misses_total",
+        "Lookups that found neither a cached value nor a negative entry.",
+    ),
+    gauge(
+        "priority_range",
+        "priority_range",
+        "Difference between the highest and lowest entry priority.",
+    ),
+    counter(
+        "probationary_evictions",
+        "probationary_evictions_total",
+        "Entries evicted from the probationary segment.",
+    ),
+    counter(
+        "probationary_hits",
+        "probationary_hits_total",
+        "Hits in the probationary segment.",
+    ),
+    gauge(
+        "probationary_size",
+        "probationary_entries",
+        "Entries in the probationary segment.",
+    ),
+    gauge(
+        "promotion_efficiency",
+        "promotion_efficiency",
+        "Promotions per probationary hit.",
+    ),
+    gauge(
+        "promotion_rate",
+        "promotion_rate",
+        "Promotions to the protected segment per request.",
+    ),
+    counter(
+        "protected_evictions",
+        "protected_evictions_total",
+        "Entries evicted from the protected segment.",
+    ),
+    counter(
+        "protected_hits",
+        "protected_hits_total",
+        "Hits in the protected segment.",
+    ),
+    gauge(
+        "protected_max_size",
+        "protected_max_entries",
+        "Capacity of the protected segment.",
+    ),
+    gauge(
+        "protected_size",
+        "protected_entries",
+        "Entries in the protected segment.",
+    ),
+    gauge(
+        "protected_utilization",
+        "protected_utilization_ratio",
+        "Entries in the protected segment relative to its capacity.",
+    ),
+    gauge(
+        "protection_ratio",
+        "protection_ratio",
+        "Share of hits served from the protected segment.",
+    ),
+    counter(
+        "read_buffer_drops",
+        "read_buffer_drops_total",
+        "Reads dropped by full read buffers.",
+    ),
+    counter(
+        "requests",
+        "requests_total",
+        "Lookups recorded by the cache.",
+    ),
+    gauge(
+        "size_based_eviction_rate",
+        "size_based_eviction_rate",
+        "Size-based evictions per request.",
+    ),
+    counter(
+        "size_based_evictions",
+        "size_based_evictions_total",
+        "Evictions caused by the size limit.",
+    ),
+    gauge(
+        "size_distribution_balance",
+        "size_distribution_balance",
+        "Share of cached entries that were at most average size.",
+    ),
+    gauge(
+        "size_eviction_efficiency",
+        "size_eviction_efficiency",
+        "Share of evictions caused by the size limit.",
+    ),
+    counter(
+        "small_items_cached",
+        "small_items_cached_total",
+        "Entries cached that were at most average size.",
+    ),
+    counter(
+        "tier_demotions",
+        "tier_demotions_total",
+        "Entries evicted from the L1 cache and written to the L2 store.",
+    ),
+    gauge(
+        "tier_hit_rate",
+        "tier_hit_ratio",
+        "Share of tiered lookups served by either tier.",
+    ),
+    counter(
+        "tier_l1_hits",
+        "tier_l1_hits_total",
+        "Tiered lookups served by the L1 cache.",
+    ),
+    gauge(
+        "tier_l2_entries",
+        "tier_l2_entries",
+        "Entries held by the L2 store.",
+    ),
+    gauge(
+        "tier_l2_hit_share",
+        "tier_l2_hit_share_ratio",
+        "Share of tiered hits served by the L2 store.",
+    ),
+    counter(
+        "tier_l2_hits",
+        "tier_l2_hits_total",
+        "Tiered lookups served by the L2 store and promoted to L1.",
+    ),
+    gauge(
+        "tier_l2_size_bytes",
+        "tier_l2_size_bytes",
+        "Total size of the entries held by the L2 store.",
+    ),
+    counter(
+        "tier_misses",
+        "tier_misses_total",
+        "Tiered lookups found in neither tier.",
+    ),
+    counter(
+        "total_age_distributed",
+        "age_distributed_total",
+        "Age added to entry priorities by aging.",
+    ),
+    counter(
+        "total_aging_events",
+        "aging_events_total",
+        "Times the global age advanced.",
+    ),
+    counter(
+        "total_bytes_requested",
+        "requested_bytes_total",
+        "Bytes requested from the cache.",
+    ),
+    counter(
+        "total_demotions",
+        "demotions_total",
+        "Demotions from the protected segment.",
+    ),
+    counter(
+        "total_frequency",
+        "frequency_total",
+        "Sum of access frequencies of accessed entries.",
+    ),
+    counter(
+        "total_frequency_increments",
+        "frequency_increments_total",
+        "Access frequency increments.",
+    ),
+    gauge(
+        "total_frequency_size_ratio",
+        "frequency_size_ratio_sum",
+        "Sum of frequency-to-size ratios of accessed entries.",
+    ),
+    counter(
+        "total_item_size_processed",
+        "processed_bytes_total",
+        "Bytes of entries processed.",
+    ),
+    counter(
+        "total_promotions",
+        "promotions_total",
+        "Promotions to the protected segment.",
+    ),
+    gauge(
+        "window_byte_hit_rate",
+        "window_byte_hit_ratio",
+        "Share of requested bytes served from the cache in the rolling window.",
+    ),
+    gauge(
+        "window_eviction_rate",
+        "window_eviction_rate",
+        "Evictions per request in the rolling window.",
+    ),
+    gauge(
+        "window_evictions",
+        "window_evictions",
+        "Entries evicted in the rolling window.",
+    ),
+    gauge(
+        "window_hit_rate",
+        "window_hit_ratio",
+        "Share of lookups that hit in the rolling window.",
+    ),
+    gauge(
+        "window_requests",
+        "window_requests",
+        "Lookups in the rolling window.",
+    ),
+];
+
+/// Returns the descriptor of a known metric key.
+fn descriptor(key: &str) -> Option<&'static Descriptor> {
+    DESCRIPTORS
+        .binary_search_by(|descriptor| descriptor.key.cmp(key))
+        .ok()
+        .map(|idx| &DESCRIPTORS[idx])
+}
+
+/// Returns the Prometheus type of a metric key, as reported by
+/// [`CacheMetrics::metrics()`].
+///
+/// Unknown keys are [`MetricType::Untyped`].
+pub fn metric_type(key: &str) -> MetricType {
+    descriptor(key).map_or(MetricType::Untyped, |descriptor| descriptor.kind)
+}
+
+/// All samples of one metric.
+struct Family {
+    kind: MetricType,
+    help: String,
+    /// Rendered label sets and values
+    samples: Vec<(String, f64)>,
+}
+
+/// Collects metrics of one or more caches and renders them in the
+/// Prometheus text exposition format.
+///
+/// # Example
+///
+/// ```
+/// use cache_rs::config::LruCacheConfig;
+/// use cache_rs::metrics::prometheus::PrometheusExporter;
+/// use cache_rs::LruCache;
+/// use core::num::NonZeroUsize;
+///
+/// let config = LruCacheConfig {
+///     capacity: NonZeroUsize::new(10).unwrap(),
+///     max_size: u64::MAX,
+///     weight_limits: None,
+/// };
+/// let mut cache = LruCache::init(config, None);
+/// cache.put("key", 1, 1);
+/// cache.get(&"key");
+///
+/// let text = PrometheusExporter::new()
+///     .add(&cache, &[("cache", "sessions")])
+///     .render();
+/// assert!(text.contains("cache_hits_total{algorithm=\"LRU\",cache=\"sessions\"} 1\n"));
+/// ```
+pub struct PrometheusExporter {
+    namespace: String,
+    families: BTreeMap<String, Family>,
+}
+
+impl PrometheusExporter {
+    /// Creates an exporter naming metrics `cache_<name>`.
+    pub fn new() -> Self {
+        Self::with_namespace(DEFAULT_NAMESPACE)
+    }
+
+    /// Creates an exporter naming metrics `<namespace>_<name>`.
+    ///
+    /// Characters not allowed in metric names are replaced by `_`.
+    pub fn with_namespace(namespace: &str) -> Self {
+        PrometheusExporter {
+            namespace: sanitize_name(namespace),
+            families: BTreeMap::new(),
+        }
+    }
+
+    /// Adds the metrics of `cache`, labeled with its algorithm and `labels`.
+    pub fn add<C>(&mut self, cache: &C, labels: &[(&str, &str)]) -> &mut Self
+    where
+        C: CacheMetrics + ?Sized,
+    {
+        self.add_metrics(cache.algorithm_name(), &cache.metrics(), labels)
+    }
+
+    /// Adds a metrics map as returned by [`CacheMetrics::metrics()`], labeled
+    /// with `algorithm` and `labels`.
+    pub fn add_metrics(
+        &mut self,
+        algorithm: &str,
+        metrics: &BTreeMap<String, f64>,
+        labels: &[(&str, &str)],
+    ) -> &mut Self {
+        let mut all_labels = Vec::with_capacity(labels.len() + 1);
+        all_labels.push(("algorithm", algorithm));
+        all_labels.extend_from_slice(labels);
+        let rendered = render_labels(&all_labels);
+
+        for (key, &value) in metrics {
+            let (name, kind, help) = match descriptor(key) {
+                Some(descriptor) => (
+                    descriptor.name,
+                    descriptor.kind,
+                    descriptor.help.to_string(),
+                ),
+                None => (
+                    key.as_str(),
+                    MetricType::Untyped,
+                    format!("cache-rs metric {key}."),
+                ),
+            };
+            let name = format!("{}_{}", self.namespace, sanitize_name(name));
+            self.families
+                .entry(name)
+                .or_insert_with(|| Family {
+                    kind,
+                    help,
+                    samples: Vec::new(),
+                })
+                .samples
+                .push((rendered.clone(), value));
+        }
+        self
+    }
+
+    /// Adds every segment of a concurrent cache separately, labeled with its
+    /// algorithm, a `segment` label holding the segment index, and `labels`.
+    ///
+    /// Exports the core metrics, entries, read buffer drops and lock
+    /// statistics of [`ShardedCache::segment_metrics()`].
+    #[cfg(feature = "concurrent-core")]
+    pub fn add_segments<C, L>(
+        &mut self,
+        cache: &ShardedCache<C, L>,
+        labels: &[(&str, &str)],
+    ) -> &mut Self
+    where
+        C: Segment,
+        L: RawRwLock,
+    {
+        let algorithm = cache.algorithm_name();
+        for (idx, segment) in cache.segment_metrics().iter().enumerate() {
+            let mut metrics = segment.core.to_btreemap();
+            metrics.insert("entries".to_string(), segment.len as f64);
+            metrics.insert(
+                "read_buffer_drops".to_string(),
+                segment.read_buffer_drops as f64,
+            );
+            metrics.insert(
+                "lock_acquisitions".to_string(),
+                segment.lock.acquisitions as f64,
+            );
+            metrics.insert("lock_waits".to_string(), segment.lock.waits as f64);
+            metrics.insert(
+                "lock_wait_seconds".to_string(),
+                segment.lock.wait_nanos as f64 / 1e9,
+            );
+            metrics.insert(
+                "lock_hold_seconds".to_string(),
+                segment.lock.hold_nanos as f64 / 1e9,
+            );
+
+            let index = idx.to_string();
+            let mut segment_labels = Vec::with_capacity(labels.len() + 1);
+            segment_labels.extend_from_slice(labels);
+            segment_labels.push(("segment", index.as_str()));
+            self.add_metrics(algorithm, &metrics, &segment_labels);
+        }
+        self
+    }
+
+    /// Renders all added metrics, sorted by metric name.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        for (name, family) in &self.families {
+            // Writing to a String cannot fail
+            let _ = writeln!(out, "# HELP {} {}", name, escape_help(&family.help));
+            let _ = writeln!(out, "# TYPE {} {}", name, family.kind.as_str());
+            for (labels, value) in &family.samples {
+                let _ = writeln!(out, "{}{} {}", name, labels, format_value(*value));
+            }
+        }
+        out
+    }
+}
+
+impl Default for PrometheusExporter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Replaces characters not allowed in metric and label names by `_`.
+fn sanitize_name(name: &str) -> String {
+    let mut sanitized: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if sanitized.is_empty() || sanitized.starts_with(|c: char| c.is_ascii_digit()) {
+        sanitized.insert(0, '_');
+    }
+    sanitized
+}
+
+/// Renders `{name="value",...}`, or nothing for an empty label set.
+///
+/// Later labels with the same name replace earlier ones.
+fn render_labels(labels: &[(&str, &str)]) -> String {
+    let mut unique: Vec<(String, &str)> = Vec::with_capacity(labels.len());
+    for &(name, value) in labels {
+        let name = sanitize_name(name);
+        match unique.iter_mut().find(|(existing, _)| *existing == name) {
+            Some(existing) => existing.1 = value,
+            None => unique.push((name, value)),
+        }
+    }
+    if unique.is_empty() {
+        return String::new();
+    }
+
+    let mut out = String::from("{");
+    for (idx, (name, value)) in unique.iter().enumerate() {
+        if idx > 0 {
+            out.push(',');
+        }
+        let _ = write!(out, "{}=\"{}\"", name, escape_label_value(value));
+    }
+    out.push('}');
+    out
+}
+
+/// Escapes `\`, `"` and line feeds in a label value.
+fn escape_label_value(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Escapes `\` and line feeds in a help text.
+fn escape_help(help: &str) -> String {
+    help.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+/// Formats a sample value, spelling infinities and NaN the Prometheus way.
+fn format_value(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_string()
+    } else if value.is_infinite() {
+        if value > 0.0 { "+Inf" } else { "-Inf" }.to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{LfuCacheConfig, LruCacheConfig};
+    use crate::{LfuCache, LruCache};
+    use core::num::NonZeroUsize;
+
+    type Labels = Vec<(String, String)>;
+
+    /// A parsed exposition: metric name to type, help and samples.
+    #[derive(Debug, Default)]
+    struct Parsed {
+        types: BTreeMap<String, String>,
+        helps: BTreeMap<String, String>,
+        samples: Vec<(String, Labels, f64)>,
+    }
+
+    /// Parses the text exposition format, panicking on malformed lines.
+    fn parse(text: &str) -> Parsed {
+        let mut parsed = Parsed::default();
+        for line in text.lines() {
+            if let Some(rest) = line.strip_prefix("# HELP ") {
+                let (name, help) = rest.split_once(' ').unwrap();
+                assert!(parsed
+                    .helps
+                    .insert(name.to_string(), help.to_string())
+                    .is_none());
+            } else if let Some(rest) = line.strip_prefix("# TYPE ") {
+                let (name, kind) = rest.split_once(' ').unwrap();
+                assert!(parsed
+                    .types
+                    .insert(name.to_string(), kind.to_string())
+                    .is_none());
+            } else {
+                let (series, value) = line.rsplit_once(' ').unwrap();
+                let value = match value {
+                    "NaN" => f64::NAN,
+                    "+Inf" => f64::INFINITY,
+                    "-Inf" => f64::NEG_INFINITY,
+                    value => value.parse().unwrap(),
+                };
+                let (name, labels) = match series.split_once('{') {
+                    Some((name, labels)) => (name, parse_labels(labels.strip_suffix('}').unwrap())),
+                    None => (series, Vec::new()),
+                };
+                // Samples follow their family's header
+                assert!(
+                    parsed.types.contains_key(name),
+                    "sample before TYPE: {line}"
+                );
+                parsed.samples.push((name.to_string(), labels, value));
+            }
+        }
+        parsed
+    }
+
+    fn parse_labels(text: &str) -> Labels {
+        let mut labels = Vec::new();
+        let mut chars = text.chars().peekable();
+        while chars.peek().is_some() {
+            let name: String = chars.by_ref().take_while(|&c| c != '=').collect();
+            assert_eq!(chars.next(), Some('"'));
+            let mut value = String::new();
+            loop {
+                match chars.next().unwrap() {
+                    '\\' => match chars.next().unwrap() {
+                        'n' => value.push('\n'),
+                        c => value.push(c),
+                    },
+                    '"' => break,
+                    c => value.push(c),
+                }
+            }
+            labels.push((name, value));
+            if chars.peek() == Some(&',') {
+                chars.next();
+            }
+        }
+        labels
+    }
+
+    fn make_lru() -> LruCache<&'static str, i32> {
+        let config = LruCacheConfig {
+            capacity: NonZeroUsize::new(2).unwrap(),
+            max_size: u64::MAX,
+            weight_limits: None,
+        };
+        let mut cache = LruCache::init(config, None);
+        cache.put("a", 1, 10);
+        cache.put("b", 2, 20);
+        cache.get(&"a");
+        cache.put("c", 3, 30);
+        cache.record_miss(5);
+        cache
+    }
+
+    #[test]
+    fn test_descriptors_are_sorted_and_unique() {
+        for pair in DESCRIPTORS.windows(2) {
+            assert!(
+                pair[0].key < pair[1].key,
+                "{} >= {}",
+                pair[0].key,
+                pair[1].key
+            );
+        }
+        for descriptor in DESCRIPTORS {
+            assert_eq!(
+                descriptor.kind == MetricType::Counter,
+                descriptor.name.ends_with("_total"),
+                "{}",
+                descriptor.key
+            );
+        }
+    }
+
+    #[test]
+    fn test_every_builtin_key_is_described() {
+        use crate::config::{GdsfCacheConfig, LfudaCacheConfig, SlruCacheConfig};
+        use crate::metrics::{MissRatioCurve, RollingWindow};
+        use crate::tiered::{MemoryStore, TieredCache};
+        use crate::{GdsfCache, LfudaCache, SlruCache};
+
+        let capacity = NonZeroUsize::new(2).unwrap();
+        let mut lfu: LfuCache<u32, u32> = LfuCache::init(
+            LfuCacheConfig {
+                capacity,
+                max_size: u64::MAX,
+                weight_limits: None,
+            },
+            None,
+        );
+        let mut lfuda: LfudaCache<u32, u32> = LfudaCache::init(
+            LfudaCacheConfig {
+                capacity,
+                initial_age: 0,
+                max_size: u64::MAX,
+                weight_limits: None,
+            },
+            None,
+        );
+        let mut slru: SlruCache<u32, u32> = SlruCache::init(
+            SlruCacheConfig {
+                capacity,
+                protected_capacity: NonZeroUsize::new(1).unwrap(),
+                max_size: u64::MAX,
+                weight_limits: None,
+            },
+            None,
+        );
+        let mut gdsf: GdsfCache<u32, u32> = GdsfCache::init(
+            GdsfCacheConfig {
+                capacity,
+                initial_age: 0.0,
+                max_size: u64::MAX,
+                weight_limits: None,
+            },
+            None,
+        );
+        lfu.set_metrics_window(Some(RollingWindow::last_requests(capacity)));
+        lfu.set_miss_ratio_curve(Some(MissRatioCurve::new(capacity, capacity)));
+        for key in 0..4 {
+            lfu.put(key, key, 1);
+            lfu.get(&key);
+            lfuda.put(key, key, 1);
+            lfuda.get(&key);
+            slru.put(key, key, 1);
+            slru.get(&key);
+            gdsf.put(key, key, 1);
+            gdsf.get(&key);
+        }
+
+        let tiered = TieredCache::new(make_lru(), MemoryStore::new(capacity, u64::MAX));
+
+        let caches: [&dyn CacheMetrics; 6] = [&make_lru(), &lfu, &lfuda, &slru, &gdsf, &tiered];
+        for cache in caches {
+            for key in cache.metrics().keys() {
+                assert!(
+                    descriptor(key).is_some(),
+                    "{}: {key}",
+                    cache.algorithm_name()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_round_trip_of_cache_metrics() {
+        let cache = make_lru();
+        let text = PrometheusExporter::new()
+            .add(&cache, &[("cache", "sessions")])
+            .render();
+        let parsed = parse(&text);
+
+        let metrics = cache.metrics();
+        assert_eq!(parsed.samples.len(), metrics.len());
+        for (key, &value) in &metrics {
+            let descriptor = descriptor(key).unwrap_or_else(|| panic!("no descriptor for {key}"));
+            let name = format!("cache_{}", descriptor.name);
+            assert_eq!(parsed.types[&name], descriptor.kind.as_str());
+            assert_eq!(parsed.helps[&name], descriptor.help);
+
+            let (_, labels, parsed_value) = parsed
+                .samples
+                .iter()
+                .find(|(sample, _, _)| *sample == name)
+                .unwrap();
+            assert_eq!(*parsed_value, value, "{key}");
+            assert_eq!(
+                labels,
+                &[
+                    ("algorithm".to_string(), "LRU".to_string()),
+                    ("cache".to_string(), "sessions".to_string())
+                ]
+            );
+        }
+        assert_eq!(metric_type("cache_hits"), MetricType::Counter);
+        assert_eq!(metric_type("hit_rate"), MetricType::Gauge);
+    }
+
+    #[test]
+    fn test_caches_share_families() {
+        let lru = make_lru();
+        let config = LfuCacheConfig {
+            capacity: NonZeroUsize::new(2).unwrap(),
+            max_size: u64::MAX,
+            weight_limits: None,
+        };
+        let mut lfu: LfuCache<&str, i32> = LfuCache::init(config, None);
+        lfu.put("a", 1, 1);
+
+        let text = PrometheusExporter::with_namespace("app")
+            .add(&lru, &[("cache", "lru")])
+            .add(&lfu, &[("cache", "lfu")])
+            .render();
+        let parsed = parse(&text);
+
+        let evictions: Vec<_> = parsed
+            .samples
+            .iter()
+            .filter(|(name, _, _)| name == "app_evictions_total")
+            .collect();
+        assert_eq!(evictions.len(), 2);
+        assert_eq!(evictions[0].2, 1.0);
+        assert_eq!(evictions[1].1[0].1, "LFU");
+        // LFU-only metrics appear once
+        assert_eq!(parsed.types["app_max_frequency"], "gauge");
+    }
+
+    #[test]
+    fn test_escaping_and_special_values() {
+        let mut metrics = BTreeMap::new();
+        metrics.insert("custom.metric".to_string(), f64::INFINITY);
+        metrics.insert("hit_rate".to_string(), f64::NAN);
+
+        let text = PrometheusExporter::with_namespace("my-app")
+            .add_metrics(
+                "X",
+                &metrics,
+                &[
+                    ("name", "quote\" backslash\\ newline\n"),
+                    ("bad label", "v"),
+                ],
+            )
+            .render();
+        let parsed = parse(&text);
+
+        assert_eq!(parsed.types["my_app_custom_metric"], "untyped");
+        let (_, labels, value) = &parsed.samples[0];
+        assert_eq!(*value, f64::INFINITY);
+        assert_eq!(
+            labels[1],
+            (
+                "name".to_string(),
+                "quote\" backslash\\ newline\n".to_string()
+            )
+        );
+        assert_eq!(labels[2].0, "bad_label");
+        assert!(parsed.samples[1].2.is_nan());
+    }
+
+    #[cfg(feature = "concurrent-core")]
+    #[test]
+    fn test_segments_are_labeled() {
+        use crate::concurrent::ConcurrentLruCache;
+        use crate::config::ConcurrentCacheConfig;
+
+        let config = ConcurrentCacheConfig {
+            base: LruCacheConfig {
+                // Room for every key in any one segment, however they hash
+                capacity: NonZeroUsize::new(128).unwrap(),
+                max_size: u64::MAX,
+                weight_limits: None,
+            },
+            segments: 4,
+            shared_budget: false,
+        };
+        let cache: ConcurrentLruCache<u32, u32> = ConcurrentLruCache::init(config, None);
+        for key in 0..32 {
+            cache.put(key, key, 1);
+        }
+
+        let text = PrometheusExporter::new()
+            .add_segments(&cache, &[("cache", "shared")])
+            .render();
+        let parsed = parse(&text);
+
+        let entries: Vec<_> = parsed
+            .samples
+            .iter()
+            .filter(|(name, _, _)| name == "cache_entries")
+            .collect();
+        assert_eq!(entries.len(), 4);
+        assert_eq!(entries.iter().map(|(_, _, value)| value).sum::<f64>(), 32.0);
+        for (idx, (_, labels, _)) in entries.iter().enumerate() {
+            assert_eq!(
+                labels[0],
+                ("algorithm".to_string(), "ConcurrentLRU".to_string())
+            );
+            assert_eq!(labels[2], ("segment".to_string(), idx.to_string()));
+        }
+        assert_eq!(parsed.types["cache_lock_acquisitions_total"], "counter");
+    }
+}
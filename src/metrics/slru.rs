@@ -4,7 +4,7 @@
 
 extern crate alloc;
 
-use super::{CacheMetrics, CoreCacheMetrics};
+use super::{CacheMetrics, CoreCacheMetrics, MetricsRecorder, MetricsSnapshot};
 use alloc::collections::BTreeMap;
 use alloc::string::{String, ToString};
 
@@ -13,7 +13,7 @@ use alloc::string::{String, ToString};
 /// This struct contains metrics specific to the SLRU (Segmented LRU) cache algorithm.
 /// SLRU divides the cache into probationary and protected segments, so these metrics
 /// focus on segment utilization and promotion/demotion patterns.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct SlruCacheMetrics {
     /// Core metrics common to all cache algorithms
     pub core: CoreCacheMetrics,
@@ -183,6 +183,60 @@ impl SlruCacheMetrics {
         }
     }
 
+    /// Returns the change since `previous`.
+    ///
+    /// Counters are subtracted; gauges keep their current values.
+    pub fn delta(&self, previous: &Self) -> Self {
+        Self {
+            core: self.core.delta(&previous.core),
+            total_promotions: self
+                .total_promotions
+                .saturating_sub(previous.total_promotions),
+            total_demotions: self
+                .total_demotions
+                .saturating_sub(previous.total_demotions),
+            probationary_hits: self
+                .probationary_hits
+                .saturating_sub(previous.probationary_hits),
+            protected_hits: self.protected_hits.saturating_sub(previous.protected_hits),
+            probationary_evictions: self
+                .probationary_evictions
+                .saturating_sub(previous.probationary_evictions),
+            protected_evictions: self
+                .protected_evictions
+                .saturating_sub(previous.protected_evictions),
+            ..self.clone()
+        }
+    }
+
+    /// Adds the metrics of another cache (or segment) to these.
+    ///
+    /// Counters and segment sizes are summed.
+    pub fn merge(&mut self, other: &Self) {
+        self.core.merge(&other.core);
+        self.probationary_size += other.probationary_size;
+        self.protected_size += other.protected_size;
+        self.protected_max_size += other.protected_max_size;
+        self.total_promotions += other.total_promotions;
+        self.total_demotions += other.total_demotions;
+        self.probationary_hits += other.probationary_hits;
+        self.protected_hits += other.protected_hits;
+        self.probationary_evictions += other.probationary_evictions;
+        self.protected_evictions += other.protected_evictions;
+    }
+
+    /// Zeroes the counters, keeping gauges that describe the cache's current
+    /// contents. See [`CoreCacheMetrics::reset()`].
+    pub fn reset(&mut self) {
+        self.core.reset();
+        self.total_promotions = 0;
+        self.total_demotions = 0;
+        self.probationary_hits = 0;
+        self.protected_hits = 0;
+        self.probationary_evictions = 0;
+        self.protected_evictions = 0;
+    }
+
     /// Converts SLRU metrics to a BTreeMap for reporting
     ///
     /// This method returns all metrics relevant to the SLRU cache algorithm,
@@ -262,6 +316,11 @@ impl CacheMetrics for SlruCacheMetrics {
         self.to_btreemap()
     }
 
+    /// Returns a typed snapshot of SLRU metrics
+    fn snapshot(&self) -> MetricsSnapshot {
+        MetricsSnapshot::Slru(self.clone())
+    }
+
     /// Returns the algorithm name for this cache implementation
     ///
     /// # Returns
@@ -270,3 +329,20 @@ impl CacheMetrics for SlruCacheMetrics {
         "SLRU"
     }
 }
+
+impl MetricsRecorder<SlruCacheMetrics> for SlruCacheMetrics {
+    #[inline]
+    fn init(metrics: impl FnOnce() -> Self) -> Self {
+        metrics()
+    }
+
+    #[inline]
+    fn record(&mut self, update: impl FnOnce(&mut Self)) {
+        update(self)
+    }
+
+    #[inline]
+    fn collected(&self) -> Option<&Self> {
+        Some(self)
+    }
+}
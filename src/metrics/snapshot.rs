@@ -0,0 +1,271 @@
+//! Typed Metrics Snapshots
+//!
+//! [`CacheMetrics::metrics()`](crate::metrics::CacheMetrics::metrics) reports
+//! every value as an `f64` keyed by name, which loses integer precision and
+//! makes derived figures such as hit rates impossible to combine. A
+//! [`MetricsSnapshot`] keeps the algorithm's metrics struct with its integer
+//! counters, so snapshots can be subtracted to get per-interval rates and
+//! merged across caches or segments before any ratio is computed.
+//!
+//! ```
+//! use cache_rs::config::LruCacheConfig;
+//! use cache_rs::metrics::CacheMetrics;
+//! use cache_rs::LruCache;
+//! use core::num::NonZeroUsize;
+//!
+//! let config = LruCacheConfig {
+//!     capacity: NonZeroUsize::new(10).unwrap(),
+//!     max_size: u64::MAX,
+//!     weight_limits: None,
+//! };
+//! let mut cache: LruCache<&str, i32> = LruCache::init(config, None);
+//! cache.put("a", 1, 1);
+//! cache.get(&"a");
+//! let before = cache.snapshot();
+//!
+//! cache.get(&"a");
+//! cache.record_miss(1);
+//! let interval = cache.snapshot().delta(&before);
+//! assert_eq!(interval.core().requests, 2);
+//! assert_eq!(interval.core().hit_rate(), 0.5);
+//! ```
+
+extern crate alloc;
+
+use super::{
+    CoreCacheMetrics, GdsfCacheMetrics, LfuCacheMetrics, LfudaCacheMetrics, LruCacheMetrics,
+    SlruCacheMetrics, NO_CORE_METRICS,
+};
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+
+/// A typed point-in-time copy of a cache's metrics.
+///
+/// The variant matches the algorithm that produced it. Caches built with
+/// [`NoMetrics`](crate::metrics::NoMetrics) report [`MetricsSnapshot::Disabled`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum MetricsSnapshot {
+    /// Metrics collection is disabled for the cache.
+    Disabled,
+    /// Only the metrics common to all algorithms are available, either because
+    /// the source only tracks those or because snapshots of different
+    /// algorithms were combined.
+    Core(CoreCacheMetrics),
+    /// Metrics of an [`LruCache`](crate::LruCache).
+    Lru(LruCacheMetrics),
+    /// Metrics of an [`LfuCache`](crate::LfuCache).
+    Lfu(LfuCacheMetrics),
+    /// Metrics of an [`LfudaCache`](crate::LfudaCache).
+    Lfuda(LfudaCacheMetrics),
+    /// Metrics of an [`SlruCache`](crate::SlruCache).
+    Slru(SlruCacheMetrics),
+    /// Metrics of a [`GdsfCache`](crate::GdsfCache).
+    Gdsf(GdsfCacheMetrics),
+}
+
+impl MetricsSnapshot {
+    /// Returns the metrics common to all algorithms.
+    ///
+    /// A disabled snapshot returns all-zero metrics.
+    pub fn core(&self) -> &CoreCacheMetrics {
+        match self {
+            MetricsSnapshot::Disabled => &NO_CORE_METRICS,
+            MetricsSnapshot::Core(core) => core,
+            MetricsSnapshot::Lru(metrics) => &metrics.core,
+            MetricsSnapshot::Lfu(metrics) => &metrics.core,
+            MetricsSnapshot::Lfuda(metrics) => &metrics.core,
+            MetricsSnapshot::Slru(metrics) => &metrics.core,
+            MetricsSnapshot::Gdsf(metrics) => &metrics.core,
+        }
+    }
+
+    /// Returns the metrics common to all algorithms for updating, or `None`
+    /// if the snapshot is disabled.
+    #[cfg(feature = "concurrent-core")]
+    pub(crate) fn core_mut(&mut self) -> Option<&mut CoreCacheMetrics> {
+        match self {
+            MetricsSnapshot::Disabled => None,
+            MetricsSnapshot::Core(core) => Some(core),
+            MetricsSnapshot::Lru(metrics) => Some(&mut metrics.core),
+            MetricsSnapshot::Lfu(metrics) => Some(&mut metrics.core),
+            MetricsSnapshot::Lfuda(metrics) => Some(&mut metrics.core),
+            MetricsSnapshot::Slru(metrics) => Some(&mut metrics.core),
+            MetricsSnapshot::Gdsf(metrics) => Some(&mut metrics.core),
+        }
+    }
+
+    /// Returns `true` if the snapshot comes from a cache without metrics.
+    pub fn is_disabled(&self) -> bool {
+        matches!(self, MetricsSnapshot::Disabled)
+    }
+
+    /// Returns the change since `previous`, an earlier snapshot of the same
+    /// cache.
+    ///
+    /// Counters are subtracted (saturating at zero, e.g. after a reset) while
+    /// gauges such as the current size keep their current values. If
+    /// `previous` is of a different algorithm only the core metrics are
+    /// compared; if it is disabled the snapshot is returned unchanged.
+    pub fn delta(&self, previous: &Self) -> Self {
+        match (self, previous) {
+            (MetricsSnapshot::Disabled, _) => MetricsSnapshot::Disabled,
+            (current, MetricsSnapshot::Disabled) => current.clone(),
+            (MetricsSnapshot::Lru(a), MetricsSnapshot::Lru(b)) => MetricsSnapshot::Lru(a.delta(b)),
+            (MetricsSnapshot::Lfu(a), MetricsSnapshot::Lfu(b)) => MetricsSnapshot::Lfu(a.delta(b)),
+            (MetricsSnapshot::Lfuda(a), MetricsSnapshot::Lfuda(b)) => {
+                MetricsSnapshot::Lfuda(a.delta(b))
+            }
+            (MetricsSnapshot::Slru(a), MetricsSnapshot::Slru(b)) => {
+                MetricsSnapshot::Slru(a.delta(b))
+            }
+            (MetricsSnapshot::Gdsf(a), MetricsSnapshot::Gdsf(b)) => {
+                MetricsSnapshot::Gdsf(a.delta(b))
+            }
+            (current, previous) => MetricsSnapshot::Core(current.core().delta(previous.core())),
+        }
+    }
+
+    /// Adds `other` into this snapshot, e.g. to aggregate the segments of a
+    /// concurrent cache.
+    ///
+    /// Snapshots of the same algorithm merge all their metrics. Merging
+    /// different algorithms keeps only the core metrics, and disabled
+    /// snapshots contribute nothing.
+    pub fn merge(&mut self, other: &Self) {
+        match (&mut *self, other) {
+            (_, MetricsSnapshot::Disabled) => {}
+            (MetricsSnapshot::Disabled, other) => *self = other.clone(),
+            (MetricsSnapshot::Lru(a), MetricsSnapshot::Lru(b)) => a.merge(b),
+            (MetricsSnapshot::Lfu(a), MetricsSnapshot::Lfu(b)) => a.merge(b),
+            (MetricsSnapshot::Lfuda(a), MetricsSnapshot::Lfuda(b)) => a.merge(b),
+            (MetricsSnapshot::Slru(a), MetricsSnapshot::Slru(b)) => a.merge(b),
+            (MetricsSnapshot::Gdsf(a), MetricsSnapshot::Gdsf(b)) => a.merge(b),
+            (current, other) => {
+                let mut core = current.core().clone();
+                core.merge(other.core());
+                *self = MetricsSnapshot::Core(core);
+            }
+        }
+    }
+
+    /// Converts the snapshot to the stringly-typed form returned by
+    /// [`CacheMetrics::metrics()`](crate::metrics::CacheMetrics::metrics).
+    ///
+    /// Rates are derived from the snapshot's counters, so the map of a merged
+    /// snapshot reports the combined hit rate rather than a sum of rates. A
+    /// disabled snapshot converts to an empty map.
+    pub fn to_btreemap(&self) -> BTreeMap<String, f64> {
+        match self {
+            MetricsSnapshot::Disabled => BTreeMap::new(),
+            MetricsSnapshot::Core(core) => core.to_btreemap(),
+            MetricsSnapshot::Lru(metrics) => metrics.to_btreemap(),
+            MetricsSnapshot::Lfu(metrics) => metrics.to_btreemap(),
+            MetricsSnapshot::Lfuda(metrics) => metrics.to_btreemap(),
+            MetricsSnapshot::Slru(metrics) => metrics.to_btreemap(),
+            MetricsSnapshot::Gdsf(metrics) => metrics.to_btreemap(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lru_metrics(requests: u64, hits: u64) -> LruCacheMetrics {
+        let mut metrics = LruCacheMetrics::new(1000);
+        for _ in 0..hits {
+            metrics.core.record_hit(10);
+        }
+        for _ in hits..requests {
+            metrics.core.record_miss(10);
+        }
+        metrics
+    }
+
+    #[test]
+    fn test_delta_subtracts_counters_and_keeps_gauges() {
+        let mut before = lru_metrics(4, 2);
+        before.core.record_insertion(100);
+        let mut after = before.clone();
+        after.core.record_hit(10);
+        after.core.record_hit(10);
+        after.core.record_insertion(50);
+
+        let delta = MetricsSnapshot::Lru(after).delta(&MetricsSnapshot::Lru(before));
+        let core = delta.core();
+        assert!(matches!(delta, MetricsSnapshot::Lru(_)));
+        assert_eq!(core.requests, 2);
+        assert_eq!(core.cache_hits, 2);
+        assert_eq!(core.bytes_written_to_cache, 50);
+        assert_eq!(core.cache_size_bytes, 150);
+        assert_eq!(core.max_cache_size_bytes, 1000);
+        assert_eq!(core.hit_rate(), 1.0);
+    }
+
+    #[test]
+    fn test_delta_saturates_after_reset() {
+        let before = MetricsSnapshot::Lru(lru_metrics(10, 5));
+        let after = MetricsSnapshot::Lru(lru_metrics(3, 1));
+        let delta = after.delta(&before);
+        assert_eq!(delta.core().requests, 0);
+        assert_eq!(delta.core().cache_hits, 0);
+    }
+
+    #[test]
+    fn test_merge_combines_rates_from_counters() {
+        let mut merged = MetricsSnapshot::Disabled;
+        merged.merge(&MetricsSnapshot::Lru(lru_metrics(4, 4)));
+        merged.merge(&MetricsSnapshot::Lru(lru_metrics(4, 0)));
+        merged.merge(&MetricsSnapshot::Disabled);
+
+        assert!(matches!(merged, MetricsSnapshot::Lru(_)));
+        assert_eq!(merged.core().requests, 8);
+        assert_eq!(merged.core().max_cache_size_bytes, 2000);
+        assert_eq!(merged.to_btreemap().get("hit_rate"), Some(&0.5));
+    }
+
+    #[test]
+    fn test_merge_algorithm_specific_metrics() {
+        let mut a = SlruCacheMetrics::new(1000, 400);
+        a.record_promotion();
+        a.probationary_size = 3;
+        let mut b = SlruCacheMetrics::new(1000, 400);
+        b.record_promotion();
+        b.record_demotion();
+        b.protected_size = 2;
+
+        let mut merged = MetricsSnapshot::Slru(a);
+        merged.merge(&MetricsSnapshot::Slru(b));
+        let MetricsSnapshot::Slru(merged) = merged else {
+            panic!("expected SLRU snapshot");
+        };
+        assert_eq!(merged.total_promotions, 2);
+        assert_eq!(merged.total_demotions, 1);
+        assert_eq!(merged.probationary_size, 3);
+        assert_eq!(merged.protected_size, 2);
+        assert_eq!(merged.protected_max_size, 800);
+    }
+
+    #[test]
+    fn test_mixed_algorithms_fall_back_to_core() {
+        let mut lfu = LfuCacheMetrics::new(1000);
+        lfu.core.record_miss(10);
+        let mut merged = MetricsSnapshot::Lru(lru_metrics(2, 1));
+        merged.merge(&MetricsSnapshot::Lfu(lfu));
+
+        assert!(matches!(merged, MetricsSnapshot::Core(_)));
+        assert_eq!(merged.core().requests, 3);
+        assert_eq!(merged.core().cache_hits, 1);
+    }
+
+    #[test]
+    fn test_btreemap_is_derived_view() {
+        let metrics = lru_metrics(5, 3);
+        assert_eq!(
+            MetricsSnapshot::Lru(metrics.clone()).to_btreemap(),
+            metrics.to_btreemap()
+        );
+        assert!(MetricsSnapshot::Disabled.to_btreemap().is_empty());
+        assert_eq!(MetricsSnapshot::Disabled.core().requests, 0);
+    }
+}
@@ -0,0 +1,100 @@
+//! Tiered Cache Metrics
+//!
+//! Metrics of a [`TieredCache`](crate::tiered::TieredCache) that describe how
+//! requests and entries move between its tiers. The metrics of the L1 cache
+//! itself are reported alongside them.
+
+extern crate alloc;
+
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+
+/// Per-tier metrics of a [`TieredCache`](crate::tiered::TieredCache).
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct TieredCacheMetrics {
+    /// Lookups served by the L1 cache.
+    pub l1_hits: u64,
+    /// Lookups that missed L1 and were served by the L2 store, promoting the
+    /// entry back to L1.
+    pub l2_hits: u64,
+    /// Lookups found in neither tier.
+    pub misses: u64,
+    /// Entries evicted from L1 and written to the L2 store.
+    pub demotions: u64,
+    /// Entries currently held by the L2 store.
+    pub l2_entries: u64,
+    /// Total size of the entries currently held by the L2 store.
+    pub l2_size_bytes: u64,
+}
+
+impl TieredCacheMetrics {
+    /// Returns the change since `previous`.
+    ///
+    /// Counters are subtracted; gauges keep their current values.
+    pub fn delta(&self, previous: &Self) -> Self {
+        Self {
+            l1_hits: self.l1_hits.saturating_sub(previous.l1_hits),
+            l2_hits: self.l2_hits.saturating_sub(previous.l2_hits),
+            misses: self.misses.saturating_sub(previous.misses),
+            demotions: self.demotions.saturating_sub(previous.demotions),
+            l2_entries: self.l2_entries,
+            l2_size_bytes: self.l2_size_bytes,
+        }
+    }
+
+    /// Adds the metrics of another tiered cache to these.
+    pub fn merge(&mut self, other: &Self) {
+        self.l1_hits += other.l1_hits;
+        self.l2_hits += other.l2_hits;
+        self.misses += other.misses;
+        self.demotions += other.demotions;
+        self.l2_entries += other.l2_entries;
+        self.l2_size_bytes += other.l2_size_bytes;
+    }
+
+    /// Zeroes the counters, keeping the L2 gauges.
+    pub fn reset(&mut self) {
+        *self = Self {
+            l2_entries: self.l2_entries,
+            l2_size_bytes: self.l2_size_bytes,
+            ..Self::default()
+        };
+    }
+
+    /// Returns the share of lookups served by either tier.
+    pub fn hit_rate(&self) -> f64 {
+        let lookups = self.l1_hits + self.l2_hits + self.misses;
+        if lookups > 0 {
+            (self.l1_hits + self.l2_hits) as f64 / lookups as f64
+        } else {
+            0.0
+        }
+    }
+
+    /// Returns the share of hits served by the L2 store.
+    pub fn l2_hit_share(&self) -> f64 {
+        let hits = self.l1_hits + self.l2_hits;
+        if hits > 0 {
+            self.l2_hits as f64 / hits as f64
+        } else {
+            0.0
+        }
+    }
+
+    /// Converts the metrics to a BTreeMap for reporting.
+    ///
+    /// Keys are prefixed with `tier_` so they do not collide with the L1
+    /// cache's own metrics.
+    pub fn to_btreemap(&self) -> BTreeMap<String, f64> {
+        let mut metrics = BTreeMap::new();
+        metrics.insert("tier_l1_hits".to_string(), self.l1_hits as f64);
+        metrics.insert("tier_l2_hits".to_string(), self.l2_hits as f64);
+        metrics.insert("tier_misses".to_string(), self.misses as f64);
+        metrics.insert("tier_demotions".to_string(), self.demotions as f64);
+        metrics.insert("tier_l2_entries".to_string(), self.l2_entries as f64);
+        metrics.insert("tier_l2_size_bytes".to_string(), self.l2_size_bytes as f64);
+        metrics.insert("tier_hit_rate".to_string(), self.hit_rate());
+        metrics.insert("tier_l2_hit_share".to_string(), self.l2_hit_share());
+        metrics
+    }
+}
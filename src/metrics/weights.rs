@@ -0,0 +1,32 @@
+//! Per-Dimension Weight Metrics
+//!
+//! Caches with [`WeightLimits`](crate::weight::WeightLimits) attached report
+//! one [`DimensionMetrics`] per dimension in
+//! [`CoreCacheMetrics::weights`](crate::metrics::CoreCacheMetrics::weights),
+//! and in [`CacheMetrics::metrics()`](crate::metrics::CacheMetrics::metrics)
+//! as `weight_<name>_used`, `weight_<name>_limit` and
+//! `weight_<name>_evictions` keys.
+
+/// Usage and evictions of one weight dimension.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct DimensionMetrics {
+    /// Name of the dimension
+    pub name: &'static str,
+    /// Total weight of the cached entries
+    pub used: u64,
+    /// Limit on the total weight
+    pub limit: u64,
+    /// Entries evicted to keep this dimension within its limit
+    pub evictions: u64,
+}
+
+impl DimensionMetrics {
+    /// Share of the limit in use, or 0.0 if the limit is 0.
+    pub fn utilization(&self) -> f64 {
+        if self.limit > 0 {
+            self.used as f64 / self.limit as f64
+        } else {
+            0.0
+        }
+    }
+}
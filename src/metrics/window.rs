@@ -0,0 +1,502 @@
+//! Rolling-Window Metrics
+//!
+//! [`CoreCacheMetrics`](crate::metrics::CoreCacheMetrics) counts from the
+//! moment the cache is created, so a lifetime hit rate barely moves when a
+//! long-running cache starts to degrade. A [`RollingWindow`] additionally
+//! tracks the most recent traffic, either the last N requests or the last T
+//! of time as measured by a pluggable [`Clock`], and reports the hit rate,
+//! byte hit rate and eviction rate over that window.
+//!
+//! Windows are attached with `set_metrics_window()` on any cache and show up
+//! in [`CacheMetrics::metrics()`](crate::metrics::CacheMetrics::metrics) as
+//! `window_*` keys:
+//!
+//! ```
+//! use cache_rs::config::LruCacheConfig;
+//! use cache_rs::metrics::{CacheMetrics, RollingWindow};
+//! use cache_rs::LruCache;
+//! use core::num::NonZeroUsize;
+//!
+//! let config = LruCacheConfig {
+//!     capacity: NonZeroUsize::new(10).unwrap(),
+//!     max_size: u64::MAX,
+//!     weight_limits: None,
+//! };
+//! let mut cache: LruCache<&str, i32> = LruCache::init(config, None);
+//! cache.set_metrics_window(Some(RollingWindow::last_requests(NonZeroUsize::new(2).unwrap())));
+//!
+//! cache.put("a", 1, 1);
+//! cache.record_miss(1);
+//! cache.get(&"a");
+//! cache.get(&"a");
+//!
+//! // The miss has left the two-request window
+//! let metrics = cache.metrics();
+//! assert_eq!(metrics.get("window_hit_rate"), Some(&1.0));
+//! assert_eq!(metrics.get("hit_rate"), Some(&(2.0 / 3.0)));
+//! ```
+
+#[cfg(feature = "std")]
+extern crate std;
+
+extern crate alloc;
+
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::num::NonZeroUsize;
+use core::time::Duration;
+
+#[cfg(feature = "std")]
+use std::time::Instant;
+
+/// Source of monotonic time for time-based windows.
+pub trait Clock: Send + Sync {
+    /// Returns the nanoseconds elapsed since an arbitrary, fixed origin.
+    ///
+    /// Must never decrease.
+    fn now_nanos(&self) -> u64;
+}
+
+/// [`Clock`] reading [`std::time::Instant`].
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy)]
+pub struct SystemClock {
+    origin: Instant,
+}
+
+#[cfg(feature = "std")]
+impl SystemClock {
+    /// Creates a clock whose origin is now.
+    pub fn new() -> Self {
+        Self {
+            origin: Instant::now(),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "std")]
+impl Clock for SystemClock {
+    fn now_nanos(&self) -> u64 {
+        u64::try_from(self.origin.elapsed().as_nanos()).unwrap_or(u64::MAX)
+    }
+}
+
+/// Requests, hits, bytes and evictions seen within a window.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct WindowStats {
+    /// Requests (gets) in the window
+    pub requests: u64,
+    /// Requests in the window that hit
+    pub cache_hits: u64,
+    /// Bytes requested in the window
+    pub bytes_requested: u64,
+    /// Bytes served from the cache in the window
+    pub bytes_served: u64,
+    /// Entries evicted in the window
+    pub evictions: u64,
+}
+
+impl WindowStats {
+    /// Share of the window's requests that hit.
+    pub fn hit_rate(&self) -> f64 {
+        ratio(self.cache_hits, self.requests)
+    }
+
+    /// Share of the window's requested bytes served from the cache.
+    pub fn byte_hit_rate(&self) -> f64 {
+        ratio(self.bytes_served, self.bytes_requested)
+    }
+
+    /// Evictions per request in the window.
+    pub fn eviction_rate(&self) -> f64 {
+        ratio(self.evictions, self.requests)
+    }
+
+    fn add(&mut self, other: &Self) {
+        self.requests += other.requests;
+        self.cache_hits += other.cache_hits;
+        self.bytes_requested += other.bytes_requested;
+        self.bytes_served += other.bytes_served;
+        self.evictions += other.evictions;
+    }
+}
+
+fn ratio(numerator: u64, denominator: u64) -> f64 {
+    if denominator > 0 {
+        numerator as f64 / denominator as f64
+    } else {
+        0.0
+    }
+}
+
+/// Tracks the metrics of the most recent requests.
+///
+/// Cloning a window clones its contents; caches give every segment an empty
+/// window of the same shape.
+#[derive(Clone)]
+pub struct RollingWindow {
+    kind: WindowKind,
+    /// Statistics of windows merged into this one.
+    merged: WindowStats,
+}
+
+#[derive(Clone)]
+enum WindowKind {
+    Requests(RequestWindow),
+    Time(TimeWindow),
+}
+
+impl RollingWindow {
+    /// Creates a window over the last `requests` requests.
+    ///
+    /// Evictions are attributed to the most recent request, so the eviction
+    /// rate covers the evictions since the oldest request in the window.
+    pub fn last_requests(requests: NonZeroUsize) -> Self {
+        Self::from_kind(WindowKind::Requests(RequestWindow {
+            samples: Vec::with_capacity(requests.get()),
+            limit: requests.get(),
+            next: 0,
+            totals: WindowStats::default(),
+            pending_evictions: 0,
+        }))
+    }
+
+    /// Creates a window over the last `span` of time as measured by `clock`.
+    ///
+    /// The span is divided into `buckets` slots and the window advances one
+    /// slot at a time, so the oldest slot may be partially expired. The clock
+    /// is read on every request and eviction.
+    pub fn last_duration(span: Duration, buckets: NonZeroUsize, clock: Arc<dyn Clock>) -> Self {
+        let span_nanos = u64::try_from(span.as_nanos()).unwrap_or(u64::MAX);
+        let bucket_nanos = (span_nanos / buckets.get() as u64).max(1);
+        Self::from_kind(WindowKind::Time(TimeWindow {
+            clock,
+            bucket_nanos,
+            buckets: alloc::vec![(NEVER, WindowStats::default()); buckets.get()],
+        }))
+    }
+
+    /// Creates a window over the last `span` of time as measured by a
+    /// [`SystemClock`].
+    #[cfg(feature = "std")]
+    pub fn last_duration_system(span: Duration, buckets: NonZeroUsize) -> Self {
+        Self::last_duration(span, buckets, Arc::new(SystemClock::new()))
+    }
+
+    fn from_kind(kind: WindowKind) -> Self {
+        Self {
+            kind,
+            merged: WindowStats::default(),
+        }
+    }
+
+    /// Returns the statistics of the requests currently in the window.
+    pub fn stats(&self) -> WindowStats {
+        let mut stats = match &self.kind {
+            WindowKind::Requests(window) => window.totals,
+            WindowKind::Time(window) => window.stats(),
+        };
+        stats.add(&self.merged);
+        stats
+    }
+
+    /// Discards everything recorded so far, keeping the window's shape.
+    pub fn clear(&mut self) {
+        self.merged = WindowStats::default();
+        match &mut self.kind {
+            WindowKind::Requests(window) => {
+                window.samples.clear();
+                window.next = 0;
+                window.totals = WindowStats::default();
+                window.pending_evictions = 0;
+            }
+            WindowKind::Time(window) => {
+                for bucket in window.buckets.iter_mut() {
+                    *bucket = (NEVER, WindowStats::default());
+                }
+            }
+        }
+    }
+
+    /// Adds the current statistics of `other` to this window's, e.g. to
+    /// aggregate the windows of a concurrent cache's segments.
+    ///
+    /// The merged statistics are fixed at merge time and do not expire.
+    pub fn merge(&mut self, other: &Self) {
+        self.merged.add(&other.stats());
+    }
+
+    /// Returns an empty window of the same shape for one of `segments`
+    /// segments. Request windows are split evenly, time windows are not.
+    #[allow(dead_code)] // Used by concurrent module when feature is enabled
+    pub(crate) fn for_segment(&self, segments: usize) -> Self {
+        let mut window = match &self.kind {
+            WindowKind::Requests(window) => {
+                let limit = window.limit.div_ceil(segments.max(1));
+                Self::last_requests(NonZeroUsize::new(limit).unwrap_or(NonZeroUsize::MIN))
+            }
+            WindowKind::Time(_) => self.clone(),
+        };
+        window.clear();
+        window
+    }
+
+    pub(crate) fn record_request(&mut self, object_size: u64, hit: bool) {
+        let served = if hit { object_size } else { 0 };
+        match &mut self.kind {
+            WindowKind::Requests(window) => window.record_request(object_size, served, hit),
+            WindowKind::Time(window) => {
+                let bucket = window.current();
+                bucket.requests += 1;
+                bucket.cache_hits += u64::from(hit);
+                bucket.bytes_requested += object_size;
+                bucket.bytes_served += served;
+            }
+        }
+    }
+
+    pub(crate) fn record_eviction(&mut self) {
+        match &mut self.kind {
+            WindowKind::Requests(window) => window.record_eviction(),
+            WindowKind::Time(window) => window.current().evictions += 1,
+        }
+    }
+}
+
+impl core::fmt::Debug for RollingWindow {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let mut s = f.debug_struct("RollingWindow");
+        match &self.kind {
+            WindowKind::Requests(window) => s.field("requests", &window.limit),
+            WindowKind::Time(window) => s
+                .field("bucket_nanos", &window.bucket_nanos)
+                .field("buckets", &window.buckets.len()),
+        };
+        s.field("stats", &self.stats()).finish()
+    }
+}
+
+/// Windows compare equal if they have the same shape and statistics; the
+/// clocks of time windows are not compared.
+impl PartialEq for RollingWindow {
+    fn eq(&self, other: &Self) -> bool {
+        let same_shape = match (&self.kind, &other.kind) {
+            (WindowKind::Requests(a), WindowKind::Requests(b)) => a.limit == b.limit,
+            (WindowKind::Time(a), WindowKind::Time(b)) => {
+                a.bucket_nanos == b.bucket_nanos && a.buckets.len() == b.buckets.len()
+            }
+            _ => false,
+        };
+        same_shape && self.stats() == other.stats()
+    }
+}
+
+impl Eq for RollingWindow {}
+
+/// One request of a [`RequestWindow`].
+#[derive(Debug, Default, Clone, Copy)]
+struct Sample {
+    bytes_requested: u64,
+    bytes_served: u64,
+    hit: bool,
+    /// Evictions recorded after this request and before the next one.
+    evictions: u64,
+}
+
+/// Ring buffer of the last `limit` requests with running totals.
+#[derive(Debug, Clone)]
+struct RequestWindow {
+    samples: Vec<Sample>,
+    limit: usize,
+    /// Slot the next request is written to.
+    next: usize,
+    totals: WindowStats,
+    /// Evictions recorded before the first request.
+    pending_evictions: u64,
+}
+
+impl RequestWindow {
+    fn record_request(&mut self, bytes_requested: u64, bytes_served: u64, hit: bool) {
+        let sample = Sample {
+            bytes_requested,
+            bytes_served,
+            hit,
+            evictions: core::mem::take(&mut self.pending_evictions),
+        };
+        if self.samples.len() < self.limit {
+            self.samples.push(sample);
+        } else {
+            let old = core::mem::replace(&mut self.samples[self.next], sample);
+            self.totals.requests -= 1;
+            self.totals.cache_hits -= u64::from(old.hit);
+            self.totals.bytes_requested -= old.bytes_requested;
+            self.totals.bytes_served -= old.bytes_served;
+            self.totals.evictions -= old.evictions;
+        }
+        self.next = (self.next + 1) % self.limit;
+        // The sample's evictions are already part of the totals
+        self.totals.requests += 1;
+        self.totals.cache_hits += u64::from(hit);
+        self.totals.bytes_requested += bytes_requested;
+        self.totals.bytes_served += bytes_served;
+    }
+
+    fn record_eviction(&mut self) {
+        self.totals.evictions += 1;
+        if self.samples.is_empty() {
+            self.pending_evictions += 1;
+        } else {
+            let last = (self.next + self.limit - 1) % self.limit;
+            self.samples[last].evictions += 1;
+        }
+    }
+}
+
+/// Bucket epoch of a slot that has never been written.
+const NEVER: u64 = u64::MAX;
+
+/// Time buckets of `bucket_nanos` each, indexed by epoch modulo their count.
+#[derive(Clone)]
+struct TimeWindow {
+    clock: Arc<dyn Clock>,
+    bucket_nanos: u64,
+    /// Epoch (time divided by `bucket_nanos`) and statistics of each slot.
+    buckets: Vec<(u64, WindowStats)>,
+}
+
+impl TimeWindow {
+    /// Returns the bucket of the current epoch, recycling an expired one.
+    fn current(&mut self) -> &mut WindowStats {
+        let epoch = self.clock.now_nanos() / self.bucket_nanos;
+        let slot = (epoch % self.buckets.len() as u64) as usize;
+        let bucket = &mut self.buckets[slot];
+        if bucket.0 != epoch {
+            *bucket = (epoch, WindowStats::default());
+        }
+        &mut bucket.1
+    }
+
+    fn stats(&self) -> WindowStats {
+        let epoch = self.clock.now_nanos() / self.bucket_nanos;
+        let len = self.buckets.len() as u64;
+        let mut stats = WindowStats::default();
+        for (bucket_epoch, bucket) in self.buckets.iter() {
+            if *bucket_epoch <= epoch && epoch - bucket_epoch < len {
+                stats.add(bucket);
+            }
+        }
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::sync::atomic::{AtomicU64, Ordering};
+
+    #[derive(Default)]
+    struct ManualClock(AtomicU64);
+
+    impl ManualClock {
+        fn advance(&self, nanos: u64) {
+            self.0.fetch_add(nanos, Ordering::Relaxed);
+        }
+    }
+
+    impl Clock for ManualClock {
+        fn now_nanos(&self) -> u64 {
+            self.0.load(Ordering::Relaxed)
+        }
+    }
+
+    fn nz(n: usize) -> NonZeroUsize {
+        NonZeroUsize::new(n).unwrap()
+    }
+
+    #[test]
+    fn test_request_window_keeps_last_n() {
+        let mut window = RollingWindow::last_requests(nz(3));
+        window.record_request(10, false);
+        window.record_request(10, false);
+        window.record_request(10, true);
+        window.record_request(30, true);
+
+        let stats = window.stats();
+        assert_eq!(stats.requests, 3);
+        assert_eq!(stats.cache_hits, 2);
+        assert_eq!(stats.bytes_requested, 50);
+        assert_eq!(stats.bytes_served, 40);
+        assert_eq!(stats.byte_hit_rate(), 0.8);
+    }
+
+    #[test]
+    fn test_request_window_expires_evictions_with_their_request() {
+        let mut window = RollingWindow::last_requests(nz(2));
+        window.record_eviction();
+        window.record_request(1, false);
+        window.record_eviction();
+        assert_eq!(window.stats().evictions, 2);
+
+        window.record_request(1, true);
+        assert_eq!(window.stats().eviction_rate(), 1.0);
+
+        // The first request, and the evictions attributed to it, expire
+        window.record_request(1, true);
+        assert_eq!(window.stats().evictions, 0);
+        assert_eq!(window.stats().hit_rate(), 1.0);
+    }
+
+    #[test]
+    fn test_time_window_expires_old_buckets() {
+        let clock = Arc::new(ManualClock::default());
+        let mut window =
+            RollingWindow::last_duration(Duration::from_secs(10), nz(5), clock.clone());
+
+        window.record_request(1, false);
+        window.record_eviction();
+        clock.advance(4_000_000_000);
+        window.record_request(1, true);
+        assert_eq!(window.stats().requests, 2);
+        assert_eq!(window.stats().evictions, 1);
+
+        clock.advance(7_000_000_000);
+        let stats = window.stats();
+        assert_eq!(stats.requests, 1);
+        assert_eq!(stats.hit_rate(), 1.0);
+        assert_eq!(stats.evictions, 0);
+
+        clock.advance(10_000_000_000);
+        assert_eq!(window.stats(), WindowStats::default());
+    }
+
+    #[test]
+    fn test_clear_and_merge() {
+        let mut a = RollingWindow::last_requests(nz(4));
+        a.record_request(1, true);
+        let mut b = RollingWindow::last_requests(nz(4));
+        b.record_request(1, false);
+
+        a.merge(&b);
+        assert_eq!(a.stats().requests, 2);
+        assert_eq!(a.stats().hit_rate(), 0.5);
+
+        a.clear();
+        assert_eq!(a.stats(), WindowStats::default());
+        assert_eq!(a, RollingWindow::last_requests(nz(4)));
+    }
+
+    #[test]
+    fn test_for_segment_splits_request_windows() {
+        let mut window = RollingWindow::last_requests(nz(10));
+        window.record_request(1, true);
+        let segment = window.for_segment(4);
+        assert_eq!(segment, RollingWindow::last_requests(nz(3)));
+    }
+}
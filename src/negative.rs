@@ -0,0 +1,296 @@
+//! Negative Caching
+//!
+//! Lookups of keys that do not exist in the backing store miss every time,
+//! so each one reaches the backend. A
+//! [`NegativeCache`](crate::negative::NegativeCache) remembers such keys for
+//! a limited time: once attached with `set_negative_cache()`, a cache's
+//! `put_absent(key, ttl)` records that `key` is known to be absent, and its
+//! `lookup()` tells the three outcomes apart with a
+//! [`Lookup`](crate::negative::Lookup):
+//!
+//! ```
+//! use cache_rs::config::LruCacheConfig;
+//! use cache_rs::metrics::{CacheMetrics, Clock};
+//! use cache_rs::negative::{Lookup, NegativeCache};
+//! use cache_rs::LruCache;
+//! use core::num::NonZeroUsize;
+//! use core::sync::atomic::{AtomicU64, Ordering};
+//! use core::time::Duration;
+//! use std::sync::Arc;
+//!
+//! /// Seconds advanced by hand
+//! #[derive(Default)]
+//! struct Seconds(AtomicU64);
+//!
+//! impl Clock for Seconds {
+//!     fn now_nanos(&self) -> u64 {
+//!         self.0.load(Ordering::Relaxed) * 1_000_000_000
+//!     }
+//! }
+//!
+//! let config = LruCacheConfig {
+//!     capacity: NonZeroUsize::new(100).unwrap(),
+//!     max_size: u64::MAX,
+//!     weight_limits: None,
+//! };
+//! let mut cache: LruCache<&str, i32> = LruCache::init(config, None);
+//! let clock = Arc::new(Seconds::default());
+//! let negative = NegativeCache::new(NonZeroUsize::new(10).unwrap(), clock.clone());
+//! cache.set_negative_cache(Some(negative));
+//!
+//! cache.put("present", 1, 1);
+//! cache.put_absent("deleted", Duration::from_secs(30));
+//!
+//! assert_eq!(cache.lookup(&"present"), Lookup::Hit(&1));
+//! assert_eq!(cache.lookup(&"deleted"), Lookup::Absent);
+//! assert_eq!(cache.lookup(&"unknown"), Lookup::Miss);
+//!
+//! // The negative entry expires after its TTL
+//! clock.0.store(30, Ordering::Relaxed);
+//! assert_eq!(cache.lookup(&"deleted"), Lookup::Miss);
+//!
+//! let core = cache.snapshot().core().clone();
+//! assert_eq!((core.negative_hits, core.negative_misses), (1, 2));
+//! ```
+//!
+//! Negative entries live outside the cache's own capacity and size budget,
+//! in the negative cache's separate entry budget, so they never evict real
+//! values. When that budget is full the entry closest to expiring makes room.
+//! Caching a value for a key drops its negative entry, and marking a key
+//! absent removes its cached value.
+
+extern crate alloc;
+
+use crate::key_map::KeyMap;
+use crate::metrics::Clock;
+use alloc::collections::BTreeMap;
+use alloc::sync::Arc;
+use core::borrow::Borrow;
+use core::fmt;
+use core::num::NonZeroUsize;
+use core::time::Duration;
+
+#[cfg(feature = "std")]
+use crate::metrics::SystemClock;
+
+/// Outcome of a cache's `lookup()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lookup<V> {
+    /// The key is cached with this value.
+    Hit(V),
+    /// The key is known to be absent from the backing store.
+    Absent,
+    /// Nothing is known about the key.
+    Miss,
+}
+
+impl<V> Lookup<V> {
+    /// Returns the cached value, if the lookup hit.
+    pub fn hit(self) -> Option<V> {
+        match self {
+            Lookup::Hit(value) => Some(value),
+            Lookup::Absent | Lookup::Miss => None,
+        }
+    }
+
+    /// Returns `true` if the key is known to be absent.
+    pub fn is_absent(&self) -> bool {
+        matches!(self, Lookup::Absent)
+    }
+
+    /// Returns `true` if nothing is known about the key.
+    pub fn is_miss(&self) -> bool {
+        matches!(self, Lookup::Miss)
+    }
+
+    /// Maps the cached value of a hit.
+    pub fn map<U>(self, f: impl FnOnce(V) -> U) -> Lookup<U> {
+        match self {
+            Lookup::Hit(value) => Lookup::Hit(f(value)),
+            Lookup::Absent => Lookup::Absent,
+            Lookup::Miss => Lookup::Miss,
+        }
+    }
+}
+
+/// Keys known to be absent from the backing store, each until its own
+/// expiry, in a budget of its own.
+///
+/// A negative cache is keyed by the hashes of the cache it is attached to,
+/// so it is only consulted through that cache.
+pub struct NegativeCache<K> {
+    capacity: usize,
+    /// Expiry and insertion sequence number of each key
+    entries: KeyMap<K, (u64, u64), ()>,
+    /// Hash and key of each entry, ordered by expiry, then insertion
+    expiries: BTreeMap<(u64, u64), (u64, K)>,
+    next_seq: u64,
+    clock: Arc<dyn Clock>,
+}
+
+impl<K: Eq> NegativeCache<K> {
+    /// Creates a negative cache holding up to `capacity` keys, whose entries
+    /// expire as measured by `clock`.
+    pub fn new(capacity: NonZeroUsize, clock: Arc<dyn Clock>) -> Self {
+        NegativeCache {
+            capacity: capacity.get(),
+            entries: KeyMap::with_capacity_and_hasher(0, ()),
+            expiries: BTreeMap::new(),
+            next_seq: 0,
+            clock,
+        }
+    }
+
+    /// Creates a negative cache holding up to `capacity` keys, whose entries
+    /// expire as measured by a [`SystemClock`].
+    #[cfg(feature = "std")]
+    pub fn with_system_clock(capacity: NonZeroUsize) -> Self {
+        Self::new(capacity, Arc::new(SystemClock::new()))
+    }
+
+    /// Returns the maximum number of keys held.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the number of keys held, including expired keys that have
+    /// not been dropped yet.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if no keys are held.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Forgets all keys.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.expiries.clear();
+    }
+
+    /// Returns an empty negative cache for one of `segments` segments, each
+    /// holding a share of the keys.
+    #[allow(dead_code)] // Used by concurrent module when feature is enabled
+    pub(crate) fn for_segment(&self, segments: usize) -> Self {
+        Self::new(
+            NonZeroUsize::new(self.capacity.div_ceil(segments)).unwrap_or(NonZeroUsize::MIN),
+            Arc::clone(&self.clock),
+        )
+    }
+
+    /// Records that `key`, whose hash is `hash`, is absent for the next
+    /// `ttl`, replacing any previous entry for it.
+    pub(crate) fn insert(&mut self, hash: u64, key: K, ttl: Duration)
+    where
+        K: Clone,
+    {
+        let now = self.clock.now_nanos();
+        let ttl = u64::try_from(ttl.as_nanos()).unwrap_or(u64::MAX);
+        self.remove(hash, &key);
+        self.drop_expired(now);
+        if self.entries.len() >= self.capacity {
+            // The entry closest to expiring makes room
+            if let Some((_, (hash, victim))) = self.expiries.pop_first() {
+                self.entries.remove(hash, &victim);
+            }
+        }
+        let entry = (now.saturating_add(ttl), self.next_seq);
+        self.next_seq += 1;
+        self.expiries.insert(entry, (hash, key.clone()));
+        self.entries.insert(hash, key, entry);
+    }
+
+    /// Returns `true` if `key`, whose hash is `hash`, is held and has not
+    /// expired, dropping it if it has.
+    pub(crate) fn check<Q>(&mut self, hash: u64, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Eq,
+    {
+        let Some(&(expiry, _)) = self.entries.get(hash, key) else {
+            return false;
+        };
+        if expiry > self.clock.now_nanos() {
+            true
+        } else {
+            self.remove(hash, key);
+            false
+        }
+    }
+
+    /// Forgets `key`, whose hash is `hash`, returning `true` if it was held.
+    pub(crate) fn remove<Q>(&mut self, hash: u64, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Eq,
+    {
+        match self.entries.remove(hash, key) {
+            Some(entry) => {
+                self.expiries.remove(&entry);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn drop_expired(&mut self, now: u64) {
+        while let Some(entry) = self.expiries.first_entry() {
+            if entry.key().0 > now {
+                break;
+            }
+            let (hash, key) = entry.remove();
+            self.entries.remove(hash, &key);
+        }
+    }
+}
+
+impl<K> fmt::Debug for NegativeCache<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NegativeCache")
+            .field("capacity", &self.capacity)
+            .field("len", &self.entries.len())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::sync::atomic::{AtomicU64, Ordering};
+
+    #[derive(Default)]
+    struct ManualClock(AtomicU64);
+
+    impl Clock for ManualClock {
+        fn now_nanos(&self) -> u64 {
+            self.0.load(Ordering::Relaxed)
+        }
+    }
+
+    #[test]
+    fn test_expired_entries_free_the_budget() {
+        let clock = Arc::new(ManualClock::default());
+        let mut negative = NegativeCache::new(NonZeroUsize::new(2).unwrap(), clock.clone());
+        negative.insert(1, "a", Duration::from_nanos(10));
+        negative.insert(2, "b", Duration::from_nanos(50));
+        // Re-inserting a key replaces its expiry instead of taking a slot
+        negative.insert(1, "a", Duration::from_nanos(100));
+        assert_eq!(negative.len(), 2);
+
+        clock.0.store(60, Ordering::Relaxed);
+        assert!(negative.check(1, "a"));
+        assert!(!negative.check(2, "b"));
+        assert_eq!(negative.len(), 1);
+
+        // A full budget drops the entry closest to expiring
+        negative.insert(3, "c", Duration::from_nanos(10));
+        negative.insert(4, "d", Duration::from_nanos(20));
+        assert!(!negative.check(3, "c"));
+        assert!(negative.check(1, "a"));
+        assert!(negative.check(4, "d"));
+        assert!(negative.remove(4, "d"));
+        assert!(!negative.remove(4, "d"));
+    }
+}
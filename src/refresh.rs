@@ -0,0 +1,323 @@
+//! Refresh-Ahead and Stale-While-Revalidate
+//!
+//! A [`RefreshPolicy`](crate::refresh::RefreshPolicy) ages every entry from
+//! its creation time ([`CacheMetadata::create_time`](crate::CacheMetadata)),
+//! so values can be refreshed before they expire instead of blocking callers
+//! once they do. Each entry goes through four stages:
+//!
+//! | Age                              | [`Freshness`](crate::refresh::Freshness) | Served |
+//! |----------------------------------|------------------------------------------|--------|
+//! | below `refresh_after`            | `Fresh`                                  | yes    |
+//! | `refresh_after` up to `ttl`      | `Stale`, needs a refresh                 | yes    |
+//! | `ttl` up to `ttl + grace`        | `Grace`, expired but a refresh failed    | yes    |
+//! | `ttl + grace` and older          | expired and removed                      | no     |
+//!
+//! Policies are attached with `set_refresh_policy()` on any cache.
+//! `get_fresh()` then returns each value with its freshness, leaving the
+//! refresh to the caller, while a
+//! [`LoadingCache`](crate::loader::LoadingCache) refreshes stale entries
+//! through its loader, once per entry, and keeps serving the stale value
+//! through the grace window if the refresh fails. Replacing a value with
+//! `put()` makes it fresh again.
+//!
+//! ```
+//! use cache_rs::config::LruCacheConfig;
+//! use cache_rs::refresh::{Freshness, RefreshPolicy};
+//! use cache_rs::LruCache;
+//! use core::num::NonZeroUsize;
+//! use core::time::Duration;
+//!
+//! let config = LruCacheConfig {
+//!     capacity: NonZeroUsize::new(100).unwrap(),
+//!     max_size: u64::MAX,
+//!     weight_limits: None,
+//! };
+//! let mut flags: LruCache<&str, bool> = LruCache::init(config, None);
+//! // Refresh right away, expire after a minute
+//! flags.set_refresh_policy(Some(RefreshPolicy::new(Duration::ZERO, Duration::from_secs(60))));
+//!
+//! flags.put("dark_mode", true, 1);
+//! assert_eq!(flags.get_fresh(&"dark_mode"), Some((&true, Freshness::Stale)));
+//! ```
+//!
+//! Ages are read from the system clock unless the cache has a
+//! [`Clock`](crate::metrics::Clock) set with `set_clock()`. Without the `std`
+//! feature there is no system clock, so entries only age under such a clock.
+//!
+//! # Early Expiration
+//!
+//! Entries inserted together with the same TTL also expire together, and
+//! their reloads stampede the backend. An [`XFetch`](crate::refresh::XFetch)
+//! attached with `set_xfetch()` spreads them out: each lookup through
+//! `get_fresh()` reports a fresh value as `Stale` with probability
+//! `exp(-remaining_ttl / (recompute_cost * beta))`, so values are reloaded
+//! sooner the closer they are to expiring and the longer they take to
+//! recompute. The value stays cached: only the caller that drew the early
+//! expiration is asked to refresh it. Randomness comes from a pluggable
+//! [`RandomSource`](crate::refresh::RandomSource), such as the bundled
+//! seedable [`SplitMix64`](crate::refresh::SplitMix64).
+
+extern crate alloc;
+
+use alloc::sync::Arc;
+use core::fmt;
+use core::sync::atomic::{AtomicU64, Ordering};
+use core::time::Duration;
+
+/// Ages at which cached values need a refresh and expire.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RefreshPolicy {
+    /// Age from which a value is stale and should be refreshed.
+    pub refresh_after: Duration,
+    /// Age at which a value expires.
+    pub ttl: Duration,
+    /// How long after `ttl` an expired value is still served while its
+    /// refresh fails.
+    pub grace: Duration,
+}
+
+/// Stage of a cached value under a [`RefreshPolicy`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Freshness {
+    /// Younger than `refresh_after`.
+    Fresh,
+    /// Past `refresh_after` but not yet expired; serve it and refresh it.
+    Stale,
+    /// Expired, but within the grace window: serve it only because its
+    /// refresh has not succeeded.
+    Grace,
+    /// Expired past the grace window.
+    Expired,
+}
+
+impl RefreshPolicy {
+    /// Creates a policy without a grace window.
+    pub fn new(refresh_after: Duration, ttl: Duration) -> Self {
+        RefreshPolicy {
+            refresh_after,
+            ttl,
+            grace: Duration::ZERO,
+        }
+    }
+
+    /// Serves expired values for `grace` longer while their refresh fails.
+    pub fn with_grace(self, grace: Duration) -> Self {
+        RefreshPolicy { grace, ..self }
+    }
+
+    /// Returns the stage of a value that is `age_nanos` old.
+    pub fn freshness(&self, age_nanos: u64) -> Freshness {
+        let nanos = |duration: Duration| u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX);
+        let ttl = nanos(self.ttl);
+        if age_nanos >= ttl.saturating_add(nanos(self.grace)) {
+            Freshness::Expired
+        } else if age_nanos >= ttl {
+            Freshness::Grace
+        } else if age_nanos >= nanos(self.refresh_after) {
+            Freshness::Stale
+        } else {
+            Freshness::Fresh
+        }
+    }
+}
+
+/// Source of random numbers for [`XFetch`].
+pub trait RandomSource: Send + Sync {
+    /// Returns a random number, uniformly distributed over all `u64` values.
+    fn next_u64(&self) -> u64;
+}
+
+/// The SplitMix64 generator: fast, `no_std`, and reproducible from its seed.
+///
+/// Not suitable for cryptographic use.
+#[derive(Debug)]
+pub struct SplitMix64(AtomicU64);
+
+impl SplitMix64 {
+    /// Creates a generator whose sequence is determined by `seed`.
+    pub fn new(seed: u64) -> Self {
+        SplitMix64(AtomicU64::new(seed))
+    }
+}
+
+impl RandomSource for SplitMix64 {
+    fn next_u64(&self) -> u64 {
+        const GAMMA: u64 = 0x9e37_79b9_7f4a_7c15;
+        let mut z = self
+            .0
+            .fetch_add(GAMMA, Ordering::Relaxed)
+            .wrapping_add(GAMMA);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+/// Probabilistic early expiration, from "Optimal Probabilistic Cache
+/// Stampede Prevention" (Vattani, Chierichetti and Lowenstein, 2015).
+///
+/// Applies to entries under a [`RefreshPolicy`], whose `ttl` it brings
+/// forward at random.
+#[derive(Clone)]
+pub struct XFetch {
+    /// Typical time to recompute a value.
+    pub recompute_cost: Duration,
+    /// Scales how early values expire; above 1 favors earlier reloads.
+    pub beta: f64,
+    rng: Arc<dyn RandomSource>,
+}
+
+impl XFetch {
+    /// Creates an early expiration mode for values that take about
+    /// `recompute_cost` to recompute, drawing randomness from `rng`, with a
+    /// `beta` of 1.
+    pub fn new(recompute_cost: Duration, rng: Arc<dyn RandomSource>) -> Self {
+        XFetch {
+            recompute_cost,
+            beta: 1.0,
+            rng,
+        }
+    }
+
+    /// Sets `beta`, which scales how early values expire.
+    pub fn with_beta(self, beta: f64) -> Self {
+        XFetch { beta, ..self }
+    }
+
+    /// Returns `true` if a value that is `age_nanos` old and expires at
+    /// `ttl_nanos` should be treated as expired now.
+    pub fn expires_early(&self, age_nanos: u64, ttl_nanos: u64) -> bool {
+        let Some(remaining) = ttl_nanos.checked_sub(age_nanos).filter(|&r| r > 0) else {
+            return true;
+        };
+        let gap = self.recompute_cost.as_nanos() as f64 * self.beta;
+        if gap.is_nan() || gap <= 0.0 {
+            return false;
+        }
+        // Uniform in [0, 1) from the top 53 bits
+        let sample = (self.rng.next_u64() >> 11) as f64 / (1u64 << 53) as f64;
+        sample < exp_neg(remaining as f64 / gap)
+    }
+
+    /// Returns the stage of a value that is `age_nanos` old under `policy`,
+    /// reporting a fresh value as stale when it expires early.
+    ///
+    /// Only the lookup that draws the early expiration sees it, so that one
+    /// caller refreshes the value while the others keep being served.
+    pub fn freshness(&self, policy: &RefreshPolicy, age_nanos: u64) -> Freshness {
+        match policy.freshness(age_nanos) {
+            Freshness::Fresh => {
+                let ttl = u64::try_from(policy.ttl.as_nanos()).unwrap_or(u64::MAX);
+                if self.expires_early(age_nanos, ttl) {
+                    Freshness::Stale
+                } else {
+                    Freshness::Fresh
+                }
+            }
+            freshness => freshness,
+        }
+    }
+}
+
+impl fmt::Debug for XFetch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("XFetch")
+            .field("recompute_cost", &self.recompute_cost)
+            .field("beta", &self.beta)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Returns `e^-x` for `x >= 0`, without the `std` feature's `exp()`.
+fn exp_neg(x: f64) -> f64 {
+    if x >= 700.0 {
+        return 0.0;
+    }
+    // e^-x = 2^-k * e^-r, with r in [0, ln 2)
+    let k = (x / core::f64::consts::LN_2) as u64;
+    let r = x - k as f64 * core::f64::consts::LN_2;
+    let (mut term, mut sum) = (1.0, 1.0);
+    for n in 1..=16 {
+        term *= -r / n as f64;
+        sum += term;
+    }
+    sum * f64::from_bits((1023 - k) << 52)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_freshness_stages() {
+        let policy = RefreshPolicy::new(Duration::from_nanos(10), Duration::from_nanos(20))
+            .with_grace(Duration::from_nanos(5));
+        assert_eq!(policy.freshness(0), Freshness::Fresh);
+        assert_eq!(policy.freshness(9), Freshness::Fresh);
+        assert_eq!(policy.freshness(10), Freshness::Stale);
+        assert_eq!(policy.freshness(20), Freshness::Grace);
+        assert_eq!(policy.freshness(24), Freshness::Grace);
+        assert_eq!(policy.freshness(25), Freshness::Expired);
+
+        // Without a grace window values expire at the TTL
+        let policy = RefreshPolicy::new(Duration::ZERO, Duration::MAX);
+        assert_eq!(policy.freshness(0), Freshness::Stale);
+        assert_eq!(policy.freshness(u64::MAX - 1), Freshness::Stale);
+        let policy = RefreshPolicy::new(Duration::ZERO, Duration::ZERO);
+        assert_eq!(policy.freshness(0), Freshness::Expired);
+    }
+
+    /// Returns the same number forever
+    struct Constant(u64);
+
+    impl RandomSource for Constant {
+        fn next_u64(&self) -> u64 {
+            self.0
+        }
+    }
+
+    #[test]
+    fn test_exp_neg() {
+        for (x, expected) in [
+            (0.0, 1.0),
+            (1.0, 0.367_879_441_171_442_3),
+            (10.0, 4.539_992_976_248_485e-5),
+        ] {
+            assert!((exp_neg(x) - expected).abs() <= expected * 1e-12, "{x}");
+        }
+        assert_eq!(exp_neg(1000.0), 0.0);
+    }
+
+    #[test]
+    fn test_xfetch_expires_early() {
+        let cost = Duration::from_nanos(100);
+        // A sample of 0 expires any value with time left, one near 1 none
+        let eager = XFetch::new(cost, Arc::new(Constant(0)));
+        let lazy = XFetch::new(cost, Arc::new(Constant(u64::MAX)));
+        assert!(eager.expires_early(0, 1_000));
+        assert!(!lazy.expires_early(999, 1_000));
+        assert!(lazy.expires_early(1_000, 1_000));
+        assert!(!XFetch::new(Duration::ZERO, Arc::new(Constant(0))).expires_early(0, 1_000));
+
+        // Values expire early more often as their TTL runs out
+        let early = |xfetch: &XFetch, age| {
+            (0..10_000)
+                .filter(|_| xfetch.expires_early(age, 1_000))
+                .count()
+        };
+        let xfetch = XFetch::new(cost, Arc::new(SplitMix64::new(7)));
+        // exp(-9) and exp(-1) of the samples
+        assert!(early(&xfetch, 100) < 10);
+        assert!((3_400..4_000).contains(&early(&xfetch, 900)));
+        let xfetch = xfetch.with_beta(10.0);
+        assert!((3_400..4_000).contains(&early(&xfetch, 0)));
+
+        let policy = RefreshPolicy::new(Duration::from_nanos(500), Duration::from_nanos(1_000))
+            .with_grace(Duration::from_nanos(100));
+        assert_eq!(eager.freshness(&policy, 0), Freshness::Stale);
+        assert_eq!(eager.freshness(&policy, 600), Freshness::Stale);
+        assert_eq!(lazy.freshness(&policy, 0), Freshness::Fresh);
+        assert_eq!(lazy.freshness(&policy, 600), Freshness::Stale);
+        assert_eq!(eager.freshness(&policy, 1_050), Freshness::Grace);
+    }
+}
@@ -0,0 +1,427 @@
+//! Side State
+//!
+//! Besides its entries, every cache segment keeps the keys known to be
+//! absent, the tags of its entries, the total weight of its entries, its
+//! hot keys and the policy and clock its entries age under. [`SideState`]
+//! holds them, and [`HasSideState`] ties them to the segment's entries on
+//! top of a few primitives each segment provides, so negative caching, tag
+//! invalidation, weight limits, hot keys and refresh policies work the same
+//! in every algorithm.
+
+extern crate alloc;
+
+use crate::entry::{CacheEntry, EntryClock};
+use crate::key_map::KeyMap;
+use crate::list::ListEntry;
+use crate::metrics::{Clock, CoreCacheMetrics, HotKeys, MissRatioCurve, RollingWindow};
+use crate::negative::{Lookup, NegativeCache};
+use crate::refresh::{Freshness, RefreshPolicy, XFetch};
+use crate::tags::TagIndex;
+use crate::weight::{WeightLimits, WeightUsage, Weights};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::time::Duration;
+
+/// Negative entries, tags, weight usage, hot keys and refresh policy of one
+/// segment.
+pub(crate) struct SideState<K> {
+    /// Keys known to be absent, if enabled
+    negative: Option<NegativeCache<K>>,
+    /// Tags of the tagged entries
+    tags: TagIndex<K>,
+    /// Weight limits, and the total weight of the entries
+    weights: WeightUsage,
+    /// Ranks the keys of hits, if enabled
+    hot_keys: Option<HotKeys<K>>,
+    /// Ages at which entries go stale and expire, if set
+    refresh: Option<RefreshPolicy>,
+    /// Early expiration of entries under the refresh policy, if set
+    xfetch: Option<XFetch>,
+    /// Time source of the entries' timestamps
+    clock: EntryClock,
+}
+
+impl<K: Eq> SideState<K> {
+    pub(crate) fn new(weight_limits: Option<WeightLimits>) -> Self {
+        SideState {
+            negative: None,
+            tags: TagIndex::new(),
+            weights: WeightUsage::new(weight_limits),
+            hot_keys: None,
+            refresh: None,
+            xfetch: None,
+            clock: EntryClock::default(),
+        }
+    }
+
+    /// Returns the current time of the entries' clock in nanoseconds.
+    #[inline]
+    pub(crate) fn now_nanos(&self) -> u64 {
+        self.clock.now_nanos()
+    }
+
+    /// Ranks a hit on `key`, whose hash is `hash`, serving `size` bytes, if
+    /// hot keys are tracked.
+    #[inline]
+    pub(crate) fn record_hit(&mut self, hash: u64, key: &K, size: u64) {
+        if let Some(hot_keys) = &mut self.hot_keys {
+            hot_keys.record(hash, key, size);
+        }
+    }
+
+    /// Forgets the negative entry and tags of `key`, whose hash is `hash`,
+    /// as its value is replaced or removed.
+    pub(crate) fn forget<Q>(&mut self, hash: u64, key: &Q)
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Eq,
+    {
+        if let Some(negative) = &mut self.negative {
+            negative.remove(hash, key);
+        }
+        self.tags.remove(hash, key);
+    }
+
+    /// Drops the tags of `key`, whose hash is `hash`, as it is evicted.
+    pub(crate) fn untag<Q>(&mut self, hash: u64, key: &Q)
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Eq,
+    {
+        self.tags.remove(hash, key);
+    }
+
+    fn clear(&mut self) {
+        if let Some(negative) = &mut self.negative {
+            negative.clear();
+        }
+        self.tags.clear();
+        self.weights.clear();
+    }
+}
+
+/// A segment keeping a [`SideState`] beside its entries.
+///
+/// The provided methods are the segment's negative caching, tagging,
+/// weighing, hot-key and refresh operations.
+pub(crate) trait HasSideState {
+    type Key: Eq;
+    type Value;
+    /// Algorithm-specific metadata of the entries
+    type Meta;
+    type Hasher;
+
+    fn side(&self) -> &SideState<Self::Key>;
+
+    fn side_mut(&mut self) -> &mut SideState<Self::Key>;
+
+    /// Returns the map from keys to the segment's entries.
+    #[allow(clippy::type_complexity)]
+    fn entries(
+        &self,
+    ) -> &KeyMap<
+        Self::Key,
+        *mut ListEntry<CacheEntry<Self::Key, Self::Value, Self::Meta>>,
+        Self::Hasher,
+    >;
+
+    /// Records into the core metrics, if metrics are collected.
+    fn record_core(&mut self, record: impl FnOnce(&mut CoreCacheMetrics));
+
+    /// Inserts or updates an entry weighing `weights`, returning the entries
+    /// evicted to make room. Without weights, a replaced value's weights are
+    /// kept and a new entry weighs nothing.
+    fn insert_entry(
+        &mut self,
+        hash: u64,
+        key: Self::Key,
+        value: Self::Value,
+        size: u64,
+        weights: Option<Weights>,
+    ) -> Option<Vec<(Self::Key, Self::Value)>>
+    where
+        Self::Key: Clone,
+        Self::Value: Clone;
+
+    /// Removes an entry, returning its value if it was cached.
+    fn remove_entry<Q>(&mut self, hash: u64, key: &Q) -> Option<Self::Value>
+    where
+        Self::Key: Borrow<Q> + Clone,
+        Self::Value: Clone,
+        Q: ?Sized + Eq;
+
+    /// Evicts the entry the algorithm would evict next, counting an
+    /// eviction.
+    fn evict_entry(&mut self) -> Option<(Self::Key, Self::Value)>
+    where
+        Self::Key: Clone,
+        Self::Value: Clone;
+
+    fn set_metrics_window(&mut self, window: Option<RollingWindow>) {
+        self.record_core(|core| core.window = window);
+    }
+
+    fn set_miss_ratio_curve(&mut self, curve: Option<MissRatioCurve>) {
+        self.record_core(|core| core.miss_ratio_curve = curve);
+    }
+
+    fn record_lookup(&mut self, hash: u64) {
+        self.record_core(|core| core.record_lookup(hash));
+    }
+
+    fn set_hot_keys(&mut self, hot_keys: Option<HotKeys<Self::Key>>) {
+        self.side_mut().hot_keys = hot_keys;
+    }
+
+    fn hot_keys(&self) -> Option<&HotKeys<Self::Key>> {
+        self.side().hot_keys.as_ref()
+    }
+
+    fn set_refresh_policy(&mut self, policy: Option<RefreshPolicy>) {
+        self.side_mut().refresh = policy;
+    }
+
+    fn refresh_policy(&self) -> Option<RefreshPolicy> {
+        self.side().refresh
+    }
+
+    fn set_xfetch(&mut self, xfetch: Option<XFetch>) {
+        self.side_mut().xfetch = xfetch;
+    }
+
+    fn xfetch(&self) -> Option<&XFetch> {
+        self.side().xfetch.as_ref()
+    }
+
+    fn set_clock(&mut self, clock: Option<Arc<dyn Clock>>) {
+        self.side_mut().clock = EntryClock::new(clock);
+    }
+
+    fn clock(&self) -> Option<&Arc<dyn Clock>> {
+        self.side().clock.clock()
+    }
+
+    /// Returns the freshness of `key`, whose hash is `hash`, under the
+    /// refresh policy without recording an access, or `None` if it is not
+    /// cached.
+    fn peek_freshness_hashed<Q>(&self, hash: u64, key: &Q) -> Option<Freshness>
+    where
+        Self::Key: Borrow<Q>,
+        Q: ?Sized + Eq,
+    {
+        let &node = self.entries().get(hash, key)?;
+        let side = self.side();
+        let Some(policy) = &side.refresh else {
+            return Some(Freshness::Fresh);
+        };
+        // SAFETY: node comes from the segment's map, so it's a valid pointer
+        let age = unsafe { (*node).get_value().metadata.age_at(side.now_nanos()) };
+        Some(match &side.xfetch {
+            Some(xfetch) => xfetch.freshness(policy, age),
+            None => policy.freshness(age),
+        })
+    }
+
+    fn set_negative_cache(&mut self, negative: Option<NegativeCache<Self::Key>>) {
+        self.side_mut().negative = negative;
+    }
+
+    fn negative_cache(&self) -> Option<&NegativeCache<Self::Key>> {
+        self.side().negative.as_ref()
+    }
+
+    /// Inserts or updates an entry, tagging it with `tags` in place of any
+    /// previous tags.
+    fn put_tagged_hashed(
+        &mut self,
+        hash: u64,
+        key: Self::Key,
+        value: Self::Value,
+        size: u64,
+        tags: &[u64],
+    ) -> Option<Vec<(Self::Key, Self::Value)>>
+    where
+        Self::Key: Clone,
+        Self::Value: Clone,
+    {
+        if tags.is_empty() {
+            return self.insert_entry(hash, key, value, size, None);
+        }
+        let tagged = key.clone();
+        let evicted = self.insert_entry(hash, key, value, size, None);
+        if self.entries().contains_key(hash, &tagged) {
+            self.side_mut().tags.insert(hash, &tagged, tags);
+        }
+        evicted
+    }
+
+    /// Inserts or updates an entry, weighing it with `weights` in place of
+    /// any previous weights, after evicting entries until every dimension of
+    /// the weight limits fits.
+    fn put_weighted_hashed(
+        &mut self,
+        hash: u64,
+        key: Self::Key,
+        value: Self::Value,
+        size: u64,
+        weights: Weights,
+    ) -> Option<Vec<(Self::Key, Self::Value)>>
+    where
+        Self::Key: Clone,
+        Self::Value: Clone,
+    {
+        // The replaced value's weights make room too
+        if let Some(&node) = self.entries().get(hash, &key) {
+            // SAFETY: node comes from the segment's map, so it's a valid pointer
+            let replaced =
+                unsafe { core::mem::take(&mut (*node).get_value_mut().metadata.weights) };
+            self.release_weights(&replaced);
+        }
+        let mut evicted = Vec::new();
+        while let Some(dimension) = self.side().weights.exceeded(&weights) {
+            let Some(entry) = self.evict_entry() else {
+                break;
+            };
+            self.record_core(|core| core.count_weight_eviction(dimension));
+            evicted.push(entry);
+        }
+        evicted.extend(
+            self.insert_entry(hash, key, value, size, Some(weights))
+                .into_iter()
+                .flatten(),
+        );
+        if evicted.is_empty() {
+            None
+        } else {
+            Some(evicted)
+        }
+    }
+
+    /// Returns the weights of `key`, whose hash is `hash`.
+    fn weights_hashed<Q>(&self, hash: u64, key: &Q) -> Weights
+    where
+        Self::Key: Borrow<Q>,
+        Q: ?Sized + Eq,
+    {
+        self.entries()
+            .get(hash, key)
+            .map_or(Weights::ZERO, |&node| {
+                // SAFETY: node comes from the segment's map, so it's a valid pointer
+                unsafe { (*node).get_value().metadata.weights }
+            })
+    }
+
+    fn set_weight_limits(&mut self, limits: Option<WeightLimits>) {
+        self.side_mut().weights.set_limits(limits);
+        self.publish_weights();
+    }
+
+    fn weight_limits(&self) -> Option<&WeightLimits> {
+        self.side().weights.limits()
+    }
+
+    /// Counts an entry weighing `weights` towards the weight limits.
+    fn add_weights(&mut self, weights: &Weights) {
+        if !weights.is_zero() {
+            self.side_mut().weights.add(weights);
+            self.publish_weights();
+        }
+    }
+
+    /// Stops counting an entry weighing `weights`.
+    fn release_weights(&mut self, weights: &Weights) {
+        if !weights.is_zero() {
+            self.side_mut().weights.sub(weights);
+            self.publish_weights();
+        }
+    }
+
+    /// Reports the total weight of the entries in the metrics.
+    fn publish_weights(&mut self) {
+        let limits = self.side().weights.limits().copied();
+        let used = self.side().weights.used();
+        self.record_core(|core| core.record_weight_usage(limits.as_ref(), used));
+    }
+
+    /// Forgets every negative entry, tag and weight, as the segment is
+    /// cleared.
+    fn clear_side(&mut self) {
+        self.side_mut().clear();
+        self.publish_weights();
+    }
+
+    /// Returns the tags of `key`, whose hash is `hash`.
+    fn tags_hashed<Q>(&self, hash: u64, key: &Q) -> &[u64]
+    where
+        Self::Key: Borrow<Q>,
+        Q: ?Sized + Eq,
+    {
+        self.side().tags.get(hash, key)
+    }
+
+    /// Removes every entry tagged with `tag`, returning how many were
+    /// removed.
+    fn invalidate_tag(&mut self, tag: u64) -> usize
+    where
+        Self::Key: Clone,
+        Self::Value: Clone,
+    {
+        let keys = self.side().tags.keys(tag);
+        keys.iter()
+            .filter(|(hash, key)| self.remove_entry(*hash, key).is_some())
+            .count()
+    }
+
+    /// Records that `key` is absent for the next `ttl`, removing its cached
+    /// value. Does nothing without a negative cache.
+    fn put_absent_hashed(&mut self, hash: u64, key: Self::Key, ttl: Duration)
+    where
+        Self::Key: Clone,
+        Self::Value: Clone,
+    {
+        if self.side().negative.is_some() {
+            self.remove_entry(hash, &key);
+        }
+        if let Some(negative) = &mut self.side_mut().negative {
+            negative.insert(hash, key, ttl);
+        }
+    }
+
+    /// Returns `true` if `key`, which is not cached, has an unexpired
+    /// negative entry, counting a negative hit or miss if there is a
+    /// negative cache.
+    fn check_absent<Q>(&mut self, hash: u64, key: &Q) -> bool
+    where
+        Self::Key: Borrow<Q>,
+        Q: ?Sized + Eq,
+    {
+        let Some(negative) = &mut self.side_mut().negative else {
+            return false;
+        };
+        let absent = negative.check(hash, key);
+        self.record_core(|core| {
+            if absent {
+                core.record_negative_hit();
+            } else {
+                core.record_negative_miss();
+            }
+        });
+        absent
+    }
+
+    /// Answers a lookup of `key`, which is not cached, telling a key known
+    /// to be absent apart from one that is not cached.
+    fn lookup_uncached<Q, T>(&mut self, hash: u64, key: &Q) -> Lookup<T>
+    where
+        Self::Key: Borrow<Q>,
+        Q: ?Sized + Eq,
+    {
+        self.record_core(|core| core.record_lookup(hash));
+        if self.check_absent(hash, key) {
+            Lookup::Absent
+        } else {
+            Lookup::Miss
+        }
+    }
+}
@@ -130,6 +130,7 @@
 //!     capacity: NonZeroUsize::new(100).unwrap(),
 //!     protected_capacity: NonZeroUsize::new(20).unwrap(),
 //!     max_size: u64::MAX,
+//!     weight_limits: None,
 //! };
 //! let mut cache = SlruCache::init(config, None);
 //!
@@ -151,6 +152,7 @@
 //!     capacity: NonZeroUsize::new(10).unwrap(),
 //!     protected_capacity: NonZeroUsize::new(3).unwrap(),
 //!     max_size: u64::MAX,
+//!     weight_limits: None,
 //! };
 //! let mut cache: SlruCache<i32, i32> = SlruCache::init(config, None);
 //!
@@ -175,25 +177,31 @@ extern crate alloc;
 
 use crate::config::SlruCacheConfig;
 use crate::entry::CacheEntry;
+use crate::key_map::KeyMap;
 use crate::list::{List, ListEntry};
-use crate::metrics::{CacheMetrics, SlruCacheMetrics};
+use crate::metrics::{
+    CacheMetrics, Clock, CoreCacheMetrics, HotKeys, MetricsRecorder, MetricsSnapshot,
+    MissRatioCurve, NoMetrics, RollingWindow, SlruCacheMetrics,
+};
+use crate::negative::{Lookup, NegativeCache};
+use crate::refresh::{Freshness, RefreshPolicy, XFetch};
+use crate::side::{HasSideState, SideState};
+use crate::weight::{WeightLimits, Weights};
 use alloc::boxed::Box;
 use alloc::collections::BTreeMap;
 use alloc::string::String;
+use alloc::sync::Arc;
 use alloc::vec::Vec;
 use core::borrow::Borrow;
 use core::hash::{BuildHasher, Hash};
 use core::num::NonZeroUsize;
+use core::time::Duration;
 
 #[cfg(feature = "hashbrown")]
 use hashbrown::DefaultHashBuilder;
-#[cfg(feature = "hashbrown")]
-use hashbrown::HashMap;
 
 #[cfg(not(feature = "hashbrown"))]
 use std::collections::hash_map::RandomState as DefaultHashBuilder;
-#[cfg(not(feature = "hashbrown"))]
-use std::collections::HashMap;
 
 /// Entry location within the SLRU cache.
 ///
@@ -235,7 +243,7 @@ pub struct SlruMeta {
 /// - The pointer was obtained from `probationary.add()` or `protected.add()`
 /// - The node has not been removed from the list
 /// - The segment has not been dropped
-pub(crate) struct SlruInner<K, V, S = DefaultHashBuilder> {
+pub(crate) struct SlruInner<K, V, S = DefaultHashBuilder, M = SlruCacheMetrics> {
     /// Configuration for the SLRU cache
     config: SlruCacheConfig,
 
@@ -247,10 +255,12 @@ pub(crate) struct SlruInner<K, V, S = DefaultHashBuilder> {
 
     /// Maps keys to their list nodes. All metadata (size, timestamp, location)
     /// is stored in the CacheEntry itself, not in the map.
-    map: HashMap<K, *mut ListEntry<CacheEntry<K, V, SlruMeta>>, S>,
+    map: KeyMap<K, *mut ListEntry<CacheEntry<K, V, SlruMeta>>, S>,
 
     /// Metrics for tracking cache performance and segment behavior
-    metrics: SlruCacheMetrics,
+    metrics: M,
+    /// Negative entries, tags, weight usage, hot keys and refresh policy
+    side: SideState<K>,
 
     /// Current total size of cached content (sum of entry sizes)
     current_size: u64,
@@ -262,12 +272,19 @@ pub(crate) struct SlruInner<K, V, S = DefaultHashBuilder> {
 // SAFETY: SlruInner owns all data and raw pointers point only to nodes owned by
 // `probationary` or `protected` lists. Concurrent access is safe when wrapped in
 // proper synchronization primitives.
-unsafe impl<K: Send, V: Send, S: Send> Send for SlruInner<K, V, S> {}
+unsafe impl<K: Send, V: Send, S: Send, M: Send> Send for SlruInner<K, V, S, M> {}
 
-// SAFETY: All mutation requires &mut self; shared references cannot cause data races.
-unsafe impl<K: Send, V: Send, S: Sync> Sync for SlruInner<K, V, S> {}
+// SAFETY: All mutation requires &mut self; shared references only hand out
+// `&K`/`&V`, so sharing requires K and V to be Sync.
+unsafe impl<K: Sync, V: Sync, S: Sync, M: Sync> Sync for SlruInner<K, V, S, M> {}
 
-impl<K: Hash + Eq, V: Clone, S: BuildHasher> SlruInner<K, V, S> {
+impl<K, V, S, M> SlruInner<K, V, S, M>
+where
+    K: Hash + Eq,
+    V: Clone,
+    S: BuildHasher,
+    M: MetricsRecorder<SlruCacheMetrics>,
+{
     /// Creates a new SLRU segment from a configuration.
     ///
     /// This is the **recommended** way to create an SLRU segment. All configuration
@@ -279,6 +296,17 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> SlruInner<K, V, S> {
     /// * `hasher` - Hash builder for the internal HashMap
     #[allow(dead_code)] // Used by concurrent module when feature is enabled
     pub(crate) fn init(config: SlruCacheConfig, hasher: S) -> Self {
+        Self::init_with_capacity(config, hasher, config.capacity.get())
+    }
+
+    /// Like [`init()`](Self::init), but preallocates room for only
+    /// `map_capacity` entries.
+    #[allow(dead_code)] // Used by concurrent module when feature is enabled
+    pub(crate) fn init_with_capacity(
+        config: SlruCacheConfig,
+        hasher: S,
+        map_capacity: usize,
+    ) -> Self {
         let capacity = config.capacity.get();
         let protected = config.protected_capacity.get();
 
@@ -291,18 +319,21 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> SlruInner<K, V, S> {
 
         let probationary_max_size = NonZeroUsize::new(capacity - protected).unwrap();
 
-        SlruInner {
+        let mut segment = SlruInner {
             config,
             probationary: List::new(probationary_max_size),
             protected: List::new(config.protected_capacity),
-            map: HashMap::with_capacity_and_hasher(
-                config.capacity.get().next_power_of_two(),
-                hasher,
-            ),
-            metrics: SlruCacheMetrics::new(config.max_size, config.protected_capacity.get() as u64),
+            map: KeyMap::with_capacity_and_hasher(map_capacity.next_power_of_two(), hasher),
+            metrics: M::init(|| {
+                SlruCacheMetrics::new(config.max_size, config.protected_capacity.get() as u64)
+            }),
+            side: SideState::new(config.weight_limits),
             current_size: 0,
             max_size: config.max_size,
-        }
+        };
+        // Limits from the config show in the metrics before any entry
+        segment.publish_weights();
+        segment
     }
 
     /// Returns the maximum number of key-value pairs the segment can hold.
@@ -341,19 +372,21 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> SlruInner<K, V, S> {
         self.max_size
     }
 
-    /// Returns a reference to the metrics for this segment.
+    /// Returns the metrics for this segment, or `None` if it collects none.
     #[inline]
-    pub(crate) fn metrics(&self) -> &SlruCacheMetrics {
-        &self.metrics
+    pub(crate) fn metrics(&self) -> Option<&SlruCacheMetrics> {
+        self.metrics.collected()
     }
 
     /// Moves an entry from the probationary segment to the protected segment.
     /// If the protected segment is full, the LRU item from protected is demoted to probationary.
     ///
-    /// Returns a raw pointer to the entry in its new location.
+    /// Returns a raw pointer to the entry in its new location. `hash` is the
+    /// hash of the entry's key.
     unsafe fn promote_to_protected(
         &mut self,
         node: *mut ListEntry<CacheEntry<K, V, SlruMeta>>,
+        hash: u64,
     ) -> *mut ListEntry<CacheEntry<K, V, SlruMeta>> {
         // Remove from probationary list
         let boxed_entry = self
@@ -370,9 +403,17 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> SlruInner<K, V, S> {
                     let old_ptr = Box::into_raw(old_entry);
                     let cache_entry = (*old_ptr).get_value();
                     let evicted_size = cache_entry.metadata.size;
-                    self.map.remove(&cache_entry.key);
+                    let evicted_hash = cache_entry.metadata.hash;
+                    self.map.remove(evicted_hash, &cache_entry.key);
+                    self.side.untag(evicted_hash, &cache_entry.key);
+                    self.release_weights(&cache_entry.metadata.weights);
                     self.current_size = self.current_size.saturating_sub(evicted_size);
-                    self.metrics.record_probationary_eviction(evicted_size);
+                    self.metrics.record(|m| {
+                        m.record_probationary_eviction(evicted_size);
+                        let (age, idle) =
+                            cache_entry.metadata.age_and_idle_at(self.side.now_nanos());
+                        m.core.record_eviction_age(age, idle);
+                    });
                     let _ = Box::from_raw(old_ptr);
                 }
             }
@@ -382,13 +423,12 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> SlruInner<K, V, S> {
         // Get the raw pointer from the box
         let entry_ptr = Box::into_raw(boxed_entry);
 
-        // Update location and timestamp in the entry
+        // Update location in the entry
         let cache_entry = (*entry_ptr).get_value_mut();
         cache_entry.metadata.algorithm.location = Location::Protected;
-        cache_entry.touch(); // Update last_accessed timestamp
 
         // Update the map pointer
-        if let Some(node_ptr) = self.map.get_mut(&cache_entry.key) {
+        if let Some(node_ptr) = self.map.get_mut(hash, &cache_entry.key) {
             *node_ptr = entry_ptr;
         }
 
@@ -410,7 +450,8 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> SlruInner<K, V, S> {
             cache_entry.metadata.algorithm.location = Location::Probationary;
 
             // Update the map pointer
-            if let Some(node_ptr) = self.map.get_mut(&cache_entry.key) {
+            let hash = cache_entry.metadata.hash;
+            if let Some(node_ptr) = self.map.get_mut(hash, &cache_entry.key) {
                 *node_ptr = lru_ptr;
             }
 
@@ -418,7 +459,7 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> SlruInner<K, V, S> {
             self.probationary.attach_from_other_list(lru_ptr);
 
             // Record demotion
-            self.metrics.record_demotion();
+            self.metrics.record(|m| m.record_demotion());
         }
     }
 
@@ -428,39 +469,56 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> SlruInner<K, V, S> {
         K: Borrow<Q>,
         Q: ?Sized + Hash + Eq,
     {
-        let node = self.map.get(key).copied()?;
+        let hash = self.map.hash(key);
+        self.get_hashed(hash, key)
+    }
+
+    /// Like [`get()`](Self::get), for a key whose hash is already known.
+    pub(crate) fn get_hashed<Q>(&mut self, hash: u64, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Eq,
+    {
+        self.record_lookup(hash);
+        let node = self.map.get(hash, key).copied()?;
 
         unsafe {
             // SAFETY: node comes from our map, so it's a valid pointer
-            let cache_entry = (*node).get_value();
+            let cache_entry = (*node).get_value_mut();
+            let interval = cache_entry.touch_at(self.side.now_nanos());
+            self.side
+                .record_hit(hash, &cache_entry.key, cache_entry.metadata.size);
             let location = cache_entry.metadata.algorithm.location;
             let size = cache_entry.metadata.size;
+            self.metrics
+                .record(|m| m.core.record_access_interval(interval));
 
             match location {
                 Location::Probationary => {
-                    self.metrics.record_probationary_hit(size);
+                    self.metrics.record(|m| m.record_probationary_hit(size));
 
-                    // Promote from probationary to protected (updates timestamp and location)
-                    let entry_ptr = self.promote_to_protected(node);
+                    // Promote from probationary to protected
+                    let entry_ptr = self.promote_to_protected(node, hash);
 
                     // Record promotion
-                    self.metrics.record_promotion();
+                    self.metrics.record(|m| m.record_promotion());
 
                     // Update segment sizes
-                    self.metrics.update_segment_sizes(
-                        self.probationary.len() as u64,
-                        self.protected.len() as u64,
-                    );
+                    self.metrics.record(|m| {
+                        m.update_segment_sizes(
+                            self.probationary.len() as u64,
+                            self.protected.len() as u64,
+                        )
+                    });
 
                     // SAFETY: entry_ptr is the return value from promote_to_protected
                     Some(&(*entry_ptr).get_value().value)
                 }
                 Location::Protected => {
-                    self.metrics.record_protected_hit(size);
+                    self.metrics.record(|m| m.record_protected_hit(size));
 
-                    // Already protected, just move to MRU position and update timestamp
+                    // Already protected, just move to MRU position
                     self.protected.move_to_front(node);
-                    (*node).get_value_mut().touch();
                     Some(&(*node).get_value().value)
                 }
             }
@@ -473,39 +531,56 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> SlruInner<K, V, S> {
         K: Borrow<Q>,
         Q: ?Sized + Hash + Eq,
     {
-        let node = self.map.get(key).copied()?;
+        let hash = self.map.hash(key);
+        self.get_mut_hashed(hash, key)
+    }
+
+    /// Like [`get_mut()`](Self::get_mut), for a key whose hash is already known.
+    pub(crate) fn get_mut_hashed<Q>(&mut self, hash: u64, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Eq,
+    {
+        self.record_lookup(hash);
+        let node = self.map.get(hash, key).copied()?;
 
         unsafe {
             // SAFETY: node comes from our map, so it's a valid pointer
-            let cache_entry = (*node).get_value();
+            let cache_entry = (*node).get_value_mut();
+            let interval = cache_entry.touch_at(self.side.now_nanos());
+            self.side
+                .record_hit(hash, &cache_entry.key, cache_entry.metadata.size);
             let location = cache_entry.metadata.algorithm.location;
             let size = cache_entry.metadata.size;
+            self.metrics
+                .record(|m| m.core.record_access_interval(interval));
 
             match location {
                 Location::Probationary => {
-                    self.metrics.record_probationary_hit(size);
+                    self.metrics.record(|m| m.record_probationary_hit(size));
 
-                    // Promote from probationary to protected (updates timestamp and location)
-                    let entry_ptr = self.promote_to_protected(node);
+                    // Promote from probationary to protected
+                    let entry_ptr = self.promote_to_protected(node, hash);
 
                     // Record promotion
-                    self.metrics.record_promotion();
+                    self.metrics.record(|m| m.record_promotion());
 
                     // Update segment sizes
-                    self.metrics.update_segment_sizes(
-                        self.probationary.len() as u64,
-                        self.protected.len() as u64,
-                    );
+                    self.metrics.record(|m| {
+                        m.update_segment_sizes(
+                            self.probationary.len() as u64,
+                            self.protected.len() as u64,
+                        )
+                    });
 
                     // SAFETY: entry_ptr is the return value from promote_to_protected
                     Some(&mut (*entry_ptr).get_value_mut().value)
                 }
                 Location::Protected => {
-                    self.metrics.record_protected_hit(size);
+                    self.metrics.record(|m| m.record_protected_hit(size));
 
-                    // Already protected, just move to MRU position and update timestamp
+                    // Already protected, just move to MRU position
                     self.protected.move_to_front(node);
-                    (*node).get_value_mut().touch();
                     Some(&mut (*node).get_value_mut().value)
                 }
             }
@@ -515,11 +590,53 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> SlruInner<K, V, S> {
     /// Records a cache miss for metrics tracking
     #[inline]
     pub(crate) fn record_miss(&mut self, object_size: u64) {
-        self.metrics.core.record_miss(object_size);
+        self.metrics.record(|m| m.core.record_miss(object_size));
+    }
+
+    /// Like [`get_hashed()`](Self::get_hashed), also returning the value's
+    /// freshness. Expired values are removed and reported as missing.
+    pub(crate) fn get_fresh_hashed<Q>(&mut self, hash: u64, key: &Q) -> Option<(&V, Freshness)>
+    where
+        K: Borrow<Q> + Clone,
+        Q: ?Sized + Eq,
+    {
+        match self.peek_freshness_hashed(hash, key) {
+            Some(Freshness::Expired) => {
+                self.remove_hashed(hash, key);
+                self.record_lookup(hash);
+                None
+            }
+            freshness => {
+                let freshness = freshness.unwrap_or(Freshness::Fresh);
+                self.get_hashed(hash, key).map(|value| (value, freshness))
+            }
+        }
+    }
+
+    /// Like [`get_hashed()`](Self::get_hashed), telling a key known to be
+    /// absent apart from one that is not cached.
+    pub(crate) fn lookup_hashed<Q>(&mut self, hash: u64, key: &Q) -> Lookup<&V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Eq,
+    {
+        if self.map.contains_key(hash, key) {
+            return self.get_hashed(hash, key).map_or(Lookup::Miss, Lookup::Hit);
+        }
+        self.lookup_uncached(hash, key)
+    }
+
+    pub(crate) fn reset_metrics(&mut self) {
+        self.metrics.record(|m| m.reset());
     }
 }
 
-impl<K: Hash + Eq + Clone, V, S: BuildHasher> SlruInner<K, V, S> {
+impl<K, V, S, M> SlruInner<K, V, S, M>
+where
+    K: Hash + Eq + Clone,
+    S: BuildHasher,
+    M: MetricsRecorder<SlruCacheMetrics>,
+{
     /// Inserts a key-value pair into the segment.
     ///
     /// # Arguments
@@ -534,13 +651,65 @@ impl<K: Hash + Eq + Clone, V, S: BuildHasher> SlruInner<K, V, S> {
     where
         V: Clone,
     {
+        let hash = self.map.hash(&key);
+        self.put_hashed(hash, key, value, size)
+    }
+
+    /// Like [`put()`](Self::put), for a key whose hash is already known.
+    pub(crate) fn put_hashed(
+        &mut self,
+        hash: u64,
+        key: K,
+        value: V,
+        size: u64,
+    ) -> Option<Vec<(K, V)>>
+    where
+        V: Clone,
+    {
+        self.put_hashed_with(hash, key, value, size, |key, value, _| (key, value))
+    }
+
+    /// Like [`put_hashed()`](Self::put_hashed), returning each evicted entry
+    /// as `evicted_entry(key, value, size)`.
+    pub(crate) fn put_hashed_with<E>(
+        &mut self,
+        hash: u64,
+        key: K,
+        value: V,
+        size: u64,
+        evicted_entry: impl FnMut(K, V, u64) -> E,
+    ) -> Option<Vec<E>>
+    where
+        V: Clone,
+    {
+        self.put_weighted_hashed_with(hash, key, value, size, None, evicted_entry)
+    }
+
+    /// Like [`put_hashed_with()`](Self::put_hashed_with), for an entry
+    /// weighing `weights`. Without them, a replaced value's weights are kept
+    /// and a new entry weighs nothing.
+    fn put_weighted_hashed_with<E>(
+        &mut self,
+        hash: u64,
+        key: K,
+        value: V,
+        size: u64,
+        weights: Option<Weights>,
+        mut evicted_entry: impl FnMut(K, V, u64) -> E,
+    ) -> Option<Vec<E>>
+    where
+        V: Clone,
+    {
+        self.side.forget(hash, &key);
         // If key is already in the cache, update it in place
-        if let Some(&node) = self.map.get(&key) {
+        if let Some(&node) = self.map.get(hash, &key) {
             unsafe {
                 // SAFETY: node comes from our map
                 let cache_entry = (*node).get_value();
                 let location = cache_entry.metadata.algorithm.location;
                 let old_size = cache_entry.metadata.size;
+                let replaced = cache_entry.metadata.weights;
+                let weights = weights.unwrap_or(replaced);
 
                 match location {
                     Location::Probationary => {
@@ -553,13 +722,20 @@ impl<K: Hash + Eq + Clone, V, S: BuildHasher> SlruInner<K, V, S> {
                             SlruMeta {
                                 location: Location::Probationary,
                             },
-                        );
+                        )
+                        .with_hash(hash)
+                        .with_weights(weights)
+                        .with_time(self.side.now_nanos());
                         let old_entry = self.probationary.update(node, new_entry, true);
+                        self.release_weights(&replaced);
+                        self.add_weights(&weights);
                         // Update size tracking
                         self.current_size = self.current_size.saturating_sub(old_size);
                         self.current_size += size;
-                        self.metrics.core.record_size_change(old_size, size);
-                        self.metrics.core.bytes_written_to_cache += size;
+                        self.metrics.record(|m| {
+                            m.core.record_size_change(old_size, size);
+                            m.core.bytes_written_to_cache += size;
+                        });
                         // Replacement is not eviction - discard old entry
                         let _ = old_entry;
                         return None;
@@ -574,13 +750,20 @@ impl<K: Hash + Eq + Clone, V, S: BuildHasher> SlruInner<K, V, S> {
                             SlruMeta {
                                 location: Location::Protected,
                             },
-                        );
+                        )
+                        .with_hash(hash)
+                        .with_weights(weights)
+                        .with_time(self.side.now_nanos());
                         let old_entry = self.protected.update(node, new_entry, true);
+                        self.release_weights(&replaced);
+                        self.add_weights(&weights);
                         // Update size tracking
                         self.current_size = self.current_size.saturating_sub(old_size);
                         self.current_size += size;
-                        self.metrics.core.record_size_change(old_size, size);
-                        self.metrics.core.bytes_written_to_cache += size;
+                        self.metrics.record(|m| {
+                            m.core.record_size_change(old_size, size);
+                            m.core.bytes_written_to_cache += size;
+                        });
                         // Replacement is not eviction - discard old entry
                         let _ = old_entry;
                         return None;
@@ -589,6 +772,7 @@ impl<K: Hash + Eq + Clone, V, S: BuildHasher> SlruInner<K, V, S> {
             }
         }
 
+        let weights = weights.unwrap_or_default();
         let mut evicted = Vec::new();
 
         // Evict while entry count limit OR size limit would be exceeded
@@ -596,9 +780,9 @@ impl<K: Hash + Eq + Clone, V, S: BuildHasher> SlruInner<K, V, S> {
         while self.len() >= self.cap().get()
             || (self.current_size + size > self.config.max_size && !self.map.is_empty())
         {
-            if let Some(entry) = self.evict() {
-                self.metrics.core.evictions += 1;
-                evicted.push(entry);
+            if let Some((old_key, old_value, old_size)) = self.evict() {
+                self.metrics.record(|m| m.core.count_eviction());
+                evicted.push(evicted_entry(old_key, old_value, old_size));
             } else {
                 break;
             }
@@ -612,15 +796,20 @@ impl<K: Hash + Eq + Clone, V, S: BuildHasher> SlruInner<K, V, S> {
             SlruMeta {
                 location: Location::Probationary,
             },
-        );
+        )
+        .with_hash(hash)
+        .with_weights(weights)
+        .with_time(self.side.now_nanos());
         let node = self.probationary.add_unchecked(cache_entry);
-        self.map.insert(key, node);
+        self.map.insert(hash, key, node);
         self.current_size += size;
+        self.add_weights(&weights);
 
         // Record insertion and update segment sizes
-        self.metrics.core.record_insertion(size);
-        self.metrics
-            .update_segment_sizes(self.probationary.len() as u64, self.protected.len() as u64);
+        self.metrics.record(|m| {
+            m.core.record_insertion(size);
+            m.update_segment_sizes(self.probationary.len() as u64, self.protected.len() as u64);
+        });
 
         if evicted.is_empty() {
             None
@@ -635,7 +824,18 @@ impl<K: Hash + Eq + Clone, V, S: BuildHasher> SlruInner<K, V, S> {
         K: Borrow<Q>,
         Q: ?Sized + Hash + Eq,
     {
-        let node = self.map.remove(key)?;
+        let hash = self.map.hash(key);
+        self.remove_hashed(hash, key)
+    }
+
+    /// Like [`remove()`](Self::remove), for a key whose hash is already known.
+    pub(crate) fn remove_hashed<Q>(&mut self, hash: u64, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Eq,
+    {
+        self.side.forget(hash, key);
+        let node = self.map.remove(hash, key)?;
 
         unsafe {
             // SAFETY: node comes from our map
@@ -650,7 +850,9 @@ impl<K: Hash + Eq + Clone, V, S: BuildHasher> SlruInner<K, V, S> {
                     let entry_ptr = Box::into_raw(boxed_entry);
                     let cache_entry = (*entry_ptr).take_value();
                     self.current_size = self.current_size.saturating_sub(removed_size);
-                    self.metrics.record_probationary_removal(removed_size);
+                    self.release_weights(&cache_entry.metadata.weights);
+                    self.metrics
+                        .record(|m| m.record_probationary_removal(removed_size));
                     let _ = Box::from_raw(entry_ptr);
                     Some(cache_entry.value)
                 }
@@ -660,7 +862,9 @@ impl<K: Hash + Eq + Clone, V, S: BuildHasher> SlruInner<K, V, S> {
                     let entry_ptr = Box::into_raw(boxed_entry);
                     let cache_entry = (*entry_ptr).take_value();
                     self.current_size = self.current_size.saturating_sub(removed_size);
-                    self.metrics.record_protected_removal(removed_size);
+                    self.release_weights(&cache_entry.metadata.weights);
+                    self.metrics
+                        .record(|m| m.record_protected_removal(removed_size));
                     let _ = Box::from_raw(entry_ptr);
                     Some(cache_entry.value)
                 }
@@ -670,6 +874,7 @@ impl<K: Hash + Eq + Clone, V, S: BuildHasher> SlruInner<K, V, S> {
 
     /// Clears the segment, removing all key-value pairs.
     pub(crate) fn clear(&mut self) {
+        self.clear_side();
         self.map.clear();
         self.probationary.clear();
         self.protected.clear();
@@ -686,7 +891,7 @@ impl<K: Hash + Eq + Clone, V, S: BuildHasher> SlruInner<K, V, S> {
         K: Borrow<Q>,
         Q: ?Sized + Hash + Eq,
     {
-        self.map.contains_key(key)
+        self.map.contains_key(self.map.hash(key), key)
     }
 
     /// Returns a reference to the value without promoting or updating access metadata.
@@ -698,7 +903,7 @@ impl<K: Hash + Eq + Clone, V, S: BuildHasher> SlruInner<K, V, S> {
         K: Borrow<Q>,
         Q: ?Sized + Hash + Eq,
     {
-        let node = self.map.get(key)?;
+        let node = self.map.get(self.map.hash(key), key)?;
         unsafe {
             // SAFETY: node comes from our map, so it's a valid pointer
             let cache_entry = (**node).get_value();
@@ -706,6 +911,34 @@ impl<K: Hash + Eq + Clone, V, S: BuildHasher> SlruInner<K, V, S> {
         }
     }
 
+    /// Returns the stored key and value without updating access metadata.
+    ///
+    /// Used by the concurrent read path, which looks entries up under a shared
+    /// lock and needs an owned copy of the key to record the access.
+    #[allow(dead_code)] // Used by concurrent module when feature is enabled
+    pub(crate) fn peek_key_value_hashed<Q>(&self, hash: u64, key: &Q) -> Option<(&K, &V)>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Eq,
+    {
+        let node = self.map.get(hash, key)?;
+        unsafe {
+            // SAFETY: node comes from our map, so it's a valid pointer
+            let cache_entry = (**node).get_value();
+            Some((&cache_entry.key, &cache_entry.value))
+        }
+    }
+
+    /// Evicts the entry this segment's policy would evict next.
+    ///
+    /// Used by the concurrent caches to enforce a budget shared by all segments.
+    #[allow(dead_code)] // Used by concurrent module when feature is enabled
+    pub(crate) fn pop_victim(&mut self) -> Option<(K, V)> {
+        let (key, value, _) = self.evict()?;
+        self.metrics.record(|m| m.core.count_eviction());
+        Some((key, value))
+    }
+
     /// Removes and returns the eviction candidate.
     ///
     /// For SLRU, the eviction candidate is the LRU entry from the probationary
@@ -715,7 +948,9 @@ impl<K: Hash + Eq + Clone, V, S: BuildHasher> SlruInner<K, V, S> {
     /// This method does **not** increment the eviction counter in metrics.
     /// Eviction metrics are only recorded when the cache internally evicts
     /// entries to make room during `put()` operations.
-    fn evict(&mut self) -> Option<(K, V)> {
+    ///
+    /// Returns the evicted key, value and size.
+    fn evict(&mut self) -> Option<(K, V, u64)> {
         // Try probationary first (normal eviction target)
         if let Some(old_entry) = self.probationary.remove_last() {
             unsafe {
@@ -724,11 +959,18 @@ impl<K: Hash + Eq + Clone, V, S: BuildHasher> SlruInner<K, V, S> {
                 let entry_ptr = Box::into_raw(old_entry);
                 let cache_entry = (*entry_ptr).take_value();
                 let evicted_size = cache_entry.metadata.size;
-                self.map.remove(&cache_entry.key);
+                let hash = cache_entry.metadata.hash;
+                self.map.remove(hash, &cache_entry.key);
+                self.side.untag(hash, &cache_entry.key);
+                self.release_weights(&cache_entry.metadata.weights);
                 self.current_size = self.current_size.saturating_sub(evicted_size);
-                self.metrics.record_probationary_removal(evicted_size);
+                self.metrics.record(|m| {
+                    m.record_probationary_removal(evicted_size);
+                    let (age, idle) = cache_entry.metadata.age_and_idle_at(self.side.now_nanos());
+                    m.core.record_eviction_age(age, idle);
+                });
                 let _ = Box::from_raw(entry_ptr);
-                return Some((cache_entry.key, cache_entry.value));
+                return Some((cache_entry.key, cache_entry.value, evicted_size));
             }
         }
 
@@ -740,11 +982,18 @@ impl<K: Hash + Eq + Clone, V, S: BuildHasher> SlruInner<K, V, S> {
                 let entry_ptr = Box::into_raw(old_entry);
                 let cache_entry = (*entry_ptr).take_value();
                 let evicted_size = cache_entry.metadata.size;
-                self.map.remove(&cache_entry.key);
+                let hash = cache_entry.metadata.hash;
+                self.map.remove(hash, &cache_entry.key);
+                self.side.untag(hash, &cache_entry.key);
+                self.release_weights(&cache_entry.metadata.weights);
                 self.current_size = self.current_size.saturating_sub(evicted_size);
-                self.metrics.record_protected_removal(evicted_size);
+                self.metrics.record(|m| {
+                    m.record_protected_removal(evicted_size);
+                    let (age, idle) = cache_entry.metadata.age_and_idle_at(self.side.now_nanos());
+                    m.core.record_eviction_age(age, idle);
+                });
                 let _ = Box::from_raw(entry_ptr);
-                return Some((cache_entry.key, cache_entry.value));
+                return Some((cache_entry.key, cache_entry.value, evicted_size));
             }
         }
 
@@ -752,8 +1001,70 @@ impl<K: Hash + Eq + Clone, V, S: BuildHasher> SlruInner<K, V, S> {
     }
 }
 
+impl<K, V, S, M> HasSideState for SlruInner<K, V, S, M>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+    M: MetricsRecorder<SlruCacheMetrics>,
+{
+    type Key = K;
+    type Value = V;
+    type Meta = SlruMeta;
+    type Hasher = S;
+
+    fn side(&self) -> &SideState<K> {
+        &self.side
+    }
+
+    fn side_mut(&mut self) -> &mut SideState<K> {
+        &mut self.side
+    }
+
+    fn entries(&self) -> &KeyMap<K, *mut ListEntry<CacheEntry<K, V, SlruMeta>>, S> {
+        &self.map
+    }
+
+    fn record_core(&mut self, record: impl FnOnce(&mut CoreCacheMetrics)) {
+        self.metrics.record(|m| record(&mut m.core));
+    }
+
+    fn insert_entry(
+        &mut self,
+        hash: u64,
+        key: K,
+        value: V,
+        size: u64,
+        weights: Option<Weights>,
+    ) -> Option<Vec<(K, V)>>
+    where
+        K: Clone,
+        V: Clone,
+    {
+        self.put_weighted_hashed_with(hash, key, value, size, weights, |key, value, _| {
+            (key, value)
+        })
+    }
+
+    fn remove_entry<Q>(&mut self, hash: u64, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q> + Clone,
+        V: Clone,
+        Q: ?Sized + Eq,
+    {
+        self.remove_hashed(hash, key)
+    }
+
+    fn evict_entry(&mut self) -> Option<(K, V)>
+    where
+        K: Clone,
+        V: Clone,
+    {
+        self.pop_victim()
+    }
+}
+
 // Implement Debug for SlruInner manually since it contains raw pointers
-impl<K, V, S> core::fmt::Debug for SlruInner<K, V, S> {
+impl<K, V, S, M> core::fmt::Debug for SlruInner<K, V, S, M> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.debug_struct("SlruInner")
             .field("capacity", &self.config.capacity)
@@ -786,6 +1097,7 @@ impl<K, V, S> core::fmt::Debug for SlruInner<K, V, S> {
 ///     capacity: NonZeroUsize::new(4).unwrap(),
 ///     protected_capacity: NonZeroUsize::new(2).unwrap(),
 ///     max_size: u64::MAX,
+///     weight_limits: None,
 /// };
 /// let mut cache = SlruCache::init(config, None);
 ///
@@ -804,11 +1116,17 @@ impl<K, V, S> core::fmt::Debug for SlruInner<K, V, S> {
 /// assert_eq!(cache.get(&"b"), None);
 /// ```
 #[derive(Debug)]
-pub struct SlruCache<K, V, S = DefaultHashBuilder> {
-    segment: SlruInner<K, V, S>,
+pub struct SlruCache<K, V, S = DefaultHashBuilder, M = SlruCacheMetrics> {
+    segment: SlruInner<K, V, S, M>,
 }
 
-impl<K: Hash + Eq, V: Clone, S: BuildHasher> SlruCache<K, V, S> {
+impl<K, V, S, M> SlruCache<K, V, S, M>
+where
+    K: Hash + Eq,
+    V: Clone,
+    S: BuildHasher,
+    M: MetricsRecorder<SlruCacheMetrics>,
+{
     /// Returns the maximum number of key-value pairs the cache can hold.
     #[inline]
     pub fn cap(&self) -> NonZeroUsize {
@@ -884,9 +1202,251 @@ impl<K: Hash + Eq, V: Clone, S: BuildHasher> SlruCache<K, V, S> {
     pub fn record_miss(&mut self, object_size: u64) {
         self.segment.record_miss(object_size);
     }
+
+    /// Tracks the metrics of the most recent requests in `window`, replacing
+    /// any previous window, or stops tracking them with `None`.
+    ///
+    /// The window is reported as `window_*` keys by
+    /// [`metrics()`](CacheMetrics::metrics). Does nothing without metrics.
+    pub fn set_metrics_window(&mut self, window: Option<RollingWindow>) {
+        self.segment.set_metrics_window(window);
+    }
+
+    /// Estimates the miss ratio an LRU cache would have at other capacities
+    /// with `curve`, replacing any previous curve, or stops with `None`.
+    ///
+    /// Every [`get()`](Self::get) and [`get_mut()`](Self::get_mut) lookup is
+    /// sampled, hit or miss. The curve is reported as `mrc_*` keys by
+    /// [`metrics()`](CacheMetrics::metrics). Does nothing without metrics.
+    pub fn set_miss_ratio_curve(&mut self, curve: Option<MissRatioCurve>) {
+        self.segment.set_miss_ratio_curve(curve);
+    }
+
+    /// Ranks the keys of lookups that hit by accesses and by bytes served
+    /// with `hot_keys`, replacing any previous tracker, or stops with `None`.
+    ///
+    /// Works with and without metrics. Read the rankings through
+    /// [`hot_keys()`](Self::hot_keys).
+    pub fn set_hot_keys(&mut self, hot_keys: Option<HotKeys<K>>) {
+        self.segment.set_hot_keys(hot_keys);
+    }
+
+    /// Returns the hot-key tracker, if one is set.
+    pub fn hot_keys(&self) -> Option<&HotKeys<K>> {
+        self.segment.hot_keys()
+    }
+
+    /// Remembers keys known to be absent from the backing store in
+    /// `negative`, replacing any previous negative cache, or stops with
+    /// `None`.
+    ///
+    /// Works with and without metrics. Mark keys absent with
+    /// [`put_absent()`](Self::put_absent) and tell them apart from keys that
+    /// are not cached with [`lookup()`](Self::lookup).
+    pub fn set_negative_cache(&mut self, negative: Option<NegativeCache<K>>) {
+        self.segment.set_negative_cache(negative);
+    }
+
+    /// Returns the negative cache, if one is set.
+    pub fn negative_cache(&self) -> Option<&NegativeCache<K>> {
+        self.segment.negative_cache()
+    }
+
+    /// Ages entries from their creation under `policy`, replacing any
+    /// previous policy, or stops with `None`.
+    ///
+    /// The policy only affects [`get_fresh()`](Self::get_fresh); other
+    /// lookups serve entries regardless of their age.
+    pub fn set_refresh_policy(&mut self, policy: Option<RefreshPolicy>) {
+        self.segment.set_refresh_policy(policy);
+    }
+
+    /// Returns the refresh policy, if one is set.
+    pub fn refresh_policy(&self) -> Option<RefreshPolicy> {
+        self.segment.refresh_policy()
+    }
+
+    /// Expires values early at random under `xfetch`, replacing any
+    /// previous mode, or stops with `None`.
+    ///
+    /// Only applies while a refresh policy is set: fresh values are reported
+    /// as stale by [`get_fresh()`](Self::get_fresh) with a probability that
+    /// grows as the `ttl` nears, and stay cached.
+    pub fn set_xfetch(&mut self, xfetch: Option<XFetch>) {
+        self.segment.set_xfetch(xfetch);
+    }
+
+    /// Returns the early expiration mode, if one is set.
+    pub fn xfetch(&self) -> Option<&XFetch> {
+        self.segment.xfetch()
+    }
+
+    /// Stamps and ages entries with `clock`, or with the system time under
+    /// `None`, the default.
+    ///
+    /// Ages drive the refresh policy and the age and idle-time metrics.
+    /// Entries keep the timestamps they were given, so set the clock before
+    /// inserting any. Without the `std` feature there is no system time, and
+    /// entries only age under a clock.
+    pub fn set_clock(&mut self, clock: Option<Arc<dyn Clock>>) {
+        self.segment.set_clock(clock);
+    }
+
+    /// Returns the clock entries are stamped with, if one is set.
+    pub fn clock(&self) -> Option<&Arc<dyn Clock>> {
+        self.segment.clock()
+    }
+
+    /// Looks up `key` like [`get()`](Self::get), also returning the value's
+    /// [`Freshness`] under the refresh policy.
+    ///
+    /// Stale values and values in the grace window are returned for the
+    /// caller to refresh, e.g. with [`put()`](Self::put). Values past the
+    /// grace window are removed and reported as missing. Without a policy
+    /// every value is fresh.
+    pub fn get_fresh<Q>(&mut self, key: &Q) -> Option<(&V, Freshness)>
+    where
+        K: Borrow<Q> + Clone,
+        Q: ?Sized + Hash + Eq,
+    {
+        let hash = self.segment.map.hash(key);
+        self.segment.get_fresh_hashed(hash, key)
+    }
+
+    /// Inserts a key-value pair like [`put()`](Self::put), tagging it with
+    /// `tags`.
+    ///
+    /// The tags replace any previous tags of the key, and are dropped with
+    /// the entry when it is replaced by [`put()`](Self::put), removed or
+    /// evicted. See [`invalidate_tag()`](Self::invalidate_tag).
+    pub fn put_tagged(&mut self, key: K, value: V, size: u64, tags: &[u64]) -> Option<Vec<(K, V)>>
+    where
+        K: Clone,
+    {
+        let hash = self.segment.map.hash(&key);
+        self.segment.put_tagged_hashed(hash, key, value, size, tags)
+    }
+
+    /// Weighs entries in the dimensions of `limits`, replacing any previous
+    /// limits, or stops with `None`.
+    ///
+    /// Cached entries keep their weights, so they count against the new
+    /// limits. Limits can also be set up front with
+    /// `weight_limits` in the config. See
+    /// [`put_weighted()`](Self::put_weighted).
+    pub fn set_weight_limits(&mut self, limits: Option<WeightLimits>) {
+        self.segment.set_weight_limits(limits);
+    }
+
+    /// Returns the weight limits, if set.
+    pub fn weight_limits(&self) -> Option<&WeightLimits> {
+        self.segment.weight_limits()
+    }
+
+    /// Inserts a key-value pair like [`put()`](Self::put), weighing it with
+    /// `weights`.
+    ///
+    /// Entries are evicted in the cache's usual order until every dimension
+    /// of the [weight limits](Self::set_weight_limits) fits `weights`, then
+    /// the entry is inserted even if it exceeds a limit on its own. The
+    /// weights replace any previous weights of the key, are kept when
+    /// [`put()`](Self::put) replaces its value, and are dropped with the
+    /// entry when it is removed or evicted. Without weight limits the weights
+    /// are kept but evict nothing.
+    pub fn put_weighted(
+        &mut self,
+        key: K,
+        value: V,
+        size: u64,
+        weights: Weights,
+    ) -> Option<Vec<(K, V)>>
+    where
+        K: Clone,
+    {
+        let hash = self.segment.map.hash(&key);
+        self.segment
+            .put_weighted_hashed(hash, key, value, size, weights)
+    }
+
+    /// Returns the weights of `key`, which are zero if it is unweighted or
+    /// not cached.
+    pub fn weights<Q>(&self, key: &Q) -> Weights
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let hash = self.segment.map.hash(key);
+        self.segment.weights_hashed(hash, key)
+    }
+
+    /// Returns the tags of `key`, which are empty if it is untagged or not
+    /// cached.
+    pub fn tags<Q>(&self, key: &Q) -> &[u64]
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let hash = self.segment.map.hash(key);
+        self.segment.tags_hashed(hash, key)
+    }
+
+    /// Removes every entry tagged with `tag`, returning how many were
+    /// removed.
+    ///
+    /// Takes time proportional to the number of entries removed. Removals
+    /// are recorded like [`remove()`](Self::remove)'s.
+    pub fn invalidate_tag(&mut self, tag: u64) -> usize
+    where
+        K: Clone,
+    {
+        self.segment.invalidate_tag(tag)
+    }
+
+    /// Records that `key` is absent from the backing store for the next
+    /// `ttl`, removing any cached value for it.
+    ///
+    /// The entry counts against the negative cache's budget, not this
+    /// cache's, and is dropped by the next [`put()`](Self::put) of the key.
+    /// Does nothing without a negative cache.
+    pub fn put_absent(&mut self, key: K, ttl: Duration)
+    where
+        K: Clone,
+    {
+        let hash = self.segment.map.hash(&key);
+        self.segment.put_absent_hashed(hash, key, ttl);
+    }
+
+    /// Looks up `key` like [`get()`](Self::get), but tells a key known to be
+    /// absent apart from one that is not cached.
+    ///
+    /// With a negative cache, lookups answered by a negative entry count as
+    /// negative hits and lookups that find neither a value nor a negative
+    /// entry count as negative misses.
+    pub fn lookup<Q>(&mut self, key: &Q) -> Lookup<&V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let hash = self.segment.map.hash(key);
+        self.segment.lookup_hashed(hash, key)
+    }
+
+    /// Zeroes the metrics counters and clears the rolling window and the
+    /// miss-ratio curve.
+    ///
+    /// Gauges describing the cache's current contents, such as its size in
+    /// bytes, are kept.
+    pub fn reset_metrics(&mut self) {
+        self.segment.reset_metrics();
+    }
 }
 
-impl<K: Hash + Eq + Clone, V, S: BuildHasher> SlruCache<K, V, S> {
+impl<K, V, S, M> SlruCache<K, V, S, M>
+where
+    K: Hash + Eq + Clone,
+    S: BuildHasher,
+    M: MetricsRecorder<SlruCacheMetrics>,
+{
     /// Inserts a key-value pair into the cache.
     ///
     /// If the key already exists, it is replaced. If at capacity, the least recently
@@ -948,6 +1508,7 @@ impl<K: Hash + Eq + Clone, V, S: BuildHasher> SlruCache<K, V, S> {
     ///     capacity: NonZeroUsize::new(10).unwrap(),
     ///     protected_capacity: NonZeroUsize::new(3).unwrap(),
     ///     max_size: u64::MAX,
+    ///     weight_limits: None,
     /// };
     /// let mut cache = SlruCache::init(config, None);
     /// cache.put("a", 1, 1);
@@ -979,6 +1540,7 @@ impl<K: Hash + Eq + Clone, V, S: BuildHasher> SlruCache<K, V, S> {
     ///     capacity: NonZeroUsize::new(3).unwrap(),
     ///     protected_capacity: NonZeroUsize::new(1).unwrap(),
     ///     max_size: u64::MAX,
+    ///     weight_limits: None,
     /// };
     /// let mut cache = SlruCache::init(config, None);
     /// cache.put("a", 1, 1);
@@ -1003,7 +1565,7 @@ where
 {
     /// Creates a new SLRU cache from a configuration.
     ///
-    /// This is the **only** way to create an SLRU cache. All configuration
+    /// This is the **recommended** way to create an SLRU cache. All configuration
     /// is specified through the [`SlruCacheConfig`] struct.
     ///
     /// # Arguments
@@ -1023,6 +1585,7 @@ where
     ///     capacity: NonZeroUsize::new(100).unwrap(),
     ///     protected_capacity: NonZeroUsize::new(20).unwrap(),
     ///     max_size: u64::MAX,
+    ///     weight_limits: None,
     /// };
     /// let mut cache: SlruCache<&str, i32> = SlruCache::init(config, None);
     /// cache.put("key", 42, 1);
@@ -1032,6 +1595,7 @@ where
     ///     capacity: NonZeroUsize::new(1000).unwrap(),
     ///     protected_capacity: NonZeroUsize::new(200).unwrap(),
     ///     max_size: 10 * 1024 * 1024,  // 10MB
+    ///     weight_limits: None,
     /// };
     /// let cache: SlruCache<String, Vec<u8>> = SlruCache::init(config, None);
     /// ```
@@ -1043,15 +1607,346 @@ where
             segment: SlruInner::init(config, hasher.unwrap_or_default()),
         }
     }
+
+    /// Creates a new SLRU cache that collects no metrics.
+    ///
+    /// Behaves exactly like a cache created with [`init()`](Self::init), but
+    /// skips all metrics bookkeeping; [`metrics()`](CacheMetrics::metrics)
+    /// returns an empty map.
+    pub fn init_without_metrics(
+        config: SlruCacheConfig,
+        hasher: Option<DefaultHashBuilder>,
+    ) -> SlruCache<K, V, DefaultHashBuilder, NoMetrics> {
+        SlruCache {
+            segment: SlruInner::init(config, hasher.unwrap_or_default()),
+        }
+    }
 }
 
-impl<K: Hash + Eq, V: Clone, S: BuildHasher> CacheMetrics for SlruCache<K, V, S> {
+impl<K, V, S, M> CacheMetrics for SlruCache<K, V, S, M>
+where
+    K: Hash + Eq,
+    V: Clone,
+    S: BuildHasher,
+    M: MetricsRecorder<SlruCacheMetrics>,
+{
     fn metrics(&self) -> BTreeMap<String, f64> {
-        self.segment.metrics().metrics()
+        self.segment
+            .metrics()
+            .map_or_else(BTreeMap::new, CacheMetrics::metrics)
+    }
+
+    fn snapshot(&self) -> MetricsSnapshot {
+        self.segment
+            .metrics()
+            .map_or(MetricsSnapshot::Disabled, CacheMetrics::snapshot)
     }
 
     fn algorithm_name(&self) -> &'static str {
-        self.segment.metrics().algorithm_name()
+        "SLRU"
+    }
+}
+
+impl<K, V, S, M> crate::tiered::PrimaryCache for SlruCache<K, V, S, M>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+    S: BuildHasher,
+    M: MetricsRecorder<SlruCacheMetrics>,
+{
+    type Key = K;
+    type Value = V;
+
+    fn get(&mut self, key: &K) -> Option<&V> {
+        self.segment.get(key)
+    }
+
+    fn peek(&self, key: &K) -> Option<&V> {
+        self.segment.peek(key)
+    }
+
+    fn contains(&self, key: &K) -> bool {
+        self.segment.contains(key)
+    }
+
+    fn put(&mut self, key: K, value: V, size: u64) -> Option<Vec<(K, V, u64)>> {
+        let hash = self.segment.map.hash(&key);
+        self.segment
+            .put_hashed_with(hash, key, value, size, |key, value, size| {
+                (key, value, size)
+            })
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        self.segment.remove(key)
+    }
+
+    fn len(&self) -> usize {
+        self.segment.len()
+    }
+
+    fn clear(&mut self) {
+        self.segment.clear()
+    }
+
+    fn set_refresh_policy(&mut self, policy: Option<RefreshPolicy>) {
+        self.segment.set_refresh_policy(policy)
+    }
+
+    fn set_xfetch(&mut self, xfetch: Option<XFetch>) {
+        self.segment.set_xfetch(xfetch)
+    }
+
+    fn peek_freshness(&self, key: &K) -> Option<Freshness> {
+        let hash = self.segment.map.hash(key);
+        self.segment.peek_freshness_hashed(hash, key)
+    }
+
+    fn record_miss(&mut self, object_size: u64) {
+        self.segment.record_miss(object_size)
+    }
+}
+
+#[cfg(feature = "concurrent-core")]
+impl<K, V, S, M> crate::concurrent::Segment for SlruCache<K, V, S, M>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+    S: BuildHasher + Clone,
+    M: MetricsRecorder<SlruCacheMetrics>,
+{
+    type Key = K;
+
+    type Value = V;
+
+    type Hasher = S;
+
+    type Config = SlruCacheConfig;
+
+    const CONCURRENT_NAME: &'static str = "ConcurrentSLRU";
+
+    fn init(config: SlruCacheConfig, hasher: S) -> Self {
+        SlruCache {
+            segment: SlruInner::init(config, hasher),
+        }
+    }
+
+    fn init_shared(config: SlruCacheConfig, hasher: S, capacity: usize, segments: usize) -> Self {
+        // Weights aren't in the shared budget, so each segment weighs its share
+        let config = SlruCacheConfig {
+            weight_limits: config
+                .weight_limits
+                .map(|limits| limits.for_segment(segments)),
+            ..config
+        };
+        SlruCache {
+            segment: SlruInner::init_with_capacity(config, hasher, capacity),
+        }
+    }
+
+    fn split_config(config: SlruCacheConfig, segments: usize) -> SlruCacheConfig {
+        SlruCacheConfig {
+            capacity: NonZeroUsize::new((config.capacity.get() / segments).max(1)).unwrap(),
+            protected_capacity: NonZeroUsize::new(
+                (config.protected_capacity.get() / segments).max(1),
+            )
+            .unwrap(),
+            max_size: config.max_size / segments as u64,
+            weight_limits: config
+                .weight_limits
+                .map(|limits| limits.for_segment(segments)),
+        }
+    }
+
+    fn capacity(&self) -> usize {
+        self.segment.cap().get()
+    }
+
+    fn len(&self) -> usize {
+        self.segment.len()
+    }
+
+    fn current_size(&self) -> u64 {
+        self.segment.current_size()
+    }
+
+    fn max_size(&self) -> u64 {
+        self.segment.max_size()
+    }
+
+    fn get<Q>(&mut self, hash: u64, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.segment.get_hashed(hash, key)
+    }
+
+    fn get_mut<Q>(&mut self, hash: u64, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.segment.get_mut_hashed(hash, key)
+    }
+
+    fn peek_key_value<Q>(&self, hash: u64, key: &Q) -> Option<(&K, &V)>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.segment.peek_key_value_hashed(hash, key)
+    }
+
+    fn put(&mut self, hash: u64, key: K, value: V, size: u64) -> Option<Vec<(K, V)>> {
+        self.segment.put_hashed(hash, key, value, size)
+    }
+
+    fn remove<Q>(&mut self, hash: u64, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.segment.remove_hashed(hash, key)
+    }
+
+    fn clear(&mut self) {
+        self.segment.clear()
+    }
+
+    fn pop_victim(&mut self) -> Option<(K, V)> {
+        self.segment.pop_victim()
+    }
+
+    fn record_miss(&mut self, object_size: u64) {
+        self.segment.record_miss(object_size)
+    }
+
+    fn record_lookup(&mut self, hash: u64) {
+        self.segment.record_lookup(hash)
+    }
+
+    fn peek_freshness<Q>(&self, hash: u64, key: &Q) -> Option<Freshness>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.segment.peek_freshness_hashed(hash, key)
+    }
+
+    fn core_metrics(&self) -> &crate::metrics::CoreCacheMetrics {
+        self.segment
+            .metrics()
+            .map_or(&crate::metrics::NO_CORE_METRICS, |metrics| &metrics.core)
+    }
+}
+
+#[cfg(feature = "concurrent-core")]
+impl<K, V, S, M> crate::concurrent::SegmentFeatures for SlruCache<K, V, S, M>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+    S: BuildHasher + Clone,
+    M: MetricsRecorder<SlruCacheMetrics>,
+{
+    fn set_metrics_window(&mut self, window: Option<RollingWindow>) {
+        self.segment.set_metrics_window(window)
+    }
+
+    fn reset_metrics(&mut self) {
+        self.segment.reset_metrics()
+    }
+
+    fn set_miss_ratio_curve(&mut self, curve: Option<MissRatioCurve>) {
+        self.segment.set_miss_ratio_curve(curve)
+    }
+
+    fn set_hot_keys(&mut self, hot_keys: Option<HotKeys<K>>) {
+        self.segment.set_hot_keys(hot_keys)
+    }
+
+    fn hot_keys(&self) -> Option<&HotKeys<K>> {
+        self.segment.hot_keys()
+    }
+
+    fn set_negative_cache(&mut self, negative: Option<NegativeCache<K>>) {
+        self.segment.set_negative_cache(negative)
+    }
+
+    fn negative_cache(&self) -> Option<&NegativeCache<K>> {
+        self.segment.negative_cache()
+    }
+
+    fn put_absent(&mut self, hash: u64, key: K, ttl: Duration) {
+        self.segment.put_absent_hashed(hash, key, ttl)
+    }
+
+    fn check_absent<Q>(&mut self, hash: u64, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.segment.check_absent(hash, key)
+    }
+
+    fn put_tagged(
+        &mut self,
+        hash: u64,
+        key: K,
+        value: V,
+        size: u64,
+        tags: &[u64],
+    ) -> Option<Vec<(K, V)>> {
+        self.segment.put_tagged_hashed(hash, key, value, size, tags)
+    }
+
+    fn invalidate_tag(&mut self, tag: u64) -> usize {
+        self.segment.invalidate_tag(tag)
+    }
+
+    fn put_weighted(
+        &mut self,
+        hash: u64,
+        key: K,
+        value: V,
+        size: u64,
+        weights: Weights,
+    ) -> Option<Vec<(K, V)>> {
+        self.segment
+            .put_weighted_hashed(hash, key, value, size, weights)
+    }
+
+    fn set_weight_limits(&mut self, limits: Option<WeightLimits>) {
+        self.segment.set_weight_limits(limits)
+    }
+
+    fn weight_limits(&self) -> Option<WeightLimits> {
+        self.segment.weight_limits().copied()
+    }
+
+    fn put_with_cost(
+        &mut self,
+        hash: u64,
+        key: K,
+        value: V,
+        size: u64,
+        cost: u64,
+    ) -> Option<Vec<(K, V)>> {
+        // SLRU doesn't weigh entries by their miss cost
+        let _ = cost;
+        self.segment.put_hashed(hash, key, value, size)
+    }
+
+    fn set_refresh_policy(&mut self, policy: Option<RefreshPolicy>) {
+        self.segment.set_refresh_policy(policy)
+    }
+
+    fn set_xfetch(&mut self, xfetch: Option<XFetch>) {
+        self.segment.set_xfetch(xfetch)
+    }
+
+    fn set_clock(&mut self, clock: Option<Arc<dyn Clock>>) {
+        self.segment.set_clock(clock)
     }
 }
 
@@ -1074,6 +1969,7 @@ mod tests {
             capacity: NonZeroUsize::new(cap).unwrap(),
             protected_capacity: NonZeroUsize::new(protected_cap).unwrap(),
             max_size: u64::MAX,
+            weight_limits: None,
         };
         SlruCache::init(config, None)
     }
@@ -1265,6 +2161,7 @@ mod tests {
             capacity: NonZeroUsize::new(4).unwrap(),
             protected_capacity: NonZeroUsize::new(2).unwrap(),
             max_size: u64::MAX,
+            weight_limits: None,
         };
         let mut segment: SlruInner<&str, i32, DefaultHashBuilder> =
             SlruInner::init(config, DefaultHashBuilder::default());
@@ -1342,6 +2239,7 @@ mod tests {
             capacity: NonZeroUsize::new(1000).unwrap(),
             protected_capacity: NonZeroUsize::new(300).unwrap(),
             max_size: 1024 * 1024,
+            weight_limits: None,
         };
         let cache: SlruCache<String, i32> = SlruCache::init(config, None);
 
@@ -1355,6 +2253,7 @@ mod tests {
             capacity: NonZeroUsize::new(100).unwrap(),
             protected_capacity: NonZeroUsize::new(30).unwrap(),
             max_size: 1024 * 1024,
+            weight_limits: None,
         };
         let cache: SlruCache<String, String> = SlruCache::init(config, None);
 
@@ -1404,6 +2303,7 @@ mod tests {
             capacity: NonZeroUsize::new(cap).unwrap(),
             protected_capacity: NonZeroUsize::new(protected).unwrap(),
             max_size,
+            weight_limits: None,
         };
         SlruCache::init(config, None)
     }
@@ -1529,6 +2429,7 @@ mod tests {
             capacity: NonZeroUsize::new(10).unwrap(),
             protected_capacity: NonZeroUsize::new(3).unwrap(),
             max_size: 100,
+            weight_limits: None,
         };
         let mut cache = SlruCache::init(config, None);
         for i in 0..10 {
@@ -1562,4 +2463,29 @@ mod tests {
         // "a" was promoted by get(), so it should still exist
         assert!(cache.contains(&"a"));
     }
+
+    #[test]
+    fn test_no_metrics_matches_default() {
+        let config = SlruCacheConfig {
+            capacity: NonZeroUsize::new(4).unwrap(),
+            protected_capacity: NonZeroUsize::new(2).unwrap(),
+            max_size: u64::MAX,
+            weight_limits: None,
+        };
+        let mut counted = SlruCache::init(config, None);
+        let mut uncounted: SlruCache<u32, u32, _, NoMetrics> =
+            SlruCache::init_without_metrics(config, None);
+        for i in 0..64u32 {
+            let key = (i * 7) % 10;
+            assert_eq!(counted.get(&key), uncounted.get(&key));
+            assert_eq!(
+                counted.put(key, i, 1 + u64::from(key % 3)),
+                uncounted.put(key, i, 1 + u64::from(key % 3))
+            );
+        }
+        assert_eq!(counted.len(), uncounted.len());
+        assert!(!counted.metrics().is_empty());
+        assert!(uncounted.metrics().is_empty());
+        assert_eq!(uncounted.algorithm_name(), "SLRU");
+    }
 }
@@ -0,0 +1,160 @@
+//! Tag Index
+//!
+//! Entries cached with `put_tagged()` carry one or more `u64` tags, such as
+//! the id of the document they were derived from. `invalidate_tag()` then
+//! removes every entry carrying a tag without knowing their keys:
+//!
+//! ```
+//! use cache_rs::config::LruCacheConfig;
+//! use cache_rs::LruCache;
+//! use core::num::NonZeroUsize;
+//!
+//! let config = LruCacheConfig {
+//!     capacity: NonZeroUsize::new(100).unwrap(),
+//!     max_size: u64::MAX,
+//!     weight_limits: None,
+//! };
+//! let mut rendered: LruCache<&str, &str> = LruCache::init(config, None);
+//! rendered.put_tagged("doc42/html", "<p>..</p>", 1, &[42]);
+//! rendered.put_tagged("doc42/summary", "..", 1, &[42]);
+//! rendered.put_tagged("index", "42, 43", 1, &[42, 43]);
+//! rendered.put("about", "..", 1);
+//!
+//! assert_eq!(rendered.invalidate_tag(42), 3);
+//! assert_eq!(rendered.len(), 1);
+//! ```
+//!
+//! Each segment keeps an index from tags to the keys carrying them and back,
+//! updated whenever an entry is replaced, removed or evicted, so an
+//! invalidation takes time proportional to the number of entries it removes.
+//! Untagged entries cost nothing. Replacing a value with `put()` drops its
+//! tags.
+
+extern crate alloc;
+
+use crate::key_map::KeyMap;
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use hashbrown::HashMap;
+
+/// Tags of the entries of one segment, and the entries carrying each tag.
+///
+/// Keyed by the hashes of the segment's keys.
+pub(crate) struct TagIndex<K> {
+    /// Keys carrying each tag
+    by_tag: HashMap<u64, KeyMap<K, (), ()>>,
+    /// Tags of each tagged key
+    by_key: KeyMap<K, Vec<u64>, ()>,
+}
+
+impl<K: Eq> TagIndex<K> {
+    pub(crate) fn new() -> Self {
+        TagIndex {
+            by_tag: HashMap::new(),
+            by_key: KeyMap::with_capacity_and_hasher(0, ()),
+        }
+    }
+
+    /// Tags `key`, whose hash is `hash`, with `tags`, replacing its previous
+    /// tags.
+    pub(crate) fn insert(&mut self, hash: u64, key: &K, tags: &[u64])
+    where
+        K: Clone,
+    {
+        self.remove(hash, key);
+        if tags.is_empty() {
+            return;
+        }
+        let mut own = Vec::with_capacity(tags.len());
+        for &tag in tags {
+            if own.contains(&tag) {
+                continue;
+            }
+            own.push(tag);
+            self.by_tag
+                .entry(tag)
+                .or_insert_with(|| KeyMap::with_capacity_and_hasher(1, ()))
+                .insert(hash, key.clone(), ());
+        }
+        self.by_key.insert(hash, key.clone(), own);
+    }
+
+    /// Forgets the tags of `key`, whose hash is `hash`.
+    pub(crate) fn remove<Q>(&mut self, hash: u64, key: &Q)
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Eq,
+    {
+        if self.by_key.is_empty() {
+            return;
+        }
+        let Some(tags) = self.by_key.remove(hash, key) else {
+            return;
+        };
+        for tag in tags {
+            if let Some(keys) = self.by_tag.get_mut(&tag) {
+                keys.remove(hash, key);
+                if keys.is_empty() {
+                    self.by_tag.remove(&tag);
+                }
+            }
+        }
+    }
+
+    /// Returns the tags of `key`, whose hash is `hash`.
+    pub(crate) fn get<Q>(&self, hash: u64, key: &Q) -> &[u64]
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Eq,
+    {
+        self.by_key.get(hash, key).map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns the hashes and keys of the entries carrying `tag`.
+    pub(crate) fn keys(&self, tag: u64) -> Vec<(u64, K)>
+    where
+        K: Clone,
+    {
+        self.by_tag
+            .get(&tag)
+            .map(|keys| {
+                keys.iter()
+                    .map(|(hash, key, _)| (hash, key.clone()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.by_tag.clear();
+        self.by_key.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn test_index_follows_removals() {
+        let mut index = TagIndex::new();
+        index.insert(1, &"a", &[7, 8, 7]);
+        index.insert(2, &"b", &[8]);
+        assert_eq!(index.get(1, "a"), &[7, 8]);
+        assert_eq!(index.keys(8).len(), 2);
+        assert_eq!(index.by_tag.len(), 2);
+
+        // Retagging replaces the previous tags
+        index.insert(1, &"a", &[9]);
+        assert_eq!(index.keys(7), vec![]);
+        assert_eq!(index.keys(8), vec![(2, "b")]);
+
+        index.remove(2, "b");
+        assert_eq!(index.keys(8), vec![]);
+        assert_eq!(index.by_tag.len(), 1);
+        index.insert(1, &"a", &[]);
+        assert_eq!(index.by_tag.len(), 0);
+        assert_eq!(index.get(1, "a"), &[] as &[u64]);
+    }
+}
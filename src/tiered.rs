@@ -0,0 +1,595 @@
+//! Tiered Caches
+//!
+//! [`TieredCache`](crate::tiered::TieredCache) puts any of the in-memory
+//! caches in front of a larger, slower
+//! [`SecondaryStore`](crate::tiered::SecondaryStore). Entries the L1 cache
+//! evicts are demoted to the store instead of being dropped, and a lookup
+//! that misses L1 but finds the key in the store promotes the entry back to
+//! L1. The tiers are exclusive: an entry lives in exactly one of them.
+//!
+//! Two stores are included: [`MemoryStore`](crate::tiered::MemoryStore), a
+//! bounded in-memory store, and with the `std` feature `DirectoryStore`,
+//! which spills values to files in a directory.
+//!
+//! ```
+//! use cache_rs::LruCache;
+//! use cache_rs::config::LruCacheConfig;
+//! use cache_rs::tiered::{MemoryStore, TieredCache};
+//! use core::num::NonZeroUsize;
+//!
+//! let config = LruCacheConfig {
+//!     capacity: NonZeroUsize::new(2).unwrap(),
+//!     max_size: u64::MAX,
+//!     weight_limits: None,
+//! };
+//! let l1 = LruCache::init(config, None);
+//! let l2 = MemoryStore::new(NonZeroUsize::new(100).unwrap(), u64::MAX);
+//! let mut cache = TieredCache::new(l1, l2);
+//!
+//! cache.put("a", 1, 1);
+//! cache.put("b", 2, 1);
+//! cache.put("c", 3, 1); // Demotes "a" to L2
+//! assert!(!cache.l1().contains(&"a"));
+//!
+//! // Found in L2 and promoted back to L1, demoting "b"
+//! assert_eq!(cache.get(&"a"), Some(&1));
+//! assert!(cache.l1().contains(&"a"));
+//! assert_eq!(cache.tier_metrics().l2_hits, 1);
+//! assert_eq!(cache.tier_metrics().demotions, 2);
+//! ```
+
+extern crate alloc;
+
+use crate::config::LruCacheConfig;
+use crate::metrics::{CacheMetrics, MetricsSnapshot, NoMetrics, TieredCacheMetrics};
+use crate::refresh::{Freshness, RefreshPolicy, XFetch};
+use crate::LruCache;
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::hash::Hash;
+use core::num::NonZeroUsize;
+
+#[cfg(feature = "hashbrown")]
+use hashbrown::DefaultHashBuilder;
+
+#[cfg(not(feature = "hashbrown"))]
+use std::collections::hash_map::RandomState as DefaultHashBuilder;
+
+#[cfg(feature = "std")]
+pub use directory::DirectoryStore;
+
+/// Key, value and size of each entry evicted by [`PrimaryCache::put()`].
+pub type EvictedEntries<K, V> = Vec<(K, V, u64)>;
+
+/// A cache that can serve as the L1 tier of a [`TieredCache`].
+///
+/// Implemented by every cache in this crate. Unlike the caches' own `put()`,
+/// [`put()`](Self::put) returns the size of each evicted entry, so it can be
+/// demoted with its size intact.
+pub trait PrimaryCache {
+    /// Key type.
+    type Key;
+    /// Value type.
+    type Value;
+
+    /// Looks up a key, recording the access like the cache's own `get()`.
+    fn get(&mut self, key: &Self::Key) -> Option<&Self::Value>;
+
+    /// Returns the value without recording an access.
+    fn peek(&self, key: &Self::Key) -> Option<&Self::Value>;
+
+    /// Returns `true` if the key is cached, without recording an access.
+    fn contains(&self, key: &Self::Key) -> bool;
+
+    /// Inserts an entry, returning the key, value and size of every entry
+    /// evicted to make room.
+    fn put(
+        &mut self,
+        key: Self::Key,
+        value: Self::Value,
+        size: u64,
+    ) -> Option<EvictedEntries<Self::Key, Self::Value>>;
+
+    /// Removes a key, returning its value.
+    fn remove(&mut self, key: &Self::Key) -> Option<Self::Value>;
+
+    /// Returns the number of cached entries.
+    fn len(&self) -> usize;
+
+    /// Returns `true` if the cache holds no entries.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes all entries.
+    fn clear(&mut self);
+
+    /// Records a miss in the cache's metrics.
+    fn record_miss(&mut self, object_size: u64);
+
+    /// Ages entries under `policy`, or stops with `None`. Does nothing by
+    /// default.
+    fn set_refresh_policy(&mut self, policy: Option<RefreshPolicy>) {
+        let _ = policy;
+    }
+
+    /// Expires entries early at random under `xfetch`, or stops with
+    /// `None`. Does nothing by default.
+    fn set_xfetch(&mut self, xfetch: Option<XFetch>) {
+        let _ = xfetch;
+    }
+
+    /// Returns the key's freshness under the refresh policy without
+    /// recording an access, or `None` if it is not cached. Every cached
+    /// entry is fresh by default.
+    fn peek_freshness(&self, key: &Self::Key) -> Option<Freshness> {
+        self.contains(key).then_some(Freshness::Fresh)
+    }
+}
+
+/// Storage behind the L1 cache of a [`TieredCache`].
+///
+/// A store decides for itself how many entries it keeps; entries it drops
+/// to stay within its limits are simply lost. Errors, e.g. from I/O, are the
+/// store's to handle: a value that cannot be stored or read back is treated
+/// as absent.
+pub trait SecondaryStore<K, V> {
+    /// Stores an entry demoted from L1, replacing any entry with the same key.
+    fn put(&mut self, key: K, value: V, size: u64);
+
+    /// Removes an entry and returns its value and size, to promote it to L1.
+    fn take(&mut self, key: &K) -> Option<(V, u64)>;
+
+    /// Removes an entry without reading its value back.
+    ///
+    /// Returns `true` if the key was present.
+    fn remove(&mut self, key: &K) -> bool {
+        self.take(key).is_some()
+    }
+
+    /// Returns `true` if the store holds the key.
+    fn contains(&self, key: &K) -> bool;
+
+    /// Returns the number of stored entries.
+    fn len(&self) -> usize;
+
+    /// Returns `true` if the store holds no entries.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the total size of the stored entries.
+    fn current_size(&self) -> u64;
+
+    /// Removes all entries.
+    fn clear(&mut self);
+}
+
+/// An in-memory L1 cache in front of a [`SecondaryStore`].
+///
+/// See the [module documentation](self) for an example.
+#[derive(Debug)]
+pub struct TieredCache<C, S> {
+    l1: C,
+    l2: S,
+    metrics: TieredCacheMetrics,
+}
+
+impl<C, S> TieredCache<C, S>
+where
+    C: PrimaryCache,
+    C::Key: Clone,
+    S: SecondaryStore<C::Key, C::Value>,
+{
+    /// Creates a tiered cache from an L1 cache and an L2 store.
+    pub fn new(l1: C, l2: S) -> Self {
+        TieredCache {
+            l1,
+            l2,
+            metrics: TieredCacheMetrics::default(),
+        }
+    }
+
+    /// Looks up a key in L1, then in L2.
+    ///
+    /// An entry found in L2 is promoted to L1, which may demote other entries.
+    pub fn get(&mut self, key: &C::Key) -> Option<&C::Value> {
+        if self.l1.contains(key) {
+            self.metrics.l1_hits += 1;
+            return self.l1.get(key);
+        }
+        let Some((value, size)) = self.l2.take(key) else {
+            self.metrics.misses += 1;
+            return None;
+        };
+        self.metrics.l2_hits += 1;
+        self.put_l1(key.clone(), value, size);
+        self.l1.peek(key)
+    }
+
+    /// Inserts an entry into L1, demoting any entries it evicts to L2.
+    ///
+    /// A stale copy of the key in L2 is removed.
+    pub fn put(&mut self, key: C::Key, value: C::Value, size: u64) {
+        self.l2.remove(&key);
+        self.put_l1(key, value, size);
+    }
+
+    /// Removes a key from whichever tier holds it, returning its value.
+    pub fn remove(&mut self, key: &C::Key) -> Option<C::Value> {
+        self.l1
+            .remove(key)
+            .or_else(|| self.l2.take(key).map(|(value, _)| value))
+    }
+
+    /// Returns `true` if either tier holds the key, without promoting it.
+    pub fn contains(&self, key: &C::Key) -> bool {
+        self.l1.contains(key) || self.l2.contains(key)
+    }
+
+    /// Returns the number of entries in both tiers.
+    pub fn len(&self) -> usize {
+        self.l1.len() + self.l2.len()
+    }
+
+    /// Returns `true` if both tiers are empty.
+    pub fn is_empty(&self) -> bool {
+        self.l1.is_empty() && self.l2.is_empty()
+    }
+
+    /// Removes all entries from both tiers.
+    pub fn clear(&mut self) {
+        self.l1.clear();
+        self.l2.clear();
+    }
+
+    /// Records a miss in the L1 cache's metrics.
+    ///
+    /// Call this after fetching a value that [`get()`](Self::get) did not
+    /// find, as with the caches' own `record_miss()`.
+    pub fn record_miss(&mut self, object_size: u64) {
+        self.l1.record_miss(object_size);
+    }
+
+    /// Returns the L1 cache.
+    pub fn l1(&self) -> &C {
+        &self.l1
+    }
+
+    /// Returns the L1 cache mutably, e.g. to attach a metrics window.
+    ///
+    /// Entries it evicts through this reference are not demoted.
+    pub fn l1_mut(&mut self) -> &mut C {
+        &mut self.l1
+    }
+
+    /// Returns the L2 store.
+    pub fn l2(&self) -> &S {
+        &self.l2
+    }
+
+    /// Returns the per-tier metrics, with the L2 gauges read from the store.
+    pub fn tier_metrics(&self) -> TieredCacheMetrics {
+        TieredCacheMetrics {
+            l2_entries: self.l2.len() as u64,
+            l2_size_bytes: self.l2.current_size(),
+            ..self.metrics.clone()
+        }
+    }
+
+    /// Zeroes the per-tier counters. The L1 cache's metrics are kept.
+    pub fn reset_tier_metrics(&mut self) {
+        self.metrics.reset();
+    }
+
+    /// Inserts into L1 and demotes the evicted entries.
+    fn put_l1(&mut self, key: C::Key, value: C::Value, size: u64) {
+        if let Some(evicted) = self.l1.put(key, value, size) {
+            for (key, value, size) in evicted {
+                self.l2.put(key, value, size);
+                self.metrics.demotions += 1;
+            }
+        }
+    }
+}
+
+impl<C, S> CacheMetrics for TieredCache<C, S>
+where
+    C: PrimaryCache + CacheMetrics,
+    C::Key: Clone,
+    S: SecondaryStore<C::Key, C::Value>,
+{
+    /// Returns the L1 cache's metrics and the `tier_*` metrics.
+    fn metrics(&self) -> BTreeMap<String, f64> {
+        let mut metrics = self.l1.metrics();
+        metrics.extend(self.tier_metrics().to_btreemap());
+        metrics
+    }
+
+    /// Returns the L1 cache's snapshot; see [`tier_metrics()`](TieredCache::tier_metrics)
+    /// for the per-tier counters.
+    fn snapshot(&self) -> MetricsSnapshot {
+        self.l1.snapshot()
+    }
+
+    fn algorithm_name(&self) -> &'static str {
+        self.l1.algorithm_name()
+    }
+}
+
+/// A bounded in-memory [`SecondaryStore`].
+///
+/// Holds demoted entries in an LRU order and drops the least recently
+/// demoted ones beyond `capacity` entries or `max_size` in total size. Useful
+/// as a larger, cheaper second tier when L1 runs a policy such as LFU that
+/// would otherwise forget evicted entries entirely.
+#[derive(Debug)]
+pub struct MemoryStore<K, V> {
+    entries: LruCache<K, (V, u64), DefaultHashBuilder, NoMetrics>,
+}
+
+impl<K, V> MemoryStore<K, V>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+{
+    /// Creates a store holding at most `capacity` entries and `max_size` in
+    /// total size.
+    pub fn new(capacity: NonZeroUsize, max_size: u64) -> Self {
+        let config = LruCacheConfig {
+            capacity,
+            max_size,
+            weight_limits: None,
+        };
+        MemoryStore {
+            entries: LruCache::<K, (V, u64)>::init_without_metrics(config, None),
+        }
+    }
+}
+
+impl<K, V> SecondaryStore<K, V> for MemoryStore<K, V>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+{
+    fn put(&mut self, key: K, value: V, size: u64) {
+        self.entries.put(key, (value, size), size);
+    }
+
+    fn take(&mut self, key: &K) -> Option<(V, u64)> {
+        self.entries.remove(key)
+    }
+
+    fn contains(&self, key: &K) -> bool {
+        self.entries.contains(key)
+    }
+
+    fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    fn current_size(&self) -> u64 {
+        self.entries.current_size()
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+#[cfg(feature = "std")]
+mod directory {
+    extern crate alloc;
+    extern crate std;
+
+    use super::{DefaultHashBuilder, SecondaryStore};
+    use crate::config::LruCacheConfig;
+    use crate::metrics::NoMetrics;
+    use crate::LruCache;
+    use alloc::format;
+    use alloc::vec::Vec;
+    use core::hash::Hash;
+    use core::num::NonZeroUsize;
+    use core::sync::atomic::{AtomicU64, Ordering};
+    use std::fs;
+    use std::io;
+    use std::path::{Path, PathBuf};
+
+    /// Extension of the files a [`DirectoryStore`] writes.
+    const EXTENSION: &str = "entry";
+
+    /// Number of stores this process created, numbering their directories.
+    static STORES: AtomicU64 = AtomicU64::new(0);
+
+    /// Location and size of a stored value.
+    #[derive(Debug, Clone, Copy)]
+    struct StoredFile {
+        id: u64,
+        size: u64,
+    }
+
+    /// A [`SecondaryStore`] that writes each value to a file in a directory.
+    ///
+    /// Values are stored as their bytes (`V: AsRef<[u8]>`) and read back with
+    /// `V::from(Vec<u8>)`. The index of stored keys stays in memory, so each
+    /// store writes to a scratch subdirectory of its own, named after the
+    /// process and the store, and deletes it when dropped. Stores sharing a
+    /// directory never touch each other's files. Beyond `capacity` entries
+    /// or `max_size` bytes, the least recently demoted files are deleted.
+    ///
+    /// I/O errors are counted by [`io_errors()`](Self::io_errors) and treat
+    /// the entry as absent.
+    ///
+    /// ```
+    /// use cache_rs::LruCache;
+    /// use cache_rs::config::LruCacheConfig;
+    /// use cache_rs::tiered::{DirectoryStore, SecondaryStore, TieredCache};
+    /// use core::num::NonZeroUsize;
+    ///
+    /// let dir = std::env::temp_dir().join(format!("cache-rs-store-doc-{}", std::process::id()));
+    /// let l2 = DirectoryStore::new(&dir, NonZeroUsize::new(1000).unwrap(), 1 << 30).unwrap();
+    /// let config = LruCacheConfig {
+    ///     capacity: NonZeroUsize::new(1).unwrap(),
+    ///     max_size: u64::MAX,
+    ///     weight_limits: None,
+    /// };
+    /// let mut cache = TieredCache::new(LruCache::init(config, None), l2);
+    ///
+    /// cache.put("a".to_string(), b"first".to_vec(), 5);
+    /// cache.put("b".to_string(), b"second".to_vec(), 6); // "a" is written to disk
+    /// assert_eq!(cache.l2().len(), 1);
+    /// assert_eq!(cache.get(&"a".to_string()), Some(&b"first".to_vec()));
+    ///
+    /// drop(cache);
+    /// std::fs::remove_dir(&dir).unwrap();
+    /// ```
+    #[derive(Debug)]
+    pub struct DirectoryStore<K, V> {
+        dir: PathBuf,
+        index: LruCache<K, StoredFile, DefaultHashBuilder, NoMetrics>,
+        next_id: u64,
+        io_errors: u64,
+        _values: core::marker::PhantomData<fn() -> V>,
+    }
+
+    impl<K, V> DirectoryStore<K, V>
+    where
+        K: Hash + Eq + Clone,
+        V: AsRef<[u8]> + From<Vec<u8>>,
+    {
+        /// Creates a store in a new subdirectory of `dir`, creating `dir` if
+        /// needed.
+        ///
+        /// The store holds at most `capacity` entries and `max_size` bytes,
+        /// counted as the sizes passed with each entry.
+        pub fn new(
+            dir: impl Into<PathBuf>,
+            capacity: NonZeroUsize,
+            max_size: u64,
+        ) -> io::Result<Self> {
+            let store = STORES.fetch_add(1, Ordering::Relaxed);
+            let dir = dir
+                .into()
+                .join(format!("store-{}-{store}", std::process::id()));
+            // Left by an earlier process with the same id, so no longer in use
+            if dir.exists() {
+                fs::remove_dir_all(&dir)?;
+            }
+            fs::create_dir_all(&dir)?;
+            let config = LruCacheConfig {
+                capacity,
+                max_size,
+                weight_limits: None,
+            };
+            Ok(DirectoryStore {
+                dir,
+                index: LruCache::<K, StoredFile>::init_without_metrics(config, None),
+                next_id: 0,
+                io_errors: 0,
+                _values: core::marker::PhantomData,
+            })
+        }
+
+        /// Returns the store's own subdirectory, holding the files.
+        pub fn dir(&self) -> &Path {
+            &self.dir
+        }
+
+        /// Returns the number of failed reads, writes and deletions.
+        pub fn io_errors(&self) -> u64 {
+            self.io_errors
+        }
+
+        fn path(&self, id: u64) -> PathBuf {
+            self.dir.join(format!("{id:016x}.{EXTENSION}"))
+        }
+
+        fn delete(&mut self, file: StoredFile) {
+            if fs::remove_file(self.path(file.id)).is_err() {
+                self.io_errors += 1;
+            }
+        }
+    }
+
+    impl<K, V> SecondaryStore<K, V> for DirectoryStore<K, V>
+    where
+        K: Hash + Eq + Clone,
+        V: AsRef<[u8]> + From<Vec<u8>>,
+    {
+        fn put(&mut self, key: K, value: V, size: u64) {
+            if let Some(old) = self.index.remove(&key) {
+                self.delete(old);
+            }
+            let id = self.next_id;
+            self.next_id += 1;
+            if fs::write(self.path(id), value.as_ref()).is_err() {
+                self.io_errors += 1;
+                return;
+            }
+            if let Some(evicted) = self.index.put(key, StoredFile { id, size }, size) {
+                for (_, file) in evicted {
+                    self.delete(file);
+                }
+            }
+        }
+
+        fn take(&mut self, key: &K) -> Option<(V, u64)> {
+            let file = self.index.remove(key)?;
+            let path = self.path(file.id);
+            let bytes = fs::read(&path);
+            let removed = fs::remove_file(&path);
+            if bytes.is_err() || removed.is_err() {
+                self.io_errors += 1;
+            }
+            Some((V::from(bytes.ok()?), file.size))
+        }
+
+        fn remove(&mut self, key: &K) -> bool {
+            let Some(file) = self.index.remove(key) else {
+                return false;
+            };
+            self.delete(file);
+            true
+        }
+
+        fn contains(&self, key: &K) -> bool {
+            self.index.contains(key)
+        }
+
+        fn len(&self) -> usize {
+            self.index.len()
+        }
+
+        fn current_size(&self) -> u64 {
+            self.index.current_size()
+        }
+
+        fn clear(&mut self) {
+            self.index.clear();
+            if remove_entry_files(&self.dir).is_err() {
+                self.io_errors += 1;
+            }
+        }
+    }
+
+    impl<K, V> Drop for DirectoryStore<K, V> {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.dir);
+        }
+    }
+
+    /// Deletes the entry files in `dir`, leaving any other files alone.
+    fn remove_entry_files(dir: &Path) -> io::Result<()> {
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path
+                .extension()
+                .is_some_and(|extension| extension == EXTENSION)
+            {
+                fs::remove_file(path)?;
+            }
+        }
+        Ok(())
+    }
+}
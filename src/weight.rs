@@ -0,0 +1,264 @@
+//! Multi-Dimensional Weights
+//!
+//! Besides `capacity` (entries) and `max_size` (bytes), a cache can bound
+//! other per-entry costs, such as open file handles or GPU upload cost.
+//! [`WeightLimits`](crate::weight::WeightLimits) names up to
+//! [`MAX_DIMENSIONS`](crate::weight::MAX_DIMENSIONS) dimensions with a limit
+//! each. With limits in the cache's config (or attached later with
+//! `set_weight_limits()`), `put_weighted(key, value, size, weights)` evicts
+//! entries until every dimension fits the new entry's
+//! [`Weights`](crate::weight::Weights):
+//!
+//! ```
+//! use cache_rs::config::LruCacheConfig;
+//! use cache_rs::metrics::CacheMetrics;
+//! use cache_rs::weight::{WeightLimits, Weights};
+//! use cache_rs::LruCache;
+//! use core::num::NonZeroUsize;
+//!
+//! let config = LruCacheConfig {
+//!     capacity: NonZeroUsize::new(100).unwrap(),
+//!     max_size: u64::MAX,
+//!     weight_limits: Some(
+//!         WeightLimits::new()
+//!             .with_dimension("handles", 2)
+//!             .with_dimension("upload", 1000),
+//!     ),
+//! };
+//! let mut files: LruCache<&str, &str> = LruCache::init(config, None);
+//!
+//! files.put_weighted("a.log", "..", 1, Weights::of(&[1, 100]));
+//! files.put_weighted("b.bin", "..", 1, Weights::of(&[0, 800]));
+//! files.put_weighted("c.log", "..", 1, Weights::of(&[1, 100]));
+//!
+//! // A third handle does not fit, so the least recently used entry goes
+//! let evicted = files.put_weighted("d.log", "..", 1, Weights::of(&[1, 50]));
+//! assert_eq!(evicted, Some(vec![("a.log", "..")]));
+//! assert_eq!(files.weights(&"d.log"), Weights::of(&[1, 50]));
+//!
+//! let metrics = files.metrics();
+//! assert_eq!(metrics.get("weight_handles_used"), Some(&2.0));
+//! assert_eq!(metrics.get("weight_handles_evictions"), Some(&1.0));
+//! assert_eq!(metrics.get("weight_upload_used"), Some(&950.0));
+//! ```
+//!
+//! Each entry keeps its weights in its
+//! [`CacheMetadata`](crate::entry::CacheMetadata), so they count against
+//! limits set after it was cached. Entries cached with `put()` weigh
+//! nothing, and replacing a value with `put()` keeps its weights. Like
+//! `max_size`, the limits make room for a new entry but never reject it, so
+//! an entry heavier than a limit on its own is still cached, alone. Lowering
+//! the limits takes effect on the next weighted insertion.
+
+/// Most dimensions a [`WeightLimits`] can name.
+pub const MAX_DIMENSIONS: usize = 4;
+
+/// Weights of one entry, one per dimension of the cache's [`WeightLimits`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Weights([u64; MAX_DIMENSIONS]);
+
+impl Weights {
+    /// Weighs nothing in every dimension.
+    pub const ZERO: Self = Weights([0; MAX_DIMENSIONS]);
+
+    /// Creates weights from one value per dimension.
+    pub const fn new(weights: [u64; MAX_DIMENSIONS]) -> Self {
+        Weights(weights)
+    }
+
+    /// Creates weights from the leading dimensions, the others weighing
+    /// nothing.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `weights` has more than [`MAX_DIMENSIONS`] values.
+    pub fn of(weights: &[u64]) -> Self {
+        assert!(
+            weights.len() <= MAX_DIMENSIONS,
+            "more than {MAX_DIMENSIONS} weight dimensions"
+        );
+        let mut all = [0; MAX_DIMENSIONS];
+        all[..weights.len()].copy_from_slice(weights);
+        Weights(all)
+    }
+
+    /// Returns the weight in `dimension`, or 0 past [`MAX_DIMENSIONS`].
+    pub fn get(&self, dimension: usize) -> u64 {
+        self.0.get(dimension).copied().unwrap_or(0)
+    }
+
+    /// Returns the weights of all dimensions.
+    pub fn as_array(&self) -> [u64; MAX_DIMENSIONS] {
+        self.0
+    }
+
+    /// Returns `true` if every dimension weighs nothing.
+    pub fn is_zero(&self) -> bool {
+        *self == Self::ZERO
+    }
+}
+
+impl From<[u64; MAX_DIMENSIONS]> for Weights {
+    fn from(weights: [u64; MAX_DIMENSIONS]) -> Self {
+        Weights(weights)
+    }
+}
+
+/// Named dimensions of entry weights, each with a limit on the total weight
+/// of a cache's entries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WeightLimits {
+    names: [&'static str; MAX_DIMENSIONS],
+    limits: [u64; MAX_DIMENSIONS],
+    dimensions: usize,
+}
+
+impl WeightLimits {
+    /// Creates limits without dimensions.
+    pub const fn new() -> Self {
+        WeightLimits {
+            names: [""; MAX_DIMENSIONS],
+            limits: [u64::MAX; MAX_DIMENSIONS],
+            dimensions: 0,
+        }
+    }
+
+    /// Adds the next dimension, named `name` in metrics, whose entries may
+    /// weigh `limit` in total.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are already [`MAX_DIMENSIONS`] dimensions.
+    pub fn with_dimension(mut self, name: &'static str, limit: u64) -> Self {
+        assert!(
+            self.dimensions < MAX_DIMENSIONS,
+            "more than {MAX_DIMENSIONS} weight dimensions"
+        );
+        self.names[self.dimensions] = name;
+        self.limits[self.dimensions] = limit;
+        self.dimensions += 1;
+        self
+    }
+
+    /// Returns the number of dimensions.
+    pub fn dimensions(&self) -> usize {
+        self.dimensions
+    }
+
+    /// Returns the names of the dimensions.
+    pub fn names(&self) -> &[&'static str] {
+        &self.names[..self.dimensions]
+    }
+
+    /// Returns the limits of the dimensions.
+    pub fn limits(&self) -> &[u64] {
+        &self.limits[..self.dimensions]
+    }
+
+    /// Returns these limits divided evenly among `segments` segments.
+    #[allow(dead_code)] // Used by concurrent module when feature is enabled
+    pub(crate) fn for_segment(&self, segments: usize) -> Self {
+        let mut limits = *self;
+        for limit in &mut limits.limits[..self.dimensions] {
+            *limit = limit.div_ceil(segments as u64);
+        }
+        limits
+    }
+}
+
+impl Default for WeightLimits {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Weight limits of one segment, and the total weight of its entries.
+///
+/// The weights themselves live in the entries' metadata.
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct WeightUsage {
+    limits: Option<WeightLimits>,
+    /// Total weight of the entries
+    used: [u64; MAX_DIMENSIONS],
+}
+
+impl WeightUsage {
+    pub(crate) fn new(limits: Option<WeightLimits>) -> Self {
+        WeightUsage {
+            limits,
+            used: [0; MAX_DIMENSIONS],
+        }
+    }
+
+    pub(crate) fn limits(&self) -> Option<&WeightLimits> {
+        self.limits.as_ref()
+    }
+
+    /// Replaces the limits, keeping the totals.
+    pub(crate) fn set_limits(&mut self, limits: Option<WeightLimits>) {
+        self.limits = limits;
+    }
+
+    /// Returns the total weight of the entries.
+    pub(crate) fn used(&self) -> Weights {
+        Weights(self.used)
+    }
+
+    /// Counts an entry weighing `weights`.
+    pub(crate) fn add(&mut self, weights: &Weights) {
+        for (used, weight) in self.used.iter_mut().zip(weights.0) {
+            *used = used.saturating_add(weight);
+        }
+    }
+
+    /// Stops counting an entry weighing `weights`.
+    pub(crate) fn sub(&mut self, weights: &Weights) {
+        for (used, weight) in self.used.iter_mut().zip(weights.0) {
+            *used = used.saturating_sub(weight);
+        }
+    }
+
+    /// Returns the first dimension whose limit adding `weights` would
+    /// exceed, or `None` without limits.
+    pub(crate) fn exceeded(&self, weights: &Weights) -> Option<usize> {
+        let limits = self.limits.as_ref()?;
+        (0..limits.dimensions)
+            .find(|&d| self.used[d].saturating_add(weights.0[d]) > limits.limits[d])
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.used = [0; MAX_DIMENSIONS];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_usage_tracks_totals() {
+        let limits = WeightLimits::new()
+            .with_dimension("handles", 2)
+            .with_dimension("upload", 100);
+        let mut usage = WeightUsage::new(Some(limits));
+        usage.add(&Weights::of(&[1, 60]));
+        usage.add(&Weights::of(&[1, 30]));
+        assert_eq!(usage.used(), Weights::of(&[2, 90]));
+        assert_eq!(usage.exceeded(&Weights::of(&[0, 10])), None);
+        assert_eq!(usage.exceeded(&Weights::of(&[1, 0])), Some(0));
+        assert_eq!(usage.exceeded(&Weights::of(&[0, 11])), Some(1));
+        usage.sub(&Weights::of(&[1, 60]));
+        assert_eq!(usage.used(), Weights::of(&[1, 30]));
+
+        // Without limits the totals are kept but never exceeded
+        usage.set_limits(None);
+        assert_eq!(usage.exceeded(&Weights::of(&[u64::MAX, 0])), None);
+        assert_eq!(usage.used(), Weights::of(&[1, 30]));
+        usage.clear();
+        assert_eq!(usage.used(), Weights::ZERO);
+
+        let halves = limits.for_segment(2);
+        assert_eq!(halves.limits(), &[1, 50]);
+        assert_eq!(halves.names(), &["handles", "upload"]);
+    }
+}
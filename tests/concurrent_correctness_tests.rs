@@ -18,18 +18,32 @@
 
 #![cfg(feature = "concurrent")]
 
+use cache_rs::concurrent::{
+    Segment, SegmentFeatures, SegmentSkew, ShardedCache, TenantCache, TenantMetrics, TenantQuota,
+};
 use cache_rs::config::{
     ConcurrentCacheConfig, ConcurrentGdsfCacheConfig, ConcurrentLfuCacheConfig,
     ConcurrentLfudaCacheConfig, ConcurrentLruCacheConfig, ConcurrentSlruCacheConfig,
-    GdsfCacheConfig, LfuCacheConfig, LfudaCacheConfig, LruCacheConfig, SlruCacheConfig,
+    GdsfCacheConfig, LfuCacheConfig, LfudaCacheConfig, LruCacheConfig, SizedConfig,
+    SlruCacheConfig,
+};
+use cache_rs::loader::{CacheWriter, ConcurrentLoadingCache, WriteMode};
+use cache_rs::metrics::{
+    CacheMetrics, Clock, CoreCacheMetrics, HotKeys, MetricsSnapshot, MissRatioCurve, RollingWindow,
 };
-use cache_rs::metrics::CacheMetrics;
+use cache_rs::negative::{Lookup, NegativeCache};
+use cache_rs::refresh::{Freshness, RefreshPolicy, SplitMix64, XFetch};
+use cache_rs::weight::{WeightLimits, Weights};
 use cache_rs::{
     ConcurrentGdsfCache, ConcurrentLfuCache, ConcurrentLfudaCache, ConcurrentLruCache,
-    ConcurrentSlruCache,
+    ConcurrentSlruCache, GdsfCache, LfuCache, LfudaCache, LruCache, SlruCache,
 };
+use std::borrow::Borrow;
+use std::collections::hash_map::RandomState;
+use std::collections::{BTreeMap, VecDeque};
+use std::hash::{BuildHasher, Hash};
 use std::num::NonZeroUsize;
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::thread;
 
@@ -43,8 +57,10 @@ fn lru_config(capacity: usize, segments: usize) -> ConcurrentLruCacheConfig {
         base: LruCacheConfig {
             capacity: NonZeroUsize::new(capacity).unwrap(),
             max_size: u64::MAX,
+            weight_limits: None,
         },
         segments,
+        shared_budget: false,
     }
 }
 
@@ -54,8 +70,10 @@ fn slru_config(capacity: usize, protected: usize, segments: usize) -> Concurrent
             capacity: NonZeroUsize::new(capacity).unwrap(),
             protected_capacity: NonZeroUsize::new(protected).unwrap(),
             max_size: u64::MAX,
+            weight_limits: None,
         },
         segments,
+        shared_budget: false,
     }
 }
 
@@ -64,8 +82,10 @@ fn lfu_config(capacity: usize, segments: usize) -> ConcurrentLfuCacheConfig {
         base: LfuCacheConfig {
             capacity: NonZeroUsize::new(capacity).unwrap(),
             max_size: u64::MAX,
+            weight_limits: None,
         },
         segments,
+        shared_budget: false,
     }
 }
 
@@ -75,8 +95,10 @@ fn lfuda_config(capacity: usize, segments: usize) -> ConcurrentLfudaCacheConfig
             capacity: NonZeroUsize::new(capacity).unwrap(),
             initial_age: 0,
             max_size: u64::MAX,
+            weight_limits: None,
         },
         segments,
+        shared_budget: false,
     }
 }
 
@@ -86,8 +108,10 @@ fn gdsf_config(capacity: usize, segments: usize) -> ConcurrentGdsfCacheConfig {
             capacity: NonZeroUsize::new(capacity).unwrap(),
             initial_age: 0.0,
             max_size: u64::MAX,
+            weight_limits: None,
         },
         segments,
+        shared_budget: false,
     }
 }
 
@@ -100,8 +124,10 @@ fn lru_config_with_size(
         base: LruCacheConfig {
             capacity: NonZeroUsize::new(capacity).unwrap(),
             max_size,
+            weight_limits: None,
         },
         segments,
+        shared_budget: false,
     }
 }
 
@@ -114,8 +140,10 @@ fn lfu_config_with_size(
         base: LfuCacheConfig {
             capacity: NonZeroUsize::new(capacity).unwrap(),
             max_size,
+            weight_limits: None,
         },
         segments,
+        shared_budget: false,
     }
 }
 
@@ -129,8 +157,10 @@ fn lfuda_config_with_size(
             capacity: NonZeroUsize::new(capacity).unwrap(),
             initial_age: 0,
             max_size,
+            weight_limits: None,
         },
         segments,
+        shared_budget: false,
     }
 }
 
@@ -144,8 +174,10 @@ fn gdsf_config_with_size(
             capacity: NonZeroUsize::new(capacity).unwrap(),
             initial_age: 0.0,
             max_size,
+            weight_limits: None,
         },
         segments,
+        shared_budget: false,
     }
 }
 
@@ -160,8 +192,10 @@ fn slru_config_with_size(
             capacity: NonZeroUsize::new(capacity).unwrap(),
             protected_capacity: NonZeroUsize::new(protected).unwrap(),
             max_size,
+            weight_limits: None,
         },
         segments,
+        shared_budget: false,
     }
 }
 
@@ -2383,7 +2417,7 @@ fn test_concurrent_clear_during_operations() {
 }
 
 // ----------------------------------------------------------------------------
-// RECORD_MISS COVERAGE (only ConcurrentLruCache has record_miss)
+// RECORD_MISS COVERAGE
 // ----------------------------------------------------------------------------
 
 #[test]
@@ -2581,7 +2615,7 @@ fn test_concurrent_gdsf_get_with() {
 }
 
 // ============================================================================
-// GET_MUT_WITH COVERAGE (ConcurrentLruCache only)
+// GET_MUT_WITH COVERAGE
 // ============================================================================
 
 #[test]
@@ -2618,6 +2652,591 @@ fn test_concurrent_lru_get_mut_with() {
     assert!(cache.len() <= cache.capacity());
 }
 
+// ============================================================================
+// ATOMIC COMPUTE / CONDITIONAL OPERATIONS
+// ============================================================================
+
+#[test]
+fn test_concurrent_compute_loses_no_updates() {
+    // Every thread increments the same few counters; with a read-modify-write
+    // under one lock the totals must be exact.
+    const COUNTERS: i32 = 4;
+
+    let lru: Arc<ConcurrentLruCache<i32, usize>> =
+        Arc::new(ConcurrentLruCache::init(lru_config(100, 4), None));
+    let lfu: Arc<ConcurrentLfuCache<i32, usize>> =
+        Arc::new(ConcurrentLfuCache::init(lfu_config(100, 4), None));
+    let lfuda: Arc<ConcurrentLfudaCache<i32, usize>> =
+        Arc::new(ConcurrentLfudaCache::init(lfuda_config(100, 4), None));
+    let slru: Arc<ConcurrentSlruCache<i32, usize>> =
+        Arc::new(ConcurrentSlruCache::init(slru_config(100, 30, 4), None));
+    let gdsf: Arc<ConcurrentGdsfCache<i32, usize>> =
+        Arc::new(ConcurrentGdsfCache::init(gdsf_config(100, 4), None));
+
+    let mut handles = vec![];
+
+    for _ in 0..NUM_THREADS {
+        let lru_c = Arc::clone(&lru);
+        let lfu_c = Arc::clone(&lfu);
+        let lfuda_c = Arc::clone(&lfuda);
+        let slru_c = Arc::clone(&slru);
+        let gdsf_c = Arc::clone(&gdsf);
+        handles.push(thread::spawn(move || {
+            let increment = |count: Option<&usize>| Some((count.map_or(1, |c| c + 1), 1));
+            for i in 0..OPS_PER_THREAD {
+                let key = i as i32 % COUNTERS;
+                lru_c.compute(key, increment);
+                lfu_c.compute(key, increment);
+                lfuda_c.compute(key, increment);
+                slru_c.compute(key, increment);
+                gdsf_c.compute(key, increment);
+            }
+        }));
+    }
+
+    for handle in handles {
+        handle.join().expect("Thread panicked");
+    }
+
+    let expected = NUM_THREADS * OPS_PER_THREAD / COUNTERS as usize;
+    for key in 0..COUNTERS {
+        assert_eq!(lru.peek(&key), Some(expected), "LRU counter {key}");
+        assert_eq!(lfu.peek(&key), Some(expected), "LFU counter {key}");
+        assert_eq!(lfuda.peek(&key), Some(expected), "LFUDA counter {key}");
+        assert_eq!(slru.peek(&key), Some(expected), "SLRU counter {key}");
+        assert_eq!(gdsf.peek(&key), Some(expected), "GDSF counter {key}");
+    }
+}
+
+#[test]
+fn test_concurrent_put_if_absent_single_winner() {
+    let cache: Arc<ConcurrentLruCache<i32, usize>> =
+        Arc::new(ConcurrentLruCache::init(lru_config(10000, 4), None));
+    let winners = Arc::new(AtomicUsize::new(0));
+
+    let mut handles = vec![];
+
+    for t in 0..NUM_THREADS {
+        let c = Arc::clone(&cache);
+        let w = Arc::clone(&winners);
+        handles.push(thread::spawn(move || {
+            for i in 0..OPS_PER_THREAD {
+                if c.put_if_absent(i as i32, t, 1).is_ok() {
+                    w.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        }));
+    }
+
+    for handle in handles {
+        handle.join().expect("Thread panicked");
+    }
+
+    // Exactly one thread inserted each key
+    assert_eq!(winners.load(Ordering::Relaxed), OPS_PER_THREAD);
+    assert_eq!(cache.len(), OPS_PER_THREAD);
+}
+
+#[test]
+fn test_concurrent_replace_if_compare_and_swap() {
+    // Threads race to advance a version number with compare-and-swap; every
+    // successful swap must be unique, so the final version equals the count.
+    let cache: Arc<ConcurrentLruCache<&'static str, usize>> =
+        Arc::new(ConcurrentLruCache::init(lru_config(10, 2), None));
+    cache.put("version", 0, 1);
+    let swaps = Arc::new(AtomicUsize::new(0));
+
+    let mut handles = vec![];
+
+    for _ in 0..NUM_THREADS {
+        let c = Arc::clone(&cache);
+        let s = Arc::clone(&swaps);
+        handles.push(thread::spawn(move || {
+            for _ in 0..OPS_PER_THREAD {
+                let current = c.peek("version").unwrap();
+                if c.replace_if("version", |v| *v == current, current + 1, 1)
+                    .is_ok()
+                {
+                    s.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        }));
+    }
+
+    for handle in handles {
+        handle.join().expect("Thread panicked");
+    }
+
+    assert_eq!(cache.peek("version"), Some(swaps.load(Ordering::Relaxed)));
+}
+
+// ============================================================================
+// SHARED BUDGET ACROSS SEGMENTS
+// ============================================================================
+
+#[test]
+fn test_all_concurrent_caches_shared_budget_under_concurrency() {
+    // With a shared budget the cache as a whole, not each segment, is bounded
+    const CAPACITY: usize = 64;
+    const MAX_SIZE: u64 = 4096;
+
+    let mut lru_cfg = lru_config_with_size(CAPACITY, MAX_SIZE, 8);
+    lru_cfg.shared_budget = true;
+    let mut lfu_cfg = lfu_config_with_size(CAPACITY, MAX_SIZE, 8);
+    lfu_cfg.shared_budget = true;
+    let mut lfuda_cfg = lfuda_config_with_size(CAPACITY, MAX_SIZE, 8);
+    lfuda_cfg.shared_budget = true;
+    let mut slru_cfg = slru_config_with_size(CAPACITY, CAPACITY / 4, MAX_SIZE, 8);
+    slru_cfg.shared_budget = true;
+    let mut gdsf_cfg = gdsf_config_with_size(CAPACITY, MAX_SIZE, 8);
+    gdsf_cfg.shared_budget = true;
+
+    let lru: Arc<ConcurrentLruCache<i32, i32>> = Arc::new(ConcurrentLruCache::init(lru_cfg, None));
+    let lfu: Arc<ConcurrentLfuCache<i32, i32>> = Arc::new(ConcurrentLfuCache::init(lfu_cfg, None));
+    let lfuda: Arc<ConcurrentLfudaCache<i32, i32>> =
+        Arc::new(ConcurrentLfudaCache::init(lfuda_cfg, None));
+    let slru: Arc<ConcurrentSlruCache<i32, i32>> =
+        Arc::new(ConcurrentSlruCache::init(slru_cfg, None));
+    let gdsf: Arc<ConcurrentGdsfCache<i32, i32>> =
+        Arc::new(ConcurrentGdsfCache::init(gdsf_cfg, None));
+
+    let mut handles = vec![];
+
+    for t in 0..NUM_THREADS {
+        let lru_c = Arc::clone(&lru);
+        let lfu_c = Arc::clone(&lfu);
+        let lfuda_c = Arc::clone(&lfuda);
+        let slru_c = Arc::clone(&slru);
+        let gdsf_c = Arc::clone(&gdsf);
+        handles.push(thread::spawn(move || {
+            for i in 0..OPS_PER_THREAD {
+                let key = (t * OPS_PER_THREAD + i) as i32;
+                let size = 1 + (key as u64 % 128);
+                lru_c.put(key, key, size);
+                lfu_c.put(key, key, size);
+                lfuda_c.put(key, key, size);
+                slru_c.put(key, key, size);
+                gdsf_c.put(key, key, size);
+            }
+        }));
+    }
+
+    for handle in handles {
+        handle.join().expect("Thread panicked");
+    }
+
+    assert_eq!(lru.capacity(), CAPACITY);
+    assert_eq!(lru.max_size(), MAX_SIZE);
+    assert!(lru.len() <= CAPACITY && lru.current_size() <= MAX_SIZE);
+    assert!(lfu.len() <= CAPACITY && lfu.current_size() <= MAX_SIZE);
+    assert!(lfuda.len() <= CAPACITY && lfuda.current_size() <= MAX_SIZE);
+    assert!(slru.len() <= CAPACITY && slru.current_size() <= MAX_SIZE);
+    assert!(gdsf.len() <= CAPACITY && gdsf.current_size() <= MAX_SIZE);
+}
+
+// ============================================================================
+// PER-SEGMENT METRICS
+// ============================================================================
+
+#[test]
+fn test_all_concurrent_caches_segment_metrics_sum_to_totals() {
+    let lru: Arc<ConcurrentLruCache<i32, i32>> =
+        Arc::new(ConcurrentLruCache::init(lru_config(10000, 8), None));
+    let lfu: Arc<ConcurrentLfuCache<i32, i32>> =
+        Arc::new(ConcurrentLfuCache::init(lfu_config(10000, 8), None));
+    let lfuda: Arc<ConcurrentLfudaCache<i32, i32>> =
+        Arc::new(ConcurrentLfudaCache::init(lfuda_config(10000, 8), None));
+    let slru: Arc<ConcurrentSlruCache<i32, i32>> =
+        Arc::new(ConcurrentSlruCache::init(slru_config(10000, 3000, 8), None));
+    let gdsf: Arc<ConcurrentGdsfCache<i32, i32>> =
+        Arc::new(ConcurrentGdsfCache::init(gdsf_config(10000, 8), None));
+
+    let mut handles = vec![];
+
+    for t in 0..NUM_THREADS {
+        let lru_c = Arc::clone(&lru);
+        let lfu_c = Arc::clone(&lfu);
+        let lfuda_c = Arc::clone(&lfuda);
+        let slru_c = Arc::clone(&slru);
+        let gdsf_c = Arc::clone(&gdsf);
+        handles.push(thread::spawn(move || {
+            for i in 0..OPS_PER_THREAD {
+                let key = (t * OPS_PER_THREAD + i) as i32;
+                lru_c.put(key, key, 1);
+                lfu_c.put(key, key, 1);
+                lfuda_c.put(key, key, 1);
+                slru_c.put(key, key, 1);
+                gdsf_c.put(key, key, 1);
+            }
+        }));
+    }
+
+    for handle in handles {
+        handle.join().expect("Thread panicked");
+    }
+
+    let writes = (NUM_THREADS * OPS_PER_THREAD) as u64;
+    for (name, segments, len) in [
+        ("LRU", lru.segment_metrics(), lru.len()),
+        ("LFU", lfu.segment_metrics(), lfu.len()),
+        ("LFUDA", lfuda.segment_metrics(), lfuda.len()),
+        ("SLRU", slru.segment_metrics(), slru.len()),
+        ("GDSF", gdsf.segment_metrics(), gdsf.len()),
+    ] {
+        assert_eq!(segments.len(), 8, "{name} segment count");
+        assert_eq!(
+            segments.iter().map(|s| s.len).sum::<usize>(),
+            len,
+            "{name} len"
+        );
+        // Every put took its segment's exclusive lock
+        let acquisitions: u64 = segments.iter().map(|s| s.lock.acquisitions).sum();
+        assert!(acquisitions >= writes, "{name} lock acquisitions");
+
+        let skew = SegmentSkew::from_segments(&segments);
+        assert!(skew.entries.max_min_ratio >= 1.0, "{name} entry skew");
+    }
+}
+
+// ============================================================================
+// USER-DEFINED SEGMENT POLICIES
+// ============================================================================
+
+/// Entry limit of a [`FifoSegment`], which has no size limit.
+#[derive(Clone, Copy)]
+struct FifoConfig {
+    capacity: usize,
+    /// Counts the entries the segments preallocate room for, if set
+    reserved: Option<&'static AtomicUsize>,
+}
+
+impl SizedConfig for FifoConfig {
+    fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    fn max_size(&self) -> u64 {
+        u64::MAX
+    }
+
+    fn set_max_size(&mut self, _max_size: u64) {}
+}
+
+/// Minimal FIFO policy: evicts in insertion order and ignores accesses.
+struct FifoSegment<K, V> {
+    capacity: usize,
+    entries: VecDeque<(K, V, u64)>,
+    metrics: CoreCacheMetrics,
+}
+
+impl<K, V> CacheMetrics for FifoSegment<K, V> {
+    fn metrics(&self) -> BTreeMap<String, f64> {
+        self.metrics.to_btreemap()
+    }
+
+    fn algorithm_name(&self) -> &'static str {
+        "FIFO"
+    }
+}
+
+impl<K: Hash + Eq + Clone, V: Clone> Segment for FifoSegment<K, V> {
+    type Key = K;
+    type Value = V;
+    type Hasher = RandomState;
+    type Config = FifoConfig;
+
+    fn init(config: FifoConfig, hasher: RandomState) -> Self {
+        Self::init_shared(config, hasher, config.capacity, 1)
+    }
+
+    fn init_shared(
+        config: FifoConfig,
+        _hasher: RandomState,
+        capacity: usize,
+        _segments: usize,
+    ) -> Self {
+        if let Some(reserved) = config.reserved {
+            reserved.fetch_add(capacity, Ordering::Relaxed);
+        }
+        FifoSegment {
+            capacity: config.capacity,
+            entries: VecDeque::with_capacity(capacity),
+            metrics: CoreCacheMetrics::new(u64::MAX),
+        }
+    }
+
+    fn split_config(config: FifoConfig, segments: usize) -> FifoConfig {
+        FifoConfig {
+            capacity: (config.capacity / segments).max(1),
+            ..config
+        }
+    }
+
+    fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    fn current_size(&self) -> u64 {
+        self.entries.iter().map(|(_, _, size)| size).sum()
+    }
+
+    fn max_size(&self) -> u64 {
+        u64::MAX
+    }
+
+    fn get<Q>(&mut self, _hash: u64, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let (_, value, size) = self.entries.iter().find(|(k, _, _)| k.borrow() == key)?;
+        self.metrics.record_hit(*size);
+        Some(value)
+    }
+
+    fn get_mut<Q>(&mut self, _hash: u64, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let (_, value, size) = self
+            .entries
+            .iter_mut()
+            .find(|(k, _, _)| (*k).borrow() == key)?;
+        self.metrics.record_hit(*size);
+        Some(value)
+    }
+
+    fn peek_key_value<Q>(&self, _hash: u64, key: &Q) -> Option<(&K, &V)>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.entries
+            .iter()
+            .find(|(k, _, _)| k.borrow() == key)
+            .map(|(k, v, _)| (k, v))
+    }
+
+    fn put(&mut self, _hash: u64, key: K, value: V, size: u64) -> Option<Vec<(K, V)>> {
+        if let Some(entry) = self.entries.iter_mut().find(|(k, _, _)| *k == key) {
+            *entry = (key, value, size);
+            return None;
+        }
+        self.metrics.record_insertion(size);
+        self.entries.push_back((key, value, size));
+        let mut evicted = Vec::new();
+        while self.entries.len() > self.capacity {
+            evicted.extend(self.pop_victim());
+        }
+        (!evicted.is_empty()).then_some(evicted)
+    }
+
+    fn remove<Q>(&mut self, _hash: u64, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let idx = self
+            .entries
+            .iter()
+            .position(|(k, _, _)| k.borrow() == key)?;
+        self.entries.remove(idx).map(|(_, value, _)| value)
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    fn pop_victim(&mut self) -> Option<(K, V)> {
+        let (key, value, size) = self.entries.pop_front()?;
+        self.metrics.record_eviction(size);
+        Some((key, value))
+    }
+
+    fn record_miss(&mut self, object_size: u64) {
+        self.metrics.record_miss(object_size);
+    }
+
+    fn core_metrics(&self) -> &CoreCacheMetrics {
+        &self.metrics
+    }
+}
+
+fn fifo_config(
+    capacity: usize,
+    segments: usize,
+    shared_budget: bool,
+) -> ConcurrentCacheConfig<FifoConfig> {
+    ConcurrentCacheConfig {
+        base: FifoConfig {
+            capacity,
+            reserved: None,
+        },
+        segments,
+        shared_budget,
+    }
+}
+
+/// Hash builder counting the keys it hashes.
+#[derive(Clone, Default)]
+struct CountingHasher(Arc<AtomicUsize>);
+
+impl BuildHasher for CountingHasher {
+    type Hasher = std::collections::hash_map::DefaultHasher;
+
+    fn build_hasher(&self) -> Self::Hasher {
+        self.0.fetch_add(1, Ordering::Relaxed);
+        Self::Hasher::new()
+    }
+}
+
+/// Checks that each operation hashes its key once, evictions included.
+fn check_hashes_once<C>(base: C::Config)
+where
+    C: SegmentFeatures<Key = i32, Value = i32, Hasher = CountingHasher>,
+{
+    let config = ConcurrentCacheConfig {
+        base,
+        segments: 1,
+        shared_budget: false,
+    };
+    let hasher = CountingHasher::default();
+    let hashes = Arc::clone(&hasher.0);
+    let cache: ShardedCache<C> = ShardedCache::with_hasher(config, hasher);
+    for key in 0..20 {
+        cache.put(key, key, 1);
+        cache.get(&key);
+        cache.get(&(key / 2));
+        cache.lookup(&(key + 100));
+    }
+    assert!(cache.len() <= 4);
+    assert_eq!(hashes.load(Ordering::Relaxed), 80);
+}
+
+#[test]
+fn test_keys_hashed_once_per_operation() {
+    let capacity = NonZeroUsize::new(4).unwrap();
+    check_hashes_once::<LruCache<i32, i32, CountingHasher>>(LruCacheConfig {
+        capacity,
+        max_size: u64::MAX,
+        weight_limits: None,
+    });
+    check_hashes_once::<LfuCache<i32, i32, CountingHasher>>(LfuCacheConfig {
+        capacity,
+        max_size: u64::MAX,
+        weight_limits: None,
+    });
+    check_hashes_once::<LfudaCache<i32, i32, CountingHasher>>(LfudaCacheConfig {
+        capacity,
+        initial_age: 0,
+        max_size: u64::MAX,
+        weight_limits: None,
+    });
+    check_hashes_once::<SlruCache<i32, i32, CountingHasher>>(SlruCacheConfig {
+        capacity,
+        protected_capacity: NonZeroUsize::new(2).unwrap(),
+        max_size: u64::MAX,
+        weight_limits: None,
+    });
+    check_hashes_once::<GdsfCache<i32, i32, CountingHasher>>(GdsfCacheConfig {
+        capacity,
+        initial_age: 0.0,
+        max_size: u64::MAX,
+        weight_limits: None,
+    });
+}
+
+#[test]
+fn test_user_defined_segment_evicts_by_its_own_policy() {
+    let cache: ShardedCache<FifoSegment<i32, i32>> =
+        ShardedCache::with_hasher(fifo_config(2, 1, false), RandomState::new());
+
+    cache.put(1, 1, 1);
+    cache.put(2, 2, 1);
+    // FIFO ignores accesses, so 1 is still the oldest entry
+    assert_eq!(cache.get(&1), Some(1));
+    assert_eq!(cache.put(3, 3, 1), Some(vec![(1, 1)]));
+
+    assert!(!cache.contains(&1));
+    assert_eq!(cache.get_mut_with(&2, |v| *v += 10), Some(()));
+    assert_eq!(cache.peek(&2), Some(12));
+    assert_eq!(cache.algorithm_name(), "Sharded");
+    assert_eq!(cache.metrics().get("evictions"), Some(&1.0));
+}
+
+#[test]
+fn test_user_defined_segment_without_features() {
+    let cache: ShardedCache<FifoSegment<i32, i32>> =
+        ShardedCache::with_hasher(fifo_config(2, 1, false), RandomState::new());
+    cache.put(1, 1, 1);
+
+    // Without SegmentFeatures there is no refresh policy, so every entry is
+    // fresh, and a loading cache still works on top
+    assert_eq!(cache.get_fresh(&1), Some((1, Freshness::Fresh)));
+    assert_eq!(cache.get_fresh(&2), None);
+    let loader = |key: &i32| Ok::<_, std::convert::Infallible>(Some((key * 10, 1)));
+    let loading = ConcurrentLoadingCache::new(cache, loader);
+    assert_eq!(loading.get(&1), Ok(Some(1)));
+    assert_eq!(loading.get(&2), Ok(Some(20)));
+}
+
+#[test]
+fn test_user_defined_segment_is_thread_safe() {
+    for shared_budget in [false, true] {
+        let cache: Arc<ShardedCache<FifoSegment<i32, i32>>> = Arc::new(ShardedCache::with_hasher(
+            fifo_config(64, 4, shared_budget),
+            RandomState::new(),
+        ));
+
+        let mut handles = vec![];
+        for t in 0..NUM_THREADS {
+            let cache = Arc::clone(&cache);
+            handles.push(thread::spawn(move || {
+                for i in 0..OPS_PER_THREAD {
+                    let key = (t * OPS_PER_THREAD + i) as i32;
+                    cache.put(key, key, 1);
+                    if let Some(value) = cache.get(&key) {
+                        assert_eq!(value, key);
+                    }
+                }
+            }));
+        }
+        for handle in handles {
+            handle.join().expect("Thread panicked");
+        }
+
+        assert_eq!(cache.capacity(), 64);
+        assert!(cache.len() <= 64, "shared_budget={shared_budget}");
+    }
+}
+
+#[test]
+fn test_shared_budget_segments_preallocate_their_share() {
+    static RESERVED: AtomicUsize = AtomicUsize::new(0);
+    let mut config = fifo_config(64, 4, true);
+    config.base.reserved = Some(&RESERVED);
+
+    // Each segment may hold all 64 entries, but preallocates 16
+    let cache: ShardedCache<FifoSegment<i32, i32>> =
+        ShardedCache::with_hasher(config, RandomState::new());
+    assert_eq!(RESERVED.swap(0, Ordering::Relaxed), 64);
+    for key in 0..64 {
+        cache.put(key, key, 1);
+    }
+    assert_eq!(cache.len(), 64);
+
+    // A tenant's segments preallocate their share of its quota
+    let tenants: TenantCache<u8, FifoSegment<i32, i32>> =
+        TenantCache::with_hasher(config, RandomState::new());
+    tenants.set_quota(1, TenantQuota::new(8, u64::MAX));
+    assert_eq!(RESERVED.load(Ordering::Relaxed), 8);
+}
+
 // ============================================================================
 // CONTAINS COVERAGE (concurrent)
 // ============================================================================
@@ -2962,6 +3581,778 @@ fn test_concurrent_all_caches_algorithm_name() {
     }
 }
 
+#[test]
+fn test_concurrent_snapshot_merges_segments() {
+    let cache: ConcurrentSlruCache<i32, i32> =
+        ConcurrentSlruCache::init(slru_config(1000, 200, 4), None);
+
+    for key in 0..100 {
+        cache.put(key, key, 1);
+        let _ = cache.get(&key);
+        cache.record_miss(1);
+    }
+
+    let snapshot = cache.snapshot();
+    let MetricsSnapshot::Slru(merged) = &snapshot else {
+        panic!("expected SLRU snapshot, got {snapshot:?}");
+    };
+    assert_eq!(merged.core.requests, 200);
+    assert_eq!(merged.core.cache_hits, 100);
+    assert_eq!(merged.total_promotions, 100);
+
+    // Rates are computed from the merged counters, not summed per segment
+    let metrics = cache.metrics();
+    assert_eq!(metrics.get("hit_rate"), Some(&0.5));
+    assert_eq!(metrics.get("requests"), Some(&200.0));
+    assert!(metrics.contains_key("lock_contention_misses"));
+
+    let before = cache.snapshot();
+    let _ = cache.get(&0);
+    assert_eq!(cache.snapshot().delta(&before).core().cache_hits, 1);
+}
+
+#[test]
+fn test_concurrent_rolling_window_and_reset() {
+    let cache: ConcurrentLruCache<i32, i32> = ConcurrentLruCache::init(lru_config(1000, 4), None);
+    for key in 0..100 {
+        cache.put(key, key, 1);
+        let _ = cache.get(&key);
+    }
+    cache.set_metrics_window(Some(RollingWindow::last_requests(
+        NonZeroUsize::new(1000).unwrap(),
+    )));
+    for _ in 0..10 {
+        cache.record_miss(1);
+    }
+    let metrics = cache.metrics();
+    assert_eq!(metrics.get("window_requests"), Some(&10.0));
+    assert_eq!(metrics.get("window_hit_rate"), Some(&0.0));
+    assert_eq!(metrics.get("requests"), Some(&110.0));
+
+    cache.reset_metrics();
+    let metrics = cache.metrics();
+    assert_eq!(metrics.get("requests"), Some(&0.0));
+    assert_eq!(metrics.get("window_requests"), Some(&0.0));
+    assert_eq!(metrics.get("read_buffer_drops"), Some(&0.0));
+    assert_eq!(metrics.get("cache_size_bytes"), Some(&100.0));
+    assert_eq!(cache.len(), 100);
+}
+
+#[test]
+fn test_concurrent_miss_ratio_curve() {
+    let cache: ConcurrentLruCache<i32, i32> = ConcurrentLruCache::init(lru_config(1000, 4), None);
+    cache.set_miss_ratio_curve(Some(MissRatioCurve::new(
+        NonZeroUsize::new(100).unwrap(),
+        NonZeroUsize::new(1000).unwrap(),
+    )));
+    // 200 keys fit the cache, but not a quarter of them each segment's share
+    // of the 100-entry reference capacity
+    for _ in 0..10 {
+        for key in 0..200 {
+            if cache.get(&key).is_none() {
+                cache.put(key, key, 1);
+            }
+        }
+    }
+
+    let metrics = cache.metrics();
+    assert_eq!(metrics.get("mrc_sampled_keys"), Some(&200.0));
+    assert!(metrics["mrc_miss_ratio_1x"] > 0.95, "{metrics:?}");
+    assert!(metrics["mrc_miss_ratio_4x"] < 0.15, "{metrics:?}");
+    let snapshot = cache.snapshot();
+    let curve = snapshot.core().miss_ratio_curve.as_ref().unwrap();
+    assert_eq!(curve.capacity(), 100);
+}
+
+#[test]
+fn test_concurrent_hot_keys() {
+    let cache: Arc<ConcurrentLfuCache<i32, i32>> =
+        Arc::new(ConcurrentLfuCache::init(lfu_config(1000, 4), None));
+    cache.set_hot_keys(Some(HotKeys::new(NonZeroUsize::new(16).unwrap())));
+    for key in 0..100 {
+        cache.put(key, key, if key == 42 { 4096 } else { 1 });
+    }
+
+    // Every thread hammers key 7 and touches key 42 once
+    let handles: Vec<_> = (0..4)
+        .map(|t| {
+            let cache = Arc::clone(&cache);
+            thread::spawn(move || {
+                let _ = cache.get(&42);
+                for i in 0..500 {
+                    let _ = cache.get(&7);
+                    let _ = cache.get(&((i * 4 + t) % 100));
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    // Replay the buffered reads still pending
+    let _ = cache.segment_metrics();
+
+    let by_accesses = cache.hot_keys_by_accesses(3);
+    assert_eq!(by_accesses[0].key, 7, "{by_accesses:?}");
+    assert_eq!(by_accesses.len(), 3);
+    assert_eq!(cache.hot_keys_by_bytes(1)[0].key, 42);
+
+    cache.set_hot_keys(None);
+    assert!(cache.hot_keys_by_accesses(3).is_empty());
+}
+
+/// Clock that never advances, so negative entries never expire
+struct FrozenClock;
+
+impl Clock for FrozenClock {
+    fn now_nanos(&self) -> u64 {
+        0
+    }
+}
+
+#[test]
+fn test_concurrent_negative_caching() {
+    let cache: Arc<ConcurrentLruCache<i32, i32>> =
+        Arc::new(ConcurrentLruCache::init(lru_config(400, 4), None));
+    assert_eq!(cache.lookup(&1), Lookup::Miss);
+    cache.set_negative_cache(Some(NegativeCache::new(
+        NonZeroUsize::new(400).unwrap(),
+        Arc::new(FrozenClock),
+    )));
+    for key in 0..100 {
+        cache.put(key, key, 1);
+    }
+
+    // Threads mark odd keys absent while others look them up
+    let handles: Vec<_> = (0..4)
+        .map(|t| {
+            let cache = Arc::clone(&cache);
+            thread::spawn(move || {
+                for key in (t * 25..(t + 1) * 25).filter(|key| key % 2 == 1) {
+                    cache.put_absent(key, std::time::Duration::from_secs(60));
+                    assert_eq!(cache.lookup(&key), Lookup::Absent);
+                    assert_eq!(cache.lookup(&(key - 1)), Lookup::Hit(key - 1));
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    assert_eq!(cache.len(), 50);
+    assert_eq!(cache.negative_len(), 50);
+    assert_eq!(cache.lookup(&1000), Lookup::Miss);
+
+    let core = cache.snapshot().core().clone();
+    assert_eq!(core.negative_hits, 50);
+    assert_eq!(core.negative_misses, 1);
+
+    // Caching a value drops its negative entry
+    cache.put(1, 1, 1);
+    assert_eq!(cache.lookup(&1), Lookup::Hit(1));
+    assert_eq!(cache.negative_len(), 49);
+    cache.clear();
+    assert_eq!(cache.negative_len(), 0);
+}
+
+/// Backing store shared by the threads of the loader tests
+#[derive(Default)]
+struct SharedStore(std::sync::Mutex<BTreeMap<i32, i32>>);
+
+impl CacheWriter<i32, i32> for SharedStore {
+    type Error = std::convert::Infallible;
+
+    fn write(&self, key: &i32, value: &i32) -> Result<(), Self::Error> {
+        self.0.lock().unwrap().insert(*key, *value);
+        Ok(())
+    }
+
+    fn delete(&self, key: &i32) -> Result<(), Self::Error> {
+        self.0.lock().unwrap().remove(key);
+        Ok(())
+    }
+}
+
+/// Writes far more keys than `config` holds through a write-back loading
+/// cache from several threads, checking that every write reaches the store.
+fn check_loading_cache_writes_back(config: ConcurrentLruCacheConfig) {
+    let loads = Arc::new(AtomicUsize::new(0));
+    let loader = {
+        let loads = Arc::clone(&loads);
+        move |key: &i32| {
+            loads.fetch_add(1, Ordering::Relaxed);
+            Ok::<_, std::convert::Infallible>(Some((-key, 1)))
+        }
+    };
+    let cache = Arc::new(
+        ConcurrentLoadingCache::new(ConcurrentLruCache::init(config, None), loader)
+            .with_writer(SharedStore::default(), WriteMode::WriteBack),
+    );
+
+    // Each thread writes its own keys, far more than the cache holds, and
+    // reads keys that only the loader knows
+    let handles: Vec<_> = (0..NUM_THREADS as i32)
+        .map(|t| {
+            let cache = Arc::clone(&cache);
+            thread::spawn(move || {
+                for i in 0..OPS_PER_THREAD as i32 {
+                    let key = t * 10_000 + i;
+                    cache.put(key, key, 1).unwrap();
+                    assert_eq!(cache.get(&(-key - 1)), Ok(Some(key + 1)));
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    assert!(loads.load(Ordering::Relaxed) >= NUM_THREADS * OPS_PER_THREAD);
+
+    // Every write reached the store, on eviction or on flush; loads did not
+    cache.flush().unwrap();
+    assert_eq!(cache.dirty_len(), 0);
+    let store = cache.writer().0.lock().unwrap();
+    assert_eq!(store.len(), NUM_THREADS * OPS_PER_THREAD);
+    assert!(store.iter().all(|(key, value)| key == value));
+}
+
+#[test]
+fn test_concurrent_loading_cache_writes_back() {
+    check_loading_cache_writes_back(lru_config(64, 4));
+    // A shared budget evicts across segments
+    check_loading_cache_writes_back(ConcurrentCacheConfig {
+        shared_budget: true,
+        ..lru_config(64, 4)
+    });
+}
+
+/// Backing store whose writes fail while `fail` is set
+#[derive(Default)]
+struct FlakyStore {
+    data: std::sync::Mutex<BTreeMap<i32, i32>>,
+    fail: std::sync::atomic::AtomicBool,
+}
+
+impl CacheWriter<i32, i32> for FlakyStore {
+    type Error = &'static str;
+
+    fn write(&self, key: &i32, value: &i32) -> Result<(), Self::Error> {
+        if self.fail.load(Ordering::Relaxed) {
+            return Err("store unavailable");
+        }
+        self.data.lock().unwrap().insert(*key, *value);
+        Ok(())
+    }
+
+    fn delete(&self, key: &i32) -> Result<(), Self::Error> {
+        self.data.lock().unwrap().remove(key);
+        Ok(())
+    }
+}
+
+#[test]
+fn test_concurrent_loading_cache_flushes_the_latest_write() {
+    let loads = Arc::new(AtomicUsize::new(0));
+    let loader = {
+        let loads = Arc::clone(&loads);
+        move |key: &i32| {
+            loads.fetch_add(1, Ordering::Relaxed);
+            Ok::<_, std::convert::Infallible>(Some((-key, 1)))
+        }
+    };
+    let cache =
+        ConcurrentLoadingCache::new(ConcurrentLruCache::init(lru_config(2, 1), None), loader)
+            .with_writer(FlakyStore::default(), WriteMode::WriteBack);
+    cache.writer().fail.store(true, Ordering::Relaxed);
+    cache.put(1, 100, 1).unwrap();
+    cache.put(2, 200, 1).unwrap();
+
+    // 1 is evicted and its write fails; reads see it instead of loading
+    cache.put(3, 300, 1).unwrap();
+    assert!(!cache.cache().contains(&1));
+    assert_eq!(cache.get(&1), Ok(Some(100)));
+    assert_eq!(cache.get_all(&[1, 3]), Ok(vec![Some(100), Some(300)]));
+    assert_eq!(loads.load(Ordering::Relaxed), 0);
+
+    // Putting 1 again supersedes the failed write
+    cache.put(1, 111, 1).unwrap();
+    assert_eq!(cache.dirty_len(), 3);
+    cache.writer().fail.store(false, Ordering::Relaxed);
+    assert_eq!(cache.flush(), Ok(3));
+    let data = cache.writer().data.lock().unwrap();
+    assert_eq!(
+        (data.get(&1), data.get(&2), data.get(&3)),
+        (Some(&111), Some(&200), Some(&300))
+    );
+}
+
+#[test]
+fn test_concurrent_loading_cache_writes_through() {
+    let loader =
+        |key: &i32| Ok::<_, std::convert::Infallible>((*key < 100).then_some((key * 2, 1)));
+    let cache =
+        ConcurrentLoadingCache::new(ConcurrentLruCache::init(lru_config(10, 2), None), loader)
+            .with_writer(SharedStore::default(), WriteMode::WriteThrough);
+    assert_eq!(
+        cache.get_all(&[1, 200, 3]),
+        Ok(vec![Some(2), None, Some(6)])
+    );
+    assert_eq!(cache.cache().get(&3), Some(6));
+
+    cache.put(5, 50, 1).unwrap();
+    assert_eq!(cache.writer().0.lock().unwrap().get(&5), Some(&50));
+    assert_eq!(cache.flush(), Ok(0));
+    assert_eq!(cache.remove(&5), Ok(Some(50)));
+    assert!(cache.writer().0.lock().unwrap().is_empty());
+}
+
+#[test]
+fn test_concurrent_get_fresh() {
+    use std::time::Duration;
+
+    let cache: ConcurrentLruCache<i32, i32> = ConcurrentLruCache::init(lru_config(100, 4), None);
+    for key in 0..10 {
+        cache.put(key, key, 1);
+    }
+    assert_eq!(cache.get_fresh(&1), Some((1, Freshness::Fresh)));
+    cache.set_refresh_policy(Some(RefreshPolicy::new(Duration::ZERO, Duration::MAX)));
+    assert_eq!(cache.get_fresh(&1), Some((1, Freshness::Stale)));
+    assert_eq!(cache.get_fresh(&100), None);
+
+    // Expired values are removed from every segment
+    cache.set_refresh_policy(Some(RefreshPolicy::new(Duration::ZERO, Duration::ZERO)));
+    for key in 0..10 {
+        assert_eq!(cache.get_fresh(&key), None);
+    }
+    assert_eq!(cache.len(), 0);
+}
+
+/// Clock advanced by hand
+#[derive(Default)]
+struct ManualClock(AtomicU64);
+
+impl Clock for ManualClock {
+    fn now_nanos(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+#[test]
+fn test_concurrent_ages_entries_with_clock() {
+    use std::time::Duration;
+
+    let cache: ConcurrentLruCache<i32, i32> = ConcurrentLruCache::init(lru_config(100, 4), None);
+    let clock = Arc::new(ManualClock::default());
+    cache.set_clock(Some(clock.clone()));
+    cache.set_refresh_policy(Some(RefreshPolicy::new(
+        Duration::from_secs(10),
+        Duration::from_secs(20),
+    )));
+    for key in 0..10 {
+        cache.put(key, key, 1);
+    }
+    assert_eq!(cache.get_fresh(&1), Some((1, Freshness::Fresh)));
+    clock.0.store(10_000_000_000, Ordering::Relaxed);
+    for key in 0..10 {
+        assert_eq!(cache.get_fresh(&key), Some((key, Freshness::Stale)));
+    }
+    clock.0.store(20_000_000_000, Ordering::Relaxed);
+    assert_eq!(cache.get_fresh(&1), None);
+    assert_eq!(cache.len(), 9);
+}
+
+#[test]
+fn test_concurrent_loading_cache_refreshes_once() {
+    use std::sync::atomic::AtomicBool;
+    use std::time::Duration;
+
+    // The first load succeeds, the second blocks until released, later ones
+    // fail
+    let loads = Arc::new(AtomicUsize::new(0));
+    let released = Arc::new(AtomicBool::new(false));
+    let loader = {
+        let (loads, released) = (Arc::clone(&loads), Arc::clone(&released));
+        move |key: &i32| match loads.fetch_add(1, Ordering::SeqCst) {
+            0 => Ok(Some((*key, 1))),
+            1 => {
+                while !released.load(Ordering::SeqCst) {
+                    thread::yield_now();
+                }
+                Ok(Some((key + 1, 1)))
+            }
+            _ => Err("source down"),
+        }
+    };
+    let cache = Arc::new(
+        ConcurrentLoadingCache::new(ConcurrentLruCache::init(lru_config(100, 4), None), loader)
+            .with_refresh(RefreshPolicy::new(Duration::ZERO, Duration::MAX)),
+    );
+    assert_eq!(cache.get(&1), Ok(Some(1)));
+
+    let refresher = {
+        let cache = Arc::clone(&cache);
+        thread::spawn(move || cache.get(&1))
+    };
+    while loads.load(Ordering::SeqCst) < 2 {
+        thread::yield_now();
+    }
+
+    // While one thread refreshes, the others serve the stale value
+    let readers: Vec<_> = (0..NUM_THREADS)
+        .map(|_| {
+            let cache = Arc::clone(&cache);
+            thread::spawn(move || {
+                for _ in 0..100 {
+                    assert_eq!(cache.get(&1), Ok(Some(1)));
+                }
+            })
+        })
+        .collect();
+    for reader in readers {
+        reader.join().unwrap();
+    }
+    assert_eq!(loads.load(Ordering::SeqCst), 2);
+    released.store(true, Ordering::SeqCst);
+    assert_eq!(refresher.join().unwrap(), Ok(Some(2)));
+
+    // A failed refresh serves the stale value, and the next get retries
+    assert_eq!(cache.get(&1), Ok(Some(2)));
+    assert_eq!(loads.load(Ordering::SeqCst), 3);
+    assert_eq!(cache.get(&1), Ok(Some(2)));
+    assert_eq!(loads.load(Ordering::SeqCst), 4);
+    assert_eq!(cache.cache().get(&1), Some(2));
+}
+
+#[test]
+fn test_concurrent_loading_cache_refreshes_after_failed_refresh_expires() {
+    use std::sync::atomic::AtomicBool;
+    use std::time::Duration;
+
+    let loads = Arc::new(AtomicUsize::new(0));
+    let down = Arc::new(AtomicBool::new(false));
+    let loader = {
+        let (loads, down) = (Arc::clone(&loads), Arc::clone(&down));
+        move |key: &i32| {
+            let load = loads.fetch_add(1, Ordering::SeqCst) as i32;
+            if down.load(Ordering::SeqCst) {
+                Err("source down")
+            } else {
+                Ok(Some((key * 10 + load, 1)))
+            }
+        }
+    };
+    let cache =
+        ConcurrentLoadingCache::new(ConcurrentLruCache::init(lru_config(100, 4), None), loader)
+            .with_refresh(RefreshPolicy::new(
+                Duration::from_secs(10),
+                Duration::from_secs(20),
+            ));
+    let clock = Arc::new(ManualClock::default());
+    cache.cache().set_clock(Some(clock.clone()));
+    assert_eq!(cache.get(&1), Ok(Some(10)));
+
+    // The refresh fails and the stale value is served
+    down.store(true, Ordering::SeqCst);
+    clock.0.store(10_000_000_000, Ordering::SeqCst);
+    assert_eq!(cache.get(&1), Ok(Some(10)));
+    assert_eq!(loads.load(Ordering::SeqCst), 2);
+
+    // The value expires while the source is still down
+    clock.0.store(20_000_000_000, Ordering::SeqCst);
+    assert_eq!(cache.get(&1), Err("source down"));
+    assert_eq!(loads.load(Ordering::SeqCst), 3);
+
+    // A value cached again goes stale and is refreshed through the loader
+    down.store(false, Ordering::SeqCst);
+    cache.cache().put(1, 99, 1);
+    clock.0.store(30_000_000_000, Ordering::SeqCst);
+    assert_eq!(cache.get(&1), Ok(Some(13)));
+    assert_eq!(loads.load(Ordering::SeqCst), 4);
+    assert_eq!(cache.cache().peek(&1), Some(13));
+}
+
+#[test]
+fn test_concurrent_xfetch_spreads_reloads() {
+    use std::time::Duration;
+
+    // Recomputing takes as long as the TTL, so each get reloads with
+    // probability exp(-1)
+    let xfetch = XFetch::new(Duration::from_nanos(u64::MAX), Arc::new(SplitMix64::new(7)));
+    let loads = Arc::new(AtomicUsize::new(0));
+    let loader = {
+        let loads = Arc::clone(&loads);
+        move |key: &i32| {
+            loads.fetch_add(1, Ordering::Relaxed);
+            Ok::<_, std::convert::Infallible>(Some((*key, 1)))
+        }
+    };
+    let cache = Arc::new(
+        ConcurrentLoadingCache::new(ConcurrentLruCache::init(lru_config(1000, 4), None), loader)
+            .with_refresh(RefreshPolicy::new(Duration::MAX, Duration::MAX))
+            .with_xfetch(xfetch),
+    );
+    let handles: Vec<_> = (0..NUM_THREADS as i32)
+        .map(|t| {
+            let cache = Arc::clone(&cache);
+            thread::spawn(move || {
+                for i in 0..1000 {
+                    let key = t * 10 + i % 10;
+                    assert_eq!(cache.get(&key), Ok(Some(key)));
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    let gets = NUM_THREADS * 1000;
+    let loads = loads.load(Ordering::Relaxed);
+    assert!(loads > gets / 5 && loads < gets / 2, "{loads} of {gets}");
+
+    // Without a refresh policy values never expire early
+    cache.cache().set_refresh_policy(None);
+    assert_eq!(cache.cache().get_fresh(&0), Some((0, Freshness::Fresh)));
+}
+
+#[test]
+fn test_concurrent_invalidate_tag() {
+    let cache: Arc<ConcurrentLruCache<i32, i32>> =
+        Arc::new(ConcurrentLruCache::init(lru_config(10_000, 8), None));
+
+    // Each thread tags its keys with the thread and with their parity
+    let handles: Vec<_> = (0..NUM_THREADS as i32)
+        .map(|t| {
+            let cache = Arc::clone(&cache);
+            thread::spawn(move || {
+                for i in 0..500 {
+                    let key = t * 1000 + i;
+                    cache.put_tagged(key, key, 1, &[t as u64, 100 + (i % 2) as u64]);
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    assert_eq!(cache.len(), NUM_THREADS * 500);
+
+    // Tags span all segments
+    assert_eq!(cache.invalidate_tag(0), 500);
+    assert_eq!(cache.invalidate_tag(100), (NUM_THREADS - 1) * 250);
+    assert_eq!(cache.invalidate_tag(0), 0);
+    assert_eq!(cache.len(), (NUM_THREADS - 1) * 250);
+    assert_eq!(cache.get(&1001), Some(1001));
+    assert_eq!(cache.get(&1000), None);
+    assert_eq!(cache.invalidate_tag(101), (NUM_THREADS - 1) * 250);
+    assert!(cache.is_empty());
+}
+
+#[test]
+fn test_concurrent_weight_limits() {
+    let cache: Arc<ConcurrentLruCache<i32, i32>> =
+        Arc::new(ConcurrentLruCache::init(lru_config(10_000, 4), None));
+    let limits = WeightLimits::new()
+        .with_dimension("handles", 40)
+        .with_dimension("upload", 1_000_000);
+    cache.set_weight_limits(Some(limits));
+
+    // Each entry holds a handle, so handles bound the entries
+    let handles: Vec<_> = (0..NUM_THREADS as i32)
+        .map(|t| {
+            let cache = Arc::clone(&cache);
+            thread::spawn(move || {
+                for i in 0..500 {
+                    let key = t * 1000 + i;
+                    cache.put_weighted(key, key, 1, Weights::of(&[1, 10]));
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    // Each of the 4 segments holds at most its 10 handles
+    assert!(cache.len() <= 40, "{} entries", cache.len());
+    let core = cache.snapshot().core().clone();
+    let handles = &core.weights[0];
+    assert_eq!(handles.name, "handles");
+    assert_eq!(handles.limit, 40);
+    assert_eq!(handles.used, cache.len() as u64);
+    assert_eq!(core.weights[1].used, 10 * cache.len() as u64);
+    assert_eq!(handles.evictions, (NUM_THREADS * 500 - cache.len()) as u64);
+    assert_eq!(core.evictions, handles.evictions);
+}
+
+#[test]
+fn test_concurrent_weight_limits_from_config() {
+    for shared_budget in [false, true] {
+        let mut config = lru_config(10_000, 4);
+        config.base.weight_limits = Some(WeightLimits::new().with_dimension("handles", 40));
+        config.shared_budget = shared_budget;
+        let cache: ConcurrentLruCache<i32, i32> = ConcurrentLruCache::init(config, None);
+
+        // Either way, each of the 4 segments weighs a quarter of the limits
+        for key in 0..1000 {
+            cache.put_weighted(key, key, 1, Weights::of(&[1]));
+        }
+        assert!(cache.len() <= 40, "{} entries", cache.len());
+        let core = cache.snapshot().core().clone();
+        assert_eq!(core.weights[0].limit, 40);
+        assert_eq!(core.weights[0].used, cache.len() as u64);
+    }
+}
+
+#[test]
+fn test_concurrent_put_with_cost() {
+    let gdsf: ConcurrentGdsfCache<&str, i32> = ConcurrentGdsfCache::init(gdsf_config(2, 1), None);
+    gdsf.put_with_cost("remote", 1, 10, 100);
+    gdsf.put("local", 2, 1);
+    assert_eq!(gdsf.put("other", 3, 1), Some(vec![("local", 2)]));
+
+    let lfuda: ConcurrentLfudaCache<&str, i32> =
+        ConcurrentLfudaCache::init(lfuda_config(2, 1), None);
+    lfuda.put_with_cost("remote", 1, 1, 10);
+    lfuda.put("local", 2, 1);
+    lfuda.get(&"local");
+    assert_eq!(lfuda.put("other", 3, 1), Some(vec![("local", 2)]));
+
+    // Other caches ignore the cost
+    let lru: ConcurrentLruCache<&str, i32> = ConcurrentLruCache::init(lru_config(2, 1), None);
+    lru.put_with_cost("remote", 1, 1, 10);
+    lru.put("local", 2, 1);
+    assert_eq!(lru.put("other", 3, 1), Some(vec![("remote", 1)]));
+}
+
+/// Fills a tenant cache of 80 entries from a noisy tenant limited to 60
+/// entries and a quiet tenant guaranteed 30.
+fn check_tenant_isolation<C>(cache: TenantCache<&'static str, C>)
+where
+    C: Segment<Key = i32, Value = i32>,
+{
+    let cache = cache.with_default_quota(TenantQuota::new(60, u64::MAX));
+    cache.set_quota("quiet", TenantQuota::unlimited().with_minimum(30, 0));
+    for key in 0..30 {
+        cache.put("quiet", key, key, 1);
+    }
+    for key in 0..1000 {
+        cache.put("noisy", key, key, 1);
+    }
+
+    // The noisy tenant only evicts its own entries
+    assert_eq!(cache.len(), 80, "{}", cache.algorithm_name());
+    assert!((0..30).all(|key| cache.contains(&"quiet", &key)));
+    let quiet = cache.tenant_metrics(&"quiet").unwrap();
+    let noisy = cache.tenant_metrics(&"noisy").unwrap();
+    assert_eq!((quiet.len, noisy.len), (30, 50));
+    assert_eq!(quiet.snapshot.core().evictions, 0);
+    assert_eq!(noisy.snapshot.core().evictions, 950);
+    assert_eq!(noisy.quota, TenantQuota::new(60, u64::MAX));
+
+    // Above its minimum, the quiet tenant evicts once it is furthest over it
+    for key in 30..70 {
+        cache.put("quiet", key, key, 1);
+    }
+    let quiet = cache.tenant_metrics(&"quiet").unwrap();
+    let noisy = cache.tenant_metrics(&"noisy").unwrap();
+    assert_eq!((quiet.len, noisy.len), (55, 25));
+    assert_eq!(quiet.snapshot.core().evictions, 15);
+
+    // Lowering a quota evicts down to it
+    let evicted = cache.set_quota("noisy", TenantQuota::new(20, u64::MAX));
+    assert_eq!(evicted.map(|e| e.len()), Some(5));
+    assert_eq!(cache.snapshot().core().evictions, 995);
+    cache.clear_tenant(&"noisy");
+    assert_eq!(cache.len(), 55);
+}
+
+#[test]
+fn test_all_caches_isolate_tenants() {
+    check_tenant_isolation::<LruCache<i32, i32>>(TenantCache::init(lru_config(80, 4), None));
+    check_tenant_isolation::<SlruCache<i32, i32>>(TenantCache::init(slru_config(80, 40, 4), None));
+    check_tenant_isolation::<LfuCache<i32, i32>>(TenantCache::init(lfu_config(80, 4), None));
+    check_tenant_isolation::<LfudaCache<i32, i32>>(TenantCache::init(lfuda_config(80, 4), None));
+    check_tenant_isolation::<GdsfCache<i32, i32>>(TenantCache::init(gdsf_config(80, 4), None));
+}
+
+#[test]
+fn test_tenant_size_quotas() {
+    let cache: TenantCache<u8, GdsfCache<i32, i32>> =
+        TenantCache::init(gdsf_config_with_size(1000, 10_000, 4), None);
+    cache.set_quota(0, TenantQuota::new(usize::MAX, 4_000));
+    cache.set_quota(1, TenantQuota::unlimited().with_minimum(0, 6_000));
+    cache.set_quota(2, TenantQuota::unlimited().with_minimum(0, 5_000));
+    let size = |tenant| cache.tenant_metrics(&tenant).map_or(0, |m| m.size);
+    for key in 0..100 {
+        cache.put(0, key, key, 100);
+    }
+    assert_eq!(size(0), 4_000);
+
+    // Tenant 1 takes bytes from tenant 0 until it is further above its
+    // minimum
+    for key in 0..80 {
+        cache.put(1, key, key, 100);
+    }
+    assert_eq!((size(0), size(1)), (2_000, 8_000));
+    assert!(cache.remove_tenant(&0));
+    assert!(!cache.remove_tenant(&0));
+
+    // Minimums that overcommit the cache leave the writer to make room
+    for key in 0..50 {
+        cache.put(2, key, key, 100);
+    }
+    assert_eq!((size(1), size(2)), (6_000, 4_000));
+    assert_eq!(cache.current_size(), 10_000);
+
+    let mut tenants = cache.tenants();
+    tenants.sort();
+    assert_eq!(tenants, vec![1, 2]);
+    cache.clear();
+    assert!(cache.is_empty());
+    assert_eq!(
+        cache.quota(&2),
+        Some(TenantQuota::unlimited().with_minimum(0, 5_000))
+    );
+}
+
+#[test]
+fn test_concurrent_tenants_respect_quotas() {
+    let cache: Arc<TenantCache<usize, LfuCache<usize, usize>>> = Arc::new(
+        TenantCache::init(lfu_config(1000, 8), None)
+            .with_default_quota(TenantQuota::new(400, u64::MAX).with_minimum(100, 0)),
+    );
+    let handles: Vec<_> = (0..NUM_THREADS)
+        .map(|t| {
+            let cache = Arc::clone(&cache);
+            thread::spawn(move || {
+                // Odd threads are noisy and write many more keys
+                let keys = if t % 2 == 0 { 100 } else { 5_000 };
+                for key in 0..keys {
+                    cache.put(t, key, key, 1);
+                    cache.get(&t, &(key / 2));
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    // Concurrent writers may overshoot the limits until the next write
+    cache.put(0, 0, 0, 1);
+    assert!(cache.len() <= 1000);
+    let metrics: BTreeMap<usize, TenantMetrics> = cache.metrics_by_tenant().into_iter().collect();
+    assert_eq!(metrics.len(), NUM_THREADS);
+    for (tenant, metrics) in &metrics {
+        assert!(metrics.len <= 400);
+        if tenant % 2 == 0 {
+            assert_eq!(metrics.len, 100);
+        }
+    }
+    let hits: u64 = metrics.values().map(|m| m.snapshot.core().cache_hits).sum();
+    assert_eq!(cache.snapshot().core().cache_hits, hits);
+}
+
 #[test]
 fn test_concurrent_all_caches_metrics() {
     let lru: Arc<ConcurrentLruCache<i32, i32>> =
@@ -26,8 +26,10 @@ fn lru_config(capacity: usize, segments: usize) -> ConcurrentLruCacheConfig {
         base: LruCacheConfig {
             capacity: NonZeroUsize::new(capacity).unwrap(),
             max_size: u64::MAX,
+            weight_limits: None,
         },
         segments,
+        shared_budget: false,
     }
 }
 
@@ -37,8 +39,10 @@ fn slru_config(capacity: usize, protected: usize, segments: usize) -> Concurrent
             capacity: NonZeroUsize::new(capacity).unwrap(),
             protected_capacity: NonZeroUsize::new(protected).unwrap(),
             max_size: u64::MAX,
+            weight_limits: None,
         },
         segments,
+        shared_budget: false,
     }
 }
 
@@ -47,8 +51,10 @@ fn lfu_config(capacity: usize, segments: usize) -> ConcurrentLfuCacheConfig {
         base: LfuCacheConfig {
             capacity: NonZeroUsize::new(capacity).unwrap(),
             max_size: u64::MAX,
+            weight_limits: None,
         },
         segments,
+        shared_budget: false,
     }
 }
 
@@ -58,8 +64,10 @@ fn lfuda_config(capacity: usize, segments: usize) -> ConcurrentLfudaCacheConfig
             capacity: NonZeroUsize::new(capacity).unwrap(),
             initial_age: 0,
             max_size: u64::MAX,
+            weight_limits: None,
         },
         segments,
+        shared_budget: false,
     }
 }
 
@@ -69,8 +77,10 @@ fn gdsf_config(capacity: usize, segments: usize) -> ConcurrentGdsfCacheConfig {
             capacity: NonZeroUsize::new(capacity).unwrap(),
             initial_age: 0.0,
             max_size: u64::MAX,
+            weight_limits: None,
         },
         segments,
+        shared_budget: false,
     }
 }
 
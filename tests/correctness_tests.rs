@@ -13,9 +13,20 @@
 use cache_rs::config::{
     GdsfCacheConfig, LfuCacheConfig, LfudaCacheConfig, LruCacheConfig, SlruCacheConfig,
 };
-use cache_rs::metrics::CacheMetrics;
+use cache_rs::gdsf::GdsfMode;
+use cache_rs::loader::{CacheLoader, CacheWriter, LoadingCache, WriteMode};
+use cache_rs::metrics::{
+    CacheMetrics, Clock, HotKeys, MetricsSnapshot, MissRatioCurve, NoMetrics, RollingWindow,
+};
+use cache_rs::negative::{Lookup, NegativeCache};
+use cache_rs::refresh::{Freshness, RandomSource, RefreshPolicy, SplitMix64, XFetch};
+use cache_rs::tiered::{MemoryStore, PrimaryCache, SecondaryStore, TieredCache};
+use cache_rs::weight::{WeightLimits, Weights};
 use cache_rs::{GdsfCache, LfuCache, LfudaCache, LruCache, SlruCache};
 use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
 
 const OBJECT_SIZE: u64 = 1;
 
@@ -28,6 +39,7 @@ fn make_lru<K: std::hash::Hash + Eq + Clone, V: Clone>(cap: usize) -> LruCache<K
     let config = LruCacheConfig {
         capacity: NonZeroUsize::new(cap).unwrap(),
         max_size: u64::MAX,
+        weight_limits: None,
     };
     LruCache::init(config, None)
 }
@@ -40,6 +52,7 @@ fn make_lru_with_limits<K: std::hash::Hash + Eq + Clone, V: Clone>(
     let config = LruCacheConfig {
         capacity: NonZeroUsize::new(cap).unwrap(),
         max_size,
+        weight_limits: None,
     };
     LruCache::init(config, None)
 }
@@ -51,6 +64,7 @@ fn make_lru_with_max_size<K: std::hash::Hash + Eq + Clone, V: Clone>(
     let config = LruCacheConfig {
         capacity: NonZeroUsize::new(16384).unwrap(),
         max_size,
+        weight_limits: None,
     };
     LruCache::init(config, None)
 }
@@ -60,6 +74,7 @@ fn make_lfu<K: std::hash::Hash + Eq + Clone, V: Clone>(cap: usize) -> LfuCache<K
     let config = LfuCacheConfig {
         capacity: NonZeroUsize::new(cap).unwrap(),
         max_size: u64::MAX,
+        weight_limits: None,
     };
     LfuCache::init(config, None)
 }
@@ -71,6 +86,7 @@ fn make_lfu_with_max_size<K: std::hash::Hash + Eq + Clone, V: Clone>(
     let config = LfuCacheConfig {
         capacity: NonZeroUsize::new(16384).unwrap(),
         max_size,
+        weight_limits: None,
     };
     LfuCache::init(config, None)
 }
@@ -81,6 +97,7 @@ fn make_lfuda<K: std::hash::Hash + Eq + Clone, V: Clone>(cap: usize) -> LfudaCac
         capacity: NonZeroUsize::new(cap).unwrap(),
         initial_age: 0,
         max_size: u64::MAX,
+        weight_limits: None,
     };
     LfudaCache::init(config, None)
 }
@@ -93,6 +110,7 @@ fn make_lfuda_with_max_size<K: std::hash::Hash + Eq + Clone, V: Clone>(
         capacity: NonZeroUsize::new(16384).unwrap(),
         initial_age: 0,
         max_size,
+        weight_limits: None,
     };
     LfudaCache::init(config, None)
 }
@@ -106,6 +124,7 @@ fn make_slru<K: std::hash::Hash + Eq + Clone, V: Clone>(
         capacity: NonZeroUsize::new(cap).unwrap(),
         protected_capacity: NonZeroUsize::new(protected_cap).unwrap(),
         max_size: u64::MAX,
+        weight_limits: None,
     };
     SlruCache::init(config, None)
 }
@@ -118,6 +137,7 @@ fn make_slru_with_max_size<K: std::hash::Hash + Eq + Clone, V: Clone>(
         capacity: NonZeroUsize::new(16384).unwrap(),
         protected_capacity: NonZeroUsize::new(3276).unwrap(), // ~20%
         max_size,
+        weight_limits: None,
     };
     SlruCache::init(config, None)
 }
@@ -128,6 +148,7 @@ fn make_gdsf<K: std::hash::Hash + Eq + Clone, V: Clone>(cap: usize) -> GdsfCache
         capacity: NonZeroUsize::new(cap).unwrap(),
         initial_age: 0.0,
         max_size: u64::MAX,
+        weight_limits: None,
     };
     GdsfCache::init(config, None)
 }
@@ -1987,6 +2008,7 @@ fn make_slru_with_limits<K: std::hash::Hash + Eq + Clone, V: Clone>(
         capacity: NonZeroUsize::new(cap).unwrap(),
         protected_capacity: NonZeroUsize::new(protected_cap).unwrap(),
         max_size,
+        weight_limits: None,
     };
     SlruCache::init(config, None)
 }
@@ -2542,6 +2564,755 @@ fn test_all_caches_metrics_after_operations() {
     );
 }
 
+#[test]
+fn test_metrics_snapshot_matches_algorithm_and_supports_delta() {
+    let mut lru: LruCache<&str, i32> = make_lru(2);
+    lru.put("a", 1, 1);
+    lru.get(&"a");
+    let before = lru.snapshot();
+    assert!(matches!(before, MetricsSnapshot::Lru(_)));
+    assert_eq!(before.to_btreemap(), lru.metrics());
+
+    lru.get(&"a");
+    lru.record_miss(1);
+    lru.put("b", 2, 1);
+    let delta = lru.snapshot().delta(&before);
+    assert_eq!(delta.core().requests, 2);
+    assert_eq!(delta.core().cache_hits, 1);
+    assert_eq!(delta.core().cache_size_bytes, 2);
+
+    let mut slru: SlruCache<&str, i32> = make_slru(3, 1);
+    slru.put("a", 1, 1);
+    slru.get(&"a");
+    match slru.snapshot() {
+        MetricsSnapshot::Slru(metrics) => assert_eq!(metrics.total_promotions, 1),
+        other => panic!("expected SLRU snapshot, got {other:?}"),
+    }
+
+    assert!(matches!(
+        make_lfu::<i32, i32>(2).snapshot(),
+        MetricsSnapshot::Lfu(_)
+    ));
+    assert!(matches!(
+        make_lfuda::<i32, i32>(2).snapshot(),
+        MetricsSnapshot::Lfuda(_)
+    ));
+    assert!(matches!(
+        make_gdsf::<i32, i32>(2).snapshot(),
+        MetricsSnapshot::Gdsf(_)
+    ));
+
+    let config = LruCacheConfig {
+        capacity: NonZeroUsize::new(2).unwrap(),
+        max_size: u64::MAX,
+        weight_limits: None,
+    };
+    let disabled: LruCache<&str, i32, _, NoMetrics> = LruCache::init_without_metrics(config, None);
+    assert!(disabled.snapshot().is_disabled());
+}
+
+#[test]
+fn test_rolling_window_tracks_recent_requests() {
+    let mut lfu: LfuCache<i32, i32> = make_lfu(2);
+    lfu.set_metrics_window(Some(RollingWindow::last_requests(
+        NonZeroUsize::new(4).unwrap(),
+    )));
+    for key in 0..4 {
+        lfu.record_miss(1);
+        lfu.put(key, key, 1);
+    }
+    assert_eq!(lfu.metrics().get("window_hit_rate"), Some(&0.0));
+    assert_eq!(lfu.metrics().get("window_eviction_rate"), Some(&0.5));
+
+    for _ in 0..4 {
+        lfu.get(&3);
+    }
+    let metrics = lfu.metrics();
+    assert_eq!(metrics.get("window_hit_rate"), Some(&1.0));
+    assert_eq!(metrics.get("window_requests"), Some(&4.0));
+    assert_eq!(metrics.get("window_evictions"), Some(&0.0));
+    assert_eq!(metrics.get("hit_rate"), Some(&0.5));
+
+    lfu.set_metrics_window(None);
+    assert!(!lfu.metrics().contains_key("window_hit_rate"));
+}
+
+#[test]
+fn test_reset_metrics_keeps_gauges() {
+    let mut slru: SlruCache<&str, i32> = make_slru(3, 1);
+    slru.put("a", 1, 10);
+    slru.put("b", 2, 10);
+    slru.get(&"a");
+    slru.reset_metrics();
+
+    let MetricsSnapshot::Slru(metrics) = slru.snapshot() else {
+        panic!("expected SLRU snapshot");
+    };
+    assert_eq!(metrics.core.requests, 0);
+    assert_eq!(metrics.core.cache_hits, 0);
+    assert_eq!(metrics.core.bytes_written_to_cache, 0);
+    assert_eq!(metrics.total_promotions, 0);
+    assert_eq!(metrics.core.cache_size_bytes, 20);
+    assert_eq!(metrics.protected_size, 1);
+
+    // Size accounting keeps working after the reset
+    slru.remove(&"a");
+    slru.get(&"b");
+    let metrics = slru.metrics();
+    assert_eq!(metrics.get("cache_size_bytes"), Some(&10.0));
+    assert_eq!(metrics.get("hit_rate"), Some(&1.0));
+
+    let mut gdsf: GdsfCache<i32, i32> = make_gdsf(2);
+    gdsf.put(1, 1, 1);
+    gdsf.get(&1);
+    gdsf.reset_metrics();
+    assert_eq!(gdsf.metrics().get("requests"), Some(&0.0));
+    assert_eq!(gdsf.metrics().get("total_frequency"), Some(&0.0));
+}
+
+#[test]
+fn test_all_caches_record_timing_histograms() {
+    fn check(snapshot: MetricsSnapshot) {
+        let core = snapshot.core();
+        assert_eq!(core.eviction_age.count(), 1, "{snapshot:?}");
+        assert_eq!(core.eviction_idle.count(), 1, "{snapshot:?}");
+        assert_eq!(core.access_interval.count(), 2, "{snapshot:?}");
+        assert!(core.eviction_idle.max() <= core.eviction_age.max());
+        let metrics = snapshot.to_btreemap();
+        assert!(metrics.contains_key("eviction_age_p99_seconds"));
+        assert!(metrics.contains_key("access_interval_p50_seconds"));
+    }
+
+    // Two hits on key 1, then one eviction to make room for key 3
+    let mut lru: LruCache<i32, i32> = make_lru(2);
+    let mut lfu: LfuCache<i32, i32> = make_lfu(2);
+    let mut lfuda: LfudaCache<i32, i32> = make_lfuda(2);
+    let mut slru: SlruCache<i32, i32> = make_slru(2, 1);
+    let mut gdsf: GdsfCache<i32, i32> = make_gdsf(2);
+    for key in 1..=3 {
+        lru.put(key, key, 1);
+        lfu.put(key, key, 1);
+        lfuda.put(key, key, 1);
+        slru.put(key, key, 1);
+        gdsf.put(key, key, 1);
+        if key == 2 {
+            lru.get(&1);
+            lfu.get(&1);
+            lfuda.get(&1);
+            slru.get(&1);
+            gdsf.get(&1);
+            lru.get_mut(&1);
+            lfu.get_mut(&1);
+            lfuda.get_mut(&1);
+            slru.get_mut(&1);
+            gdsf.get_mut(&1);
+        }
+    }
+    check(lru.snapshot());
+    check(lfu.snapshot());
+    check(lfuda.snapshot());
+    check(slru.snapshot());
+    check(gdsf.snapshot());
+
+    lru.reset_metrics();
+    assert!(lru.snapshot().core().eviction_age.is_empty());
+    assert!(!lru.metrics().contains_key("eviction_age_p99_seconds"));
+}
+
+#[test]
+fn test_miss_ratio_curve_predicts_other_capacities() {
+    // An SLRU cache too small for a loop over 30 keys, estimating LRU
+    let mut slru: SlruCache<i32, i32> = make_slru(20, 10);
+    let capacity = NonZeroUsize::new(20).unwrap();
+    let max_samples = NonZeroUsize::new(1000).unwrap();
+    slru.set_miss_ratio_curve(Some(MissRatioCurve::new(capacity, max_samples)));
+    for _ in 0..10 {
+        for key in 0..30 {
+            if slru.get(&key).is_none() {
+                slru.put(key, key, 1);
+            }
+        }
+    }
+
+    let metrics = slru.metrics();
+    assert_eq!(metrics.get("mrc_sample_rate"), Some(&1.0));
+    assert_eq!(metrics.get("mrc_sampled_keys"), Some(&30.0));
+    assert_eq!(metrics.get("mrc_miss_ratio_1x"), Some(&1.0));
+    assert_eq!(metrics.get("mrc_miss_ratio_2x"), Some(&0.1));
+
+    let snapshot = slru.snapshot();
+    let curve = snapshot.core().miss_ratio_curve.as_ref().unwrap();
+    assert_eq!(curve.lookups(), 300.0);
+    assert_eq!(curve.miss_ratio(29), Some(1.0));
+    assert_eq!(curve.miss_ratio(30), Some(0.1));
+    assert_eq!(curve.curve().len(), 64);
+
+    slru.reset_metrics();
+    assert!(!slru.metrics().contains_key("mrc_miss_ratio_1x"));
+    slru.get(&29);
+    assert_eq!(slru.metrics().get("mrc_miss_ratio_2x"), Some(&0.0));
+
+    slru.set_miss_ratio_curve(None);
+    assert!(!slru.metrics().contains_key("mrc_sample_rate"));
+}
+
+#[test]
+fn test_all_caches_track_hot_keys() {
+    fn check(hot_keys: Option<&HotKeys<i32>>) {
+        let hot_keys = hot_keys.expect("tracker is set");
+        let by_accesses = hot_keys.top_by_accesses(2);
+        assert_eq!(by_accesses[0].key, 7);
+        assert_eq!(by_accesses[0].count, 20);
+        assert_eq!(by_accesses[1].key, 3);
+        assert_eq!(hot_keys.top_by_bytes(1)[0].key, 3);
+    }
+
+    // Key 7 is hit 20 times with 1 byte, key 3 twice with 100 bytes
+    let counters = NonZeroUsize::new(4).unwrap();
+    let mut lru: LruCache<i32, i32> = make_lru(10);
+    let mut lfu: LfuCache<i32, i32> = make_lfu(10);
+    let mut lfuda: LfudaCache<i32, i32> = make_lfuda(10);
+    let mut slru: SlruCache<i32, i32> = make_slru(10, 5);
+    let mut gdsf: GdsfCache<i32, i32> = make_gdsf(10);
+    lru.set_hot_keys(Some(HotKeys::new(counters)));
+    lfu.set_hot_keys(Some(HotKeys::new(counters)));
+    lfuda.set_hot_keys(Some(HotKeys::new(counters)));
+    slru.set_hot_keys(Some(HotKeys::new(counters)));
+    gdsf.set_hot_keys(Some(HotKeys::new(counters)));
+    for (key, size) in [(7, 1), (3, 100)] {
+        lru.put(key, key, size);
+        lfu.put(key, key, size);
+        lfuda.put(key, key, size);
+        slru.put(key, key, size);
+        gdsf.put(key, key, size);
+    }
+    for i in 0..22 {
+        let key = if i % 11 == 0 { 3 } else { 7 };
+        lru.get(&key);
+        lfu.get_mut(&key);
+        lfuda.get(&key);
+        slru.get_mut(&key);
+        gdsf.get(&key);
+    }
+    check(lru.hot_keys());
+    check(lfu.hot_keys());
+    check(lfuda.hot_keys());
+    check(slru.hot_keys());
+    check(gdsf.hot_keys());
+
+    // Misses and peeks are not counted
+    lru.get(&99);
+    lru.peek(&3);
+    assert_eq!(lru.hot_keys().unwrap().top_by_accesses(3).len(), 2);
+    lru.set_hot_keys(None);
+    assert!(lru.hot_keys().is_none());
+}
+
+/// Clock advanced by hand
+#[derive(Default)]
+struct ManualClock(AtomicU64);
+
+impl ManualClock {
+    fn advance(&self, duration: Duration) {
+        self.0
+            .fetch_add(duration.as_nanos() as u64, Ordering::Relaxed);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now_nanos(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+#[test]
+fn test_all_caches_cache_absent_keys() {
+    let clock = Arc::new(ManualClock::default());
+    let negative = || {
+        Some(NegativeCache::new(
+            NonZeroUsize::new(2).unwrap(),
+            clock.clone(),
+        ))
+    };
+    let mut lru: LruCache<i32, i32> = make_lru(2);
+    let mut lfu: LfuCache<i32, i32> = make_lfu(2);
+    let mut lfuda: LfudaCache<i32, i32> = make_lfuda(2);
+    let mut slru: SlruCache<i32, i32> = make_slru(2, 1);
+    let mut gdsf: GdsfCache<i32, i32> = make_gdsf(2);
+    lru.set_negative_cache(negative());
+    lfu.set_negative_cache(negative());
+    lfuda.set_negative_cache(negative());
+    slru.set_negative_cache(negative());
+    gdsf.set_negative_cache(negative());
+
+    // Negative entries use their own budget and never evict values
+    for (key, ttl) in [(10, 10), (11, 20), (12, 30)] {
+        lru.put(key - 10, key, 1);
+        lru.put_absent(key, Duration::from_secs(ttl));
+        lfu.put(key - 10, key, 1);
+        lfu.put_absent(key, Duration::from_secs(ttl));
+        lfuda.put(key - 10, key, 1);
+        lfuda.put_absent(key, Duration::from_secs(ttl));
+        slru.put(key - 10, key, 1);
+        slru.put_absent(key, Duration::from_secs(ttl));
+        gdsf.put(key - 10, key, 1);
+        gdsf.put_absent(key, Duration::from_secs(ttl));
+    }
+    clock.advance(Duration::from_secs(25));
+    let lookups = |lookup: &mut dyn FnMut(i32) -> Lookup<i32>| {
+        // Key 10 made room for key 12, key 11 has expired
+        [2, 10, 11, 12].map(lookup)
+    };
+    let expected = [Lookup::Hit(12), Lookup::Miss, Lookup::Miss, Lookup::Absent];
+    assert_eq!(lookups(&mut |key| lru.lookup(&key).map(|v| *v)), expected);
+    assert_eq!(lookups(&mut |key| lfu.lookup(&key).map(|v| *v)), expected);
+    assert_eq!(lookups(&mut |key| lfuda.lookup(&key).map(|v| *v)), expected);
+    assert_eq!(lookups(&mut |key| slru.lookup(&key).map(|v| *v)), expected);
+    assert_eq!(lookups(&mut |key| gdsf.lookup(&key)), expected);
+    assert_eq!(lru.len(), 2);
+    assert_eq!(lru.negative_cache().unwrap().len(), 1);
+
+    let core = lru.snapshot().core().clone();
+    assert_eq!((core.negative_hits, core.negative_misses), (1, 2));
+    assert_eq!(lru.metrics().get("negative_hits"), Some(&1.0));
+
+    // Putting a value drops the negative entry, marking a key absent drops
+    // its value
+    lru.put(12, 12, 1);
+    assert_eq!(lru.lookup(&12), Lookup::Hit(&12));
+    lru.put_absent(12, Duration::from_secs(10));
+    assert_eq!(lru.peek(&12), None);
+    assert_eq!(lru.lookup(&12), Lookup::Absent);
+    lru.remove(&12);
+    assert_eq!(lru.lookup(&12), Lookup::Miss);
+
+    // Without a negative cache nothing is remembered or counted
+    lru.set_negative_cache(None);
+    lru.put_absent(13, Duration::from_secs(10));
+    assert_eq!(lru.lookup(&13), Lookup::Miss);
+    assert_eq!(lru.snapshot().core().negative_misses, 3);
+}
+
+#[test]
+fn test_all_caches_demote_to_tiered_store() {
+    fn check<C>(l1: C)
+    where
+        C: PrimaryCache<Key = i32, Value = i32> + CacheMetrics,
+    {
+        let algorithm = l1.algorithm_name();
+        let l2 = MemoryStore::new(NonZeroUsize::new(100).unwrap(), u64::MAX);
+        let mut cache = TieredCache::new(l1, l2);
+        for key in 0..10 {
+            cache.put(key, key * 10, key as u64 + 1);
+        }
+        assert_eq!(cache.l1().len(), 3, "{algorithm}");
+        assert_eq!(cache.l2().len(), 7, "{algorithm}");
+        assert_eq!(cache.len(), 10, "{algorithm}");
+
+        // Demoted entries keep their sizes
+        let demoted = (0..10).find(|key| !cache.l1().contains(key)).unwrap();
+        let l2_size = cache.l2().current_size();
+        assert_eq!(
+            l2_size,
+            (0..10)
+                .filter(|key| !cache.l1().contains(key))
+                .map(|key| key + 1)
+                .sum::<i32>() as u64
+        );
+
+        // An L2 hit promotes the entry and demotes another one
+        assert_eq!(cache.get(&demoted), Some(&(demoted * 10)), "{algorithm}");
+        assert!(cache.l1().contains(&demoted), "{algorithm}");
+        assert!(!cache.l2().contains(&demoted), "{algorithm}");
+        assert_eq!(cache.l2().len(), 7, "{algorithm}");
+        assert_eq!(cache.get(&demoted), Some(&(demoted * 10)), "{algorithm}");
+        assert_eq!(cache.get(&99), None, "{algorithm}");
+
+        let metrics = cache.tier_metrics();
+        assert_eq!(metrics.l1_hits, 1, "{algorithm}");
+        assert_eq!(metrics.l2_hits, 1, "{algorithm}");
+        assert_eq!(metrics.misses, 1, "{algorithm}");
+        assert_eq!(metrics.demotions, 8, "{algorithm}");
+        assert_eq!(metrics.l2_entries, 7, "{algorithm}");
+
+        // Writing a key drops its stale L2 copy
+        let in_l2 = (0..10).find(|key| cache.l2().contains(key)).unwrap();
+        cache.put(in_l2, -1, 1);
+        assert!(!cache.l2().contains(&in_l2), "{algorithm}");
+        assert_eq!(cache.remove(&in_l2), Some(-1), "{algorithm}");
+        assert_eq!(cache.len(), 9, "{algorithm}");
+
+        let report = cache.metrics();
+        assert_eq!(report.get("tier_l2_hits"), Some(&1.0), "{algorithm}");
+        assert!(report.contains_key("cache_hits"), "{algorithm}");
+        cache.clear();
+        assert!(cache.is_empty(), "{algorithm}");
+    }
+
+    check(make_lru::<i32, i32>(3));
+    check(make_lfu::<i32, i32>(3));
+    check(make_lfuda::<i32, i32>(3));
+    check(make_slru::<i32, i32>(3, 1));
+    check(make_gdsf::<i32, i32>(3));
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn test_directory_store_spills_values_to_files() {
+    use cache_rs::tiered::DirectoryStore;
+
+    let dir = std::env::temp_dir().join(format!("cache-rs-tiered-{}", std::process::id()));
+    let mut store: DirectoryStore<String, Vec<u8>> =
+        DirectoryStore::new(&dir, NonZeroUsize::new(2).unwrap(), u64::MAX).unwrap();
+    let files =
+        |store: &DirectoryStore<String, Vec<u8>>| std::fs::read_dir(store.dir()).unwrap().count();
+    store.put("a".to_string(), b"alpha".to_vec(), 5);
+    store.put("b".to_string(), b"beta".to_vec(), 4);
+    store.put("a".to_string(), b"alpha2".to_vec(), 6);
+    assert_eq!(store.len(), 2);
+    assert_eq!(store.current_size(), 10);
+    assert_eq!(files(&store), 2);
+
+    // The least recently written file is deleted beyond capacity
+    store.put("c".to_string(), b"gamma".to_vec(), 5);
+    assert!(!store.contains(&"b".to_string()));
+    assert_eq!(files(&store), 2);
+
+    assert_eq!(store.take(&"a".to_string()), Some((b"alpha2".to_vec(), 6)));
+    assert_eq!(store.take(&"a".to_string()), None);
+    assert!(store.remove(&"c".to_string()));
+    assert_eq!(files(&store), 0);
+    assert_eq!(store.io_errors(), 0);
+
+    store.put("d".to_string(), b"delta".to_vec(), 5);
+    drop(store);
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+    std::fs::remove_dir(&dir).unwrap();
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn test_directory_stores_share_a_directory() {
+    use cache_rs::tiered::DirectoryStore;
+
+    let dir = std::env::temp_dir().join(format!("cache-rs-tiered-shared-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("other.entry"), b"not ours").unwrap();
+    let new_store = || -> DirectoryStore<String, Vec<u8>> {
+        DirectoryStore::new(&dir, NonZeroUsize::new(10).unwrap(), u64::MAX).unwrap()
+    };
+
+    let mut first = new_store();
+    first.put("a".to_string(), b"alpha".to_vec(), 5);
+    // Neither creating, clearing nor dropping a store touches other files
+    let mut second = new_store();
+    assert_ne!(first.dir(), second.dir());
+    second.put("a".to_string(), b"beta".to_vec(), 4);
+    second.clear();
+    drop(second);
+    assert_eq!(first.take(&"a".to_string()), Some((b"alpha".to_vec(), 5)));
+    drop(first);
+
+    assert_eq!(std::fs::read(dir.join("other.entry")).unwrap(), b"not ours");
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+/// Writes fake cgroup memory files into a fresh fixture directory
+#[cfg(feature = "std")]
+fn cgroup_fixture(name: &str, files: &[(&str, &str)]) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("cache-rs-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    for (file, contents) in files {
+        let path = dir.join(file);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    }
+    dir
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn test_cgroup_memory_sizes_configs() {
+    use cache_rs::memory::{CgroupMemory, CgroupVersion};
+    use std::io::ErrorKind;
+
+    let gdsf = GdsfCacheConfig {
+        capacity: NonZeroUsize::new(1000).unwrap(),
+        initial_age: 0.0,
+        max_size: 1 << 20,
+        weight_limits: None,
+    };
+
+    let v2 = cgroup_fixture(
+        "cgroup-v2",
+        &[
+            ("memory.max", "4294967296\n"),
+            ("memory.current", "1073741824\n"),
+        ],
+    );
+    let cgroup = CgroupMemory::at(&v2);
+    assert_eq!(cgroup.version().unwrap(), CgroupVersion::V2);
+    assert_eq!(cgroup.limit().unwrap(), Some(4 << 30));
+    assert_eq!(cgroup.usage().unwrap(), 1 << 30);
+    assert_eq!(cgroup.budget(0.5).unwrap(), Some(2 << 30));
+    assert_eq!(cgroup.size_config(gdsf, 0.25).unwrap().max_size, 1 << 30);
+
+    // Without a limit the configured size stays
+    std::fs::write(v2.join("memory.max"), "max\n").unwrap();
+    assert_eq!(cgroup.limit().unwrap(), None);
+    assert_eq!(cgroup.size_config(gdsf, 0.25).unwrap().max_size, 1 << 20);
+
+    let v1 = cgroup_fixture(
+        "cgroup-v1",
+        &[
+            ("memory/memory.limit_in_bytes", "536870912\n"),
+            ("memory/memory.usage_in_bytes", "1048576\n"),
+        ],
+    );
+    let cgroup = CgroupMemory::at(&v1);
+    assert_eq!(cgroup.version().unwrap(), CgroupVersion::V1);
+    assert_eq!(cgroup.usage().unwrap(), 1 << 20);
+    let lru = LruCacheConfig {
+        capacity: NonZeroUsize::new(1000).unwrap(),
+        max_size: 1 << 20,
+        weight_limits: None,
+    };
+    assert_eq!(cgroup.size_config(lru, 0.5).unwrap().max_size, 256 << 20);
+
+    // v1 reports a huge limit instead of `max`
+    std::fs::write(
+        v1.join("memory/memory.limit_in_bytes"),
+        "9223372036854771712\n",
+    )
+    .unwrap();
+    assert_eq!(cgroup.limit().unwrap(), None);
+    std::fs::write(v1.join("memory/memory.limit_in_bytes"), "lots\n").unwrap();
+    assert_eq!(cgroup.limit().unwrap_err().kind(), ErrorKind::InvalidData);
+
+    std::fs::remove_dir_all(&v1).unwrap();
+    assert_eq!(cgroup.limit().unwrap_err().kind(), ErrorKind::NotFound);
+    std::fs::remove_dir_all(&v2).unwrap();
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn test_memory_watcher_reports_watermark_crossings() {
+    use cache_rs::memory::{CgroupMemory, MemoryPressure, MemoryWatcher};
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    let dir = cgroup_fixture(
+        "cgroup-watcher",
+        &[("memory.max", "1000\n"), ("memory.current", "500\n")],
+    );
+    // Replaced whole, so the background thread never reads a partial file
+    let set_usage = |usage: u64| {
+        std::fs::write(dir.join("memory.current.new"), usage.to_string())?;
+        std::fs::rename(dir.join("memory.current.new"), dir.join("memory.current"))
+    };
+    let mut events = Vec::new();
+    let mut watcher = MemoryWatcher::new(CgroupMemory::at(&dir), 0.6, 0.9, |event| {
+        events.push((event.pressure, event.usage))
+    })
+    .unwrap();
+
+    // Usage between the watermarks only reports after crossing one
+    assert_eq!(watcher.poll().unwrap(), None);
+    set_usage(900).unwrap();
+    assert!(watcher.poll().unwrap().is_some());
+    set_usage(950).unwrap();
+    assert_eq!(watcher.poll().unwrap(), None);
+    set_usage(700).unwrap();
+    assert_eq!(watcher.poll().unwrap(), None);
+    assert!(watcher.is_high());
+    set_usage(600).unwrap();
+    assert!(watcher.poll().unwrap().is_some());
+    assert!(!watcher.is_high());
+    drop(watcher);
+    assert_eq!(
+        events,
+        vec![(MemoryPressure::High, 900), (MemoryPressure::Low, 600)]
+    );
+
+    // In the background, the hook runs on the watcher's thread
+    let (sender, receiver) = mpsc::channel();
+    let watcher = MemoryWatcher::new(CgroupMemory::at(&dir), 0.6, 0.9, move |event| {
+        let _ = sender.send(event.pressure);
+    })
+    .unwrap();
+    let handle = watcher.spawn(Duration::from_millis(5)).unwrap();
+    set_usage(990).unwrap();
+    let timeout = Duration::from_secs(10);
+    assert_eq!(receiver.recv_timeout(timeout), Ok(MemoryPressure::High));
+    set_usage(10).unwrap();
+    assert_eq!(receiver.recv_timeout(timeout), Ok(MemoryPressure::Low));
+    handle.stop().unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+/// Backing store for the loader tests, recording writes and deletions
+#[derive(Default)]
+struct RecordingStore {
+    data: std::cell::RefCell<std::collections::BTreeMap<i32, i32>>,
+    deleted: std::cell::RefCell<Vec<i32>>,
+    fail: std::cell::Cell<bool>,
+}
+
+impl CacheWriter<i32, i32> for RecordingStore {
+    type Error = &'static str;
+
+    fn write(&self, key: &i32, value: &i32) -> Result<(), &'static str> {
+        if self.fail.get() {
+            return Err("store unavailable");
+        }
+        self.data.borrow_mut().insert(*key, *value);
+        Ok(())
+    }
+
+    fn delete(&self, key: &i32) -> Result<(), &'static str> {
+        if self.fail.get() {
+            return Err("store unavailable");
+        }
+        self.data.borrow_mut().remove(key);
+        self.deleted.borrow_mut().push(*key);
+        Ok(())
+    }
+}
+
+/// Loader of `key * 10` for non-negative keys, counting calls
+#[derive(Default)]
+struct CountingLoader {
+    loads: std::cell::Cell<usize>,
+    batches: std::cell::Cell<usize>,
+}
+
+impl CacheLoader<i32, i32> for CountingLoader {
+    type Error = &'static str;
+
+    fn load(&self, key: &i32) -> Result<Option<(i32, u64)>, &'static str> {
+        self.loads.set(self.loads.get() + 1);
+        Ok((*key >= 0).then_some((key * 10, 1)))
+    }
+
+    fn load_all(&self, keys: &[i32]) -> Result<Vec<Option<(i32, u64)>>, &'static str> {
+        self.batches.set(self.batches.get() + 1);
+        keys.iter().map(|key| self.load(key)).collect()
+    }
+}
+
+#[test]
+fn test_loading_cache_reads_through() {
+    let mut cache = LoadingCache::new(make_lru::<i32, i32>(3), CountingLoader::default());
+    assert_eq!(cache.get(&1), Ok(Some(&10)));
+    assert_eq!(cache.get(&1), Ok(Some(&10)));
+    assert_eq!(cache.loader().loads.get(), 1);
+    assert_eq!(cache.get(&-1), Ok(None));
+    assert!(!cache.cache().contains(&-1));
+    assert_eq!(cache.cache().metrics().get("cache_misses"), Some(&1.0));
+
+    // Only the missed keys are loaded, in a single batch
+    assert_eq!(
+        cache.get_all(&[1, 2, -5, 3]),
+        Ok(vec![Some(10), Some(20), None, Some(30)])
+    );
+    assert_eq!(cache.loader().batches.get(), 1);
+    assert_eq!(cache.loader().loads.get(), 5);
+    assert_eq!(cache.get_all(&[2, 3]), Ok(vec![Some(20), Some(30)]));
+    assert_eq!(cache.loader().batches.get(), 1);
+
+    let failing = |_: &i32| Err::<Option<(i32, u64)>, _>("source down");
+    let mut cache = LoadingCache::new(make_lfu::<i32, i32>(3), failing);
+    assert_eq!(cache.get(&1), Err("source down"));
+}
+
+#[test]
+fn test_loading_cache_writes_through() {
+    let mut cache = LoadingCache::new(make_lru::<i32, i32>(2), CountingLoader::default())
+        .with_writer(RecordingStore::default(), WriteMode::WriteThrough);
+    cache.put(1, 100, 1).unwrap();
+    assert_eq!(cache.writer().data.borrow().get(&1), Some(&100));
+    assert_eq!(cache.get(&1), Ok(Some(&100)));
+    assert_eq!(cache.dirty_len(), 0);
+
+    // A failed write is not cached
+    cache.writer().fail.set(true);
+    assert_eq!(cache.put(2, 200, 1), Err("store unavailable"));
+    assert!(!cache.cache().contains(&2));
+    assert_eq!(cache.remove(&1), Err("store unavailable"));
+    assert!(cache.cache().contains(&1));
+
+    cache.writer().fail.set(false);
+    assert_eq!(cache.remove(&1), Ok(Some(100)));
+    assert_eq!(*cache.writer().deleted.borrow(), vec![1]);
+    assert!(cache.writer().data.borrow().is_empty());
+}
+
+#[test]
+fn test_loading_cache_writes_back_on_eviction_and_flush() {
+    let mut cache = LoadingCache::new(make_lru::<i32, i32>(2), CountingLoader::default())
+        .with_writer(RecordingStore::default(), WriteMode::WriteBack);
+    cache.put(1, 100, 1).unwrap();
+    cache.put(2, 200, 1).unwrap();
+    assert!(cache.writer().data.borrow().is_empty());
+    assert!(cache.is_dirty(&1));
+
+    // Evicting a dirty entry writes it; loaded entries are clean
+    assert_eq!(cache.get(&3), Ok(Some(&30)));
+    assert_eq!(*cache.writer().data.borrow(), [(1, 100)].into());
+    assert!(!cache.is_dirty(&3));
+    cache.get(&4).unwrap();
+    assert_eq!(cache.writer().data.borrow().len(), 2);
+    cache.get(&5).unwrap();
+    assert_eq!(cache.writer().data.borrow().len(), 2);
+
+    // Failed writes of evicted entries are retried by flush()
+    cache.put(6, 600, 1).unwrap();
+    cache.writer().fail.set(true);
+    cache.put(7, 700, 1).unwrap();
+    cache.put(8, 800, 1).unwrap();
+    assert!(cache.is_dirty(&6));
+    assert_eq!(cache.dirty_len(), 3);
+    assert_eq!(cache.flush(), Err("store unavailable"));
+    assert_eq!(cache.dirty_len(), 3);
+
+    cache.writer().fail.set(false);
+    assert_eq!(cache.flush(), Ok(3));
+    assert_eq!(cache.dirty_len(), 0);
+    assert_eq!(cache.flush(), Ok(0));
+    let data = cache.writer().data.borrow();
+    assert_eq!(
+        (data.get(&6), data.get(&7), data.get(&8)),
+        (Some(&600), Some(&700), Some(&800))
+    );
+}
+
+#[test]
+fn test_loading_cache_flushes_the_latest_write() {
+    let mut cache = LoadingCache::new(make_lru::<i32, i32>(2), CountingLoader::default())
+        .with_writer(RecordingStore::default(), WriteMode::WriteBack);
+    cache.writer().fail.set(true);
+    cache.put(1, 100, 1).unwrap();
+    cache.put(2, 200, 1).unwrap();
+
+    // 1 is evicted and its write fails; reads see it instead of loading
+    cache.put(3, 300, 1).unwrap();
+    assert!(!cache.cache().contains(&1));
+    assert_eq!(cache.get(&1), Ok(Some(&100)));
+    assert_eq!(cache.get_all(&[1, 3]), Ok(vec![Some(100), Some(300)]));
+    assert_eq!(cache.loader().loads.get(), 0);
+
+    // Putting 1 again supersedes the failed write
+    cache.put(1, 111, 1).unwrap();
+    assert_eq!(cache.dirty_len(), 3);
+    cache.writer().fail.set(false);
+    assert_eq!(cache.flush(), Ok(3));
+    let data = cache.writer().data.borrow();
+    assert_eq!(
+        (data.get(&1), data.get(&2), data.get(&3)),
+        (Some(&111), Some(&200), Some(&300))
+    );
+}
+
 // ============================================================================
 // LRU ITER / ITER_MUT COVERAGE
 // ============================================================================
@@ -2602,6 +3373,93 @@ fn test_gdsf_global_age_increases_on_eviction() {
     );
 }
 
+#[test]
+fn test_gdsf_weighs_priority_by_cost() {
+    let mut cache: GdsfCache<&str, i32> = make_gdsf(2);
+    cache.put_with_cost("remote", 1, 10, 100);
+    cache.put("local", 2, 1);
+    for _ in 0..3 {
+        cache.get(&"local");
+    }
+
+    // remote: 1 * 100 / 10 = 10 outranks local: 4 * 1 / 1 = 4
+    assert_eq!(cache.put("other", 3, 1), Some(vec![("local", 2)]));
+    cache.get(&"remote");
+    cache.record_miss_with_cost(10, 50);
+
+    let metrics = cache.metrics();
+    assert_eq!(metrics.get("cost_saved"), Some(&103.0));
+    assert_eq!(metrics.get("cost_missed"), Some(&50.0));
+    assert_eq!(metrics.get("cost_evicted"), Some(&1.0));
+    assert_eq!(metrics.get("cost_hit_rate"), Some(&(103.0 / 153.0)));
+}
+
+#[test]
+fn test_gdsf_put_keeps_cost() {
+    let mut cache: GdsfCache<&str, i32> = make_gdsf(2);
+    cache.put_with_cost("remote", 1, 10, 100);
+    cache.put("local", 2, 1);
+    for _ in 0..3 {
+        cache.get(&"local");
+    }
+
+    // Replacing the value keeps its cost: 1 * 100 / 10 = 10 still outranks 4
+    cache.put("remote", 10, 10);
+    assert_eq!(cache.put("other", 3, 1), Some(vec![("local", 2)]));
+    cache.get(&"remote");
+    assert_eq!(cache.metrics().get("cost_saved"), Some(&103.0));
+}
+
+#[test]
+fn test_gdsf_greedy_dual_size_ignores_frequency() {
+    for (mode, victim) in [(GdsfMode::Gdsf, "b"), (GdsfMode::GreedyDualSize, "a")] {
+        let mut cache: GdsfCache<&str, i32> = make_gdsf(2);
+        cache.set_mode(mode);
+        assert_eq!(cache.mode(), mode);
+        cache.put("a", 1, 1);
+        cache.put_with_cost("b", 2, 1, 2);
+        for _ in 0..5 {
+            cache.get(&"a");
+        }
+
+        let evicted = cache.put("c", 3, 1).unwrap();
+        assert_eq!(evicted[0].0, victim, "{mode:?}");
+    }
+}
+
+#[test]
+fn test_lfuda_weighs_priority_by_cost() {
+    let mut cache: LfudaCache<&str, i32> = make_lfuda(2);
+    cache.put_with_cost("remote", 1, 1, 10);
+    cache.put("local", 2, 1);
+    for _ in 0..3 {
+        cache.get(&"local");
+    }
+
+    // remote: 1 * 10 outranks local: 4 * 1
+    assert_eq!(cache.put("other", 3, 1), Some(vec![("local", 2)]));
+
+    // Raising the cost of a cached entry raises its priority
+    cache.put_with_cost("other", 4, 1, 100);
+    assert_eq!(cache.put("next", 5, 1), Some(vec![("remote", 1)]));
+    assert_eq!(cache.get(&"other"), Some(&4));
+}
+
+#[test]
+fn test_lfuda_put_keeps_cost() {
+    let mut cache: LfudaCache<&str, i32> = make_lfuda(2);
+    cache.put_with_cost("remote", 1, 1, 10);
+    cache.put("local", 2, 1);
+    for _ in 0..3 {
+        cache.get(&"local");
+    }
+
+    // Replacing the value keeps its cost: 1 * 10 still outranks 4 * 1
+    cache.put("remote", 10, 1);
+    assert_eq!(cache.put("other", 3, 1), Some(vec![("local", 2)]));
+    assert_eq!(cache.get(&"remote"), Some(&10));
+}
+
 #[test]
 fn test_slru_protected_max_size() {
     let cache: SlruCache<&str, i32> = make_slru(10, 4);
@@ -2610,3 +3468,623 @@ fn test_slru_protected_max_size() {
     let cache2: SlruCache<&str, i32> = make_slru(100, 25);
     assert_eq!(cache2.protected_max_size().get(), 25);
 }
+
+/// Policies that put every new entry in a known stage, whatever the clock
+fn policy_for(stage: Freshness) -> RefreshPolicy {
+    match stage {
+        Freshness::Fresh => RefreshPolicy::new(Duration::MAX, Duration::MAX),
+        Freshness::Stale => RefreshPolicy::new(Duration::ZERO, Duration::MAX),
+        Freshness::Grace => {
+            RefreshPolicy::new(Duration::ZERO, Duration::ZERO).with_grace(Duration::MAX)
+        }
+        Freshness::Expired => RefreshPolicy::new(Duration::ZERO, Duration::ZERO),
+    }
+}
+
+#[test]
+fn test_all_caches_report_freshness() {
+    let mut lru: LruCache<i32, i32> = make_lru(4);
+    let mut lfu: LfuCache<i32, i32> = make_lfu(4);
+    let mut lfuda: LfudaCache<i32, i32> = make_lfuda(4);
+    let mut slru: SlruCache<i32, i32> = make_slru(4, 2);
+    let mut gdsf: GdsfCache<i32, i32> = make_gdsf(4);
+    for key in 1..=2 {
+        lru.put(key, key, 1);
+        lfu.put(key, key, 1);
+        lfuda.put(key, key, 1);
+        slru.put(key, key, 1);
+        gdsf.put(key, key, 1);
+    }
+
+    // Without a policy every value is fresh
+    assert_eq!(lru.get_fresh(&1), Some((&1, Freshness::Fresh)));
+    assert_eq!(gdsf.get_fresh(&1), Some((1, Freshness::Fresh)));
+
+    for stage in [Freshness::Fresh, Freshness::Stale, Freshness::Grace] {
+        let policy = Some(policy_for(stage));
+        lru.set_refresh_policy(policy);
+        lfu.set_refresh_policy(policy);
+        lfuda.set_refresh_policy(policy);
+        slru.set_refresh_policy(policy);
+        gdsf.set_refresh_policy(policy);
+        assert_eq!(lru.refresh_policy(), policy);
+        assert_eq!(lru.get_fresh(&1), Some((&1, stage)));
+        assert_eq!(lfu.get_fresh(&1), Some((&1, stage)));
+        assert_eq!(lfuda.get_fresh(&1), Some((&1, stage)));
+        assert_eq!(slru.get_fresh(&1), Some((&1, stage)));
+        assert_eq!(gdsf.get_fresh(&1), Some((1, stage)));
+        assert_eq!(lru.get_fresh(&3), None);
+    }
+
+    // Expired values are removed
+    let policy = Some(policy_for(Freshness::Expired));
+    lru.set_refresh_policy(policy);
+    lfu.set_refresh_policy(policy);
+    lfuda.set_refresh_policy(policy);
+    slru.set_refresh_policy(policy);
+    gdsf.set_refresh_policy(policy);
+    assert_eq!(lru.get_fresh(&2), None);
+    assert_eq!(lfu.get_fresh(&2), None);
+    assert_eq!(lfuda.get_fresh(&2), None);
+    assert_eq!(slru.get_fresh(&2), None);
+    assert_eq!(gdsf.get_fresh(&2), None);
+    assert_eq!(
+        [lru.len(), lfu.len(), lfuda.len(), slru.len(), gdsf.len()],
+        [1; 5]
+    );
+
+    // Other lookups ignore the policy
+    assert_eq!(lru.get(&1), Some(&1));
+    assert_eq!(gdsf.get(&1), Some(1));
+}
+
+/// Loader of `key * 100 + version` for non-negative keys, failing on demand
+#[derive(Default)]
+struct VersionedLoader {
+    version: std::cell::Cell<i32>,
+    fail: std::cell::Cell<bool>,
+    loads: std::cell::Cell<usize>,
+}
+
+impl CacheLoader<i32, i32> for VersionedLoader {
+    type Error = &'static str;
+
+    fn load(&self, key: &i32) -> Result<Option<(i32, u64)>, &'static str> {
+        self.loads.set(self.loads.get() + 1);
+        if self.fail.get() {
+            return Err("source down");
+        }
+        Ok((*key >= 0).then_some((key * 100 + self.version.get(), 1)))
+    }
+}
+
+#[test]
+fn test_loading_cache_refreshes_stale_values() {
+    let mut cache = LoadingCache::new(make_lru::<i32, i32>(3), VersionedLoader::default())
+        .with_refresh(policy_for(Freshness::Stale));
+    assert_eq!(cache.get(&1), Ok(Some(&100)));
+    assert_eq!(cache.loader().loads.get(), 1);
+
+    // Every value is stale at once, so each get refreshes it
+    cache.loader().version.set(1);
+    assert_eq!(cache.get(&1), Ok(Some(&101)));
+    assert_eq!(cache.loader().loads.get(), 2);
+
+    // A failed refresh serves the stale value and is not retried
+    cache.loader().fail.set(true);
+    assert_eq!(cache.get(&1), Ok(Some(&101)));
+    assert_eq!(cache.get(&1), Ok(Some(&101)));
+    assert_eq!(cache.loader().loads.get(), 3);
+
+    // Replacing the value allows refreshing it again
+    cache.loader().fail.set(false);
+    cache.put(1, 7, 1).unwrap();
+    assert_eq!(cache.get(&1), Ok(Some(&101)));
+    assert_eq!(cache.loader().loads.get(), 4);
+
+    // A value the loader no longer has is dropped
+    cache.put(-1, 7, 1).unwrap();
+    assert_eq!(cache.get(&-1), Ok(None));
+    assert!(!cache.cache().contains(&-1));
+    assert_eq!(cache.loader().loads.get(), 5);
+
+    // Within the grace window, expired values are served while the refresh
+    // fails
+    let mut cache = LoadingCache::new(make_lfu::<i32, i32>(3), VersionedLoader::default())
+        .with_refresh(policy_for(Freshness::Grace));
+    assert_eq!(cache.get(&2), Ok(Some(&200)));
+    cache.loader().fail.set(true);
+    assert_eq!(cache.get(&2), Ok(Some(&200)));
+    assert_eq!(cache.get(&2), Ok(Some(&200)));
+    assert_eq!(cache.loader().loads.get(), 2);
+
+    // Past it, they are loaded like misses
+    let mut cache = LoadingCache::new(make_slru::<i32, i32>(3, 1), VersionedLoader::default())
+        .with_refresh(policy_for(Freshness::Expired));
+    assert_eq!(cache.get(&3), Ok(Some(&300)));
+    cache.loader().version.set(1);
+    assert_eq!(cache.get(&3), Ok(Some(&301)));
+    cache.loader().fail.set(true);
+    assert_eq!(cache.get(&3), Err("source down"));
+    assert!(!cache.cache().contains(&3));
+
+    // Dirty entries are newer than the store and never refreshed
+    let mut cache = LoadingCache::new(make_lru::<i32, i32>(3), VersionedLoader::default())
+        .with_writer(RecordingStore::default(), WriteMode::WriteBack)
+        .with_refresh(policy_for(Freshness::Stale));
+    cache.put(4, 4, 1).unwrap();
+    assert_eq!(cache.get(&4), Ok(Some(&4)));
+    assert_eq!(cache.loader().loads.get(), 0);
+}
+
+#[test]
+fn test_all_caches_age_entries_with_clock() {
+    let mut lru: LruCache<i32, i32> = make_lru(4);
+    let mut lfu: LfuCache<i32, i32> = make_lfu(4);
+    let mut lfuda: LfudaCache<i32, i32> = make_lfuda(4);
+    let mut slru: SlruCache<i32, i32> = make_slru(4, 2);
+    let mut gdsf: GdsfCache<i32, i32> = make_gdsf(4);
+    let clock = Arc::new(ManualClock::default());
+    let policy = Some(RefreshPolicy::new(
+        Duration::from_secs(10),
+        Duration::from_secs(20),
+    ));
+    lru.set_clock(Some(clock.clone()));
+    lfu.set_clock(Some(clock.clone()));
+    lfuda.set_clock(Some(clock.clone()));
+    slru.set_clock(Some(clock.clone()));
+    gdsf.set_clock(Some(clock.clone()));
+    assert!(lru.clock().is_some());
+    lru.set_refresh_policy(policy);
+    lfu.set_refresh_policy(policy);
+    lfuda.set_refresh_policy(policy);
+    slru.set_refresh_policy(policy);
+    gdsf.set_refresh_policy(policy);
+    lru.put(1, 1, 1);
+    lfu.put(1, 1, 1);
+    lfuda.put(1, 1, 1);
+    slru.put(1, 1, 1);
+    gdsf.put(1, 1, 1);
+
+    // Entries only age as the clock advances, with or without std
+    assert_eq!(lru.get_fresh(&1), Some((&1, Freshness::Fresh)));
+    clock.advance(Duration::from_secs(10));
+    assert_eq!(lru.get_fresh(&1), Some((&1, Freshness::Stale)));
+    assert_eq!(lfu.get_fresh(&1), Some((&1, Freshness::Stale)));
+    assert_eq!(lfuda.get_fresh(&1), Some((&1, Freshness::Stale)));
+    assert_eq!(slru.get_fresh(&1), Some((&1, Freshness::Stale)));
+    assert_eq!(gdsf.get_fresh(&1), Some((1, Freshness::Stale)));
+
+    // A replaced value is stamped with the clock's time
+    lru.put(1, 2, 1);
+    assert_eq!(lru.get_fresh(&1), Some((&2, Freshness::Fresh)));
+
+    clock.advance(Duration::from_secs(10));
+    assert_eq!(lru.get_fresh(&1), Some((&2, Freshness::Stale)));
+    assert_eq!(lfu.get_fresh(&1), None);
+    assert_eq!(lfuda.get_fresh(&1), None);
+    assert_eq!(slru.get_fresh(&1), None);
+    assert_eq!(gdsf.get_fresh(&1), None);
+}
+
+#[test]
+fn test_loading_cache_refreshes_ahead_with_clock() {
+    let clock = Arc::new(ManualClock::default());
+    let mut lru = make_lru::<i32, i32>(3);
+    lru.set_clock(Some(clock.clone()));
+    let mut cache = LoadingCache::new(lru, VersionedLoader::default()).with_refresh(
+        RefreshPolicy::new(Duration::from_secs(10), Duration::from_secs(20)),
+    );
+    assert_eq!(cache.get(&1), Ok(Some(&100)));
+    cache.loader().version.set(1);
+    assert_eq!(cache.get(&1), Ok(Some(&100)));
+    assert_eq!(cache.loader().loads.get(), 1);
+
+    clock.advance(Duration::from_secs(10));
+    assert_eq!(cache.get(&1), Ok(Some(&101)));
+    assert_eq!(cache.get(&1), Ok(Some(&101)));
+    assert_eq!(cache.loader().loads.get(), 2);
+}
+
+/// Random source returning the same number forever
+struct FixedRandom(u64);
+
+impl RandomSource for FixedRandom {
+    fn next_u64(&self) -> u64 {
+        self.0
+    }
+}
+
+#[test]
+fn test_all_caches_expire_early_with_xfetch() {
+    let mut lru: LruCache<i32, i32> = make_lru(4);
+    let mut lfu: LfuCache<i32, i32> = make_lfu(4);
+    let mut lfuda: LfudaCache<i32, i32> = make_lfuda(4);
+    let mut slru: SlruCache<i32, i32> = make_slru(4, 2);
+    let mut gdsf: GdsfCache<i32, i32> = make_gdsf(4);
+    let policy = Some(policy_for(Freshness::Fresh));
+    lru.set_refresh_policy(policy);
+    lfu.set_refresh_policy(policy);
+    lfuda.set_refresh_policy(policy);
+    slru.set_refresh_policy(policy);
+    gdsf.set_refresh_policy(policy);
+    for key in 1..=2 {
+        lru.put(key, key, 1);
+        lfu.put(key, key, 1);
+        lfuda.put(key, key, 1);
+        slru.put(key, key, 1);
+        gdsf.put(key, key, 1);
+    }
+
+    // With a recompute cost far above the TTL, only the largest sample
+    // keeps a value fresh
+    let xfetch = |sample| Some(XFetch::new(Duration::MAX, Arc::new(FixedRandom(sample))));
+    lru.set_xfetch(xfetch(u64::MAX));
+    lfu.set_xfetch(xfetch(u64::MAX));
+    lfuda.set_xfetch(xfetch(u64::MAX));
+    slru.set_xfetch(xfetch(u64::MAX));
+    gdsf.set_xfetch(xfetch(u64::MAX));
+    assert_eq!(lru.xfetch().map(|x| x.recompute_cost), Some(Duration::MAX));
+    assert_eq!(lru.get_fresh(&1), Some((&1, Freshness::Fresh)));
+    assert_eq!(lfu.get_fresh(&1), Some((&1, Freshness::Fresh)));
+    assert_eq!(lfuda.get_fresh(&1), Some((&1, Freshness::Fresh)));
+    assert_eq!(slru.get_fresh(&1), Some((&1, Freshness::Fresh)));
+    assert_eq!(gdsf.get_fresh(&1), Some((1, Freshness::Fresh)));
+
+    lru.set_xfetch(xfetch(0));
+    lfu.set_xfetch(xfetch(0));
+    lfuda.set_xfetch(xfetch(0));
+    slru.set_xfetch(xfetch(0));
+    gdsf.set_xfetch(xfetch(0));
+    // Values expired early are reported stale to that caller but stay cached
+    assert_eq!(lru.get_fresh(&1), Some((&1, Freshness::Stale)));
+    assert_eq!(lfu.get_fresh(&1), Some((&1, Freshness::Stale)));
+    assert_eq!(lfuda.get_fresh(&1), Some((&1, Freshness::Stale)));
+    assert_eq!(slru.get_fresh(&1), Some((&1, Freshness::Stale)));
+    assert_eq!(gdsf.get_fresh(&1), Some((1, Freshness::Stale)));
+    assert_eq!(
+        [lru.len(), lfu.len(), lfuda.len(), slru.len(), gdsf.len()],
+        [2; 5]
+    );
+    lru.set_xfetch(xfetch(u64::MAX));
+    assert_eq!(lru.get_fresh(&1), Some((&1, Freshness::Fresh)));
+
+    // Without a refresh policy there is no TTL to bring forward
+    lru.set_refresh_policy(None);
+    assert_eq!(lru.get_fresh(&2), Some((&2, Freshness::Fresh)));
+}
+
+#[test]
+fn test_loading_cache_reloads_early_with_xfetch() {
+    // Recomputing takes as long as the TTL, so each get reloads the value
+    // with probability exp(-1); a seeded generator makes this reproducible
+    let xfetch = || {
+        XFetch::new(
+            Duration::from_nanos(u64::MAX),
+            Arc::new(SplitMix64::new(42)),
+        )
+    };
+    let loads = |cache: &mut LoadingCache<LruCache<i32, i32>, VersionedLoader>| {
+        for _ in 0..100 {
+            cache.get(&1).unwrap();
+        }
+        cache.loader().loads.get()
+    };
+    let mut cache = LoadingCache::new(make_lru::<i32, i32>(3), VersionedLoader::default())
+        .with_refresh(policy_for(Freshness::Fresh))
+        .with_xfetch(xfetch());
+    let first = loads(&mut cache);
+    assert!((20..60).contains(&first));
+
+    let mut cache = LoadingCache::new(make_lru::<i32, i32>(3), VersionedLoader::default())
+        .with_refresh(policy_for(Freshness::Fresh))
+        .with_xfetch(xfetch());
+    assert_eq!(loads(&mut cache), first);
+}
+
+#[test]
+fn test_all_caches_invalidate_tags() {
+    let mut lru: LruCache<i32, i32> = make_lru(4);
+    let mut lfu: LfuCache<i32, i32> = make_lfu(4);
+    let mut lfuda: LfudaCache<i32, i32> = make_lfuda(4);
+    let mut slru: SlruCache<i32, i32> = make_slru(4, 2);
+    let mut gdsf: GdsfCache<i32, i32> = make_gdsf(4);
+    // An untagged put drops the key's tags
+    let puts: [(i32, &[u64]); 4] = [(1, &[10]), (2, &[10, 20]), (3, &[20]), (3, &[])];
+    for (key, tags) in puts {
+        lru.put_tagged(key, key, 1, tags);
+        lfu.put_tagged(key, key, 1, tags);
+        lfuda.put_tagged(key, key, 1, tags);
+        slru.put_tagged(key, key, 1, tags);
+        gdsf.put_tagged(key, key, 1, tags);
+    }
+    assert_eq!(
+        [
+            lru.invalidate_tag(20),
+            lfu.invalidate_tag(20),
+            lfuda.invalidate_tag(20),
+            slru.invalidate_tag(20),
+            gdsf.invalidate_tag(20),
+        ],
+        [1; 5]
+    );
+
+    // Removed entries leave the index
+    lru.put_tagged(4, 4, 1, &[10]);
+    lfu.put_tagged(4, 4, 1, &[10]);
+    lfuda.put_tagged(4, 4, 1, &[10]);
+    slru.put_tagged(4, 4, 1, &[10]);
+    gdsf.put_tagged(4, 4, 1, &[10]);
+    lru.remove(&4);
+    lfu.remove(&4);
+    lfuda.remove(&4);
+    slru.remove(&4);
+    gdsf.remove(&4);
+    assert_eq!(
+        [
+            lru.invalidate_tag(10),
+            lfu.invalidate_tag(10),
+            lfuda.invalidate_tag(10),
+            slru.invalidate_tag(10),
+            gdsf.invalidate_tag(10),
+        ],
+        [1; 5]
+    );
+    assert_eq!(lru.invalidate_tag(10), 0);
+    assert_eq!(
+        [lru.len(), lfu.len(), lfuda.len(), slru.len(), gdsf.len()],
+        [1; 5]
+    );
+    assert_eq!(lru.tags(&3), &[] as &[u64]);
+
+    // Evicted entries leave the index, so invalidating their tag does not
+    // touch the entries that replaced them
+    let mut lru: LruCache<i32, i32> = make_lru(2);
+    lru.put_tagged(1, 1, 1, &[7]);
+    lru.put_tagged(2, 2, 1, &[7, 8]);
+    assert_eq!(lru.tags(&2), &[7, 8]);
+    lru.put(3, 3, 1);
+    lru.put(1, 1, 1);
+    assert_eq!(lru.tags(&1), &[] as &[u64]);
+    assert_eq!(lru.invalidate_tag(7), 0);
+    assert_eq!(lru.len(), 2);
+
+    // Invalidations are recorded as removals
+    lru.put_tagged(4, 4, 5, &[9]);
+    assert_eq!(lru.invalidate_tag(9), 1);
+    assert_eq!(lru.current_size(), 1);
+    lru.clear();
+    lru.put(4, 4, 1);
+    assert_eq!(lru.invalidate_tag(9), 0);
+}
+
+#[test]
+fn test_all_caches_evict_until_weights_fit() {
+    let limits = WeightLimits::new()
+        .with_dimension("handles", 3)
+        .with_dimension("upload", 100);
+    let mut lru: LruCache<i32, i32> = make_lru(10);
+    let mut lfu: LfuCache<i32, i32> = make_lfu(10);
+    let mut lfuda: LfudaCache<i32, i32> = make_lfuda(10);
+    let mut slru: SlruCache<i32, i32> = make_slru(10, 5);
+    let mut gdsf: GdsfCache<i32, i32> = make_gdsf(10);
+    lru.set_weight_limits(Some(limits));
+    lfu.set_weight_limits(Some(limits));
+    lfuda.set_weight_limits(Some(limits));
+    slru.set_weight_limits(Some(limits));
+    gdsf.set_weight_limits(Some(limits));
+
+    // The fourth entry exceeds the upload limit and the fifth the handles
+    // limit, each evicting one entry
+    let puts = [
+        (1, [1, 40]),
+        (2, [1, 40]),
+        (3, [1, 10]),
+        (4, [0, 30]),
+        (5, [2, 0]),
+    ];
+    let mut evicted = [0; 5];
+    for (key, weights) in puts {
+        let weights = Weights::of(&weights);
+        let count = |evicted: Option<Vec<(i32, i32)>>| evicted.map_or(0, |e| e.len());
+        evicted[0] += count(lru.put_weighted(key, key, 1, weights));
+        evicted[1] += count(lfu.put_weighted(key, key, 1, weights));
+        evicted[2] += count(lfuda.put_weighted(key, key, 1, weights));
+        evicted[3] += count(slru.put_weighted(key, key, 1, weights));
+        evicted[4] += count(gdsf.put_weighted(key, key, 1, weights));
+    }
+    assert_eq!(evicted, [2; 5]);
+    assert_eq!(
+        [lru.len(), lfu.len(), lfuda.len(), slru.len(), gdsf.len()],
+        [3; 5]
+    );
+    for metrics in [
+        lru.metrics(),
+        lfu.metrics(),
+        lfuda.metrics(),
+        slru.metrics(),
+        gdsf.metrics(),
+    ] {
+        assert_eq!(metrics.get("weight_handles_limit"), Some(&3.0));
+        assert!(metrics["weight_handles_used"] <= 3.0);
+        assert!(metrics["weight_upload_used"] <= 100.0);
+        assert_eq!(
+            metrics["weight_handles_evictions"] + metrics["weight_upload_evictions"],
+            2.0
+        );
+        assert_eq!(metrics.get("evictions"), Some(&2.0));
+    }
+    assert!(!lru.contains(&1) && !lru.contains(&2));
+    assert_eq!(lru.weights(&5), Weights::of(&[2, 0]));
+    let core = lru.snapshot().core().clone();
+    let usage: Vec<_> = core
+        .weights
+        .iter()
+        .map(|d| (d.name, d.used, d.evictions))
+        .collect();
+    assert_eq!(usage, [("handles", 3, 1), ("upload", 40, 1)]);
+
+    // Replacing with put() keeps the weights, removing releases them
+    lru.put(3, 3, 1);
+    lru.remove(&4);
+    assert_eq!(lru.weights(&3), Weights::of(&[1, 10]));
+    assert_eq!(lru.metrics().get("weight_handles_used"), Some(&3.0));
+    assert_eq!(lru.metrics().get("weight_upload_used"), Some(&10.0));
+
+    // An entry over a limit on its own is cached alone
+    let evicted = lru.put_weighted(6, 6, 1, Weights::of(&[0, 500]));
+    assert_eq!(evicted.map(|e| e.len()), Some(2));
+    assert_eq!(lru.len(), 1);
+    assert_eq!(lru.metrics().get("weight_upload_used"), Some(&500.0));
+
+    lru.clear();
+    assert_eq!(lru.metrics().get("weight_upload_used"), Some(&0.0));
+    lru.set_weight_limits(None);
+    lru.put_weighted(7, 7, 1, Weights::of(&[9, 9]));
+    assert_eq!(lru.weights(&7), Weights::of(&[9, 9]));
+    assert_eq!(lru.metrics().get("weight_handles_used"), None);
+
+    // Weights cached without limits count against limits set later
+    lru.set_weight_limits(Some(limits));
+    assert_eq!(lru.metrics().get("weight_handles_used"), Some(&9.0));
+    let evicted = lru.put_weighted(8, 8, 1, Weights::of(&[1, 0]));
+    assert_eq!(evicted, Some(vec![(7, 7)]));
+}
+
+#[test]
+fn test_all_caches_keep_weights_when_put_replaces_value() {
+    let limits = WeightLimits::new().with_dimension("handles", 2);
+    let mut lru: LruCache<i32, i32> = make_lru(10);
+    let mut lfu: LfuCache<i32, i32> = make_lfu(10);
+    let mut lfuda: LfudaCache<i32, i32> = make_lfuda(10);
+    let mut slru: SlruCache<i32, i32> = make_slru(10, 5);
+    let mut gdsf: GdsfCache<i32, i32> = make_gdsf(10);
+    lru.set_weight_limits(Some(limits));
+    lfu.set_weight_limits(Some(limits));
+    lfuda.set_weight_limits(Some(limits));
+    slru.set_weight_limits(Some(limits));
+    gdsf.set_weight_limits(Some(limits));
+
+    let weights = Weights::of(&[2]);
+    lru.put_weighted(1, 1, 1, weights);
+    lfu.put_weighted(1, 1, 1, weights);
+    lfuda.put_weighted(1, 1, 1, weights);
+    slru.put_weighted(1, 1, 1, weights);
+    gdsf.put_weighted(1, 1, 1, weights);
+
+    // Neither put() nor put_tagged() resets the weights of the key
+    lru.put(1, 10, 1);
+    lfu.put(1, 10, 1);
+    lfuda.put(1, 10, 1);
+    slru.put(1, 10, 1);
+    gdsf.put(1, 10, 1);
+    lru.put_tagged(1, 11, 1, &[7]);
+    lfu.put_tagged(1, 11, 1, &[7]);
+    lfuda.put_tagged(1, 11, 1, &[7]);
+    slru.put_tagged(1, 11, 1, &[7]);
+    gdsf.put_tagged(1, 11, 1, &[7]);
+    assert_eq!(
+        [
+            lru.weights(&1),
+            lfu.weights(&1),
+            lfuda.weights(&1),
+            slru.weights(&1),
+            gdsf.weights(&1),
+        ],
+        [weights; 5]
+    );
+    for metrics in [
+        lru.metrics(),
+        lfu.metrics(),
+        lfuda.metrics(),
+        slru.metrics(),
+        gdsf.metrics(),
+    ] {
+        assert_eq!(metrics.get("weight_handles_used"), Some(&2.0));
+    }
+
+    // So the key still counts against the limits
+    let count = |evicted: Option<Vec<(i32, i32)>>| evicted.map_or(0, |e| e.len());
+    let weights = Weights::of(&[1]);
+    let evicted = [
+        count(lru.put_weighted(2, 2, 1, weights)),
+        count(lfu.put_weighted(2, 2, 1, weights)),
+        count(lfuda.put_weighted(2, 2, 1, weights)),
+        count(slru.put_weighted(2, 2, 1, weights)),
+        count(gdsf.put_weighted(2, 2, 1, weights)),
+    ];
+    assert_eq!(evicted, [1; 5]);
+    assert!(!lru.contains(&1) && !gdsf.contains(&1));
+}
+
+#[test]
+fn test_all_caches_take_weight_limits_from_config() {
+    let limits = Some(WeightLimits::new().with_dimension("handles", 2));
+    let capacity = NonZeroUsize::new(10).unwrap();
+    let mut lru: LruCache<i32, i32> = LruCache::init(
+        LruCacheConfig {
+            capacity,
+            max_size: u64::MAX,
+            weight_limits: limits,
+        },
+        None,
+    );
+    let mut lfu: LfuCache<i32, i32> = LfuCache::init(
+        LfuCacheConfig {
+            capacity,
+            max_size: u64::MAX,
+            weight_limits: limits,
+        },
+        None,
+    );
+    let mut lfuda: LfudaCache<i32, i32> = LfudaCache::init(
+        LfudaCacheConfig {
+            capacity,
+            initial_age: 0,
+            max_size: u64::MAX,
+            weight_limits: limits,
+        },
+        None,
+    );
+    let mut slru: SlruCache<i32, i32> = SlruCache::init(
+        SlruCacheConfig {
+            capacity,
+            protected_capacity: NonZeroUsize::new(5).unwrap(),
+            max_size: u64::MAX,
+            weight_limits: limits,
+        },
+        None,
+    );
+    let mut gdsf: GdsfCache<i32, i32> = GdsfCache::init(
+        GdsfCacheConfig {
+            capacity,
+            initial_age: 0.0,
+            max_size: u64::MAX,
+            weight_limits: limits,
+        },
+        None,
+    );
+    assert_eq!(lru.weight_limits(), limits.as_ref());
+    assert_eq!(gdsf.metrics().get("weight_handles_limit"), Some(&2.0));
+
+    let mut evicted = [0; 5];
+    for key in 0..3 {
+        let weights = Weights::of(&[1]);
+        let count = |evicted: Option<Vec<(i32, i32)>>| evicted.map_or(0, |e| e.len());
+        evicted[0] += count(lru.put_weighted(key, key, 1, weights));
+        evicted[1] += count(lfu.put_weighted(key, key, 1, weights));
+        evicted[2] += count(lfuda.put_weighted(key, key, 1, weights));
+        evicted[3] += count(slru.put_weighted(key, key, 1, weights));
+        evicted[4] += count(gdsf.put_weighted(key, key, 1, weights));
+    }
+    assert_eq!(evicted, [1; 5]);
+    for metrics in [
+        lru.metrics(),
+        lfu.metrics(),
+        lfuda.metrics(),
+        slru.metrics(),
+        gdsf.metrics(),
+    ] {
+        assert_eq!(metrics.get("weight_handles_used"), Some(&2.0));
+    }
+}
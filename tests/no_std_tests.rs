@@ -20,6 +20,7 @@ fn make_lru<K: core::hash::Hash + Eq + Clone, V: Clone>(cap: usize) -> LruCache<
     let config = LruCacheConfig {
         capacity: NonZeroUsize::new(cap).unwrap(),
         max_size: u64::MAX,
+        weight_limits: None,
     };
     LruCache::init(config, None)
 }
@@ -28,6 +29,7 @@ fn make_lfu<K: core::hash::Hash + Eq + Clone, V: Clone>(cap: usize) -> LfuCache<
     let config = LfuCacheConfig {
         capacity: NonZeroUsize::new(cap).unwrap(),
         max_size: u64::MAX,
+        weight_limits: None,
     };
     LfuCache::init(config, None)
 }
@@ -37,6 +39,7 @@ fn make_lfuda<K: core::hash::Hash + Eq + Clone, V: Clone>(cap: usize) -> LfudaCa
         capacity: NonZeroUsize::new(cap).unwrap(),
         initial_age: 0,
         max_size: u64::MAX,
+        weight_limits: None,
     };
     LfudaCache::init(config, None)
 }
@@ -49,6 +52,7 @@ fn make_slru<K: core::hash::Hash + Eq + Clone, V: Clone>(
         capacity: NonZeroUsize::new(cap).unwrap(),
         protected_capacity: NonZeroUsize::new(protected_cap).unwrap(),
         max_size: u64::MAX,
+        weight_limits: None,
     };
     SlruCache::init(config, None)
 }
@@ -58,6 +62,7 @@ fn make_gdsf<K: core::hash::Hash + Eq + Clone, V: Clone>(cap: usize) -> GdsfCach
         capacity: NonZeroUsize::new(cap).unwrap(),
         initial_age: 0.0,
         max_size: u64::MAX,
+        weight_limits: None,
     };
     GdsfCache::init(config, None)
 }
@@ -219,3 +224,69 @@ fn test_complex_types_in_no_std() {
     assert_eq!(*cache.get(&key1).unwrap(), value1);
     assert_eq!(*cache.get(&key2).unwrap(), value2);
 }
+
+#[cfg(feature = "concurrent-core")]
+mod concurrent {
+    use super::*;
+    use cache_rs::concurrent::{RawSpinRwLock, ShardedCache};
+    use cache_rs::config::{ConcurrentCacheConfig, ConcurrentLruCacheConfig};
+
+    type SpinLruCache<K, V> = ShardedCache<LruCache<K, V>, RawSpinRwLock>;
+
+    fn make_spin_lru<K: core::hash::Hash + Eq + Clone, V: Clone>(
+        cap: usize,
+        segments: usize,
+    ) -> SpinLruCache<K, V> {
+        let config: ConcurrentLruCacheConfig = ConcurrentCacheConfig {
+            base: LruCacheConfig {
+                capacity: NonZeroUsize::new(cap).unwrap(),
+                max_size: u64::MAX,
+                weight_limits: None,
+            },
+            segments,
+            shared_budget: false,
+        };
+        ShardedCache::with_hasher(config, Default::default())
+    }
+
+    #[test]
+    fn test_spin_locked_lru_in_no_std() {
+        // One segment, so LRU order is global
+        let cache = make_spin_lru(2, 1);
+
+        let key1 = String::from("key1");
+        let key2 = String::from("key2");
+        let key3 = String::from("key3");
+
+        cache.put(key1.clone(), 1, 1);
+        cache.put(key2.clone(), 2, 1);
+        assert_eq!(cache.get(&key1), Some(1));
+
+        // key2 is least recently used
+        cache.put(key3.clone(), 3, 1);
+        assert_eq!(cache.get(&key2), None);
+        assert_eq!(cache.get(&key1), Some(1));
+        assert_eq!(cache.get(&key3), Some(3));
+    }
+
+    #[test]
+    fn test_spin_locked_operations_in_no_std() {
+        let cache = make_spin_lru(64, 4);
+
+        for i in 0..32 {
+            cache.put(format!("key{i}"), i, 1);
+        }
+        assert_eq!(cache.len(), 32);
+        assert_eq!(cache.try_get("key7"), Ok(Some(7)));
+        assert!(cache.try_put(String::from("key32"), 32, 1).is_ok());
+        assert_eq!(
+            cache.compute(String::from("key0"), |v| v.map(|v| (v + 100, 1))),
+            (Some(100), None)
+        );
+        assert_eq!(cache.remove_if("key1", |&v| v == 1), Some(1));
+        assert_eq!(cache.try_remove("key2"), Ok(Some(2)));
+
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+}